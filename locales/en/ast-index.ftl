@@ -0,0 +1,90 @@
+# CLI messages (English). Every message here must also exist in the other locales.
+
+## Common
+
+index-not-found = Index not found. Run 'ast-index rebuild' first.
+time = Time: { $elapsed }
+total-time = Total time: { $elapsed }
+and-more = ... and { $count } more
+config-ignored = Using the default settings: { $error }
+
+## Search
+
+search-results = Search results for '{ $query }':
+search-files = Files (by path):
+search-symbols = Symbols:
+search-content = Content matches:
+search-ambiguous = '{ $query }' is declared in { $count } places; qualify it to pick one:
+search-time = Time: { $elapsed } (files: { $files }, symbols: { $symbols }, content: { $content })
+no-results = No results found.
+symbols-matching = Symbols matching '{ $name }'{ $kind }:
+no-symbols = No symbols found.
+classes-matching = Classes matching '{ $name }':
+no-classes = No classes found.
+
+## Rebuild / update
+
+including-ignored = Including gitignored files (build/, etc.)...
+rebuilding-full = Rebuilding full index...
+rebuilding-symbols = Rebuilding symbols index...
+rebuilding-modules = Rebuilding modules index...
+indexing-module-deps = Indexing module dependencies...
+indexing-xml = Indexing XML layouts...
+indexing-resources = Indexing resources...
+indexing-storyboards = Indexing storyboards/xibs...
+indexing-ios-assets = Indexing iOS assets...
+indexed-files = Indexed { $count ->
+    [one] { $count } file
+   *[other] { $count } files
+}
+indexed-modules = Indexed { $count ->
+    [one] { $count } module
+   *[other] { $count } modules
+}
+indexed-deps = Indexed { $count ->
+    [one] { $count } dependency
+   *[other] { $count } dependencies
+}
+indexed-modules-deps = Indexed { $modules } modules, { $deps } dependencies
+indexed-summary-mixed = Indexed { $files } files, { $modules } modules, { $deps } deps, { $xml } XML usages, { $resources } resources, { $storyboards } storyboard usages, { $assets } assets
+indexed-summary-ios = Indexed { $files } files, { $modules } modules, { $storyboards } storyboard usages, { $assets } assets ({ $asset_usages } usages)
+indexed-summary-android = Indexed { $files } files, { $modules } modules, { $deps } deps, { $transitive } transitive, { $xml } XML usages, { $resources } resources ({ $resource_usages } usages)
+unknown-index-type = Unknown index type: { $type }
+checking-changes = Checking for changes...
+index-up-to-date = Index is up to date.
+updated-files = Updated: { $total } files ({ $changed } changed, { $deleted } deleted)
+
+## Unused symbols
+
+unused-symbols = Potentially unused symbols in '{ $target }' ({ $total }/{ $checked } checked):
+unused-symbols-new = New potentially unused symbols in '{ $target }' ({ $total }/{ $checked } checked):
+unreachable-symbols = Unreachable symbols in '{ $target }' ({ $total }/{ $checked } checked):
+unreachable-symbols-new = New unreachable symbols in '{ $target }' ({ $total }/{ $checked } checked):
+no-unused-symbols = No unused symbols found.
+baseline-written = Wrote { $count ->
+    [one] { $count } unused symbol
+   *[other] { $count } unused symbols
+} to baseline { $path }
+baseline-fixed = { $count ->
+    [one] { $count } baseline entry is used or gone now
+   *[other] { $count } baseline entries are used or gone now
+}; --update-baseline drops them
+baseline-new-findings = { $count ->
+    [one] { $count } unused symbol
+   *[other] { $count } unused symbols
+} not in baseline { $path }
+baseline-invalid = { $path } is not an unused-symbols baseline
+baseline-version = Unsupported baseline version { $version } in { $path } (expected { $expected })
+
+## Optimize
+
+index-optimized = Index optimized:
+optimize-before = Before:     { $size } MB ({ $free }% free pages)
+optimize-after = After:      { $size } MB ({ $free }% free pages)
+optimize-reclaimed = Reclaimed:  { $size } MB
+optimize-db-path = DB path:    { $path }
+
+## Symbols-only indexes
+
+no-refs-index = '{ $command }' needs references, but the index was built with --no-refs; run 'ast-index rebuild' to index them
+no-refs-grep = The index has no references (built with --no-refs); searching with grep
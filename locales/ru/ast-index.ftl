@@ -0,0 +1,87 @@
+# Сообщения CLI (русский)
+
+## Общие
+
+index-not-found = Индекс не найден. Сначала выполните 'ast-index rebuild'.
+time = Время: { $elapsed }
+total-time = Общее время: { $elapsed }
+and-more = ... и ещё { $count }
+config-ignored = Используются настройки по умолчанию: { $error }
+
+## Поиск
+
+search-results = Результаты поиска '{ $query }':
+search-files = Файлы (по пути):
+search-symbols = Символы:
+search-content = Совпадения в содержимом:
+search-ambiguous = '{ $query }' объявлен в { $count ->
+    [one] { $count } месте
+   *[other] { $count } местах
+}; уточните имя, чтобы выбрать:
+search-time = Время: { $elapsed } (файлы: { $files }, символы: { $symbols }, содержимое: { $content })
+no-results = Ничего не найдено.
+symbols-matching = Символы по запросу '{ $name }'{ $kind }:
+no-symbols = Символы не найдены.
+classes-matching = Классы по запросу '{ $name }':
+no-classes = Классы не найдены.
+
+## Перестроение / обновление
+
+including-ignored = Включая файлы из .gitignore (build/ и т.п.)...
+rebuilding-full = Полное перестроение индекса...
+rebuilding-symbols = Перестроение индекса символов...
+rebuilding-modules = Перестроение индекса модулей...
+indexing-module-deps = Индексация зависимостей модулей...
+indexing-xml = Индексация XML-разметки...
+indexing-resources = Индексация ресурсов...
+indexing-storyboards = Индексация storyboard/xib...
+indexing-ios-assets = Индексация ассетов iOS...
+indexed-files = { $count ->
+    [one] Проиндексирован { $count } файл
+    [few] Проиндексировано { $count } файла
+   *[other] Проиндексировано { $count } файлов
+}
+indexed-modules = { $count ->
+    [one] Проиндексирован { $count } модуль
+    [few] Проиндексировано { $count } модуля
+   *[other] Проиндексировано { $count } модулей
+}
+indexed-deps = { $count ->
+    [one] Проиндексирована { $count } зависимость
+    [few] Проиндексировано { $count } зависимости
+   *[other] Проиндексировано { $count } зависимостей
+}
+indexed-modules-deps = Проиндексировано: модулей — { $modules }, зависимостей — { $deps }
+indexed-summary-mixed = Проиндексировано: файлов — { $files }, модулей — { $modules }, зависимостей — { $deps }, XML-использований — { $xml }, ресурсов — { $resources }, использований в storyboard — { $storyboards }, ассетов — { $assets }
+indexed-summary-ios = Проиндексировано: файлов — { $files }, модулей — { $modules }, использований в storyboard — { $storyboards }, ассетов — { $assets } (использований — { $asset_usages })
+indexed-summary-android = Проиндексировано: файлов — { $files }, модулей — { $modules }, зависимостей — { $deps }, транзитивных — { $transitive }, XML-использований — { $xml }, ресурсов — { $resources } (использований — { $resource_usages })
+unknown-index-type = Неизвестный тип индекса: { $type }
+checking-changes = Проверка изменений...
+index-up-to-date = Индекс актуален.
+updated-files = Обновлено файлов: { $total } (изменено — { $changed }, удалено — { $deleted })
+
+## Неиспользуемые символы
+
+unused-symbols = Возможно, неиспользуемые символы в '{ $target }' ({ $total } из { $checked } проверенных):
+unused-symbols-new = Новые возможно неиспользуемые символы в '{ $target }' ({ $total } из { $checked } проверенных):
+unreachable-symbols = Недостижимые символы в '{ $target }' ({ $total } из { $checked } проверенных):
+unreachable-symbols-new = Новые недостижимые символы в '{ $target }' ({ $total } из { $checked } проверенных):
+no-unused-symbols = Неиспользуемые символы не найдены.
+baseline-written = Записано неиспользуемых символов в базовый список { $path }: { $count }
+baseline-fixed = Записей базового списка, которые теперь используются или удалены: { $count }; --update-baseline уберёт их
+baseline-new-findings = Неиспользуемых символов не из базового списка { $path }: { $count }
+baseline-invalid = { $path } не является базовым списком unused-symbols
+baseline-version = Неподдерживаемая версия базового списка { $version } в { $path } (ожидается { $expected })
+
+## Оптимизация
+
+index-optimized = Индекс оптимизирован:
+optimize-before = До:            { $size } МБ (свободных страниц: { $free }%)
+optimize-after = После:         { $size } МБ (свободных страниц: { $free }%)
+optimize-reclaimed = Освобождено:   { $size } МБ
+optimize-db-path = Путь к БД:     { $path }
+
+## Индексы без ссылок
+
+no-refs-index = Для '{ $command }' нужны ссылки, но индекс построен с --no-refs; выполните 'ast-index rebuild', чтобы проиндексировать их
+no-refs-grep = В индексе нет ссылок (построен с --no-refs); поиск через grep
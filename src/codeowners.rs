@@ -0,0 +1,148 @@
+//! Code ownership from `CODEOWNERS`
+//!
+//! Looked up where GitHub and GitLab look: `.github/`, the project root, `docs/` and `.gitlab/`.
+//! Each line is a path pattern followed by owners; the last matching line wins, and a pattern
+//! with no owners leaves its files unowned. Patterns follow gitignore rules: a leading or inner
+//! `/` anchors the pattern to the root, `*` stays within a directory, `**` crosses directories
+//! and a directory pattern covers everything below it. GitLab `[Section]` headers are skipped.
+
+use std::path::Path;
+
+use anyhow::Result;
+use regex::Regex;
+
+/// Owner label of files no rule assigns to anyone
+pub const UNOWNED: &str = "(unowned)";
+
+/// Where `CODEOWNERS` is looked up, first found wins
+const LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS", ".gitlab/CODEOWNERS"];
+
+struct Rule {
+    pattern: Regex,
+    owners: Vec<String>,
+}
+
+/// Parsed `CODEOWNERS` rules
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+}
+
+impl CodeOwners {
+    /// Rules of the project's `CODEOWNERS`
+    pub fn load(root: &Path) -> Result<CodeOwners> {
+        for location in LOCATIONS {
+            let path = root.join(location);
+            if path.is_file() {
+                return Ok(CodeOwners::parse(&std::fs::read_to_string(path)?));
+            }
+        }
+        anyhow::bail!("No CODEOWNERS file in the project (looked in {})", LOCATIONS.join(", "))
+    }
+
+    pub fn parse(text: &str) -> CodeOwners {
+        let rules = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('[') && !line.starts_with("^["))
+            .filter_map(|line| {
+                let line = line.split(" #").next().unwrap_or(line);
+                let mut fields = line.split_whitespace();
+                let pattern = pattern_regex(fields.next()?)?;
+                Some(Rule { pattern, owners: fields.map(str::to_string).collect() })
+            })
+            .collect();
+        CodeOwners { rules }
+    }
+
+    /// Owners of a project-relative path; empty when no rule or an owner-less rule matches
+    pub fn owners_of(&self, path: &str) -> &[String] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.pattern.is_match(path))
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Owners of a path for grouping: its owners, or `UNOWNED`
+    pub fn labels_of(&self, path: &str) -> Vec<String> {
+        match self.owners_of(path) {
+            [] => vec![UNOWNED.to_string()],
+            owners => owners.to_vec(),
+        }
+    }
+}
+
+/// Regex for a gitignore-style pattern over project-relative paths
+fn pattern_regex(pattern: &str) -> Option<Regex> {
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.contains('/');
+    let body = trimmed.trim_start_matches('/');
+    if body.is_empty() {
+        // `/` alone: the whole project
+        return Regex::new("").ok();
+    }
+
+    let mut regex = String::from("^");
+    if !anchored {
+        regex.push_str("(?:.*/)?");
+    }
+    let mut rest = body;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**/") {
+            regex.push_str("(?:.*/)?");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("**") {
+            regex.push_str(".*");
+            rest = after;
+        } else {
+            match c {
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                _ => regex.push_str(&regex::escape(&c.to_string())),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    // A directory pattern covers its contents
+    regex.push_str("(?:/.*)?$");
+    Regex::new(&regex).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_matching_rule_wins() {
+        let owners = CodeOwners::parse(
+            "\
+# Default owners
+*                       @org/platform
+*.kt                    @org/android
+/app/payments/          @org/payments @alice
+docs/**/*.md            @org/docs
+[Generated]
+/app/payments/gen/
+",
+        );
+        assert_eq!(owners.owners_of("README.md"), ["@org/platform"]);
+        assert_eq!(owners.owners_of("core/src/Util.kt"), ["@org/android"]);
+        assert_eq!(owners.owners_of("app/payments/src/Pay.kt"), ["@org/payments", "@alice"]);
+        assert_eq!(owners.owners_of("docs/guide/intro.md"), ["@org/docs"]);
+        assert_eq!(owners.owners_of("docs/intro.md"), ["@org/docs"]);
+        assert!(owners.owners_of("app/payments/gen/Api.kt").is_empty());
+        // Anchored: only at the root
+        assert_eq!(owners.owners_of("lib/app/payments/Pay.java"), ["@org/platform"]);
+    }
+
+    #[test]
+    fn test_wildcards_stay_in_directory() {
+        let owners = CodeOwners::parse("src/*.rs @core\nbuild/ @ci\n");
+        assert_eq!(owners.owners_of("src/main.rs"), ["@core"]);
+        assert!(owners.owners_of("src/commands/mod.rs").is_empty());
+        // Unanchored directory pattern matches at any depth
+        assert_eq!(owners.owners_of("tools/build/run.sh"), ["@ci"]);
+        assert_eq!(owners.labels_of("README.md"), [UNOWNED]);
+    }
+}
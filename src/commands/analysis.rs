@@ -1,176 +1,875 @@
 //! Code analysis commands
 //!
-//! - unused-symbols: Find potentially unused public symbols
+//! - unused-symbols: Find potentially unused public symbols, by references or by reachability
+//!   from entry points; a baseline file of accepted findings leaves only new ones
+//! - similar: Find names confusingly close to a given one
+//! - duplicates: Find definitions repeated verbatim (name, kind, signature) across files
+//! - orphan-tests: Find test files whose subject or imported project symbols are gone
+//! - complexity: Functions with the highest cyclomatic complexity or length
+//! - god-classes: Types with the most members and incoming references
+//! - uncalled-overrides: Overridden methods that nothing calls, overrides included
+//! - untested: Public symbols no test file refers to, as a coverage proxy
+//! - lint-names: Declarations breaking the `[[naming]]` rules (or the built-in ones)
 
+use std::collections::BTreeSet;
+use std::io::IsTerminal;
 use std::path::Path;
 use std::time::Instant;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
-use rusqlite::params;
+use rusqlite::params_from_iter;
+use serde::{Deserialize, Serialize};
 
+use crate::config::{EntryPointsConfig, NamingRule};
 use crate::db;
+use super::Page;
+use crate::parsers::conditions;
+use crate::t;
+
+/// What counts as unused for `unused-symbols`, and the baseline findings are checked against
+pub struct UnusedOptions<'a> {
+    /// Only exported (capitalized) names
+    pub export_only: bool,
+    /// Active conditional-compilation names; declarations compiled out are skipped
+    pub active_cfg: &'a [String],
+    /// Unused means unreachable from these entry points rather than unreferenced
+    pub reachability: Option<&'a EntryPointsConfig>,
+    /// Accepted findings: written on the first run, then only findings outside it are reported
+    pub baseline: Option<&'a str>,
+    /// Rewrite the baseline with the current findings
+    pub update_baseline: bool,
+}
 
 /// Find potentially unused symbols in a module or project
+/// (`scope` narrows by module path, visibility and language). With `reachability`, symbols are
+/// unused when no entry point reaches them instead of when nothing refers to them. With a
+/// baseline, only findings missing from it are listed, and any such finding is an error.
 pub fn cmd_unused_symbols(
     root: &Path,
-    module: Option<&str>,
-    export_only: bool,
-    limit: usize,
+    scope: &db::SearchScope,
+    options: &UnusedOptions,
+    page: Page,
     format: &str,
 ) -> Result<()> {
     let start = Instant::now();
+    let UnusedOptions { export_only, active_cfg, reachability, .. } = *options;
 
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
 
     let conn = db::open_db(root)?;
+    if !super::require_refs(&conn, "unused-symbols") {
+        return Ok(());
+    }
 
-    // Build query based on filters
-    let (sql, filter_param) = if let Some(mod_path) = module {
-        (
-            r#"
-            SELECT s.name, s.kind, s.line, s.signature, f.path
-            FROM symbols s
-            JOIN files f ON s.file_id = f.id
-            WHERE f.path LIKE ?1
-              AND s.kind IN ('class', 'interface', 'function', 'object', 'enum', 'protocol', 'struct')
-            ORDER BY f.path, s.line
-            "#,
-            Some(format!("{}%", mod_path)),
-        )
-    } else if export_only {
-        (
-            r#"
-            SELECT s.name, s.kind, s.line, s.signature, f.path
-            FROM symbols s
-            JOIN files f ON s.file_id = f.id
-            WHERE s.kind IN ('class', 'interface', 'function', 'object', 'enum', 'protocol', 'struct')
-              AND s.name GLOB '[A-Z]*'
-            ORDER BY f.path, s.line
-            "#,
-            None,
-        )
+    let progress = Progress::new(format);
+
+    progress.stage("Checking symbols for usages...");
+    let mut rows = unused_candidates(&conn, scope, export_only, reachability.is_none())?;
+    // Declarations compiled out under the given configuration are not candidates
+    if !active_cfg.is_empty() {
+        rows.retain(|c| c.condition.as_deref().is_none_or(|c| conditions::is_active(c, active_cfg)));
+    }
+    let checked = rows.len();
+
+    let reachable = match reachability {
+        Some(config) => {
+            progress.stage(&format!("Walking references from entry points to {} symbols...", checked));
+            let classes = if config.android_manifest { super::android::manifest_classes(root) } else { Vec::new() };
+            let entry = db::EntryPoints {
+                annotations: &config.annotations,
+                functions: &config.functions,
+                classes: &classes,
+                // --no-tests leaves tests out as entry points too
+                tests: config.tests && scope.tests != Some(false),
+            };
+            Some(db::reachable_symbols(&conn, &entry)?)
+        }
+        None => None,
+    };
+    progress.finish();
+
+    let mut unused: Vec<db::SearchResult> = rows
+        .into_iter()
+        .filter(|c| match &reachable {
+            Some(reachable) => !reachable.contains(&c.id),
+            None => !c.used,
+        })
+        .map(|c| c.symbol)
+        .collect();
+
+    let fixed = match options.baseline {
+        Some(path) => match apply_baseline(path, options.update_baseline, &mut unused)? {
+            Some(fixed) => Some(fixed),
+            // The baseline was written: nothing to report
+            None => return Ok(()),
+        },
+        None => None,
+    };
+    let total = page.take(&mut unused);
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(serde_json::to_value(&unused)?, &[("symbols", total)]))?);
+        return finish_baseline(options.baseline, total);
+    }
+
+    let target = scope.module.unwrap_or("project");
+    let heading = match (reachable.is_some(), options.baseline.is_some()) {
+        (true, false) => t!("unreachable-symbols", target = target, total = total, checked = checked),
+        (true, true) => t!("unreachable-symbols-new", target = target, total = total, checked = checked),
+        (false, false) => t!("unused-symbols", target = target, total = total, checked = checked),
+        (false, true) => t!("unused-symbols-new", target = target, total = total, checked = checked),
+    };
+    println!("{}", heading.bold());
+
+    for s in &unused {
+        println!("  {} [{}]: {}:{}", s.name.yellow(), s.kind, s.path, s.line);
+    }
+
+    if unused.is_empty() {
+        println!("  {}", t!("no-unused-symbols"));
+    }
+    if let Some(fixed) = fixed.filter(|fixed| *fixed > 0) {
+        println!("  {}", t!("baseline-fixed", count = fixed).dimmed());
+    }
+
+    page.print_summary(total);
+    eprintln!(
+        "\n{}",
+        t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed()
+    );
+    finish_baseline(options.baseline, total)
+}
+
+/// Baseline format version, bumped when entries change shape
+const BASELINE_VERSION: u32 = 1;
+
+/// An accepted `unused-symbols` finding; the line is left out so edits elsewhere in the file
+/// keep it matched
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct BaselineEntry {
+    path: String,
+    kind: String,
+    name: String,
+    container: Option<String>,
+}
+
+impl BaselineEntry {
+    fn of(s: &db::SearchResult) -> BaselineEntry {
+        BaselineEntry { path: s.path.clone(), kind: s.kind.clone(), name: s.name.clone(), container: s.container.clone() }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Baseline {
+    version: u32,
+    symbols: BTreeSet<BaselineEntry>,
+}
+
+fn write_baseline(path: &str, symbols: BTreeSet<BaselineEntry>) -> Result<()> {
+    let baseline = Baseline { version: BASELINE_VERSION, symbols };
+    let text = serde_json::to_string_pretty(&baseline)? + "\n";
+    std::fs::write(path, text).with_context(|| format!("Failed to write {}", path))
+}
+
+fn read_baseline(path: &str) -> Result<BTreeSet<BaselineEntry>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+    let baseline: Baseline = serde_json::from_str(&text).with_context(|| t!("baseline-invalid", path = path))?;
+    if baseline.version != BASELINE_VERSION {
+        anyhow::bail!("{}", t!("baseline-version", version = baseline.version, path = path, expected = BASELINE_VERSION));
+    }
+    Ok(baseline.symbols)
+}
+
+/// Check `unused` against the baseline at `path`. On the first run, or with `update`, the
+/// findings are written as the baseline and None is returned; else only findings missing from
+/// it are kept, and the result is how many accepted findings are used or gone now
+fn apply_baseline(path: &str, update: bool, unused: &mut Vec<db::SearchResult>) -> Result<Option<usize>> {
+    let current: BTreeSet<BaselineEntry> = unused.iter().map(BaselineEntry::of).collect();
+    if update || !Path::new(path).exists() {
+        write_baseline(path, current)?;
+        eprintln!("{}", t!("baseline-written", count = unused.len(), path = path).dimmed());
+        return Ok(None);
+    }
+    let accepted = read_baseline(path)?;
+    unused.retain(|s| !accepted.contains(&BaselineEntry::of(s)));
+    Ok(Some(accepted.difference(&current).count()))
+}
+
+/// With a baseline, findings outside it fail the run (for CI)
+fn finish_baseline(baseline: Option<&str>, new: usize) -> Result<()> {
+    match baseline {
+        Some(path) if new > 0 => anyhow::bail!("{}", t!("baseline-new-findings", count = new, path = path)),
+        _ => Ok(()),
+    }
+}
+
+/// A declaration `unused-symbols` checks
+struct UnusedCandidate {
+    symbol: db::SearchResult,
+    /// Conditional-compilation condition the declaration is under
+    condition: Option<String>,
+    id: i64,
+    /// Something refers to the name; only filled in with `check_usages`
+    used: bool,
+}
+
+/// Declarations `unused-symbols` checks in `scope`, in path order. With `check_usages`, whether
+/// anything uses each name is decided by anti-joins in the same statement: refs (with --lang only
+/// usages from those languages, with --no-tests only usages outside tests and with --only-tests
+/// only those in tests), Android layouts and storyboards
+fn unused_candidates(
+    conn: &rusqlite::Connection,
+    scope: &db::SearchScope,
+    export_only: bool,
+    check_usages: bool,
+) -> Result<Vec<UnusedCandidate>> {
+    let (used_column, mut all_params) = if !check_usages {
+        ("0".to_string(), Vec::new())
     } else {
-        (
-            r#"
-            SELECT s.name, s.kind, s.line, s.signature, f.path
+        let mut ref_filters = String::new();
+        if !scope.languages.is_empty() {
+            ref_filters.push_str(&format!(" AND rf.language IN ({})", vec!["?"; scope.languages.len()].join(", ")));
+        }
+        if let Some(tests) = scope.tests {
+            ref_filters.push_str(&format!(" AND rf.is_test = {}", i32::from(tests)));
+        }
+        let refs = if ref_filters.is_empty() {
+            "SELECT 1 FROM refs r WHERE r.name = s.name".to_string()
+        } else {
+            format!("SELECT 1 FROM refs r JOIN files rf ON r.file_id = rf.id WHERE r.name = s.name{}", ref_filters)
+        };
+        let column = format!(
+            "(EXISTS ({}) OR EXISTS (SELECT 1 FROM xml_usages x WHERE x.class_name = s.name)
+              OR EXISTS (SELECT 1 FROM storyboard_usages sb WHERE sb.class_name = s.name))",
+            refs
+        );
+        (column, scope.languages.to_vec())
+    };
+
+    // Build query based on filters; a module filter takes precedence over --export-only
+    let export_filter = if export_only && scope.module.is_none() { " AND s.name GLOB '[A-Z]*'" } else { "" };
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    all_params.extend(scope_params);
+    let sql = format!(
+        r#"
+            SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility, s.condition, s.id, {}
             FROM symbols s
             JOIN files f ON s.file_id = f.id
             WHERE s.kind IN ('class', 'interface', 'function', 'object', 'enum', 'protocol', 'struct')
+              AND s.generated_by IS NULL{}{}
             ORDER BY f.path, s.line
             "#,
-            None,
-        )
-    };
+        used_column, export_filter, scope_clause
+    );
 
-    let mut stmt = conn.prepare(sql)?;
-    let symbols: Vec<db::SearchResult> = if let Some(ref pattern) = filter_param {
-        stmt.query_map(params![pattern], |row| {
-            Ok(db::SearchResult {
-                name: row.get(0)?,
-                kind: row.get(1)?,
-                line: row.get(2)?,
-                signature: row.get(3)?,
-                path: row.get(4)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?
-    } else {
-        stmt.query_map([], |row| {
-            Ok(db::SearchResult {
-                name: row.get(0)?,
-                kind: row.get(1)?,
-                line: row.get(2)?,
-                signature: row.get(3)?,
-                path: row.get(4)?,
+    let mut stmt = conn.prepare(&sql)?;
+    let candidates = stmt
+        .query_map(params_from_iter(all_params), |row| {
+            Ok(UnusedCandidate {
+                symbol: db::SearchResult::from_row(row)?,
+                condition: row.get(8)?,
+                id: row.get(9)?,
+                used: row.get(10)?,
             })
         })?
-        .collect::<Result<Vec<_>, _>>()?
-    };
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(candidates)
+}
+
+/// Stage messages for long analyses on an interactive stderr, erased before results print
+struct Progress {
+    enabled: bool,
+}
+
+impl Progress {
+    fn new(format: &str) -> Self {
+        Progress { enabled: format == "text" && std::io::stderr().is_terminal() }
+    }
+
+    fn stage(&self, message: &str) {
+        if self.enabled {
+            eprint!("\r\x1b[2K{}", message.dimmed());
+        }
+    }
+
+    fn finish(&self) {
+        if self.enabled {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
+/// Names within `threshold` normalized edit distance of `name`, closest first, each with its
+/// declarations (`scope` narrows by kind, path and language)
+pub fn cmd_similar(
+    root: &Path,
+    name: &str,
+    threshold: f64,
+    scope: &db::SearchScope,
+    limit: usize,
+    format: &str,
+) -> Result<()> {
+    let start = Instant::now();
 
-    // Check each symbol for references
-    let mut unused: Vec<&db::SearchResult> = Vec::new();
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    let similar = db::near_duplicate_names(&conn, name, threshold, limit, scope)?;
 
-    for sym in &symbols {
-        // Check refs table
-        let ref_count: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM refs WHERE name = ?1 LIMIT 1",
-                params![sym.name],
-                |row| row.get(0),
-            )
-            .unwrap_or(0);
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&similar)?);
+        return Ok(());
+    }
 
-        if ref_count > 0 {
-            continue;
+    println!("{}", format!("Names similar to '{}' ({}):", name, similar.len()).bold());
+    for duplicate in &similar {
+        println!("  {} {}", duplicate.name.yellow(), format!("(distance {:.2})", duplicate.distance).dimmed());
+        for s in &duplicate.declarations {
+            println!("    [{}] {}:{}", s.kind, s.path, s.line);
         }
+    }
+
+    if similar.is_empty() {
+        println!("  No similar names found.");
+    }
+
+    eprintln!(
+        "\n{}",
+        t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed()
+    );
+    Ok(())
+}
+
+/// Definitions with the same name, kind and signature in several files, the most repeated first
+pub fn cmd_duplicates(root: &Path, scope: &db::SearchScope, limit: usize, format: &str) -> Result<()> {
+    let start = Instant::now();
 
-        // Check xml_usages
-        let xml_count: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM xml_usages WHERE class_name = ?1 LIMIT 1",
-                params![sym.name],
-                |row| row.get(0),
-            )
-            .unwrap_or(0);
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    let duplicates = db::duplicate_definitions(&conn, limit, scope)?;
 
-        if xml_count > 0 {
-            continue;
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&duplicates)?);
+        return Ok(());
+    }
+
+    println!("{}", format!("Duplicate definitions ({}):", duplicates.len()).bold());
+    for duplicate in &duplicates {
+        println!(
+            "  {} [{}] {}",
+            duplicate.name.yellow(),
+            duplicate.kind,
+            format!("({} files)", duplicate.declarations.len()).dimmed()
+        );
+        println!("    {}", duplicate.signature.dimmed());
+        for s in &duplicate.declarations {
+            println!("    {}:{}", s.path, s.line);
         }
+    }
+
+    if duplicates.is_empty() {
+        println!("  No duplicate definitions found.");
+    }
+
+    eprintln!(
+        "\n{}",
+        t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed()
+    );
+    Ok(())
+}
+
+/// Test files in `scope` exercising code that no longer exists in the index
+pub fn cmd_orphan_tests(root: &Path, scope: &db::SearchScope, format: &str) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
 
-        // Check storyboard_usages
-        let sb_count: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM storyboard_usages WHERE class_name = ?1 LIMIT 1",
-                params![sym.name],
-                |row| row.get(0),
-            )
-            .unwrap_or(0);
+    let conn = db::open_db(root)?;
+    let orphans = db::orphan_tests(&conn, scope)?;
 
-        if sb_count > 0 {
-            continue;
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&orphans)?);
+        return Ok(());
+    }
+
+    println!("{}", format!("Orphan tests ({}):", orphans.len()).bold());
+    for orphan in &orphans {
+        println!("  {}", orphan.path.yellow());
+        if let Some(subject) = &orphan.missing_subject {
+            println!("    subject {} is not declared", subject.red());
+        }
+        for import in &orphan.missing_imports {
+            println!("    :{} imports {} (not declared)", import.line, import.path.red());
         }
+    }
+
+    if orphans.is_empty() {
+        println!("  No orphan tests found.");
+    }
+
+    eprintln!(
+        "\n{}",
+        t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed()
+    );
+    Ok(())
+}
+
+/// The `top` most complex functions (the longest when `sort` is `lines`) in `scope`
+pub fn cmd_complexity(root: &Path, top: usize, sort: &str, scope: &db::SearchScope, format: &str) -> Result<()> {
+    let start = Instant::now();
+    let by_lines = match sort {
+        "complexity" => false,
+        "lines" => true,
+        _ => anyhow::bail!("Unknown complexity sort '{}' (known: complexity, lines)", sort),
+    };
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
 
-        unused.push(sym);
-        if unused.len() >= limit {
-            break;
+    let conn = db::open_db(root)?;
+    if !db::has_complexity(&conn)? {
+        println!("{}", "The index has no complexity metrics yet: run `ast-index rebuild`".yellow());
+        return Ok(());
+    }
+    let functions = db::complex_functions(&conn, top, by_lines, scope)?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&functions)?);
+        return Ok(());
+    }
+
+    println!("{}", format!("Most {} functions ({}):", if by_lines { "long" } else { "complex" }, functions.len()).bold());
+    for f in &functions {
+        println!(
+            "  {:>4} {:>5}  {} {}:{}",
+            f.complexity.to_string().yellow(),
+            format!("{}L", f.lines).dimmed(),
+            f.function.display_name().cyan(),
+            f.function.path,
+            f.function.line
+        );
+    }
+
+    if functions.is_empty() {
+        println!("  No functions found.");
+    }
+
+    eprintln!(
+        "\n{}",
+        t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed()
+    );
+    Ok(())
+}
+
+/// Types in `scope` with at least `threshold` members, the largest first
+pub fn cmd_god_classes(root: &Path, threshold: usize, limit: usize, scope: &db::SearchScope, format: &str) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    if !super::require_refs(&conn, "god-classes") {
+        return Ok(());
+    }
+    let classes = db::large_classes(&conn, threshold, limit, scope)?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&classes)?);
+        return Ok(());
+    }
+
+    println!("{}", format!("Classes with {}+ members ({}):", threshold, classes.len()).bold());
+    for c in &classes {
+        println!(
+            "  {:>4} members {} [{}]: {}:{}",
+            c.members.to_string().yellow(),
+            c.class.display_name().cyan(),
+            c.class.kind,
+            c.class.path,
+            c.class.line
+        );
+        println!(
+            "       {}",
+            format!("{} functions, {} properties, {} refs from other files", c.functions, c.properties, c.refs).dimmed()
+        );
+    }
+
+    if classes.is_empty() {
+        println!("  No classes found.");
+    }
+
+    eprintln!(
+        "\n{}",
+        t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed()
+    );
+    Ok(())
+}
+
+/// Methods that are overridden but never called, base and overrides alike, in `scope`
+pub fn cmd_uncalled_overrides(root: &Path, limit: usize, scope: &db::SearchScope, format: &str) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    if !super::require_refs(&conn, "uncalled-overrides") {
+        return Ok(());
+    }
+    let uncalled = db::uncalled_overrides(&conn, limit, scope)?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&uncalled)?);
+        return Ok(());
+    }
+
+    println!("{}", format!("Overridden methods never called ({}):", uncalled.len()).bold());
+    for u in &uncalled {
+        println!("  {} [{}]: {}:{}", u.base.display_name().yellow(), u.base.kind, u.base.path, u.base.line);
+        for o in &u.overrides {
+            println!("    {} {}:{}", "overridden in".dimmed(), o.path, o.line);
         }
     }
 
+    if uncalled.is_empty() {
+        println!("  No uncalled overridden methods found.");
+    }
+
+    eprintln!(
+        "\n{}",
+        t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed()
+    );
+    Ok(())
+}
+
+/// Public symbols in `scope` that no test file references
+pub fn cmd_untested(root: &Path, scope: &db::SearchScope, page: Page, format: &str) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    if !super::require_refs(&conn, "untested") {
+        return Ok(());
+    }
+    let db::TestCoverage { checked, mut untested } = db::untested_symbols(&conn, scope)?;
+    let total = page.take(&mut untested);
+
     if format == "json" {
-        println!("{}", serde_json::to_string_pretty(&unused)?);
+        let result = serde_json::json!({ "checked": checked, "untested": untested });
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(result, &[("untested", total)]))?);
         return Ok(());
     }
 
-    let scope = module.unwrap_or("project");
+    let share = if checked > 0 { total as f64 * 100.0 / checked as f64 } else { 0.0 };
     println!(
         "{}",
-        format!(
-            "Potentially unused symbols in '{}' ({}/{} checked):",
-            scope,
-            unused.len(),
-            symbols.len()
-        )
-        .bold()
+        format!("Public symbols without references from tests: {} of {} ({:.0}%)", total, checked, share).bold()
     );
-
-    for s in &unused {
-        println!("  {} [{}]: {}:{}", s.name.yellow(), s.kind, s.path, s.line);
+    for s in &untested {
+        println!("  {} [{}]: {}:{}", s.display_name().yellow(), s.kind, s.path, s.line);
     }
 
-    if unused.is_empty() {
-        println!("  No unused symbols found.");
+    if untested.is_empty() {
+        println!("  Every public symbol is referenced from tests.");
     }
 
+    page.print_summary(total);
     eprintln!(
         "\n{}",
-        format!("Time: {:?}", start.elapsed()).dimmed()
+        t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed()
     );
     Ok(())
 }
+
+/// A declaration breaking a naming rule
+#[derive(Serialize)]
+struct NamingViolation<'a> {
+    rule: String,
+    #[serde(flatten)]
+    symbol: &'a db::SearchResult,
+}
+
+/// Check declarations in `scope` against naming `rules`, showing up to `limit` violations per
+/// rule; errors when any rule is broken, for CI
+pub fn cmd_lint_names(root: &Path, rules: &[NamingRule], scope: &db::SearchScope, limit: usize, format: &str) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    // Violations per rule, in rule order
+    let mut violations: Vec<(&NamingRule, Vec<db::SearchResult>)> = Vec::new();
+    for rule in rules {
+        if rule.style.is_none() && rule.pattern.is_none() {
+            anyhow::bail!("Naming rule '{}' needs a style or a pattern", rule.display_name());
+        }
+        let kinds = rule
+            .kinds
+            .iter()
+            .map(|kind| db::SymbolKind::from_name(kind).with_context(|| format!("Unknown symbol kind '{}' in naming rule '{}'", kind, rule.display_name())))
+            .collect::<Result<Vec<_>>>()?;
+        let languages = crate::parsers::resolve_languages(&rule.lang)?;
+        let pattern = rule.pattern.as_deref().map(regex::Regex::new).transpose()?;
+        let signature = rule.signature.as_deref().map(regex::Regex::new).transpose()?;
+        let rule_scope = db::SearchScope {
+            module: scope.module,
+            dir_prefix: scope.dir_prefix,
+            tests: scope.tests,
+            visibility: rule.visibility.as_deref(),
+            kinds: &kinds,
+            languages: &languages,
+            ..db::SearchScope::none()
+        };
+        let broken = db::declarations(&conn, &rule_scope)?
+            .into_iter()
+            .filter(|s| signature.as_ref().is_none_or(|re| s.signature.as_deref().is_some_and(|sig| re.is_match(sig))))
+            .filter(|s| {
+                !rule.style.is_none_or(|style| style.matches(&s.name)) || !pattern.as_ref().is_none_or(|re| re.is_match(&s.name))
+            })
+            .collect();
+        violations.push((rule, broken));
+    }
+    let count: usize = violations.iter().map(|(_, broken)| broken.len()).sum();
+
+    if format == "json" {
+        let list: Vec<NamingViolation> = violations
+            .iter()
+            .flat_map(|(rule, broken)| broken.iter().take(limit).map(|symbol| NamingViolation { rule: rule.display_name(), symbol }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "rules": rules.len(), "count": count, "violations": list }))?);
+    } else {
+        if count == 0 {
+            println!("{}", format!("No naming violations ({} rules)", rules.len()).green());
+        } else {
+            println!("{}", format!("Naming violations ({}):", count).bold());
+        }
+        for (rule, broken) in violations.iter().filter(|(_, broken)| !broken.is_empty()) {
+            println!("  {} {}", rule.display_name().yellow(), format!("({})", broken.len()).dimmed());
+            for s in broken.iter().take(limit) {
+                println!("    {}:{} [{}] {}", s.path, s.line, s.kind, s.name.red());
+            }
+            if broken.len() > limit {
+                println!("    {}", format!("... and {} more", broken.len() - limit).dimmed());
+            }
+        }
+        eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    }
+
+    if count > 0 {
+        anyhow::bail!("{} naming violations", count);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::{params, Connection};
+
+    /// Kotlin and Swift sources and tests: `Repo` is used only from a Kotlin test, `Cache` and the
+    /// Kotlin `Shared` only from Swift code, `Layout` only from an XML layout and `Dead` from nowhere
+    fn usage_fixture() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO files (id, path, mtime, size, language, is_test) VALUES
+                 (1, 'app/Repo.kt', 0, 0, 'kotlin', 0),
+                 (2, 'app/RepoTest.kt', 0, 0, 'kotlin', 1),
+                 (3, 'ios/Cache.swift', 0, 0, 'swift', 0);
+             INSERT INTO symbols (file_id, name, kind, line) VALUES
+                 (1, 'Repo', 'class', 1), (1, 'Layout', 'class', 5), (1, 'Dead', 'function', 9), (1, 'Shared', 'object', 12),
+                 (3, 'Cache', 'class', 1);
+             INSERT INTO refs (file_id, name, line) VALUES (2, 'Repo', 3), (3, 'Cache', 7), (3, 'Shared', 8);
+             INSERT INTO xml_usages (file_path, line, class_name) VALUES ('res/layout/main.xml', 2, 'Layout');",
+        )
+        .unwrap();
+        conn
+    }
+
+    /// The per-symbol COUNT queries the anti-joins replaced
+    fn used_by_count(conn: &Connection, name: &str, scope: &db::SearchScope) -> bool {
+        let mut ref_sql = "SELECT COUNT(*) FROM refs r JOIN files f ON r.file_id = f.id WHERE r.name = ?1".to_string();
+        if !scope.languages.is_empty() {
+            let placeholders: Vec<String> = (0..scope.languages.len()).map(|i| format!("?{}", i + 2)).collect();
+            ref_sql.push_str(&format!(" AND f.language IN ({})", placeholders.join(", ")));
+        }
+        if let Some(tests) = scope.tests {
+            ref_sql.push_str(&format!(" AND f.is_test = {}", i32::from(tests)));
+        }
+        let ref_params = std::iter::once(name).chain(scope.languages.iter().map(String::as_str));
+        let count = |sql: &str| conn.query_row(sql, params![name], |row| row.get::<_, i64>(0)).unwrap();
+        conn.query_row(&ref_sql, params_from_iter(ref_params), |row| row.get::<_, i64>(0)).unwrap() > 0
+            || count("SELECT COUNT(*) FROM xml_usages WHERE class_name = ?1") > 0
+            || count("SELECT COUNT(*) FROM storyboard_usages WHERE class_name = ?1") > 0
+    }
+
+    fn unused_names(conn: &Connection, scope: &db::SearchScope) -> Vec<String> {
+        unused_candidates(conn, scope, false, true)
+            .unwrap()
+            .into_iter()
+            .filter(|c| !c.used)
+            .map(|c| c.symbol.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_unused_anti_join_matches_per_symbol_counts() {
+        let conn = usage_fixture();
+        let kotlin = vec!["kotlin".to_string()];
+        let swift = vec!["swift".to_string()];
+        let scopes = [
+            db::SearchScope::none(),
+            db::SearchScope { languages: &kotlin, ..db::SearchScope::none() },
+            db::SearchScope { languages: &swift, ..db::SearchScope::none() },
+            db::SearchScope { tests: Some(false), ..db::SearchScope::none() },
+            db::SearchScope { tests: Some(true), ..db::SearchScope::none() },
+            db::SearchScope { languages: &kotlin, tests: Some(false), ..db::SearchScope::none() },
+        ];
+        for scope in &scopes {
+            for candidate in unused_candidates(&conn, scope, false, true).unwrap() {
+                let name = &candidate.symbol.name;
+                assert_eq!(candidate.used, used_by_count(&conn, name, scope), "{} with {:?}/{:?}", name, scope.languages, scope.tests);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unused_usage_filters() {
+        let conn = usage_fixture();
+        assert_eq!(unused_names(&conn, &db::SearchScope::none()), vec!["Dead"]);
+        // Usages from tests do not count with --no-tests
+        let no_tests = db::SearchScope { tests: Some(false), ..db::SearchScope::none() };
+        assert_eq!(unused_names(&conn, &no_tests), vec!["Repo", "Dead"]);
+        // With --lang kotlin only Kotlin definitions are candidates and only Kotlin usages count
+        let kotlin = vec!["kotlin".to_string()];
+        let only_kotlin = db::SearchScope { languages: &kotlin, ..db::SearchScope::none() };
+        assert_eq!(unused_names(&conn, &only_kotlin), vec!["Dead", "Shared"]);
+        let swift = vec!["swift".to_string()];
+        let only_swift = db::SearchScope { languages: &swift, ..db::SearchScope::none() };
+        assert!(unused_names(&conn, &only_swift).is_empty());
+        // Reachability mode leaves usages unchecked
+        assert!(unused_candidates(&conn, &db::SearchScope::none(), false, false).unwrap().iter().all(|c| !c.used));
+    }
+
+    fn finding(path: &str, name: &str) -> db::SearchResult {
+        db::SearchResult {
+            name: name.to_string(),
+            kind: "class".to_string(),
+            line: 1,
+            signature: None,
+            path: path.to_string(),
+            container: None,
+            qualified_name: None,
+            visibility: None,
+            partial_parts: vec![],
+            params: None,
+            arity: None,
+        }
+    }
+
+    fn names(findings: &[db::SearchResult]) -> Vec<&str> {
+        findings.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_baseline_first_run_writes_it_and_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("unused.json");
+        let path = path.to_str().unwrap();
+        let mut unused = vec![finding("a/Repo.kt", "Repo"), finding("a/Old.kt", "Old")];
+
+        assert_eq!(apply_baseline(path, false, &mut unused).unwrap(), None);
+        let accepted = read_baseline(path).unwrap();
+        assert_eq!(accepted.len(), 2);
+        assert!(accepted.contains(&BaselineEntry::of(&finding("a/Repo.kt", "Repo"))));
+        // The command returns before reporting, so the run succeeds
+        assert!(finish_baseline(Some(path), 0).is_ok());
+    }
+
+    #[test]
+    fn test_baseline_new_finding_fails_the_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("unused.json");
+        let path = path.to_str().unwrap();
+        apply_baseline(path, false, &mut vec![finding("a/Repo.kt", "Repo"), finding("a/Old.kt", "Old")]).unwrap();
+
+        // `Old` got used, `Cache` is new; a moved line does not matter
+        let mut repo = finding("a/Repo.kt", "Repo");
+        repo.line = 40;
+        let mut unused = vec![repo, finding("a/Cache.kt", "Cache")];
+        assert_eq!(apply_baseline(path, false, &mut unused).unwrap(), Some(1));
+        assert_eq!(names(&unused), vec!["Cache"]);
+        let err = finish_baseline(Some(path), unused.len()).unwrap_err();
+        assert!(err.to_string().contains(path), "{}", err);
+
+        // Without new findings the run passes
+        let mut unused = vec![finding("a/Repo.kt", "Repo")];
+        assert_eq!(apply_baseline(path, false, &mut unused).unwrap(), Some(1));
+        assert!(unused.is_empty());
+        assert!(finish_baseline(Some(path), unused.len()).is_ok());
+        assert!(finish_baseline(None, 3).is_ok());
+    }
+
+    #[test]
+    fn test_update_baseline_rewrites_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("unused.json");
+        let path = path.to_str().unwrap();
+        apply_baseline(path, false, &mut vec![finding("a/Repo.kt", "Repo"), finding("a/Old.kt", "Old")]).unwrap();
+
+        let mut unused = vec![finding("a/Repo.kt", "Repo"), finding("a/Cache.kt", "Cache")];
+        assert_eq!(apply_baseline(path, true, &mut unused).unwrap(), None);
+        let accepted = read_baseline(path).unwrap();
+        let mut accepted_names: Vec<&str> = accepted.iter().map(|e| e.name.as_str()).collect();
+        accepted_names.sort();
+        assert_eq!(accepted_names, vec!["Cache", "Repo"]);
+
+        let mut unused = vec![finding("a/Cache.kt", "Cache")];
+        assert_eq!(apply_baseline(path, false, &mut unused).unwrap(), Some(1));
+        assert!(unused.is_empty());
+    }
+
+    #[test]
+    fn test_baseline_version_mismatch_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("unused.json");
+        std::fs::write(&path, r#"{"version": 99, "symbols": []}"#).unwrap();
+        let path = path.to_str().unwrap();
+        let err = apply_baseline(path, false, &mut vec![finding("a/Repo.kt", "Repo")]).unwrap_err();
+        assert!(err.to_string().contains("99"), "{}", err);
+
+        std::fs::write(path, "[]").unwrap();
+        assert!(read_baseline(path).is_err());
+    }
+}
@@ -3,23 +3,59 @@
 //! Commands for working with Android codebases:
 //! - xml_usages: Find XML usages of a class (layouts, views)
 //! - resource_usages: Find Android resource usages (drawables, strings, etc.)
+//! - manifest_classes: Components declared in AndroidManifest.xml, for reachability
+//! - jni: Kotlin/Java native methods matched with their C/C++ JNI implementations
 
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::LazyLock;
 use std::time::Instant;
 
 use anyhow::Result;
 use colored::Colorize;
+use regex::Regex;
 use rusqlite::params;
 
 use crate::db;
+use crate::indexer;
+use crate::jni;
+use crate::t;
+
+static MANIFEST_COMPONENT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"<(?:application|activity|activity-alias|service|receiver|provider|instrumentation)\b[^>]*?\bandroid:name\s*=\s*"([^"]+)""#).unwrap()
+});
+
+/// Simple names of the application, activities, services, receivers and providers declared in
+/// the project's `AndroidManifest.xml` files; the system creates them, so nothing in the code
+/// refers to them
+pub fn manifest_classes(root: &Path) -> Vec<String> {
+    let walker = ignore::WalkBuilder::new(root)
+        .hidden(true)
+        .filter_entry(|entry| !indexer::is_excluded_dir(entry))
+        .build();
+    let mut classes = Vec::new();
+    for entry in walker.flatten() {
+        if entry.file_name() != "AndroidManifest.xml" {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(entry.path()) else { continue };
+        for caps in MANIFEST_COMPONENT_RE.captures_iter(&content) {
+            // `.MainActivity`, `com.example.MainActivity` and `MainActivity` all name MainActivity
+            let name = &caps[1];
+            classes.push(name.rsplit('.').next().unwrap_or(name).to_string());
+        }
+    }
+    classes.sort();
+    classes.dedup();
+    classes
+}
 
 /// Find XML usages of a class (layouts, views)
 pub fn cmd_xml_usages(root: &Path, class_name: &str, module_filter: Option<&str>) -> Result<()> {
     let start = Instant::now();
 
     if !db::db_exists(root) {
-        println!("{}", "Index not found. Run 'ast-index rebuild' first.".red());
+        println!("{}", t!("index-not-found").red());
         return Ok(());
     }
 
@@ -85,7 +121,7 @@ pub fn cmd_xml_usages(root: &Path, class_name: &str, module_filter: Option<&str>
         println!("  No XML usages found.");
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -100,7 +136,7 @@ pub fn cmd_resource_usages(
     let start = Instant::now();
 
     if !db::db_exists(root) {
-        println!("{}", "Index not found. Run 'ast-index rebuild' first.".red());
+        println!("{}", t!("index-not-found").red());
         return Ok(());
     }
 
@@ -236,7 +272,7 @@ pub fn cmd_resource_usages(
         }
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -270,3 +306,62 @@ fn parse_resource_reference(resource: &str) -> (String, String) {
     // Default: assume it's a string resource
     ("string".to_string(), resource.to_string())
 }
+
+/// Match Kotlin/Java native methods with `Java_` functions and `RegisterNatives` tables in the
+/// indexed C/C++ files; errors when any binding is unmatched, for CI
+pub fn cmd_jni(root: &Path, format: &str) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    let methods = jni::native_methods(&conn, root)?;
+    let mut functions = Vec::new();
+    let mut registered = Vec::new();
+    for file in db::file_stats(&conn)?.into_iter().filter(|f| f.language.as_deref() == Some("cpp")) {
+        let Ok(content) = std::fs::read_to_string(root.join(&file.path)) else { continue };
+        if content.contains("Java_") || content.contains("JNINativeMethod") {
+            let (file_functions, file_registered) = jni::scan_native_source(&file.path, &content);
+            functions.extend(file_functions);
+            registered.extend(file_registered);
+        }
+    }
+    let report = jni::check(methods, functions, registered);
+    let unmatched = report.unimplemented.len() + report.undeclared_functions.len() + report.undeclared_registrations.len();
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        let title = format!("JNI bindings: {} bound, {} unmatched", report.bound, unmatched);
+        println!("{}", if unmatched == 0 { title.green() } else { title.bold() });
+        if !report.unimplemented.is_empty() {
+            println!("\n{}", "Native methods without an implementation:".yellow());
+            for m in &report.unimplemented {
+                let expected = m.classes.first().map(|class| jni::mangle(class, &m.name)).unwrap_or_default();
+                println!("  {}:{} {} {}", m.path, m.line, m.name.red(), format!("(expected {})", expected).dimmed());
+            }
+        }
+        if !report.undeclared_functions.is_empty() {
+            println!("\n{}", "JNI functions without a native declaration:".yellow());
+            for f in &report.undeclared_functions {
+                println!("  {}:{} {} {}", f.path, f.line, f.symbol.red(), format!("({}.{})", f.class, f.method).dimmed());
+            }
+        }
+        if !report.undeclared_registrations.is_empty() {
+            println!("\n{}", "Registered natives without a native declaration:".yellow());
+            for r in &report.undeclared_registrations {
+                let classes = if r.classes.is_empty() { "any class".to_string() } else { r.classes.join(", ") };
+                println!("  {}:{} {}{} {}", r.path, r.line, r.method.red(), r.signature, format!("({})", classes).dimmed());
+            }
+        }
+        eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    }
+
+    if unmatched > 0 {
+        anyhow::bail!("{} unmatched JNI bindings", unmatched);
+    }
+    Ok(())
+}
@@ -0,0 +1,154 @@
+//! Public API snapshots
+//!
+//! - api-surface: Write the public declarations with their signatures as a JSON snapshot
+//! - api-diff: Compare a snapshot with the current index (or another snapshot) and fail on
+//!   removed or changed entries, as a semver gate in CI
+//!
+//! Entries are keyed by qualified name and kind; overloads share a key and are told apart by
+//! signature, so a key that loses one signature and gains another counts as changed.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::db::{self, ApiEntry, SearchScope};
+use crate::t;
+
+/// Snapshot format version, bumped when entries change shape
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct ApiSnapshot {
+    version: u32,
+    symbols: Vec<ApiEntry>,
+}
+
+/// An entry whose signature differs between the snapshots
+#[derive(Serialize)]
+struct ChangedEntry<'a> {
+    name: &'a str,
+    kind: &'a str,
+    old_signature: &'a str,
+    new_signature: &'a str,
+    path: &'a str,
+}
+
+/// Write the public API in `scope` as a snapshot to `out`, or to stdout
+pub fn cmd_api_surface(root: &Path, scope: &SearchScope, out: Option<&str>) -> Result<()> {
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+    let conn = db::open_db(root)?;
+    let snapshot = ApiSnapshot { version: SNAPSHOT_VERSION, symbols: db::public_api(&conn, scope)? };
+
+    let mut writer: Box<dyn Write> = match out {
+        Some(path) => Box::new(BufWriter::new(
+            std::fs::File::create(path).with_context(|| format!("Failed to create {}", path))?,
+        )),
+        None => Box::new(BufWriter::new(std::io::stdout().lock())),
+    };
+    serde_json::to_writer_pretty(&mut writer, &snapshot)?;
+    writeln!(writer)?;
+    writer.flush()?;
+
+    if let Some(path) = out {
+        eprintln!("{}", format!("Wrote {} public symbols to {}", snapshot.symbols.len(), path).dimmed());
+    }
+    Ok(())
+}
+
+/// Compare the snapshot at `old` with `new` (another snapshot) or the current index in `scope`;
+/// errors when entries were removed or changed
+pub fn cmd_api_diff(root: &Path, old: &str, new: Option<&str>, scope: &SearchScope, format: &str) -> Result<()> {
+    let old_entries = read_snapshot(old)?;
+    let new_entries = match new {
+        Some(path) => read_snapshot(path)?,
+        None => {
+            if !db::db_exists(root) {
+                println!("{}", t!("index-not-found").red());
+                return Ok(());
+            }
+            db::public_api(&db::open_db(root)?, scope)?
+        }
+    };
+
+    // Signatures per (name, kind), each with the first entry declaring it
+    let by_key = |entries: &[ApiEntry]| {
+        let mut keys: BTreeMap<(String, String), BTreeMap<String, usize>> = BTreeMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            keys.entry((entry.name.clone(), entry.kind.clone())).or_default().entry(entry.signature.clone()).or_insert(i);
+        }
+        keys
+    };
+    let (old_keys, new_keys) = (by_key(&old_entries), by_key(&new_entries));
+    let no_signatures = BTreeMap::new();
+
+    let mut added: Vec<&ApiEntry> = Vec::new();
+    let mut removed: Vec<&ApiEntry> = Vec::new();
+    let mut changed: Vec<ChangedEntry> = Vec::new();
+    let keys: BTreeSet<&(String, String)> = old_keys.keys().chain(new_keys.keys()).collect();
+    for key in keys {
+        let before = old_keys.get(key).unwrap_or(&no_signatures);
+        let after = new_keys.get(key).unwrap_or(&no_signatures);
+        let gone: Vec<&ApiEntry> =
+            before.iter().filter(|(sig, _)| !after.contains_key(*sig)).map(|(_, i)| &old_entries[*i]).collect();
+        let came: Vec<&ApiEntry> =
+            after.iter().filter(|(sig, _)| !before.contains_key(*sig)).map(|(_, i)| &new_entries[*i]).collect();
+        // Pair signatures that replaced each other; the rest were added or removed outright
+        let paired = gone.len().min(came.len());
+        for (old_entry, new_entry) in gone.iter().zip(&came) {
+            changed.push(ChangedEntry {
+                name: &new_entry.name,
+                kind: &new_entry.kind,
+                old_signature: &old_entry.signature,
+                new_signature: &new_entry.signature,
+                path: &new_entry.path,
+            });
+        }
+        removed.extend(&gone[paired..]);
+        added.extend(&came[paired..]);
+    }
+    let breaking = removed.len() + changed.len();
+
+    if format == "json" {
+        let result = serde_json::json!({ "added": added, "removed": removed, "changed": changed, "breaking": breaking > 0 });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        println!(
+            "{}",
+            format!("API changes: {} added, {} removed, {} changed", added.len(), removed.len(), changed.len()).bold()
+        );
+        for entry in &removed {
+            println!("  {} {} [{}] {}", "-".red(), entry.name.red(), entry.kind, entry.path.dimmed());
+            println!("      {}", entry.signature.dimmed());
+        }
+        for entry in &changed {
+            println!("  {} {} [{}] {}", "~".yellow(), entry.name.yellow(), entry.kind, entry.path.dimmed());
+            println!("      {} {}", "-".red(), entry.old_signature);
+            println!("      {} {}", "+".green(), entry.new_signature);
+        }
+        for entry in &added {
+            println!("  {} {} [{}] {}", "+".green(), entry.name.green(), entry.kind, entry.path.dimmed());
+            println!("      {}", entry.signature.dimmed());
+        }
+    }
+
+    if breaking > 0 {
+        anyhow::bail!("{} breaking API changes (removed or changed public symbols)", breaking);
+    }
+    Ok(())
+}
+
+fn read_snapshot(path: &str) -> Result<Vec<ApiEntry>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+    let snapshot: ApiSnapshot = serde_json::from_str(&text).with_context(|| format!("{} is not an API snapshot", path))?;
+    if snapshot.version != SNAPSHOT_VERSION {
+        anyhow::bail!("Unsupported API snapshot version {} in {} (expected {})", snapshot.version, path, SNAPSHOT_VERSION);
+    }
+    Ok(snapshot.symbols)
+}
@@ -0,0 +1,212 @@
+//! Call graph commands over the `calls` table
+//!
+//! - callers: Functions calling a function, up to `--depth` levels
+//! - callees: Functions a function calls, down to `--depth` levels
+//!
+//! Calls are resolved by name, so same-named methods share their edges. A function already on
+//! the path from the root is marked as a cycle instead of being expanded again, and one
+//! expanded elsewhere in the tree is listed without repeating its subtree.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::Result;
+use colored::Colorize;
+use rusqlite::Connection;
+
+use crate::db::{self, SearchScope};
+use crate::t;
+
+/// A `callers` or `callees` request
+pub struct CallGraphQuery<'a> {
+    pub name: &'a str,
+    /// Levels to follow; 1 lists direct callers or callees only
+    pub depth: usize,
+    /// Max callers or callees per function
+    pub limit: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Callers,
+    Callees,
+}
+
+/// One function in a call tree
+struct CallNode {
+    name: String,
+    /// Declaration of the function; None for callees the index does not declare
+    function: Option<db::SearchResult>,
+    /// Lines of the calls, in the caller's file
+    call_lines: Vec<i64>,
+    /// Already on the path from the root
+    cycle: bool,
+    /// Expanded earlier in the tree
+    seen: bool,
+    children: Vec<CallNode>,
+}
+
+/// Depth-first walk of the call graph from one function
+struct Walk<'a> {
+    conn: &'a Connection,
+    direction: Direction,
+    limit: usize,
+    scope: &'a SearchScope<'a>,
+    path: Vec<String>,
+    expanded: HashSet<String>,
+}
+
+impl Walk<'_> {
+    fn expand(&mut self, name: &str, depth: usize) -> Result<Vec<CallNode>> {
+        let edges: Vec<(String, Option<db::SearchResult>, Vec<i64>)> = match self.direction {
+            Direction::Callers => db::find_callers(self.conn, name, self.limit, self.scope)?
+                .into_iter()
+                .map(|c| (c.function.name.clone(), Some(c.function), c.call_lines))
+                .collect(),
+            Direction::Callees => db::find_callees(self.conn, name, self.limit, self.scope)?
+                .into_iter()
+                .map(|c| (c.name, c.definition, c.call_lines))
+                .collect(),
+        };
+
+        let mut nodes = Vec::with_capacity(edges.len());
+        for (name, function, call_lines) in edges {
+            let cycle = self.path.contains(&name);
+            // Library functions have no edges to follow
+            let expandable = depth > 1 && !cycle && function.is_some();
+            let seen = expandable && self.expanded.contains(&name);
+            let mut children = Vec::new();
+            if expandable && !seen {
+                self.expanded.insert(name.clone());
+                self.path.push(name.clone());
+                children = self.expand(&name, depth - 1)?;
+                self.path.pop();
+            }
+            nodes.push(CallNode { name, function, call_lines, cycle, seen, children });
+        }
+        Ok(nodes)
+    }
+}
+
+/// Functions calling `query.name`, as a tree up to `query.depth` levels; falls back to the
+/// grep-based search without an index or call graph
+pub fn cmd_callers(root: &Path, query: &CallGraphQuery, scope: &SearchScope, format: &str) -> Result<()> {
+    if !db::db_exists(root) {
+        return super::grep::cmd_callers(root, query.name, query.limit);
+    }
+    let conn = db::open_db(root)?;
+    if !db::refs_indexed(&conn) {
+        eprintln!("{}", t!("no-refs-grep").dimmed());
+        return super::grep::cmd_callers(root, query.name, query.limit);
+    }
+    if !db::has_call_graph(&conn)? {
+        eprintln!("{}", "The index has no call graph yet (run `ast-index rebuild`); searching with grep".dimmed());
+        return super::grep::cmd_callers(root, query.name, query.limit);
+    }
+    call_graph(&conn, query, scope, format, Direction::Callers)
+}
+
+/// Functions `query.name` calls, as a tree down to `query.depth` levels
+pub fn cmd_callees(root: &Path, query: &CallGraphQuery, scope: &SearchScope, format: &str) -> Result<()> {
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+    let conn = db::open_db(root)?;
+    if !super::require_refs(&conn, "callees") {
+        return Ok(());
+    }
+    if !db::has_call_graph(&conn)? {
+        println!("{}", "The index has no call graph yet: run `ast-index rebuild`".yellow());
+        return Ok(());
+    }
+    call_graph(&conn, query, scope, format, Direction::Callees)
+}
+
+fn call_graph(conn: &Connection, query: &CallGraphQuery, scope: &SearchScope, format: &str, direction: Direction) -> Result<()> {
+    let start = Instant::now();
+    let mut walk = Walk {
+        conn,
+        direction,
+        limit: query.limit,
+        scope,
+        path: vec![query.name.to_string()],
+        expanded: HashSet::from([query.name.to_string()]),
+    };
+    let tree = walk.expand(query.name, query.depth.max(1))?;
+    let key = match direction {
+        Direction::Callers => "callers",
+        Direction::Callees => "callees",
+    };
+
+    if format == "json" {
+        let result = serde_json::json!({ "function": query.name, key: to_json(&tree, key) });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    let title = match direction {
+        Direction::Callers => format!("Callers of '{}':", query.name),
+        Direction::Callees => format!("Callees of '{}':", query.name),
+    };
+    println!("{}", title.bold());
+    if tree.is_empty() {
+        println!("  No calls found.");
+    }
+    print_tree(&tree, direction, 1);
+
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+fn to_json(nodes: &[CallNode], key: &str) -> serde_json::Value {
+    nodes
+        .iter()
+        .map(|node| {
+            let mut value = match &node.function {
+                Some(function) => serde_json::to_value(function).unwrap_or_default(),
+                None => serde_json::json!({ "name": node.name }),
+            };
+            value["call_lines"] = serde_json::json!(node.call_lines);
+            if node.cycle {
+                value["cycle"] = serde_json::json!(true);
+            }
+            if node.seen {
+                value["seen"] = serde_json::json!(true);
+            }
+            if !node.children.is_empty() {
+                value[key] = to_json(&node.children, key);
+            }
+            value
+        })
+        .collect()
+}
+
+fn print_tree(nodes: &[CallNode], direction: Direction, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let arrow = match direction {
+        Direction::Callers => "←",
+        Direction::Callees => "→",
+    };
+    for node in nodes {
+        let lines: Vec<String> = node.call_lines.iter().map(|l| l.to_string()).collect();
+        let calls_at = format!("(calls at {})", lines.join(", ")).dimmed();
+        match &node.function {
+            Some(f) if node.cycle => println!("{}{} {} {}", indent, arrow, f.display_name().yellow(), "(cycle)".red()),
+            Some(f) => println!(
+                "{}{} {} [{}]: {}:{} {}{}",
+                indent,
+                arrow,
+                f.display_name_with_params().cyan(),
+                f.kind,
+                f.path,
+                f.line,
+                calls_at,
+                if node.seen { " (expanded above)".dimmed() } else { "".normal() }
+            ),
+            None => println!("{}{} {} {} {}", indent, arrow, node.name, "(not in index)".dimmed(), calls_at),
+        }
+        print_tree(&node.children, direction, depth + 1);
+    }
+}
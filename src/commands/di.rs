@@ -0,0 +1,57 @@
+//! Dependency injection
+//!
+//! - di-graph: What provides a type to the Dagger/Hilt/Koin/Spring container, what it needs
+//!   and where it is injected
+
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::db;
+use crate::t;
+
+pub fn cmd_di_graph(root: &Path, type_name: &str, format: &str) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    let graph = db::di_graph(&conn, type_name)?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&graph)?);
+        return Ok(());
+    }
+
+    if graph.providers.is_empty() && graph.injections.is_empty() && graph.dependencies.is_empty() {
+        println!("{}", format!("No DI bindings for '{}' in the index", type_name).yellow());
+    } else {
+        println!("{}", format!("DI graph for {}:", type_name).bold());
+        println!("\n  {} ({}):", "Provided by".cyan(), graph.providers.len());
+        for b in &graph.providers {
+            println!("    {} {} {}", b.mechanism, b.symbol.as_deref().unwrap_or("-").yellow(), format!("{}:{}", b.path, b.line).dimmed());
+        }
+        if graph.providers.is_empty() {
+            println!(
+                "    {}",
+                format!("nothing in the index provides {}; it may come from a library or generated code", type_name).yellow()
+            );
+        }
+        println!("\n  {} ({}):", "Depends on".cyan(), graph.dependencies.len());
+        for b in &graph.dependencies {
+            println!("    {} via {} {}", b.type_name.yellow(), b.mechanism, format!("{}:{}", b.path, b.line).dimmed());
+        }
+        println!("\n  {} ({}):", "Injected into".cyan(), graph.injections.len());
+        for b in &graph.injections {
+            println!("    {} via {} {}", b.symbol.as_deref().unwrap_or("-").yellow(), b.mechanism, format!("{}:{}", b.path, b.line).dimmed());
+        }
+    }
+
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
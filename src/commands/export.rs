@@ -0,0 +1,445 @@
+//! Dataset export commands
+//!
+//! - export-dataset: Dump symbols with signature, doc, body span and references as JSONL (or one JSON
+//!   array with `--format json`)
+
+use std::collections::HashMap;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use regex::Regex;
+use rusqlite::params;
+use serde::Serialize;
+
+use crate::db;
+use crate::parsers;
+use crate::t;
+
+/// One exported record (one line of JSONL)
+#[derive(Serialize)]
+struct DatasetRecord<'a> {
+    symbol: &'a str,
+    container: Option<&'a str>,
+    kind: &'a str,
+    path: &'a str,
+    signature: Option<&'a str>,
+    doc: Option<&'a str>,
+    body: BodySpan,
+    license: Option<&'a str>,
+    references: Vec<DatasetRef>,
+}
+
+#[derive(Serialize)]
+struct BodySpan {
+    start_line: usize,
+    end_line: usize,
+}
+
+#[derive(Serialize)]
+struct DatasetRef {
+    path: String,
+    line: i64,
+}
+
+/// License filters for export-dataset
+pub struct LicenseFilter<'a> {
+    /// Only export files with one of these SPDX ids (empty = any)
+    pub allow: &'a [String],
+    /// Never export files with one of these SPDX ids
+    pub deny: &'a [String],
+    /// Export files whose license could not be detected
+    pub include_unknown: bool,
+}
+
+impl LicenseFilter<'_> {
+    fn accepts(&self, license: Option<&str>) -> bool {
+        match license {
+            None => self.include_unknown,
+            Some(id) => {
+                if self.deny.iter().any(|d| d.eq_ignore_ascii_case(id)) {
+                    return false;
+                }
+                self.allow.is_empty() || self.allow.iter().any(|a| a.eq_ignore_ascii_case(id))
+            }
+        }
+    }
+}
+
+/// How export-dataset writes its records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DatasetFormat {
+    /// One record per line (the default)
+    Jsonl,
+    /// One JSON array of records
+    Json,
+}
+
+impl DatasetFormat {
+    fn parse(format: Option<&str>) -> Result<Self> {
+        match format {
+            None | Some("jsonl") => Ok(DatasetFormat::Jsonl),
+            Some("json") => Ok(DatasetFormat::Json),
+            Some(other) => anyhow::bail!("export-dataset writes --format jsonl or json, not '{}'", other),
+        }
+    }
+}
+
+/// Export symbols as a JSONL dataset (one record per symbol)
+pub fn cmd_export_dataset(
+    root: &Path,
+    format: Option<&str>,
+    output: Option<&str>,
+    filter: &LicenseFilter,
+    max_refs: usize,
+) -> Result<()> {
+    let start = Instant::now();
+    let format = DatasetFormat::parse(format)?;
+
+    if !db::db_exists(root) {
+        println!(
+            "{}",
+            t!("index-not-found").red()
+        );
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(BufWriter::new(
+            std::fs::File::create(path).with_context(|| format!("Failed to create {}", path))?,
+        )),
+        None => Box::new(BufWriter::new(std::io::stdout().lock())),
+    };
+    let (exported, skipped_files) = write_dataset(&conn, root, &mut out, filter, max_refs, format)?;
+    out.flush()?;
+
+    eprintln!(
+        "{}",
+        format!(
+            "Exported {} records ({} files skipped by license filter), time: {:?}",
+            exported,
+            skipped_files,
+            start.elapsed()
+        )
+        .dimmed()
+    );
+    Ok(())
+}
+
+/// Write every symbol the license filter accepts; returns (records written, files skipped)
+fn write_dataset(
+    conn: &rusqlite::Connection,
+    root: &Path,
+    out: &mut dyn Write,
+    filter: &LicenseFilter,
+    max_refs: usize,
+    format: DatasetFormat,
+) -> Result<(usize, usize)> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.end_line, s.doc
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.kind NOT IN ('import', 'package')
+        ORDER BY f.path, s.line
+        "#,
+    )?;
+    let mut refs_stmt = conn.prepare(
+        r#"
+        SELECT f.path, r.line
+        FROM refs r
+        JOIN files f ON r.file_id = f.id
+        WHERE r.name = ?1
+        ORDER BY f.path, r.line
+        LIMIT ?2
+        "#,
+    )?;
+
+    let mut rows = stmt.query([])?;
+    let mut license_cache: HashMap<std::path::PathBuf, Option<String>> = HashMap::new();
+    let mut current_path = String::new();
+    let mut current_lines: Vec<String> = Vec::new();
+    let mut current_license: Option<String> = None;
+    let mut skipped_files = 0usize;
+    let mut exported = 0usize;
+
+    if format == DatasetFormat::Json {
+        out.write_all(b"[")?;
+    }
+    while let Some(row) = rows.next()? {
+        let name: String = row.get(0)?;
+        let kind: String = row.get(1)?;
+        let line: i64 = row.get(2)?;
+        let signature: Option<String> = row.get(3)?;
+        let path: String = row.get(4)?;
+        let container: Option<String> = row.get(5)?;
+        let end_line: Option<i64> = row.get(6)?;
+        let doc: Option<String> = row.get(7)?;
+
+        if path != current_path {
+            current_path = path.clone();
+            let content = crate::encoding::read_file(&root.join(&path)).map(|(text, _)| text).unwrap_or_default();
+            current_license = detect_license(root, &path, &content, &mut license_cache);
+            if filter.accepts(current_license.as_deref()) {
+                current_lines = content.lines().map(|l| l.to_string()).collect();
+            } else {
+                current_lines.clear();
+                skipped_files += 1;
+            }
+        }
+        if !filter.accepts(current_license.as_deref()) {
+            continue;
+        }
+
+        let line = line.max(1) as usize;
+        let references = refs_stmt
+            .query_map(params![name, max_refs as i64], |r| {
+                Ok(DatasetRef { path: r.get(0)?, line: r.get(1)? })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let record = DatasetRecord {
+            symbol: &name,
+            container: container.as_deref(),
+            kind: &kind,
+            path: &path,
+            signature: signature.as_deref(),
+            doc: doc.as_deref(),
+            body: BodySpan {
+                start_line: line,
+                end_line: end_line.map_or_else(|| body_end_line(&current_lines, line, &path), |end| end as usize),
+            },
+            license: current_license.as_deref(),
+            references,
+        };
+        match format {
+            DatasetFormat::Jsonl => {
+                serde_json::to_writer(&mut *out, &record)?;
+                out.write_all(b"\n")?;
+            }
+            DatasetFormat::Json => {
+                out.write_all(if exported == 0 { b"\n" } else { b",\n" })?;
+                serde_json::to_writer(&mut *out, &record)?;
+            }
+        }
+        exported += 1;
+    }
+    if format == DatasetFormat::Json {
+        out.write_all(b"\n]\n")?;
+    }
+    Ok((exported, skipped_files))
+}
+
+/// End of the declaration for indexes built before `symbols.end_line` was stored
+fn body_end_line(lines: &[String], line: usize, path: &str) -> usize {
+    let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let ext = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("");
+    parsers::span::end_line(&lines, line, parsers::FileType::from_extension(ext))
+}
+
+static SPDX_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"SPDX-License-Identifier:\s*([A-Za-z0-9.+-]+)").unwrap());
+
+const LICENSE_FILES: &[&str] = &["LICENSE", "LICENSE.md", "LICENSE.txt", "LICENCE", "COPYING"];
+
+/// Detect the license of a file: SPDX header first, then the nearest LICENSE file up to root
+fn detect_license(
+    root: &Path,
+    rel_path: &str,
+    content: &str,
+    cache: &mut HashMap<std::path::PathBuf, Option<String>>,
+) -> Option<String> {
+    let header: String = content.lines().take(30).collect::<Vec<_>>().join("\n");
+    if let Some(caps) = SPDX_RE.captures(&header) {
+        return Some(caps[1].to_string());
+    }
+
+    let mut dir = root.join(rel_path);
+    while dir.pop() {
+        if !dir.starts_with(root) {
+            break;
+        }
+        if let Some(cached) = cache.get(&dir) {
+            if cached.is_some() {
+                return cached.clone();
+            }
+        } else {
+            let found = LICENSE_FILES
+                .iter()
+                .find_map(|name| std::fs::read_to_string(dir.join(name)).ok())
+                .map(|text| classify_license(&text));
+            cache.insert(dir.clone(), found.clone());
+            if found.is_some() {
+                return found;
+            }
+        }
+        if dir == root {
+            break;
+        }
+    }
+    None
+}
+
+/// Map license text to an SPDX identifier
+fn classify_license(text: &str) -> String {
+    let lower = text.to_lowercase();
+    let id = if lower.contains("apache license") && lower.contains("version 2.0") {
+        "Apache-2.0"
+    } else if lower.contains("gnu lesser general public license") {
+        "LGPL-3.0"
+    } else if lower.contains("gnu affero general public license") {
+        "AGPL-3.0"
+    } else if lower.contains("gnu general public license") {
+        if lower.contains("version 2") { "GPL-2.0" } else { "GPL-3.0" }
+    } else if lower.contains("mozilla public license") {
+        "MPL-2.0"
+    } else if lower.contains("permission is hereby granted, free of charge") {
+        "MIT"
+    } else if lower.contains("redistribution and use in source and binary forms") {
+        if lower.contains("neither the name") { "BSD-3-Clause" } else { "BSD-2-Clause" }
+    } else if lower.contains("this is free and unencumbered software") {
+        "Unlicense"
+    } else {
+        "LicenseRef-Unknown"
+    };
+    id.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+    use std::fs;
+
+    const MIT_TEXT: &str = "MIT License\n\nPermission is hereby granted, free of charge, to any person obtaining a copy\n";
+
+    /// Index with `src/Repo.kt` (MIT through the root LICENSE) and `vendor/Lib.kt` (GPL-3.0 SPDX header)
+    fn dataset_fixture() -> (tempfile::TempDir, Connection) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("LICENSE"), MIT_TEXT).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("vendor")).unwrap();
+        fs::write(root.join("src/Repo.kt"), "class Repo {\n    fun load() {}\n}\n").unwrap();
+        fs::write(root.join("vendor/Lib.kt"), "// SPDX-License-Identifier: GPL-3.0\nclass Lib\n").unwrap();
+
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO files (id, path, mtime, size) VALUES (1, 'src/Repo.kt', 0, 0), (2, 'vendor/Lib.kt', 0, 0);
+             INSERT INTO symbols (file_id, name, kind, line, signature, container, end_line, doc)
+                 VALUES (1, 'Repo', 'class', 1, 'class Repo', NULL, 3, 'Loads users.'),
+                        (1, 'load', 'function', 2, 'fun load()', 'Repo', 2, NULL),
+                        (2, 'Lib', 'class', 2, 'class Lib', NULL, 2, NULL);
+             INSERT INTO refs (file_id, name, line) VALUES (2, 'Repo', 2), (1, 'Repo', 3), (1, 'load', 3);",
+        )
+        .unwrap();
+        (dir, conn)
+    }
+
+    fn export(conn: &Connection, root: &Path, filter: &LicenseFilter, format: DatasetFormat) -> (String, usize, usize) {
+        let mut out = Vec::new();
+        let (exported, skipped) = write_dataset(conn, root, &mut out, filter, 1, format).unwrap();
+        (String::from_utf8(out).unwrap(), exported, skipped)
+    }
+
+    #[test]
+    fn test_export_dataset_jsonl_records() {
+        let (dir, conn) = dataset_fixture();
+        let any = LicenseFilter { allow: &[], deny: &[], include_unknown: true };
+        let (text, exported, skipped) = export(&conn, dir.path(), &any, DatasetFormat::Jsonl);
+        assert_eq!((exported, skipped), (3, 0));
+
+        let records: Vec<serde_json::Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(records.len(), 3);
+        let repo = &records[0];
+        assert_eq!(repo["symbol"], "Repo");
+        assert_eq!(repo["kind"], "class");
+        assert_eq!(repo["license"], "MIT");
+        // The doc is the one the parser stored, not a guess from the lines above
+        assert_eq!(repo["doc"], "Loads users.");
+        assert_eq!(records[2]["doc"], serde_json::Value::Null);
+        assert_eq!(repo["body"], serde_json::json!({"start_line": 1, "end_line": 3}));
+        // --max-refs 1 keeps the first reference by path
+        assert_eq!(repo["references"], serde_json::json!([{"path": "src/Repo.kt", "line": 3}]));
+        assert_eq!(records[1]["container"], "Repo");
+        assert_eq!(records[2]["license"], "GPL-3.0");
+    }
+
+    #[test]
+    fn test_export_dataset_license_filters() {
+        let (dir, conn) = dataset_fixture();
+        let gpl = ["GPL-3.0".to_string()];
+        let deny_gpl = LicenseFilter { allow: &[], deny: &gpl, include_unknown: true };
+        let (text, exported, skipped) = export(&conn, dir.path(), &deny_gpl, DatasetFormat::Jsonl);
+        assert_eq!((exported, skipped), (2, 1));
+        assert!(!text.contains("\"Lib\""));
+
+        let only_gpl = LicenseFilter { allow: &gpl, deny: &[], include_unknown: false };
+        let (_, exported, skipped) = export(&conn, dir.path(), &only_gpl, DatasetFormat::Jsonl);
+        assert_eq!((exported, skipped), (1, 1));
+
+        // Without a LICENSE file the MIT file becomes unlicensed
+        fs::remove_file(dir.path().join("LICENSE")).unwrap();
+        let known = LicenseFilter { allow: &[], deny: &[], include_unknown: false };
+        assert_eq!(export(&conn, dir.path(), &known, DatasetFormat::Jsonl).1, 1);
+    }
+
+    #[test]
+    fn test_export_dataset_json_array_and_format_check() {
+        let (dir, conn) = dataset_fixture();
+        let any = LicenseFilter { allow: &[], deny: &[], include_unknown: true };
+        let (text, _, _) = export(&conn, dir.path(), &any, DatasetFormat::Json);
+        let records: Vec<serde_json::Value> = serde_json::from_str(&text).unwrap();
+        assert_eq!(records.len(), 3);
+
+        let none = LicenseFilter { allow: &["MIT".to_string()], deny: &["MIT".to_string()], include_unknown: false };
+        let (text, _, _) = export(&conn, dir.path(), &none, DatasetFormat::Json);
+        assert_eq!(serde_json::from_str::<Vec<serde_json::Value>>(&text).unwrap().len(), 0);
+
+        assert_eq!(DatasetFormat::parse(None).unwrap(), DatasetFormat::Jsonl);
+        assert_eq!(DatasetFormat::parse(Some("json")).unwrap(), DatasetFormat::Json);
+        assert!(DatasetFormat::parse(Some("text")).is_err());
+    }
+
+    #[test]
+    fn test_detect_license() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("LICENSE"), MIT_TEXT).unwrap();
+        fs::create_dir_all(root.join("third_party/lib")).unwrap();
+        fs::write(
+            root.join("third_party/LICENSE.txt"),
+            "Apache License\nVersion 2.0, January 2004\n",
+        )
+        .unwrap();
+        let mut cache = HashMap::new();
+
+        assert_eq!(detect_license(root, "src/A.kt", "class A", &mut cache).as_deref(), Some("MIT"));
+        // The nearest LICENSE file wins
+        assert_eq!(detect_license(root, "third_party/lib/B.kt", "", &mut cache).as_deref(), Some("Apache-2.0"));
+        // An SPDX header beats any LICENSE file
+        let header = "/*\n * SPDX-License-Identifier: BSD-3-Clause\n */\nclass C";
+        assert_eq!(detect_license(root, "third_party/lib/C.kt", header, &mut cache).as_deref(), Some("BSD-3-Clause"));
+
+        let bare = tempfile::tempdir().unwrap();
+        assert_eq!(detect_license(bare.path(), "src/A.kt", "class A", &mut HashMap::new()), None);
+    }
+
+    #[test]
+    fn test_classify_license() {
+        assert_eq!(classify_license("GNU GENERAL PUBLIC LICENSE\nVersion 2, June 1991"), "GPL-2.0");
+        assert_eq!(classify_license("GNU GENERAL PUBLIC LICENSE\nVersion 3"), "GPL-3.0");
+        assert_eq!(classify_license("GNU LESSER GENERAL PUBLIC LICENSE"), "LGPL-3.0");
+        assert_eq!(
+            classify_license("Redistribution and use in source and binary forms... Neither the name of"),
+            "BSD-3-Clause"
+        );
+        assert_eq!(classify_license("All rights reserved."), "LicenseRef-Unknown");
+    }
+}
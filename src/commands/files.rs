@@ -6,6 +6,7 @@
 //! - imports: Show file imports
 //! - api: Show module public API
 //! - changed: Show changed symbols in git diff
+//! - explain: Show how the index recorded a file, or why it is missing or was not parsed
 
 use std::path::{Path, PathBuf};
 use std::time::Instant;
@@ -17,6 +18,8 @@ use regex::Regex;
 use crate::db::SymbolKind;
 
 use crate::db;
+use super::Page;
+use crate::t;
 use super::{search_files, relative_path};
 
 /// Outline helper: parse file with tree-sitter and print symbols, skipping specified kinds.
@@ -35,13 +38,13 @@ fn outline_via_treesitter(content: &str, file_type: crate::parsers::FileType, sk
 }
 
 /// Find files by pattern
-pub fn cmd_file(root: &Path, pattern: &str, exact: bool, limit: usize) -> Result<()> {
+pub fn cmd_file(root: &Path, pattern: &str, exact: bool, page: Page) -> Result<()> {
     let start = Instant::now();
 
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
@@ -49,7 +52,7 @@ pub fn cmd_file(root: &Path, pattern: &str, exact: bool, limit: usize) -> Result
     let conn = db::open_db(root)?;
 
     let search_pattern = if exact { pattern.to_string() } else { pattern.to_string() };
-    let files = db::find_files(&conn, &search_pattern, limit)?;
+    let (files, total) = db::find_files(&conn, &search_pattern, page.window(), false)?;
 
     println!("{}", format!("Files matching '{}':", pattern).bold());
 
@@ -61,7 +64,8 @@ pub fn cmd_file(root: &Path, pattern: &str, exact: bool, limit: usize) -> Result
         println!("  No files found.");
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    page.print_summary(total);
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -81,7 +85,7 @@ pub fn cmd_outline(root: &Path, file: &str) -> Result<()> {
         return Ok(());
     }
 
-    let content = std::fs::read_to_string(&file_path)?;
+    let (content, _) = crate::encoding::read_file(&file_path)?;
 
     // Detect file type
     let ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
@@ -130,8 +134,8 @@ pub fn cmd_outline(root: &Path, file: &str) -> Result<()> {
         }
     } else if is_python {
         // Python patterns
-        let class_re = Regex::new(r"^class\s+([A-Za-z_][A-Za-z0-9_]*)")?;
-        let func_re = Regex::new(r"^(async\s+)?def\s+([A-Za-z_][A-Za-z0-9_]*)")?;
+        let class_re = Regex::new(r"^class\s+([\p{L}_]\w*)")?;
+        let func_re = Regex::new(r"^(async\s+)?def\s+([\p{L}_]\w*)")?;
 
         for (line_num, line) in content.lines().enumerate() {
             let line_num = line_num + 1;
@@ -153,9 +157,9 @@ pub fn cmd_outline(root: &Path, file: &str) -> Result<()> {
     } else if is_go {
         // Go patterns
         let package_re = Regex::new(r"^package\s+([a-z][a-z0-9_]*)")?;
-        let struct_re = Regex::new(r"^type\s+([A-Z][a-zA-Z0-9_]*)\s+struct")?;
-        let interface_re = Regex::new(r"^type\s+([A-Z][a-zA-Z0-9_]*)\s+interface")?;
-        let func_re = Regex::new(r"^func\s+(?:\([^)]+\)\s*)?([A-Za-z_][A-Za-z0-9_]*)\s*\(")?;
+        let struct_re = Regex::new(r"^type\s+(\p{Lu}\w*)\s+struct")?;
+        let interface_re = Regex::new(r"^type\s+(\p{Lu}\w*)\s+interface")?;
+        let func_re = Regex::new(r"^func\s+(?:\([^)]+\)\s*)?([\p{L}_]\w*)\s*\(")?;
 
         for (line_num, line) in content.lines().enumerate() {
             let line_num = line_num + 1;
@@ -187,8 +191,8 @@ pub fn cmd_outline(root: &Path, file: &str) -> Result<()> {
     } else if is_cpp {
         // C++ patterns
         let namespace_re = Regex::new(r"^namespace\s+([\w:]+)\s*\{")?;
-        let class_re = Regex::new(r"^(?:class|struct)\s+([A-Z][a-zA-Z0-9_]*)")?;
-        let func_re = Regex::new(r"^(?:[\w:]+(?:<[^>]*>)?\s*[*&]?\s+)?([A-Z][a-zA-Z0-9_]*::)?([A-Za-z_][A-Za-z0-9_]*)\s*\([^)]*\)\s*(?:const)?\s*(?:override)?\s*\{")?;
+        let class_re = Regex::new(r"^(?:class|struct)\s+(\p{Lu}\w*)")?;
+        let func_re = Regex::new(r"^(?:[\w:]+(?:<[^>]*>)?\s*[*&]?\s+)?(\p{Lu}\w*::)?([\p{L}_]\w*)\s*\([^)]*\)\s*(?:const)?\s*(?:override)?\s*\{")?;
 
         for (line_num, line) in content.lines().enumerate() {
             let line_num = line_num + 1;
@@ -276,7 +280,7 @@ pub fn cmd_outline(root: &Path, file: &str) -> Result<()> {
         println!("  No symbols found.");
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -295,7 +299,7 @@ pub fn cmd_imports(root: &Path, file: &str) -> Result<()> {
         return Ok(());
     }
 
-    let content = std::fs::read_to_string(&file_path)?;
+    let (content, _) = crate::encoding::read_file(&file_path)?;
 
     // Detect file type by extension
     let ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
@@ -325,8 +329,8 @@ pub fn cmd_imports(root: &Path, file: &str) -> Result<()> {
         }
     } else if is_python {
         // Python: import module or from module import something
-        let import_re = Regex::new(r"^import\s+([A-Za-z_][A-Za-z0-9_\.]*)")?;
-        let from_re = Regex::new(r"^from\s+([A-Za-z_][A-Za-z0-9_\.]*)\s+import\s+(.+)")?;
+        let import_re = Regex::new(r"^import\s+([\p{L}_][\w.]*)")?;
+        let from_re = Regex::new(r"^from\s+([\p{L}_][\w.]*)\s+import\s+(.+)")?;
         for line in content.lines() {
             if let Some(caps) = from_re.captures(line) {
                 let module = caps.get(1).map(|m| m.as_str()).unwrap_or("");
@@ -341,7 +345,7 @@ pub fn cmd_imports(root: &Path, file: &str) -> Result<()> {
         // Go: import "module" or import ( "module1" "module2" )
         let single_import_re = Regex::new(r#"^import\s+"([^"]+)""#)?;
         let import_block_start = Regex::new(r"^import\s*\(")?;
-        let import_line_re = Regex::new(r#"^\s*(?:[a-zA-Z_][a-zA-Z0-9_]*\s+)?"([^"]+)""#)?;
+        let import_line_re = Regex::new(r#"^\s*(?:[\p{L}_]\w*\s+)?"([^"]+)""#)?;
 
         let mut in_import_block = false;
         for line in content.lines() {
@@ -387,7 +391,7 @@ pub fn cmd_imports(root: &Path, file: &str) -> Result<()> {
         println!("\n  Total: {} imports", imports.len());
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -457,7 +461,7 @@ pub fn cmd_api(root: &Path, module_path: &str, limit: usize) -> Result<()> {
         println!("  No public API found.");
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -549,6 +553,24 @@ fn normalize_base_for_vcs(vcs: &str, base: &str) -> String {
     }
 }
 
+/// Files under `root` (relative to it) that differ from `rev` in git: committed since, staged,
+/// modified or untracked; deleted files are left out since nothing of them is indexed
+pub fn files_changed_since(root: &Path, rev: &str) -> Result<Vec<String>> {
+    let git = |args: &[&str]| -> Result<Vec<String>> {
+        let output = std::process::Command::new("git").args(args).current_dir(root).output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git {} failed: {}", args[0], stderr.trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect())
+    };
+    let mut files = git(&["diff", "--name-only", "--relative", "--diff-filter=d", rev, "--"])?;
+    files.extend(git(&["ls-files", "--others", "--exclude-standard"])?);
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
 /// Show changed symbols in git/arc diff
 pub fn cmd_changed(root: &Path, base: &str) -> Result<()> {
     let start = Instant::now();
@@ -625,6 +647,65 @@ pub fn cmd_changed(root: &Path, base: &str) -> Result<()> {
         }
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+/// Show how the index recorded a file: parsed with its counts, skipped with the reason, or
+/// missing with the likely cause
+pub fn cmd_explain(root: &Path, file: &str, format: &str) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let file_path = if file.starts_with('/') {
+        PathBuf::from(file)
+    } else {
+        root.join(file)
+    };
+    let rel_path = relative_path(root, &file_path);
+    let conn = db::open_db(root)?;
+    let report = db::file_report(&conn, &rel_path)?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "path": rel_path, "indexed": report }))?);
+        return Ok(());
+    }
+
+    println!("{}", rel_path.bold());
+    match report {
+        Some(report) => {
+            match report.skipped.as_deref() {
+                Some(reason) => {
+                    let description = db::SkipReason::from_name(reason).map_or(reason, |r| r.description());
+                    println!("  Status:    {}", format!("skipped, not parsed: {}", description).yellow());
+                }
+                None => println!("  Status:    {}", "indexed".green()),
+            }
+            println!("  Size:      {} bytes", report.size);
+            println!("  Language:  {}", report.language.as_deref().unwrap_or("-"));
+            println!("  Encoding:  {}", report.encoding.as_deref().unwrap_or("-"));
+            println!("  Test file: {}", if report.is_test { "yes" } else { "no" });
+            println!("  Symbols:   {}", report.symbols);
+            println!("  Refs:      {}", report.refs);
+            println!("  Imports:   {}", report.imports);
+        }
+        None => {
+            let ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            let cause = if !file_path.exists() {
+                "the file does not exist".to_string()
+            } else if crate::parsers::language_of(ext).is_none() {
+                format!("no parser handles .{} files", ext)
+            } else {
+                "excluded by ignore rules or an excluded directory, or added since the last 'ast-index update'".to_string()
+            };
+            println!("  Status:    {}", format!("not in the index: {}", cause).yellow());
+        }
+    }
+
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
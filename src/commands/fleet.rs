@@ -0,0 +1,277 @@
+//! Multi-repository indexing
+//!
+//! - fleet: Clone/update a list of repositories, index each one into its own DB
+//!   and combine them into a merged index
+//! - merge: Combine index DBs built separately (e.g. CI shards of one monorepo) into one
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use rusqlite::{Connection, OpenFlags};
+
+use crate::db;
+use crate::t;
+
+/// One entry of the repos file
+struct RepoEntry {
+    /// Git URL or local path
+    source: String,
+    /// Directory/index name inside the target directory
+    name: String,
+}
+
+/// Outcome of indexing a single repository
+struct RepoOutcome {
+    name: String,
+    db_path: PathBuf,
+    error: Option<String>,
+}
+
+/// Parse the repos file: one `<url-or-path> [name]` per line, `#` starts a comment. Names become
+/// file and directory names under the target, so each must be unique and a single path component
+fn parse_repos_file(content: &str) -> Result<Vec<RepoEntry>> {
+    let mut repos: Vec<RepoEntry> = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let source = parts.next().unwrap_or_default().to_string();
+        let name = parts
+            .next()
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| repo_name_from_source(&source));
+        if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+            anyhow::bail!("Line {}: invalid repository name '{}': a name must be one path component", idx + 1, name);
+        }
+        if repos.iter().any(|repo| repo.name.eq_ignore_ascii_case(&name)) {
+            anyhow::bail!("Line {}: repository name '{}' is already used; give one of them another name: <url-or-path> <name>", idx + 1, name);
+        }
+        repos.push(RepoEntry { source, name });
+    }
+    Ok(repos)
+}
+
+/// Derive a directory name from a git URL or path (`git@host:org/app.git` -> `app`)
+fn repo_name_from_source(source: &str) -> String {
+    source
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or(source)
+        .trim_end_matches(".git")
+        .to_string()
+}
+
+/// Clone or update a repository; local directories are indexed in place
+fn sync_repo(entry: &RepoEntry, checkout_dir: &Path, log: &mut std::fs::File) -> Result<PathBuf> {
+    let local = Path::new(&entry.source);
+    if local.is_dir() {
+        return Ok(local.canonicalize()?);
+    }
+
+    let dest = checkout_dir.join(&entry.name);
+    let args: Vec<String> = if dest.join(".git").exists() {
+        vec!["-C".into(), dest.to_string_lossy().into(), "pull".into(), "--ff-only".into(), "--quiet".into()]
+    } else {
+        vec!["clone".into(), "--depth".into(), "1".into(), "--quiet".into(), entry.source.clone(), dest.to_string_lossy().into()]
+    };
+    let status = Command::new("git")
+        .args(&args)
+        .stdout(log.try_clone()?)
+        .stderr(log.try_clone()?)
+        .status()
+        .context("Failed to run git")?;
+    if !status.success() {
+        anyhow::bail!("git {} failed ({})", args[0], status);
+    }
+    Ok(dest)
+}
+
+/// Run `ast-index rebuild` for one repository with its own DB path
+fn index_repo(entry: &RepoEntry, target: &Path, no_deps: bool) -> RepoOutcome {
+    let db_path = target.join("indexes").join(format!("{}.db", entry.name));
+    let result = (|| -> Result<()> {
+        let mut log = std::fs::File::create(target.join("logs").join(format!("{}.log", entry.name)))?;
+        let repo_dir = sync_repo(entry, &target.join("repos"), &mut log)?;
+
+        let mut cmd = Command::new(std::env::current_exe()?);
+        cmd.arg("rebuild")
+            .current_dir(&repo_dir)
+            .env("AST_INDEX_DB_PATH", &db_path)
+            .env("AST_INDEX_THREADS", "2")
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log);
+        if no_deps {
+            cmd.arg("--no-deps");
+        }
+        let status = cmd.status().context("Failed to run rebuild")?;
+        if !status.success() {
+            anyhow::bail!("rebuild failed ({}), see logs/{}.log", status, entry.name);
+        }
+        Ok(())
+    })();
+
+    RepoOutcome {
+        name: entry.name.clone(),
+        db_path,
+        error: result.err().map(|e| e.to_string()),
+    }
+}
+
+/// Index a list of repositories in parallel into `<target>/indexes/<name>.db`
+/// and merge them into `<target>/merged.db`
+pub fn cmd_fleet(repos_file: &str, target: &str, jobs: usize, no_merge: bool, no_deps: bool) -> Result<()> {
+    let start = Instant::now();
+
+    let content = std::fs::read_to_string(repos_file)
+        .with_context(|| format!("Failed to read {}", repos_file))?;
+    let repos = parse_repos_file(&content).with_context(|| format!("Invalid {}", repos_file))?;
+    if repos.is_empty() {
+        println!("{}", format!("No repositories listed in {}", repos_file).yellow());
+        return Ok(());
+    }
+
+    let target = PathBuf::from(target);
+    for sub in ["repos", "indexes", "logs"] {
+        std::fs::create_dir_all(target.join(sub))?;
+    }
+    let target = target.canonicalize()?;
+
+    let jobs = jobs.clamp(1, repos.len());
+    println!(
+        "{}",
+        format!("Indexing {} repositories with {} jobs into {}", repos.len(), jobs, target.display()).cyan()
+    );
+
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::with_capacity(repos.len()));
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let Some(entry) = repos.get(i) else { break };
+                let t = Instant::now();
+                let outcome = index_repo(entry, &target, no_deps);
+                let n = done.fetch_add(1, Ordering::SeqCst) + 1;
+                match &outcome.error {
+                    None => println!("[{}/{}] {} {}", n, repos.len(), entry.name.green(), format!("{:?}", t.elapsed()).dimmed()),
+                    Some(e) => println!("[{}/{}] {} {}", n, repos.len(), entry.name.red(), e),
+                }
+                outcomes.lock().unwrap().push(outcome);
+            });
+        }
+    });
+    let mut outcomes = outcomes.into_inner().unwrap();
+    outcomes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let failed = outcomes.iter().filter(|o| o.error.is_some()).count();
+
+    if !no_merge {
+        let merged_path = target.join("merged.db");
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(target.join(format!("merged.db{}", suffix)));
+        }
+        let mut conn = Connection::open(&merged_path)?;
+        db::init_db(&conn)?;
+        let (mut files, mut symbols) = (0, 0);
+        for outcome in outcomes.iter().filter(|o| o.error.is_none()) {
+            let stats = db::merge_database(&mut conn, &outcome.db_path, &format!("{}/", outcome.name))?;
+            files += stats.files;
+            symbols += stats.symbols;
+        }
+        println!(
+            "{}",
+            format!("Merged index: {} ({} files, {} symbols)", merged_path.display(), files, symbols).dimmed()
+        );
+    }
+
+    println!(
+        "{}",
+        format!("Done: {} indexed, {} failed", outcomes.len() - failed, failed).green()
+    );
+    eprintln!("{}", t!("total-time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+
+    if failed > 0 {
+        anyhow::bail!("{} repositories failed to index", failed);
+    }
+    Ok(())
+}
+
+/// Merge index DBs into a new index at `out`. File IDs are renumbered; a path found in several
+/// inputs is kept once, from the input where its mtime is newest
+pub fn cmd_merge(inputs: &[String], out: &str) -> Result<()> {
+    let start = Instant::now();
+    let out = PathBuf::from(out);
+    let mut refs_indexed = false;
+    for input in inputs {
+        let path = Path::new(input);
+        if !path.is_file() {
+            anyhow::bail!("Index {} not found", input);
+        }
+        if out.exists() && path.canonicalize()? == out.canonicalize()? {
+            anyhow::bail!("--out {} is also an input; write the merged index to a new file", out.display());
+        }
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get::<_, i64>(0))
+            .with_context(|| format!("{} is not an ast-index database", input))?;
+        refs_indexed |= db::refs_indexed(&conn);
+    }
+
+    if let Some(dir) = out.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", out.display(), suffix));
+    }
+    let mut conn = Connection::open(&out)?;
+    conn.pragma_update(None, "foreign_keys", "ON")?;
+    db::init_db(&conn)?;
+    // Symbols-only unless some input has references
+    db::set_refs_indexed(&conn, refs_indexed)?;
+
+    for input in inputs {
+        let stats = db::merge_database_with(&mut conn, Path::new(input), "", db::Duplicates::Newer)?;
+        let mut line = format!("{}: {} files, {} symbols, {} refs", input, stats.files, stats.symbols, stats.refs);
+        if stats.replaced_files > 0 {
+            line.push_str(&format!(", {} replaced older copies", stats.replaced_files));
+        }
+        if stats.skipped_files > 0 {
+            line.push_str(&format!(", {} already merged", stats.skipped_files));
+        }
+        println!("{}", line.dimmed());
+    }
+
+    let files: i64 = conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))?;
+    let symbols: i64 = conn.query_row("SELECT COUNT(*) FROM symbols", [], |row| row.get(0))?;
+    println!(
+        "{}",
+        format!("Merged {} indexes into {} ({} files, {} symbols)", inputs.len(), out.display(), files, symbols).green()
+    );
+    eprintln!("{}", t!("total-time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_repos_file() {
+        let repos = parse_repos_file("# services\ngit@a:org1/app.git\ngit@b:org2/app.git app2  # fork\n../local/lib/\n").unwrap();
+        let names: Vec<&str> = repos.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["app", "app2", "lib"]);
+
+        for content in ["git@a:org1/app.git\ngit@b:org2/app.git", "git@a:org/App.git\ngit@b:org/app.git", "git@a:org/x.git ../x", "git@a:org/x.git a/b", "/tmp/.."] {
+            assert!(parse_repos_file(content).is_err(), "{}", content);
+        }
+    }
+}
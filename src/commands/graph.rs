@@ -0,0 +1,164 @@
+//! Dependency graph export and checks
+//!
+//! - graph: File dependencies from the imports and refs tables, collapsed into modules or
+//!   directories and printed as DOT, Mermaid, JSON or a plain edge list
+//! - check-arch: File dependencies checked against the `[[architecture]]` layering rules
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::config::ArchitectureRule;
+use crate::db;
+use crate::t;
+
+/// How `graph` groups files into nodes
+pub struct GraphOptions<'a> {
+    /// `module`, `dir` or `file`
+    pub scope: &'a str,
+    /// Directory levels kept when collapsing by directory
+    pub depth: usize,
+    /// Leave out edges with fewer used symbols
+    pub min_weight: usize,
+}
+
+pub fn cmd_graph(root: &Path, options: &GraphOptions, format: &str) -> Result<()> {
+    let start = Instant::now();
+    if !matches!(options.scope, "module" | "dir" | "file") {
+        anyhow::bail!("Unknown graph scope '{}' (known: module, dir, file)", options.scope);
+    }
+    if !matches!(format, "text" | "json" | "dot" | "mermaid") {
+        anyhow::bail!("Unknown graph format '{}' (known: text, json, dot, mermaid)", format);
+    }
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+    let conn = db::open_db(root)?;
+
+    let areas = if options.scope == "module" { super::Areas::modules(&conn)? } else { super::Areas::directories() };
+    let node_of = |path: &str| -> String {
+        if options.scope == "file" {
+            path.to_string()
+        } else {
+            areas.area_of(path, options.depth)
+        }
+    };
+
+    let mut edges: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for dep in db::file_dependencies(&conn)? {
+        let (from, to) = (node_of(&dep.from), node_of(&dep.to));
+        if from != to {
+            *edges.entry((from, to)).or_default() += dep.symbols;
+        }
+    }
+    edges.retain(|_, weight| *weight >= options.min_weight);
+    let nodes: BTreeSet<&str> = edges.keys().flat_map(|(from, to)| [from.as_str(), to.as_str()]).collect();
+
+    match format {
+        "json" => {
+            let edges: Vec<serde_json::Value> = edges
+                .iter()
+                .map(|((from, to), weight)| serde_json::json!({ "from": from, "to": to, "symbols": weight }))
+                .collect();
+            let result = serde_json::json!({ "scope": options.scope, "nodes": nodes, "edges": edges });
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        }
+        "dot" => {
+            let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+            println!("digraph dependencies {{");
+            println!("  rankdir=LR;");
+            println!("  node [shape=box];");
+            for node in &nodes {
+                println!("  {};", quote(node));
+            }
+            for ((from, to), weight) in &edges {
+                println!("  {} -> {} [label=\"{}\"];", quote(from), quote(to), weight);
+            }
+            println!("}}");
+        }
+        "mermaid" => {
+            // Mermaid ids must be plain words, so nodes get numbered ids and quoted labels
+            let ids: BTreeMap<&str, usize> = nodes.iter().enumerate().map(|(i, node)| (*node, i)).collect();
+            println!("graph LR");
+            for (node, id) in &ids {
+                println!("  n{}[\"{}\"]", id, node.replace('"', "#quot;"));
+            }
+            for ((from, to), weight) in &edges {
+                println!("  n{} -->|{}| n{}", ids[from.as_str()], weight, ids[to.as_str()]);
+            }
+        }
+        _ => {
+            println!("{}", format!("Dependencies by {} ({} nodes, {} edges):", options.scope, nodes.len(), edges.len()).bold());
+            for ((from, to), weight) in &edges {
+                println!("  {} → {} {}", from.cyan(), to, format!("({})", weight).dimmed());
+            }
+            eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+        }
+    }
+    Ok(())
+}
+
+/// Check file dependencies against `rules`; errors when any rule is broken, for CI. A file
+/// matching a rule's `deny` globs itself may depend on its own layer.
+pub fn cmd_check_arch(root: &Path, rules: &[ArchitectureRule], format: &str) -> Result<()> {
+    let start = Instant::now();
+    if rules.is_empty() {
+        println!("{}", "No [[architecture]] rules in .ast-index.toml".yellow());
+        return Ok(());
+    }
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+    let conn = db::open_db(root)?;
+
+    let denied = |rule: &ArchitectureRule, path: &str| rule.deny.iter().any(|glob| db::glob_matches(glob, path));
+    let dependencies = db::file_dependencies(&conn)?;
+    // Violations per rule, in rule order
+    let violations: Vec<(&ArchitectureRule, Vec<&db::FileDependency>)> = rules
+        .iter()
+        .map(|rule| {
+            let broken = dependencies
+                .iter()
+                .filter(|dep| db::glob_matches(&rule.from, &dep.from) && !denied(rule, &dep.from) && denied(rule, &dep.to))
+                .collect();
+            (rule, broken)
+        })
+        .collect();
+    let count: usize = violations.iter().map(|(_, deps)| deps.len()).sum();
+
+    if format == "json" {
+        let list: Vec<serde_json::Value> = violations
+            .iter()
+            .flat_map(|(rule, deps)| {
+                deps.iter().map(|dep| {
+                    serde_json::json!({ "rule": rule.display_name(), "from": dep.from, "to": dep.to, "symbols": dep.symbols })
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "rules": rules.len(), "violations": list }))?);
+    } else {
+        if count == 0 {
+            println!("{}", format!("No architecture violations ({} rules)", rules.len()).green());
+        } else {
+            println!("{}", format!("Architecture violations ({}):", count).bold());
+        }
+        for (rule, deps) in violations.iter().filter(|(_, deps)| !deps.is_empty()) {
+            println!("  {}", rule.display_name().yellow());
+            for dep in deps {
+                println!("    {} → {} {}", dep.from, dep.to.red(), format!("({} symbols)", dep.symbols).dimmed());
+            }
+        }
+        eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    }
+
+    if count > 0 {
+        anyhow::bail!("{} architecture violations", count);
+    }
+    Ok(())
+}
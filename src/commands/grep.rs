@@ -23,6 +23,7 @@ use anyhow::Result;
 use colored::Colorize;
 use regex::Regex;
 
+use crate::t;
 use super::{search_files_limited, relative_path};
 
 /// Find TODO/FIXME/HACK comments
@@ -74,7 +75,7 @@ pub fn cmd_todo(root: &Path, pattern: &str, limit: usize) -> Result<()> {
         }
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -115,7 +116,7 @@ pub fn cmd_callers(root: &Path, function_name: &str, limit: usize) -> Result<()>
         }
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -131,7 +132,7 @@ pub fn cmd_call_tree(root: &Path, function_name: &str, max_depth: usize, limit_p
 
     build_call_tree(root, function_name, 1, max_depth, limit_per_level, &mut visited)?;
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -326,7 +327,7 @@ pub fn cmd_provides(root: &Path, type_name: &str, limit: usize) -> Result<()> {
         println!("    {}", truncated);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -360,7 +361,7 @@ pub fn cmd_suspend(root: &Path, query: Option<&str>, limit: usize) -> Result<()>
         println!("  {}: {}:{}", func_name.cyan(), path, line_num);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -425,7 +426,7 @@ pub fn cmd_composables(root: &Path, query: Option<&str>, limit: usize) -> Result
         println!("  {}: {}:{}", func_name.cyan(), path, line_num);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -457,7 +458,7 @@ pub fn cmd_deprecated(root: &Path, query: Option<&str>, limit: usize) -> Result<
         println!("    {}", content);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -487,7 +488,7 @@ pub fn cmd_suppress(root: &Path, query: Option<&str>, limit: usize) -> Result<()
         println!("    {}", content);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -522,7 +523,7 @@ pub fn cmd_inject(root: &Path, type_name: &str, limit: usize) -> Result<()> {
         println!("    {}", content);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -553,7 +554,7 @@ pub fn cmd_annotations(root: &Path, annotation: &str, limit: usize) -> Result<()
         println!("    {}", content);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -586,7 +587,7 @@ pub fn cmd_deeplinks(root: &Path, query: Option<&str>, limit: usize) -> Result<(
         println!("    {}", content);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -626,7 +627,7 @@ pub fn cmd_extensions(root: &Path, receiver_type: &str, limit: usize) -> Result<
         }
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -661,7 +662,7 @@ pub fn cmd_flows(root: &Path, query: Option<&str>, limit: usize) -> Result<()> {
         println!("    {}", content);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -726,7 +727,7 @@ pub fn cmd_previews(root: &Path, query: Option<&str>, limit: usize) -> Result<()
         println!("  {}: {}:{}", func_name.cyan(), path, line_num);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -0,0 +1,91 @@
+//! Symbol history
+//!
+//! - history: When declarations of a name appeared, changed signature, moved and disappeared,
+//!   by commit, from the optional per-commit history (`history --update` builds it; later
+//!   queries bring it up to date with HEAD first)
+
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::history;
+use crate::t;
+
+/// Commit abbreviation in text output
+const SHORT_SHA: usize = 10;
+
+pub fn cmd_history(
+    root: &Path,
+    symbol: Option<&str>,
+    update: bool,
+    max_commits: Option<usize>,
+    kind: Option<&str>,
+    format: &str,
+) -> Result<()> {
+    let start = Instant::now();
+    if symbol.is_none() && !update {
+        anyhow::bail!("Give a symbol name, or --update to index the git history");
+    }
+    if !update && !history::history_exists(root) {
+        println!("{}", "No symbol history yet: run `ast-index history --update` (--max-commits N limits it to recent commits)".yellow());
+        return Ok(());
+    }
+
+    let mut conn = history::open_history_db(root)?;
+    let progress = |done: usize, total: usize| {
+        if done.is_multiple_of(100) || done == total {
+            eprint!("\r{}", format!("Indexing history: {}/{} commits", done, total).dimmed());
+            if done == total {
+                eprintln!();
+            }
+        }
+    };
+    let indexed = history::update_history(&mut conn, root, max_commits, &progress)?;
+    let range = history::history_range(&conn)?;
+
+    let Some(symbol) = symbol else {
+        println!("Indexed {} new commits ({} in history)", indexed, range.commits);
+        eprintln!("{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+        return Ok(());
+    };
+    let events = history::symbol_history(&conn, symbol, kind)?;
+
+    if format == "json" {
+        let result = serde_json::json!({ "symbol": symbol, "range": range, "events": events });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    let first = range.first.as_deref().map(short).unwrap_or("");
+    println!("{}", format!("History of '{}' ({} commits since {}):", symbol, range.commits, first).bold());
+    if events.is_empty() {
+        println!("  No changes recorded.");
+    }
+    for e in &events {
+        let change = match e.change.as_str() {
+            "added" => e.change.green(),
+            "removed" => e.change.red(),
+            _ => e.change.yellow(),
+        };
+        let location = match &e.previous_path {
+            Some(from) => format!("{} → {}:{}", from, e.path, e.line.unwrap_or(0)),
+            None => format!("{}:{}", e.path, e.line.unwrap_or(0)),
+        };
+        println!("  {}  {}  {:<7}  [{}] {}", e.date, short(&e.sha).cyan(), change, e.kind, location);
+        println!("      {} — {}", e.subject, e.author.dimmed());
+        if e.change != "removed" {
+            if let Some(signature) = &e.signature {
+                println!("      {}", signature.dimmed());
+            }
+        }
+    }
+
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(SHORT_SHA)]
+}
@@ -1,165 +1,465 @@
 //! Index-based search commands
 //!
 //! Commands for searching through the code index:
-//! - search: Full-text search across files and symbols
+//! - search: Full-text search across files and symbols (or by meaning with --semantic)
 //! - symbol: Find symbol by name
 //! - class: Find class by name
 //! - implementations: Find implementations of interface/class
 //! - hierarchy: Show class hierarchy
 //! - usages: Find symbol usages (indexed or grep-based)
+//! - complete: Name completion for editors
 
+use std::collections::HashMap;
 use std::path::Path;
 use std::time::Instant;
 
 use anyhow::Result;
 use colored::Colorize;
 use regex::Regex;
-use rusqlite::{params, Connection};
+use rusqlite::params;
 
 use crate::db::{self, SearchScope};
-use super::{search_files, relative_path};
+use crate::embed;
+use crate::fuzzy;
+use crate::parsers;
+use crate::t;
+use super::{search_files, relative_path, ContextLines, Page};
+
+/// How `search` matches symbols against the query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Name prefix via FTS, or a boolean query when the query has AND/OR/NOT or quotes
+    Default,
+    /// exact → prefix → contains → within a few typos
+    Fuzzy,
+    /// The query is an FTS5 expression, passed through unchanged
+    RawFts,
+    /// Only names equal to, starting with or containing the query
+    Name(db::NameMatch),
+}
 
 /// Full-text search across files, symbols, and file contents
-pub fn cmd_search(root: &Path, query: &str, limit: usize, format: &str, scope: &SearchScope, fuzzy: bool) -> Result<()> {
+pub fn cmd_search(root: &Path, query: &str, page: Page, format: &str, scope: &SearchScope, mode: SearchMode, context: Option<usize>) -> Result<()> {
     let total_start = Instant::now();
+    let limit = page.fetch_limit();
+    let mut context = context.map(|radius| ContextLines::new(root, radius));
 
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
 
+    // Boolean (`Payment AND Refund NOT Legacy`) and raw FTS5 queries only apply to symbols
+    let fts_query = match mode {
+        SearchMode::RawFts => Some(query.to_string()),
+        SearchMode::Default => db::boolean_fts_query(query)?,
+        _ => None,
+    };
+    if fts_query.is_some() && scope.case_sensitive {
+        eprintln!("{}", "Boolean and raw FTS5 queries always ignore case".dimmed());
+    }
+    let fuzzy = mode == SearchMode::Fuzzy;
     let conn = db::open_db(root)?;
+    // A kind filter or an FTS expression narrows the search to symbols: no file names or grep matches
+    let symbols_only = !scope.kinds.is_empty() || fts_query.is_some();
 
     // 1. Search in file paths (index)
     let files_start = Instant::now();
-    let mut files = db::find_files(&conn, query, limit)?;
+    let mut files = if symbols_only { vec![] } else { db::find_files(&conn, query, db::Window::first(limit), scope.case_sensitive)?.0 };
     if let Some(prefix) = scope.dir_prefix {
         files.retain(|f| f.starts_with(prefix));
     }
+    files.retain(|f| scope.allows_path(f));
+    let files_total = page.take(&mut files);
     let files_time = files_start.elapsed();
 
     // 2. Search in symbols using FTS or fuzzy (index)
     let symbols_start = Instant::now();
-    let symbols = if fuzzy {
+    let filter_fuzzy = symbols_only || !scope.languages.is_empty() || !scope.paths.is_empty() || !scope.exclude_paths.is_empty() || scope.changed_files.is_some();
+    let mut symbols = if let Some(fts_query) = &fts_query {
+        db::search_symbols_fts(&conn, fts_query, limit, scope)?
+    } else if let SearchMode::Name(name_match) = mode {
+        db::search_symbols_matching(&conn, query, name_match, limit, scope)?
+    } else if fuzzy && filter_fuzzy {
+        let mut all = db::search_symbols_fuzzy(&conn, query, limit * 5)?;
+        all.retain(|s| (scope.kinds.is_empty() || scope.kinds.iter().any(|k| k.as_str() == s.kind)) && scope.allows_path(&s.path));
+        all.truncate(limit);
+        all
+    } else if fuzzy {
         db::search_symbols_fuzzy(&conn, query, limit)?
+    } else if db::is_qualified_query(query) {
+        // `billing.Invoice`: qualified names ending with it, else the plain name search
+        let qualified = db::search_qualified(&conn, query, limit, scope)?;
+        if qualified.is_empty() {
+            db::search_symbols_scoped(&conn, &format!("{}*", query), limit, scope)?
+        } else {
+            qualified
+        }
     } else {
         let fts_query = format!("{}*", query); // Prefix search
         db::search_symbols_scoped(&conn, &fts_query, limit, scope)?
     };
+    // CamelHump abbreviations (`PRImpl` -> PaymentRepositoryImpl) fill the remaining slots
+    let abbreviations = matches!(mode, SearchMode::Default | SearchMode::Fuzzy) && fts_query.is_none();
+    if symbols.len() < limit && abbreviations && fuzzy::is_abbreviation(query) {
+        for s in db::search_camel_humps(&conn, query, limit, scope)? {
+            if symbols.len() >= limit {
+                break;
+            }
+            if !symbols.iter().any(|r| r.path == s.path && r.line == s.line && r.name == s.name) {
+                symbols.push(s);
+            }
+        }
+    }
+    let ambiguous = if fts_query.is_none() { db::ambiguous_declarations(query, &symbols) } else { vec![] };
+    // Partial parts fold into one result before paging, so pages stay full and totals count results
+    let mut symbols = db::merge_partial_types(&conn, symbols)?;
+    let symbols_total = page.take(&mut symbols);
     let symbols_time = symbols_start.elapsed();
 
-    // 3. Search in file contents (grep)
+    // 3. Search in file contents (the `content_fts` line index, else grep)
     let content_start = Instant::now();
-    let pattern = regex::escape(query);
     let mut content_matches: Vec<(String, usize, String)> = vec![];
+    let in_scope = |rel_path: &str| {
+        scope.dir_prefix.is_none_or(|prefix| rel_path.starts_with(prefix))
+            && scope.in_file.is_none_or(|in_file| rel_path.contains(in_file))
+            && scope.module.is_none_or(|module| rel_path.starts_with(module))
+            && scope.allows_path(rel_path)
+    };
 
-    super::search_files_limited(root, &pattern, &["kt", "java", "swift", "m", "h", "py", "go", "rs", "cpp", "c", "proto"], limit, |path, line_num, line| {
-        let rel_path = super::relative_path(root, path);
-        // Apply scope filter for grep results
-        if let Some(prefix) = scope.dir_prefix {
-            if !rel_path.starts_with(prefix) { return; }
-        }
-        if let Some(in_file) = scope.in_file {
-            if !rel_path.contains(in_file) { return; }
+    if !symbols_only && db::has_content_fts(&conn) {
+        content_matches = db::search_content(&conn, query, limit, |path, text| {
+            in_scope(path) && (!scope.case_sensitive || text.contains(query))
+        })?;
+        for (_, _, content) in &mut content_matches {
+            *content = content.chars().take(100).collect();
         }
-        if let Some(module) = scope.module {
-            if !rel_path.starts_with(module) { return; }
-        }
-        let content: String = line.trim().chars().take(100).collect();
-        content_matches.push((rel_path, line_num, content));
-    })?;
+    } else if !symbols_only {
+        let pattern = if scope.case_sensitive { regex::escape(query) } else { format!("(?i){}", regex::escape(query)) };
+        super::search_files_limited(root, &pattern, &["kt", "java", "swift", "m", "h", "py", "go", "rs", "cpp", "c", "proto"], limit, |path, line_num, line| {
+            let rel_path = super::relative_path(root, path);
+            // Apply scope filter for grep results
+            if !in_scope(&rel_path) { return; }
+            let content: String = line.trim().chars().take(100).collect();
+            content_matches.push((rel_path, line_num, content));
+        })?;
+    }
+    let content_total = page.take(&mut content_matches);
     let content_time = content_start.elapsed();
+    let totals = [("files", files_total), ("symbols", symbols_total), ("content_matches", content_total)];
 
     if format == "json" {
-        let result = serde_json::json!({
+        let mut result = serde_json::json!({
             "files": files,
             "symbols": symbols,
             "content_matches": content_matches.iter().map(|(p, l, c)| {
                 serde_json::json!({"path": p, "line": l, "content": c})
             }).collect::<Vec<_>>()
         });
-        println!("{}", serde_json::to_string_pretty(&result)?);
+        if let Some(lines) = &mut context {
+            for (i, s) in symbols.iter().enumerate() {
+                result["symbols"][i]["context"] = lines.json(&s.path, s.line as usize);
+            }
+            for (i, (path, line, _)) in content_matches.iter().enumerate() {
+                result["content_matches"][i]["context"] = lines.json(path, *line);
+            }
+        }
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(result, &totals))?);
         return Ok(());
     }
 
     // Output results
-    println!("{}", format!("Search results for '{}':", query).bold());
+    println!("{}", t!("search-results", query = query).bold());
 
     if !files.is_empty() {
-        println!("\n{}", "Files (by path):".cyan());
+        println!("\n{}", t!("search-files").cyan());
         for path in files.iter().take(limit) {
             println!("  {}", path);
         }
         if files.len() > limit {
-            println!("  ... and {} more", files.len() - limit);
+            println!("  {}", t!("and-more", count = files.len() - limit));
         }
     }
 
     if !symbols.is_empty() {
-        println!("\n{}", "Symbols:".cyan());
+        println!("\n{}", t!("search-symbols").cyan());
         for s in symbols.iter().take(limit) {
-            println!("  {} [{}]: {}:{}", s.name.cyan(), s.kind, s.path, s.line);
+            println!("  {} [{}]: {}:{}", s.display_name_with_params().cyan(), s.kind, s.path, s.line);
+            print_partial_parts(s);
+            if let Some(lines) = &mut context {
+                lines.print(&s.path, s.line as usize, &s.name, "    ");
+            }
         }
+        print_ambiguous(query, &ambiguous);
     }
 
     if !content_matches.is_empty() {
-        println!("\n{}", "Content matches:".cyan());
+        println!("\n{}", t!("search-content").cyan());
         for (path, line_num, content) in content_matches.iter().take(limit) {
             println!("  {}:{}", path.cyan(), line_num);
-            println!("    {}", content.dimmed());
+            match &mut context {
+                Some(lines) => lines.print(path, *line_num, query, "    "),
+                None => println!("    {}", content.dimmed()),
+            }
         }
         if content_matches.len() > limit {
-            println!("  ... and {} more", content_matches.len() - limit);
+            println!("  {}", t!("and-more", count = content_matches.len() - limit));
         }
     }
 
     if files.is_empty() && symbols.is_empty() && content_matches.is_empty() {
-        println!("  No results found.");
+        println!("  {}", t!("no-results"));
     }
 
+    page.print_summary(files_total.max(symbols_total).max(content_total));
     // Timing breakdown
-    eprintln!("\n{}", format!(
-        "Time: {:?} (files: {:?}, symbols: {:?}, content: {:?})",
-        total_start.elapsed(), files_time, symbols_time, content_time
+    eprintln!("\n{}", t!(
+        "search-time",
+        elapsed = format!("{:?}", total_start.elapsed()),
+        files = format!("{:?}", files_time),
+        symbols = format!("{:?}", symbols_time),
+        content = format!("{:?}", content_time),
     ).dimmed());
     Ok(())
 }
 
+/// Several declarations share the searched name: list the shortest qualified suffix that picks each
+fn print_ambiguous(query: &str, ambiguous: &[(String, String)]) {
+    if ambiguous.is_empty() {
+        return;
+    }
+    println!("\n{}", t!("search-ambiguous", query = query, count = ambiguous.len()).yellow());
+    for (suffix, qualified) in ambiguous {
+        println!("  {}  {}", suffix.cyan(), qualified.dimmed());
+    }
+}
+
+/// Search symbols by fully qualified name
+pub fn cmd_search_qualified(root: &Path, query: &str, page: Page, format: &str, scope: &SearchScope) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!(
+            "{}",
+            t!("index-not-found").red()
+        );
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    let mut symbols = db::search_qualified(&conn, query, page.fetch_limit(), scope)?;
+    let ambiguous = db::ambiguous_declarations(query, &symbols);
+    let total = page.take(&mut symbols);
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(serde_json::to_value(&symbols)?, &[("symbols", total)]))?);
+        return Ok(());
+    }
+
+    println!("{}", format!("Symbols with qualified name '{}':", query).bold());
+    for s in &symbols {
+        let name = s.qualified_name.clone().unwrap_or_else(|| s.display_name());
+        println!("  {} [{}]: {}:{}", name.cyan(), s.kind, s.path, s.line);
+    }
+
+    print_ambiguous(query, &ambiguous);
+    if symbols.is_empty() {
+        println!("  {}", t!("no-symbols"));
+    }
+
+    page.print_summary(total);
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+/// Search symbols by the text of their doc comments
+pub fn cmd_search_docs(root: &Path, query: &str, page: Page, format: &str, scope: &SearchScope) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!(
+            "{}",
+            t!("index-not-found").red()
+        );
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    let (matches, total) = db::search_docs(&conn, query, page.window(), scope)?;
+
+    if format == "json" {
+        let result: Vec<_> = matches
+            .iter()
+            .map(|(s, doc)| {
+                let mut value = serde_json::to_value(s)?;
+                value["doc"] = doc.clone().into();
+                Ok(value)
+            })
+            .collect::<Result<_>>()?;
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(serde_json::Value::Array(result), &[("symbols", total)]))?);
+        return Ok(());
+    }
+
+    println!("{}", format!("Symbols documented with '{}':", query).bold());
+    for (s, doc) in &matches {
+        println!("  {} [{}]: {}:{}", s.display_name().cyan(), s.kind, s.path, s.line);
+        println!("    {}", doc.dimmed());
+    }
+
+    if matches.is_empty() {
+        println!("  {}", t!("no-symbols"));
+    }
+
+    page.print_summary(total);
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+/// Rank symbols by similarity of their embedding to the query's (`search --semantic`)
+pub fn cmd_search_semantic(root: &Path, query: &str, page: Page, format: &str, scope: &SearchScope) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!(
+            "{}",
+            t!("index-not-found").red()
+        );
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    let embedder = embed::embedder();
+    let (embedded, symbol_count) = db::embedding_counts(&conn)?;
+    let stored_model = db::embedding_model(&conn)?;
+    if embedded == 0 || stored_model.as_deref() != Some(embedder.id().as_str()) {
+        println!("{}", "No embeddings for the configured model; run `ast-index embed` first".red());
+        return Ok(());
+    }
+    if embedded < symbol_count {
+        eprintln!(
+            "{}",
+            format!("{} of {} symbols have no embedding yet; run `ast-index embed`", symbol_count - embedded, symbol_count).yellow()
+        );
+    }
+
+    let query_vector = embedder.embed(&[query.to_string()])?.pop().unwrap_or_default();
+    let mut matches = db::search_symbols_semantic(&conn, &query_vector, page.fetch_limit(), scope)?;
+    let total = page.take(&mut matches);
+
+    if format == "json" {
+        let result: Vec<_> = matches
+            .iter()
+            .map(|(s, score)| {
+                let mut value = serde_json::to_value(s)?;
+                value["score"] = serde_json::json!((*score * 1000.0).round() / 1000.0);
+                Ok(value)
+            })
+            .collect::<Result<_>>()?;
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(serde_json::Value::Array(result), &[("symbols", total)]))?);
+        return Ok(());
+    }
+
+    println!("{}", format!("Symbols similar to '{}':", query).bold());
+    for (s, score) in &matches {
+        println!("  {:.3} {} [{}]: {}:{}", score, s.display_name().cyan(), s.kind, s.path, s.line);
+    }
+
+    if matches.is_empty() {
+        println!("  {}", t!("no-symbols"));
+    }
+
+    page.print_summary(total);
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+/// List symbols carrying an annotation/decorator/attribute
+pub fn cmd_search_annotation(root: &Path, annotation: &str, page: Page, format: &str, scope: &SearchScope) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!(
+            "{}",
+            t!("index-not-found").red()
+        );
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    let (matches, total) = db::search_annotation(&conn, annotation, page.window(), scope)?;
+
+    if format == "json" {
+        let result: Vec<_> = matches
+            .iter()
+            .map(|(s, written)| {
+                let mut value = serde_json::to_value(s)?;
+                value["annotation"] = written.clone().into();
+                Ok(value)
+            })
+            .collect::<Result<_>>()?;
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(serde_json::Value::Array(result), &[("symbols", total)]))?);
+        return Ok(());
+    }
+
+    println!("{}", format!("Symbols annotated with '{}':", annotation).bold());
+    for (s, written) in &matches {
+        println!(
+            "  {} [{}]: {}:{}  {}",
+            s.display_name().cyan(),
+            s.kind,
+            s.path,
+            s.line,
+            written.dimmed()
+        );
+    }
+
+    if matches.is_empty() {
+        println!("  {}", t!("no-symbols"));
+    }
+
+    page.print_summary(total);
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
 /// Find symbol by name
-pub fn cmd_symbol(root: &Path, name: &str, kind: Option<&str>, limit: usize, format: &str, scope: &SearchScope, fuzzy: bool) -> Result<()> {
+pub fn cmd_symbol(root: &Path, name: &str, kind: Option<&str>, page: Page, format: &str, scope: &SearchScope, fuzzy: bool) -> Result<()> {
     let start = Instant::now();
 
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
 
     let conn = db::open_db(root)?;
     let symbols = if fuzzy && kind.is_none() {
-        db::search_symbols_fuzzy(&conn, name, limit)?
+        db::search_symbols_fuzzy(&conn, name, page.fetch_limit())?
     } else {
-        db::find_symbols_by_name_scoped(&conn, name, kind, limit, scope)?
+        db::find_symbols_by_name_scoped(&conn, name, kind, page.fetch_limit(), scope)?
     };
+    let mut symbols = db::merge_partial_types(&conn, symbols)?;
+    let total = page.take(&mut symbols);
 
     if format == "json" {
-        println!("{}", serde_json::to_string_pretty(&symbols)?);
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(serde_json::to_value(&symbols)?, &[("symbols", total)]))?);
         return Ok(());
     }
 
     let kind_str = kind.map(|k| format!(" ({})", k)).unwrap_or_default();
     println!(
         "{}",
-        format!("Symbols matching '{}'{}:", name, kind_str).bold()
+        t!("symbols-matching", name = name, kind = kind_str).bold()
     );
 
     for s in &symbols {
-        println!("  {} [{}]: {}:{}", s.name.cyan(), s.kind, s.path, s.line);
+        println!("  {} [{}]: {}:{}", s.display_name().cyan(), s.kind, s.path, s.line);
+        print_partial_parts(s);
         if let Some(sig) = &s.signature {
             let truncated: String = sig.chars().take(70).collect();
             println!("    {}", truncated.dimmed());
@@ -167,21 +467,22 @@ pub fn cmd_symbol(root: &Path, name: &str, kind: Option<&str>, limit: usize, for
     }
 
     if symbols.is_empty() {
-        println!("  No symbols found.");
+        println!("  {}", t!("no-symbols"));
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    page.print_summary(total);
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
 /// Find class by name (classes, interfaces, objects, enums)
-pub fn cmd_class(root: &Path, name: &str, limit: usize, format: &str, scope: &SearchScope, fuzzy: bool) -> Result<()> {
+pub fn cmd_class(root: &Path, name: &str, page: Page, format: &str, scope: &SearchScope, fuzzy: bool) -> Result<()> {
     let start = Instant::now();
 
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
@@ -189,6 +490,7 @@ pub fn cmd_class(root: &Path, name: &str, limit: usize, format: &str, scope: &Se
     let conn = db::open_db(root)?;
 
     // Single query for all class-like symbols
+    let limit = page.fetch_limit();
     let results = if fuzzy {
         // Fuzzy: search all symbols then filter to class-like kinds
         let all = db::search_symbols_fuzzy(&conn, name, limit * 5)?;
@@ -199,57 +501,91 @@ pub fn cmd_class(root: &Path, name: &str, limit: usize, format: &str, scope: &Se
     } else {
         db::find_class_like_scoped(&conn, name, limit, scope)?
     };
+    let mut results = db::merge_partial_types(&conn, results)?;
+    let total = page.take(&mut results);
 
     if format == "json" {
-        println!("{}", serde_json::to_string_pretty(&results)?);
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(serde_json::to_value(&results)?, &[("classes", total)]))?);
         return Ok(());
     }
 
-    println!("{}", format!("Classes matching '{}':", name).bold());
+    println!("{}", t!("classes-matching", name = name).bold());
 
     for s in &results {
-        println!("  {} [{}]: {}:{}", s.name.cyan(), s.kind, s.path, s.line);
+        println!("  {} [{}]: {}:{}", s.display_name().cyan(), s.kind, s.path, s.line);
+        print_partial_parts(s);
     }
 
     if results.is_empty() {
-        println!("  No classes found.");
+        println!("  {}", t!("no-classes"));
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    page.print_summary(total);
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
-/// Find implementations of interface/class
-pub fn cmd_implementations(root: &Path, parent: &str, limit: usize, format: &str, scope: &SearchScope) -> Result<()> {
+/// Find implementations of interface/class, including implementors of implementors unless `direct`
+pub fn cmd_implementations(root: &Path, parent: &str, direct: bool, page: Page, format: &str, scope: &SearchScope) -> Result<()> {
     let start = Instant::now();
 
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
 
     let conn = db::open_db(root)?;
-    let impls = if scope.is_empty() {
-        db::find_implementations(&conn, parent, limit)?
+    let limit = page.fetch_limit();
+    // Scoped queries filter results post-query, so fetch more
+    let fetch = if scope.is_empty() { limit } else { limit * 5 };
+    let all = if direct {
+        let mut all = db::find_implementations(&conn, parent, fetch)?;
+        // Go types satisfy interfaces implicitly: add types whose methods cover the interface's method set
+        for found in db::find_go_implementations(&conn, parent, fetch)? {
+            if !all.iter().any(|s| s.path == found.path && s.line == found.line) {
+                all.push(found);
+            }
+        }
+        all.into_iter().map(|symbol| db::Implementation { symbol, via: None }).collect()
+    } else {
+        db::find_implementations_transitive(&conn, parent, fetch)?
+    };
+    let mut impls: Vec<_> = if scope.is_empty() {
+        all.into_iter().take(limit).collect()
     } else {
-        // For scoped implementations, filter results post-query
-        let all = db::find_implementations(&conn, parent, limit * 5)?;
-        all.into_iter().filter(|s| {
+        all.into_iter().filter(|i| {
+            let s = &i.symbol;
             if let Some(in_file) = scope.in_file {
                 if !s.path.contains(in_file) { return false; }
             }
             if let Some(module) = scope.module {
                 if !s.path.starts_with(module) { return false; }
             }
+            if let Some(visibility) = scope.visibility {
+                if s.visibility.as_deref() != Some(visibility) { return false; }
+            }
             true
         }).take(limit).collect()
     };
+    // Partial parts fold into the first part, before paging; a dropped part takes its `via` with it
+    let vias: HashMap<(String, i64), String> = impls
+        .iter_mut()
+        .filter_map(|i| Some(((i.symbol.path.clone(), i.symbol.line), i.via.take()?)))
+        .collect();
+    let mut impls: Vec<db::Implementation> = db::merge_partial_types(&conn, impls.into_iter().map(|i| i.symbol).collect())?
+        .into_iter()
+        .map(|symbol| {
+            let via = vias.get(&(symbol.path.clone(), symbol.line)).cloned();
+            db::Implementation { symbol, via }
+        })
+        .collect();
+    let total = page.take(&mut impls);
 
     if format == "json" {
-        println!("{}", serde_json::to_string_pretty(&impls)?);
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(serde_json::to_value(&impls)?, &[("implementations", total)]))?);
         return Ok(());
     }
 
@@ -258,40 +594,128 @@ pub fn cmd_implementations(root: &Path, parent: &str, limit: usize, format: &str
         format!("Implementations of '{}':", parent).bold()
     );
 
-    for s in &impls {
-        println!("  {} [{}]: {}:{}", s.name.cyan(), s.kind, s.path, s.line);
+    for i in &impls {
+        let s = &i.symbol;
+        let via = i.via.as_ref().map(|v| format!(" (via {})", v).dimmed()).unwrap_or_default();
+        println!("  {} [{}]: {}:{}{}", s.display_name().cyan(), s.kind, s.path, s.line, via);
+        print_partial_parts(s);
     }
 
     if impls.is_empty() {
         println!("  No implementations found.");
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    page.print_summary(total);
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
+/// What `refs` looks up and how it lists usages
+pub struct RefsQuery<'a> {
+    pub symbol: &'a str,
+    /// Only usages of this kind (a `RefKind` name)
+    pub kind: Option<&'a str>,
+    /// Only overloads taking and calls passing this many arguments
+    pub arity: Option<usize>,
+    /// Group usages by `file` or `kind`
+    pub group_by: Option<&'a str>,
+}
+
+/// Usages keyed by file or by reference kind, biggest groups first
+fn group_refs(usages: Vec<db::RefResult>, by_kind: bool) -> Vec<(String, Vec<db::RefResult>)> {
+    let mut groups: Vec<(String, Vec<db::RefResult>)> = Vec::new();
+    for r in usages {
+        let key = if by_kind { r.ref_kind.clone().unwrap_or_else(|| "unknown".to_string()) } else { r.path.clone() };
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, members)) => members.push(r),
+            None => groups.push((key, vec![r])),
+        }
+    }
+    let occurrences = |refs: &[db::RefResult]| refs.iter().map(|r| r.count).sum::<i64>();
+    groups.sort_by(|a, b| occurrences(&b.1).cmp(&occurrences(&a.1)).then_with(|| a.0.cmp(&b.0)));
+    groups
+}
+
 /// Show cross-references: definitions, imports, usages
-pub fn cmd_refs(root: &Path, symbol: &str, limit: usize, format: &str) -> Result<()> {
+pub fn cmd_refs(root: &Path, query: &RefsQuery, page: Page, format: &str, scope: &SearchScope, context: Option<usize>) -> Result<()> {
     let start = Instant::now();
+    let symbol = query.symbol;
+    let mut context = context.map(|radius| ContextLines::new(root, radius));
+
+    let kind = query.kind
+        .map(|name| {
+            db::RefKind::from_name(name).ok_or_else(|| {
+                let known: Vec<&str> = db::RefKind::ALL.iter().map(|k| k.as_str()).collect();
+                anyhow::anyhow!("Unknown reference kind '{}' (known: {})", name, known.join(", "))
+            })
+        })
+        .transpose()?;
+    let by_kind = match query.group_by {
+        None | Some("file") => false,
+        Some("kind") => true,
+        Some(other) => anyhow::bail!("Unknown grouping '{}' (known: file, kind)", other),
+    };
 
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
 
     let conn = db::open_db(root)?;
-    let (definitions, imports, usages) = db::find_cross_references(&conn, symbol, limit)?;
+    if !super::require_refs(&conn, "refs") {
+        return Ok(());
+    }
+    // Group counts cover every usage, so grouping fetches them all and pages through groups
+    let fetch = if query.group_by.is_some() { Page::ALL } else { page.fetch_limit() };
+    let (mut definitions, mut imports, mut usages) = db::find_cross_references(&conn, symbol, kind, query.arity, fetch, scope)?;
+    let mut groups = match query.group_by {
+        Some(_) => group_refs(std::mem::take(&mut usages), by_kind),
+        None => vec![],
+    };
+    let occurrences = |refs: &[db::RefResult]| refs.iter().map(|r| r.count as usize).sum::<usize>();
+    let usage_count = occurrences(&usages) + groups.iter().map(|(_, members)| occurrences(members)).sum::<usize>();
+    let totals = [
+        ("definitions", page.take(&mut definitions)),
+        ("imports", page.take(&mut imports)),
+        if query.group_by.is_some() { ("groups", page.take(&mut groups)) } else { ("usages", page.take(&mut usages)) },
+    ];
 
     if format == "json" {
-        let result = serde_json::json!({
-            "definitions": definitions,
-            "imports": imports,
-            "usages": usages,
-        });
-        println!("{}", serde_json::to_string_pretty(&result)?);
+        let mut result = match query.group_by {
+            Some(group_by) => serde_json::json!({
+                "definitions": definitions,
+                "imports": imports,
+                "usage_count": usage_count,
+                "groups": groups.iter().map(|(key, members)| {
+                    serde_json::json!({group_by: key, "count": occurrences(members), "usages": members})
+                }).collect::<Vec<_>>(),
+            }),
+            None => serde_json::json!({
+                "definitions": definitions,
+                "imports": imports,
+                "usages": usages,
+            }),
+        };
+        if let Some(lines) = &mut context {
+            for (i, s) in definitions.iter().enumerate() {
+                result["definitions"][i]["context"] = lines.json(&s.path, s.line as usize);
+            }
+            for (i, s) in imports.iter().enumerate() {
+                result["imports"][i]["context"] = lines.json(&s.path, s.line as usize);
+            }
+            for (i, r) in usages.iter().enumerate() {
+                result["usages"][i]["context"] = lines.json(&r.path, r.line as usize);
+            }
+            for (g, (_, members)) in groups.iter().enumerate() {
+                for (i, r) in members.iter().enumerate() {
+                    result["groups"][g]["usages"][i]["context"] = lines.json(&r.path, r.line as usize);
+                }
+            }
+        }
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(result, &totals))?);
         return Ok(());
     }
 
@@ -300,7 +724,10 @@ pub fn cmd_refs(root: &Path, symbol: &str, limit: usize, format: &str) -> Result
     if !definitions.is_empty() {
         println!("\n  {}", "Definitions:".cyan());
         for s in &definitions {
-            println!("    {} [{}]: {}:{}", s.name.cyan(), s.kind, s.path, s.line);
+            println!("    {} [{}]: {}:{}", s.display_name_with_params().cyan(), s.kind, s.path, s.line);
+            if let Some(lines) = &mut context {
+                lines.print(&s.path, s.line as usize, symbol, "      ");
+            }
         }
     }
 
@@ -308,8 +735,39 @@ pub fn cmd_refs(root: &Path, symbol: &str, limit: usize, format: &str) -> Result
         println!("\n  {}", "Imports:".cyan());
         for s in &imports {
             println!("    {}:{}", s.path.cyan(), s.line);
-            if let Some(sig) = &s.signature {
-                println!("      {}", sig.dimmed());
+            match (&mut context, &s.signature) {
+                (Some(lines), _) => lines.print(&s.path, s.line as usize, symbol, "      "),
+                (None, Some(sig)) => println!("      {}", sig.dimmed()),
+                (None, None) => {}
+            }
+        }
+    }
+
+    if !groups.is_empty() {
+        let by = if by_kind { "kind" } else { "file" };
+        println!("\n  {}", format!("Usages by {} ({} in {} groups):", by, usage_count, totals[2].1).cyan());
+        for (key, members) in &groups {
+            println!("    {} ({})", key.cyan(), occurrences(members));
+            for r in members {
+                // The group key already names the file or the kind
+                let mut location = if by_kind { format!("{}:{}", r.path, r.line) } else { r.line.to_string() };
+                if let (false, Some(ref_kind)) = (by_kind, &r.ref_kind) {
+                    location.push_str(&format!(" [{}]", ref_kind));
+                }
+                if let Some(note) = r.aggregate_note() {
+                    location.push_str(&format!(" {}", note));
+                }
+                match (&mut context, &r.context) {
+                    (Some(lines), _) => {
+                        println!("      {}", location);
+                        lines.print(&r.path, r.line as usize, symbol, "        ");
+                    }
+                    (None, Some(ctx)) => {
+                        let truncated: String = ctx.chars().take(80).collect();
+                        println!("      {}  {}", location, truncated.dimmed());
+                    }
+                    (None, None) => println!("      {}", location),
+                }
             }
         }
     }
@@ -317,19 +775,159 @@ pub fn cmd_refs(root: &Path, symbol: &str, limit: usize, format: &str) -> Result
     if !usages.is_empty() {
         println!("\n  {}", "Usages:".cyan());
         for r in &usages {
-            println!("    {}:{}", r.path.cyan(), r.line);
-            if let Some(ctx) = &r.context {
-                let truncated: String = ctx.chars().take(80).collect();
-                println!("      {}", truncated.dimmed());
+            let note = r.aggregate_note().map(|note| format!(" {}", note.dimmed())).unwrap_or_default();
+            match &r.ref_kind {
+                Some(ref_kind) => println!("    {}:{} [{}]{}", r.path.cyan(), r.line, ref_kind, note),
+                None => println!("    {}:{}{}", r.path.cyan(), r.line, note),
+            }
+            match (&mut context, &r.context) {
+                (Some(lines), _) => lines.print(&r.path, r.line as usize, symbol, "      "),
+                (None, Some(ctx)) => {
+                    let truncated: String = ctx.chars().take(80).collect();
+                    println!("      {}", truncated.dimmed());
+                }
+                (None, None) => {}
             }
         }
     }
 
-    if definitions.is_empty() && imports.is_empty() && usages.is_empty() {
+    if definitions.is_empty() && imports.is_empty() && usages.is_empty() && groups.is_empty() {
         println!("  No references found.");
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    page.print_summary(totals.iter().map(|(_, total)| *total).max().unwrap_or(0));
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+/// Complete a name prefix for editors: one `name<TAB>kind<TAB>path:line` per line, no header or
+/// timing, so the output can be read as is
+pub fn cmd_complete(root: &Path, prefix: &str, limit: usize, format: &str) -> Result<()> {
+    if !db::db_exists(root) {
+        println!(
+            "{}",
+            t!("index-not-found").red()
+        );
+        return Ok(());
+    }
+
+    let conn = db::open_db_readonly(root)?;
+    let completions = db::complete_names(&conn, prefix, limit)?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string(&completions)?);
+        return Ok(());
+    }
+
+    let mut out = String::new();
+    for c in &completions {
+        out.push_str(&format!("{}\t{}\t{}:{}\n", c.name, c.kind, c.path, c.line));
+    }
+    print!("{}", out);
+    Ok(())
+}
+
+/// Go to definition: the single best definition of `name` as `path:line:col`, seen from
+/// `from` (`file` or `file:line`) when given
+pub fn cmd_def(root: &Path, name: &str, from: Option<&str>, format: &str) -> Result<()> {
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    // `file:line`, or just a file; absolute paths are made relative to the project root
+    let from = from.map(|from| {
+        let (file, line) = match from.rsplit_once(':') {
+            Some((file, line)) if line.parse::<usize>().is_ok() => (file, line.parse().ok()),
+            _ => (from, None),
+        };
+        (relative_path(root, Path::new(file)), line)
+    });
+
+    let conn = db::open_db(root)?;
+    let Some(def) = db::find_best_definition(&conn, name, from.as_ref().map(|(file, line)| (file.as_str(), *line)))? else {
+        println!("{}", format!("Definition of '{}' not found.", name).red());
+        return Ok(());
+    };
+    let s = &def.symbol;
+
+    // Column of the name on its line (1-based, in characters); 1 if the file can't be read
+    let content = crate::encoding::read_file(&root.join(&s.path)).map(|(text, _)| text).unwrap_or_default();
+    let column = content
+        .lines()
+        .nth((s.line.max(1) - 1) as usize)
+        .and_then(|text| text.find(&s.name).map(|byte| text[..byte].chars().count() + 1))
+        .unwrap_or(1);
+
+    if format == "json" {
+        let mut value = serde_json::to_value(&def)?;
+        value["column"] = column.into();
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    println!("{}:{}:{}", s.path, s.line, column);
+    Ok(())
+}
+
+/// Print the full definition of a symbol using its stored line range
+pub fn cmd_show(root: &Path, name: &str, page: Page, format: &str, scope: &SearchScope) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    let (definitions, total) = db::find_definitions(&conn, name, page.window(), scope)?;
+
+    let mut shown = Vec::with_capacity(definitions.len());
+    for def in &definitions {
+        let content = crate::encoding::read_file(&root.join(&def.symbol.path)).map(|(text, _)| text).unwrap_or_default();
+        let lines: Vec<&str> = content.lines().collect();
+        let line = def.symbol.line.max(1) as usize;
+        // Indexes built before spans were stored: estimate the end now
+        let end_line = match def.end_line {
+            Some(end) => end as usize,
+            None => {
+                let ext = Path::new(&def.symbol.path).extension().and_then(|e| e.to_str()).unwrap_or("");
+                parsers::span::end_line(&lines, line, parsers::FileType::from_extension(ext))
+            }
+        };
+        let body: Vec<String> = lines.iter().skip(line - 1).take(end_line.saturating_sub(line) + 1).map(|l| l.to_string()).collect();
+        shown.push((def, end_line, body));
+    }
+
+    if format == "json" {
+        let result: Vec<_> = shown
+            .iter()
+            .map(|(def, end_line, body)| {
+                let mut value = serde_json::to_value(def)?;
+                value["end_line"] = (*end_line).into();
+                value["body"] = body.join("\n").into();
+                Ok(value)
+            })
+            .collect::<Result<_>>()?;
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(serde_json::Value::Array(result), &[("definitions", total)]))?);
+        return Ok(());
+    }
+
+    for (def, end_line, body) in &shown {
+        let s = &def.symbol;
+        println!("{} [{}]: {}:{}-{}", s.display_name().cyan().bold(), s.kind, s.path, s.line, end_line);
+        for (offset, text) in body.iter().enumerate() {
+            println!("{} {}", format!("{:>5}", s.line as usize + offset).dimmed(), text);
+        }
+        println!();
+    }
+
+    if shown.is_empty() {
+        println!("  {}", t!("no-symbols"));
+    }
+
+    page.print_summary(total);
+    eprintln!("{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -340,7 +938,7 @@ pub fn cmd_hierarchy(root: &Path, name: &str) -> Result<()> {
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
@@ -353,18 +951,29 @@ pub fn cmd_hierarchy(root: &Path, name: &str) -> Result<()> {
     let packages = db::find_symbols_by_name(&conn, name, Some("package"), 1)?;
     let protocols = db::find_symbols_by_name(&conn, name, Some("protocol"), 1)?;
 
-    let target = classes.first().or(interfaces.first()).or(packages.first()).or(protocols.first());
-
-    if target.is_none() {
+    let Some(target) = classes.into_iter().chain(interfaces).chain(packages).chain(protocols).next() else {
         println!("{}", format!("Class '{}' not found.", name).red());
         return Ok(());
-    }
+    };
 
     println!("{}", format!("Hierarchy for '{}':", name).bold());
 
-    // Find parents
+    // Parents below are collected from every part of a `partial` type
+    let target = db::merge_partial_types(&conn, vec![target])?.remove(0);
+    if !target.partial_parts.is_empty() {
+        println!("\n  {}", "Partial parts:".cyan());
+        println!("    {}:{}", target.path, target.line);
+        for part in &target.partial_parts {
+            println!("    {}", part);
+        }
+    }
+
+    // Find parents, including conformances added by Swift extensions (`Name+Extension`)
+    // and Rust trait impls (`impl Trait for Name`)
     let mut stmt = conn.prepare(
-        "SELECT i.parent_name, i.kind FROM inheritance i JOIN symbols s ON i.child_id = s.id WHERE s.name = ?1",
+        "SELECT DISTINCT i.parent_name, i.kind FROM inheritance i JOIN symbols s ON i.child_id = s.id
+         WHERE (s.name = ?1 OR s.name = ?1 || '+Extension' OR s.name LIKE 'impl % for ' || ?1)
+           AND i.parent_name != ?1 AND i.kind != 'permits'",
     )?;
     let parents: Vec<(String, String)> = stmt
         .query_map([name], |row| Ok((row.get(0)?, row.get(1)?)))?
@@ -378,7 +987,7 @@ pub fn cmd_hierarchy(root: &Path, name: &str) -> Result<()> {
     }
 
     // Find children
-    let children = db::find_implementations(&conn, name, 20)?;
+    let children = db::merge_partial_types(&conn, db::find_implementations(&conn, name, 20)?)?;
     if !children.is_empty() {
         println!("\n  {}", "Children:".cyan());
         for c in &children {
@@ -386,35 +995,54 @@ pub fn cmd_hierarchy(root: &Path, name: &str) -> Result<()> {
         }
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
+/// Print the other declarations of a `partial` type under its result line
+fn print_partial_parts(s: &db::SearchResult) {
+    for part in &s.partial_parts {
+        println!("    {} {}", "partial:".dimmed(), part);
+    }
+}
+
 /// Find symbol usages (indexed or grep-based)
-pub fn cmd_usages(root: &Path, symbol: &str, limit: usize, format: &str, scope: &SearchScope) -> Result<()> {
+pub fn cmd_usages(root: &Path, symbol: &str, page: Page, format: &str, scope: &SearchScope) -> Result<()> {
     let start = Instant::now();
+    let limit = page.fetch_limit();
 
     // Try to use index first
-    let db_path = db::get_db_path(root)?;
-    if db_path.exists() {
-        let conn = Connection::open(&db_path)?;
+    if db::db_exists(root) {
+        let conn = db::open_db(root)?;
+
+        // A qualified name (`com.example.User`, `crate::db::open`) is resolved through each file's imports
+        let qualified = db::split_qualified(symbol);
+        let name = qualified.map_or(symbol, |(_, _, name)| name);
 
         // Check if refs table has data
-        let refs_count: i64 = conn.query_row("SELECT COUNT(*) FROM refs WHERE name = ?1 LIMIT 1", params![symbol], |row| row.get(0)).unwrap_or(0);
+        let refs_count: i64 = conn.query_row("SELECT COUNT(*) FROM refs WHERE name = ?1 LIMIT 1", params![name], |row| row.get(0)).unwrap_or(0);
 
         if refs_count > 0 {
             // Use indexed references with scope filtering
-            let refs = db::find_references_scoped(&conn, symbol, limit, scope)?;
+            let mut refs = if qualified.is_some() {
+                db::find_references_qualified(&conn, symbol, limit, scope)?
+            } else {
+                db::find_references_scoped(&conn, symbol, limit, scope)?
+            };
+            let total = page.take(&mut refs);
 
             if format == "json" {
-                println!("{}", serde_json::to_string_pretty(&refs)?);
+                println!("{}", serde_json::to_string_pretty(&page.wrap_json(serde_json::to_value(&refs)?, &[("usages", total)]))?);
                 return Ok(());
             }
 
-            println!("{}", format!("Usages of '{}' ({}):", symbol, refs.len()).bold());
+            println!("{}", format!("Usages of '{}' ({}):", symbol, total).bold());
 
             for r in &refs {
-                println!("  {}:{}", r.path.cyan(), r.line);
+                match r.aggregate_note() {
+                    Some(note) => println!("  {}:{} {}", r.path.cyan(), r.line, note.dimmed()),
+                    None => println!("  {}:{}", r.path.cyan(), r.line),
+                }
                 if let Some(ctx) = &r.context {
                     let truncated: String = ctx.chars().take(80).collect();
                     println!("    {}", truncated);
@@ -425,12 +1053,13 @@ pub fn cmd_usages(root: &Path, symbol: &str, limit: usize, format: &str, scope:
                 println!("  No usages found in index.");
             }
 
-            eprintln!("\n{}", format!("Time: {:?} (indexed)", start.elapsed()).dimmed());
+            page.print_summary(total);
+            eprintln!("\n{}", t!("time", elapsed = format!("{:?} (indexed)", start.elapsed())).dimmed());
             return Ok(());
         }
     }
 
-    // Fallback to grep-based search
+    // Fallback to the `content_fts` line index, else grep
     let pattern = format!(r"\b{}\b", regex::escape(symbol));
     let def_pattern = Regex::new(&format!(
         r"(class|interface|object|fun|val|var|typealias)\s+{}\b",
@@ -438,34 +1067,50 @@ pub fn cmd_usages(root: &Path, symbol: &str, limit: usize, format: &str, scope:
     ))?;
 
     let mut usages: Vec<(String, usize, String)> = vec![];
+    let conn = if db::db_exists(root) { Some(db::open_db(root)?) } else { None };
+    let content_fts = conn.as_ref().filter(|conn| db::has_content_fts(conn));
+
+    if let Some(conn) = content_fts {
+        let word = Regex::new(&pattern)?;
+        usages = db::search_content(conn, symbol, limit, |path, line| {
+            word.is_match(line)
+                && !def_pattern.is_match(line)
+                && scope.in_file.is_none_or(|in_file| path.contains(in_file))
+                && scope.module.is_none_or(|module| path.starts_with(module))
+        })?;
+        for (_, _, content) in &mut usages {
+            *content = content.chars().take(80).collect();
+        }
+    } else {
+        search_files(root, &pattern, &["kt", "java"], |path, line_num, line| {
+            if usages.len() >= limit { return; }
 
-    search_files(root, &pattern, &["kt", "java"], |path, line_num, line| {
-        if usages.len() >= limit { return; }
-
-        // Skip definitions
-        if def_pattern.is_match(line) { return; }
+            // Skip definitions
+            if def_pattern.is_match(line) { return; }
 
-        let rel_path = relative_path(root, path);
-        // Apply scope filter for grep results
-        if let Some(in_file) = scope.in_file {
-            if !rel_path.contains(in_file) { return; }
-        }
-        if let Some(module) = scope.module {
-            if !rel_path.starts_with(module) { return; }
-        }
-        let content: String = line.trim().chars().take(80).collect();
-        usages.push((rel_path, line_num, content));
-    })?;
+            let rel_path = relative_path(root, path);
+            // Apply scope filter for grep results
+            if let Some(in_file) = scope.in_file {
+                if !rel_path.contains(in_file) { return; }
+            }
+            if let Some(module) = scope.module {
+                if !rel_path.starts_with(module) { return; }
+            }
+            let content: String = line.trim().chars().take(80).collect();
+            usages.push((rel_path, line_num, content));
+        })?;
+    }
+    let total = page.take(&mut usages);
 
     if format == "json" {
         let result: Vec<_> = usages.iter().map(|(p, l, c)| {
             serde_json::json!({"path": p, "line": l, "content": c})
         }).collect();
-        println!("{}", serde_json::to_string_pretty(&result)?);
+        println!("{}", serde_json::to_string_pretty(&page.wrap_json(serde_json::Value::Array(result), &[("usages", total)]))?);
         return Ok(());
     }
 
-    println!("{}", format!("Usages of '{}' ({}):", symbol, usages.len()).bold());
+    println!("{}", format!("Usages of '{}' ({}):", symbol, total).bold());
 
     for (path, line_num, content) in &usages {
         println!("  {}:{}", path.cyan(), line_num);
@@ -476,6 +1121,56 @@ pub fn cmd_usages(root: &Path, symbol: &str, limit: usize, format: &str, scope:
         println!("  No usages found.");
     }
 
-    eprintln!("\n{}", format!("Time: {:?} (grep)", start.elapsed()).dimmed());
+    page.print_summary(total);
+    let method = if content_fts.is_some() { "content index" } else { "grep" };
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?} ({})", start.elapsed(), method)).dimmed());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(path: &str, line: i64, kind: Option<&str>, count: i64) -> db::RefResult {
+        db::RefResult {
+            name: "Repo".to_string(),
+            line,
+            context: None,
+            path: path.to_string(),
+            ref_kind: kind.map(str::to_string),
+            count,
+            sample_lines: vec![],
+        }
+    }
+
+    fn keys(groups: &[(String, Vec<db::RefResult>)]) -> Vec<(&str, usize)> {
+        groups.iter().map(|(key, members)| (key.as_str(), members.len())).collect()
+    }
+
+    #[test]
+    fn test_group_refs_by_file() {
+        let usages = vec![
+            usage("a.kt", 1, Some("call"), 1),
+            usage("b.kt", 2, Some("type"), 1),
+            usage("a.kt", 5, Some("type"), 1),
+            usage("c.kt", 3, Some("call"), 1),
+        ];
+        let groups = group_refs(usages, false);
+        // Biggest group first, ties by key; members keep their order
+        assert_eq!(keys(&groups), vec![("a.kt", 2), ("b.kt", 1), ("c.kt", 1)]);
+        assert_eq!(groups[0].1.iter().map(|r| r.line).collect::<Vec<_>>(), vec![1, 5]);
+    }
+
+    #[test]
+    fn test_group_refs_by_kind_weighs_aggregated_refs() {
+        let usages = vec![
+            usage("a.kt", 1, Some("call"), 1),
+            usage("b.kt", 2, Some("call"), 1),
+            // One aggregated row standing for 5 usages outweighs two single ones
+            usage("c.kt", 3, Some("type"), 5),
+            usage("d.kt", 4, None, 1),
+        ];
+        let groups = group_refs(usages, true);
+        assert_eq!(keys(&groups), vec![("type", 1), ("call", 2), ("unknown", 1)]);
+    }
+}
@@ -17,6 +17,7 @@ use colored::Colorize;
 use regex::Regex;
 
 use crate::db;
+use crate::t;
 use super::{search_files, relative_path};
 
 /// Find storyboard usages of a class
@@ -26,7 +27,7 @@ pub fn cmd_storyboard_usages(root: &Path, class_name: &str, module: Option<&str>
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
@@ -79,7 +80,7 @@ pub fn cmd_storyboard_usages(root: &Path, class_name: &str, module: Option<&str>
         }
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -90,7 +91,7 @@ pub fn cmd_asset_usages(root: &Path, asset: &str, module: Option<&str>, asset_ty
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
@@ -191,7 +192,7 @@ pub fn cmd_asset_usages(root: &Path, asset: &str, module: Option<&str>, asset_ty
         }
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -253,7 +254,7 @@ pub fn cmd_swiftui(root: &Path, query: Option<&str>, limit: usize) -> Result<()>
         }
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -296,7 +297,7 @@ pub fn cmd_async_funcs(root: &Path, query: Option<&str>, limit: usize) -> Result
         println!("  {}: {}:{}", func_name.cyan(), path, line_num);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -346,7 +347,7 @@ pub fn cmd_publishers(root: &Path, query: Option<&str>, limit: usize) -> Result<
         println!("    {}", content.dimmed());
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -385,6 +386,6 @@ pub fn cmd_main_actor(root: &Path, query: Option<&str>, limit: usize) -> Result<
         println!("    {}", content);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
@@ -3,23 +3,43 @@
 //! Commands for managing the code index:
 //! - rebuild: Rebuild the index (full or partial)
 //! - update: Incrementally update the index
-//! - stats: Show index statistics
+//! - stats: Show index statistics, overall or per module, language or directory
+//! - embed: Compute symbol embeddings for semantic search
+//! - build_memory_index: Index into the in-memory database (--db :memory:)
+//! - migrate: Apply pending schema migrations, or preview them
+//! - optimize: Compact the index database
 
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::time::Instant;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
+use serde::Serialize;
 
+use crate::codeowners::CodeOwners;
+use crate::config::RefsLayout;
 use crate::db;
+use crate::embed;
 use crate::indexer;
+use crate::parsers;
+use crate::profiles;
+use crate::t;
 
 
 /// File count threshold for auto-switching to sub-projects mode
 const AUTO_SUB_PROJECTS_THRESHOLD: usize = 65_000;
 
 /// Rebuild the index (full or partial)
-pub fn cmd_rebuild(root: &Path, index_type: &str, index_deps: bool, no_ignore: bool, sub_projects: bool, verbose: bool) -> Result<()> {
+pub fn cmd_rebuild(
+    root: &Path,
+    index_type: &str,
+    index_deps: bool,
+    no_ignore: bool,
+    sub_projects: bool,
+    verbose: bool,
+    rev: Option<&str>,
+) -> Result<()> {
     if verbose {
         std::env::set_var("AST_INDEX_VERBOSE", "1");
         eprintln!("[verbose] rebuild started for: {}", root.display());
@@ -27,13 +47,17 @@ pub fn cmd_rebuild(root: &Path, index_type: &str, index_deps: bool, no_ignore: b
         eprintln!("[verbose] db path: {:?}", db::get_db_path(root).ok());
     }
 
+    if rev.is_some() && (sub_projects || !matches!(index_type, "all" | "files" | "symbols")) {
+        anyhow::bail!("--rev indexes the source files of a revision; it does not combine with --sub-projects or --type {}", index_type);
+    }
+
     // Explicit sub-projects mode
     if sub_projects {
         return cmd_rebuild_sub_projects(root, index_type, index_deps, no_ignore, verbose);
     }
 
     // Auto-detect: if sub-projects exist and file count >= threshold, switch automatically
-    if index_type == "all" {
+    if index_type == "all" && rev.is_none() {
         let t = Instant::now();
         let subs = indexer::find_sub_projects(root);
         if verbose {
@@ -100,6 +124,10 @@ pub fn cmd_rebuild(root: &Path, index_type: &str, index_deps: bool, no_ignore: b
     let t = Instant::now();
     let mut conn = db::open_db(root)?;
     db::init_db(&conn)?;
+    db::set_refs_indexed(&conn, indexer::index_config().refs != RefsLayout::None)?;
+    db::set_indexed_languages(&conn, &parsers::enabled_languages())?;
+    shard_refs(&conn, root)?;
+    create_content_fts(&conn)?;
     if verbose { eprintln!("[verbose] DB opened + schema created in {:?}", t.elapsed()); }
 
     // Restore extra roots
@@ -117,7 +145,20 @@ pub fn cmd_rebuild(root: &Path, index_type: &str, index_deps: bool, no_ignore: b
             "INSERT OR REPLACE INTO metadata (key, value) VALUES ('no_ignore', '1')",
             [],
         ).ok();
-        println!("{}", "Including gitignored files (build/, etc.)...".yellow());
+        println!("{}", t!("including-ignored").yellow());
+    }
+
+    // A revision is read from git objects: only its source files, with no worktree access
+    if let Some(rev) = rev {
+        println!("{}", format!("Indexing revision {}...", rev).cyan());
+        let (commit, file_count) = indexer::index_git_revision(&mut conn, root, rev, true)?;
+        db::prune_string_refs(&conn)?;
+        db::sync_name_trigrams(&conn)?;
+        db::set_indexed_revision(&conn, &commit.sha)?;
+        println!("{}", format!("Indexed {} files at {} ({})", file_count, rev, &commit.sha[..12.min(commit.sha.len())]).green());
+        println!("{}", "Modules, dependencies and resources are not indexed from a revision".dimmed());
+        eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+        return Ok(());
     }
 
     // Detect project type — check actual platform markers for Mixed projects
@@ -127,7 +168,7 @@ pub fn cmd_rebuild(root: &Path, index_type: &str, index_deps: bool, no_ignore: b
 
     match index_type {
         "all" => {
-            println!("{}", "Rebuilding full index...".cyan());
+            println!("{}", t!("rebuilding-full").cyan());
             if verbose { eprintln!("[verbose] starting file walk + parse..."); }
             let t = Instant::now();
             let walk = indexer::index_directory(&mut conn, root, true, no_ignore)?;
@@ -152,6 +193,10 @@ pub fn cmd_rebuild(root: &Path, index_type: &str, index_deps: bool, no_ignore: b
                 }
             }
 
+            // String-literal refs are kept only for names that some indexed file declares
+            db::prune_string_refs(&conn)?;
+            db::sync_name_trigrams(&conn)?;
+
             let t = Instant::now();
             let module_count = indexer::index_modules_from_files(&conn, root, &all_module_files)?;
             if verbose { eprintln!("[verbose] index_modules: {} modules in {:?}", module_count, t.elapsed()); }
@@ -173,7 +218,7 @@ pub fn cmd_rebuild(root: &Path, index_type: &str, index_deps: bool, no_ignore: b
                 indexer::has_android_markers(std::path::Path::new(r))
             });
             if index_deps && any_has_deps {
-                println!("{}", "Indexing module dependencies...".cyan());
+                println!("{}", t!("indexing-module-deps").cyan());
                 if verbose { eprintln!("[verbose] indexing module deps..."); }
                 let t = Instant::now();
                 dep_count = indexer::index_module_dependencies(&mut conn, root, &all_module_files, true)?;
@@ -188,12 +233,12 @@ pub fn cmd_rebuild(root: &Path, index_type: &str, index_deps: bool, no_ignore: b
             let mut res_count = 0;
             let mut res_usage_count = 0;
             if is_android {
-                println!("{}", "Indexing XML layouts...".cyan());
+                println!("{}", t!("indexing-xml").cyan());
                 let t = Instant::now();
                 xml_count = indexer::index_xml_usages(&mut conn, root, &walk.xml_layout_files, true)?;
                 if verbose { eprintln!("[verbose] xml_usages: {} in {:?}", xml_count, t.elapsed()); }
 
-                println!("{}", "Indexing resources...".cyan());
+                println!("{}", t!("indexing-resources").cyan());
                 let t = Instant::now();
                 let (rc, ruc) = indexer::index_resources(&mut conn, root, &walk.res_files, true)?;
                 res_count = rc;
@@ -206,12 +251,12 @@ pub fn cmd_rebuild(root: &Path, index_type: &str, index_deps: bool, no_ignore: b
             let mut asset_count = 0;
             let mut asset_usage_count = 0;
             if is_ios {
-                println!("{}", "Indexing storyboards/xibs...".cyan());
+                println!("{}", t!("indexing-storyboards").cyan());
                 let t = Instant::now();
                 sb_count = indexer::index_storyboard_usages(&mut conn, root, &walk.storyboard_files, true)?;
                 if verbose { eprintln!("[verbose] storyboard_usages: {} in {:?}", sb_count, t.elapsed()); }
 
-                println!("{}", "Indexing iOS assets...".cyan());
+                println!("{}", t!("indexing-ios-assets").cyan());
                 let t = Instant::now();
                 let (ac, auc) = indexer::index_ios_assets(&mut conn, root, &walk.xcassets_dirs, true)?;
                 asset_count = ac;
@@ -223,66 +268,115 @@ pub fn cmd_rebuild(root: &Path, index_type: &str, index_deps: bool, no_ignore: b
             if is_android && is_ios {
                 println!(
                     "{}",
-                    format!(
-                        "Indexed {} files, {} modules, {} deps, {} XML usages, {} resources, {} storyboard usages, {} assets",
-                        file_count, module_count, dep_count, xml_count, res_count, sb_count, asset_count
+                    t!(
+                        "indexed-summary-mixed",
+                        files = file_count, modules = module_count, deps = dep_count, xml = xml_count,
+                        resources = res_count, storyboards = sb_count, assets = asset_count,
                     ).green()
                 );
             } else if is_ios {
                 println!(
                     "{}",
-                    format!(
-                        "Indexed {} files, {} modules, {} storyboard usages, {} assets ({} usages)",
-                        file_count, module_count, sb_count, asset_count, asset_usage_count
+                    t!(
+                        "indexed-summary-ios",
+                        files = file_count, modules = module_count, storyboards = sb_count,
+                        assets = asset_count, asset_usages = asset_usage_count,
                     ).green()
                 );
             } else {
                 println!(
                     "{}",
-                    format!(
-                        "Indexed {} files, {} modules, {} deps, {} transitive, {} XML usages, {} resources ({} usages)",
-                        file_count, module_count, dep_count, trans_count, xml_count, res_count, res_usage_count
+                    t!(
+                        "indexed-summary-android",
+                        files = file_count, modules = module_count, deps = dep_count, transitive = trans_count,
+                        xml = xml_count, resources = res_count, resource_usages = res_usage_count,
                     ).green()
                 );
             }
         }
         "files" | "symbols" => {
-            println!("{}", "Rebuilding symbols index...".cyan());
-            conn.execute("DELETE FROM symbols", [])?;
+            println!("{}", t!("rebuilding-symbols").cyan());
+            conn.execute("DELETE FROM symbols_data", [])?;
             conn.execute("DELETE FROM files", [])?;
             let walk = indexer::index_directory(&mut conn, root, true, no_ignore)?;
-            println!("{}", format!("Indexed {} files", walk.file_count).green());
+            db::prune_string_refs(&conn)?;
+            db::sync_name_trigrams(&conn)?;
+            println!("{}", t!("indexed-files", count = walk.file_count).green());
         }
         "modules" => {
-            println!("{}", "Rebuilding modules index...".cyan());
+            println!("{}", t!("rebuilding-modules").cyan());
             conn.execute("DELETE FROM module_deps", [])?;
             conn.execute("DELETE FROM modules", [])?;
             let module_count = indexer::index_modules(&conn, root)?;
 
             if index_deps {
-                println!("{}", "Indexing module dependencies...".cyan());
+                println!("{}", t!("indexing-module-deps").cyan());
                 let gradle_files = indexer::collect_build_files_from_db(&conn, root)?;
                 let dep_count = indexer::index_module_dependencies(&mut conn, root, &gradle_files, true)?;
                 println!(
                     "{}",
-                    format!("Indexed {} modules, {} dependencies", module_count, dep_count).green()
+                    t!("indexed-modules-deps", modules = module_count, deps = dep_count).green()
                 );
             } else {
-                println!("{}", format!("Indexed {} modules", module_count).green());
+                println!("{}", t!("indexed-modules", count = module_count).green());
             }
         }
         "deps" => {
-            println!("{}", "Indexing module dependencies...".cyan());
+            println!("{}", t!("indexing-module-deps").cyan());
             let gradle_files = indexer::collect_build_files_from_db(&conn, root)?;
             let dep_count = indexer::index_module_dependencies(&mut conn, root, &gradle_files, true)?;
-            println!("{}", format!("Indexed {} dependencies", dep_count).green());
+            println!("{}", t!("indexed-deps", count = dep_count).green());
         }
         _ => {
-            println!("{}", format!("Unknown index type: {}", index_type).red());
+            println!("{}", t!("unknown-index-type", type = index_type).red());
         }
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+/// Why `update` and `watch` leave an index of a git revision alone
+pub fn revision_index_message(sha: &str) -> String {
+    format!(
+        "The index was built from revision {}, not the worktree; run 'rebuild --rev <rev>' to refresh it or 'rebuild' to index the worktree",
+        &sha[..12.min(sha.len())]
+    )
+}
+
+/// Split the refs of a new index by the `[index] refs_shards` directories
+fn shard_refs(conn: &rusqlite::Connection, root: &Path) -> Result<()> {
+    let dirs = &indexer::index_config().refs_shards;
+    if dirs.is_empty() {
+        return Ok(());
+    }
+    if db::in_memory() {
+        eprintln!("{}", "refs_shards is ignored for an in-memory index".yellow());
+        return Ok(());
+    }
+    db::shards::create(conn, &db::get_db_path(root)?, dirs)?;
+    println!("{}", format!("Refs sharded by {}", dirs.join(", ")).dimmed());
+    Ok(())
+}
+
+/// Create the line index of a new index when `[index] content_fts` is on
+fn create_content_fts(conn: &rusqlite::Connection) -> Result<()> {
+    if indexer::index_config().content_fts {
+        db::create_content_fts(conn)?;
+    }
+    Ok(())
+}
+
+/// Bring an existing index in line with `[index] content_fts`: a line index turned off is dropped
+/// so it cannot go stale, one turned on is filled by the next `rebuild`
+pub fn sync_content_fts_setting(conn: &rusqlite::Connection) -> Result<()> {
+    let enabled = indexer::index_config().content_fts;
+    if !enabled && db::has_content_fts(conn) {
+        db::drop_content_fts(conn)?;
+        println!("{}", "content_fts is off: removed the line index".dimmed());
+    } else if enabled && !db::has_content_fts(conn) {
+        println!("{}", "content_fts is on: run 'ast-index rebuild' to build the line index".yellow());
+    }
     Ok(())
 }
 
@@ -324,6 +418,10 @@ fn cmd_rebuild_sub_projects(root: &Path, _index_type: &str, _index_deps: bool, n
     }
     let mut conn = db::open_db(root)?;
     db::init_db(&conn)?;
+    db::set_refs_indexed(&conn, indexer::index_config().refs != RefsLayout::None)?;
+    db::set_indexed_languages(&conn, &parsers::enabled_languages())?;
+    shard_refs(&conn, root)?;
+    create_content_fts(&conn)?;
     if verbose { eprintln!("[verbose] DB created in {:?}", t.elapsed()); }
 
     if no_ignore {
@@ -365,6 +463,9 @@ fn cmd_rebuild_sub_projects(root: &Path, _index_type: &str, _index_deps: bool, n
         }
     }
 
+    db::prune_string_refs(&conn)?;
+    db::sync_name_trigrams(&conn)?;
+
     println!();
     println!(
         "{}",
@@ -373,7 +474,7 @@ fn cmd_rebuild_sub_projects(root: &Path, _index_type: &str, _index_deps: bool, n
             success_count, total_files, fail_count
         ).green()
     );
-    eprintln!("{}", format!("Total time: {:?}", start.elapsed()).dimmed());
+    eprintln!("{}", t!("total-time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -384,38 +485,69 @@ pub fn cmd_update(root: &Path) -> Result<()> {
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
 
     let mut conn = db::open_db(root)?;
+    if let Some(sha) = db::indexed_revision(&conn) {
+        println!("{}", revision_index_message(&sha).yellow());
+        return Ok(());
+    }
 
-    println!("{}", "Checking for changes...".cyan());
+    sync_content_fts_setting(&conn)?;
+    println!("{}", t!("checking-changes").cyan());
     let (updated, changed, deleted) = indexer::update_directory_incremental(&mut conn, root, true)?;
 
     if updated == 0 && deleted == 0 {
-        println!("{}", "Index is up to date.".green());
+        println!("{}", t!("index-up-to-date").green());
     } else {
         println!(
             "{}",
-            format!(
-                "Updated: {} files ({} changed, {} deleted)",
-                updated + deleted,
-                changed,
-                deleted
-            )
-            .green()
+            t!("updated-files", total = updated + deleted, changed = changed, deleted = deleted).green()
         );
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+/// Index the project into the in-memory database before a command runs (`--db :memory:`); only
+/// the summary line is printed, on stderr, so the command's output stays clean
+pub fn build_memory_index(root: &Path) -> Result<()> {
+    let start = Instant::now();
+    let mut conn = db::open_db(root)?;
+    db::init_db(&conn)?;
+    db::set_refs_indexed(&conn, indexer::index_config().refs != RefsLayout::None)?;
+    create_content_fts(&conn)?;
+
+    let walk = indexer::index_directory(&mut conn, root, false, false)?;
+    db::prune_string_refs(&conn)?;
+    db::sync_name_trigrams(&conn)?;
+    indexer::index_modules_from_files(&conn, root, &walk.module_files)?;
+    if indexer::has_android_markers(root) {
+        indexer::index_module_dependencies(&mut conn, root, &walk.module_files, false)?;
+        indexer::build_transitive_deps(&mut conn, false)?;
+        indexer::index_xml_usages(&mut conn, root, &walk.xml_layout_files, false)?;
+        indexer::index_resources(&mut conn, root, &walk.res_files, false)?;
+    }
+    if indexer::has_ios_markers(root) {
+        indexer::index_ios_package_managers(&conn, root, false)?;
+        indexer::index_storyboard_usages(&mut conn, root, &walk.storyboard_files, false)?;
+        indexer::index_ios_assets(&mut conn, root, &walk.xcassets_dirs, false)?;
+    }
+
+    eprintln!("{}", format!("Indexed {} files in memory in {:?}", walk.file_count, start.elapsed()).dimmed());
     Ok(())
 }
 
 /// Restore index from a .db file
 pub fn cmd_restore(root: &Path, db_file: &str) -> Result<()> {
     let src = std::path::Path::new(db_file);
+    if db::in_memory() {
+        anyhow::bail!("restore copies into the index file; to query a saved index, pass it with --db {}", db_file);
+    }
 
     if !src.exists() {
         anyhow::bail!("File not found: {}", db_file);
@@ -467,6 +599,101 @@ pub fn cmd_restore(root: &Path, db_file: &str) -> Result<()> {
     Ok(())
 }
 
+/// Compute embeddings for symbols that have none yet, for `search --semantic`
+pub fn cmd_embed(root: &Path) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!(
+            "{}",
+            t!("index-not-found").red()
+        );
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    let embedder = embed::embedder();
+    let model = embedder.id();
+    if db::set_embedding_model(&conn, &model)? {
+        println!("{}", format!("Model changed to {}: recomputing all embeddings", model).yellow());
+    }
+
+    let sources = db::symbols_without_embedding(&conn)?;
+    // Stored in chunks so an interrupted run keeps what it computed
+    for chunk in sources.chunks(1000) {
+        let texts: Vec<String> = chunk
+            .iter()
+            .map(|s| embed::symbol_text(&s.name, &s.kind, s.signature.as_deref(), s.doc.as_deref()))
+            .collect();
+        let vectors = embedder.embed(&texts)?;
+        let embeddings: Vec<(i64, Vec<f32>)> = chunk.iter().map(|s| s.symbol_id).zip(vectors).collect();
+        db::store_embeddings(&conn, &embeddings)?;
+    }
+
+    let (embedded, _) = db::embedding_counts(&conn)?;
+    println!(
+        "{}",
+        format!("Embedded {} new symbols with {} ({} total)", sources.len(), model, embedded).green()
+    );
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+/// List the named index profiles, marking those without an index yet
+pub fn cmd_profiles(format: &str) -> Result<()> {
+    let registry = profiles::Registry::load()?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&registry.profiles)?);
+        return Ok(());
+    }
+
+    if registry.profiles.is_empty() {
+        println!("{}", "No index profiles; add one with 'ast-index profile-add <name> [--root DIR] [--db FILE]'".yellow());
+        return Ok(());
+    }
+    println!("{}", format!("Index profiles ({}):", profiles::Registry::path()?.display()).bold());
+    for (name, profile) in &registry.profiles {
+        let db_path = match &profile.db {
+            Some(db) => db.clone(),
+            None => db::get_db_path(&profile.root)?,
+        };
+        let db_note = match &profile.db {
+            Some(db) => format!(" (db {})", db.display()),
+            None => String::new(),
+        };
+        let missing = if db_path.exists() { "" } else { " no index yet" };
+        println!("  {} {}{}{}", name.cyan(), profile.root.display(), db_note.dimmed(), missing.yellow());
+    }
+    Ok(())
+}
+
+/// Add or replace the profile `name` for the project at `root`, with its index at `db` if given
+pub fn cmd_profile_add(name: &str, root: &Path, db_path: Option<&str>) -> Result<()> {
+    let root = root.canonicalize().with_context(|| format!("Project root {} does not exist", root.display()))?;
+    let db_path = match db_path {
+        Some(db::MEMORY_DB_PATH) => anyhow::bail!("A profile cannot use an in-memory index"),
+        Some(path) => Some(std::env::current_dir()?.join(path)),
+        None => None,
+    };
+    let mut registry = profiles::Registry::load()?;
+    registry.add(name, profiles::Profile { root: root.clone(), db: db_path })?;
+    registry.save()?;
+    println!("Profile '{}' → {}; use it with 'ast-index --index {} <command>'", name.cyan(), root.display(), name);
+    Ok(())
+}
+
+/// Remove the profile `name`; its index stays in place
+pub fn cmd_profile_remove(name: &str) -> Result<()> {
+    let mut registry = profiles::Registry::load()?;
+    if !registry.remove(name) {
+        registry.get(name)?;
+    }
+    registry.save()?;
+    println!("Removed profile '{}'", name);
+    Ok(())
+}
+
 /// Clear index database for current project
 pub fn cmd_clear(root: &Path) -> Result<()> {
     db::delete_db(root)?;
@@ -475,16 +702,24 @@ pub fn cmd_clear(root: &Path) -> Result<()> {
 }
 
 /// Show index statistics
-pub fn cmd_stats(root: &Path, format: &str) -> Result<()> {
+pub fn cmd_stats(root: &Path, by: Option<&str>, depth: usize, format: &str) -> Result<()> {
+    if let Some(by) = by {
+        if !matches!(by, "module" | "language" | "directory" | "owner") {
+            anyhow::bail!("Unknown stats grouping '{}' (known: module, language, directory, owner)", by);
+        }
+    }
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
 
     let conn = db::open_db(root)?;
+    if let Some(by) = by {
+        return area_stats(&conn, root, by, depth, format);
+    }
     let stats = db::get_stats(&conn)?;
     let db_path = db::get_db_path(root)?;
     let db_size = std::fs::metadata(&db_path)
@@ -497,6 +732,8 @@ pub fn cmd_stats(root: &Path, format: &str) -> Result<()> {
             "stats": stats,
             "db_size_bytes": db_size,
             "db_path": db_path.display().to_string(),
+            "refs_indexed": db::refs_indexed(&conn),
+            "revision": db::indexed_revision(&conn),
         });
         println!("{}", serde_json::to_string_pretty(&result)?);
         return Ok(());
@@ -507,11 +744,30 @@ pub fn cmd_stats(root: &Path, format: &str) -> Result<()> {
 
     println!("{}", "Index Statistics:".bold());
     println!("  Project:    {}", project_type.as_str());
+    if let Some(sha) = db::indexed_revision(&conn) {
+        println!("  Revision:   {}", sha);
+    }
     println!("  Files:      {}", stats.file_count);
     println!("  Symbols:    {}", stats.symbol_count);
-    println!("  Refs:       {}", stats.refs_count);
+    if db::refs_indexed(&conn) {
+        println!("  Refs:       {}", stats.refs_count);
+    } else {
+        println!("  Refs:       {}", "not indexed (symbols only)".yellow());
+    }
     println!("  Modules:    {}", stats.module_count);
 
+    let skipped: i64 = stats.skipped_files.values().sum();
+    if skipped > 0 {
+        let reasons: Vec<String> = stats.skipped_files.iter().map(|(reason, count)| format!("{} {}", reason, count)).collect();
+        println!("  Skipped:    {} files not parsed ({})", skipped, reasons.join(", "));
+        for (path, reason, size) in db::skipped_files(&conn, SKIPPED_FILES_SHOWN)? {
+            println!("    {} {}", path, format!("{}, {:.2} MB", reason, size as f64 / 1024.0 / 1024.0).dimmed());
+        }
+        if skipped > SKIPPED_FILES_SHOWN as i64 {
+            println!("    {}", format!("... and {} more; 'ast-index explain <file>' shows one", skipped - SKIPPED_FILES_SHOWN as i64).dimmed());
+        }
+    }
+
     // Show Android-specific stats if relevant
     if stats.xml_usages_count > 0 || stats.resources_count > 0 {
         println!("  XML usages: {}", stats.xml_usages_count);
@@ -524,9 +780,24 @@ pub fn cmd_stats(root: &Path, format: &str) -> Result<()> {
         println!("  iOS assets: {}", stats.ios_assets_count);
     }
 
+    if stats.fts_enabled {
+        println!("  Search:     FTS5");
+    } else {
+        println!("  Search:     {}", "LIKE fallback (FTS5 unavailable, degraded)".yellow());
+    }
+
     println!("  DB size:    {:.2} MB", db_size as f64 / 1024.0 / 1024.0);
     println!("  DB path:    {}", db_path.display());
 
+    let shard_counts = db::shards::counts(&conn)?;
+    if !shard_counts.is_empty() {
+        let shards: Vec<String> = shard_counts
+            .iter()
+            .map(|(dir, count)| format!("{} {}", dir.as_deref().unwrap_or("(rest)"), count))
+            .collect();
+        println!("  Refs shards: {}", shards.join(", "));
+    }
+
     // Show extra roots if any
     let extra_roots = db::get_extra_roots(&conn)?;
     if !extra_roots.is_empty() {
@@ -539,12 +810,120 @@ pub fn cmd_stats(root: &Path, format: &str) -> Result<()> {
     Ok(())
 }
 
+/// Skipped files listed by `stats`, largest first
+const SKIPPED_FILES_SHOWN: usize = 5;
+
+/// Largest files kept per area
+const LARGEST_FILES: usize = 3;
+
+/// Totals for one module, language, directory or owner
+#[derive(Serialize)]
+struct AreaStats {
+    area: String,
+    files: usize,
+    test_files: usize,
+    /// Share of files that are tests, 0..1
+    test_ratio: f64,
+    symbols: i64,
+    /// Symbols by kind
+    kinds: BTreeMap<String, i64>,
+    refs: i64,
+    /// References per KB of source
+    refs_per_kb: f64,
+    size: i64,
+    largest_files: Vec<LargeFile>,
+}
+
+#[derive(Serialize)]
+struct LargeFile {
+    path: String,
+    size: i64,
+}
+
+/// `stats --by`: counts summed per module (files outside modules by directory), language,
+/// directory cut to `depth` levels or `CODEOWNERS` owner, largest areas first. A file with
+/// several owners counts for each of them.
+fn area_stats(conn: &rusqlite::Connection, root: &Path, by: &str, depth: usize, format: &str) -> Result<()> {
+    let areas = if by == "module" { super::Areas::modules(conn)? } else { super::Areas::directories() };
+    let owners = match by {
+        "owner" => Some(CodeOwners::load(root)?),
+        _ => None,
+    };
+    let mut grouped: BTreeMap<String, AreaStats> = BTreeMap::new();
+    for file in db::file_stats(conn)? {
+        let file_areas = match by {
+            "language" => vec![file.language.clone().unwrap_or_else(|| "other".to_string())],
+            "owner" => owners.as_ref().map(|o| o.labels_of(&file.path)).unwrap_or_default(),
+            _ => vec![areas.area_of(&file.path, depth)],
+        };
+        for area in file_areas {
+            let stats = grouped.entry(area.clone()).or_insert_with(|| AreaStats {
+                area,
+                files: 0,
+                test_files: 0,
+                test_ratio: 0.0,
+                symbols: 0,
+                kinds: BTreeMap::new(),
+                refs: 0,
+                refs_per_kb: 0.0,
+                size: 0,
+                largest_files: Vec::new(),
+            });
+            stats.files += 1;
+            stats.test_files += usize::from(file.is_test);
+            stats.refs += file.refs;
+            stats.size += file.size;
+            for (kind, count) in &file.symbols {
+                stats.symbols += count;
+                *stats.kinds.entry(kind.clone()).or_default() += count;
+            }
+            stats.largest_files.push(LargeFile { path: file.path.clone(), size: file.size });
+        }
+    }
+
+    let mut areas: Vec<AreaStats> = grouped.into_values().collect();
+    for stats in &mut areas {
+        stats.test_ratio = stats.test_files as f64 / stats.files as f64;
+        stats.refs_per_kb = if stats.size > 0 { stats.refs as f64 * 1024.0 / stats.size as f64 } else { 0.0 };
+        stats.largest_files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+        stats.largest_files.truncate(LARGEST_FILES);
+    }
+    areas.sort_by(|a, b| b.symbols.cmp(&a.symbols).then_with(|| a.area.cmp(&b.area)));
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "by": by, "areas": areas }))?);
+        return Ok(());
+    }
+
+    let width = areas.iter().map(|a| a.area.chars().count()).max().unwrap_or(0).max(by.len());
+    println!("{}", format!("Index Statistics by {}:", by).bold());
+    println!(
+        "  {:<width$}  {:>6}  {:>6}  {:>8}  {:>7}  {:>7}  {:>9}  {:>7}  Largest file",
+        by, "Files", "Tests", "Symbols", "Types", "Funcs", "Refs", "Refs/KB",
+        width = width
+    );
+    for a in &areas {
+        let types: i64 = ["class", "interface", "object", "enum"].iter().filter_map(|k| a.kinds.get(*k)).sum();
+        let functions = a.kinds.get("function").copied().unwrap_or(0);
+        let largest = a.largest_files.first().map(|f| f.path.as_str()).unwrap_or("");
+        println!(
+            "  {:<width$}  {:>6}  {:>5.0}%  {:>8}  {:>7}  {:>7}  {:>9}  {:>7.1}  {}",
+            a.area, a.files, a.test_ratio * 100.0, a.symbols, types, functions, a.refs, a.refs_per_kb, largest.dimmed(),
+            width = width
+        );
+    }
+    if areas.is_empty() {
+        println!("  No files indexed.");
+    }
+    Ok(())
+}
+
 /// Add an extra source root
 pub fn cmd_add_root(root: &Path, path: &str, force: bool) -> Result<()> {
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
@@ -592,7 +971,7 @@ pub fn cmd_remove_root(root: &Path, path: &str) -> Result<()> {
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
@@ -618,7 +997,7 @@ pub fn cmd_list_roots(root: &Path) -> Result<()> {
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
@@ -705,48 +1084,271 @@ pub fn cmd_db_path(root: &Path) -> Result<()> {
     Ok(())
 }
 
-/// Show database schema (tables and columns)
-pub fn cmd_schema(root: &Path) -> Result<()> {
-    let conn = db::open_db(root)?;
+/// Show database schema with column descriptions and example queries.
+/// `format` is `json` (default), `sql` or `markdown`; works without an index by describing a fresh schema.
+pub fn cmd_schema(root: &Path, format: &str) -> Result<()> {
+    let indexed = db::db_exists(root);
+    let conn = if indexed {
+        db::open_db(root)?
+    } else {
+        let conn = rusqlite::Connection::open_in_memory()?;
+        db::init_db(&conn)?;
+        conn
+    };
 
     let mut stmt = conn.prepare(
-        "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' AND name NOT LIKE '%_fts%' ORDER BY name"
+        "SELECT name, sql FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' AND name NOT LIKE '%_fts%' ORDER BY name"
     )?;
-
-    let tables: Vec<String> = stmt.query_map([], |row| row.get(0))?
+    let tables: Vec<(String, String)> = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
         .filter_map(|r| r.ok())
         .collect();
+    let table_doc = |name: &str| db::SCHEMA_DOCS.iter().find(|d| d.name == name);
+    let column_doc = |table: &str, column: &str| {
+        table_doc(table)
+            .and_then(|d| d.columns.iter().find(|(c, _)| *c == column))
+            .map(|(_, desc)| *desc)
+            .unwrap_or("")
+    };
 
-    let mut schema = serde_json::Map::new();
-
-    for table in &tables {
-        let mut cols_stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
-        let columns: Vec<serde_json::Value> = cols_stmt.query_map([], |row| {
-            let name: String = row.get(1)?;
-            let col_type: String = row.get(2)?;
-            let not_null: bool = row.get(3)?;
-            let pk: bool = row.get(5)?;
-            Ok(serde_json::json!({
-                "name": name,
-                "type": col_type,
-                "not_null": not_null,
-                "primary_key": pk,
-            }))
-        })?.filter_map(|r| r.ok()).collect();
-
-        // Get row count
-        let count: i64 = conn.query_row(
-            &format!("SELECT COUNT(*) FROM {}", table),
-            [],
-            |row| row.get(0),
-        ).unwrap_or(0);
+    match format {
+        "sql" => {
+            for (table, sql) in &tables {
+                if let Some(doc) = table_doc(table) {
+                    println!("-- {}: {}", table, doc.description);
+                    for (column, desc) in doc.columns {
+                        println!("--   {}: {}", column, desc);
+                    }
+                }
+                println!("{};", sql.trim());
+                let mut idx_stmt = conn.prepare(
+                    "SELECT sql FROM sqlite_master WHERE type IN ('index', 'trigger') AND tbl_name = ?1 AND sql IS NOT NULL ORDER BY name"
+                )?;
+                for index_sql in idx_stmt.query_map([table], |row| row.get::<_, String>(0))? {
+                    println!("{};", index_sql?.trim());
+                }
+                for example in table_doc(table).map(|d| d.examples).unwrap_or_default() {
+                    println!("-- Example: {};", example);
+                }
+                println!();
+            }
+        }
+        "markdown" => {
+            println!("# ast-index database schema\n");
+            for (table, _) in &tables {
+                println!("## {}\n", table);
+                if let Some(doc) = table_doc(table) {
+                    println!("{}\n", doc.description);
+                }
+                println!("| Column | Type | Description |");
+                println!("|--------|------|-------------|");
+                let mut cols_stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+                for col in cols_stmt.query_map([], |row| Ok((row.get::<_, String>(1)?, row.get::<_, String>(2)?)))? {
+                    let (name, col_type) = col?;
+                    println!("| `{}` | {} | {} |", name, col_type, column_doc(table, &name));
+                }
+                println!();
+                for example in table_doc(table).map(|d| d.examples).unwrap_or_default() {
+                    println!("```sql\n{}\n```\n", example);
+                }
+            }
+        }
+        "json" | "text" => {
+            let mut schema = serde_json::Map::new();
+            for (table, _) in &tables {
+                let mut cols_stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+                let columns: Vec<serde_json::Value> = cols_stmt.query_map([], |row| {
+                    let name: String = row.get(1)?;
+                    let col_type: String = row.get(2)?;
+                    let not_null: bool = row.get(3)?;
+                    let pk: bool = row.get(5)?;
+                    Ok(serde_json::json!({
+                        "name": name,
+                        "type": col_type,
+                        "not_null": not_null,
+                        "primary_key": pk,
+                        "description": column_doc(table, &name),
+                    }))
+                })?.filter_map(|r| r.ok()).collect();
+
+                // Get row count
+                let count: i64 = conn.query_row(
+                    &format!("SELECT COUNT(*) FROM {}", table),
+                    [],
+                    |row| row.get(0),
+                ).unwrap_or(0);
+
+                schema.insert(table.clone(), serde_json::json!({
+                    "description": table_doc(table).map(|d| d.description).unwrap_or(""),
+                    "columns": columns,
+                    "row_count": count,
+                    "examples": table_doc(table).map(|d| d.examples).unwrap_or_default(),
+                }));
+            }
+            println!("{}", serde_json::to_string_pretty(&serde_json::Value::Object(schema))?);
+        }
+        other => anyhow::bail!("Unknown schema format '{}': expected json, sql or markdown", other),
+    }
+    Ok(())
+}
 
-        schema.insert(table.clone(), serde_json::json!({
-            "columns": columns,
-            "row_count": count,
-        }));
+#[derive(Serialize)]
+struct MigrationReport {
+    version: u32,
+    latest: u32,
+    pending: Vec<&'static db::migrations::Migration>,
+    applied: bool,
+}
+
+/// Apply pending schema migrations (normally done on open), or list them with `dry_run`
+pub fn cmd_migrate(root: &Path, dry_run: bool, format: &str) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let conn = db::open_db_unmigrated(root)?;
+    let version = db::migrations::current_version(&conn)?;
+    let latest = db::migrations::latest_version();
+    let pending = db::migrations::pending(&conn)?;
+    let applied = !dry_run && !pending.is_empty();
+    if applied {
+        db::migrations::migrate(&conn)?;
+    }
+
+    if format == "json" {
+        let report = MigrationReport { version, latest, pending, applied };
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
     }
 
-    println!("{}", serde_json::to_string_pretty(&serde_json::Value::Object(schema))?);
+    if version > latest {
+        println!(
+            "{}",
+            format!(
+                "Index schema version {} is newer than this ast-index supports ({}); run 'ast-index rebuild' if commands fail",
+                version, latest
+            )
+            .yellow()
+        );
+    } else if pending.is_empty() {
+        println!("{}", format!("Index schema is up to date (version {})", version).green());
+    } else {
+        let title = if dry_run { "Pending migrations" } else { "Applied migrations" };
+        println!("{}", format!("{} (version {} → {}):", title, version, latest).bold());
+        for migration in &pending {
+            println!("  {:>3}  {}", migration.version, migration.description);
+        }
+        if dry_run {
+            println!("
+{}", "Run 'ast-index migrate' or any command that opens the index to apply them".dimmed());
+        }
+    }
+
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+/// Size of the index database including its WAL
+fn db_size_with_wal(db_path: &Path) -> u64 {
+    ["db", "db-wal"]
+        .iter()
+        .filter_map(|ext| std::fs::metadata(db_path.with_extension(ext)).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Merge FTS segments, refresh planner statistics and VACUUM the index, reporting the size change
+pub fn cmd_optimize(root: &Path, format: &str) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let db_path = db::get_db_path(root)?;
+    let conn = db::open_db(root)?;
+    let size_before = db_size_with_wal(&db_path);
+    let pages_before = db::page_stats(&conn)?;
+    db::optimize(&conn)?;
+    let size_after = db_size_with_wal(&db_path);
+    let pages_after = db::page_stats(&conn)?;
+
+    if format == "json" {
+        let result = serde_json::json!({
+            "db_path": db_path.display().to_string(),
+            "size_before_bytes": size_before,
+            "size_after_bytes": size_after,
+            "pages_before": pages_before,
+            "pages_after": pages_after,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    let mb = |bytes: u64| bytes as f64 / 1024.0 / 1024.0;
+    let free_share = |p: db::PageStats| if p.pages > 0 { p.free_pages as f64 * 100.0 / p.pages as f64 } else { 0.0 };
+    println!("{}", t!("index-optimized").bold());
+    println!("  {}", t!("optimize-before", size = format!("{:.2}", mb(size_before)), free = format!("{:.0}", free_share(pages_before))));
+    println!("  {}", t!("optimize-after", size = format!("{:.2}", mb(size_after)), free = format!("{:.0}", free_share(pages_after))));
+    if size_before > size_after {
+        println!("  {}", t!("optimize-reclaimed", size = format!("{:.2}", mb(size_before - size_after))).green());
+    }
+    println!("  {}", t!("optimize-db-path", path = db_path.display().to_string()));
+
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+/// Show version; with `verbose`, also SQLite build info and enabled parsers
+pub fn cmd_version(verbose: bool, format: &str) -> Result<()> {
+    let version = env!("CARGO_PKG_VERSION");
+    if !verbose {
+        println!("ast-index v{}", version);
+        return Ok(());
+    }
+
+    let conn = rusqlite::Connection::open_in_memory()?;
+    let sqlite_source = if cfg!(feature = "bundled-sqlite") { "bundled" } else { "system" };
+    let fts5 = db::fts5_available(&conn);
+    let json1 = conn.query_row("SELECT json('{}')", [], |row| row.get::<_, String>(0)).is_ok();
+    let parsers: Vec<(&str, &str)> = parsers::FileType::ALL
+        .iter()
+        .filter_map(|ft| parsers::parser_backend(*ft).map(|backend| (ft.name(), backend)))
+        .collect();
+    let custom: Vec<&str> = parsers::custom::registered().iter().map(|p| p.name.as_str()).collect();
+
+    if format == "json" {
+        let output = serde_json::json!({
+            "version": version,
+            "sqlite": {
+                "version": rusqlite::version(),
+                "source": sqlite_source,
+                "fts5": fts5,
+                "json1": json1,
+            },
+            "tree_sitter": cfg!(feature = "tree-sitter"),
+            "parsers": parsers.iter().map(|(lang, backend)| serde_json::json!({"language": lang, "backend": backend})).collect::<Vec<_>>(),
+            "custom_parsers": custom,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    let yes_no = |b: bool| if b { "yes".green() } else { "no".red() };
+    println!("{}", format!("ast-index v{}", version).bold());
+    println!("  SQLite:      {} ({}, chosen at build time)", rusqlite::version(), sqlite_source);
+    println!("  FTS5:        {}", yes_no(fts5));
+    println!("  JSON1:       {}", yes_no(json1));
+    println!("  Tree-sitter: {}", yes_no(cfg!(feature = "tree-sitter")));
+    println!("  Parsers:");
+    for (lang, backend) in &parsers {
+        println!("    {:<12} {}", lang, backend.dimmed());
+    }
+    if !custom.is_empty() {
+        println!("  Custom parsers: {}", custom.join(", "));
+    }
     Ok(())
 }
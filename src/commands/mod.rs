@@ -9,6 +9,15 @@
 //! - android: Android-specific (resources, strings)
 //! - ios: iOS-specific commands
 //! - perl: Perl-specific commands
+//! - calls: Call graph (callers, callees)
+//! - graph: Dependency graph export (DOT, Mermaid)
+//! - api: Public API snapshots and diffs
+//! - rename: Rename preview (locations and conflicts)
+//! - owners: CODEOWNERS ownership of paths and symbols
+//! - history: Symbol history across git commits
+//! - proto: Proto declarations linked to their generated Kotlin/Java/Swift/Go code
+//! - di: Dependency injection providers and injection points of a type
+//! - serve: JSON HTTP API over a long-lived index connection
 
 pub mod grep;
 pub mod management;
@@ -20,14 +29,26 @@ pub mod ios;
 pub mod perl;
 pub mod watch;
 pub mod analysis;
+pub mod calls;
+pub mod graph;
+pub mod api;
+pub mod rename;
+pub mod owners;
+pub mod history;
+pub mod proto;
+pub mod di;
 pub mod project_info;
+pub mod export;
+pub mod fleet;
+pub mod serve;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 use anyhow::{Context, Result};
+use colored::Colorize;
 use crossbeam_channel as channel;
 use grep_regex::RegexMatcher;
 use grep_searcher::{SearcherBuilder, sinks::UTF8};
@@ -49,6 +70,15 @@ pub fn is_no_ignore_enabled(root: &Path) -> bool {
     false
 }
 
+/// Whether the index has references; otherwise tells the user that `command` needs them
+pub fn require_refs(conn: &rusqlite::Connection, command: &str) -> bool {
+    let indexed = db::refs_indexed(conn);
+    if !indexed {
+        println!("{}", crate::t!("no-refs-index", command = command).yellow());
+    }
+    indexed
+}
+
 /// Get number of available CPU cores
 pub fn num_cpus() -> usize {
     std::thread::available_parallelism()
@@ -64,6 +94,199 @@ pub fn relative_path(root: &Path, path: &Path) -> String {
         .to_string()
 }
 
+/// Groups indexed paths into project modules or directories, for per-area reports
+pub struct Areas {
+    /// (name, path) of project modules, longest path first so nested modules win
+    modules: Vec<(String, String)>,
+}
+
+impl Areas {
+    /// Group by the project modules in the index; files outside them fall back to directories
+    pub fn modules(conn: &rusqlite::Connection) -> Result<Areas> {
+        let mut stmt = conn.prepare("SELECT name, path FROM modules WHERE kind IS NULL")?;
+        let mut modules: Vec<(String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        modules.sort_by_key(|(_, path)| std::cmp::Reverse(path.len()));
+        Ok(Areas { modules })
+    }
+
+    /// Group by directory only
+    pub fn directories() -> Areas {
+        Areas { modules: Vec::new() }
+    }
+
+    /// Module containing `path`, else its directory cut to `depth` levels (`.` at the root)
+    pub fn area_of(&self, path: &str, depth: usize) -> String {
+        let module = self.modules.iter().find(|(_, dir)| {
+            path == dir || (path.starts_with(dir.as_str()) && path[dir.len()..].starts_with('/'))
+        });
+        if let Some((name, _)) = module {
+            return name.clone();
+        }
+        let dirs: Vec<&str> = path.split('/').collect();
+        let dirs = &dirs[..dirs.len() - 1];
+        if dirs.is_empty() {
+            ".".to_string()
+        } else {
+            dirs[..dirs.len().min(depth.max(1))].join("/")
+        }
+    }
+}
+
+/// Paging for query commands: `--limit` results starting at `--offset`, or where a `--cursor`
+/// from the previous page points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub offset: usize,
+    pub limit: usize,
+    /// `--offset` or `--cursor` was given: every match is fetched so the total can be reported
+    pub explicit: bool,
+}
+
+impl Page {
+    /// Fetch limit standing for "all matches"; small enough that callers may still multiply it
+    pub const ALL: usize = 1 << 32;
+
+    pub fn new(limit: usize, offset: Option<usize>, cursor: Option<&str>) -> Result<Self> {
+        let explicit = offset.is_some() || cursor.is_some();
+        let offset = match cursor {
+            Some(cursor) => cursor
+                .strip_prefix('p')
+                .and_then(|hex| usize::from_str_radix(hex, 16).ok())
+                .with_context(|| format!("Invalid --cursor '{}'", cursor))?,
+            None => offset.unwrap_or(0),
+        };
+        Ok(Page { offset, limit, explicit })
+    }
+
+    /// How many matches to fetch for results filtered or ranked after the query: all of them when
+    /// paging, so totals are exact; else one page
+    pub fn fetch_limit(&self) -> usize {
+        if self.explicit { Self::ALL } else { self.limit }
+    }
+
+    /// This page as a query window, for results the query returns in their final order: SQL skips
+    /// to the page and counts the total separately
+    pub fn window(&self) -> db::Window {
+        db::Window { offset: self.offset, limit: self.limit, count: self.explicit }
+    }
+
+    /// Cut this page out of `items`, the matches in order; returns how many there were
+    pub fn take<T>(&self, items: &mut Vec<T>) -> usize {
+        let total = items.len();
+        items.drain(..self.offset.min(total));
+        items.truncate(self.limit);
+        total
+    }
+
+    /// Opaque cursor for the page after this one, if `total` matches reach past it
+    pub fn next_cursor(&self, total: usize) -> Option<String> {
+        let next = self.offset + self.limit;
+        (self.limit > 0 && next < total).then(|| format!("p{:x}", next))
+    }
+
+    /// JSON output with paging details when paging: arrays become `{"items": [...], "page": {...}}`
+    /// and objects gain a `page` key. `totals` lists each result list's total; a single list's
+    /// total is reported as a number
+    pub fn wrap_json(&self, value: serde_json::Value, totals: &[(&str, usize)]) -> serde_json::Value {
+        if !self.explicit {
+            return value;
+        }
+        let most = totals.iter().map(|(_, total)| *total).max().unwrap_or(0);
+        let total = match totals {
+            [(_, total)] => serde_json::json!(total),
+            _ => serde_json::Value::Object(totals.iter().map(|(name, total)| (name.to_string(), (*total).into())).collect()),
+        };
+        let page = serde_json::json!({
+            "offset": self.offset,
+            "limit": self.limit,
+            "total": total,
+            "next_cursor": self.next_cursor(most),
+        });
+        match value {
+            serde_json::Value::Object(mut object) => {
+                object.insert("page".to_string(), page);
+                serde_json::Value::Object(object)
+            }
+            items => serde_json::json!({ "items": items, "page": page }),
+        }
+    }
+
+    /// When paging, note on stderr which results were shown out of `total` and how to get the next page
+    pub fn print_summary(&self, total: usize) {
+        if !self.explicit || total == 0 {
+            return;
+        }
+        let shown_to = (self.offset + self.limit).min(total);
+        let mut summary = if self.offset < shown_to {
+            format!("Results {}-{} of {}", self.offset + 1, shown_to, total)
+        } else {
+            format!("No results past {} (total {})", self.offset, total)
+        };
+        if let Some(cursor) = self.next_cursor(total) {
+            summary.push_str(&format!("; next page: --cursor {}", cursor));
+        }
+        eprintln!("{}", summary.dimmed());
+    }
+}
+
+/// Source lines around hits for `--context N`, reading each file once
+pub struct ContextLines<'a> {
+    root: &'a Path,
+    radius: usize,
+    files: HashMap<String, Vec<String>>,
+}
+
+impl<'a> ContextLines<'a> {
+    pub fn new(root: &'a Path, radius: usize) -> Self {
+        ContextLines { root, radius, files: HashMap::new() }
+    }
+
+    /// `(line number, text)` of the lines within the radius of 1-based `line`; empty when the file
+    /// cannot be read
+    pub fn around(&mut self, path: &str, line: usize) -> Vec<(usize, String)> {
+        let root = self.root;
+        let lines = self.files.entry(path.to_string()).or_insert_with(|| {
+            crate::encoding::read_file(&root.join(path))
+                .map(|(text, _)| text.lines().map(str::to_string).collect())
+                .unwrap_or_default()
+        });
+        let first = line.saturating_sub(self.radius).max(1);
+        let last = (line + self.radius).min(lines.len());
+        (first..=last).map(|n| (n, lines[n - 1].clone())).collect()
+    }
+
+    /// Print the lines around a hit the way ripgrep does: `12-` marks context, `13:` the hit line,
+    /// where `needle` is highlighted
+    pub fn print(&mut self, path: &str, line: usize, needle: &str, indent: &str) {
+        for (n, text) in self.around(path, line) {
+            if n == line {
+                println!("{}{}{}", indent, format!("{}:", n).green(), highlight(&text, needle));
+            } else {
+                println!("{}{}{}", indent, format!("{}-", n).dimmed(), text.dimmed());
+            }
+        }
+    }
+
+    /// JSON form of `around`: `[{"line": 12, "text": "..."}, ...]`
+    pub fn json(&mut self, path: &str, line: usize) -> serde_json::Value {
+        self.around(path, line)
+            .into_iter()
+            .map(|(n, text)| serde_json::json!({"line": n, "text": text}))
+            .collect()
+    }
+}
+
+/// `line` with every occurrence of `needle` (ignoring case) in bold red
+fn highlight(line: &str, needle: &str) -> String {
+    let re = match regex::Regex::new(&format!("(?i){}", regex::escape(needle))) {
+        Ok(re) if !needle.is_empty() => re,
+        _ => return line.to_string(),
+    };
+    re.replace_all(line, |caps: &regex::Captures| caps[0].red().bold().to_string()).into_owned()
+}
+
 /// Fast parallel file search using grep-searcher and ignore crates
 pub fn search_files<F>(root: &Path, pattern: &str, extensions: &[&str], mut handler: F) -> Result<()>
 where
@@ -174,7 +397,9 @@ where
     }
     let walker = wb.build_parallel();
 
-    let (tx, rx) = channel::bounded::<(Arc<Path>, usize, String)>(limit.max(1000));
+    // Drained only after the walk, so it must hold every match; the walk stops at `limit`
+    // matches, and an unbounded channel avoids preallocating for a paging fetch of all of them
+    let (tx, rx) = channel::unbounded::<(Arc<Path>, usize, String)>();
 
     let extensions: Arc<HashSet<String>> = Arc::new(
         extensions.iter().map(|s| s.to_string()).collect()
@@ -250,3 +475,101 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_new() {
+        assert_eq!(Page::new(20, None, None).unwrap(), Page { offset: 0, limit: 20, explicit: false });
+        assert_eq!(Page::new(20, Some(40), None).unwrap(), Page { offset: 40, limit: 20, explicit: true });
+        // Cursors are the next offset in hex
+        assert_eq!(Page::new(20, None, Some("p3c")).unwrap(), Page { offset: 60, limit: 20, explicit: true });
+        assert!(Page::new(20, None, Some("60")).is_err());
+        assert!(Page::new(20, None, Some("pzz")).is_err());
+    }
+
+    #[test]
+    fn test_page_take() {
+        let page = Page::new(2, Some(1), None).unwrap();
+        let mut items = vec!['a', 'b', 'c', 'd'];
+        assert_eq!(page.take(&mut items), 4);
+        assert_eq!(items, vec!['b', 'c']);
+
+        // Past the end: nothing left, the total still counts every match
+        let page = Page::new(2, Some(10), None).unwrap();
+        let mut items = vec!['a', 'b'];
+        assert_eq!(page.take(&mut items), 2);
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn test_page_next_cursor() {
+        let page = Page::new(10, Some(20), None).unwrap();
+        assert_eq!(page.next_cursor(31).as_deref(), Some("p1e"));
+        assert_eq!(Page::new(10, None, page.next_cursor(31).as_deref()).unwrap().offset, 30);
+        assert_eq!(page.next_cursor(30), None);
+        assert_eq!(Page::new(0, None, None).unwrap().next_cursor(5), None);
+    }
+
+    #[test]
+    fn test_page_window() {
+        assert_eq!(Page::new(5, None, None).unwrap().window(), db::Window::first(5));
+        assert_eq!(
+            Page::new(5, Some(10), None).unwrap().window(),
+            db::Window { offset: 10, limit: 5, count: true }
+        );
+    }
+
+    #[test]
+    fn test_require_refs() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        assert!(require_refs(&conn, "refs"));
+        db::set_refs_indexed(&conn, false).unwrap();
+        assert!(!require_refs(&conn, "refs"));
+    }
+
+    #[test]
+    fn test_context_lines_window() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("A.kt"), "one\ntwo\nthree\nfour\nfive\n").unwrap();
+        let mut lines = ContextLines::new(dir.path(), 1);
+        let numbers = |around: Vec<(usize, String)>| around.into_iter().map(|(n, _)| n).collect::<Vec<_>>();
+
+        assert_eq!(lines.around("A.kt", 3), vec![(2, "two".to_string()), (3, "three".to_string()), (4, "four".to_string())]);
+        // Clipped at the first and last line of the file
+        assert_eq!(numbers(lines.around("A.kt", 1)), vec![1, 2]);
+        assert_eq!(numbers(lines.around("A.kt", 5)), vec![4, 5]);
+        assert_eq!(numbers(ContextLines::new(dir.path(), 10).around("A.kt", 2)), vec![1, 2, 3, 4, 5]);
+        assert_eq!(numbers(ContextLines::new(dir.path(), 0).around("A.kt", 4)), vec![4]);
+        assert!(lines.around("Missing.kt", 1).is_empty());
+        // A stale line number past the end of the file keeps what is left of the window
+        assert_eq!(numbers(lines.around("A.kt", 6)), vec![5]);
+        assert!(lines.around("A.kt", 9).is_empty());
+
+        assert_eq!(lines.json("A.kt", 1), serde_json::json!([{"line": 1, "text": "one"}, {"line": 2, "text": "two"}]));
+    }
+
+    #[test]
+    fn test_page_wrap_json() {
+        let items = serde_json::json!([1, 2]);
+        // Without --offset/--cursor the output keeps its shape
+        assert_eq!(Page::new(2, None, None).unwrap().wrap_json(items.clone(), &[("usages", 5)]), items);
+
+        let page = Page::new(2, Some(0), None).unwrap();
+        assert_eq!(
+            page.wrap_json(items, &[("usages", 5)]),
+            serde_json::json!({
+                "items": [1, 2],
+                "page": {"offset": 0, "limit": 2, "total": 5, "next_cursor": "p2"},
+            })
+        );
+        // Objects gain a `page` key with a total per list; the cursor follows the longest list
+        let wrapped = page.wrap_json(serde_json::json!({"files": []}), &[("files", 0), ("symbols", 3)]);
+        assert_eq!(wrapped["files"], serde_json::json!([]));
+        assert_eq!(wrapped["page"]["total"], serde_json::json!({"files": 0, "symbols": 3}));
+        assert_eq!(wrapped["page"]["next_cursor"], "p2");
+    }
+}
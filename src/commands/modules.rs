@@ -18,6 +18,7 @@ use walkdir::WalkDir;
 
 use crate::db;
 use crate::indexer;
+use crate::t;
 
 /// Find modules by pattern
 pub fn cmd_module(root: &Path, pattern: &str, limit: usize) -> Result<()> {
@@ -26,7 +27,7 @@ pub fn cmd_module(root: &Path, pattern: &str, limit: usize) -> Result<()> {
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
@@ -51,7 +52,7 @@ pub fn cmd_module(root: &Path, pattern: &str, limit: usize) -> Result<()> {
         println!("  No modules found.");
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -62,7 +63,7 @@ pub fn cmd_deps(root: &Path, module: &str) -> Result<()> {
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
@@ -117,7 +118,7 @@ pub fn cmd_deps(root: &Path, module: &str) -> Result<()> {
         println!("  No dependencies found.");
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -128,7 +129,7 @@ pub fn cmd_dependents(root: &Path, module: &str) -> Result<()> {
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
@@ -183,7 +184,7 @@ pub fn cmd_dependents(root: &Path, module: &str) -> Result<()> {
         println!("  No dependents found.");
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -199,7 +200,7 @@ pub fn cmd_unused_deps(
     let start = Instant::now();
 
     if !db::db_exists(root) {
-        println!("{}", "Index not found. Run 'ast-index rebuild' first.".red());
+        println!("{}", t!("index-not-found").red());
         return Ok(());
     }
 
@@ -528,7 +529,7 @@ pub fn cmd_unused_deps(
         println!("  - Exported (api): {}", exported.len());
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -0,0 +1,144 @@
+//! Code ownership
+//!
+//! - owners: Owners of a path (with indexed files per owner for a directory), or of a symbol's
+//!   declarations together with the owners of the files that reference it, to size the
+//!   cross-team impact of changing it
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::codeowners::CodeOwners;
+use crate::db::{self, SearchResult, SearchScope};
+use crate::t;
+use super::Page;
+
+/// Files and references under one owner
+#[derive(Serialize)]
+struct OwnerImpact {
+    owner: String,
+    files: usize,
+    refs: usize,
+    /// Owns none of the declarations
+    external: bool,
+}
+
+#[derive(Serialize)]
+struct OwnedSymbol {
+    #[serde(flatten)]
+    symbol: SearchResult,
+    owners: Vec<String>,
+}
+
+pub fn cmd_owners(root: &Path, target: &str, format: &str) -> Result<()> {
+    let start = Instant::now();
+    let owners = CodeOwners::load(root)?;
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+    let conn = db::open_db(root)?;
+
+    let path = target.trim_start_matches("./").trim_end_matches('/');
+    if target.contains('/') || root.join(path).exists() {
+        path_owners(&conn, &owners, path, format)?;
+    } else {
+        symbol_owners(&conn, &owners, target, format)?;
+    }
+
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+/// Owners of `path`, and for a directory the indexed files below it per owner
+fn path_owners(conn: &rusqlite::Connection, owners: &CodeOwners, path: &str, format: &str) -> Result<()> {
+    let path_owners = owners.labels_of(path);
+    let prefix = format!("{}/", path);
+    let mut files: BTreeMap<String, usize> = BTreeMap::new();
+    for file in db::file_stats(conn)? {
+        if file.path.starts_with(&prefix) {
+            for owner in owners.labels_of(&file.path) {
+                *files.entry(owner).or_default() += 1;
+            }
+        }
+    }
+    let mut breakdown: Vec<(String, usize)> = files.into_iter().collect();
+    breakdown.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    if format == "json" {
+        let files: Vec<_> = breakdown.iter().map(|(owner, files)| serde_json::json!({ "owner": owner, "files": files })).collect();
+        let result = serde_json::json!({ "path": path, "owners": path_owners, "files": files });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    println!("{} {}", path.bold(), path_owners.join(" ").cyan());
+    if !breakdown.is_empty() {
+        println!("\n{}", "Indexed files by owner:".bold());
+        for (owner, count) in &breakdown {
+            println!("  {:>6}  {}", count, owner);
+        }
+    }
+    Ok(())
+}
+
+/// Owners of the declarations of `name` and of the files referencing it
+fn symbol_owners(conn: &rusqlite::Connection, owners: &CodeOwners, name: &str, format: &str) -> Result<()> {
+    let definitions: Vec<OwnedSymbol> = db::find_symbols_by_name_scoped(conn, name, None, Page::ALL, &SearchScope::none())?
+        .into_iter()
+        .filter(|s| s.kind != "import")
+        .map(|symbol| OwnedSymbol { owners: owners.labels_of(&symbol.path), symbol })
+        .collect();
+    let declaring: BTreeSet<&str> = definitions.iter().flat_map(|d| d.owners.iter().map(String::as_str)).collect();
+
+    let mut grouped: BTreeMap<String, (BTreeSet<String>, usize)> = BTreeMap::new();
+    for r in db::find_references(conn, name, Page::ALL)? {
+        for owner in owners.labels_of(&r.path) {
+            let (files, refs) = grouped.entry(owner).or_default();
+            files.insert(r.path.clone());
+            *refs += r.count as usize;
+        }
+    }
+    let mut impact: Vec<OwnerImpact> = grouped
+        .into_iter()
+        .map(|(owner, (files, refs))| OwnerImpact { external: !declaring.contains(owner.as_str()), owner, files: files.len(), refs })
+        .collect();
+    impact.sort_by(|a, b| b.refs.cmp(&a.refs).then_with(|| a.owner.cmp(&b.owner)));
+    let external = impact.iter().filter(|i| i.external).count();
+
+    if format == "json" {
+        let result = serde_json::json!({
+            "symbol": name,
+            "definitions": definitions,
+            "external_owners": external,
+            "impact": impact,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    if definitions.is_empty() {
+        println!("{}", format!("'{}' is not declared in the index", name).yellow());
+    } else {
+        println!("{}", format!("Declarations of '{}':", name).bold());
+        for d in &definitions {
+            println!("  [{}] {}:{} {}", d.symbol.kind, d.symbol.path, d.symbol.line, d.owners.join(" ").cyan());
+        }
+    }
+    if impact.is_empty() {
+        println!("\nNo references.");
+        return Ok(());
+    }
+    println!("\n{}", format!("References by owner ({} other owners):", external).bold());
+    println!("  {:>6}  {:>6}  Owner", "Files", "Refs");
+    for i in &impact {
+        let owner = if i.external { i.owner.yellow() } else { i.owner.normal() };
+        println!("  {:>6}  {:>6}  {}", i.files, i.refs, owner);
+    }
+    Ok(())
+}
+
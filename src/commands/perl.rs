@@ -13,6 +13,7 @@ use std::time::Instant;
 use anyhow::Result;
 use colored::Colorize;
 
+use crate::t;
 use super::{search_files_limited, relative_path};
 
 /// Find Perl @EXPORT and @EXPORT_OK definitions
@@ -46,7 +47,7 @@ pub fn cmd_perl_exports(root: &Path, query: Option<&str>, limit: usize) -> Resul
         println!("    {}", content);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -81,7 +82,7 @@ pub fn cmd_perl_subs(root: &Path, query: Option<&str>, limit: usize) -> Result<(
         println!("    {}", content);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -117,7 +118,7 @@ pub fn cmd_perl_pod(root: &Path, query: Option<&str>, limit: usize) -> Result<()
         println!("    {}", content);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -154,7 +155,7 @@ pub fn cmd_perl_tests(root: &Path, query: Option<&str>, limit: usize) -> Result<
         println!("    {}", content);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -202,6 +203,6 @@ pub fn cmd_perl_imports(root: &Path, query: Option<&str>, limit: usize) -> Resul
         println!("    {}", content);
     }
 
-    eprintln!("\n{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
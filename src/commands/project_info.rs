@@ -13,6 +13,7 @@ use rusqlite::params;
 use serde::Serialize;
 
 use crate::db;
+use crate::t;
 
 // ── map ──────────────────────────────────────────────────────────────
 
@@ -193,7 +194,7 @@ pub fn cmd_map(
     let start = Instant::now();
 
     if !db::db_exists(root) {
-        println!("{}", "Index not found. Run 'ast-index rebuild' first.".red());
+        println!("{}", t!("index-not-found").red());
         return Ok(());
     }
 
@@ -209,7 +210,7 @@ pub fn cmd_map(
         cmd_map_summary(&conn, &project_type, &stats, limit, depth, format)?;
     }
 
-    eprintln!("{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
 
@@ -405,7 +406,7 @@ fn cmd_map_detailed(
             FROM inheritance i
             JOIN symbols s ON i.child_id = s.id
             JOIN files f ON s.file_id = f.id
-            WHERE s.parent_id IS NULL AND f.path LIKE ?1
+            WHERE s.parent_id IS NULL AND i.kind != 'permits' AND f.path LIKE ?1
             ORDER BY s.name
             "#
         } else {
@@ -413,7 +414,7 @@ fn cmd_map_detailed(
             SELECT DISTINCT s.name, i.parent_name
             FROM inheritance i
             JOIN symbols s ON i.child_id = s.id
-            WHERE s.parent_id IS NULL
+            WHERE s.parent_id IS NULL AND i.kind != 'permits'
             ORDER BY s.name
             "#
         };
@@ -674,7 +675,7 @@ pub fn cmd_conventions(root: &Path, format: &str) -> Result<()> {
     let start = Instant::now();
 
     if !db::db_exists(root) {
-        println!("{}", "Index not found. Run 'ast-index rebuild' first.".red());
+        println!("{}", t!("index-not-found").red());
         return Ok(());
     }
 
@@ -814,6 +815,6 @@ pub fn cmd_conventions(root: &Path, format: &str) -> Result<()> {
         println!();
     }
 
-    eprintln!("{}", format!("Time: {:?}", start.elapsed()).dimmed());
+    eprintln!("{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
     Ok(())
 }
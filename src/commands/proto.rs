@@ -0,0 +1,74 @@
+//! Protocol Buffers
+//!
+//! - proto-usages: Where the Kotlin, Java, Swift and Go code generated from a proto message,
+//!   enum or service is used, per platform
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::db;
+use crate::proto;
+use crate::t;
+
+/// References shown per platform in text output
+const SHOWN_REFS: usize = 50;
+
+pub fn cmd_proto_usages(root: &Path, name: &str, format: &str) -> Result<()> {
+    let start = Instant::now();
+
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let conn = db::open_db(root)?;
+    let declarations = proto::find_declarations(&conn, name)?;
+    let mut linked = Vec::new();
+    for declaration in declarations {
+        let usages = proto::find_usages(&conn, &declaration)?;
+        linked.push((declaration, usages));
+    }
+
+    if format == "json" {
+        let result: Vec<serde_json::Value> = linked
+            .iter()
+            .map(|(declaration, usages)| serde_json::json!({ "declaration": declaration, "platforms": usages }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    if linked.is_empty() {
+        println!("{}", format!("No proto message, enum or service named '{}' in the index", name).yellow());
+    }
+    for (declaration, usages) in &linked {
+        println!("{}", format!("{} {} ({}:{})", declaration.kind.as_str(), declaration.name, declaration.path, declaration.line).bold());
+        for usage in usages {
+            let files: BTreeSet<&str> = usage.references.iter().map(|r| r.path.as_str()).collect();
+            let count: i64 = usage.references.iter().map(|r| r.count).sum();
+            let names: Vec<&str> = usage.names.iter().map(|n| n.name.as_str()).collect();
+            println!(
+                "\n  {} {}",
+                usage.language.cyan(),
+                format!("({} refs in {} files; {})", count, files.len(), names.join(", ")).dimmed()
+            );
+            for g in &usage.generated {
+                println!("    generated {} {}", g.name, format!("{}:{}", g.path, g.line).dimmed());
+            }
+            for r in usage.references.iter().take(SHOWN_REFS) {
+                println!("    {}:{} {} {}", r.path, r.line, r.name.yellow(), r.aggregate_note().unwrap_or_default().dimmed());
+            }
+            if usage.references.len() > SHOWN_REFS {
+                println!("    {}", format!("... and {} more", usage.references.len() - SHOWN_REFS).dimmed());
+            }
+        }
+        println!();
+    }
+
+    eprintln!("{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
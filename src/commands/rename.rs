@@ -0,0 +1,156 @@
+//! Rename preview
+//!
+//! - rename-preview: Every location a rename would touch (definitions, imports, refs including
+//!   string refs, layout and storyboard usages), and declarations the new name would clash with
+//!
+//! The refs table leaves out names used in the file that declares them, so the declaring files
+//! are also scanned for the old name as a whole word; those hits are listed as `text` and include
+//! comments.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use anyhow::Result;
+use colored::Colorize;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::db::{self, SearchResult, SearchScope};
+use crate::t;
+use super::Page;
+
+static IDENTIFIER_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[\p{L}_][\p{L}\p{N}_]*$").unwrap());
+
+/// One place to edit
+#[derive(Serialize)]
+struct Location {
+    path: String,
+    line: i64,
+    /// definition, import, a ref kind (call, type, string, ...), xml, storyboard or text
+    kind: String,
+    context: Option<String>,
+}
+
+/// An existing declaration of the new name
+#[derive(Serialize)]
+struct Conflict {
+    #[serde(flatten)]
+    symbol: SearchResult,
+    /// Declared next to one of the renamed definitions (same container or package), so the
+    /// rename would collide rather than shadow
+    same_scope: bool,
+}
+
+pub fn cmd_rename_preview(root: &Path, old: &str, new: &str, format: &str) -> Result<()> {
+    let start = Instant::now();
+    if !IDENTIFIER_RE.is_match(new) {
+        anyhow::bail!("'{}' is not a valid identifier", new);
+    }
+    if old == new {
+        anyhow::bail!("The old and new names are the same");
+    }
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+    let conn = db::open_db(root)?;
+    if !super::require_refs(&conn, "rename-preview") {
+        return Ok(());
+    }
+    let scope = SearchScope::none();
+
+    let definitions: Vec<SearchResult> = db::find_symbols_by_name_scoped(&conn, old, None, Page::ALL, &scope)?
+        .into_iter()
+        .filter(|s| s.kind != "import")
+        .collect();
+    let mut locations: Vec<Location> = Vec::new();
+    for s in &definitions {
+        locations.push(Location { path: s.path.clone(), line: s.line, kind: "definition".to_string(), context: s.signature.clone() });
+    }
+    for s in db::find_imports(&conn, old, Page::ALL)? {
+        locations.push(Location { path: s.path, line: s.line, kind: "import".to_string(), context: s.signature });
+    }
+    // Files where aggregated refs rows stand for several lines are scanned like declaring files
+    let mut scanned: BTreeSet<String> = definitions.iter().map(|s| s.path.clone()).collect();
+    for r in db::find_references_of_kind(&conn, old, None, None, Page::ALL, &scope)? {
+        if r.count > 1 {
+            scanned.insert(r.path.clone());
+        }
+        let kind = r.ref_kind.unwrap_or_else(|| "ref".to_string());
+        locations.push(Location { path: r.path, line: r.line, kind, context: r.context });
+    }
+    for u in db::resource_usages(&conn, old)? {
+        locations.push(Location { path: u.path, line: u.line, kind: u.source.to_string(), context: u.usage_type });
+    }
+    // Same-file usages are not in the refs table
+    let word = Regex::new(&format!(r"\b{}\b", regex::escape(old)))?;
+    for path in &scanned {
+        let Ok((content, _)) = crate::encoding::read_file(&root.join(path)) else { continue };
+        for (idx, text) in content.lines().enumerate() {
+            if word.is_match(text) {
+                locations.push(Location { path: path.clone(), line: idx as i64 + 1, kind: "text".to_string(), context: Some(text.trim().to_string()) });
+            }
+        }
+    }
+    // One entry per line, the most specific kind first
+    locations.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+    locations.dedup_by(|later, earlier| later.path == earlier.path && later.line == earlier.line);
+    let files: BTreeSet<&str> = locations.iter().map(|l| l.path.as_str()).collect();
+    let file_count = files.len();
+
+    let renamed_scopes: BTreeSet<String> = definitions.iter().map(declaration_scope).collect();
+    let conflicts: Vec<Conflict> = db::find_symbols_by_name_scoped(&conn, new, None, Page::ALL, &scope)?
+        .into_iter()
+        .filter(|s| s.kind != "import")
+        .map(|symbol| Conflict { same_scope: renamed_scopes.contains(&declaration_scope(&symbol)), symbol })
+        .collect();
+
+    if format == "json" {
+        let result = serde_json::json!({
+            "old": old,
+            "new": new,
+            "files": file_count,
+            "conflicts": conflicts,
+            "locations": locations,
+        });
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    println!("{}", format!("Rename '{}' → '{}': {} locations in {} files", old, new, locations.len(), file_count).bold());
+    if definitions.is_empty() {
+        println!("  {}", format!("'{}' is not declared in the index", old).yellow());
+    }
+    if !conflicts.is_empty() {
+        println!("\n{}", format!("'{}' already exists ({}):", new, conflicts.len()).red());
+        for c in &conflicts {
+            let clash = if c.same_scope { " (same scope)".red() } else { "".normal() };
+            println!("  [{}] {}:{} {}{}", c.symbol.kind, c.symbol.path, c.symbol.line, c.symbol.display_name(), clash);
+        }
+    }
+    let mut current = "";
+    for l in &locations {
+        if l.path != current {
+            println!("\n{}", l.path.cyan());
+            current = &l.path;
+        }
+        println!("  {:>5}  {:<13} {}", l.line, l.kind, l.context.as_deref().unwrap_or("").dimmed());
+    }
+
+    eprintln!("\n{}", t!("time", elapsed = format!("{:?}", start.elapsed())).dimmed());
+    Ok(())
+}
+
+/// Container or package a declaration lives in: its qualified name without the last part,
+/// else its container, else its directory
+fn declaration_scope(s: &SearchResult) -> String {
+    if let Some((scope, _)) = s.qualified_name.as_deref().and_then(|q| q.rsplit_once('.')) {
+        return scope.to_string();
+    }
+    match &s.container {
+        Some(container) => container.clone(),
+        None => s.path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("").to_string(),
+    }
+}
@@ -0,0 +1,366 @@
+//! Index server
+//!
+//! - serve: Answer search, refs, def and stats queries as JSON over HTTP from one long-lived
+//!   connection, so editors and bots skip process startup and reuse prepared statements
+//!
+//! Each client sends its request head to a thread of its own, within a size limit and a deadline;
+//! a few workers, each with its own connection, then answer the requests, so a slow client holds
+//! up nobody else. `watch` or `update` in another process keeps the index current, since readers
+//! see each committed write.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use colored::Colorize;
+use crossbeam_channel as channel;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+
+use crate::db::{self, SearchScope};
+use crate::t;
+
+/// Results per request unless `limit` is given, and the most a request can ask for
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 1000;
+/// Prepared statements kept on each connection between requests
+const STATEMENT_CACHE: usize = 64;
+/// Threads answering requests, each on its own connection, and read requests waiting for one
+const WORKERS: usize = 4;
+const QUEUED_REQUESTS: usize = 64;
+/// Clients sending their request head at the same time; more are turned away with 503
+const MAX_PENDING_CLIENTS: usize = 256;
+/// Longest request line plus headers accepted from a client
+const MAX_HEAD_BYTES: u64 = 8192;
+/// Time a client has to send its request head, and to take the response
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A client whose request head has been read; `line` is None if the head was too large
+struct Request {
+    stream: TcpStream,
+    line: Option<String>,
+    start: Instant,
+}
+
+pub fn cmd_serve(root: &Path, host: &str, port: u16) -> Result<()> {
+    if !db::db_exists(root) {
+        println!("{}", t!("index-not-found").red());
+        return Ok(());
+    }
+
+    let (sender, receiver) = channel::bounded::<Request>(QUEUED_REQUESTS);
+    for _ in 0..WORKERS {
+        let conn = db::open_db(root)?;
+        conn.set_prepared_statement_cache_capacity(STATEMENT_CACHE);
+        let receiver = receiver.clone();
+        std::thread::spawn(move || {
+            for request in receiver {
+                if let Err(e) = answer(&conn, request) {
+                    eprintln!("{}", format!("Request failed: {}", e).yellow());
+                }
+            }
+        });
+    }
+    let listener = TcpListener::bind((host, port))?;
+    println!(
+        "{}",
+        format!("Serving {} on http://{} (GET /search?q=, /refs?name=, /def?name=, /stats)", root.display(), listener.local_addr()?).bold()
+    );
+
+    // Heads are read on a thread per client, so a slow client never holds a worker
+    let pending = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("{}", format!("Connection failed: {}", e).yellow());
+                continue;
+            }
+        };
+        if pending.load(Ordering::Relaxed) >= MAX_PENDING_CLIENTS {
+            stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+            write_response(&stream, 503, &json!({ "error": "Too many clients; retry later" })).ok();
+            continue;
+        }
+        pending.fetch_add(1, Ordering::Relaxed);
+        let (sender, pending) = (sender.clone(), Arc::clone(&pending));
+        std::thread::spawn(move || {
+            let start = Instant::now();
+            let line = read_request_line(DeadlineReader { stream: &stream, deadline: start + REQUEST_TIMEOUT });
+            pending.fetch_sub(1, Ordering::Relaxed);
+            match line {
+                // A full queue turns the client away rather than parking this thread
+                Ok(line) => {
+                    if let Err(channel::TrySendError::Full(request)) = sender.try_send(Request { stream, line, start }) {
+                        request.stream.set_write_timeout(Some(REQUEST_TIMEOUT)).ok();
+                        write_response(&request.stream, 503, &json!({ "error": "Server busy; retry later" })).ok();
+                    }
+                }
+                Err(e) => eprintln!("{}", format!("Request failed: {}", e).yellow()),
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Reads `stream` until `deadline`, however the client spaces out its bytes
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "request not received in time"));
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        let mut stream = self.stream;
+        stream.read(buf)
+    }
+}
+
+/// Request line of the head read from `input`, skipping the headers; None if the head runs past
+/// `MAX_HEAD_BYTES`. A head the client ends without the blank line is served as it is
+fn read_request_line(input: impl Read) -> std::io::Result<Option<String>> {
+    let mut reader = BufReader::new(input.take(MAX_HEAD_BYTES));
+    let mut request_line = String::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        if !line.ends_with('\n') {
+            if reader.get_ref().limit() == 0 {
+                return Ok(None);
+            }
+            if request_line.is_empty() {
+                request_line = line;
+            }
+            break;
+        }
+        if request_line.is_empty() {
+            request_line = line.clone();
+        } else if line.trim().is_empty() {
+            break;
+        }
+    }
+    Ok(Some(request_line))
+}
+
+/// Answer a read request and close the connection
+fn answer(conn: &Connection, request: Request) -> Result<()> {
+    let mut parts = request.line.as_deref().unwrap_or_default().split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or("/"));
+    let (status, body) = if request.line.is_none() {
+        (431, json!({ "error": format!("The request head is over {} bytes", MAX_HEAD_BYTES) }))
+    } else if method == "GET" {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        match route(conn, path, &query_params(query)) {
+            Ok(reply) => reply,
+            Err(e) => (500, json!({ "error": e.to_string() })),
+        }
+    } else {
+        (405, json!({ "error": format!("{} is not supported; use GET", method) }))
+    };
+
+    request.stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    write_response(&request.stream, status, &body)?;
+    eprintln!("{}", format!("{} {} {} {:?}", method, target, status, request.start.elapsed()).dimmed());
+    Ok(())
+}
+
+fn write_response(mut stream: &TcpStream, status: u16, body: &Value) -> Result<()> {
+    let body = serde_json::to_string(body)?;
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Status and JSON body for a GET of `path`
+fn route(conn: &Connection, path: &str, params: &HashMap<String, String>) -> Result<(u16, Value)> {
+    let limit = match params.get("limit").map(|l| l.parse::<usize>()) {
+        Some(Ok(limit)) => limit.clamp(1, MAX_LIMIT),
+        Some(Err(_)) => return Ok((400, json!({ "error": "limit must be a number" }))),
+        None => DEFAULT_LIMIT,
+    };
+    let required = |name: &str| params.get(name).filter(|v| !v.is_empty());
+
+    Ok(match path {
+        "/search" => {
+            let Some(query) = required("q") else {
+                return Ok((400, json!({ "error": "missing q" })));
+            };
+            let symbols = db::search_symbols_scoped(conn, &format!("{}*", query), limit, &SearchScope::none())?;
+            (200, json!({ "query": query, "symbols": symbols }))
+        }
+        "/refs" => {
+            let Some(name) = required("name") else {
+                return Ok((400, json!({ "error": "missing name" })));
+            };
+            let refs = db::find_references(conn, name, limit)?;
+            (200, json!({ "name": name, "refs": refs }))
+        }
+        "/def" => {
+            let Some(name) = required("name") else {
+                return Ok((400, json!({ "error": "missing name" })));
+            };
+            // `from=path:line` ranks the definition visible from there first, like `def --from`
+            let from = params.get("from").map(|from| match from.rsplit_once(':') {
+                Some((file, line)) if line.parse::<usize>().is_ok() => (file, line.parse().ok()),
+                _ => (from.as_str(), None),
+            });
+            match db::find_best_definition(conn, name, from)? {
+                Some(def) => (200, serde_json::to_value(&def)?),
+                None => (404, json!({ "error": format!("Definition of '{}' not found", name) })),
+            }
+        }
+        "/stats" => {
+            let mut stats = serde_json::to_value(db::get_stats(conn)?)?;
+            stats["refs_indexed"] = db::refs_indexed(conn).into();
+            (200, stats)
+        }
+        _ => (404, json!({ "error": format!("Unknown endpoint {}; use /search, /refs, /def or /stats", path) })),
+    })
+}
+
+/// Decoded `key=value` pairs of a query string
+fn query_params(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes and `+` (space) of a URL component
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO files (id, path, mtime, size) VALUES (1, 'app/Repo.kt', 0, 0), (2, 'app/Main.kt', 0, 0);
+             INSERT INTO symbols (file_id, name, kind, line) VALUES (1, 'Repo', 'class', 3), (1, 'RepoCache', 'class', 9);
+             INSERT INTO refs (file_id, name, line, context) VALUES (2, 'Repo', 8, 'val repo = Repo()');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn get(conn: &Connection, target: &str) -> (u16, Value) {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        route(conn, path, &query_params(query)).unwrap()
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a+b%20c"), "a b c");
+        assert_eq!(percent_decode("%D0%9F%D1%80"), "Пр");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+        assert_eq!(percent_decode("%41"), "A");
+    }
+
+    #[test]
+    fn test_query_params() {
+        let params = query_params("q=user+repo&limit=5&&flag&from=app%2FMain.kt%3A8");
+        assert_eq!(params.len(), 4);
+        assert_eq!(params["q"], "user repo");
+        assert_eq!(params["limit"], "5");
+        assert_eq!(params["flag"], "");
+        assert_eq!(params["from"], "app/Main.kt:8");
+        assert!(query_params("").is_empty());
+    }
+
+    #[test]
+    fn test_route() {
+        let conn = fixture();
+
+        let (status, body) = get(&conn, "/search?q=Repo&limit=1");
+        assert_eq!((status, body["symbols"].as_array().unwrap().len()), (200, 1));
+        assert_eq!(get(&conn, "/search?q=Repo").1["symbols"].as_array().unwrap().len(), 2);
+        assert_eq!(get(&conn, "/search").0, 400);
+        assert_eq!(get(&conn, "/search?q=Repo&limit=many").0, 400);
+        // A star the client typed does not break the FTS query the prefix star is added to
+        assert_eq!(get(&conn, "/search?q=Repo*").1["symbols"].as_array().unwrap().len(), 2);
+        assert_eq!(get(&conn, "/search?q=*").0, 200);
+
+        let (status, body) = get(&conn, "/refs?name=Repo");
+        assert_eq!(status, 200);
+        assert_eq!(body["refs"][0]["path"], "app/Main.kt");
+        assert_eq!(body["refs"][0]["line"], 8);
+        assert_eq!(get(&conn, "/refs?name=").0, 400);
+
+        let (status, body) = get(&conn, "/def?name=Repo&from=app%2FMain.kt%3A8");
+        assert_eq!((status, body["path"].as_str()), (200, Some("app/Repo.kt")));
+        assert_eq!(get(&conn, "/def?name=Missing").0, 404);
+
+        let (status, body) = get(&conn, "/stats");
+        assert_eq!((status, body["refs_indexed"].as_bool()), (200, Some(true)));
+        assert_eq!(get(&conn, "/nope").0, 404);
+    }
+
+    #[test]
+    fn test_read_request_line() {
+        let head = "GET /stats HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        assert_eq!(read_request_line(head.as_bytes()).unwrap().as_deref(), Some("GET /stats HTTP/1.1\r\n"));
+        // A client that closes without the blank line is still answered
+        assert_eq!(read_request_line("GET /stats".as_bytes()).unwrap().as_deref(), Some("GET /stats"));
+
+        let huge = format!("GET /stats HTTP/1.1\r\nCookie: {}\r\n\r\n", "x".repeat(MAX_HEAD_BYTES as usize));
+        assert_eq!(read_request_line(huge.as_bytes()).unwrap(), None);
+        // The limit stops an endless header before it is buffered
+        assert_eq!(read_request_line(std::io::repeat(b'x')).unwrap(), None);
+    }
+}
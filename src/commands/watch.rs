@@ -1,6 +1,9 @@
 //! Watch mode — automatically update index on file changes
+//!
+//! Only the files named by the debounced events are re-indexed; removed files and directories
+//! are deleted from the index.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
@@ -9,17 +12,25 @@ use colored::Colorize;
 use notify::RecursiveMode;
 use notify_debouncer_mini::new_debouncer;
 
-use crate::{db, indexer, parsers};
+use crate::{db, indexer};
+use crate::t;
 
 /// Watch for file changes and incrementally update the index
 pub fn cmd_watch(root: &Path) -> Result<()> {
     if !db::db_exists(root) {
         println!(
             "{}",
-            "Index not found. Run 'ast-index rebuild' first.".red()
+            t!("index-not-found").red()
         );
         return Ok(());
     }
+    let conn = db::open_db(root)?;
+    if let Some(sha) = db::indexed_revision(&conn) {
+        println!("{}", crate::commands::management::revision_index_message(&sha).yellow());
+        return Ok(());
+    }
+    crate::commands::management::sync_content_fts_setting(&conn)?;
+    drop(conn);
 
     println!(
         "{}",
@@ -27,6 +38,7 @@ pub fn cmd_watch(root: &Path) -> Result<()> {
     );
     println!("{}", "Press Ctrl+C to stop.".dimmed());
 
+    let filter = indexer::PathFilter::new(indexer::index_config());
     let (tx, rx) = mpsc::channel();
 
     let mut debouncer = new_debouncer(Duration::from_millis(500), tx)?;
@@ -35,28 +47,12 @@ pub fn cmd_watch(root: &Path) -> Result<()> {
     loop {
         match rx.recv() {
             Ok(Ok(events)) => {
-                let changed: Vec<_> = events
+                // Same rules as rebuild and update: hidden and excluded directories, supported
+                // extensions and `[index] include`/`exclude`
+                let changed: Vec<PathBuf> = events
                     .iter()
-                    .filter(|e| {
-                        let path = &e.path;
-                        // Only process supported source files
-                        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                            if !parsers::is_supported_extension(ext) {
-                                return false;
-                            }
-                        } else {
-                            return false;
-                        }
-                        // Skip excluded directories
-                        !path.components().any(|c| {
-                            let s = c.as_os_str().to_str().unwrap_or("");
-                            matches!(
-                                s,
-                                "build" | "node_modules" | ".gradle" | ".git" | "target"
-                                    | ".idea" | "__pycache__" | ".dart_tool"
-                            )
-                        })
-                    })
+                    .filter(|e| !indexer::is_unwatched(root, &e.path, &filter))
+                    .map(|e| e.path.clone())
                     .collect();
 
                 if changed.is_empty() {
@@ -70,7 +66,7 @@ pub fn cmd_watch(root: &Path) -> Result<()> {
                     format!("Detected {} changed file(s), updating...", file_count).yellow()
                 );
 
-                match update_index(root) {
+                match update_index(root, &changed) {
                     Ok((updated, deleted)) => {
                         if updated > 0 || deleted > 0 {
                             eprintln!(
@@ -108,10 +104,7 @@ pub fn cmd_watch(root: &Path) -> Result<()> {
     Ok(())
 }
 
-fn update_index(root: &Path) -> Result<(usize, usize)> {
+fn update_index(root: &Path, paths: &[PathBuf]) -> Result<(usize, usize)> {
     let mut conn = db::open_db(root)?;
-    let (updated, changed, deleted) =
-        indexer::update_directory_incremental(&mut conn, root, false)?;
-    let _ = changed; // suppress unused
-    Ok((updated, deleted))
+    indexer::update_files(&mut conn, root, paths)
 }
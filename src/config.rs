@@ -0,0 +1,635 @@
+//! Project configuration loaded from `.ast-index.toml` in the project root
+//!
+//! Every section is optional; a missing file means default settings.
+//!
+//! ```toml
+//! locale = "ru"
+//! rails = true
+//! case_sensitive = true
+//!
+//! [[parsers]]
+//! name = "feature-flags"
+//! extensions = ["flags"]
+//!
+//! [[parsers.rules]]
+//! regex = '^\s*(?P<kind>flag|experiment)\s+(?P<name>[\w.]+)'
+//! kind = "constant"
+//! kinds = { experiment = "object" }
+//!
+//! [[generated]]
+//! derive = "Builder"
+//! symbols = ["class {type}Builder", "function {type}Builder.build"]
+//!
+//! [[generated]]
+//! macro = "DECLARE_HANDLER"
+//! symbols = ["function handle_{1}"]
+//!
+//! [embed]
+//! command = ["python3", "tools/embed.py"]
+//!
+//! [references]
+//! stoplist = ["Timber", "Log"]
+//! languages = { kotlin = ["Modifier"] }
+//!
+//! [search]
+//! stemming = true
+//! synonyms = { auth = ["authentication", "login"], msg = ["message"] }
+//!
+//! [entry_points]
+//! annotations = ["Test", "Provides", "GetMapping", "Scheduled"]
+//! functions = ["main", "handler"]
+//!
+//! [[architecture]]
+//! name = "UI talks to repositories, not the database"
+//! from = "app/ui/**"
+//! deny = ["app/db/**", "**/*Dao.kt"]
+//!
+//! [[naming]]
+//! kinds = ["property"]
+//! lang = ["kotlin"]
+//! signature = '\bconst\s+val\b'
+//! style = "SCREAMING_SNAKE_CASE"
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Config file name looked up in the project root
+pub const CONFIG_FILE: &str = ".ast-index.toml";
+
+/// Parsed `.ast-index.toml`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// UI language for CLI messages (`en`, `ru`); defaults to `LC_ALL`/`LC_MESSAGES`/`LANG`
+    pub locale: Option<String>,
+    /// Index Rails DSL declarations in Ruby files; detected from the Gemfile when unset
+    pub rails: Option<bool>,
+    /// Whether `search` matches letter case when neither `--case-sensitive` nor `--ignore-case`
+    /// is given; ignores case when unset
+    pub case_sensitive: Option<bool>,
+    /// User-defined regex parsers
+    pub parsers: Vec<CustomParserConfig>,
+    /// Symbols generated by Rust derives and C/C++/Objective-C macros; entries replace the
+    /// built-in rule for the same derive or macro
+    pub generated: Vec<GeneratedConfig>,
+    /// Local embedding model for `embed` and `search --semantic`; the built-in one when unset
+    pub embed: Option<EmbedConfig>,
+    /// Full-text search vocabulary
+    pub search: SearchConfig,
+    /// How the index is written
+    pub index: IndexConfig,
+    /// Noise control for reference extraction
+    pub references: ReferencesConfig,
+    /// Where `unused-symbols --reachability` starts
+    pub entry_points: EntryPointsConfig,
+    /// Layering rules checked by `check-arch`
+    pub architecture: Vec<ArchitectureRule>,
+    /// Naming rules checked by `lint-names`; the built-in ones when empty
+    pub naming: Vec<NamingRule>,
+}
+
+/// A user-defined parser for one or more file extensions
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomParserConfig {
+    /// Display name (used in error messages)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// File extensions without the dot
+    pub extensions: Vec<String>,
+    /// Symbol rules, applied to every line
+    pub rules: Vec<CustomRuleConfig>,
+}
+
+/// One symbol regex of a custom parser.
+/// Named groups: `name` (required), `kind` and `signature` (optional).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomRuleConfig {
+    pub regex: String,
+    /// Symbol kind when the regex has no `kind` group (or it is not mapped)
+    #[serde(default = "default_rule_kind")]
+    pub kind: String,
+    /// Maps values of the `kind` group to symbol kinds
+    #[serde(default)]
+    pub kinds: HashMap<String, String>,
+}
+
+/// Symbols one Rust derive or one macro invocation generates.
+/// Each symbol is `"<kind> <name>"`; `{type}` in a name is the deriving type, `{1}`, `{2}`, ...
+/// are the macro arguments, and `Owner.name` makes the symbol a member of `Owner`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GeneratedConfig {
+    /// Rust derive name (`Builder`, `Serialize`)
+    #[serde(default)]
+    pub derive: Option<String>,
+    /// Macro name (`G_DEFINE_TYPE`)
+    #[serde(default, rename = "macro")]
+    pub macro_name: Option<String>,
+    /// Generated symbols; empty disables a built-in rule
+    pub symbols: Vec<String>,
+}
+
+/// Embedding model run as a command: it reads a JSON array of texts on stdin and prints a JSON
+/// array of vectors, one per text
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmbedConfig {
+    /// Program and its arguments
+    pub command: Vec<String>,
+    /// Texts sent per run
+    #[serde(default = "default_embed_batch_size")]
+    pub batch_size: usize,
+}
+
+/// Team vocabulary for full-text search
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchConfig {
+    /// Words that find each other: `auth = ["authentication", "login"]` makes a search for any of
+    /// the three also match the other two
+    pub synonyms: HashMap<String, Vec<String>>,
+    /// Porter stemming in the full-text index, so `authenticate` also finds `authenticated`;
+    /// changing it rebuilds the index's FTS table on the next run
+    pub stemming: bool,
+}
+
+/// Index writing
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexConfig {
+    /// References written per `INSERT` statement during `rebuild`/`update`; 1 writes one
+    /// statement per reference
+    pub insert_batch_size: usize,
+    /// How references are stored
+    pub refs: RefsLayout,
+    /// Top-level directories whose references `rebuild` keeps in a database of their own
+    pub refs_shards: Vec<String>,
+    /// Files larger than this many bytes are recorded as skipped instead of parsed
+    pub max_file_size: u64,
+    /// Files larger than this many bytes are parsed for symbols only, without references
+    pub refs_max_file_size: u64,
+    /// Most source bytes parsed at once across threads; files parsed past it get symbols only
+    pub parse_memory_limit: u64,
+    /// Path globs (`src/**`) of the source files to index; all when empty
+    pub include: Vec<String>,
+    /// Path globs (`**/generated/**`, `**/*.pb.go`) of source files never indexed
+    pub exclude: Vec<String>,
+    /// Store source lines in an FTS5 table so content search queries the index instead of
+    /// reading the files; takes effect on the next `rebuild`
+    pub content_fts: bool,
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        IndexConfig {
+            insert_batch_size: 500,
+            refs: RefsLayout::Occurrences,
+            refs_shards: vec![],
+            max_file_size: 1_000_000,
+            refs_max_file_size: 250_000,
+            parse_memory_limit: 256 * 1024 * 1024,
+            include: vec![],
+            exclude: vec![],
+            content_fts: false,
+        }
+    }
+}
+
+/// Noise control for reference extraction; changes take effect for files written afterwards
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReferencesConfig {
+    /// Names never recorded as references, on top of the built-in keywords and common standard
+    /// library types
+    pub stoplist: Vec<String>,
+    /// Built-in stoplist names to record after all (`Result`, `List`)
+    pub keep: Vec<String>,
+    /// Extra stoplists by language name (`kotlin = ["Modifier"]`)
+    pub languages: HashMap<String, Vec<String>>,
+    /// Shortest called function name recorded, in characters; in Python, Ruby, Rust and Go the
+    /// shortest name of any reference
+    pub min_name_length: usize,
+    /// Lines longer than this many bytes (minified or generated code) yield no references
+    pub max_line_length: usize,
+    /// Longest line stored as a reference's context, in bytes; longer ones are cut
+    pub max_context_length: usize,
+}
+
+impl Default for ReferencesConfig {
+    fn default() -> Self {
+        ReferencesConfig {
+            stoplist: vec![],
+            keep: vec![],
+            languages: HashMap::new(),
+            min_name_length: 3,
+            max_line_length: 2000,
+            max_context_length: 500,
+        }
+    }
+}
+
+/// Layout of the `refs` table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RefsLayout {
+    /// A row per occurrence
+    #[default]
+    Occurrences,
+    /// A row per file, name, kind and arity with the number of occurrences and the first lines
+    Aggregated,
+    /// No references: `rebuild` indexes definitions only, like `rebuild --no-refs`
+    None,
+}
+
+/// Symbols the runtime or a framework uses without a reference in the code
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EntryPointsConfig {
+    /// Annotations on entry points (DI providers, tests, request handlers); replaces the
+    /// built-in list. Matched ignoring case and package (`pytest.fixture` for `fixture`)
+    pub annotations: Vec<String>,
+    /// Names of functions the runtime calls
+    pub functions: Vec<String>,
+    /// Classes named in `AndroidManifest.xml` files (activities, services, receivers, providers)
+    pub android_manifest: bool,
+    /// Everything declared in test files
+    pub tests: bool,
+}
+
+impl Default for EntryPointsConfig {
+    fn default() -> Self {
+        let annotations = [
+            "Test", "ParameterizedTest", "Before", "BeforeEach", "After", "AfterEach", "fixture",
+            "Provides", "Binds", "Inject", "Module", "Component", "HiltAndroidApp", "AndroidEntryPoint",
+            "HiltViewModel", "Preview", "SpringBootApplication", "Bean", "Configuration", "RestController",
+            "Controller", "RequestMapping", "GetMapping", "PostMapping", "PutMapping", "DeleteMapping",
+            "EventListener", "Scheduled", "JvmStatic", "IBAction", "objc", "main", "test", "tokio::main",
+            "no_mangle", "export_name",
+        ];
+        EntryPointsConfig {
+            annotations: annotations.iter().map(|a| a.to_string()).collect(),
+            functions: vec!["main".to_string()],
+            android_manifest: true,
+            tests: true,
+        }
+    }
+}
+
+/// A layering rule: files matching `from` must not depend on files matching `deny`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArchitectureRule {
+    /// Shown with violations; `<from> must not depend on <deny>` when unset
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Path glob of the depending files (`app/ui/**`)
+    pub from: String,
+    /// Path globs of files they must not depend on
+    pub deny: Vec<String>,
+}
+
+impl ArchitectureRule {
+    pub fn display_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| format!("{} must not depend on {}", self.from, self.deny.join(", ")))
+    }
+}
+
+/// A naming rule: declarations of `kinds` (narrowed by language, visibility and signature) must
+/// follow `style` and match `pattern`, whichever are given
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NamingRule {
+    /// Shown with violations; `<kinds> are <style>` when unset
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Symbol kinds the rule covers (`class`, `function`, ...)
+    pub kinds: Vec<String>,
+    /// Languages the rule covers; all when empty
+    #[serde(default)]
+    pub lang: Vec<String>,
+    /// Only declarations with this visibility (`public`, `private`, ...)
+    #[serde(default)]
+    pub visibility: Option<String>,
+    /// Regex the declaration's signature must match for the rule to apply (`\bconst\b`)
+    #[serde(default)]
+    pub signature: Option<String>,
+    #[serde(default)]
+    pub style: Option<NamingStyle>,
+    /// Regex names must match
+    #[serde(default)]
+    pub pattern: Option<String>,
+}
+
+/// Letter case convention of a name; leading underscores are ignored
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum NamingStyle {
+    #[serde(rename = "PascalCase")]
+    Pascal,
+    #[serde(rename = "camelCase")]
+    Camel,
+    #[serde(rename = "snake_case")]
+    Snake,
+    #[serde(rename = "SCREAMING_SNAKE_CASE")]
+    ScreamingSnake,
+}
+
+impl NamingStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NamingStyle::Pascal => "PascalCase",
+            NamingStyle::Camel => "camelCase",
+            NamingStyle::Snake => "snake_case",
+            NamingStyle::ScreamingSnake => "SCREAMING_SNAKE_CASE",
+        }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.trim_start_matches('_');
+        let Some(first) = name.chars().next() else {
+            return false;
+        };
+        let words_ok = |lower: bool| {
+            name.split('_').all(|word| {
+                !word.is_empty()
+                    && word.chars().all(|c| c.is_ascii_digit() || if lower { c.is_lowercase() } else { c.is_uppercase() })
+            })
+        };
+        match self {
+            NamingStyle::Pascal => first.is_uppercase() && name.chars().all(char::is_alphanumeric),
+            NamingStyle::Camel => first.is_lowercase() && name.chars().all(char::is_alphanumeric),
+            NamingStyle::Snake => first.is_lowercase() && words_ok(true),
+            NamingStyle::ScreamingSnake => first.is_uppercase() && words_ok(false),
+        }
+    }
+}
+
+impl NamingRule {
+    /// Rules used when `.ast-index.toml` declares none
+    pub fn defaults() -> Vec<NamingRule> {
+        let rule = |name: &str, kinds: &[&str], lang: &[&str], style: NamingStyle| NamingRule {
+            name: Some(name.to_string()),
+            kinds: kinds.iter().map(|k| k.to_string()).collect(),
+            lang: lang.iter().map(|l| l.to_string()).collect(),
+            visibility: None,
+            signature: None,
+            style: Some(style),
+            pattern: None,
+        };
+        let types = ["class", "interface", "object", "enum", "typealias"];
+        let type_languages = ["kotlin", "java", "swift", "scala", "csharp", "dart", "typescript", "rust", "python"];
+        vec![
+            rule("Types are PascalCase", &types, &type_languages, NamingStyle::Pascal),
+            NamingRule {
+                signature: Some(r"\bconst\s+val\b".to_string()),
+                ..rule("Kotlin constants are SCREAMING_SNAKE_CASE", &["property"], &["kotlin"], NamingStyle::ScreamingSnake)
+            },
+            NamingRule {
+                visibility: Some("public".to_string()),
+                ..rule("Exported Go functions are PascalCase", &["function"], &["go"], NamingStyle::Pascal)
+            },
+        ]
+    }
+
+    pub fn display_name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let expected: Vec<String> = self
+            .style
+            .map(|style| style.as_str().to_string())
+            .into_iter()
+            .chain(self.pattern.as_ref().map(|pattern| format!("match {}", pattern)))
+            .collect();
+        format!("{} are {}", self.kinds.join(", "), expected.join(" and "))
+    }
+}
+
+fn default_embed_batch_size() -> usize {
+    64
+}
+
+fn default_rule_kind() -> String {
+    "constant".to_string()
+}
+
+impl Config {
+    /// Load config from `<root>/.ast-index.toml`; missing file yields defaults
+    pub fn load(root: &Path) -> Result<Config> {
+        let path = root.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Config::parse(&content).with_context(|| format!("Invalid {}", path.display()))
+    }
+
+    /// Rules for `lint-names`: the configured ones, else the built-in ones
+    pub fn naming_rules(&self) -> Vec<NamingRule> {
+        if self.naming.is_empty() { NamingRule::defaults() } else { self.naming.clone() }
+    }
+
+    /// Parse config from TOML text
+    pub fn parse(content: &str) -> Result<Config> {
+        Ok(toml::from_str(content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config() {
+        let config = Config::parse("").unwrap();
+        assert!(config.parsers.is_empty());
+    }
+
+    #[test]
+    fn test_parse_custom_parsers() {
+        let config = Config::parse(r#"
+[[parsers]]
+name = "routes"
+extensions = ["routes"]
+
+[[parsers.rules]]
+regex = '^(?P<kind>GET|POST)\s+(?P<name>\S+)'
+kind = "function"
+kinds = { POST = "property" }
+"#).unwrap();
+        assert_eq!(config.parsers.len(), 1);
+        let parser = &config.parsers[0];
+        assert_eq!(parser.extensions, vec!["routes"]);
+        assert_eq!(parser.rules[0].kind, "function");
+        assert_eq!(parser.rules[0].kinds.get("POST").map(String::as_str), Some("property"));
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(Config::parse("locale = \"ru\"").unwrap().locale.as_deref(), Some("ru"));
+        assert!(Config::parse("").unwrap().locale.is_none());
+    }
+
+    #[test]
+    fn test_parse_rails() {
+        assert_eq!(Config::parse("rails = false").unwrap().rails, Some(false));
+        assert!(Config::parse("").unwrap().rails.is_none());
+    }
+
+    #[test]
+    fn test_parse_case_sensitive() {
+        assert_eq!(Config::parse("case_sensitive = true").unwrap().case_sensitive, Some(true));
+        assert!(Config::parse("").unwrap().case_sensitive.is_none());
+    }
+
+    #[test]
+    fn test_parse_entry_points() {
+        let defaults = Config::parse("").unwrap().entry_points;
+        assert!(defaults.annotations.iter().any(|a| a == "Provides"));
+        assert_eq!(defaults.functions, vec!["main"]);
+        let config = Config::parse("[entry_points]\nannotations = [\"Route\"]\ntests = false").unwrap();
+        assert_eq!(config.entry_points.annotations, vec!["Route"]);
+        assert_eq!(config.entry_points.functions, vec!["main"]);
+        assert!(config.entry_points.android_manifest);
+        assert!(!config.entry_points.tests);
+    }
+
+    #[test]
+    fn test_parse_architecture() {
+        assert!(Config::parse("").unwrap().architecture.is_empty());
+        let config = Config::parse(r#"
+[[architecture]]
+from = "ui/**"
+deny = ["db/**"]
+
+[[architecture]]
+name = "Domain stays pure"
+from = "domain/**"
+deny = ["ui/**", "db/**"]
+"#).unwrap();
+        assert_eq!(config.architecture.len(), 2);
+        assert_eq!(config.architecture[0].display_name(), "ui/** must not depend on db/**");
+        assert_eq!(config.architecture[1].display_name(), "Domain stays pure");
+        assert!(Config::parse("[[architecture]]\nfrom = \"ui/**\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_naming() {
+        let defaults = Config::parse("").unwrap().naming_rules();
+        assert!(defaults.iter().any(|r| r.lang == ["kotlin"] && r.style == Some(NamingStyle::ScreamingSnake)));
+        let config = Config::parse(r#"
+[[naming]]
+kinds = ["function"]
+lang = ["python"]
+style = "snake_case"
+
+[[naming]]
+kinds = ["class"]
+pattern = "(Impl|Fake)$"
+"#).unwrap();
+        let rules = config.naming_rules();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].display_name(), "function are snake_case");
+        assert_eq!(rules[1].display_name(), "class are match (Impl|Fake)$");
+        assert!(Config::parse("[[naming]]\nkinds = [\"class\"]\nstyle = \"Title Case\"").is_err());
+    }
+
+    #[test]
+    fn test_naming_styles() {
+        assert!(NamingStyle::Pascal.matches("HttpClient") && NamingStyle::Pascal.matches("ServeHTTP"));
+        assert!(!NamingStyle::Pascal.matches("httpClient") && !NamingStyle::Pascal.matches("Http_Client"));
+        assert!(NamingStyle::Camel.matches("loadUser2") && !NamingStyle::Camel.matches("load_user"));
+        assert!(NamingStyle::Snake.matches("_load_user") && !NamingStyle::Snake.matches("load__user"));
+        assert!(NamingStyle::ScreamingSnake.matches("MAX_RETRIES") && !NamingStyle::ScreamingSnake.matches("MaxRetries"));
+        assert!(!NamingStyle::Snake.matches("__"));
+    }
+
+    #[test]
+    fn test_parse_generated() {
+        let config = Config::parse(r#"
+[[generated]]
+derive = "Builder"
+symbols = ["class {type}Builder"]
+
+[[generated]]
+macro = "G_DEFINE_TYPE"
+symbols = []
+"#).unwrap();
+        assert_eq!(config.generated[0].derive.as_deref(), Some("Builder"));
+        assert_eq!(config.generated[1].macro_name.as_deref(), Some("G_DEFINE_TYPE"));
+        assert!(config.generated[1].symbols.is_empty());
+    }
+
+    #[test]
+    fn test_parse_embed() {
+        let config = Config::parse("[embed]\ncommand = [\"python3\", \"embed.py\"]").unwrap();
+        let embed = config.embed.unwrap();
+        assert_eq!(embed.command, vec!["python3", "embed.py"]);
+        assert_eq!(embed.batch_size, 64);
+        assert!(Config::parse("").unwrap().embed.is_none());
+    }
+
+    #[test]
+    fn test_parse_search() {
+        let config = Config::parse("[search]\nstemming = true\nsynonyms = { auth = [\"authentication\"] }").unwrap();
+        assert!(config.search.stemming);
+        assert_eq!(config.search.synonyms["auth"], vec!["authentication"]);
+        let config = Config::parse("").unwrap();
+        assert!(!config.search.stemming && config.search.synonyms.is_empty());
+    }
+
+    #[test]
+    fn test_parse_index() {
+        let config = Config::parse("[index]\ninsert_batch_size = 50\nrefs = \"aggregated\"").unwrap();
+        assert_eq!(config.index.insert_batch_size, 50);
+        assert_eq!(config.index.refs, RefsLayout::Aggregated);
+        let config = Config::parse("").unwrap();
+        assert_eq!((config.index.insert_batch_size, config.index.refs), (500, RefsLayout::Occurrences));
+        assert!(Config::parse("[index]\nrefs = \"grouped\"").is_err());
+        assert_eq!(Config::parse("[index]\nrefs = \"none\"").unwrap().index.refs, RefsLayout::None);
+        let config = Config::parse("[index]\nrefs_shards = [\"app\", \"services\"]").unwrap();
+        assert_eq!(config.index.refs_shards, vec!["app", "services"]);
+        assert_eq!(config.index.max_file_size, 1_000_000);
+        assert_eq!(Config::parse("[index]\nmax_file_size = 250000").unwrap().index.max_file_size, 250_000);
+        let config = Config::parse("[index]\ninclude = [\"src/**\"]\nexclude = [\"**/generated/**\"]").unwrap();
+        assert_eq!((config.index.include, config.index.exclude), (vec!["src/**".to_string()], vec!["**/generated/**".to_string()]));
+        assert!(!config.index.content_fts);
+        assert_eq!((config.index.refs_max_file_size, config.index.parse_memory_limit), (250_000, 256 * 1024 * 1024));
+        let config = Config::parse("[index]\nrefs_max_file_size = 300000\nparse_memory_limit = 64000000").unwrap();
+        assert_eq!((config.index.refs_max_file_size, config.index.parse_memory_limit), (300_000, 64_000_000));
+        assert!(Config::parse("[index]\ncontent_fts = true").unwrap().index.content_fts);
+    }
+
+    #[test]
+    fn test_parse_references() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.references.min_name_length, 3);
+        assert_eq!((config.references.max_line_length, config.references.max_context_length), (2000, 500));
+        let config = Config::parse(
+            "[references]\nstoplist = [\"Log\"]\nkeep = [\"Result\"]\nmin_name_length = 4\nlanguages = { kotlin = [\"Modifier\"] }",
+        )
+        .unwrap();
+        assert_eq!(config.references.stoplist, vec!["Log"]);
+        assert_eq!(config.references.keep, vec!["Result"]);
+        assert_eq!(config.references.min_name_length, 4);
+        assert_eq!(config.references.languages["kotlin"], vec!["Modifier"]);
+    }
+
+    #[test]
+    fn test_unknown_key_rejected() {
+        assert!(Config::parse("unknown_key = 1").is_err());
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert!(config.parsers.is_empty());
+    }
+}
@@ -2,10 +2,69 @@
 
 use anyhow::{Context, Result};
 use rusqlite::{params, Connection};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs::File;
 use std::path::{Path, PathBuf};
 
+use crate::config::SearchConfig;
+use crate::fuzzy;
+use crate::parsers::{self, signature};
+
+pub mod migrations;
+pub mod shards;
+
+/// `AST_INDEX_DB_PATH` value (or `--db`) that keeps the index in memory for the current process
+pub const MEMORY_DB_PATH: &str = ":memory:";
+
+/// URI of the in-memory index; the memdb VFS shares a database named with a leading `/` between
+/// the connections of one process
+const MEMORY_DB_URI: &str = "file:/ast-index.db?vfs=memdb";
+
+/// Connection that keeps the in-memory index alive until the process exits
+static MEMORY_DB: std::sync::OnceLock<std::sync::Mutex<Connection>> = std::sync::OnceLock::new();
+
+/// Whether the index lives in memory for this process (`--db :memory:` / `--ephemeral`)
+pub fn in_memory() -> bool {
+    std::env::var("AST_INDEX_DB_PATH").is_ok_and(|path| path == MEMORY_DB_PATH)
+}
+
+/// Open a connection to the index file, or to the in-memory index
+fn connect(db_path: &Path) -> Result<Connection> {
+    if db_path.as_os_str() != MEMORY_DB_PATH {
+        return Ok(Connection::open(db_path)?);
+    }
+    let open = || {
+        Connection::open_with_flags(MEMORY_DB_URI, rusqlite::OpenFlags::default() | rusqlite::OpenFlags::SQLITE_OPEN_URI)
+    };
+    if MEMORY_DB.get().is_none() {
+        let _ = MEMORY_DB.set(std::sync::Mutex::new(open()?));
+    }
+    Ok(open()?)
+}
+
+/// Write the in-memory index to `dest` (replacing it), e.g. to cache an index built in CI
+pub fn save_memory_db(dest: &Path) -> Result<()> {
+    if MEMORY_DB.get().is_none() {
+        anyhow::bail!("No in-memory index to save: --save-db needs --db {} (or --ephemeral)", MEMORY_DB_PATH);
+    }
+    if let Some(parent) = dest.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    for suffix in ["", "-wal", "-shm"] {
+        let mut path = dest.as_os_str().to_owned();
+        path.push(suffix);
+        if Path::new(&path).exists() {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    // VACUUM INTO writes through the VFS of the connection, so run it from one on the default VFS
+    let conn = Connection::open_in_memory_with_flags(rusqlite::OpenFlags::default() | rusqlite::OpenFlags::SQLITE_OPEN_URI)?;
+    conn.execute("ATTACH ?1 AS memory_index", params![MEMORY_DB_URI])?;
+    conn.execute("VACUUM memory_index INTO ?1", params![dest.to_string_lossy().as_ref()])?;
+    Ok(())
+}
+
 /// Get the database path for the current project
 pub fn get_db_path(project_root: &Path) -> Result<PathBuf> {
     // Check env: new name first, fallback to old
@@ -109,11 +168,15 @@ pub fn migrate_legacy_project(project_root: &Path) {
 
 /// Acquire an exclusive lock file for rebuild operations.
 /// Returns the lock file handle — lock is held until the handle is dropped.
-/// If another process holds the lock, returns an error immediately.
-pub fn acquire_rebuild_lock(project_root: &Path) -> Result<File> {
+/// If another process holds the lock, returns an error immediately; `None` for an in-memory
+/// index, which no other process can see.
+pub fn acquire_rebuild_lock(project_root: &Path) -> Result<Option<File>> {
     use fs2::FileExt;
 
     let db_path = get_db_path(project_root)?;
+    if db_path.as_os_str() == MEMORY_DB_PATH {
+        return Ok(None);
+    }
     let lock_path = db_path.with_extension("lock");
 
     // Ensure parent dir exists
@@ -124,21 +187,183 @@ pub fn acquire_rebuild_lock(project_root: &Path) -> Result<File> {
     let lock_file = File::create(&lock_path)?;
     lock_file.try_lock_exclusive()
         .map_err(|_| anyhow::anyhow!("Another rebuild is already running for this project. Wait for it to finish or remove {}", lock_path.display()))?;
-    Ok(lock_file)
+    Ok(Some(lock_file))
 }
 
 /// Delete DB file and WAL/SHM files for the project
 pub fn delete_db(project_root: &Path) -> Result<()> {
     let db_path = get_db_path(project_root)?;
+    if db_path.as_os_str() == MEMORY_DB_PATH {
+        if db_exists(project_root) {
+            clear_db(&connect(&db_path)?)?;
+        }
+        return Ok(());
+    }
     for suffix in ["", "-wal", "-shm"] {
         let p = db_path.with_extension(format!("db{}", suffix));
         if p.exists() {
             std::fs::remove_file(&p)?;
         }
     }
+    for p in shards::shard_files(&db_path) {
+        std::fs::remove_file(&p)?;
+    }
     Ok(())
 }
 
+/// Names each file imports, for resolving references; also created on open for older indexes
+const IMPORTS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS imports (
+        id INTEGER PRIMARY KEY,
+        file_id INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        path TEXT NOT NULL,
+        line INTEGER NOT NULL,
+        FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+    );
+    CREATE INDEX IF NOT EXISTS idx_imports_path ON imports(path);
+    CREATE INDEX IF NOT EXISTS idx_imports_file ON imports(file_id);
+"#;
+
+/// Distinct symbol names and their trigrams for `search --fuzzy`; also created on open for older indexes
+const NAME_TRIGRAMS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS symbol_names (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE IF NOT EXISTS name_trigrams (
+        trigram TEXT NOT NULL,
+        name_id INTEGER NOT NULL,
+        PRIMARY KEY (trigram, name_id)
+    ) WITHOUT ROWID;
+"#;
+
+/// Annotations/decorators/attributes per symbol; also created on open for older indexes
+const SYMBOL_ANNOTATIONS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS symbol_annotations (
+        id INTEGER PRIMARY KEY,
+        symbol_id INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        args TEXT,
+        FOREIGN KEY (symbol_id) REFERENCES symbols_data(id) ON DELETE CASCADE
+    );
+    CREATE INDEX IF NOT EXISTS idx_symbol_annotations_symbol ON symbol_annotations(symbol_id);
+    CREATE INDEX IF NOT EXISTS idx_symbol_annotations_name ON symbol_annotations(name);
+"#;
+
+/// Vectors computed by `ast-index embed` for `search --semantic`; also created on open for older indexes
+const EMBEDDINGS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS symbol_embeddings (
+        symbol_id INTEGER PRIMARY KEY,
+        vector BLOB NOT NULL,
+        FOREIGN KEY (symbol_id) REFERENCES symbols_data(id) ON DELETE CASCADE
+    );
+"#;
+
+/// Caller→callee edges for `callers`/`callees`; also created on open for older indexes (filled
+/// by the next rebuild)
+const CALLS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS calls (
+        id INTEGER PRIMARY KEY,
+        caller_id INTEGER NOT NULL,
+        callee TEXT NOT NULL,
+        line INTEGER NOT NULL,
+        FOREIGN KEY (caller_id) REFERENCES symbols_data(id) ON DELETE CASCADE
+    );
+    CREATE INDEX IF NOT EXISTS idx_calls_callee ON calls(callee);
+    CREATE INDEX IF NOT EXISTS idx_calls_caller ON calls(caller_id);
+"#;
+
+/// Types each file provides to or injects from a DI container for `di-graph`; also created on
+/// open for older indexes (filled by the next rebuild)
+const DI_BINDINGS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS di_bindings (
+        id INTEGER PRIMARY KEY,
+        file_id INTEGER NOT NULL,
+        type TEXT NOT NULL,
+        role TEXT NOT NULL,
+        mechanism TEXT NOT NULL,
+        symbol TEXT,
+        line INTEGER NOT NULL,
+        FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+    );
+    CREATE INDEX IF NOT EXISTS idx_di_bindings_type ON di_bindings(type);
+    CREATE INDEX IF NOT EXISTS idx_di_bindings_symbol ON di_bindings(symbol);
+    CREATE INDEX IF NOT EXISTS idx_di_bindings_file ON di_bindings(file_id);
+"#;
+
+/// Case-insensitive name index of indexes from before symbol names were interned
+const NAME_NOCASE_INDEX: &str = "CREATE INDEX IF NOT EXISTS idx_symbols_name_nocase ON symbols(name COLLATE NOCASE);";
+
+/// Name lookups on refs that also filter by file (`unused-symbols --lang/--no-tests`) read the file
+/// from the index; replaces the name-only index of older indexes
+const REFS_NAME_INDEX: &str = "
+    CREATE INDEX IF NOT EXISTS idx_refs_name_file ON refs(name, file_id);
+    DROP INDEX IF EXISTS idx_refs_name;
+";
+
+/// `refs` as a view over `refs_data`, whose names are ids into `names`; writes through the view
+/// intern the name
+const REFS_VIEW: &str = "
+    CREATE INDEX IF NOT EXISTS idx_refs_data_name_file ON refs_data(name_id, file_id);
+    CREATE INDEX IF NOT EXISTS idx_refs_data_file ON refs_data(file_id);
+    CREATE VIEW IF NOT EXISTS refs AS
+        SELECT r.id, r.file_id, n.text AS name, r.line, r.context, r.ref_kind, r.arity, r.count, r.sample_lines
+        FROM refs_data r JOIN names n ON n.id = r.name_id;
+    CREATE TRIGGER IF NOT EXISTS refs_insert INSTEAD OF INSERT ON refs BEGIN
+        INSERT OR IGNORE INTO names (text) VALUES (NEW.name);
+        INSERT INTO refs_data (id, file_id, name_id, line, context, ref_kind, arity, count, sample_lines)
+        VALUES (NEW.id, NEW.file_id, (SELECT id FROM names WHERE text = NEW.name), NEW.line, NEW.context,
+                NEW.ref_kind, NEW.arity, NEW.count, NEW.sample_lines);
+    END;
+    CREATE TRIGGER IF NOT EXISTS refs_delete INSTEAD OF DELETE ON refs BEGIN
+        DELETE FROM refs_data WHERE id = OLD.id;
+    END;
+";
+
+/// `symbols` as a view over `symbols_data`, whose names are ids into `names`, with writes through
+/// the view interning the name. Names are also indexed ignoring case for `ast-index complete`
+/// range scans
+const SYMBOLS_VIEW: &str = "
+    CREATE INDEX IF NOT EXISTS idx_symbols_data_name ON symbols_data(name_id);
+    CREATE INDEX IF NOT EXISTS idx_names_text_nocase ON names(text COLLATE NOCASE);
+    CREATE VIEW IF NOT EXISTS symbols AS
+        SELECT s.id, s.file_id, n.text AS name, s.kind, s.line, s.parent_id, s.signature, s.container,
+               s.qualified_name, s.doc, s.visibility, s.end_line, s.start_byte, s.end_byte, s.partial,
+               s.condition, s.arity, s.signature_hash, s.generated_by, s.humps, s.complexity
+        FROM symbols_data s JOIN names n ON n.id = s.name_id;
+    CREATE TRIGGER IF NOT EXISTS symbols_insert INSTEAD OF INSERT ON symbols BEGIN
+        INSERT OR IGNORE INTO names (text) VALUES (NEW.name);
+        INSERT INTO symbols_data (id, file_id, name_id, kind, line, parent_id, signature, container, qualified_name,
+                                  doc, visibility, end_line, start_byte, end_byte, partial, condition, arity,
+                                  signature_hash, generated_by, humps, complexity)
+        VALUES (NEW.id, NEW.file_id, (SELECT id FROM names WHERE text = NEW.name), NEW.kind, NEW.line, NEW.parent_id,
+                NEW.signature, NEW.container, NEW.qualified_name, NEW.doc, NEW.visibility, NEW.end_line,
+                NEW.start_byte, NEW.end_byte, NEW.partial, NEW.condition, NEW.arity, NEW.signature_hash,
+                NEW.generated_by, NEW.humps, NEW.complexity);
+    END;
+    CREATE TRIGGER IF NOT EXISTS symbols_update INSTEAD OF UPDATE ON symbols BEGIN
+        INSERT OR IGNORE INTO names (text) VALUES (NEW.name);
+        UPDATE symbols_data SET id = NEW.id, file_id = NEW.file_id, name_id = (SELECT id FROM names WHERE text = NEW.name),
+            kind = NEW.kind, line = NEW.line, parent_id = NEW.parent_id, signature = NEW.signature,
+            container = NEW.container, qualified_name = NEW.qualified_name, doc = NEW.doc,
+            visibility = NEW.visibility, end_line = NEW.end_line, start_byte = NEW.start_byte,
+            end_byte = NEW.end_byte, partial = NEW.partial, condition = NEW.condition, arity = NEW.arity,
+            signature_hash = NEW.signature_hash, generated_by = NEW.generated_by, humps = NEW.humps,
+            complexity = NEW.complexity
+        WHERE id = OLD.id;
+    END;
+    CREATE TRIGGER IF NOT EXISTS symbols_delete INSTEAD OF DELETE ON symbols BEGIN
+        DELETE FROM symbols_data WHERE id = OLD.id;
+    END;
+";
+
+/// Id of `name` in `names`, adding it if new
+pub fn intern_name(conn: &Connection, name: &str) -> Result<i64> {
+    conn.prepare_cached("INSERT OR IGNORE INTO names (text) VALUES (?1)")?.execute([name])?;
+    Ok(conn.prepare_cached("SELECT id FROM names WHERE text = ?1")?.query_row([name], |row| row.get(0))?)
+}
+
 /// Initialize the database schema
 pub fn init_db(conn: &Connection) -> Result<()> {
     conn.execute_batch(
@@ -148,44 +373,47 @@ pub fn init_db(conn: &Connection) -> Result<()> {
             id INTEGER PRIMARY KEY,
             path TEXT NOT NULL UNIQUE,
             mtime INTEGER NOT NULL,
-            size INTEGER NOT NULL
+            size INTEGER NOT NULL,
+            encoding TEXT,
+            language TEXT,
+            is_test INTEGER NOT NULL DEFAULT 0,
+            skipped TEXT
         );
         CREATE INDEX IF NOT EXISTS idx_files_path ON files(path);
 
-        -- Symbols table (classes, interfaces, functions, etc.)
-        CREATE TABLE IF NOT EXISTS symbols (
+        -- Identifiers declared by symbols or referenced by refs, stored once
+        CREATE TABLE IF NOT EXISTS names (
+            id INTEGER PRIMARY KEY,
+            text TEXT NOT NULL UNIQUE
+        );
+
+        -- Symbols table (classes, interfaces, functions, etc.), read through the symbols view
+        CREATE TABLE IF NOT EXISTS symbols_data (
             id INTEGER PRIMARY KEY,
             file_id INTEGER NOT NULL,
-            name TEXT NOT NULL,
+            name_id INTEGER NOT NULL,
             kind TEXT NOT NULL,
             line INTEGER NOT NULL,
             parent_id INTEGER,
             signature TEXT,
+            container TEXT,
+            qualified_name TEXT,
+            doc TEXT,
+            visibility TEXT,
+            end_line INTEGER,
+            start_byte INTEGER,
+            end_byte INTEGER,
+            partial INTEGER,
+            condition TEXT,
+            arity INTEGER,
+            signature_hash TEXT,
+            generated_by TEXT,
+            humps TEXT,
+            complexity INTEGER,
             FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
         );
-        CREATE INDEX IF NOT EXISTS idx_symbols_name ON symbols(name);
-        CREATE INDEX IF NOT EXISTS idx_symbols_kind ON symbols(kind);
-        CREATE INDEX IF NOT EXISTS idx_symbols_file ON symbols(file_id);
-
-        -- FTS5 virtual table for full-text search
-        CREATE VIRTUAL TABLE IF NOT EXISTS symbols_fts USING fts5(
-            name,
-            signature,
-            content=symbols,
-            content_rowid=id
-        );
-
-        -- Triggers to keep FTS in sync
-        CREATE TRIGGER IF NOT EXISTS symbols_ai AFTER INSERT ON symbols BEGIN
-            INSERT INTO symbols_fts(rowid, name, signature) VALUES (new.id, new.name, new.signature);
-        END;
-        CREATE TRIGGER IF NOT EXISTS symbols_ad AFTER DELETE ON symbols BEGIN
-            INSERT INTO symbols_fts(symbols_fts, rowid, name, signature) VALUES('delete', old.id, old.name, old.signature);
-        END;
-        CREATE TRIGGER IF NOT EXISTS symbols_au AFTER UPDATE ON symbols BEGIN
-            INSERT INTO symbols_fts(symbols_fts, rowid, name, signature) VALUES('delete', old.id, old.name, old.signature);
-            INSERT INTO symbols_fts(rowid, name, signature) VALUES (new.id, new.name, new.signature);
-        END;
+        CREATE INDEX IF NOT EXISTS idx_symbols_kind ON symbols_data(kind);
+        CREATE INDEX IF NOT EXISTS idx_symbols_file ON symbols_data(file_id);
 
         -- Modules table
         CREATE TABLE IF NOT EXISTS modules (
@@ -214,22 +442,24 @@ pub fn init_db(conn: &Connection) -> Result<()> {
             child_id INTEGER NOT NULL,
             parent_name TEXT NOT NULL,
             kind TEXT NOT NULL,
-            FOREIGN KEY (child_id) REFERENCES symbols(id) ON DELETE CASCADE
+            FOREIGN KEY (child_id) REFERENCES symbols_data(id) ON DELETE CASCADE
         );
         CREATE INDEX IF NOT EXISTS idx_inheritance_child ON inheritance(child_id);
         CREATE INDEX IF NOT EXISTS idx_inheritance_parent ON inheritance(parent_name);
 
-        -- References table (symbol usages)
-        CREATE TABLE IF NOT EXISTS refs (
+        -- References table (symbol usages), read through the refs view
+        CREATE TABLE IF NOT EXISTS refs_data (
             id INTEGER PRIMARY KEY,
             file_id INTEGER NOT NULL,
-            name TEXT NOT NULL,
+            name_id INTEGER NOT NULL,
             line INTEGER NOT NULL,
             context TEXT,
+            ref_kind TEXT,
+            arity INTEGER,
+            count INTEGER,
+            sample_lines TEXT,
             FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
         );
-        CREATE INDEX IF NOT EXISTS idx_refs_name ON refs(name);
-        CREATE INDEX IF NOT EXISTS idx_refs_file ON refs(file_id);
 
         -- XML usages (classes used in XML layouts)
         CREATE TABLE IF NOT EXISTS xml_usages (
@@ -327,21 +557,583 @@ pub fn init_db(conn: &Connection) -> Result<()> {
         );
         "#,
     )?;
+    conn.execute_batch(SYMBOL_ANNOTATIONS_SCHEMA)?;
+    conn.execute_batch(IMPORTS_SCHEMA)?;
+    conn.execute_batch(NAME_TRIGRAMS_SCHEMA)?;
+    conn.execute_batch(EMBEDDINGS_SCHEMA)?;
+    conn.execute_batch(CALLS_SCHEMA)?;
+    conn.execute_batch(DI_BINDINGS_SCHEMA)?;
+    conn.execute_batch(SYMBOLS_VIEW)?;
+    conn.execute_batch(REFS_VIEW)?;
+    migrations::stamp_latest(conn)?;
+
+    // Full-text search only if this SQLite build has FTS5; otherwise search uses LIKE
+    if fts5_available(conn) {
+        create_symbols_fts(conn)?;
+    }
     Ok(())
 }
 
-/// Open or create database connection
-pub fn open_db(project_root: &Path) -> Result<Connection> {
-    let db_path = get_db_path(project_root)?;
-    let conn = Connection::open(&db_path)?;
+/// Human-readable description of a table for `ast-index schema`
+pub struct TableDoc {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// (column, description)
+    pub columns: &'static [(&'static str, &'static str)],
+    pub examples: &'static [&'static str],
+}
 
-    // Enable foreign keys and WAL mode for better performance
-    conn.pragma_update(None, "foreign_keys", "ON")?;
-    // journal_mode returns result, use query_row
-    let _: String = conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))?;
-    conn.pragma_update(None, "synchronous", "NORMAL")?;
-    conn.pragma_update(None, "cache_size", "-8000")?; // 8 MB cache to limit memory
-    let _: i64 = conn.query_row("PRAGMA busy_timeout = 5000", [], |row| row.get(0))?; // Wait up to 5s if DB is locked
+/// Descriptions of every table and view created by `init_db`; a test keeps this in sync with the schema
+pub const SCHEMA_DOCS: &[TableDoc] = &[
+    TableDoc {
+        name: "files",
+        description: "Indexed source files",
+        columns: &[
+            ("id", "Row id"),
+            ("path", "Path relative to the project root"),
+            ("mtime", "Modification time (Unix seconds) at indexing"),
+            ("size", "File size in bytes at indexing"),
+            ("encoding", "Detected source encoding (utf-8, utf-16le, utf-16be, windows-1251, koi8-r, windows-1252); files are transcoded to UTF-8 before parsing, so symbol byte offsets refer to the UTF-8 text. NULL if not read (too large) or indexed before encodings were recorded"),
+            ("language", "Language of the file by extension (kotlin, swift, cpp, typescript, ... or a custom parser's name); NULL for files no parser handles"),
+            ("is_test", "1 for test files by path (`test`/`spec`/`__tests__` directories, `*Test.kt`, `*_test.go`, `test_*.py`, `*.spec.ts`, ...), else 0"),
+            ("skipped", "Why the file was not parsed: too-large (over `[index] max_file_size`), binary or minified; NULL for parsed files"),
+        ],
+        examples: &["SELECT path FROM files WHERE path LIKE '%.kt' ORDER BY size DESC LIMIT 10"],
+    },
+    TableDoc {
+        name: "symbols",
+        description: "Declarations found by the parsers; name, signature, qualified_name and doc are mirrored in the symbols_fts FTS5 table. A view over symbols_data and names that also accepts inserts, updates and deletes",
+        columns: &[
+            ("id", "Row id"),
+            ("file_id", "Declaring file (files.id)"),
+            ("name", "Short name"),
+            ("kind", "class, interface, object, enum, function, property, typealias, package, constant, import or annotation"),
+            ("line", "1-based declaration line"),
+            ("parent_id", "Unused, always NULL"),
+            ("signature", "Declaration line as written"),
+            ("container", "Enclosing type chain for members, e.g. Outer.Inner"),
+            ("qualified_name", "Namespace + container + name, e.g. com.example.billing.Invoice.total"),
+            ("doc", "First paragraph of the doc comment"),
+            ("visibility", "public, protected, internal, private, package (Java), crate (Rust pub(...)) or fileprivate (Swift); NULL when unknown"),
+            ("end_line", "Last line of the declaration including its body (closing brace, `end`, last indented line)"),
+            ("start_byte", "Byte offset of the first non-blank character of the declaration line"),
+            ("end_byte", "Byte offset just past the last character of end_line; substr of the file gives the full definition"),
+            ("partial", "1 for one part of a C# `partial` type or method (parts share qualified_name), NULL otherwise"),
+            ("condition", "Conditional compilation guarding the declaration in Rust cfg syntax (`DEBUG`, `not(DEBUG)`, `all(A, not(B))`, `feature = \"serde\"`) from #if/#ifdef blocks or #[cfg(...)]; NULL if always compiled"),
+            ("arity", "Number of parameters of a function (receivers like `self` not counted); NULL for other kinds or when the signature has no parameter list"),
+            ("signature_hash", "Hash of the function's parameter list; overloads of one name have different hashes"),
+            ("generated_by", "Derive or macro that generates the symbol (`derive(Builder)`, `G_DEFINE_TYPE`); NULL for symbols declared in source"),
+            ("humps", "Uppercased first letters of the name's humps (`PRI` for PaymentRepositoryImpl, `PHR` for parse_http_request), for CamelHump search"),
+            ("complexity", "Approximate cyclomatic complexity of a function: 1 + branch keywords and `&&`/`||` in its body; NULL for other kinds"),
+        ],
+        examples: &[
+            "SELECT s.name, f.path, s.line FROM symbols s JOIN files f ON s.file_id = f.id WHERE s.kind = 'class' AND s.name LIKE '%ViewModel'",
+            "SELECT kind, COUNT(*) FROM symbols GROUP BY kind ORDER BY 2 DESC",
+            "SELECT name, end_line - line + 1 AS lines FROM symbols WHERE kind = 'function' ORDER BY lines DESC LIMIT 10",
+            "SELECT name, container, COUNT(DISTINCT signature_hash) AS overloads FROM symbols WHERE kind = 'function' GROUP BY name, container HAVING overloads > 1",
+        ],
+    },
+    TableDoc {
+        name: "symbols_data",
+        description: "Storage of the symbols view: the same columns with the name as an id into names",
+        columns: &[
+            ("id", "Row id"),
+            ("file_id", "Declaring file (files.id)"),
+            ("name_id", "Short name (names.id)"),
+            ("kind", "Kind, as in symbols"),
+            ("line", "1-based declaration line"),
+            ("parent_id", "Unused, always NULL"),
+            ("signature", "Declaration line as written"),
+            ("container", "Enclosing type chain, as in symbols"),
+            ("qualified_name", "Qualified name, as in symbols"),
+            ("doc", "First paragraph of the doc comment"),
+            ("visibility", "Visibility, as in symbols"),
+            ("end_line", "Last line of the declaration, as in symbols"),
+            ("start_byte", "Byte offset of the declaration, as in symbols"),
+            ("end_byte", "Byte offset past the declaration, as in symbols"),
+            ("partial", "Partial type or method part, as in symbols"),
+            ("condition", "Conditional compilation, as in symbols"),
+            ("arity", "Number of parameters, as in symbols"),
+            ("signature_hash", "Hash of the parameter list, as in symbols"),
+            ("generated_by", "Generating derive or macro, as in symbols"),
+            ("humps", "CamelHump skeleton, as in symbols"),
+            ("complexity", "Cyclomatic complexity, as in symbols"),
+        ],
+        examples: &["SELECT n.text, COUNT(*) FROM symbols_data s JOIN names n ON n.id = s.name_id GROUP BY s.name_id HAVING COUNT(*) > 1"],
+    },
+    TableDoc {
+        name: "symbol_annotations",
+        description: "Annotations, decorators and attributes on symbols",
+        columns: &[
+            ("id", "Row id"),
+            ("symbol_id", "Annotated symbol (symbols.id)"),
+            ("name", "Annotation name without @ / #[ ] / [ ], e.g. Composable, pytest.fixture, derive"),
+            ("args", "Arguments as written, e.g. name = \"users\"; derive lists traits as Debug, Serialize"),
+        ],
+        examples: &["SELECT s.name, a.args FROM symbol_annotations a JOIN symbols s ON a.symbol_id = s.id WHERE a.name = 'Composable'"],
+    },
+    TableDoc {
+        name: "inheritance",
+        description: "Supertypes of class-like symbols",
+        columns: &[
+            ("id", "Row id"),
+            ("child_id", "Subtype (symbols.id)"),
+            ("parent_name", "Supertype name as written"),
+            ("kind", "extends, implements, with (Dart mixins), includes (Ruby mixins), or permits (reverse hint: a sealed type naming a permitted subtype)"),
+        ],
+        examples: &["SELECT s.name FROM inheritance i JOIN symbols s ON i.child_id = s.id WHERE i.parent_name = 'ViewModel'"],
+    },
+    TableDoc {
+        name: "refs",
+        description: "Identifier usages (calls, type references) per line; with `[index] refs = \"aggregated\"` one row stands for every usage of a name with the same kind and arity in a file. A view over refs_data and names that also accepts inserts and deletes",
+        columns: &[
+            ("id", "Row id"),
+            ("file_id", "File containing the usage (files.id)"),
+            ("name", "Referenced identifier"),
+            ("line", "1-based line of the usage"),
+            ("context", "Trimmed source line"),
+            ("ref_kind", "How the name is used: call, instantiation, type, extension (supertype or extended type), string (named in a string literal), other; NULL in indexes built before it was stored"),
+            ("arity", "Number of arguments of a call or instantiation; NULL for other kinds or when no argument list follows the name"),
+            ("count", "Usages the row stands for in an aggregated row (line and context are the first one's); NULL for a single usage"),
+            ("sample_lines", "Comma-separated lines of the first usages of an aggregated row; NULL for a single usage"),
+        ],
+        examples: &[
+            "SELECT f.path, r.line FROM refs r JOIN files f ON r.file_id = f.id WHERE r.name = 'UserRepository'",
+            "SELECT r.name, SUM(COALESCE(r.count, 1)) FROM refs r GROUP BY r.name ORDER BY 2 DESC LIMIT 10",
+            "SELECT f.path, r.line FROM refs r JOIN files f ON r.file_id = f.id WHERE r.name = 'User' AND r.ref_kind = 'instantiation'",
+        ],
+    },
+    TableDoc {
+        name: "names",
+        description: "Declared and referenced identifiers, stored once and referred to by symbols_data and refs_data",
+        columns: &[
+            ("id", "Row id"),
+            ("text", "Identifier"),
+        ],
+        examples: &["SELECT COUNT(*) FROM names"],
+    },
+    TableDoc {
+        name: "refs_data",
+        description: "Storage of the refs view: the same columns with the name as an id into names",
+        columns: &[
+            ("id", "Row id"),
+            ("file_id", "File containing the usage (files.id)"),
+            ("name_id", "Referenced identifier (names.id)"),
+            ("line", "1-based line of the usage"),
+            ("context", "Trimmed source line"),
+            ("ref_kind", "Kind of usage, as in refs"),
+            ("arity", "Number of arguments, as in refs"),
+            ("count", "Usages of an aggregated row, as in refs"),
+            ("sample_lines", "Lines of an aggregated row, as in refs"),
+        ],
+        examples: &["SELECT n.text, COUNT(*) FROM refs_data r JOIN names n ON n.id = r.name_id GROUP BY r.name_id ORDER BY 2 DESC LIMIT 10"],
+    },
+    TableDoc {
+        name: "imports",
+        description: "Names brought into scope by import/use/using statements (Kotlin, Java, Scala, Python, Rust, C#)",
+        columns: &[
+            ("id", "Row id"),
+            ("file_id", "Importing file (files.id)"),
+            ("name", "Name bound in the file (the alias if renamed), * for wildcard imports"),
+            ("path", "Imported qualified name, or the namespace for wildcard imports; same form as symbols.qualified_name"),
+            ("line", "1-based line of the import statement"),
+        ],
+        examples: &["SELECT f.path FROM imports i JOIN files f ON i.file_id = f.id WHERE i.path = 'com.example.model.User'"],
+    },
+    TableDoc {
+        name: "modules",
+        description: "Build modules and external iOS dependencies",
+        columns: &[
+            ("id", "Row id"),
+            ("name", "Module name, e.g. :features:payments"),
+            ("path", "Module directory relative to the project root"),
+            ("kind", "NULL for project modules, cocoapods or carthage for iOS dependencies"),
+        ],
+        examples: &["SELECT name, path FROM modules ORDER BY name"],
+    },
+    TableDoc {
+        name: "module_deps",
+        description: "Direct dependencies between modules",
+        columns: &[
+            ("id", "Row id"),
+            ("module_id", "Dependent module (modules.id)"),
+            ("dep_module_id", "Dependency (modules.id)"),
+            ("dep_kind", "Dependency configuration, e.g. api or implementation"),
+        ],
+        examples: &["SELECT m.name, d.name FROM module_deps md JOIN modules m ON md.module_id = m.id JOIN modules d ON md.dep_module_id = d.id"],
+    },
+    TableDoc {
+        name: "transitive_deps",
+        description: "Cached transitive module dependencies",
+        columns: &[
+            ("id", "Row id"),
+            ("module_id", "Dependent module (modules.id)"),
+            ("dependency_id", "Reachable module (modules.id)"),
+            ("depth", "Number of hops, 1 for direct dependencies"),
+            ("path", "Module chain leading to the dependency"),
+        ],
+        examples: &["SELECT d.name, t.depth FROM transitive_deps t JOIN modules d ON t.dependency_id = d.id WHERE t.module_id = 1 ORDER BY t.depth"],
+    },
+    TableDoc {
+        name: "xml_usages",
+        description: "Classes referenced from Android XML layouts",
+        columns: &[
+            ("id", "Row id"),
+            ("module_id", "Module of the XML file (modules.id)"),
+            ("file_path", "XML file path"),
+            ("line", "1-based line"),
+            ("class_name", "Referenced class"),
+            ("usage_type", "fragment or view_class_attr"),
+            ("element_id", "android:id of the element, if any"),
+        ],
+        examples: &["SELECT file_path, line FROM xml_usages WHERE class_name LIKE '%ProfileView'"],
+    },
+    TableDoc {
+        name: "resources",
+        description: "Android resource definitions",
+        columns: &[
+            ("id", "Row id"),
+            ("module_id", "Declaring module (modules.id)"),
+            ("type", "Resource type, e.g. string, drawable, layout"),
+            ("name", "Resource name"),
+            ("file_path", "Defining file"),
+            ("line", "1-based line for values resources"),
+        ],
+        examples: &["SELECT type, name FROM resources WHERE id NOT IN (SELECT resource_id FROM resource_usages)"],
+    },
+    TableDoc {
+        name: "resource_usages",
+        description: "Usages of Android resources in code and XML",
+        columns: &[
+            ("id", "Row id"),
+            ("resource_id", "Used resource (resources.id)"),
+            ("usage_file", "File containing the usage"),
+            ("usage_line", "1-based line"),
+            ("usage_type", "code or xml"),
+        ],
+        examples: &["SELECT usage_file, usage_line FROM resource_usages u JOIN resources r ON u.resource_id = r.id WHERE r.name = 'app_name'"],
+    },
+    TableDoc {
+        name: "storyboard_usages",
+        description: "Classes referenced from iOS storyboards and xibs",
+        columns: &[
+            ("id", "Row id"),
+            ("module_id", "Module of the storyboard (modules.id)"),
+            ("file_path", "Storyboard or xib path"),
+            ("line", "1-based line"),
+            ("class_name", "Referenced class"),
+            ("usage_type", "viewController, view, cell or segue"),
+            ("storyboard_id", "Storyboard identifier, if any"),
+        ],
+        examples: &["SELECT file_path, class_name FROM storyboard_usages WHERE usage_type = 'viewController'"],
+    },
+    TableDoc {
+        name: "ios_assets",
+        description: "Assets from .xcassets catalogs",
+        columns: &[
+            ("id", "Row id"),
+            ("module_id", "Owning module (modules.id)"),
+            ("type", "Asset type, e.g. imageset or colorset"),
+            ("name", "Asset name"),
+            ("file_path", "Asset directory"),
+        ],
+        examples: &["SELECT name FROM ios_assets WHERE id NOT IN (SELECT asset_id FROM ios_asset_usages)"],
+    },
+    TableDoc {
+        name: "ios_asset_usages",
+        description: "Usages of iOS assets in code",
+        columns: &[
+            ("id", "Row id"),
+            ("asset_id", "Used asset (ios_assets.id)"),
+            ("usage_file", "File containing the usage"),
+            ("usage_line", "1-based line"),
+            ("usage_type", "Always code"),
+        ],
+        examples: &["SELECT a.name, COUNT(*) FROM ios_asset_usages u JOIN ios_assets a ON u.asset_id = a.id GROUP BY a.name"],
+    },
+    TableDoc {
+        name: "symbol_names",
+        description: "Distinct symbol names, for typo-tolerant search",
+        columns: &[
+            ("id", "Row id"),
+            ("name", "Symbol name"),
+        ],
+        examples: &["SELECT COUNT(*) FROM symbol_names"],
+    },
+    TableDoc {
+        name: "name_trigrams",
+        description: "Lowercase three-character slices of each symbol name; `search --fuzzy` ranks names sharing trigrams with the query",
+        columns: &[
+            ("trigram", "Three lowercase characters"),
+            ("name_id", "Name containing it (symbol_names.id)"),
+        ],
+        examples: &["SELECT n.name, COUNT(*) AS shared FROM name_trigrams t JOIN symbol_names n ON t.name_id = n.id WHERE t.trigram IN ('pay', 'aym', 'rep') GROUP BY n.id ORDER BY shared DESC LIMIT 10"],
+    },
+    TableDoc {
+        name: "symbol_embeddings",
+        description: "Embedding vectors of symbols for `search --semantic`, filled by `ast-index embed`; re-indexed symbols lose theirs until the next `embed`",
+        columns: &[
+            ("symbol_id", "Embedded symbol (symbols.id)"),
+            ("vector", "Little-endian f32 components of the vector of the symbol's name words, kind, signature and doc; the model is metadata.embed_model"),
+        ],
+        examples: &["SELECT COUNT(*) FROM symbol_embeddings"],
+    },
+    TableDoc {
+        name: "calls",
+        description: "Call graph: calls made inside each function, by name (a call is a name followed by `(` in code); calls outside any function are not recorded",
+        columns: &[
+            ("id", "Row id"),
+            ("caller_id", "Calling function (symbols.id), the innermost one around the call"),
+            ("callee", "Called name as written, resolved to symbols by name"),
+            ("line", "1-based line of the call"),
+        ],
+        examples: &["SELECT s.name, COUNT(*) FROM calls c JOIN symbols s ON c.caller_id = s.id GROUP BY s.id ORDER BY 2 DESC LIMIT 10"],
+    },
+    TableDoc {
+        name: "di_bindings",
+        description: "Dependency injection: types Kotlin and Java files provide to or inject from a Dagger/Hilt, Koin or Spring container",
+        columns: &[
+            ("id", "Row id"),
+            ("file_id", "Declaring file (files.id)"),
+            ("type", "Simple type name, without generics; Provider<T>/Lazy<T> count as T"),
+            ("role", "provides or injects"),
+            ("mechanism", "How, e.g. @Provides, @Inject constructor, @Autowired field, koin single, koin get"),
+            ("symbol", "Providing function, class or Koin module; for injections the type receiving the dependency"),
+            ("line", "1-based line of the declaration"),
+        ],
+        examples: &["SELECT d.type, f.path, d.line FROM di_bindings d JOIN files f ON d.file_id = f.id WHERE d.role = 'injects' AND d.type NOT IN (SELECT type FROM di_bindings WHERE role = 'provides')"],
+    },
+    TableDoc {
+        name: "metadata",
+        description: "Index settings as key/value pairs",
+        columns: &[
+            ("key", "Setting name, e.g. project_root, embed_model"),
+            ("value", "Setting value"),
+        ],
+        examples: &["SELECT key, value FROM metadata"],
+    },
+    TableDoc {
+        name: "schema_version",
+        description: "Schema migrations applied to the index (`ast-index migrate`)",
+        columns: &[
+            ("version", "Migration number; the highest is the schema version"),
+            ("description", "What the migration changed"),
+            ("applied_at", "When it was applied (Unix seconds); indexes created with the current schema record their creation time"),
+        ],
+        examples: &["SELECT version, description, datetime(applied_at, 'unixepoch') FROM schema_version ORDER BY version"],
+    },
+];
+
+/// Columns of `symbols` indexed by `symbols_fts`
+const FTS_COLUMNS: &[&str] = &["name", "signature", "qualified_name", "doc"];
+
+/// Tokenizer of `symbols_fts`: letters, digits and combining marks of any script form tokens,
+/// so non-ASCII identifiers (`ПроверитьКонтракт`, Devanagari with vowel signs) stay one
+/// case-folded token
+const FTS_TOKENIZER: &str = "unicode61 remove_diacritics 2 categories 'L* N* Co M*'";
+
+/// `[search]` settings for this run
+#[derive(Debug, Default)]
+struct SearchSettings {
+    synonyms: Synonyms,
+    stemming: bool,
+}
+
+static SEARCH_SETTINGS: std::sync::OnceLock<SearchSettings> = std::sync::OnceLock::new();
+
+/// Register `[search]` synonyms and stemming; called once at startup, before the index is opened
+pub fn register_search(config: &SearchConfig) {
+    let _ = SEARCH_SETTINGS.set(SearchSettings { synonyms: Synonyms::new(&config.synonyms), stemming: config.stemming });
+}
+
+fn search_settings() -> &'static SearchSettings {
+    SEARCH_SETTINGS.get_or_init(SearchSettings::default)
+}
+
+/// `FTS_TOKENIZER`, behind the porter stemmer when `[search] stemming` is on
+fn fts_tokenizer(stemming: bool) -> String {
+    if stemming {
+        format!("porter {}", FTS_TOKENIZER)
+    } else {
+        FTS_TOKENIZER.to_string()
+    }
+}
+
+/// Synonym groups from `[search] synonyms`, by lowercase word: each entry's key and values all
+/// find each other, and a word listed in several entries joins their groups
+#[derive(Debug, Default)]
+pub struct Synonyms(std::collections::HashMap<String, Vec<String>>);
+
+impl Synonyms {
+    pub fn new(entries: &std::collections::HashMap<String, Vec<String>>) -> Self {
+        let mut groups: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
+        for (key, values) in entries {
+            let group: Vec<String> = std::iter::once(key).chain(values).map(|w| w.trim().to_lowercase()).collect();
+            for word in &group {
+                let members = groups.entry(word.clone()).or_default();
+                members.extend(group.iter().filter(|w| *w != word).cloned());
+                members.sort();
+                members.dedup();
+            }
+        }
+        Synonyms(groups)
+    }
+
+    /// Synonyms of `word` (ignoring case), not including it
+    pub fn of(&self, word: &str) -> &[String] {
+        self.0.get(&word.to_lowercase()).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+/// `word` as an FTS5 term (`"word"` plus `suffix`, e.g. `*` for a prefix), or a parenthesized OR
+/// of it and its synonyms
+fn fts_term_with_synonyms(word: &str, suffix: &str, synonyms: &Synonyms) -> String {
+    let term = |w: &str| format!("\"{}\"{}", w.replace('"', "\"\""), suffix);
+    let others = synonyms.of(word);
+    if others.is_empty() {
+        return term(word);
+    }
+    let terms: Vec<String> = std::iter::once(word).chain(others.iter().map(String::as_str)).map(term).collect();
+    format!("({})", terms.join(" OR "))
+}
+
+/// Create the FTS5 index over symbols and the triggers on `symbols_data` that keep it in sync
+fn create_symbols_fts(conn: &Connection) -> Result<()> {
+    let columns = FTS_COLUMNS.join(", ");
+    let values = |prefix: &str| {
+        FTS_COLUMNS
+            .iter()
+            .map(|&c| match c {
+                "name" => format!("(SELECT text FROM names WHERE id = {}.name_id)", prefix),
+                _ => format!("{}.{}", prefix, c),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    conn.execute_batch(&format!(
+        r#"
+        -- FTS5 virtual table for full-text search
+        CREATE VIRTUAL TABLE IF NOT EXISTS symbols_fts USING fts5(
+            {columns},
+            content=symbols,
+            content_rowid=id,
+            tokenize="{tokenizer}"
+        );
+
+        -- Triggers to keep FTS in sync
+        CREATE TRIGGER IF NOT EXISTS symbols_ai AFTER INSERT ON symbols_data BEGIN
+            INSERT INTO symbols_fts(rowid, {columns}) VALUES (new.id, {new});
+        END;
+        CREATE TRIGGER IF NOT EXISTS symbols_ad AFTER DELETE ON symbols_data BEGIN
+            INSERT INTO symbols_fts(symbols_fts, rowid, {columns}) VALUES('delete', old.id, {old});
+        END;
+        CREATE TRIGGER IF NOT EXISTS symbols_au AFTER UPDATE ON symbols_data BEGIN
+            INSERT INTO symbols_fts(symbols_fts, rowid, {columns}) VALUES('delete', old.id, {old});
+            INSERT INTO symbols_fts(rowid, {columns}) VALUES (new.id, {new});
+        END;
+        "#,
+        columns = columns,
+        tokenizer = fts_tokenizer(search_settings().stemming),
+        new = values("new"),
+        old = values("old"),
+    ))?;
+    Ok(())
+}
+
+/// Recreate `symbols_fts` if it was built with a different column set or tokenizer (including
+/// a change of `[search] stemming`)
+fn upgrade_symbols_fts(conn: &Connection) -> Result<()> {
+    if !has_fts_index(conn) {
+        return Ok(());
+    }
+    let columns: Vec<String> = conn
+        .prepare("PRAGMA table_info(symbols_fts)")?
+        .query_map([], |row| row.get(1))?
+        .collect::<Result<_, _>>()?;
+    let sql: String =
+        conn.query_row("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'symbols_fts'", [], |row| row.get(0))?;
+    let tokenize = format!("tokenize=\"{}\"", fts_tokenizer(search_settings().stemming));
+    if columns.iter().map(String::as_str).eq(FTS_COLUMNS.iter().copied()) && sql.contains(&tokenize) {
+        return Ok(());
+    }
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS symbols_ai; DROP TRIGGER IF EXISTS symbols_ad; DROP TRIGGER IF EXISTS symbols_au;
+         DROP TABLE IF EXISTS symbols_fts;",
+    )?;
+    create_symbols_fts(conn)?;
+    conn.execute("INSERT INTO symbols_fts(symbols_fts) VALUES('rebuild')", [])?;
+    Ok(())
+}
+
+/// Check if the linked SQLite library supports FTS5 (probed once per process)
+pub fn fts5_available(conn: &Connection) -> bool {
+    static FTS5: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *FTS5.get_or_init(|| {
+        let ok = conn
+            .execute_batch("CREATE VIRTUAL TABLE temp.fts5_probe USING fts5(x); DROP TABLE temp.fts5_probe;")
+            .is_ok();
+        if !ok {
+            eprintln!("Warning: SQLite was built without FTS5, falling back to LIKE search (slower)");
+        }
+        ok
+    })
+}
+
+/// Check if full-text search can be used for this DB (FTS5 available and symbols_fts built)
+pub fn has_fts_index(conn: &Connection) -> bool {
+    fts5_available(conn)
+        && conn
+            .query_row(
+                "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'symbols_fts'",
+                [],
+                |_| Ok(()),
+            )
+            .is_ok()
+}
+
+/// Page counts of the database file
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PageStats {
+    pub page_size: i64,
+    pub pages: i64,
+    pub free_pages: i64,
+}
+
+pub fn page_stats(conn: &Connection) -> Result<PageStats> {
+    let pragma = |name: &str| -> Result<i64> { Ok(conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))?) };
+    Ok(PageStats { page_size: pragma("page_size")?, pages: pragma("page_count")?, free_pages: pragma("freelist_count")? })
+}
+
+/// Compact the index: drop reference names no reference uses any more, merge the FTS segments,
+/// refresh the planner statistics, rewrite the file without free pages and truncate the WAL
+pub fn optimize(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "DELETE FROM names WHERE id NOT IN (SELECT name_id FROM symbols_data) AND text NOT IN (SELECT name FROM refs)",
+        [],
+    )?;
+    if has_fts_index(conn) {
+        conn.execute("INSERT INTO symbols_fts(symbols_fts) VALUES('optimize')", [])?;
+    }
+    conn.execute_batch("PRAGMA optimize; VACUUM;")?;
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    Ok(())
+}
+
+/// Open or create database connection, applying pending schema migrations
+pub fn open_db(project_root: &Path) -> Result<Connection> {
+    let conn = open_db_unmigrated(project_root)?;
+
+    // A new file has no tables yet; init_db creates the current schema and stamps its version
+    let initialized = conn
+        .query_row("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'files'", [], |_| Ok(()))
+        .is_ok();
+    if initialized {
+        migrations::migrate(&conn)?;
+    }
+    upgrade_symbols_fts(&conn)?;
+    if initialized {
+        shards::attach(&conn, &get_db_path(project_root)?)?;
+    }
 
     // Store project root for hash migration
     conn.execute(
@@ -356,14 +1148,52 @@ pub fn open_db(project_root: &Path) -> Result<Connection> {
     Ok(conn)
 }
 
+/// Open the database with the settings of `open_db` but without migrating it; for `migrate --dry-run`
+pub fn open_db_unmigrated(project_root: &Path) -> Result<Connection> {
+    let db_path = get_db_path(project_root)?;
+    let conn = connect(&db_path)?;
+
+    // Enable foreign keys and WAL mode for better performance
+    conn.pragma_update(None, "foreign_keys", "ON")?;
+    // journal_mode returns result, use query_row
+    let _: String = conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    conn.pragma_update(None, "cache_size", "-8000")?; // 8 MB cache to limit memory
+    let _: i64 = conn.query_row("PRAGMA busy_timeout = 5000", [], |row| row.get(0))?; // Wait up to 5s if DB is locked
+
+    // DB built with FTS5 but opened by a library without it: FTS triggers would fail every write
+    if !fts5_available(&conn) {
+        conn.execute_batch(
+            "DROP TRIGGER IF EXISTS symbols_ai; DROP TRIGGER IF EXISTS symbols_ad; DROP TRIGGER IF EXISTS symbols_au;",
+        ).ok();
+    }
+
+    Ok(conn)
+}
+
+/// Open the database read-only, skipping the schema upgrades of `open_db`; for latency-sensitive
+/// lookups such as `complete`
+pub fn open_db_readonly(project_root: &Path) -> Result<Connection> {
+    let db_path = get_db_path(project_root)?;
+    if db_path.as_os_str() == MEMORY_DB_PATH {
+        return connect(&db_path);
+    }
+    let conn = Connection::open_with_flags(
+        &db_path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    let _: i64 = conn.query_row("PRAGMA busy_timeout = 5000", [], |row| row.get(0))?;
+    Ok(conn)
+}
+
 /// Check if database exists and is initialized
 pub fn db_exists(project_root: &Path) -> bool {
     if let Ok(db_path) = get_db_path(project_root) {
-        if !db_path.exists() {
+        if !db_path.exists() && db_path.as_os_str() != MEMORY_DB_PATH {
             return false;
         }
         // Also check if tables exist
-        if let Ok(conn) = Connection::open(&db_path) {
+        if let Ok(conn) = connect(&db_path) {
             conn.query_row(
                 "SELECT 1 FROM sqlite_master WHERE type='table' AND name='files'",
                 [],
@@ -398,6 +1228,12 @@ pub enum SymbolKind {
 }
 
 impl SymbolKind {
+    pub const ALL: [SymbolKind; 11] = [
+        SymbolKind::Class, SymbolKind::Interface, SymbolKind::Object, SymbolKind::Enum, SymbolKind::Function,
+        SymbolKind::Property, SymbolKind::TypeAlias, SymbolKind::Package, SymbolKind::Constant, SymbolKind::Import,
+        SymbolKind::Annotation,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             SymbolKind::Class => "class",
@@ -413,13 +1249,106 @@ impl SymbolKind {
             SymbolKind::Annotation => "annotation",
         }
     }
+
+    /// Parse a kind name as stored in the DB (`as_str` output)
+    pub fn from_name(s: &str) -> Option<SymbolKind> {
+        match s {
+            "class" => Some(SymbolKind::Class),
+            "interface" => Some(SymbolKind::Interface),
+            "object" => Some(SymbolKind::Object),
+            "enum" => Some(SymbolKind::Enum),
+            "function" => Some(SymbolKind::Function),
+            "property" => Some(SymbolKind::Property),
+            "typealias" => Some(SymbolKind::TypeAlias),
+            "package" => Some(SymbolKind::Package),
+            "constant" => Some(SymbolKind::Constant),
+            "import" => Some(SymbolKind::Import),
+            "annotation" => Some(SymbolKind::Annotation),
+            _ => None,
+        }
+    }
+}
+
+/// How a reference uses the referenced name (heuristic, from the surrounding tokens)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefKind {
+    /// `process(`, `obj.Process(`, ObjC message sends
+    Call,
+    /// `new Foo(`, `Foo(`, `Foo::new(`
+    Instantiation,
+    /// Type position: after `:`, `as`, `is`, `->`, inside `<...>`, `@Annotation`
+    Type,
+    /// Supertype or extended type: `: Base()`, `extends`/`implements`, `fun Foo.bar()`, `impl Trait for Foo`
+    Extension,
+    /// Anything else (`Foo.create()`, `Color.RED`)
+    Other,
+    /// Named in a string literal (`Class.forName("com.x.Foo")`, `"User".constantize`)
+    String,
+}
+
+impl RefKind {
+    pub const ALL: [RefKind; 6] = [
+        RefKind::Call, RefKind::Instantiation, RefKind::Type, RefKind::Extension, RefKind::Other, RefKind::String,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RefKind::Call => "call",
+            RefKind::Instantiation => "instantiation",
+            RefKind::Type => "type",
+            RefKind::Extension => "extension",
+            RefKind::Other => "other",
+            RefKind::String => "string",
+        }
+    }
+
+    /// Parse a kind name as stored in the DB (`as_str` output)
+    pub fn from_name(s: &str) -> Option<RefKind> {
+        RefKind::ALL.into_iter().find(|k| k.as_str() == s)
+    }
+}
+
+/// Why a file was recorded without being parsed (`files.skipped`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Larger than `[index] max_file_size`
+    TooLarge,
+    /// Zero bytes in a file that is not UTF-16
+    Binary,
+    /// Generated or minified: lines averaging over a thousand characters
+    Minified,
+}
+
+impl SkipReason {
+    pub const ALL: [SkipReason; 3] = [SkipReason::TooLarge, SkipReason::Binary, SkipReason::Minified];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SkipReason::TooLarge => "too-large",
+            SkipReason::Binary => "binary",
+            SkipReason::Minified => "minified",
+        }
+    }
+
+    /// Parse a reason as stored in the DB (`as_str` output)
+    pub fn from_name(s: &str) -> Option<SkipReason> {
+        SkipReason::ALL.into_iter().find(|r| r.as_str() == s)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SkipReason::TooLarge => "larger than max_file_size",
+            SkipReason::Binary => "binary content",
+            SkipReason::Minified => "generated or minified (very long lines)",
+        }
+    }
 }
 
 /// Insert or update a file record
 pub fn upsert_file(conn: &Connection, path: &str, mtime: i64, size: i64) -> Result<i64> {
     conn.execute(
-        "INSERT OR REPLACE INTO files (path, mtime, size) VALUES (?1, ?2, ?3)",
-        params![path, mtime, size],
+        "INSERT OR REPLACE INTO files (path, mtime, size, language, is_test) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![path, mtime, size, parsers::language_of_path(path), parsers::is_test_path(path)],
     )?;
     Ok(conn.last_insert_rowid())
 }
@@ -433,9 +1362,10 @@ pub fn insert_symbol(
     line: usize,
     signature: Option<&str>,
 ) -> Result<i64> {
+    // Straight into symbols_data: an insert through the view leaves last_insert_rowid unchanged
     conn.execute(
-        "INSERT INTO symbols (file_id, name, kind, line, signature) VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![file_id, name, kind.as_str(), line as i64, signature],
+        "INSERT INTO symbols_data (file_id, name_id, kind, line, signature, humps) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![file_id, intern_name(conn, name)?, kind.as_str(), line as i64, signature, fuzzy::hump_skeleton(name)],
     )?;
     Ok(conn.last_insert_rowid())
 }
@@ -472,36 +1402,172 @@ fn escape_fts5_query(query: &str) -> String {
     format!("\"{}\"{}",  escaped, suffix)
 }
 
+/// FTS query restricted to the name and signature columns; a query with `[search] synonyms`
+/// also matches them
+fn name_fts_query(query: &str, synonyms: &Synonyms) -> String {
+    if query.trim().is_empty() {
+        return format!("{{name signature}} : {}", escape_fts5_query(query));
+    }
+    let (word, suffix) = match query.strip_suffix('*') {
+        Some(word) => (word, "*"),
+        None => (query, ""),
+    };
+    format!("{{name signature}} : {}", fts_term_with_synonyms(word, suffix, synonyms))
+}
+
+/// Translate boolean query syntax into an FTS5 expression over name and signature: `AND`,
+/// `OR` and `NOT` (uppercase) combine terms, parentheses group them, `"quoted phrases"` match
+/// as written and bare words as prefixes. `Payment AND Refund NOT Legacy` becomes
+/// `{name signature} : ("Payment"* AND "Refund"* NOT "Legacy"*)`. None when the query uses
+/// neither operators nor quotes. Bare words also match their `[search] synonyms`
+pub fn boolean_fts_query(query: &str) -> Result<Option<String>> {
+    boolean_fts_expression(query, &search_settings().synonyms)
+}
+
+fn boolean_fts_expression(query: &str, synonyms: &Synonyms) -> Result<Option<String>> {
+    let is_operator = |w: &str| matches!(w, "AND" | "OR" | "NOT");
+    if !query.contains('"') && !query.split_whitespace().any(is_operator) {
+        return Ok(None);
+    }
+
+    let mut tokens: Vec<String> = Vec::new();
+    let mut rest = query.trim_start();
+    while let Some(c) = rest.chars().next() {
+        if c == '"' {
+            let Some(end) = rest[1..].find('"') else {
+                anyhow::bail!("Unterminated quote in query: {}", query);
+            };
+            let phrase = &rest[1..1 + end];
+            if !phrase.trim().is_empty() {
+                tokens.push(escape_fts5_query(phrase));
+            }
+            rest = &rest[end + 2..];
+        } else if c == '(' || c == ')' {
+            tokens.push(c.to_string());
+            rest = &rest[1..];
+        } else {
+            let end = rest.find(|c: char| c.is_whitespace() || matches!(c, '"' | '(' | ')')).unwrap_or(rest.len());
+            let word = &rest[..end];
+            if is_operator(word) {
+                tokens.push(word.to_string());
+            } else if !word.trim_end_matches('*').is_empty() {
+                tokens.push(fts_term_with_synonyms(word.trim_end_matches('*'), "*", synonyms));
+            }
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+
+    // FTS5 operators are binary: each needs a term (or group) on both sides
+    let mut depth = 0usize;
+    let mut after_operand = false;
+    for token in &tokens {
+        match token.as_str() {
+            "(" => {
+                depth += 1;
+                after_operand = false;
+            }
+            ")" => {
+                if depth == 0 || !after_operand {
+                    anyhow::bail!("Unbalanced parentheses in query: {}", query);
+                }
+                depth -= 1;
+            }
+            op if is_operator(op) => {
+                if !after_operand {
+                    anyhow::bail!("{} needs a term on each side (e.g. Payment {} Legacy): {}", op, op, query);
+                }
+                after_operand = false;
+            }
+            _ => after_operand = true,
+        }
+    }
+    if depth > 0 {
+        anyhow::bail!("Unbalanced parentheses in query: {}", query);
+    }
+    if !after_operand {
+        anyhow::bail!("Query ends without a term: {}", query);
+    }
+    Ok(Some(format!("{{name signature}} : ({})", tokens.join(" "))))
+}
+
 /// Search symbols by name (FTS5)
 pub fn search_symbols(conn: &Connection, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
-    // Handle empty query
-    if query.trim().is_empty() {
+    search_symbols_scoped(conn, query, limit, &SearchScope::none())
+}
+
+/// ORDER BY terms ranking names against the query parameter `?{param}`: equal names first, then
+/// names equal ignoring case, then names starting with it, then the rest; shorter names first
+/// within a tier and location last, so a query always lists its results in the same order
+fn name_rank_order(param: usize) -> String {
+    format!(
+        "CASE WHEN s.name = ?{p} THEN 0 \
+              WHEN s.name = ?{p} COLLATE NOCASE THEN 1 \
+              WHEN substr(s.name, 1, length(?{p})) = ?{p} COLLATE NOCASE THEN 2 \
+              ELSE 3 END, length(s.name), s.name, f.path, s.line",
+        p = param
+    )
+}
+
+/// How `search --exact`, `--prefix` and `--contains` compare symbol names with the query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameMatch {
+    Exact,
+    Prefix,
+    Contains,
+}
+
+/// Symbols whose name equals, starts with or contains `query` (ignoring ASCII case unless
+/// `scope.case_sensitive`), ranked by `name_rank_order`
+pub fn search_symbols_matching(
+    conn: &Connection,
+    query: &str,
+    mode: NameMatch,
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<SearchResult>> {
+    if query.is_empty() {
         return Ok(vec![]);
     }
+    let condition = match (mode, scope.case_sensitive) {
+        (NameMatch::Exact, true) => "s.name = ?1",
+        (NameMatch::Exact, false) => "s.name = ?1 COLLATE NOCASE",
+        (NameMatch::Prefix, true) => "substr(s.name, 1, length(?1)) = ?1",
+        (NameMatch::Prefix, false) => "substr(s.name, 1, length(?1)) = ?1 COLLATE NOCASE",
+        (NameMatch::Contains, true) => "instr(s.name, ?1) > 0",
+        (NameMatch::Contains, false) => "instr(lower(s.name), lower(?1)) > 0",
+    };
+    // Substrings narrow the names through their trigrams before `instr` checks each candidate
+    let trigram_clause = if mode == NameMatch::Contains { name_trigram_clause(conn, query)? } else { String::new() };
+    let (scope_clause, scope_params) = scope.symbol_condition();
 
-    let escaped_query = escape_fts5_query(query);
-
-    let mut stmt = conn.prepare(
+    let sql = format!(
         r#"
-        SELECT s.name, s.kind, s.line, s.signature, f.path
-        FROM symbols_fts fts
-        JOIN symbols s ON fts.rowid = s.id
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM symbols s
         JOIN files f ON s.file_id = f.id
-        WHERE symbols_fts MATCH ?1
-        LIMIT ?2
+        WHERE {}{}{}
+        ORDER BY {}
+        LIMIT ?{}
         "#,
-    )?;
+        condition,
+        trigram_clause,
+        scope_clause,
+        name_rank_order(1),
+        2 + scope_params.len()
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    all_params.push(Box::new(query.to_string()));
+    for p in &scope_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    all_params.push(Box::new(limit as i64));
 
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
     let results = stmt
-        .query_map(params![escaped_query, limit as i64], |row| {
-            Ok(SearchResult {
-                name: row.get(0)?,
-                kind: row.get(1)?,
-                line: row.get(2)?,
-                signature: row.get(3)?,
-                path: row.get(4)?,
-            })
-        })?
+        .query_map(param_refs.as_slice(), SearchResult::from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(results)
@@ -515,20 +1581,153 @@ pub struct SearchResult {
     pub line: i64,
     pub signature: Option<String>,
     pub path: String,
+    /// Enclosing type chain for members, e.g. "Outer.Inner"
+    pub container: Option<String>,
+    /// Namespace + container + name
+    pub qualified_name: Option<String>,
+    /// public, private, internal, ... (None when the language has no rule)
+    pub visibility: Option<String>,
+    /// Other declarations of the same `partial` type as `path:line` (see `merge_partial_types`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub partial_parts: Vec<String>,
+    /// Parameter list of a function, read from its signature; tells overloads apart
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<String>,
+    /// Number of parameters of a function
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arity: Option<usize>,
 }
 
-/// Find files by name pattern
-pub fn find_files(conn: &Connection, pattern: &str, limit: usize) -> Result<Vec<String>> {
-    let mut stmt = conn.prepare(
-        "SELECT path FROM files WHERE path LIKE ?1 LIMIT ?2",
-    )?;
+impl SearchResult {
+    /// Build from a row selected as `s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility`
+    pub fn from_row(row: &rusqlite::Row) -> rusqlite::Result<SearchResult> {
+        let name: String = row.get(0)?;
+        let kind: String = row.get(1)?;
+        let signature: Option<String> = row.get(3)?;
+        let params = signature.as_deref().filter(|_| kind == "function").and_then(|sig| signature::parameters(sig, &name));
+        let arity = (kind == "function").then(|| signature::function_arity(&name, params.as_deref())).flatten();
+        Ok(SearchResult {
+            line: row.get(2)?,
+            path: row.get(4)?,
+            container: row.get(5)?,
+            qualified_name: row.get(6)?,
+            visibility: row.get(7)?,
+            partial_parts: Vec::new(),
+            name,
+            kind,
+            signature,
+            params,
+            arity,
+        })
+    }
 
-    let pattern = format!("%{}%", pattern);
-    let results = stmt
-        .query_map(params![pattern, limit as i64], |row| row.get(0))?
-        .collect::<Result<Vec<_>, _>>()?;
+    /// Name qualified with its container (`ClassName.method`)
+    pub fn display_name(&self) -> String {
+        match &self.container {
+            Some(container) => format!("{}.{}", container, self.name),
+            None => self.name.clone(),
+        }
+    }
 
-    Ok(results)
+    /// `display_name` followed by the parameter list for functions (`Processor.process(item: Item)`),
+    /// so overloads of one name can be told apart
+    pub fn display_name_with_params(&self) -> String {
+        match &self.params {
+            Some(params) => format!("{}({})", self.display_name(), params),
+            None => self.display_name(),
+        }
+    }
+}
+
+/// Fold the parts of C# `partial` types into one result per type.
+/// The first part keeps its place and lists every other declaration of the type
+/// in `partial_parts`, including parts the original query did not return.
+pub fn merge_partial_types(conn: &Connection, results: Vec<SearchResult>) -> Result<Vec<SearchResult>> {
+    let any_partial: bool = conn
+        .query_row("SELECT EXISTS(SELECT 1 FROM symbols WHERE partial = 1)", [], |row| row.get(0))
+        .unwrap_or(false);
+    if !any_partial {
+        return Ok(results);
+    }
+
+    let mut stmt = conn.prepare_cached(
+        "SELECT f.path, s.line FROM symbols s JOIN files f ON s.file_id = f.id
+         WHERE s.partial = 1 AND s.qualified_name = ?1 AND s.kind = ?2
+         ORDER BY f.path, s.line",
+    )?;
+    let mut merged: Vec<SearchResult> = Vec::with_capacity(results.len());
+    let mut seen: HashSet<(String, String)> = HashSet::new();
+    for mut result in results {
+        let Some(qualified) = result.qualified_name.clone() else {
+            merged.push(result);
+            continue;
+        };
+        if !matches!(result.kind.as_str(), "class" | "interface" | "struct") {
+            merged.push(result);
+            continue;
+        }
+        let parts: Vec<(String, i64)> = stmt
+            .query_map(params![qualified, result.kind], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        if !parts.iter().any(|(path, line)| *path == result.path && *line == result.line) {
+            merged.push(result);
+            continue;
+        }
+        if !seen.insert((qualified, result.kind.clone())) {
+            continue;
+        }
+        result.partial_parts = parts
+            .into_iter()
+            .filter(|(path, line)| !(*path == result.path && *line == result.line))
+            .map(|(path, line)| format!("{}:{}", path, line))
+            .collect();
+        merged.push(result);
+    }
+    Ok(merged)
+}
+
+/// A completion candidate for `ast-index complete`
+#[derive(Debug, Serialize)]
+pub struct Completion {
+    pub name: String,
+    pub kind: String,
+    pub path: String,
+    pub line: i64,
+}
+
+const COMPLETE_SQL: &str = "
+    SELECT s.name, s.kind, f.path, s.line
+    FROM symbols s
+    JOIN files f ON s.file_id = f.id
+    WHERE s.name >= ?1 COLLATE NOCASE AND s.name < ?2 COLLATE NOCASE AND s.kind != 'import'
+    ORDER BY s.name COLLATE NOCASE
+    LIMIT ?3";
+
+/// Symbols whose name starts with `prefix` (ignoring ASCII case) in name order, imports left
+/// out. A range scan over `idx_names_text_nocase`: no sorting, so the cost depends on `limit`
+/// rather than on how many names share the prefix
+pub fn complete_names(conn: &Connection, prefix: &str, limit: usize) -> Result<Vec<Completion>> {
+    let mut stmt = conn.prepare_cached(COMPLETE_SQL)?;
+    // Every name starting with the prefix sorts below the prefix followed by the largest char
+    let upper = format!("{}{}", prefix, char::MAX);
+    let results = stmt
+        .query_map(params![prefix, upper, limit as i64], |row| {
+            Ok(Completion { name: row.get(0)?, kind: row.get(1)?, path: row.get(2)?, line: row.get(3)? })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(results)
+}
+
+/// Find files whose path contains `pattern`, ignoring ASCII case unless `case_sensitive`;
+/// returns the files in `window` and how many match
+pub fn find_files(conn: &Connection, pattern: &str, window: Window, case_sensitive: bool) -> Result<(Vec<String>, usize)> {
+    let sql = if case_sensitive {
+        "SELECT path FROM files WHERE instr(path, ?1) > 0"
+    } else {
+        "SELECT path FROM files WHERE path LIKE ?1"
+    };
+    let pattern = if case_sensitive { pattern.to_string() } else { format!("%{}%", pattern) };
+    query_window(conn, sql, &[Box::new(pattern)], window, |row| row.get(0))
 }
 
 /// Find symbols by name (exact match first, then prefix/contains if no results)
@@ -541,7 +1740,7 @@ pub fn find_symbols_by_name(
     // Try exact match first
     let exact_query = if kind.is_some() {
         r#"
-        SELECT s.name, s.kind, s.line, s.signature, f.path
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
         FROM symbols s
         JOIN files f ON s.file_id = f.id
         WHERE s.name = ?1 AND s.kind = ?2
@@ -549,7 +1748,7 @@ pub fn find_symbols_by_name(
         "#
     } else {
         r#"
-        SELECT s.name, s.kind, s.line, s.signature, f.path
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
         FROM symbols s
         JOIN files f ON s.file_id = f.id
         WHERE s.name = ?1
@@ -560,26 +1759,10 @@ pub fn find_symbols_by_name(
     let mut stmt = conn.prepare(exact_query)?;
 
     let results: Vec<SearchResult> = if let Some(k) = kind {
-        stmt.query_map(params![name, k, limit as i64], |row| {
-            Ok(SearchResult {
-                name: row.get(0)?,
-                kind: row.get(1)?,
-                line: row.get(2)?,
-                signature: row.get(3)?,
-                path: row.get(4)?,
-            })
-        })?
+        stmt.query_map(params![name, k, limit as i64], SearchResult::from_row)?
         .collect::<Result<Vec<_>, _>>()?
     } else {
-        stmt.query_map(params![name, limit as i64], |row| {
-            Ok(SearchResult {
-                name: row.get(0)?,
-                kind: row.get(1)?,
-                line: row.get(2)?,
-                signature: row.get(3)?,
-                path: row.get(4)?,
-            })
-        })?
+        stmt.query_map(params![name, limit as i64], SearchResult::from_row)?
         .collect::<Result<Vec<_>, _>>()?
     };
 
@@ -588,7 +1771,7 @@ pub fn find_symbols_by_name(
         let pattern = format!("{}%", name);
         let prefix_query = if kind.is_some() {
             r#"
-            SELECT s.name, s.kind, s.line, s.signature, f.path
+            SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
             FROM symbols s
             JOIN files f ON s.file_id = f.id
             WHERE s.name LIKE ?1 AND s.kind = ?2
@@ -597,7 +1780,7 @@ pub fn find_symbols_by_name(
             "#
         } else {
             r#"
-            SELECT s.name, s.kind, s.line, s.signature, f.path
+            SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
             FROM symbols s
             JOIN files f ON s.file_id = f.id
             WHERE s.name LIKE ?1
@@ -608,26 +1791,10 @@ pub fn find_symbols_by_name(
 
         let mut stmt = conn.prepare(prefix_query)?;
         let results: Vec<SearchResult> = if let Some(k) = kind {
-            stmt.query_map(params![pattern, k, limit as i64], |row| {
-                Ok(SearchResult {
-                    name: row.get(0)?,
-                    kind: row.get(1)?,
-                    line: row.get(2)?,
-                    signature: row.get(3)?,
-                    path: row.get(4)?,
-                })
-            })?
+            stmt.query_map(params![pattern, k, limit as i64], SearchResult::from_row)?
             .collect::<Result<Vec<_>, _>>()?
         } else {
-            stmt.query_map(params![pattern, limit as i64], |row| {
-                Ok(SearchResult {
-                    name: row.get(0)?,
-                    kind: row.get(1)?,
-                    line: row.get(2)?,
-                    signature: row.get(3)?,
-                    path: row.get(4)?,
-                })
-            })?
+            stmt.query_map(params![pattern, limit as i64], SearchResult::from_row)?
             .collect::<Result<Vec<_>, _>>()?
         };
         return Ok(results);
@@ -644,7 +1811,7 @@ pub fn find_class_like(
 ) -> Result<Vec<SearchResult>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT s.name, s.kind, s.line, s.signature, f.path
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
         FROM symbols s
         JOIN files f ON s.file_id = f.id
         WHERE s.name = ?1 AND s.kind IN ('class', 'interface', 'object', 'enum', 'protocol', 'struct', 'actor', 'package')
@@ -653,21 +1820,14 @@ pub fn find_class_like(
     )?;
 
     let results = stmt
-        .query_map(params![name, limit as i64], |row| {
-            Ok(SearchResult {
-                name: row.get(0)?,
-                kind: row.get(1)?,
-                line: row.get(2)?,
-                signature: row.get(3)?,
-                path: row.get(4)?,
-            })
-        })?
+        .query_map(params![name, limit as i64], SearchResult::from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(results)
 }
 
-/// Find implementations (subclasses/implementors)
+/// Find implementations (subclasses/implementors).
+/// Types listed in a sealed parent's `permits` clause are included after direct subtypes.
 pub fn find_implementations(
     conn: &Connection,
     parent_name: &str,
@@ -678,46 +1838,406 @@ pub fn find_implementations(
     let contains_pattern = format!("%{}%", parent_name);
     let mut stmt = conn.prepare(
         r#"
-        SELECT s.name, s.kind, s.line, s.signature, f.path
-        FROM inheritance i
-        JOIN symbols s ON i.child_id = s.id
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM (
+            SELECT i.child_id AS id,
+                CASE
+                    WHEN i.parent_name = ?1 THEN 0
+                    WHEN i.parent_name LIKE ?2 THEN 1
+                    ELSE 2
+                END AS rank
+            FROM inheritance i
+            WHERE i.kind != 'permits'
+              AND (i.parent_name = ?1 OR i.parent_name LIKE ?2 OR i.parent_name LIKE ?3)
+            UNION ALL
+            SELECT sub.id, 3
+            FROM inheritance i
+            JOIN symbols sealed ON i.child_id = sealed.id
+            JOIN symbols sub ON sub.name = i.parent_name
+            WHERE i.kind = 'permits' AND sealed.name = ?1
+              AND sub.kind IN ('class', 'interface', 'enum', 'object')
+        ) m
+        JOIN symbols s ON m.id = s.id
         JOIN files f ON s.file_id = f.id
-        WHERE i.parent_name = ?1 OR i.parent_name LIKE ?2 OR i.parent_name LIKE ?3
-        ORDER BY
-            CASE
-                WHEN i.parent_name = ?1 THEN 0
-                WHEN i.parent_name LIKE ?2 THEN 1
-                ELSE 2
-            END, s.name
+        GROUP BY s.id
+        ORDER BY MIN(m.rank), s.name
         LIMIT ?4
         "#,
     )?;
 
     let results = stmt
-        .query_map(params![parent_name, suffix_pattern, contains_pattern, limit as i64], |row| {
-            Ok(SearchResult {
-                name: row.get(0)?,
-                kind: row.get(1)?,
-                line: row.get(2)?,
-                signature: row.get(3)?,
-                path: row.get(4)?,
-            })
-        })?
+        .query_map(params![parent_name, suffix_pattern, contains_pattern, limit as i64], SearchResult::from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(results)
 }
 
+/// An implementation found by `find_implementations_transitive`
+#[derive(Debug, Serialize)]
+pub struct Implementation {
+    #[serde(flatten)]
+    pub symbol: SearchResult,
+    /// Implementor it inherits from, for implementations that are not direct
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub via: Option<String>,
+}
+
+/// Implementations of `parent`, then of each implementor in turn: `class Card : BasePayment`
+/// counts for `Payable` when `BasePayment` implements it. Direct matches come first as in
+/// `find_implementations`, plus Go types by method set; deeper levels match parent names
+/// exactly (generic arguments and package qualifiers aside). Each declaration is listed once.
+pub fn find_implementations_transitive(conn: &Connection, parent: &str, limit: usize) -> Result<Vec<Implementation>> {
+    let mut direct = find_implementations(conn, parent, limit)?;
+    for found in find_go_implementations(conn, parent, limit)? {
+        if !direct.iter().any(|s| s.path == found.path && s.line == found.line) {
+            direct.push(found);
+        }
+    }
+
+    let mut seen: HashSet<(String, i64)> = HashSet::new();
+    let mut results: Vec<Implementation> = direct
+        .into_iter()
+        .filter(|s| seen.insert((s.path.clone(), s.line)))
+        .map(|symbol| Implementation { symbol, via: None })
+        .collect();
+
+    let mut subtypes_stmt = conn.prepare(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM inheritance i
+        JOIN symbols s ON i.child_id = s.id
+        JOIN files f ON s.file_id = f.id
+        WHERE i.kind != 'permits'
+          AND (i.parent_name = ?1 OR i.parent_name LIKE '%.' || ?1 OR i.parent_name LIKE ?1 || '<%')
+        ORDER BY s.name, f.path, s.line
+        "#,
+    )?;
+    let mut expanded: HashSet<String> = HashSet::from([parent.to_string()]);
+    let mut next = 0;
+    while next < results.len() && results.len() < limit {
+        // Swift extensions and Rust impl blocks stand for the type they extend
+        let declared = &results[next].symbol.name;
+        let name = declared
+            .strip_suffix("+Extension")
+            .or_else(|| declared.strip_prefix("impl ").and_then(|rest| rest.rsplit_once(" for ")).map(|(_, ty)| ty))
+            .unwrap_or(declared)
+            .to_string();
+        next += 1;
+        if !expanded.insert(name.clone()) {
+            continue;
+        }
+        let subtypes: Vec<SearchResult> =
+            subtypes_stmt.query_map(params![name], SearchResult::from_row)?.collect::<Result<_, _>>()?;
+        for symbol in subtypes {
+            if seen.insert((symbol.path.clone(), symbol.line)) {
+                results.push(Implementation { symbol, via: Some(name.clone()) });
+            }
+        }
+    }
+    results.truncate(limit);
+    Ok(results)
+}
+
+/// Find Go types that satisfy an interface structurally: types in the same package as
+/// methods named like every method of the interface, including methods of embedded
+/// interfaces that are in the index. Receiver kinds and signatures are not compared.
+pub fn find_go_implementations(conn: &Connection, interface: &str, limit: usize) -> Result<Vec<SearchResult>> {
+    let package_dir = |path: &str| path.rsplit_once('/').map_or(String::new(), |(dir, _)| dir.to_string());
+
+    let mut iface_stmt = conn.prepare(
+        "SELECT s.id, s.name, f.path FROM symbols s JOIN files f ON s.file_id = f.id
+         WHERE s.name = ?1 AND s.kind = 'interface' AND f.path LIKE '%.go'",
+    )?;
+    let mut methods_stmt = conn.prepare(
+        "SELECT s.name FROM symbols s JOIN files f ON s.file_id = f.id
+         WHERE f.path = ?1 AND s.kind = 'function' AND s.container = ?2",
+    )?;
+    let mut embedded_stmt = conn.prepare("SELECT parent_name FROM inheritance WHERE child_id = ?1 AND kind = 'extends'")?;
+
+    // Method set of the interface, following embedded interfaces
+    let mut required: HashSet<String> = HashSet::new();
+    let mut seen: HashSet<i64> = HashSet::new();
+    let mut pending = vec![interface.to_string()];
+    while let Some(name) = pending.pop() {
+        let found: Vec<(i64, String, String)> = iface_stmt
+            .query_map(params![name], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<_, _>>()?;
+        for (id, iface_name, path) in found {
+            if !seen.insert(id) {
+                continue;
+            }
+            for method in methods_stmt.query_map(params![path, iface_name], |row| row.get::<_, String>(0))? {
+                required.insert(method?);
+            }
+            for parent in embedded_stmt.query_map(params![id], |row| row.get::<_, String>(0))? {
+                pending.push(parent?);
+            }
+        }
+    }
+    if required.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // (package dir, receiver type) pairs that have every required method
+    let mut receivers_stmt = conn.prepare(
+        "SELECT s.container, f.path FROM symbols s JOIN files f ON s.file_id = f.id
+         WHERE s.name = ?1 AND s.kind = 'function' AND s.container IS NOT NULL AND f.path LIKE '%.go'",
+    )?;
+    let mut candidates: Option<HashSet<(String, String)>> = None;
+    for method in &required {
+        let have: HashSet<(String, String)> = receivers_stmt
+            .query_map(params![method], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .map(|r| r.map(|(container, path)| (package_dir(&path), container)))
+            .collect::<Result<_, _>>()?;
+        candidates = Some(match candidates {
+            None => have,
+            Some(prev) => prev.intersection(&have).cloned().collect(),
+        });
+    }
+
+    let mut type_stmt = conn.prepare(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.name = ?1 AND s.kind IN ('class', 'typealias') AND f.path LIKE '%.go'
+        "#,
+    )?;
+    let mut results = Vec::new();
+    for (dir, type_name) in candidates.unwrap_or_default() {
+        for found in type_stmt.query_map(params![type_name], SearchResult::from_row)? {
+            let found = found?;
+            if package_dir(&found.path) == dir {
+                results.push(found);
+            }
+        }
+    }
+    results.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+    results.truncate(limit);
+    Ok(results)
+}
+
+/// One file depending on another, for `ast-index graph`
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FileDependency {
+    pub from: String,
+    pub to: String,
+    /// Distinct symbols of `to` that `from` imports or references
+    pub symbols: usize,
+}
+
+/// File-to-file dependencies from the imports and refs tables: an import of a symbol's
+/// qualified name, or a reference to a type or top-level function declared in exactly one file.
+/// Names declared in several files cannot be told apart without an import and are left out.
+pub fn file_dependencies(conn: &Connection) -> Result<Vec<FileDependency>> {
+    let mut stmt = conn.prepare(
+        r#"
+        WITH unique_decls AS (
+            SELECT name, MIN(file_id) AS file_id, MIN(id) AS id
+            FROM symbols
+            WHERE kind IN ('class', 'interface', 'object', 'enum', 'typealias')
+               OR (kind = 'function' AND container IS NULL)
+            GROUP BY name
+            HAVING COUNT(DISTINCT file_id) = 1
+        ),
+        uses AS (
+            SELECT i.file_id AS from_id, s.file_id AS to_id, s.id AS symbol_id
+            FROM imports i
+            JOIN symbols s ON s.name = i.name AND s.qualified_name = i.path AND s.kind != 'import'
+            UNION
+            SELECT r.file_id, d.file_id, d.id
+            FROM refs r
+            JOIN unique_decls d ON d.name = r.name
+        )
+        SELECT fa.path, fb.path, COUNT(DISTINCT u.symbol_id)
+        FROM uses u
+        JOIN files fa ON u.from_id = fa.id
+        JOIN files fb ON u.to_id = fb.id
+        WHERE u.from_id != u.to_id
+        GROUP BY u.from_id, u.to_id
+        ORDER BY fa.path, fb.path
+        "#,
+    )?;
+    let deps = stmt
+        .query_map([], |row| {
+            Ok(FileDependency { from: row.get(0)?, to: row.get(1)?, symbols: row.get::<_, i64>(2)? as usize })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(deps)
+}
+
+/// Where `reachable_symbols` starts
+pub struct EntryPoints<'a> {
+    /// Annotation names, matched ignoring case and any package or path prefix
+    pub annotations: &'a [String],
+    /// Function names
+    pub functions: &'a [String],
+    /// Class names used from outside the code, like Android manifests; classes named in layouts
+    /// and storyboards are read from the index
+    pub classes: &'a [String],
+    /// Everything declared in test files
+    pub tests: bool,
+}
+
+/// Ids of the symbols reachable from entry points over references and calls.
+///
+/// A reference or call belongs to the innermost declaration whose span contains it; ones outside
+/// every declaration (imports, top-level statements) belong to the file and count once anything
+/// in it is reached. Reaching a symbol reaches every declaration of each name it uses, the
+/// declarations enclosing it and, for a type, its overriding members, which callers reach
+/// through a supertype. Names resolve without imports, so more is reachable than really is;
+/// uses inside the declaring file are known only from calls, as refs leave them out.
+pub fn reachable_symbols(conn: &Connection, entry: &EntryPoints) -> Result<HashSet<i64>> {
+    struct Decl {
+        id: i64,
+        file_id: i64,
+        name: String,
+        kind: String,
+        line: i64,
+        end_line: Option<i64>,
+        is_test: bool,
+        overrides: bool,
+    }
+
+    let java_overrides: HashSet<i64> = conn
+        .prepare("SELECT symbol_id FROM symbol_annotations WHERE name = 'Override'")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    // Outer declarations before inner ones starting on the same line
+    let decls: Vec<Decl> = conn
+        .prepare(
+            "SELECT s.id, s.file_id, s.name, s.kind, s.line, s.end_line, f.is_test, COALESCE(s.signature, '')
+             FROM symbols s JOIN files f ON s.file_id = f.id
+             WHERE s.kind NOT IN ('import', 'annotation')
+             ORDER BY s.file_id, s.line, s.end_line DESC",
+        )?
+        .query_map([], |row| {
+            let id: i64 = row.get(0)?;
+            let signature: String = row.get(7)?;
+            Ok(Decl {
+                id,
+                file_id: row.get(1)?,
+                name: row.get(2)?,
+                kind: row.get(3)?,
+                line: row.get(4)?,
+                end_line: row.get(5)?,
+                is_test: row.get(6)?,
+                overrides: java_overrides.contains(&id) || signature.split_whitespace().any(|w| w == "override"),
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut by_id: HashMap<i64, usize> = HashMap::new();
+    for (i, d) in decls.iter().enumerate() {
+        by_name.entry(d.name.as_str()).or_default().push(i);
+        by_id.insert(d.id, i);
+    }
+
+    // Enclosing declaration of each one, found with a stack of open spans per file
+    let mut parent: Vec<Option<usize>> = vec![None; decls.len()];
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); decls.len()];
+    let mut open: Vec<usize> = Vec::new();
+    for (i, d) in decls.iter().enumerate() {
+        open.retain(|&o| decls[o].file_id == d.file_id && decls[o].end_line.is_some_and(|end| end >= d.line));
+        if let Some(&outer) = open.last() {
+            parent[i] = Some(outer);
+            members[outer].push(i);
+        }
+        if d.end_line.is_some() {
+            open.push(i);
+        }
+    }
+
+    // Names each declaration uses; refs come sorted so one sweep finds their owners
+    let mut uses: Vec<Vec<String>> = vec![Vec::new(); decls.len()];
+    let mut file_uses: HashMap<i64, Vec<String>> = HashMap::new();
+    let mut refs_stmt = conn.prepare("SELECT file_id, name, line FROM refs ORDER BY file_id, line")?;
+    let mut refs = refs_stmt.query([])?;
+    let mut next = 0;
+    open.clear();
+    while let Some(row) = refs.next()? {
+        let (file_id, name, line): (i64, String, i64) = (row.get(0)?, row.get(1)?, row.get(2)?);
+        while next < decls.len() && (decls[next].file_id, decls[next].line) <= (file_id, line) {
+            open.push(next);
+            next += 1;
+        }
+        open.retain(|&o| decls[o].file_id == file_id && decls[o].end_line.is_some_and(|end| end >= line));
+        match open.last() {
+            Some(&owner) => uses[owner].push(name),
+            None => file_uses.entry(file_id).or_default().push(name),
+        }
+    }
+    let mut calls_stmt = conn.prepare("SELECT caller_id, callee FROM calls")?;
+    let calls = calls_stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?;
+    for call in calls {
+        let (caller, callee) = call?;
+        if let Some(&i) = by_id.get(&caller) {
+            uses[i].push(callee);
+        }
+    }
+
+    // Entry points
+    let annotations: HashSet<String> = entry.annotations.iter().map(|a| a.to_lowercase()).collect();
+    let mut pending: Vec<usize> = Vec::new();
+    let mut annotated = conn.prepare("SELECT symbol_id, name FROM symbol_annotations")?;
+    for row in annotated.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))? {
+        let (id, name) = row?;
+        let simple = name.rsplit(['.', ':']).next().unwrap_or(&name).to_lowercase();
+        if annotations.contains(&name.to_lowercase()) || annotations.contains(&simple) {
+            pending.extend(by_id.get(&id));
+        }
+    }
+    for name in entry.functions {
+        pending.extend(by_name.get(name.as_str()).into_iter().flatten().filter(|&&i| decls[i].kind == "function"));
+    }
+    let mut classes: Vec<String> = entry.classes.to_vec();
+    for table in ["xml_usages", "storyboard_usages"] {
+        let mut stmt = conn.prepare(&format!("SELECT DISTINCT class_name FROM {}", table))?;
+        for name in stmt.query_map([], |row| row.get::<_, String>(0))? {
+            let name = name?;
+            classes.push(name.rsplit('.').next().unwrap_or(&name).to_string());
+        }
+    }
+    for name in &classes {
+        pending.extend(by_name.get(name.as_str()).into_iter().flatten());
+    }
+    if entry.tests {
+        pending.extend(decls.iter().enumerate().filter(|(_, d)| d.is_test).map(|(i, _)| i));
+    }
+
+    let mut reached = vec![false; decls.len()];
+    let mut live_files: HashSet<i64> = HashSet::new();
+    while let Some(i) = pending.pop() {
+        if std::mem::replace(&mut reached[i], true) {
+            continue;
+        }
+        let d = &decls[i];
+        let file_names = live_files.insert(d.file_id).then(|| file_uses.get(&d.file_id)).flatten();
+        for name in uses[i].iter().chain(file_names.into_iter().flatten()) {
+            pending.extend(by_name.get(name.as_str()).into_iter().flatten());
+        }
+        pending.extend(parent[i]);
+        pending.extend(members[i].iter().filter(|&&m| decls[m].overrides));
+    }
+    Ok(decls.iter().zip(reached).filter(|(_, r)| *r).map(|(d, _)| d.id).collect())
+}
+
 /// Get database statistics
 pub fn get_stats(conn: &Connection) -> Result<DbStats> {
     let file_count: i64 = conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))?;
     let symbol_count: i64 = conn.query_row("SELECT COUNT(*) FROM symbols", [], |row| row.get(0))?;
     let module_count: i64 = conn.query_row("SELECT COUNT(*) FROM modules", [], |row| row.get(0))?;
-    let refs_count: i64 = conn.query_row("SELECT COUNT(*) FROM refs", [], |row| row.get(0)).unwrap_or(0);
+    let refs_count: i64 = conn.query_row("SELECT COALESCE(SUM(COALESCE(count, 1)), 0) FROM refs", [], |row| row.get(0)).unwrap_or(0);
     let xml_usages_count: i64 = conn.query_row("SELECT COUNT(*) FROM xml_usages", [], |row| row.get(0)).unwrap_or(0);
     let resources_count: i64 = conn.query_row("SELECT COUNT(*) FROM resources", [], |row| row.get(0)).unwrap_or(0);
     let storyboard_usages_count: i64 = conn.query_row("SELECT COUNT(*) FROM storyboard_usages", [], |row| row.get(0)).unwrap_or(0);
     let ios_assets_count: i64 = conn.query_row("SELECT COUNT(*) FROM ios_assets", [], |row| row.get(0)).unwrap_or(0);
+    let fts_enabled = has_fts_index(conn);
+    let skipped_files = conn
+        .prepare("SELECT skipped, COUNT(*) FROM files WHERE skipped IS NOT NULL GROUP BY skipped")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
 
     Ok(DbStats {
         file_count,
@@ -728,6 +2248,8 @@ pub fn get_stats(conn: &Connection) -> Result<DbStats> {
         resources_count,
         storyboard_usages_count,
         ios_assets_count,
+        fts_enabled,
+        skipped_files,
     })
 }
 
@@ -741,6 +2263,114 @@ pub struct DbStats {
     pub resources_count: i64,
     pub storyboard_usages_count: i64,
     pub ios_assets_count: i64,
+    /// false when search runs in degraded LIKE mode (no FTS5)
+    pub fts_enabled: bool,
+    /// Files recorded without parsing, by `SkipReason`
+    pub skipped_files: BTreeMap<String, i64>,
+}
+
+/// Index record of one file, for `explain`
+#[derive(Debug, Serialize)]
+pub struct FileReport {
+    pub path: String,
+    pub size: i64,
+    pub mtime: i64,
+    pub encoding: Option<String>,
+    pub language: Option<String>,
+    pub is_test: bool,
+    /// `SkipReason` name if the file was not parsed
+    pub skipped: Option<String>,
+    pub symbols: i64,
+    pub refs: i64,
+    pub imports: i64,
+}
+
+/// What the index holds for the file at `path` (relative to the project root)
+pub fn file_report(conn: &Connection, path: &str) -> Result<Option<FileReport>> {
+    let report = conn.query_row(
+        "SELECT f.path, f.size, f.mtime, f.encoding, f.language, f.is_test, f.skipped,
+                (SELECT COUNT(*) FROM symbols s WHERE s.file_id = f.id),
+                (SELECT COALESCE(SUM(COALESCE(r.count, 1)), 0) FROM refs r WHERE r.file_id = f.id),
+                (SELECT COUNT(*) FROM imports i WHERE i.file_id = f.id)
+         FROM files f WHERE f.path = ?1",
+        params![path],
+        |row| {
+            Ok(FileReport {
+                path: row.get(0)?,
+                size: row.get(1)?,
+                mtime: row.get(2)?,
+                encoding: row.get(3)?,
+                language: row.get(4)?,
+                is_test: row.get(5)?,
+                skipped: row.get(6)?,
+                symbols: row.get(7)?,
+                refs: row.get(8)?,
+                imports: row.get(9)?,
+            })
+        },
+    );
+    match report {
+        Ok(report) => Ok(Some(report)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Files recorded without parsing, largest first: (path, reason, size)
+pub fn skipped_files(conn: &Connection, limit: usize) -> Result<Vec<(String, String, i64)>> {
+    let rows = conn
+        .prepare("SELECT path, skipped, size FROM files WHERE skipped IS NOT NULL ORDER BY size DESC, path LIMIT ?1")?
+        .query_map(params![limit as i64], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<_, _>>()?;
+    Ok(rows)
+}
+
+/// Counts for one indexed file, summed per area by `stats --by`
+#[derive(Debug, Serialize)]
+pub struct FileStats {
+    pub path: String,
+    pub language: Option<String>,
+    pub is_test: bool,
+    pub size: i64,
+    pub refs: i64,
+    /// Symbols by kind
+    pub symbols: BTreeMap<String, i64>,
+}
+
+/// Symbol and reference counts of every indexed file, by path
+pub fn file_stats(conn: &Connection) -> Result<Vec<FileStats>> {
+    let mut stmt = conn.prepare(
+        "SELECT f.id, f.path, f.language, f.is_test, f.size, (SELECT COALESCE(SUM(COALESCE(r.count, 1)), 0) FROM refs r WHERE r.file_id = f.id) FROM files f ORDER BY f.path",
+    )?;
+    let mut ids = HashMap::new();
+    let mut files = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, FileStats {
+                path: row.get(1)?,
+                language: row.get(2)?,
+                is_test: row.get(3)?,
+                size: row.get(4)?,
+                refs: row.get(5)?,
+                symbols: BTreeMap::new(),
+            }))
+        })?
+        .enumerate()
+        .map(|(i, row)| {
+            let (id, file) = row?;
+            ids.insert(id, i);
+            Ok(file)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut stmt = conn.prepare("SELECT file_id, kind, COUNT(*) FROM symbols GROUP BY file_id, kind")?;
+    let counts = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?)))?;
+    for count in counts {
+        let (file_id, kind, count) = count?;
+        if let Some(&i) = ids.get(&file_id) {
+            files[i].symbols.insert(kind, count);
+        }
+    }
+    Ok(files)
 }
 
 /// Clear all data from the database
@@ -755,11 +2385,18 @@ pub fn clear_db(conn: &Connection) -> Result<()> {
         DELETE FROM xml_usages;
         DELETE FROM transitive_deps;
         DELETE FROM refs;
+        DELETE FROM imports;
+        DELETE FROM symbol_annotations;
+        DELETE FROM symbol_embeddings;
+        DELETE FROM calls;
+        DELETE FROM di_bindings;
         DELETE FROM inheritance;
         DELETE FROM module_deps;
         DELETE FROM modules;
-        DELETE FROM symbols;
+        DELETE FROM symbols_data;
         DELETE FROM files;
+        DELETE FROM name_trigrams;
+        DELETE FROM symbol_names;
         "#,
     )?;
     Ok(())
@@ -772,6 +2409,52 @@ pub struct RefResult {
     pub line: i64,
     pub context: Option<String>,
     pub path: String,
+    /// `RefKind` name; None for indexes built before kinds were stored
+    pub ref_kind: Option<String>,
+    /// Usages in the file this result stands for (more than 1 with aggregated refs); `line` and
+    /// `context` are the first one's
+    #[serde(skip_serializing_if = "is_one")]
+    pub count: i64,
+    /// Lines of the first usages when `count` > 1
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sample_lines: Vec<i64>,
+}
+
+fn is_one(count: &i64) -> bool {
+    *count == 1
+}
+
+impl RefResult {
+    /// Build from a row selected as `r.name, r.line, r.context, f.path, r.ref_kind, r.count,
+    /// r.sample_lines`
+    pub fn from_row(row: &rusqlite::Row) -> rusqlite::Result<RefResult> {
+        let sample_lines: Option<String> = row.get(6)?;
+        Ok(RefResult {
+            name: row.get(0)?,
+            line: row.get(1)?,
+            context: row.get(2)?,
+            path: row.get(3)?,
+            ref_kind: row.get(4)?,
+            count: row.get::<_, Option<i64>>(5)?.unwrap_or(1),
+            sample_lines: sample_lines
+                .iter()
+                .flat_map(|lines| lines.split(','))
+                .filter_map(|line| line.parse().ok())
+                .collect(),
+        })
+    }
+
+    /// `(12 usages: lines 4, 9, 30, ...)` for an aggregated result
+    pub fn aggregate_note(&self) -> Option<String> {
+        if self.count <= 1 {
+            return None;
+        }
+        let mut lines: Vec<String> = self.sample_lines.iter().map(|l| l.to_string()).collect();
+        if (lines.len() as i64) < self.count {
+            lines.push("...".to_string());
+        }
+        Some(format!("({} usages: lines {})", self.count, lines.join(", ")))
+    }
 }
 
 /// Find references (usages) of a symbol
@@ -780,41 +2463,194 @@ pub fn find_references(
     name: &str,
     limit: usize,
 ) -> Result<Vec<RefResult>> {
-    let mut stmt = conn.prepare(
+    find_references_of_kind(conn, name, None, None, limit, &SearchScope::none())
+}
+
+/// Find references (usages) of a symbol, optionally only those of one `RefKind`
+/// and calls with `arity` arguments, in files matching `scope`
+pub fn find_references_of_kind(
+    conn: &Connection,
+    name: &str,
+    kind: Option<RefKind>,
+    arity: Option<usize>,
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<RefResult>> {
+    let (scope_clause, scope_params) = scope.path_condition();
+    let sql = format!(
         r#"
-        SELECT r.name, r.line, r.context, f.path
+        SELECT r.name, r.line, r.context, f.path, r.ref_kind, r.count, r.sample_lines
         FROM refs r
         JOIN files f ON r.file_id = f.id
-        WHERE r.name = ?1
+        WHERE r.name = ?1 AND (?2 IS NULL OR r.ref_kind = ?2) AND (?3 IS NULL OR r.arity = ?3){}
         ORDER BY f.path, r.line
-        LIMIT ?2
+        LIMIT ?{}
         "#,
-    )?;
+        scope_clause,
+        4 + scope_params.len()
+    );
+
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![
+        Box::new(name.to_string()),
+        Box::new(kind.map(|k| k.as_str())),
+        Box::new(arity.map(|a| a as i64)),
+    ];
+    for p in scope_params {
+        all_params.push(Box::new(p));
+    }
+    all_params.push(Box::new(limit as i64));
 
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
     let results = stmt
-        .query_map(params![name, limit as i64], |row| {
-            Ok(RefResult {
-                name: row.get(0)?,
-                line: row.get(1)?,
-                context: row.get(2)?,
-                path: row.get(3)?,
-            })
-        })?
+        .query_map(param_refs.as_slice(), RefResult::from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(results)
 }
 
+/// Drop `string` references that name no indexed symbol; run once all files are indexed
+pub fn prune_string_refs(conn: &Connection) -> Result<usize> {
+    // Deletes through the refs view report no changes, so count first
+    const UNDECLARED: &str = "ref_kind = 'string' AND name NOT IN (SELECT name FROM symbols)";
+    let count: usize = conn.query_row(&format!("SELECT COUNT(*) FROM refs WHERE {}", UNDECLARED), [], |row| row.get(0))?;
+    if count > 0 {
+        conn.execute(&format!("DELETE FROM refs WHERE {}", UNDECLARED), [])?;
+    }
+    Ok(count)
+}
+
+/// Bring `symbol_names`/`name_trigrams` in line with the names in `symbols`; run once all files are indexed
+pub fn sync_name_trigrams(conn: &Connection) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(
+        r#"
+        DELETE FROM name_trigrams WHERE name_id IN (SELECT id FROM symbol_names WHERE name NOT IN (SELECT name FROM symbols));
+        DELETE FROM symbol_names WHERE name NOT IN (SELECT name FROM symbols);
+        "#,
+    )?;
+    let new_names: Vec<String> = tx
+        .prepare("SELECT DISTINCT name FROM symbols WHERE name NOT IN (SELECT name FROM symbol_names)")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    {
+        let mut name_stmt = tx.prepare("INSERT INTO symbol_names (name) VALUES (?1)")?;
+        let mut trigram_stmt = tx.prepare("INSERT INTO name_trigrams (trigram, name_id) VALUES (?1, ?2)")?;
+        for name in &new_names {
+            name_stmt.execute(params![name])?;
+            let name_id = tx.last_insert_rowid();
+            for trigram in fuzzy::trigrams(name) {
+                trigram_stmt.execute(params![trigram, name_id])?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Bits of a `content_fts` rowid that hold the line number; the file id takes the rest
+const CONTENT_LINE_BITS: i64 = 20;
+
+/// Source lines for `[index] content_fts`, keyed by `file_id << 20 | line` so a file's lines are
+/// one rowid range; the trigram tokenizer matches any substring of three or more characters
+const CONTENT_FTS_SCHEMA: &str = "
+    CREATE VIRTUAL TABLE IF NOT EXISTS content_fts USING fts5(text, tokenize='trigram');
+    CREATE TRIGGER IF NOT EXISTS content_fts_files_delete AFTER DELETE ON files BEGIN
+        DELETE FROM content_fts WHERE rowid BETWEEN OLD.id << 20 AND (OLD.id << 20) + 1048575;
+    END;
+";
+
+/// Create the line index searched instead of the files on disk; `rebuild` fills it
+pub fn create_content_fts(conn: &Connection) -> Result<()> {
+    if !fts5_available(conn) {
+        anyhow::bail!("content_fts needs an SQLite build with FTS5");
+    }
+    // The trigram tokenizer came with SQLite 3.34; a system library may be older
+    if rusqlite::version_number() < 3_034_000 {
+        anyhow::bail!("content_fts needs SQLite 3.34 or newer, this build uses {}", rusqlite::version());
+    }
+    conn.execute_batch(CONTENT_FTS_SCHEMA)?;
+    Ok(())
+}
+
+pub fn drop_content_fts(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TRIGGER IF EXISTS content_fts_files_delete; DROP TABLE IF EXISTS content_fts;")?;
+    Ok(())
+}
+
+/// Whether the index has the line index of `[index] content_fts`
+pub fn has_content_fts(conn: &Connection) -> bool {
+    conn.query_row("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'content_fts'", [], |_| Ok(()))
+        .is_ok()
+}
+
+/// Lines `content_fts` can key: line numbers fit in `CONTENT_LINE_BITS`
+pub fn content_line_fits(line: usize) -> bool {
+    line < 1 << CONTENT_LINE_BITS
+}
+
+/// `content_fts` rowid of `line` of the file `file_id`
+pub fn content_rowid(file_id: i64, line: usize) -> i64 {
+    file_id << CONTENT_LINE_BITS | line as i64
+}
+
+/// Drop the `content_fts` lines of the file at `path`, before it is written again
+pub fn delete_content_lines(conn: &Connection, path: &str) -> Result<()> {
+    let file_id: i64 = match conn.query_row("SELECT id FROM files WHERE path = ?1", params![path], |row| row.get(0)) {
+        Ok(id) => id,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    conn.prepare_cached("DELETE FROM content_fts WHERE rowid BETWEEN ?1 AND ?2")?
+        .execute(params![content_rowid(file_id, 0), content_rowid(file_id + 1, 0) - 1])?;
+    Ok(())
+}
+
+/// Indexed lines containing `query` (ignoring case), by path and line, as (path, line, text);
+/// `keep` narrows them further (case, word boundaries, scope) before `limit` applies
+pub fn search_content(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+    mut keep: impl FnMut(&str, &str) -> bool,
+) -> Result<Vec<(String, usize, String)>> {
+    // Trigrams need three characters; shorter queries scan the lines with LIKE
+    let (condition, pattern) = if query.chars().count() >= 3 {
+        ("c.text MATCH ?1", format!("\"{}\"", query.replace('"', "\"\"")))
+    } else {
+        let escaped = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        ("c.text LIKE ?1 ESCAPE '\\'", format!("%{}%", escaped))
+    };
+    let sql = format!(
+        "SELECT f.path, c.rowid & {mask}, c.text FROM content_fts c JOIN files f ON f.id = c.rowid >> {bits} \
+         WHERE {condition} ORDER BY c.rowid",
+        mask = (1 << CONTENT_LINE_BITS) - 1,
+        bits = CONTENT_LINE_BITS,
+        condition = condition,
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query(params![pattern])?;
+    let mut matches = vec![];
+    while matches.len() < limit {
+        let Some(row) = rows.next()? else { break };
+        let (path, line, text): (String, i64, String) = (row.get(0)?, row.get(1)?, row.get(2)?);
+        if keep(&path, &text) {
+            matches.push((path, line as usize, text));
+        }
+    }
+    Ok(matches)
+}
+
 /// Count references in the database
 pub fn count_refs(conn: &Connection) -> Result<i64> {
-    Ok(conn.query_row("SELECT COUNT(*) FROM refs", [], |row| row.get(0))?)
+    Ok(conn.query_row("SELECT COALESCE(SUM(COALESCE(count, 1)), 0) FROM refs", [], |row| row.get(0))?)
 }
 
 /// Find import statements for a symbol name
 pub fn find_imports(conn: &Connection, name: &str, limit: usize) -> Result<Vec<SearchResult>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT s.name, s.kind, s.line, s.signature, f.path
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
         FROM symbols s
         JOIN files f ON s.file_id = f.id
         WHERE s.kind = 'import' AND s.name = ?1
@@ -823,473 +2659,3498 @@ pub fn find_imports(conn: &Connection, name: &str, limit: usize) -> Result<Vec<S
     )?;
 
     let results = stmt
-        .query_map(params![name, limit as i64], |row| {
-            Ok(SearchResult {
-                name: row.get(0)?,
-                kind: row.get(1)?,
-                line: row.get(2)?,
-                signature: row.get(3)?,
-                path: row.get(4)?,
-            })
-        })?
+        .query_map(params![name, limit as i64], SearchResult::from_row)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(results)
 }
 
-/// Find all cross-references for a symbol: definitions, imports, and usages
-pub fn find_cross_references(
-    conn: &Connection,
-    name: &str,
-    limit: usize,
-) -> Result<(Vec<SearchResult>, Vec<SearchResult>, Vec<RefResult>)> {
-    // 1. Definitions (non-import symbols)
-    let definitions = find_symbols_by_name(conn, name, None, limit)?
-        .into_iter()
-        .filter(|s| s.kind != "import")
-        .collect();
-
-    // 2. Imports
-    let imports = find_imports(conn, name, limit)?;
+/// A class used from an Android layout or an iOS storyboard
+#[derive(Debug, Serialize)]
+pub struct ResourceUsage {
+    /// `xml` or `storyboard`
+    pub source: &'static str,
+    pub path: String,
+    pub line: i64,
+    pub usage_type: Option<String>,
+}
 
-    // 3. Usages (refs table)
-    let usages = find_references(conn, name, limit)?;
+/// Layout and storyboard usages of the class `name`, written as the simple or qualified name
+pub fn resource_usages(conn: &Connection, name: &str) -> Result<Vec<ResourceUsage>> {
+    let qualified = format!("%.{}", name);
+    let mut usages = Vec::new();
+    for (source, table) in [("xml", "xml_usages"), ("storyboard", "storyboard_usages")] {
+        let sql = format!(
+            "SELECT file_path, line, usage_type FROM {} WHERE class_name = ?1 OR class_name LIKE ?2 ORDER BY file_path, line",
+            table
+        );
+        let rows = conn
+            .prepare(&sql)?
+            .query_map(params![name, qualified], |row| {
+                Ok(ResourceUsage { source, path: row.get(0)?, line: row.get(1)?, usage_type: row.get(2)? })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        usages.extend(rows);
+    }
+    Ok(usages)
+}
 
-    Ok((definitions, imports, usages))
+/// A function calling a name, with the lines of its calls
+#[derive(Debug, Serialize)]
+pub struct Caller {
+    #[serde(flatten)]
+    pub function: SearchResult,
+    pub call_lines: Vec<i64>,
 }
 
-/// Fuzzy search for symbols: exact → prefix → contains cascade
-pub fn search_symbols_fuzzy(
-    conn: &Connection,
-    query: &str,
-    limit: usize,
-) -> Result<Vec<SearchResult>> {
-    // Single query: contains match with ranking by relevance
-    // exact match (name = query) first, then prefix, then contains — sorted by length
-    let contains_pattern = format!("%{}%", query);
-    let mut stmt = conn.prepare(
-        r#"
-        SELECT s.name, s.kind, s.line, s.signature, f.path
-        FROM symbols s
-        JOIN files f ON s.file_id = f.id
-        WHERE s.name LIKE ?1
-        ORDER BY
-            CASE WHEN s.name = ?2 THEN 0
-                 WHEN s.name LIKE ?3 THEN 1
-                 ELSE 2 END,
-            length(s.name)
-        LIMIT ?4
-        "#,
-    )?;
-    let prefix_pattern = format!("{}%", query);
-    let results: Vec<SearchResult> = stmt
-        .query_map(params![contains_pattern, query, prefix_pattern, limit as i64], |row| {
-            Ok(SearchResult {
-                name: row.get(0)?,
-                kind: row.get(1)?,
-                line: row.get(2)?,
-                signature: row.get(3)?,
-                path: row.get(4)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
-
-    Ok(results)
+/// A name called by a function, with its declaration when the index has one
+#[derive(Debug, Serialize)]
+pub struct Callee {
+    pub name: String,
+    /// Lines in the caller where it is called
+    pub call_lines: Vec<i64>,
+    /// Called function (or type, for constructor calls); None for names not declared in the
+    /// index, such as library functions
+    pub definition: Option<SearchResult>,
 }
 
-/// Scope filter for narrowing search results by file path or module
-pub struct SearchScope<'a> {
-    pub in_file: Option<&'a str>,
-    pub module: Option<&'a str>,
-    /// Directory prefix filter: only return results under this path (relative to project root)
-    pub dir_prefix: Option<&'a str>,
+/// Whether the index has call edges; indexes built before the call graph need a rebuild
+pub fn has_call_graph(conn: &Connection) -> Result<bool> {
+    Ok(conn.query_row("SELECT EXISTS (SELECT 1 FROM calls)", [], |row| row.get(0))?)
 }
 
-impl<'a> SearchScope<'a> {
-    pub fn none() -> Self {
-        SearchScope { in_file: None, module: None, dir_prefix: None }
-    }
+/// Line numbers from a `group_concat` of call lines, in order
+fn call_lines(concatenated: String) -> Vec<i64> {
+    let mut lines: Vec<i64> = concatenated.split(',').filter_map(|l| l.parse().ok()).collect();
+    lines.sort();
+    lines.dedup();
+    lines
+}
 
-    pub fn is_empty(&self) -> bool {
-        self.in_file.is_none() && self.module.is_none() && self.dir_prefix.is_none()
+/// Functions in `scope` that call `name`, by path and line
+pub fn find_callers(conn: &Connection, name: &str, limit: usize, scope: &SearchScope) -> Result<Vec<Caller>> {
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    let sql = format!(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility, group_concat(c.line)
+        FROM calls c
+        JOIN symbols s ON c.caller_id = s.id
+        JOIN files f ON s.file_id = f.id
+        WHERE c.callee = ?1{}
+        GROUP BY s.id
+        ORDER BY f.path, s.line
+        LIMIT ?{}
+        "#,
+        scope_clause,
+        2 + scope_params.len()
+    );
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(name.to_string())];
+    for p in scope_params {
+        all_params.push(Box::new(p));
     }
+    all_params.push(Box::new(limit as i64));
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
 
-    /// Build WHERE clause fragment and collect params
-    fn path_condition(&self) -> (String, Vec<String>) {
-        let mut conditions = Vec::new();
-        let mut params = Vec::new();
-        if let Some(prefix) = self.dir_prefix {
-            conditions.push("f.path LIKE ?".to_string());
-            params.push(format!("{}%", prefix));
-        }
-        if let Some(file) = self.in_file {
-            conditions.push("f.path LIKE ?".to_string());
-            params.push(format!("%{}", file));
-        }
-        if let Some(module) = self.module {
-            conditions.push("f.path LIKE ?".to_string());
-            params.push(format!("{}%", module));
-        }
-        if conditions.is_empty() {
-            (String::new(), params)
-        } else {
-            (format!(" AND {}", conditions.join(" AND ")), params)
-        }
-    }
+    let callers = conn
+        .prepare(&sql)?
+        .query_map(param_refs.as_slice(), |row| {
+            Ok(Caller { function: SearchResult::from_row(row)?, call_lines: call_lines(row.get(8)?) })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(callers)
 }
 
-/// Search symbols with scope filtering (file/module)
-pub fn search_symbols_scoped(
-    conn: &Connection,
-    query: &str,
-    limit: usize,
-    scope: &SearchScope,
-) -> Result<Vec<SearchResult>> {
-    if scope.is_empty() {
-        return search_symbols(conn, query, limit);
+/// Names called by the functions named `name` in `scope`, in the order of their first call;
+/// each is resolved to the first function (else type) declared with that name
+pub fn find_callees(conn: &Connection, name: &str, limit: usize, scope: &SearchScope) -> Result<Vec<Callee>> {
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    let sql = format!(
+        r#"
+        SELECT c.callee, group_concat(c.line)
+        FROM calls c
+        JOIN symbols s ON c.caller_id = s.id
+        JOIN files f ON s.file_id = f.id
+        WHERE s.name = ?1{}
+        GROUP BY c.callee
+        ORDER BY MIN(c.id)
+        LIMIT ?{}
+        "#,
+        scope_clause,
+        2 + scope_params.len()
+    );
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(name.to_string())];
+    for p in scope_params {
+        all_params.push(Box::new(p));
     }
+    all_params.push(Box::new(limit as i64));
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
+    let called: Vec<(String, String)> = conn
+        .prepare(&sql)?
+        .query_map(param_refs.as_slice(), |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
 
-    if query.trim().is_empty() {
-        return Ok(vec![]);
+    let mut definition_stmt = conn.prepare(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.name = ?1 AND s.kind IN ('function', 'class', 'object', 'enum', 'interface')
+        ORDER BY s.kind != 'function', f.path, s.line
+        LIMIT 1
+        "#,
+    )?;
+    let mut callees = Vec::with_capacity(called.len());
+    for (callee, lines) in called {
+        let definition = match definition_stmt.query_row(params![callee], SearchResult::from_row) {
+            Ok(definition) => Some(definition),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e.into()),
+        };
+        callees.push(Callee { name: callee, call_lines: call_lines(lines), definition });
     }
+    Ok(callees)
+}
 
-    let escaped_query = escape_fts5_query(query);
-    let (scope_clause, scope_params) = scope.path_condition();
+/// A function with its complexity and length
+#[derive(Debug, Serialize)]
+pub struct FunctionComplexity {
+    #[serde(flatten)]
+    pub function: SearchResult,
+    pub complexity: i64,
+    /// Lines from the declaration to the end of the body
+    pub lines: i64,
+}
 
+/// Whether functions have complexity; indexes built before it was measured need a rebuild
+pub fn has_complexity(conn: &Connection) -> Result<bool> {
+    Ok(conn.query_row("SELECT EXISTS (SELECT 1 FROM symbols WHERE complexity IS NOT NULL)", [], |row| row.get(0))?)
+}
+
+/// The `limit` most complex functions in `scope` (the longest with `by_lines`)
+pub fn complex_functions(conn: &Connection, limit: usize, by_lines: bool, scope: &SearchScope) -> Result<Vec<FunctionComplexity>> {
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    let order = if by_lines { "lines DESC, s.complexity DESC" } else { "s.complexity DESC, lines DESC" };
     let sql = format!(
         r#"
-        SELECT s.name, s.kind, s.line, s.signature, f.path
-        FROM symbols_fts fts
-        JOIN symbols s ON fts.rowid = s.id
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility,
+               s.complexity, COALESCE(s.end_line, s.line) - s.line + 1 AS lines
+        FROM symbols s
         JOIN files f ON s.file_id = f.id
-        WHERE symbols_fts MATCH ?1{}
+        WHERE s.complexity IS NOT NULL{}
+        ORDER BY {}, f.path, s.line
         LIMIT ?{}
         "#,
         scope_clause,
-        2 + scope_params.len()
+        order,
+        1 + scope_params.len()
     );
-
-    let mut stmt = conn.prepare(&sql)?;
     let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
-    all_params.push(Box::new(escaped_query));
-    for p in &scope_params {
-        all_params.push(Box::new(p.clone()));
+    for p in scope_params {
+        all_params.push(Box::new(p));
     }
     all_params.push(Box::new(limit as i64));
-
     let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
-    let results = stmt
+
+    let functions = conn
+        .prepare(&sql)?
         .query_map(param_refs.as_slice(), |row| {
-            Ok(SearchResult {
-                name: row.get(0)?,
-                kind: row.get(1)?,
-                line: row.get(2)?,
-                signature: row.get(3)?,
-                path: row.get(4)?,
-            })
+            Ok(FunctionComplexity { function: SearchResult::from_row(row)?, complexity: row.get(8)?, lines: row.get(9)? })
         })?
         .collect::<Result<Vec<_>, _>>()?;
-
-    Ok(results)
+    Ok(functions)
 }
 
-/// Find symbols by name with scope filtering
-pub fn find_symbols_by_name_scoped(
+/// Find all cross-references for a symbol: definitions, imports, and usages (of `kind` only, if given),
+/// in files matching `scope`
+pub fn find_cross_references(
     conn: &Connection,
     name: &str,
-    kind: Option<&str>,
+    kind: Option<RefKind>,
+    arity: Option<usize>,
     limit: usize,
     scope: &SearchScope,
+) -> Result<(Vec<SearchResult>, Vec<SearchResult>, Vec<RefResult>)> {
+    // 1. Definitions (non-import symbols); with an arity, only the overloads taking that many parameters
+    let definitions = find_symbols_by_name_scoped(conn, name, None, limit, scope)?
+        .into_iter()
+        .filter(|s| s.kind != "import" && (arity.is_none() || s.arity == arity))
+        .collect();
+
+    // 2. Imports
+    let imports = if scope.is_empty() {
+        find_imports(conn, name, limit)?
+    } else {
+        find_symbols_by_name_scoped(conn, name, Some("import"), limit, scope)?
+    };
+
+    // 3. Usages (refs table)
+    let usages = find_references_of_kind(conn, name, kind, arity, limit, scope)?;
+
+    Ok((definitions, imports, usages))
+}
+
+/// Fuzzy search for symbols: exact → prefix → contains cascade
+pub fn search_symbols_fuzzy(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
 ) -> Result<Vec<SearchResult>> {
-    if scope.is_empty() {
-        return find_symbols_by_name(conn, name, kind, limit);
+    // Single query: contains match with ranking by relevance
+    // exact match (name = query) first, then prefix, then contains — sorted by length
+    let contains_pattern = format!("%{}%", query);
+    let mut stmt = conn.prepare(&format!(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.name LIKE ?1{}
+        ORDER BY
+            CASE WHEN s.name = ?2 THEN 0
+                 WHEN s.name LIKE ?3 THEN 1
+                 ELSE 2 END,
+            length(s.name), f.path, s.line
+        LIMIT ?4
+        "#,
+        name_trigram_clause(conn, query)?,
+    ))?;
+    let prefix_pattern = format!("{}%", query);
+    let mut results: Vec<SearchResult> = stmt
+        .query_map(params![contains_pattern, query, prefix_pattern, limit as i64], SearchResult::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Then names within a few typos of the query or of a part of it
+    if results.len() < limit {
+        let found: HashSet<String> = results.iter().map(|r| r.name.clone()).collect();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+            FROM symbols s
+            JOIN files f ON s.file_id = f.id
+            WHERE s.name = ?1
+            ORDER BY f.path, s.line
+            LIMIT ?2
+            "#,
+        )?;
+        for name in similar_names(conn, query)?.into_iter().filter(|n| !found.contains(n)) {
+            let remaining = limit - results.len();
+            results.extend(
+                stmt.query_map(params![name, remaining as i64], SearchResult::from_row)?
+                    .collect::<Result<Vec<_>, _>>()?,
+            );
+            if results.len() >= limit {
+                break;
+            }
+        }
     }
 
-    let (scope_clause, scope_params) = scope.path_condition();
+    Ok(results)
+}
 
-    let mut sql = format!(
-        "SELECT s.name, s.kind, s.line, s.signature, f.path FROM symbols s JOIN files f ON s.file_id = f.id WHERE s.name = ?1{}",
-        scope_clause
-    );
-    if kind.is_some() {
-        sql.push_str(&format!(" AND s.kind = ?{}", 2 + scope_params.len()));
-        sql.push_str(&format!(" LIMIT ?{}", 3 + scope_params.len()));
-    } else {
-        sql.push_str(&format!(" LIMIT ?{}", 2 + scope_params.len()));
+/// Names sharing trigrams with `query` that are at most `fuzzy::max_distance` edits from it or
+/// from a part of them, best first
+fn similar_names(conn: &Connection, query: &str) -> Result<Vec<String>> {
+    let candidates = trigram_candidates(conn, query)?;
+    let max = fuzzy::max_distance(query);
+    let mut scored: Vec<((usize, bool, usize), String)> = candidates
+        .into_iter()
+        .filter_map(|name| {
+            let (distance, start) = fuzzy::substring_distance(query, &name);
+            (distance <= max).then(|| ((distance, start != 0, name.chars().count()), name))
+        })
+        .collect();
+    scored.sort();
+    Ok(scored.into_iter().map(|(_, name)| name).collect())
+}
+
+/// Indexes built before trigrams were stored get them on first use
+fn ensure_name_trigrams(conn: &Connection) -> Result<()> {
+    let has_trigrams: bool = conn.query_row("SELECT EXISTS (SELECT 1 FROM symbol_names)", [], |row| row.get(0))?;
+    if !has_trigrams {
+        sync_name_trigrams(conn)?;
     }
+    Ok(())
+}
 
-    let mut stmt = conn.prepare(&sql)?;
-    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
-    all_params.push(Box::new(name.to_string()));
-    for p in &scope_params {
-        all_params.push(Box::new(p.clone()));
+/// Clause (`AND s.name IN (...)`) keeping the symbols whose name holds every trigram of `query`,
+/// so substring matches probe `name_trigrams` instead of scanning all symbols; empty for queries
+/// under three characters, which have no trigrams
+fn name_trigram_clause(conn: &Connection, query: &str) -> Result<String> {
+    let trigrams = fuzzy::trigrams(query);
+    if trigrams.is_empty() {
+        return Ok(String::new());
     }
-    if let Some(k) = kind {
-        all_params.push(Box::new(k.to_string()));
+    ensure_name_trigrams(conn)?;
+    let literals: Vec<String> = trigrams.iter().map(|t| format!("'{}'", t.replace('\'', "''"))).collect();
+    Ok(format!(
+        " AND s.name IN (SELECT n.name FROM name_trigrams t JOIN symbol_names n ON t.name_id = n.id \
+         WHERE t.trigram IN ({}) GROUP BY n.id HAVING COUNT(*) = {})",
+        literals.join(", "),
+        trigrams.len()
+    ))
+}
+
+/// Distinct symbol names sharing the most trigrams with `query`, the candidates for scoring
+fn trigram_candidates(conn: &Connection, query: &str) -> Result<Vec<String>> {
+    /// Names with the most shared trigrams that get scored
+    const CANDIDATES: i64 = 500;
+    let trigrams = fuzzy::trigrams(query);
+    if trigrams.is_empty() {
+        return Ok(vec![]);
     }
-    all_params.push(Box::new(limit as i64));
+    ensure_name_trigrams(conn)?;
 
-    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
-    let results = stmt
-        .query_map(param_refs.as_slice(), |row| {
-            Ok(SearchResult {
-                name: row.get(0)?,
-                kind: row.get(1)?,
-                line: row.get(2)?,
-                signature: row.get(3)?,
-                path: row.get(4)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+    let placeholders = vec!["?"; trigrams.len()].join(", ");
+    let sql = format!(
+        "SELECT n.name FROM name_trigrams t JOIN symbol_names n ON t.name_id = n.id \
+         WHERE t.trigram IN ({}) GROUP BY n.id ORDER BY COUNT(*) DESC, length(n.name) LIMIT {}",
+        placeholders, CANDIDATES
+    );
+    let candidates: Vec<String> = conn
+        .prepare(&sql)?
+        .query_map(rusqlite::params_from_iter(&trigrams), |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    Ok(candidates)
+}
 
-    Ok(results)
+/// A name confusingly close to another, with where it is declared
+#[derive(Debug, Serialize)]
+pub struct NearDuplicate {
+    pub name: String,
+    /// `fuzzy::normalized_distance` to the name asked for
+    pub distance: f64,
+    pub declarations: Vec<SearchResult>,
 }
 
-/// Find class-like symbols with scope filtering
-pub fn find_class_like_scoped(
+/// Names other than `name` within `threshold` normalized edit distance of it (case ignored, so
+/// `paymentManager` counts at 0), closest first; only names declared within `scope`
+pub fn near_duplicate_names(
     conn: &Connection,
     name: &str,
+    threshold: f64,
     limit: usize,
     scope: &SearchScope,
-) -> Result<Vec<SearchResult>> {
-    if scope.is_empty() {
-        return find_class_like(conn, name, limit);
+) -> Result<Vec<NearDuplicate>> {
+    let mut scored: Vec<(f64, String)> = trigram_candidates(conn, name)?
+        .into_iter()
+        .filter(|candidate| candidate != name)
+        .map(|candidate| (fuzzy::normalized_distance(name, &candidate), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .collect();
+    scored.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    let sql = format!(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.name = ?1 AND s.kind != 'import'{}
+        ORDER BY f.path, s.line
+        "#,
+        scope_clause
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut duplicates = Vec::new();
+    for (distance, candidate) in scored {
+        if duplicates.len() >= limit {
+            break;
+        }
+        let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(candidate.clone())];
+        for p in &scope_params {
+            all_params.push(Box::new(p.clone()));
+        }
+        let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
+        let declarations = stmt
+            .query_map(param_refs.as_slice(), SearchResult::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        if !declarations.is_empty() {
+            duplicates.push(NearDuplicate { name: candidate, distance, declarations });
+        }
     }
+    Ok(duplicates)
+}
 
-    let (scope_clause, scope_params) = scope.path_condition();
+/// One definition (same name, kind and signature) repeated in several files
+#[derive(Debug, Serialize)]
+pub struct DuplicateDefinition {
+    pub name: String,
+    pub kind: String,
+    pub signature: String,
+    pub declarations: Vec<SearchResult>,
+}
 
+/// Definitions with the same name, kind and signature in more than one file within `scope`,
+/// the most repeated first. Imports, packages, generated symbols, `partial` parts and
+/// `override` members (same signature by design) are left out.
+pub fn duplicate_definitions(conn: &Connection, limit: usize, scope: &SearchScope) -> Result<Vec<DuplicateDefinition>> {
+    let (scope_clause, scope_params) = scope.symbol_condition();
     let sql = format!(
         r#"
-        SELECT s.name, s.kind, s.line, s.signature, f.path
+        SELECT s.name, s.kind, s.signature
         FROM symbols s
         JOIN files f ON s.file_id = f.id
-        WHERE s.name = ?1 AND s.kind IN ('class', 'interface', 'object', 'enum', 'protocol', 'struct', 'actor', 'package'){}
+        WHERE s.kind NOT IN ('import', 'package') AND s.signature IS NOT NULL
+          AND s.generated_by IS NULL AND s.partial IS NULL
+          AND s.signature NOT LIKE '%override %' AND s.signature NOT LIKE '%@Override%'{}
+        GROUP BY s.name, s.kind, s.signature
+        HAVING COUNT(DISTINCT s.file_id) > 1
+        ORDER BY COUNT(DISTINCT s.file_id) DESC, s.name, s.kind
         LIMIT ?{}
         "#,
         scope_clause,
-        2 + scope_params.len()
+        1 + scope_params.len()
     );
-
-    let mut stmt = conn.prepare(&sql)?;
     let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
-    all_params.push(Box::new(name.to_string()));
     for p in &scope_params {
         all_params.push(Box::new(p.clone()));
     }
     all_params.push(Box::new(limit as i64));
-
     let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
-    let results = stmt
-        .query_map(param_refs.as_slice(), |row| {
-            Ok(SearchResult {
-                name: row.get(0)?,
-                kind: row.get(1)?,
-                line: row.get(2)?,
-                signature: row.get(3)?,
-                path: row.get(4)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+    let groups: Vec<(String, String, String)> = conn
+        .prepare(&sql)?
+        .query_map(param_refs.as_slice(), |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<_, _>>()?;
 
-    Ok(results)
+    let sql = format!(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.name = ?1 AND s.kind = ?2 AND s.signature = ?3 AND s.generated_by IS NULL AND s.partial IS NULL{}
+        ORDER BY f.path, s.line
+        "#,
+        scope_clause
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mut duplicates = Vec::with_capacity(groups.len());
+    for (name, kind, signature) in groups {
+        let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> =
+            vec![Box::new(name.clone()), Box::new(kind.clone()), Box::new(signature.clone())];
+        for p in &scope_params {
+            all_params.push(Box::new(p.clone()));
+        }
+        let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
+        let declarations = stmt
+            .query_map(param_refs.as_slice(), SearchResult::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        duplicates.push(DuplicateDefinition { name, kind, signature, declarations });
+    }
+    Ok(duplicates)
 }
 
-/// Find references with scope filtering
-pub fn find_references_scoped(
-    conn: &Connection,
-    name: &str,
-    limit: usize,
-    scope: &SearchScope,
-) -> Result<Vec<RefResult>> {
-    if scope.is_empty() {
-        return find_references(conn, name, limit);
-    }
+/// A public declaration in an API snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiEntry {
+    /// Qualified name, else container and name
+    pub name: String,
+    pub kind: String,
+    /// Declaration as written; empty when the parser records none
+    pub signature: String,
+    pub path: String,
+}
 
-    let (scope_clause, scope_params) = scope.path_condition();
+/// Public declarations in `scope` (imports, packages and generated symbols aside), sorted by
+/// name, kind and signature so snapshots diff cleanly
+pub fn public_api(conn: &Connection, scope: &SearchScope) -> Result<Vec<ApiEntry>> {
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    let sql = format!(
+        r#"
+        SELECT COALESCE(s.qualified_name, CASE WHEN s.container IS NULL THEN s.name ELSE s.container || '.' || s.name END),
+               s.kind, COALESCE(s.signature, ''), f.path
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.visibility = 'public' AND s.kind NOT IN ('import', 'package') AND s.generated_by IS NULL{}
+        ORDER BY 1, 2, 3, 4
+        "#,
+        scope_clause
+    );
+    let entries = conn
+        .prepare(&sql)?
+        .query_map(rusqlite::params_from_iter(scope_params), |row| {
+            Ok(ApiEntry { name: row.get(0)?, kind: row.get(1)?, signature: row.get(2)?, path: row.get(3)? })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
 
+/// Declarations in `scope` (imports, packages and generated symbols aside) by path and line
+pub fn declarations(conn: &Connection, scope: &SearchScope) -> Result<Vec<SearchResult>> {
+    let (scope_clause, scope_params) = scope.symbol_condition();
     let sql = format!(
         r#"
-        SELECT r.name, r.line, r.context, f.path
-        FROM refs r
-        JOIN files f ON r.file_id = f.id
-        WHERE r.name = ?1{}
-        ORDER BY f.path, r.line
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.kind NOT IN ('import', 'package') AND s.generated_by IS NULL{}
+        ORDER BY f.path, s.line
+        "#,
+        scope_clause
+    );
+    let results = conn
+        .prepare(&sql)?
+        .query_map(rusqlite::params_from_iter(scope_params), SearchResult::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(results)
+}
+
+/// A type with its member counts and incoming references
+#[derive(Debug, Serialize)]
+pub struct LargeClass {
+    #[serde(flatten)]
+    pub class: SearchResult,
+    /// Symbols declared directly inside the type in its file
+    pub members: i64,
+    pub functions: i64,
+    pub properties: i64,
+    /// References to the type's name from other files
+    pub refs: i64,
+}
+
+/// Types in `scope` with at least `threshold` direct members, the largest first (then the most
+/// referenced); members are the symbols of the same file whose container is the type
+pub fn large_classes(conn: &Connection, threshold: usize, limit: usize, scope: &SearchScope) -> Result<Vec<LargeClass>> {
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    // Containers are counted first so only the large ones are matched to their declarations
+    let sql = format!(
+        r#"
+        WITH containers AS (
+            SELECT file_id, container, COUNT(*) AS members, SUM(kind = 'function') AS functions,
+                   SUM(kind = 'property') AS properties
+            FROM symbols
+            WHERE container IS NOT NULL
+            GROUP BY file_id, container
+            HAVING COUNT(*) >= ?1
+        )
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility,
+               c.members, c.functions, c.properties,
+               (SELECT COALESCE(SUM(COALESCE(r.count, 1)), 0) FROM refs r WHERE r.name = s.name) AS refs
+        FROM containers c
+        JOIN symbols s ON s.file_id = c.file_id
+            AND c.container = CASE WHEN s.container IS NULL THEN s.name ELSE s.container || '.' || s.name END
+        JOIN files f ON s.file_id = f.id
+        WHERE s.kind IN ('class', 'interface', 'object', 'enum'){}
+        GROUP BY s.id
+        ORDER BY c.members DESC, refs DESC, f.path, s.line
         LIMIT ?{}
         "#,
         scope_clause,
         2 + scope_params.len()
     );
-
-    let mut stmt = conn.prepare(&sql)?;
-    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
-    all_params.push(Box::new(name.to_string()));
-    for p in &scope_params {
-        all_params.push(Box::new(p.clone()));
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(threshold as i64)];
+    for p in scope_params {
+        all_params.push(Box::new(p));
     }
     all_params.push(Box::new(limit as i64));
-
     let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
-    let results = stmt
+
+    let classes = conn
+        .prepare(&sql)?
         .query_map(param_refs.as_slice(), |row| {
-            Ok(RefResult {
-                name: row.get(0)?,
-                line: row.get(1)?,
-                context: row.get(2)?,
-                path: row.get(3)?,
+            Ok(LargeClass {
+                class: SearchResult::from_row(row)?,
+                members: row.get(8)?,
+                functions: row.get(9)?,
+                properties: row.get(10)?,
+                refs: row.get(11)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
+    Ok(classes)
+}
 
-    Ok(results)
+/// Public declarations checked by `untested` and those no test file refers to
+#[derive(Debug, Serialize)]
+pub struct TestCoverage {
+    /// Public declarations checked
+    pub checked: usize,
+    pub untested: Vec<SearchResult>,
 }
 
-/// Get extra source roots stored in metadata
-pub fn get_extra_roots(conn: &Connection) -> Result<Vec<String>> {
-    let result: Result<String, _> = conn.query_row(
-        "SELECT value FROM metadata WHERE key = 'extra_roots'",
-        [],
-        |row| row.get(0),
+/// Public declarations outside test files in `scope` (types and functions unless `scope.kinds`
+/// says otherwise) whose name no test file references, by path and line; generated code is
+/// left out
+pub fn untested_symbols(conn: &Connection, scope: &SearchScope) -> Result<TestCoverage> {
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    let kinds = if scope.kinds.is_empty() {
+        " AND s.kind IN ('class', 'interface', 'object', 'enum', 'protocol', 'struct', 'function')"
+    } else {
+        ""
+    };
+    let sql = format!(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility,
+               EXISTS (SELECT 1 FROM refs r JOIN files rf ON r.file_id = rf.id WHERE r.name = s.name AND rf.is_test = 1)
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.visibility = 'public' AND f.is_test = 0 AND s.generated_by IS NULL{}{}
+        ORDER BY f.path, s.line
+        "#,
+        kinds, scope_clause
     );
-    match result {
-        Ok(json) => {
-            let roots: Vec<String> = serde_json::from_str(&json).unwrap_or_default();
-            Ok(roots)
+    let rows: Vec<(SearchResult, bool)> = conn
+        .prepare(&sql)?
+        .query_map(rusqlite::params_from_iter(scope_params), |row| Ok((SearchResult::from_row(row)?, row.get(8)?)))?
+        .collect::<Result<_, _>>()?;
+    let checked = rows.len();
+    let untested = rows.into_iter().filter(|(_, tested)| !tested).map(|(s, _)| s).collect();
+    Ok(TestCoverage { checked, untested })
+}
+
+/// A type provided to or injected from a DI container, as stored by the indexer
+#[derive(Debug, Serialize)]
+pub struct DiBinding {
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// `@Provides`, `@Inject constructor`, `koin single`, `@Autowired field`, ...
+    pub mechanism: String,
+    /// The providing function, class or Koin module; for injections the dependent type
+    pub symbol: Option<String>,
+    pub path: String,
+    pub line: i64,
+}
+
+/// What provides a type to the DI container, where it is injected and what it needs itself
+#[derive(Debug, Serialize)]
+pub struct DiGraph {
+    pub providers: Vec<DiBinding>,
+    pub injections: Vec<DiBinding>,
+    /// Types injected into the type's constructor, fields or provider functions
+    pub dependencies: Vec<DiBinding>,
+}
+
+/// DI bindings of the type named `type_name` (simple name), by path and line
+pub fn di_graph(conn: &Connection, type_name: &str) -> Result<DiGraph> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT d.type, d.mechanism, d.symbol, f.path, d.line
+        FROM di_bindings d
+        JOIN files f ON d.file_id = f.id
+        WHERE d.role = ?1 AND (CASE WHEN ?2 THEN d.symbol ELSE d.type END) = ?3
+        ORDER BY f.path, d.line
+        "#,
+    )?;
+    let mut bindings = |role: &str, by_symbol: bool| -> Result<Vec<DiBinding>> {
+        Ok(stmt
+            .query_map(params![role, by_symbol, type_name], |row| {
+                Ok(DiBinding { type_name: row.get(0)?, mechanism: row.get(1)?, symbol: row.get(2)?, path: row.get(3)?, line: row.get(4)? })
+            })?
+            .collect::<Result<_, _>>()?)
+    };
+    Ok(DiGraph {
+        providers: bindings("provides", false)?,
+        injections: bindings("injects", false)?,
+        dependencies: bindings("injects", true)?,
+    })
+}
+
+/// A method that is overridden but never called, with its overrides
+#[derive(Debug, Serialize)]
+pub struct UncalledOverride {
+    /// The declaration the overrides go back to
+    #[serde(flatten)]
+    pub base: SearchResult,
+    pub overrides: Vec<SearchResult>,
+}
+
+/// Simple name of a supertype as written (`Base` for `com.app.Base<T>`)
+fn simple_type_name(name: &str) -> &str {
+    let name = name.split(['<', '(', '[']).next().unwrap_or(name).trim();
+    name.rsplit(['.', ':']).next().unwrap_or(name)
+}
+
+/// Methods in `scope` that are overridden (`override` or `@Override`) but that nothing calls
+/// or references by name, the most overridden first. The base is the first declaration up the
+/// supertypes that is not itself an override; overrides of library methods have none in the
+/// index and are left out, as the library calls them. Calls from inside the family (`super`
+/// calls) do not count, nor do references written as `super.name`.
+pub fn uncalled_overrides(conn: &Connection, limit: usize, scope: &SearchScope) -> Result<Vec<UncalledOverride>> {
+    struct Method {
+        id: i64,
+        symbol: SearchResult,
+        /// Simple name of the declaring type
+        owner: String,
+        overrides: bool,
+    }
+
+    let annotated: HashSet<i64> = conn
+        .prepare("SELECT symbol_id FROM symbol_annotations WHERE name = 'Override'")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    // Member functions sharing a name with some override: the overrides and their candidate bases
+    let methods: Vec<Method> = conn
+        .prepare(
+            "SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility, s.id
+             FROM symbols s JOIN files f ON s.file_id = f.id
+             WHERE s.kind = 'function' AND s.container IS NOT NULL AND s.name IN (
+                 SELECT name FROM symbols
+                 WHERE kind = 'function' AND (signature LIKE '%override%'
+                     OR id IN (SELECT symbol_id FROM symbol_annotations WHERE name = 'Override'))
+             )
+             ORDER BY f.path, s.line",
+        )?
+        .query_map([], |row| {
+            let symbol = SearchResult::from_row(row)?;
+            let id: i64 = row.get(8)?;
+            let container = symbol.container.clone().unwrap_or_default();
+            let overrides = annotated.contains(&id)
+                || symbol.signature.as_deref().is_some_and(|sig| sig.split_whitespace().any(|w| w == "override"));
+            Ok(Method { id, owner: simple_type_name(&container).to_string(), symbol, overrides })
+        })?
+        .collect::<Result<_, _>>()?;
+    if methods.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut supertypes: HashMap<String, Vec<String>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT s.name, i.parent_name FROM inheritance i JOIN symbols s ON i.child_id = s.id")?;
+    for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+        let (child, parent) = row?;
+        supertypes.entry(child).or_default().push(simple_type_name(&parent).to_string());
+    }
+    let mut by_owner: HashMap<(&str, &str), Vec<usize>> = HashMap::new();
+    for (i, m) in methods.iter().enumerate() {
+        by_owner.entry((m.owner.as_str(), m.symbol.name.as_str())).or_default().push(i);
+    }
+
+    // Each override's base: breadth-first up the supertypes to a declaration that is no override
+    let mut families: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, m) in methods.iter().enumerate().filter(|(_, m)| m.overrides) {
+        let mut queue: VecDeque<&str> = supertypes.get(&m.owner).into_iter().flatten().map(String::as_str).collect();
+        let mut seen: HashSet<&str> = HashSet::from([m.owner.as_str()]);
+        while let Some(owner) = queue.pop_front() {
+            if !seen.insert(owner) {
+                continue;
+            }
+            let declared = by_owner.get(&(owner, m.symbol.name.as_str())).map(Vec::as_slice).unwrap_or(&[]);
+            if let Some(&base) = declared.iter().find(|&&d| !methods[d].overrides) {
+                families.entry(base).or_default().push(i);
+                break;
+            }
+            queue.extend(supertypes.get(owner).into_iter().flatten().map(String::as_str));
+        }
+    }
+
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    let in_scope: HashSet<i64> = conn
+        .prepare(&format!(
+            "SELECT s.id FROM symbols s JOIN files f ON s.file_id = f.id WHERE s.kind = 'function' AND s.container IS NOT NULL{}",
+            scope_clause
+        ))?
+        .query_map(rusqlite::params_from_iter(scope_params), |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    let mut referenced = conn.prepare(
+        "SELECT 1 FROM refs WHERE name = ?1 AND COALESCE(context, '') NOT LIKE '%super.' || ?1 || '%'
+             AND COALESCE(context, '') NOT LIKE '%super::' || ?1 || '%' AND COALESCE(context, '') NOT LIKE '%[super ' || ?1 || '%'
+         LIMIT 1",
+    )?;
+    let mut callers = conn.prepare("SELECT caller_id FROM calls WHERE callee = ?1")?;
+
+    let mut uncalled = Vec::new();
+    for (base, overrides) in families {
+        if !in_scope.contains(&methods[base].id) {
+            continue;
+        }
+        let name = &methods[base].symbol.name;
+        if referenced.exists(params![name])? {
+            continue;
+        }
+        let family: HashSet<i64> = overrides.iter().chain([&base]).map(|&i| methods[i].id).collect();
+        let called = callers
+            .query_map(params![name], |row| row.get::<_, i64>(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
+            .any(|caller| !family.contains(caller));
+        if !called {
+            uncalled.push((base, overrides));
         }
-        Err(_) => Ok(vec![]),
     }
+    uncalled.sort_by_key(|(_, overrides)| std::cmp::Reverse(overrides.len()));
+    uncalled.truncate(limit);
+
+    let mut methods: Vec<Option<Method>> = methods.into_iter().map(Some).collect();
+    Ok(uncalled
+        .into_iter()
+        .filter_map(|(base, overrides)| {
+            Some(UncalledOverride {
+                base: methods[base].take()?.symbol,
+                overrides: overrides.iter().filter_map(|&i| methods[i].take()).map(|m| m.symbol).collect(),
+            })
+        })
+        .collect())
 }
 
-/// Add an extra source root
-pub fn add_extra_root(conn: &Connection, path: &str) -> Result<()> {
-    let mut roots = get_extra_roots(conn)?;
-    if !roots.contains(&path.to_string()) {
-        roots.push(path.to_string());
+/// A test file exercising code that is gone from the index
+#[derive(Debug, Serialize)]
+pub struct OrphanTest {
+    pub path: String,
+    /// Subject the file is named after (`Payment` for `PaymentTest.kt`) that no production
+    /// file declares any more
+    pub missing_subject: Option<String>,
+    /// Imports from project namespaces naming symbols that no longer exist
+    pub missing_imports: Vec<MissingImport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MissingImport {
+    /// Imported qualified name
+    pub path: String,
+    pub line: i64,
+}
+
+/// Names generated at build time that code imports but no parser sees (Android `R` and
+/// `BuildConfig`, view bindings, Dagger and Hilt classes, Safe Args)
+fn is_build_generated(name: &str) -> bool {
+    matches!(name, "R" | "BuildConfig")
+        || ["Dagger", "Hilt_"].iter().any(|prefix| name.starts_with(prefix))
+        || ["Binding", "_Factory", "_MembersInjector", "Directions", "Args"].iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// Namespace of a qualified name: everything before the last `.` or `::`
+fn parent_namespace(qualified: &str) -> Option<&str> {
+    let dot = qualified.rfind('.').map(|i| (i, 1));
+    let colons = qualified.rfind("::").map(|i| (i, 2));
+    dot.max(colons).map(|(i, _)| &qualified[..i])
+}
+
+/// Test files in `scope` whose subject or imported project symbols no longer exist: the subject
+/// comes from the file name (`parsers::test_subject`) and is found when a production file
+/// declares a symbol or is named after it; an import counts when its namespace holds production
+/// symbols but its qualified name is declared nowhere
+pub fn orphan_tests(conn: &Connection, scope: &SearchScope) -> Result<Vec<OrphanTest>> {
+    let mut qualified_names: HashSet<String> = HashSet::new();
+    let mut namespaces: HashSet<String> = HashSet::new();
+    let mut production_names: HashSet<String> = HashSet::new();
+    let mut stmt = conn.prepare("SELECT s.name, s.qualified_name, f.is_test FROM symbols s JOIN files f ON s.file_id = f.id WHERE s.kind != 'import'")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let name: String = row.get(0)?;
+        let qualified: Option<String> = row.get(1)?;
+        let is_test: bool = row.get(2)?;
+        if let Some(qualified) = qualified {
+            if !is_test {
+                if let Some(namespace) = parent_namespace(&qualified) {
+                    namespaces.insert(namespace.to_string());
+                }
+            }
+            qualified_names.insert(qualified);
+        }
+        if !is_test {
+            production_names.insert(name);
+        }
     }
-    let json = serde_json::to_string(&roots)?;
-    conn.execute(
-        "INSERT OR REPLACE INTO metadata (key, value) VALUES ('extra_roots', ?1)",
-        params![json],
-    )?;
-    Ok(())
+    let mut stmt = conn.prepare("SELECT path FROM files WHERE is_test = 0")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let path: String = row.get(0)?;
+        let file = path.rsplit('/').next().unwrap_or(&path);
+        production_names.insert(file.split('.').next().unwrap_or(file).to_string());
+    }
+
+    let (scope_clause, scope_params) = scope.path_condition();
+    let sql = format!("SELECT f.id, f.path FROM files f WHERE f.is_test = 1{} ORDER BY f.path", scope_clause);
+    let test_files: Vec<(i64, String)> = conn
+        .prepare(&sql)?
+        .query_map(rusqlite::params_from_iter(scope_params), |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut imports_stmt = conn.prepare("SELECT path, line FROM imports WHERE file_id = ?1 AND name != '*' ORDER BY line")?;
+    let mut orphans = Vec::new();
+    for (file_id, path) in test_files {
+        let missing_subject = parsers::test_subject(&path)
+            .filter(|subject| !production_names.contains(*subject))
+            .map(str::to_string);
+        let missing_imports: Vec<MissingImport> = imports_stmt
+            .query_map(params![file_id], |row| Ok(MissingImport { path: row.get(0)?, line: row.get(1)? }))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|import| {
+                let name = import.path.rsplit(['.', ':']).next().unwrap_or(&import.path);
+                !qualified_names.contains(&import.path)
+                    && !is_build_generated(name)
+                    && parent_namespace(&import.path).is_some_and(|namespace| namespaces.contains(namespace))
+            })
+            .collect();
+        if missing_subject.is_some() || !missing_imports.is_empty() {
+            orphans.push(OrphanTest { path, missing_subject, missing_imports });
+        }
+    }
+    Ok(orphans)
 }
 
-/// Remove an extra source root
-pub fn remove_extra_root(conn: &Connection, path: &str) -> Result<bool> {
-    let mut roots = get_extra_roots(conn)?;
-    let len_before = roots.len();
-    roots.retain(|r| r != path);
-    if roots.len() == len_before {
-        return Ok(false);
+/// Rows `offset..offset + limit` of a query's results (`--offset`/`--cursor` paging). With
+/// `count` the query also reports how many rows match in all
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    pub offset: usize,
+    pub limit: usize,
+    pub count: bool,
+}
+
+impl Window {
+    /// The first `limit` rows, uncounted
+    pub fn first(limit: usize) -> Self {
+        Window { offset: 0, limit, count: false }
     }
-    let json = serde_json::to_string(&roots)?;
-    conn.execute(
-        "INSERT OR REPLACE INTO metadata (key, value) VALUES ('extra_roots', ?1)",
-        params![json],
-    )?;
-    Ok(true)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Run `sql`, a query without LIMIT whose numbered parameters are `params`, for the rows in
+/// `window`. Returns them with the number of matching rows: from a separate `COUNT(*)` when the
+/// window is counted, else just the rows returned
+fn query_window<T>(
+    conn: &Connection,
+    sql: &str,
+    params: &[Box<dyn rusqlite::types::ToSql>],
+    window: Window,
+    map: impl FnMut(&rusqlite::Row) -> rusqlite::Result<T>,
+) -> Result<(Vec<T>, usize)> {
+    let (limit, offset) = (window.limit as i64, window.offset as i64);
+    let mut param_refs: Vec<&dyn rusqlite::types::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+    param_refs.extend([&limit as &dyn rusqlite::types::ToSql, &offset]);
+    let paged = format!("{} LIMIT ?{} OFFSET ?{}", sql, params.len() + 1, params.len() + 2);
+    let rows = conn
+        .prepare_cached(&paged)?
+        .query_map(param_refs.as_slice(), map)?
+        .collect::<Result<Vec<_>, _>>()?;
+    if !window.count {
+        let total = rows.len();
+        return Ok((rows, total));
+    }
+    let total: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM ({})", sql), &param_refs[..params.len()], |row| row.get(0))?;
+    Ok((rows, total as usize))
+}
 
-    fn create_test_db() -> Connection {
-        let conn = Connection::open_in_memory().unwrap();
-        init_db(&conn).unwrap();
-        conn
+/// Scope filter for narrowing search results by file path or module
+pub struct SearchScope<'a> {
+    pub in_file: Option<&'a str>,
+    pub module: Option<&'a str>,
+    /// Directory prefix filter: only return results under this path (relative to project root)
+    pub dir_prefix: Option<&'a str>,
+    /// Only symbols with this visibility (public, private, internal, ...); ignored for refs
+    pub visibility: Option<&'a str>,
+    /// Only symbols of these kinds; empty for all; ignored for refs
+    pub kinds: &'a [SymbolKind],
+    /// Only files in these languages (`files.language`); empty for all
+    pub languages: &'a [String],
+    /// Only files matching one of these path globs; empty for all
+    pub paths: &'a [String],
+    /// Skip files matching any of these path globs
+    pub exclude_paths: &'a [String],
+    /// Only these files (paths relative to the project root, e.g. those changed since a
+    /// revision); None for all
+    pub changed_files: Option<&'a [String]>,
+    /// Some(false) skips test files (`files.is_test`), Some(true) keeps only them; None for all
+    pub tests: Option<bool>,
+    /// Name searches match the query's letter case; otherwise case is ignored (not a filter, so
+    /// `is_empty` does not look at it)
+    pub case_sensitive: bool,
+}
+
+/// Expand a path glob into plain wildcard patterns where `*` matches any run of characters
+/// (`/` included, as in SQLite GLOB) and `?` one character. `**/` also matches no directory,
+/// so `**/test/**` yields `test/*` and `*/test/*`
+fn glob_alternatives(glob: &str) -> Vec<String> {
+    let mut alternatives = vec![String::new()];
+    let mut rest = glob.trim_start_matches("./");
+    let mut at_segment_start = true;
+    while let Some(c) = rest.chars().next() {
+        if at_segment_start && rest.starts_with("**/") {
+            alternatives = alternatives.into_iter().flat_map(|a| [a.clone(), a + "*/"]).collect();
+            rest = &rest[3..];
+            continue;
+        }
+        let len = if rest.starts_with("**") { 2 } else { c.len_utf8() };
+        for alternative in &mut alternatives {
+            alternative.push(c);
+        }
+        at_segment_start = c == '/';
+        rest = &rest[len..];
+    }
+    alternatives.sort();
+    alternatives.dedup();
+    alternatives
+}
+
+/// Match `text` against a pattern from `glob_alternatives`, the way SQLite GLOB would
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    // Last `*` seen and the text position it currently stretches to
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || (p[pi] != '*' && p[pi] == t[ti])) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Whether a project-relative path matches a path glob (`ui/**`, `**/*Dao.kt`) as `--path` does
+pub fn glob_matches(glob: &str, path: &str) -> bool {
+    glob_alternatives(glob).iter().any(|pattern| wildcard_match(pattern, path))
+}
+
+/// Path globs expanded once, for matching many paths
+#[derive(Debug, Clone, Default)]
+pub struct PathGlobs {
+    patterns: Vec<String>,
+}
+
+impl PathGlobs {
+    pub fn new(globs: &[String]) -> Self {
+        PathGlobs { patterns: globs.iter().flat_map(|g| glob_alternatives(g)).collect() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether a project-relative path matches any of the globs, as `glob_matches` would
+    pub fn matches(&self, path: &str) -> bool {
+        self.patterns.iter().any(|pattern| wildcard_match(pattern, path))
+    }
+}
+
+/// `(f.path GLOB ? OR ...)` over every expansion of `globs`, with `[` escaped for GLOB
+fn path_glob_condition(globs: &[String]) -> (String, Vec<String>) {
+    let patterns: Vec<String> = globs.iter().flat_map(|g| glob_alternatives(g)).map(|p| p.replace('[', "[[]")).collect();
+    (format!("({})", vec!["f.path GLOB ?"; patterns.len()].join(" OR ")), patterns)
+}
+
+impl<'a> SearchScope<'a> {
+    pub fn none() -> Self {
+        SearchScope { in_file: None, module: None, dir_prefix: None, visibility: None, kinds: &[], languages: &[], paths: &[], exclude_paths: &[], changed_files: None, tests: None, case_sensitive: false }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.in_file.is_none()
+            && self.module.is_none()
+            && self.dir_prefix.is_none()
+            && self.visibility.is_none()
+            && self.kinds.is_empty()
+            && self.languages.is_empty()
+            && self.paths.is_empty()
+            && self.exclude_paths.is_empty()
+            && self.changed_files.is_none()
+            && self.tests.is_none()
+    }
+
+    /// Whether a path passes the file filters (language, path globs, changed and test files); for
+    /// results not found through SQL
+    pub fn allows_path(&self, path: &str) -> bool {
+        let matches = |globs: &[String]| globs.iter().flat_map(|g| glob_alternatives(g)).any(|p| wildcard_match(&p, path));
+        (self.languages.is_empty()
+            || parsers::language_of_path(path).is_some_and(|lang| self.languages.iter().any(|l| l == lang)))
+            && (self.paths.is_empty() || matches(self.paths))
+            && !matches(self.exclude_paths)
+            && self.changed_files.is_none_or(|files| files.iter().any(|f| f == path))
+            && self.tests.is_none_or(|tests| parsers::is_test_path(path) == tests)
+    }
+
+    /// Build WHERE clause fragment and collect params
+    fn path_condition(&self) -> (String, Vec<String>) {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+        if let Some(prefix) = self.dir_prefix {
+            conditions.push("f.path LIKE ?".to_string());
+            params.push(format!("{}%", prefix));
+        }
+        if let Some(file) = self.in_file {
+            conditions.push("f.path LIKE ?".to_string());
+            params.push(format!("%{}", file));
+        }
+        if let Some(module) = self.module {
+            conditions.push("f.path LIKE ?".to_string());
+            params.push(format!("{}%", module));
+        }
+        if !self.languages.is_empty() {
+            conditions.push(format!("f.language IN ({})", vec!["?"; self.languages.len()].join(", ")));
+            params.extend(self.languages.iter().cloned());
+        }
+        if !self.paths.is_empty() {
+            let (condition, globs) = path_glob_condition(self.paths);
+            conditions.push(condition);
+            params.extend(globs);
+        }
+        if !self.exclude_paths.is_empty() {
+            let (condition, globs) = path_glob_condition(self.exclude_paths);
+            conditions.push(format!("NOT {}", condition));
+            params.extend(globs);
+        }
+        if let Some(files) = self.changed_files {
+            conditions.push(format!("f.path IN ({})", vec!["?"; files.len()].join(", ")));
+            params.extend(files.iter().cloned());
+        }
+        if let Some(tests) = self.tests {
+            conditions.push(format!("f.is_test = {}", i32::from(tests)));
+        }
+        if conditions.is_empty() {
+            (String::new(), params)
+        } else {
+            (format!(" AND {}", conditions.join(" AND ")), params)
+        }
+    }
+
+    /// Path condition plus symbol-level filters; queries must alias symbols as `s`
+    pub(crate) fn symbol_condition(&self) -> (String, Vec<String>) {
+        let (mut clause, mut params) = self.path_condition();
+        if let Some(visibility) = self.visibility {
+            clause.push_str(" AND s.visibility = ?");
+            params.push(visibility.to_string());
+        }
+        if !self.kinds.is_empty() {
+            clause.push_str(&format!(" AND s.kind IN ({})", vec!["?"; self.kinds.len()].join(", ")));
+            params.extend(self.kinds.iter().map(|k| k.as_str().to_string()));
+        }
+        (clause, params)
+    }
+}
+
+/// Search symbols with scope filtering (file/module)
+pub fn search_symbols_scoped(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<SearchResult>> {
+    if query.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    if !has_fts_index(conn) {
+        return search_symbols_like(conn, query, limit, scope);
+    }
+
+    let fts_query = name_fts_query(query, &search_settings().synonyms);
+    fts_search(conn, &fts_query, Some(query.trim_end_matches('*')), limit, scope)
+}
+
+/// Symbols matching an FTS5 expression (from `boolean_fts_query`, or written by the user for
+/// `search --raw-fts`) within the scope
+pub fn search_symbols_fts(
+    conn: &Connection,
+    fts_query: &str,
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<SearchResult>> {
+    fts_search(conn, fts_query, None, limit, scope)
+}
+
+/// Run an FTS5 query over symbols; results are ranked by name against `rank_name` when given
+/// (see `name_rank_order`), by FTS relevance otherwise
+fn fts_search(
+    conn: &Connection,
+    fts_query: &str,
+    rank_name: Option<&str>,
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<SearchResult>> {
+    if !has_fts_index(conn) {
+        anyhow::bail!("FTS5 queries need an index built with FTS5 support");
+    }
+    let (mut scope_clause, scope_params) = scope.symbol_condition();
+    // FTS5 folds case; a name search can still require the query as typed
+    if rank_name.is_some() && scope.case_sensitive {
+        let name_param = 2 + scope_params.len();
+        scope_clause.push_str(&format!(" AND (instr(s.name, ?{0}) > 0 OR instr(s.signature, ?{0}) > 0)", name_param));
+    }
+
+    let sql = format!(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM symbols_fts fts
+        JOIN symbols s ON fts.rowid = s.id
+        JOIN files f ON s.file_id = f.id
+        WHERE symbols_fts MATCH ?1{}
+        ORDER BY {}
+        LIMIT ?{}
+        "#,
+        scope_clause,
+        match rank_name {
+            Some(_) => name_rank_order(2 + scope_params.len()),
+            None => "fts.rank, s.name, f.path, s.line".to_string(),
+        },
+        2 + scope_params.len() + usize::from(rank_name.is_some())
+    );
+
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    all_params.push(Box::new(fts_query.to_string()));
+    for p in &scope_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    if let Some(name) = rank_name {
+        all_params.push(Box::new(name.to_string()));
+    }
+    all_params.push(Box::new(limit as i64));
+
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
+    let results = stmt
+        .query_map(param_refs.as_slice(), SearchResult::from_row)?
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Invalid FTS5 query: {}", fts_query))?;
+
+    Ok(results)
+}
+
+/// CamelHump abbreviation search (`PRImpl` finds `PaymentRepositoryImpl`): candidates whose
+/// `humps` contain the query's hump initials in order, confirmed by `fuzzy::camel_match`;
+/// names with the fewest humps come first
+pub fn search_camel_humps(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<SearchResult>> {
+    /// Candidates checked per requested result
+    const CANDIDATES_PER_RESULT: usize = 20;
+    let skeleton = fuzzy::hump_skeleton(query);
+    if skeleton.is_empty() {
+        return Ok(vec![]);
+    }
+    let pattern = format!("*{}*", skeleton.chars().map(String::from).collect::<Vec<_>>().join("*"));
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    let sql = format!(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.humps GLOB ?1{}
+        ORDER BY length(s.humps), length(s.name), f.path, s.line
+        LIMIT ?{}
+        "#,
+        scope_clause,
+        2 + scope_params.len()
+    );
+
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(pattern)];
+    for p in scope_params {
+        all_params.push(Box::new(p));
+    }
+    all_params.push(Box::new((limit * CANDIDATES_PER_RESULT) as i64));
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
+
+    let mut results = Vec::new();
+    let mut stmt = conn.prepare(&sql)?;
+    for row in stmt.query_map(param_refs.as_slice(), SearchResult::from_row)? {
+        let result = row?;
+        if fuzzy::camel_match(query, &result.name) {
+            results.push(result);
+            if results.len() >= limit {
+                break;
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Split a qualified name into identifier segments (`a.b::C` -> `[a, b, C]`)
+fn qualified_segments(name: &str) -> Vec<&str> {
+    name.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Whether a plain search query reads as a qualified name: identifier segments joined by `.`
+/// or `::` (`billing.Invoice`, `net::http::Client`), nothing else
+pub fn is_qualified_query(query: &str) -> bool {
+    let separators_only = query.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.' || c == ':');
+    let well_formed = query
+        .split("::")
+        .flat_map(|part| part.split('.'))
+        .all(|segment| segment.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_'));
+    separators_only && well_formed && qualified_segments(query).len() >= 2
+}
+
+/// Declarations among `results` that `query` names equally well: the same short name, qualified
+/// names ending with the query's segments, two or more distinct qualified names. Each comes with
+/// the shortest suffix of its qualified name that tells it apart (`billing.Invoice` for
+/// `com.example.billing.Invoice` next to `com.example.legacy.Invoice`); empty when unambiguous
+pub fn ambiguous_declarations(query: &str, results: &[SearchResult]) -> Vec<(String, String)> {
+    let wanted = qualified_segments(query);
+    let Some(short_name) = wanted.last() else { return vec![] };
+    let mut names: Vec<&str> = results
+        .iter()
+        .filter(|r| r.kind != "import" && r.name == *short_name)
+        .filter_map(|r| r.qualified_name.as_deref())
+        .filter(|q| qualified_segments(q).ends_with(&wanted))
+        .collect();
+    names.sort();
+    names.dedup();
+    if names.len() < 2 {
+        return vec![];
+    }
+
+    let segments: Vec<Vec<&str>> = names.iter().map(|n| qualified_segments(n)).collect();
+    names
+        .iter()
+        .zip(&segments)
+        .map(|(name, own)| {
+            let shares = |other: &Vec<&str>, k: usize| other.len() >= k && other[other.len() - k..] == own[own.len() - k..];
+            let k = (1..=own.len())
+                .find(|&k| segments.iter().filter(|other| shares(other, k)).count() == 1)
+                .unwrap_or(own.len());
+            // Cut the name as written, keeping its own separators
+            let is_word = |c: char| c.is_alphanumeric() || c == '_';
+            let starts: Vec<usize> = name
+                .char_indices()
+                .filter(|&(i, c)| is_word(c) && name[..i].chars().next_back().is_none_or(|p| !is_word(p)))
+                .map(|(i, _)| i)
+                .collect();
+            (name[starts[starts.len() - k]..].to_string(), name.to_string())
+        })
+        .collect()
+}
+
+/// Search by qualified name (`com.example.billing.Invoice`, `billing.Invoice`, `net::http::Client`).
+/// Segments must appear consecutively in the qualified name; separators are interchangeable.
+/// Exact matches come first, then names ending with the query, then other matches.
+pub fn search_qualified(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<SearchResult>> {
+    let prefix = query.ends_with('*');
+    let segments = qualified_segments(query);
+    if segments.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    let (sql, pattern) = if has_fts_index(conn) {
+        (
+            format!(
+                r#"
+                SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+                FROM symbols_fts fts
+                JOIN symbols s ON fts.rowid = s.id
+                JOIN files f ON s.file_id = f.id
+                WHERE symbols_fts MATCH ?1{}
+                LIMIT ?{}
+                "#,
+                scope_clause,
+                2 + scope_params.len()
+            ),
+            format!("{{qualified_name}} : \"{}\"{}", segments.join(" "), if prefix { "*" } else { "" }),
+        )
+    } else {
+        (
+            format!(
+                r#"
+                SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+                FROM symbols s
+                JOIN files f ON s.file_id = f.id
+                WHERE s.qualified_name LIKE ?1{}
+                LIMIT ?{}
+                "#,
+                scope_clause,
+                2 + scope_params.len()
+            ),
+            format!("%{}%", segments.join("%")),
+        )
+    };
+
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![Box::new(pattern)];
+    for p in &scope_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    // Over-fetch so exact matches are not cut off before ranking
+    all_params.push(Box::new((limit * 10) as i64));
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
+
+    let mut results = conn
+        .prepare(&sql)?
+        .query_map(param_refs.as_slice(), SearchResult::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let rank = |r: &SearchResult| {
+        let name = r.qualified_name.as_deref().unwrap_or(&r.name);
+        let found = qualified_segments(name);
+        if found == segments {
+            0
+        } else if found.ends_with(&segments) {
+            1
+        } else {
+            2
+        }
+    };
+    results.sort_by_cached_key(|r| (rank(r), r.qualified_name.as_ref().map_or(0, |q| q.len())));
+    results.truncate(limit);
+    Ok(results)
+}
+
+/// Search doc comments: every word of the query (or, with the FTS index, one of its
+/// `[search] synonyms`) must appear in the symbol's doc, in the same letter case if
+/// `scope.case_sensitive`. Returns the matches in `window` with their doc text, best FTS rank
+/// first, and how many match.
+pub fn search_docs(
+    conn: &Connection,
+    query: &str,
+    window: Window,
+    scope: &SearchScope,
+) -> Result<(Vec<(SearchResult, String)>, usize)> {
+    let words: Vec<&str> = query
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+        .collect();
+    if words.is_empty() {
+        return Ok((vec![], 0));
+    }
+
+    let (mut scope_clause, mut scope_params) = scope.symbol_condition();
+    // FTS5 and LIKE ignore case; each word as typed must appear too
+    if scope.case_sensitive {
+        for word in &words {
+            scope_clause.push_str(" AND instr(s.doc, ?) > 0");
+            scope_params.push(word.to_string());
+        }
+    }
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    let sql = if has_fts_index(conn) {
+        let synonyms = &search_settings().synonyms;
+        let terms: Vec<String> = words.iter().map(|w| fts_term_with_synonyms(w, "", synonyms)).collect();
+        all_params.push(Box::new(format!("{{doc}} : {}", terms.join(" "))));
+        format!(
+            r#"
+            SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility, s.doc
+            FROM symbols_fts fts
+            JOIN symbols s ON fts.rowid = s.id
+            JOIN files f ON s.file_id = f.id
+            WHERE symbols_fts MATCH ?1{}
+            ORDER BY fts.rank
+            "#,
+            scope_clause
+        )
+    } else {
+        let conditions: Vec<String> = (1..=words.len()).map(|i| format!("s.doc LIKE ?{}", i)).collect();
+        for word in &words {
+            all_params.push(Box::new(format!("%{}%", word)));
+        }
+        format!(
+            r#"
+            SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility, s.doc
+            FROM symbols s
+            JOIN files f ON s.file_id = f.id
+            WHERE {}{}
+            "#,
+            conditions.join(" AND "),
+            scope_clause
+        )
+    };
+    for p in &scope_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    query_window(conn, &sql, &all_params, window, |row| Ok((SearchResult::from_row(row)?, row.get(8)?)))
+}
+
+/// A symbol's text for `ast-index embed`
+pub struct EmbedSource {
+    pub symbol_id: i64,
+    pub name: String,
+    pub kind: String,
+    pub signature: Option<String>,
+    pub doc: Option<String>,
+}
+
+/// Model that computed the stored embeddings (`metadata.embed_model`)
+pub fn embedding_model(conn: &Connection) -> Result<Option<String>> {
+    match conn.query_row("SELECT value FROM metadata WHERE key = 'embed_model'", [], |row| row.get(0)) {
+        Ok(model) => Ok(Some(model)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Record that embeddings come from `model`; vectors of a different model are dropped.
+/// Returns whether any were dropped
+pub fn set_embedding_model(conn: &Connection, model: &str) -> Result<bool> {
+    let previous = embedding_model(conn)?;
+    let changed = previous.as_deref().is_some_and(|previous| previous != model);
+    if changed {
+        conn.execute("DELETE FROM symbol_embeddings", [])?;
+    }
+    conn.execute(
+        "INSERT OR REPLACE INTO metadata (key, value) VALUES ('embed_model', ?1)",
+        params![model],
+    )?;
+    Ok(changed)
+}
+
+/// Symbols that have no embedding yet: new since the last `embed`, or re-indexed
+pub fn symbols_without_embedding(conn: &Connection) -> Result<Vec<EmbedSource>> {
+    let sources = conn
+        .prepare(
+            "SELECT s.id, s.name, s.kind, s.signature, s.doc FROM symbols s
+             WHERE NOT EXISTS (SELECT 1 FROM symbol_embeddings e WHERE e.symbol_id = s.id)
+             ORDER BY s.id",
+        )?
+        .query_map([], |row| {
+            Ok(EmbedSource {
+                symbol_id: row.get(0)?,
+                name: row.get(1)?,
+                kind: row.get(2)?,
+                signature: row.get(3)?,
+                doc: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(sources)
+}
+
+/// Store one vector per symbol, replacing earlier ones
+pub fn store_embeddings(conn: &Connection, embeddings: &[(i64, Vec<f32>)]) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut stmt = tx.prepare("INSERT OR REPLACE INTO symbol_embeddings (symbol_id, vector) VALUES (?1, ?2)")?;
+        for (symbol_id, vector) in embeddings {
+            stmt.execute(params![symbol_id, crate::embed::to_blob(vector)])?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Number of symbols with an embedding, and of all symbols
+pub fn embedding_counts(conn: &Connection) -> Result<(usize, usize)> {
+    let embedded: i64 = conn.query_row("SELECT COUNT(*) FROM symbol_embeddings", [], |row| row.get(0))?;
+    let total: i64 = conn.query_row("SELECT COUNT(*) FROM symbols", [], |row| row.get(0))?;
+    Ok((embedded as usize, total as usize))
+}
+
+/// Symbols ranked by cosine similarity of their embedding to `query`, most similar first.
+/// Vectors of another length than `query` are skipped
+pub fn search_symbols_semantic(
+    conn: &Connection,
+    query: &[f32],
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<(SearchResult, f32)>> {
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    let sql = format!(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility, e.vector
+        FROM symbol_embeddings e
+        JOIN symbols s ON e.symbol_id = s.id
+        JOIN files f ON s.file_id = f.id
+        WHERE length(e.vector) = ?1{}
+        "#,
+        scope_clause
+    );
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    all_params.push(Box::new((query.len() * 4) as i64));
+    for p in &scope_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
+
+    let mut results = conn
+        .prepare(&sql)?
+        .query_map(param_refs.as_slice(), |row| {
+            let vector: Vec<u8> = row.get(8)?;
+            Ok((SearchResult::from_row(row)?, crate::embed::cosine(query, &crate::embed::from_blob(&vector))))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    results.sort_by(|a, b| {
+        b.1.total_cmp(&a.1)
+            .then_with(|| a.0.path.cmp(&b.0.path))
+            .then_with(|| a.0.line.cmp(&b.0.line))
+    });
+    results.truncate(limit);
+    Ok(results)
+}
+
+/// Annotations other languages write for the same thing, searched along with a name given here
+/// (lowercase): (annotation, text its arguments must contain)
+const ANNOTATION_EQUIVALENTS: &[(&str, &[(&str, &str)])] = &[
+    // Rust #[deprecated] matches by case already; Swift @available(*, deprecated), C# [Obsolete]
+    ("deprecated", &[("available", "deprecated"), ("Obsolete", "")]),
+];
+
+/// Find symbols carrying an annotation (`Composable`, `@pytest.fixture`, `#[derive(Serialize)]`, `[HttpGet]`).
+/// A bare name also matches dotted/path suffixes (`fixture` -> `pytest.fixture`), derived traits
+/// and other languages' spelling of it (`Deprecated` -> `#[deprecated]`, `[Obsolete]`,
+/// `@available(*, deprecated)`); names ignore case unless `scope.case_sensitive`.
+/// `Name(text)` additionally requires `text` in the arguments.
+/// Returns the matches in `window` with the annotation as written, e.g. `Table(name = "users")`,
+/// and how many match.
+pub fn search_annotation(
+    conn: &Connection,
+    annotation: &str,
+    window: Window,
+    scope: &SearchScope,
+) -> Result<(Vec<(SearchResult, String)>, usize)> {
+    let query = annotation.trim();
+    let query = query.strip_prefix("#[").or_else(|| query.strip_prefix('[')).unwrap_or(query);
+    let query = query.strip_suffix(']').unwrap_or(query).trim_start_matches('@');
+    let (name, args) = match query.split_once('(') {
+        Some((name, args)) => (name.trim(), args.trim_end_matches(')').trim()),
+        None => (query.trim(), ""),
+    };
+    if name.is_empty() {
+        return Ok((vec![], 0));
+    }
+
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![
+        Box::new(name.to_string()),
+        Box::new(format!("%.{}", name)),
+        Box::new(format!("%::{}", name)),
+        Box::new(format!("%, {},%", name)),
+        Box::new(format!("%{}%", args)),
+    ];
+    // LIKE ignores case, so only the exact comparison needs NOCASE; matching case means the name
+    // as typed appears in the annotation or, for derives, its arguments
+    let (exact, cased) = if scope.case_sensitive {
+        ("a.name = ?1", " AND instr(a.name || ',' || COALESCE(a.args, ''), ?1) > 0")
+    } else {
+        ("a.name = ?1 COLLATE NOCASE", "")
+    };
+    let mut equivalents = String::new();
+    let lowercase = name.to_lowercase();
+    let aliases = ANNOTATION_EQUIVALENTS.iter().filter(|_| !scope.case_sensitive).find(|(n, _)| *n == lowercase);
+    for (alias, required) in aliases.map_or(&[][..], |(_, aliases)| aliases) {
+        let n = all_params.len();
+        equivalents.push_str(&format!(" OR (a.name = ?{} AND COALESCE(a.args, '') LIKE ?{})", n + 1, n + 2));
+        all_params.push(Box::new(alias.to_string()));
+        all_params.push(Box::new(format!("%{}%", required)));
+    }
+    let (scope_clause, scope_params) = scope.symbol_condition();
+    let sql = format!(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility, a.name, a.args
+        FROM symbol_annotations a
+        JOIN symbols s ON a.symbol_id = s.id
+        JOIN files f ON s.file_id = f.id
+        WHERE (({} OR a.name LIKE ?2 OR a.name LIKE ?3
+               OR (a.name = 'derive' AND ', ' || a.args || ',' LIKE ?4)){}{})
+          AND COALESCE(a.args, '') LIKE ?5{}
+        ORDER BY f.path, s.line
+        "#,
+        exact,
+        cased,
+        equivalents,
+        scope_clause
+    );
+
+    for p in &scope_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    query_window(conn, &sql, &all_params, window, |row| {
+        let name: String = row.get(8)?;
+        let args: Option<String> = row.get(9)?;
+        let written = match args {
+            Some(args) => format!("{}({})", name, args),
+            None => name,
+        };
+        Ok((SearchResult::from_row(row)?, written))
+    })
+}
+
+/// LIKE-based replacement for FTS search when FTS5 is unavailable.
+/// `Foo*` matches names starting with Foo, anything else matches name or signature substrings.
+fn search_symbols_like(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<SearchResult>> {
+    let (condition, pattern) = match query.strip_suffix('*') {
+        Some(prefix) => ("s.name LIKE ?1", format!("{}%", prefix)),
+        None => ("(s.name LIKE ?1 OR s.signature LIKE ?1)", format!("%{}%", query)),
+    };
+    let (mut scope_clause, scope_params) = scope.symbol_condition();
+    // LIKE ignores ASCII case; the query as typed (bound for ranking) narrows it down
+    if scope.case_sensitive {
+        let name_param = 2 + scope_params.len();
+        scope_clause.push_str(&if query.ends_with('*') {
+            format!(" AND substr(s.name, 1, length(?{0})) = ?{0}", name_param)
+        } else {
+            format!(" AND (instr(s.name, ?{0}) > 0 OR instr(s.signature, ?{0}) > 0)", name_param)
+        });
+    }
+
+    let sql = format!(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE {}{}
+        ORDER BY {}
+        LIMIT ?{}
+        "#,
+        condition,
+        scope_clause,
+        name_rank_order(2 + scope_params.len()),
+        3 + scope_params.len()
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    all_params.push(Box::new(pattern));
+    for p in &scope_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    all_params.push(Box::new(query.trim_end_matches('*').to_string()));
+    all_params.push(Box::new(limit as i64));
+
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
+    let results = stmt
+        .query_map(param_refs.as_slice(), SearchResult::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(results)
+}
+
+/// Find symbols by name with scope filtering
+pub fn find_symbols_by_name_scoped(
+    conn: &Connection,
+    name: &str,
+    kind: Option<&str>,
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<SearchResult>> {
+    if scope.is_empty() {
+        return find_symbols_by_name(conn, name, kind, limit);
+    }
+
+    let (scope_clause, scope_params) = scope.symbol_condition();
+
+    let mut sql = format!(
+        "SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility FROM symbols s JOIN files f ON s.file_id = f.id WHERE s.name = ?1{}",
+        scope_clause
+    );
+    if kind.is_some() {
+        sql.push_str(&format!(" AND s.kind = ?{}", 2 + scope_params.len()));
+        sql.push_str(&format!(" LIMIT ?{}", 3 + scope_params.len()));
+    } else {
+        sql.push_str(&format!(" LIMIT ?{}", 2 + scope_params.len()));
+    }
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    all_params.push(Box::new(name.to_string()));
+    for p in &scope_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    if let Some(k) = kind {
+        all_params.push(Box::new(k.to_string()));
+    }
+    all_params.push(Box::new(limit as i64));
+
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
+    let results = stmt
+        .query_map(param_refs.as_slice(), SearchResult::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(results)
+}
+
+/// Find class-like symbols with scope filtering
+pub fn find_class_like_scoped(
+    conn: &Connection,
+    name: &str,
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<SearchResult>> {
+    if scope.is_empty() {
+        return find_class_like(conn, name, limit);
+    }
+
+    let (scope_clause, scope_params) = scope.symbol_condition();
+
+    let sql = format!(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.name = ?1 AND s.kind IN ('class', 'interface', 'object', 'enum', 'protocol', 'struct', 'actor', 'package'){}
+        LIMIT ?{}
+        "#,
+        scope_clause,
+        2 + scope_params.len()
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    all_params.push(Box::new(name.to_string()));
+    for p in &scope_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    all_params.push(Box::new(limit as i64));
+
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
+    let results = stmt
+        .query_map(param_refs.as_slice(), SearchResult::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(results)
+}
+
+/// A symbol with the stored extent of its definition
+#[derive(Debug, Serialize)]
+pub struct SymbolDefinition {
+    #[serde(flatten)]
+    pub symbol: SearchResult,
+    /// Last line of the definition (NULL for indexes built before spans were tracked)
+    pub end_line: Option<i64>,
+    pub start_byte: Option<i64>,
+    pub end_byte: Option<i64>,
+}
+
+/// Find definitions by name with their line/byte extents.
+/// `Container.name` (e.g. `UserRepo.save`) also matches on the enclosing type.
+/// Returns the definitions in `window` and how many match.
+pub fn find_definitions(
+    conn: &Connection,
+    name: &str,
+    window: Window,
+    scope: &SearchScope,
+) -> Result<(Vec<SymbolDefinition>, usize)> {
+    let (container, short_name) = match name.rsplit_once('.') {
+        Some((container, short)) if !container.is_empty() && !short.is_empty() => (Some(container), short),
+        _ => (None, name),
+    };
+    let (scope_clause, scope_params) = scope.symbol_condition();
+
+    let sql = format!(
+        r#"
+        SELECT s.name, s.kind, s.line, s.signature, f.path, s.container, s.qualified_name, s.visibility,
+               s.end_line, s.start_byte, s.end_byte
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.name = ?1
+          AND s.kind NOT IN ('import', 'package')
+          AND (?2 IS NULL OR s.container = ?2 OR s.container LIKE '%.' || ?2){}
+        ORDER BY f.path, s.line
+        "#,
+        scope_clause
+    );
+
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    all_params.push(Box::new(short_name.to_string()));
+    all_params.push(Box::new(container.map(str::to_string)));
+    for p in &scope_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    query_window(conn, &sql, &all_params, window, |row| {
+        Ok(SymbolDefinition {
+            symbol: SearchResult::from_row(row)?,
+            end_line: row.get(8)?,
+            start_byte: row.get(9)?,
+            end_byte: row.get(10)?,
+        })
+    })
+}
+
+/// The single best definition of `name` for go-to-definition. With `from` (a file's relative
+/// path and optionally a line) the file's point of view decides: the definition an import of
+/// the name points at (aliases included), then one in the same file (the closest above the
+/// line), then one reached through a wildcard import, then one in the same directory. Ties and
+/// lookups without `from` prefer types over functions over other kinds, then path and line
+pub fn find_best_definition(
+    conn: &Connection,
+    name: &str,
+    from: Option<(&str, Option<usize>)>,
+) -> Result<Option<SymbolDefinition>> {
+    /// Definitions of one name that get ranked
+    const CANDIDATES: usize = 1000;
+    let none = SearchScope::none();
+
+    // Imports of the originating file that can bring `name` into scope
+    let mut imported: Vec<String> = Vec::new();
+    let mut wildcards: Vec<String> = Vec::new();
+    if let Some((path, _)) = from {
+        let mut stmt = conn.prepare_cached(
+            "SELECT i.name, i.path FROM imports i JOIN files f ON i.file_id = f.id
+             WHERE f.path = ?1 AND (i.name = ?2 OR i.name = '*')",
+        )?;
+        let rows = stmt.query_map(params![path, name], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (local, target) = row?;
+            if local == "*" { wildcards.push(target) } else { imported.push(target) }
+        }
+    }
+
+    let mut candidates = find_definitions(conn, name, Window::first(CANDIDATES), &none)?.0;
+    // `import a.b.User as U`: the definition is found under its own name
+    for target in &imported {
+        if let Some((_, _, simple)) = split_qualified(target) {
+            if simple != name {
+                candidates.extend(find_definitions(conn, simple, Window::first(CANDIDATES), &none)?.0);
+            }
+        }
+    }
+
+    let directory = |path: &str| path.rsplit_once('/').map_or("", |(dir, _)| dir).to_string();
+    let rank = |d: &SymbolDefinition| {
+        let s = &d.symbol;
+        let qualified = s.qualified_name.as_deref().unwrap_or("");
+        let namespace = split_qualified(qualified).map_or("", |(namespace, _, _)| namespace);
+        let (origin, distance) = match from {
+            _ if imported.iter().any(|target| target == qualified) => (0, 0),
+            Some((path, line)) if s.path == path => {
+                // Closest definition at or above the line, then the ones below it
+                let line = line.unwrap_or(0) as i64;
+                (1, if s.line <= line { line - s.line } else { i64::MAX / 2 + s.line })
+            }
+            _ if wildcards.iter().any(|w| w == namespace) => (2, 0),
+            Some((path, _)) if directory(&s.path) == directory(path) => (3, 0),
+            _ => (4, 0),
+        };
+        let kind = match s.kind.as_str() {
+            "class" | "interface" | "object" | "enum" | "protocol" | "struct" | "actor" | "typealias" => 0,
+            "function" => 1,
+            _ => 2,
+        };
+        (origin, distance, kind, s.path.clone(), s.line)
+    };
+    Ok(candidates.into_iter().min_by_key(rank))
+}
+
+/// Split a qualified name into its namespace, separator and simple name
+/// (`com.example.User` -> `com.example`, `.`, `User`; `crate::db::open` -> `crate::db`, `::`, `open`)
+pub fn split_qualified(qualified: &str) -> Option<(&str, &str, &str)> {
+    let sep = if qualified.contains("::") { "::" } else { "." };
+    let (namespace, name) = qualified.rsplit_once(sep)?;
+    (!namespace.is_empty() && !name.is_empty()).then_some((namespace, sep, name))
+}
+
+/// Find references that resolve to the fully qualified `qualified` name: uses of the
+/// name (or its alias) in files importing it, in files importing its whole namespace,
+/// and in files of the same namespace
+pub fn find_references_qualified(
+    conn: &Connection,
+    qualified: &str,
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<RefResult>> {
+    let Some((namespace, sep, name)) = split_qualified(qualified) else {
+        return find_references_scoped(conn, qualified, limit, scope);
+    };
+    let (scope_clause, scope_params) = scope.path_condition();
+
+    let sql = format!(
+        r#"
+        SELECT r.name, r.line, r.context, f.path, r.ref_kind, r.count, r.sample_lines
+        FROM refs r
+        JOIN files f ON r.file_id = f.id
+        WHERE (r.file_id, r.id) IN (
+            SELECT r2.file_id, r2.id FROM imports i JOIN refs r2 ON r2.file_id = i.file_id AND r2.name = i.name
+            WHERE i.path = ?1
+            UNION
+            SELECT r2.file_id, r2.id FROM imports i JOIN refs r2 ON r2.file_id = i.file_id
+            WHERE i.name = '*' AND i.path = ?2 AND r2.name = ?3
+            UNION
+            SELECT r2.file_id, r2.id FROM refs r2 JOIN symbols s ON s.file_id = r2.file_id
+            WHERE r2.name = ?3 AND s.kind != 'import' AND s.qualified_name = ?2 || ?4 || s.name
+        ){}
+        ORDER BY f.path, r.line
+        LIMIT ?{}
+        "#,
+        scope_clause,
+        5 + scope_params.len()
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![
+        Box::new(qualified.to_string()),
+        Box::new(namespace.to_string()),
+        Box::new(name.to_string()),
+        Box::new(sep.to_string()),
+    ];
+    for p in &scope_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    all_params.push(Box::new(limit as i64));
+
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
+    let results = stmt
+        .query_map(param_refs.as_slice(), RefResult::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(results)
+}
+
+/// Find references with scope filtering
+pub fn find_references_scoped(
+    conn: &Connection,
+    name: &str,
+    limit: usize,
+    scope: &SearchScope,
+) -> Result<Vec<RefResult>> {
+    if scope.is_empty() {
+        return find_references(conn, name, limit);
+    }
+
+    let (scope_clause, scope_params) = scope.path_condition();
+
+    let sql = format!(
+        r#"
+        SELECT r.name, r.line, r.context, f.path, r.ref_kind, r.count, r.sample_lines
+        FROM refs r
+        JOIN files f ON r.file_id = f.id
+        WHERE r.name = ?1{}
+        ORDER BY f.path, r.line
+        LIMIT ?{}
+        "#,
+        scope_clause,
+        2 + scope_params.len()
+    );
+
+    let mut stmt = conn.prepare_cached(&sql)?;
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+    all_params.push(Box::new(name.to_string()));
+    for p in &scope_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    all_params.push(Box::new(limit as i64));
+
+    let param_refs: Vec<&dyn rusqlite::types::ToSql> = all_params.iter().map(|p| p.as_ref()).collect();
+    let results = stmt
+        .query_map(param_refs.as_slice(), RefResult::from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(results)
+}
+
+/// Whether the index holds references; `false` for one built by `rebuild --no-refs` or with
+/// `[index] refs = "none"`, whose updates skip them too
+pub fn refs_indexed(conn: &Connection) -> bool {
+    conn.query_row("SELECT value FROM metadata WHERE key = 'refs'", [], |row| row.get::<_, String>(0))
+        .map_or(true, |value| value != "none")
+}
+
+/// Record whether a new index holds references
+pub fn set_refs_indexed(conn: &Connection, indexed: bool) -> Result<()> {
+    if indexed {
+        conn.execute("DELETE FROM metadata WHERE key = 'refs'", [])?;
+    } else {
+        conn.execute("INSERT OR REPLACE INTO metadata (key, value) VALUES ('refs', 'none')", [])?;
+    }
+    Ok(())
+}
+
+/// Languages a `rebuild --languages` index was restricted to; empty for an index of all
+/// languages. `update` and `watch` stay within them
+pub fn indexed_languages(conn: &Connection) -> Vec<String> {
+    conn.query_row("SELECT value FROM metadata WHERE key = 'languages'", [], |row| row.get::<_, String>(0))
+        .map(|value| value.split(',').filter(|s| !s.is_empty()).map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Record the languages a new index was restricted to (empty for all)
+pub fn set_indexed_languages(conn: &Connection, languages: &[&str]) -> Result<()> {
+    if languages.is_empty() {
+        conn.execute("DELETE FROM metadata WHERE key = 'languages'", [])?;
+    } else {
+        conn.execute(
+            "INSERT OR REPLACE INTO metadata (key, value) VALUES ('languages', ?1)",
+            params![languages.join(",")],
+        )?;
+    }
+    Ok(())
+}
+
+/// Commit a `rebuild --rev` index was built from; None for an index of the worktree
+pub fn indexed_revision(conn: &Connection) -> Option<String> {
+    conn.query_row("SELECT value FROM metadata WHERE key = 'revision'", [], |row| row.get(0)).ok()
+}
+
+/// Record the commit a new index was built from
+pub fn set_indexed_revision(conn: &Connection, sha: &str) -> Result<()> {
+    conn.execute("INSERT OR REPLACE INTO metadata (key, value) VALUES ('revision', ?1)", params![sha])?;
+    Ok(())
+}
+
+/// Get extra source roots stored in metadata
+pub fn get_extra_roots(conn: &Connection) -> Result<Vec<String>> {
+    let result: Result<String, _> = conn.query_row(
+        "SELECT value FROM metadata WHERE key = 'extra_roots'",
+        [],
+        |row| row.get(0),
+    );
+    match result {
+        Ok(json) => {
+            let roots: Vec<String> = serde_json::from_str(&json).unwrap_or_default();
+            Ok(roots)
+        }
+        Err(_) => Ok(vec![]),
+    }
+}
+
+/// Add an extra source root
+pub fn add_extra_root(conn: &Connection, path: &str) -> Result<()> {
+    let mut roots = get_extra_roots(conn)?;
+    if !roots.contains(&path.to_string()) {
+        roots.push(path.to_string());
+    }
+    let json = serde_json::to_string(&roots)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO metadata (key, value) VALUES ('extra_roots', ?1)",
+        params![json],
+    )?;
+    Ok(())
+}
+
+/// Remove an extra source root
+pub fn remove_extra_root(conn: &Connection, path: &str) -> Result<bool> {
+    let mut roots = get_extra_roots(conn)?;
+    let len_before = roots.len();
+    roots.retain(|r| r != path);
+    if roots.len() == len_before {
+        return Ok(false);
+    }
+    let json = serde_json::to_string(&roots)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO metadata (key, value) VALUES ('extra_roots', ?1)",
+        params![json],
+    )?;
+    Ok(true)
+}
+
+/// Counts of rows copied by `merge_database`
+#[derive(Debug, Default, Serialize)]
+pub struct MergeStats {
+    pub files: usize,
+    /// Files already in the target index and kept there
+    pub skipped_files: usize,
+    /// Files already in the target index, replaced by a newer copy from the source
+    pub replaced_files: usize,
+    pub symbols: usize,
+    pub refs: usize,
+}
+
+/// What merging does with a file whose path is already in the target index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Duplicates {
+    /// Keep the target's copy
+    Keep,
+    /// Keep whichever copy has the newer mtime; the target's copy wins a tie
+    Newer,
+}
+
+/// Columns of `table` present in both the main and the attached `src` schema
+fn shared_columns(conn: &Connection, table: &str, skip: &[&str]) -> Result<Vec<String>> {
+    let cols = |schema: &str| -> Result<Vec<String>> {
+        let mut stmt = conn.prepare(&format!("PRAGMA {}.table_info({})", schema, table))?;
+        let names = stmt.query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(names)
+    };
+    let src_cols = cols("src")?;
+    Ok(cols("main")?
+        .into_iter()
+        .filter(|c| src_cols.contains(c) && !skip.contains(&c.as_str()))
+        .collect())
+}
+
+/// Merge files, symbols, inheritance, annotations and refs from another index DB into `conn`.
+/// File paths are prefixed with `path_prefix`; files whose prefixed path already
+/// exists are skipped together with their symbols and refs.
+pub fn merge_database(conn: &mut Connection, src_db: &Path, path_prefix: &str) -> Result<MergeStats> {
+    merge_database_with(conn, src_db, path_prefix, Duplicates::Keep)
+}
+
+/// `merge_database` with a choice of which copy of a file in both indexes survives; replacing
+/// deletes the target's rows for the file, so `conn` needs foreign keys on
+pub fn merge_database_with(conn: &mut Connection, src_db: &Path, path_prefix: &str, duplicates: Duplicates) -> Result<MergeStats> {
+    conn.execute("ATTACH DATABASE ?1 AS src", params![src_db.to_string_lossy().as_ref()])
+        .with_context(|| format!("Failed to attach {}", src_db.display()))?;
+    let (refs_source, shard_aliases) = shards::attach_source(conn, "src", src_db)?;
+    let result = merge_attached(conn, path_prefix, &refs_source, duplicates);
+    for alias in shard_aliases {
+        conn.execute("DETACH DATABASE ?1", params![alias])?;
+    }
+    conn.execute("DETACH DATABASE src", [])?;
+    result
+}
+
+/// `refs_source` is `src.refs`, or the union of its shards for a sharded source index
+fn merge_attached(conn: &mut Connection, path_prefix: &str, refs_source: &str, duplicates: Duplicates) -> Result<MergeStats> {
+    let file_cols = shared_columns(conn, "files", &["id", "path"])?;
+    let symbol_cols = shared_columns(conn, "symbols", &["id", "file_id"])?;
+    let ref_cols = shared_columns(conn, "refs", &["id", "file_id"])?;
+    let tx = conn.transaction()?;
+    let mut stats = MergeStats::default();
+
+    if duplicates == Duplicates::Newer {
+        stats.replaced_files = tx.execute(
+            "DELETE FROM main.files WHERE id IN (
+                SELECT f.id FROM main.files f JOIN src.files s ON f.path = ?1 || s.path WHERE s.mtime > f.mtime
+            )",
+            params![path_prefix],
+        )?;
+    }
+
+    let src_files: i64 = tx.query_row("SELECT COUNT(*) FROM src.files", [], |row| row.get(0))?;
+    tx.execute("DROP TABLE IF EXISTS temp.merge_files", [])?;
+    tx.execute(
+        "CREATE TEMP TABLE merge_files AS
+         SELECT id AS old_id, ?1 || path AS path, NULL AS new_id
+         FROM src.files WHERE ?1 || path NOT IN (SELECT path FROM main.files)",
+        params![path_prefix],
+    )?;
+    let cols = file_cols.join(", ");
+    let src_cols = file_cols.iter().map(|c| format!("f.{}", c)).collect::<Vec<_>>().join(", ");
+    stats.files = tx.execute(
+        &format!(
+            "INSERT INTO main.files (path, {cols})
+             SELECT m.path, {src_cols} FROM temp.merge_files m JOIN src.files f ON f.id = m.old_id"
+        ),
+        [],
+    )?;
+    stats.skipped_files = src_files as usize - stats.files;
+    tx.execute(
+        "UPDATE temp.merge_files SET new_id = (SELECT id FROM main.files f WHERE f.path = merge_files.path)",
+        [],
+    )?;
+
+    // Symbol ids are shifted by the current max id so inheritance rows can follow them
+    let offset: i64 = tx.query_row("SELECT COALESCE(MAX(id), 0) FROM main.symbols_data", [], |row| row.get(0))?;
+    let cols = symbol_cols.join(", ");
+    let src_cols = symbol_cols.iter().map(|c| format!("s.{}", c)).collect::<Vec<_>>().join(", ");
+    // Inserts through the symbols view report no changes, so count first
+    let src_symbols = "FROM src.symbols s JOIN temp.merge_files m ON s.file_id = m.old_id";
+    stats.symbols = tx.query_row(&format!("SELECT COUNT(*) {src_symbols}"), [], |row| row.get(0))?;
+    tx.execute(
+        &format!("INSERT INTO main.symbols (id, file_id, {cols}) SELECT s.id + ?1, m.new_id, {src_cols} {src_symbols}"),
+        params![offset],
+    )?;
+    tx.execute(
+        "INSERT INTO main.inheritance (child_id, parent_name, kind)
+         SELECT i.child_id + ?1, i.parent_name, i.kind
+         FROM src.inheritance i
+         JOIN src.symbols s ON i.child_id = s.id
+         JOIN temp.merge_files m ON s.file_id = m.old_id",
+        params![offset],
+    )?;
+
+    let src_has_annotations: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM src.sqlite_master WHERE type = 'table' AND name = 'symbol_annotations'",
+        [],
+        |row| row.get(0),
+    )?;
+    if src_has_annotations {
+        tx.execute(
+            "INSERT INTO main.symbol_annotations (symbol_id, name, args)
+             SELECT a.symbol_id + ?1, a.name, a.args
+             FROM src.symbol_annotations a
+             JOIN src.symbols s ON a.symbol_id = s.id
+             JOIN temp.merge_files m ON s.file_id = m.old_id",
+            params![offset],
+        )?;
+    }
+
+    let cols = ref_cols.join(", ");
+    let src_cols = ref_cols.iter().map(|c| format!("r.{}", c)).collect::<Vec<_>>().join(", ");
+    // Inserts through the refs view (routed to shards if any) report no changes, so count first
+    let src_refs = format!("FROM {refs_source} r JOIN temp.merge_files m ON r.file_id = m.old_id");
+    stats.refs = tx.query_row(&format!("SELECT COUNT(*) {src_refs}"), [], |row| row.get(0))?;
+    tx.execute(&format!("INSERT INTO refs (file_id, {cols}) SELECT m.new_id, {src_cols} {src_refs}"), [])?;
+
+    let src_has_imports: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM src.sqlite_master WHERE type = 'table' AND name = 'imports'",
+        [],
+        |row| row.get(0),
+    )?;
+    if src_has_imports {
+        tx.execute(
+            "INSERT INTO main.imports (file_id, name, path, line)
+             SELECT m.new_id, i.name, i.path, i.line
+             FROM src.imports i JOIN temp.merge_files m ON i.file_id = m.old_id",
+            [],
+        )?;
+    }
+
+    tx.execute("DROP TABLE temp.merge_files", [])?;
+    tx.commit()?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_db(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_memory_db_shared_and_saved() {
+        let path = Path::new(MEMORY_DB_PATH);
+        let conn = connect(path).unwrap();
+        init_db(&conn).unwrap();
+        conn.execute("INSERT INTO files (path, mtime, size) VALUES ('a.kt', 0, 0)", []).unwrap();
+        drop(conn);
+
+        let count: i64 = connect(path).unwrap().query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("cache").join("index.db");
+        save_memory_db(&dest).unwrap();
+        let saved = Connection::open(&dest).unwrap();
+        let count: i64 = saved.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_optimize_reclaims_free_pages() {
+        let conn = create_test_db();
+        conn.execute("INSERT INTO files (path, mtime, size) VALUES ('a.kt', 0, 0)", []).unwrap();
+        for i in 0..2000 {
+            conn.execute(
+                "INSERT INTO symbols (file_id, name, kind, line, signature) VALUES (1, ?1, 'class', ?2, ?3)",
+                params![format!("Symbol{}", i), i, "x".repeat(200)],
+            ).unwrap();
+        }
+        conn.execute("DELETE FROM symbols", []).unwrap();
+        assert!(page_stats(&conn).unwrap().free_pages > 0);
+
+        optimize(&conn).unwrap();
+        assert_eq!(page_stats(&conn).unwrap().free_pages, 0);
+    }
+
+    #[test]
+    fn test_refs_names_interned() {
+        let conn = create_test_db();
+        conn.execute("INSERT INTO files (path, mtime, size) VALUES ('a.kt', 0, 0), ('b.kt', 0, 0)", []).unwrap();
+        conn.execute(
+            "INSERT INTO refs (file_id, name, line) VALUES (1, 'Repo', 1), (1, 'Repo', 2), (2, 'Repo', 1), (2, 'Cache', 3)",
+            [],
+        )
+        .unwrap();
+        let names = |conn: &Connection| -> i64 { conn.query_row("SELECT COUNT(*) FROM names", [], |row| row.get(0)).unwrap() };
+        assert_eq!(names(&conn), 2);
+        let lines: Vec<(String, i64)> = conn
+            .prepare("SELECT name, line FROM refs WHERE file_id = 1 ORDER BY line")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(lines, vec![("Repo".to_string(), 1), ("Repo".to_string(), 2)]);
+
+        // Deleting a file keeps its names until optimize drops the unused ones
+        conn.execute("DELETE FROM files WHERE id = 2", []).unwrap();
+        assert_eq!(names(&conn), 2);
+        optimize(&conn).unwrap();
+        assert_eq!(names(&conn), 1);
+    }
+
+    #[test]
+    fn test_symbol_names_interned() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "a.kt", 0, 0).unwrap();
+        let repo = insert_symbol(&conn, file_id, "Repo", SymbolKind::Class, 1, None).unwrap();
+        conn.execute("INSERT INTO symbols (file_id, name, kind, line) VALUES (?1, 'Cache', 'class', 5)", params![file_id]).unwrap();
+        conn.execute("INSERT INTO refs (file_id, name, line) VALUES (?1, 'Repo', 9)", params![file_id]).unwrap();
+        let names = |conn: &Connection| -> Vec<String> {
+            conn.prepare("SELECT text FROM names ORDER BY text").unwrap().query_map([], |row| row.get(0)).unwrap().collect::<Result<_, _>>().unwrap()
+        };
+        assert_eq!(names(&conn), vec!["Cache", "Repo"]);
+        assert_eq!(find_symbols_by_name(&conn, "Repo", None, 10).unwrap()[0].line, 1);
+
+        // Renaming through the view interns the new name and reindexes it for full-text search
+        conn.execute("UPDATE symbols SET name = 'UserRepo' WHERE id = ?1", params![repo]).unwrap();
+        assert_eq!(search_symbols(&conn, "UserRepo", 10).unwrap().len(), 1);
+        assert!(search_symbols(&conn, "Cache", 10).unwrap().iter().all(|s| s.name == "Cache"));
+
+        // The reference keeps Repo; Cache goes once no symbol declares it
+        conn.execute("DELETE FROM symbols WHERE name = 'Cache'", []).unwrap();
+        optimize(&conn).unwrap();
+        assert_eq!(names(&conn), vec!["Repo", "UserRepo"]);
+    }
+
+    #[test]
+    fn test_refs_indexed_flag() {
+        let conn = create_test_db();
+        assert!(refs_indexed(&conn));
+        set_refs_indexed(&conn, false).unwrap();
+        assert!(!refs_indexed(&conn));
+        set_refs_indexed(&conn, true).unwrap();
+        assert!(refs_indexed(&conn));
+    }
+
+    #[test]
+    fn test_indexed_languages() {
+        let conn = create_test_db();
+        assert!(indexed_languages(&conn).is_empty());
+        set_indexed_languages(&conn, &["kotlin", "java"]).unwrap();
+        assert_eq!(indexed_languages(&conn), vec!["kotlin", "java"]);
+        set_indexed_languages(&conn, &[]).unwrap();
+        assert!(indexed_languages(&conn).is_empty());
+    }
+
+    #[test]
+    fn test_file_report_and_skipped_files() {
+        let conn = create_test_db();
+        conn.execute(
+            "INSERT INTO files (path, mtime, size, skipped) VALUES ('a.kt', 0, 10, NULL), ('dist/app.js', 0, 5000, 'minified'), ('big.kt', 0, 9000, 'too-large')",
+            [],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO refs (file_id, name, line) VALUES (1, 'Repo', 1), (1, 'Cache', 2)", []).unwrap();
+
+        let report = file_report(&conn, "a.kt").unwrap().unwrap();
+        assert_eq!((report.skipped, report.refs), (None, 2));
+        assert_eq!(file_report(&conn, "dist/app.js").unwrap().unwrap().skipped.as_deref(), Some("minified"));
+        assert!(file_report(&conn, "missing.kt").unwrap().is_none());
+
+        let stats = get_stats(&conn).unwrap();
+        assert_eq!(stats.skipped_files.get("minified"), Some(&1));
+        assert_eq!(stats.skipped_files.get("too-large"), Some(&1));
+        let skipped = skipped_files(&conn, 1).unwrap();
+        assert_eq!(skipped, vec![("big.kt".to_string(), "too-large".to_string(), 9000)]);
+    }
+
+    #[test]
+    fn test_simple_hash_deterministic() {
+        let h1 = simple_hash("/Users/test/project");
+        let h2 = simple_hash("/Users/test/project");
+        assert_eq!(h1, h2);
+    }
+
+    #[test]
+    fn test_simple_hash_different() {
+        let h1 = simple_hash("/Users/test/project1");
+        let h2 = simple_hash("/Users/test/project2");
+        assert_ne!(h1, h2);
+    }
+
+    #[test]
+    fn test_init_db() {
+        let conn = create_test_db();
+        // Check tables exist
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='files'",
+            [], |row| row.get(0)
+        ).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_escape_fts5_query_simple() {
+        assert_eq!(escape_fts5_query("MyClass"), "\"MyClass\"");
+    }
+
+    #[test]
+    fn test_escape_fts5_query_prefix() {
+        assert_eq!(escape_fts5_query("Slow*"), "\"Slow\"*");
+        assert_eq!(escape_fts5_query("SlowUpstream*"), "\"SlowUpstream\"*");
+    }
+
+    #[test]
+    fn test_escape_fts5_query_empty() {
+        assert_eq!(escape_fts5_query(""), "");
+        assert_eq!(escape_fts5_query("   "), "");
+    }
+
+    #[test]
+    fn test_escape_fts5_query_with_quotes() {
+        assert_eq!(escape_fts5_query("say \"hello\""), "\"say \"\"hello\"\"\"");
+    }
+
+    #[test]
+    fn test_boolean_fts_query() {
+        assert_eq!(boolean_fts_query("PaymentRepo").unwrap(), None);
+        assert_eq!(boolean_fts_query("and or not").unwrap(), None);
+        assert_eq!(
+            boolean_fts_query("Payment AND Refund NOT Legacy").unwrap().unwrap(),
+            "{name signature} : (\"Payment\"* AND \"Refund\"* NOT \"Legacy\"*)"
+        );
+        assert_eq!(
+            boolean_fts_query("\"fun refund\" OR (cancel* AND order)").unwrap().unwrap(),
+            "{name signature} : (\"fun refund\" OR ( \"cancel\"* AND \"order\"* ))"
+        );
+        for malformed in ["NOT Legacy", "Payment AND", "Payment OR OR Refund", "(Payment AND Refund", "Payment AND Refund)", "\"open"] {
+            assert!(boolean_fts_query(malformed).is_err(), "{}", malformed);
+        }
+    }
+
+    #[test]
+    fn test_synonyms() {
+        let entries: std::collections::HashMap<String, Vec<String>> = [
+            ("auth".to_string(), vec!["Authentication".to_string(), "login".to_string()]),
+            ("login".to_string(), vec!["signin".to_string()]),
+        ].into_iter().collect();
+        let synonyms = Synonyms::new(&entries);
+        assert_eq!(synonyms.of("AUTH"), ["authentication", "login"]);
+        assert_eq!(synonyms.of("login"), ["auth", "authentication", "signin"]);
+        assert!(synonyms.of("payment").is_empty());
+
+        assert_eq!(name_fts_query("Payment*", &synonyms), "{name signature} : \"Payment\"*");
+        assert_eq!(
+            name_fts_query("auth*", &synonyms),
+            "{name signature} : (\"auth\"* OR \"authentication\"* OR \"login\"*)"
+        );
+        assert_eq!(
+            boolean_fts_expression("auth AND token", &synonyms).unwrap().unwrap(),
+            "{name signature} : ((\"auth\"* OR \"authentication\"* OR \"login\"*) AND \"token\"*)"
+        );
+        // Quoted phrases match as written
+        assert_eq!(boolean_fts_expression("\"auth\" OR x", &synonyms).unwrap().unwrap(), "{name signature} : (\"auth\" OR \"x\"*)");
+
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Auth.kt", 1, 1).unwrap();
+        insert_symbol(&conn, file_id, "AuthenticationManager", SymbolKind::Class, 1, None).unwrap();
+        insert_symbol(&conn, file_id, "LoginScreen", SymbolKind::Class, 2, None).unwrap();
+        let found = fts_search(&conn, &name_fts_query("auth*", &synonyms), Some("auth"), 10, &SearchScope::none()).unwrap();
+        let mut names: Vec<String> = found.into_iter().map(|r| r.name).collect();
+        names.sort();
+        assert_eq!(names, vec!["AuthenticationManager", "LoginScreen"]);
+    }
+
+    #[test]
+    fn test_search_symbols_query_ending_in_star() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Pay.kt", 1, 1).unwrap();
+        insert_symbol(&conn, file_id, "PaymentRepo", SymbolKind::Class, 1, None).unwrap();
+        // `search` adds its own prefix `*` to what the user typed
+        for query in ["Payment*", "Payment**"] {
+            let found = search_symbols(&conn, query, 10).unwrap();
+            assert_eq!(found.into_iter().map(|r| r.name).collect::<Vec<_>>(), vec!["PaymentRepo"], "{}", query);
+        }
+        for query in ["*", "**"] {
+            assert!(search_symbols(&conn, query, 10).is_ok(), "{}", query);
+        }
+    }
+
+    #[test]
+    fn test_stemming_tokenizer() {
+        let conn = Connection::open_in_memory().unwrap();
+        if !fts5_available(&conn) {
+            return;
+        }
+        conn.execute_batch(&format!("CREATE VIRTUAL TABLE docs USING fts5(doc, tokenize=\"{}\")", fts_tokenizer(true))).unwrap();
+        conn.execute("INSERT INTO docs (doc) VALUES ('Authenticates the user before connecting')", []).unwrap();
+        for query in ["authenticated", "connection", "users"] {
+            let hits: i64 = conn.query_row("SELECT COUNT(*) FROM docs WHERE docs MATCH ?1", params![query], |r| r.get(0)).unwrap();
+            assert_eq!(hits, 1, "{}", query);
+        }
+        assert_eq!(fts_tokenizer(false), FTS_TOKENIZER);
+    }
+
+    #[test]
+    fn test_boolean_search() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Pay.kt", 1, 1).unwrap();
+        insert_symbol(&conn, file_id, "refund", SymbolKind::Function, 1, Some("fun refund(payment: Payment)")).unwrap();
+        insert_symbol(&conn, file_id, "refundLegacy", SymbolKind::Function, 2, Some("fun refundLegacy(payment: Payment, legacy: Legacy)")).unwrap();
+        insert_symbol(&conn, file_id, "charge", SymbolKind::Function, 3, Some("fun charge(payment: Payment)")).unwrap();
+
+        let names = |query: &str| {
+            let fts_query = boolean_fts_query(query).unwrap().unwrap();
+            let mut names: Vec<String> = search_symbols_fts(&conn, &fts_query, 10, &SearchScope::none()).unwrap().into_iter().map(|r| r.name).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names("Payment AND refund"), vec!["refund", "refundLegacy"]);
+        assert_eq!(names("Payment AND refund NOT Legacy"), vec!["refund"]);
+        assert_eq!(names("charge OR \"refund\""), vec!["charge", "refund"]);
+        assert!(search_symbols_fts(&conn, "{name} : AND AND", 10, &SearchScope::none()).is_err());
+    }
+
+    #[test]
+    fn test_contains_search_through_name_trigrams() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Pay.kt", 1, 1).unwrap();
+        for (line, name) in ["PaymentService", "RepaymentPlan", "PayRepository", "Raymond", "Ok"].iter().enumerate() {
+            insert_symbol(&conn, file_id, name, SymbolKind::Class, line + 1, None).unwrap();
+        }
+        let none = SearchScope::none();
+        let names = |query: &str| {
+            let mut names: Vec<String> =
+                search_symbols_matching(&conn, query, NameMatch::Contains, 10, &none).unwrap().into_iter().map(|r| r.name).collect();
+            names.sort();
+            names
+        };
+
+        // Candidates hold every trigram of the query; `instr` rules out the rest
+        assert_eq!(names("ayment"), vec!["PaymentService", "RepaymentPlan"]);
+        assert_eq!(names("AYREP"), vec!["PayRepository"]);
+        assert!(names("mentRay").is_empty());
+        // Too short for trigrams: a plain scan
+        assert_eq!(names("Ok"), vec!["Ok"]);
+        assert!(name_trigram_clause(&conn, "ay").unwrap().is_empty());
+        assert!(name_trigram_clause(&conn, "it's").unwrap().contains("'t''s'"));
+    }
+
+    #[test]
+    fn test_name_match_modes_and_ranking() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Pay.kt", 1, 1).unwrap();
+        for (line, name) in ["PaymentService", "PaymentRepositoryImpl", "paymentrepository", "DefaultPaymentRepository", "PaymentRepository"].iter().enumerate() {
+            insert_symbol(&conn, file_id, name, SymbolKind::Class, line + 1, None).unwrap();
+        }
+        let names = |results: Vec<SearchResult>| results.into_iter().map(|r| r.name).collect::<Vec<_>>();
+        let none = SearchScope::none();
+
+        // Exact name first, then the same name in another case, then longer prefix matches
+        assert_eq!(
+            names(search_symbols(&conn, "PaymentRepository*", 10).unwrap()),
+            vec!["PaymentRepository", "paymentrepository", "PaymentRepositoryImpl"]
+        );
+        assert_eq!(names(search_symbols(&conn, "PaymentRepository*", 1).unwrap()), vec!["PaymentRepository"]);
+
+        assert_eq!(
+            names(search_symbols_matching(&conn, "PaymentRepository", NameMatch::Exact, 10, &none).unwrap()),
+            vec!["PaymentRepository", "paymentrepository"]
+        );
+        assert_eq!(
+            names(search_symbols_matching(&conn, "paymentrepo", NameMatch::Prefix, 10, &none).unwrap()),
+            vec!["PaymentRepository", "paymentrepository", "PaymentRepositoryImpl"]
+        );
+        assert_eq!(
+            names(search_symbols_matching(&conn, "Repository", NameMatch::Contains, 10, &none).unwrap()),
+            vec!["PaymentRepository", "paymentrepository", "PaymentRepositoryImpl", "DefaultPaymentRepository"]
+        );
+        let kinds = [SymbolKind::Function];
+        let scope = SearchScope { kinds: &kinds, ..SearchScope::none() };
+        assert!(search_symbols_matching(&conn, "Payment", NameMatch::Contains, 10, &scope).unwrap().is_empty());
+
+        let cased = SearchScope { case_sensitive: true, ..SearchScope::none() };
+        assert_eq!(names(search_symbols_matching(&conn, "PaymentRepository", NameMatch::Exact, 10, &cased).unwrap()), vec!["PaymentRepository"]);
+        assert_eq!(names(search_symbols_matching(&conn, "paymentrepo", NameMatch::Prefix, 10, &cased).unwrap()), vec!["paymentrepository"]);
+        assert_eq!(
+            names(search_symbols_matching(&conn, "Repository", NameMatch::Contains, 10, &cased).unwrap()),
+            vec!["PaymentRepository", "PaymentRepositoryImpl", "DefaultPaymentRepository"]
+        );
+    }
+
+    #[test]
+    fn test_case_sensitive_search() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Pay.kt", 1, 1).unwrap();
+        for (line, name) in ["PaymentService", "paymentHelper"].iter().enumerate() {
+            let id = insert_symbol(&conn, file_id, name, SymbolKind::Class, line + 1, None).unwrap();
+            conn.execute("UPDATE symbols SET doc = 'Handles Refunds' WHERE id = ?1", params![id]).unwrap();
+        }
+        let names = |results: Vec<SearchResult>| results.into_iter().map(|r| r.name).collect::<Vec<_>>();
+        let cased = SearchScope { case_sensitive: true, ..SearchScope::none() };
+
+        // FTS and the LIKE fallback agree
+        for fts in [true, false] {
+            if !fts {
+                conn.execute_batch("DROP TABLE IF EXISTS symbols_fts").unwrap();
+            }
+            assert_eq!(names(search_symbols_scoped(&conn, "payment*", 10, &SearchScope::none()).unwrap()).len(), 2);
+            assert_eq!(names(search_symbols_scoped(&conn, "payment*", 10, &cased).unwrap()), vec!["paymentHelper"]);
+            assert_eq!(names(search_symbols_scoped(&conn, "Payment*", 10, &cased).unwrap()), vec!["PaymentService"]);
+            assert_eq!(search_docs(&conn, "refunds", Window::first(10), &SearchScope::none()).unwrap().0.len(), 2);
+            assert!(search_docs(&conn, "refunds", Window::first(10), &cased).unwrap().0.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_upsert_and_search() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/main.kt", 1000, 100).unwrap();
+        assert!(file_id > 0);
+
+        insert_symbol(&conn, file_id, "MyService", SymbolKind::Class, 10, Some("class MyService")).unwrap();
+        insert_symbol(&conn, file_id, "processData", SymbolKind::Function, 20, Some("fun processData()")).unwrap();
+
+        let results = search_symbols(&conn, "MyService", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "MyService");
+        assert_eq!(results[0].kind, "class");
+        assert_eq!(results[0].path, "src/main.kt");
+    }
+
+    #[test]
+    fn test_search_empty_query() {
+        let conn = create_test_db();
+        let results = search_symbols(&conn, "", 10).unwrap();
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_unicode_names() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Контракты.bsl", 1000, 100).unwrap();
+        insert_symbol(&conn, file_id, "ПроверитьКонтракт", SymbolKind::Function, 3, None).unwrap();
+        // Vowel signs are combining marks, which the default tokenizer treats as separators
+        insert_symbol(&conn, file_id, "परीक्षाकरें", SymbolKind::Function, 9, None).unwrap();
+
+        for query in ["ПроверитьКонтракт", "проверитьконтракт", "Проверить*"] {
+            let results = search_symbols(&conn, query, 10).unwrap();
+            assert_eq!(results.len(), 1, "{}", query);
+            assert_eq!(results[0].name, "ПроверитьКонтракт");
+        }
+        assert!(search_symbols(&conn, "Контракт", 10).unwrap().is_empty());
+        assert_eq!(search_symbols(&conn, "परीक्षाकरें", 10).unwrap().len(), 1);
+        assert!(search_symbols(&conn, "पर", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_search_typos() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Payments.kt", 1000, 100).unwrap();
+        for (name, line) in [("PaymentRepository", 3), ("PaymentService", 9), ("InvoiceRepository", 15)] {
+            insert_symbol(&conn, file_id, name, SymbolKind::Class, line, None).unwrap();
+        }
+
+        // Trigrams are built on first use for indexes that have none yet
+        let names = |query: &str| -> Vec<String> {
+            search_symbols_fuzzy(&conn, query, 10).unwrap().into_iter().map(|r| r.name).collect()
+        };
+        assert_eq!(names("PaymntRepo"), vec!["PaymentRepository"]);
+        assert_eq!(names("Repository"), vec!["PaymentRepository", "InvoiceRepository"]);
+        assert_eq!(names("PaymetnServce"), vec!["PaymentService"]);
+        assert!(names("Shipment").is_empty());
+
+        // Renamed symbols drop out once trigrams are synced
+        conn.execute("UPDATE symbols SET name = 'ShipmentService' WHERE name = 'PaymentService'", []).unwrap();
+        sync_name_trigrams(&conn).unwrap();
+        assert!(names("PaymetnServce").is_empty());
+        assert_eq!(names("Shipmnt"), vec!["ShipmentService"]);
+    }
+
+    #[test]
+    fn test_search_camel_humps() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Payments.kt", 1000, 100).unwrap();
+        for (name, line) in [("PaymentRepositoryImpl", 3), ("PaymentRepository", 9), ("PriorityImplementation", 15), ("ProductRatingInfoImpl", 21)] {
+            insert_symbol(&conn, file_id, name, SymbolKind::Class, line, None).unwrap();
+        }
+        let names = |query: &str| -> Vec<String> {
+            search_camel_humps(&conn, query, 10, &SearchScope::none()).unwrap().into_iter().map(|r| r.name).collect()
+        };
+        assert_eq!(names("PRImpl"), vec!["PaymentRepositoryImpl", "ProductRatingInfoImpl"]);
+        assert_eq!(names("PaReIm"), vec!["PaymentRepositoryImpl"]);
+        assert_eq!(names("PR"), vec!["PaymentRepository", "PaymentRepositoryImpl", "ProductRatingInfoImpl"]);
+        assert!(names("XY").is_empty());
+    }
+
+    #[test]
+    fn test_find_files() {
+        let conn = create_test_db();
+        upsert_file(&conn, "src/main.kt", 1000, 100).unwrap();
+        upsert_file(&conn, "src/utils/Helper.kt", 2000, 200).unwrap();
+
+        let (files, _) = find_files(&conn, "Helper", Window::first(10), false).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].contains("Helper"));
+        assert_eq!(find_files(&conn, "helper", Window::first(10), false).unwrap().0.len(), 1);
+        assert!(find_files(&conn, "helper", Window::first(10), true).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn test_query_window_pages_in_sql() {
+        let conn = create_test_db();
+        for i in 0..5 {
+            upsert_file(&conn, &format!("src/File{}.kt", i), 0, 0).unwrap();
+        }
+        let window = Window { offset: 3, limit: 2, count: true };
+        let (files, total) = find_files(&conn, "src/", window, false).unwrap();
+        assert_eq!((files.len(), total), (2, 5));
+        let (files, total) = find_files(&conn, "src/", Window { offset: 4, ..window }, false).unwrap();
+        assert_eq!((files.len(), total), (1, 5));
+        let (files, total) = find_files(&conn, "src/", Window { offset: 9, ..window }, false).unwrap();
+        assert_eq!((files.len(), total), (0, 5));
+        // Uncounted, the total is just the rows returned
+        assert_eq!(find_files(&conn, "src/", Window::first(2), false).unwrap().1, 2);
+    }
+
+    #[test]
+    fn test_call_graph() {
+        let conn = create_test_db();
+        let pay = upsert_file(&conn, "src/Pay.kt", 1, 1).unwrap();
+        let process = insert_symbol(&conn, pay, "process", SymbolKind::Function, 1, None).unwrap();
+        let charge = insert_symbol(&conn, pay, "charge", SymbolKind::Function, 10, None).unwrap();
+        let test = upsert_file(&conn, "src/PayTest.kt", 1, 1).unwrap();
+        let test_fn = insert_symbol(&conn, test, "testCharge", SymbolKind::Function, 3, None).unwrap();
+        assert!(!has_call_graph(&conn).unwrap());
+        for (caller, callee, line) in [(process, "charge", 4), (process, "charge", 2), (process, "println", 5), (charge, "process", 11), (test_fn, "charge", 4)] {
+            conn.execute("INSERT INTO calls (caller_id, callee, line) VALUES (?1, ?2, ?3)", params![caller, callee, line]).unwrap();
+        }
+        assert!(has_call_graph(&conn).unwrap());
+
+        let callers = find_callers(&conn, "charge", 10, &SearchScope::none()).unwrap();
+        let found: Vec<(&str, &[i64])> = callers.iter().map(|c| (c.function.name.as_str(), c.call_lines.as_slice())).collect();
+        assert_eq!(found, vec![("process", &[2i64, 4][..]), ("testCharge", &[4i64][..])]);
+        let scope = SearchScope { tests: Some(false), ..SearchScope::none() };
+        assert_eq!(find_callers(&conn, "charge", 10, &scope).unwrap().len(), 1);
+
+        let callees = find_callees(&conn, "process", 10, &SearchScope::none()).unwrap();
+        assert_eq!(callees.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["charge", "println"]);
+        assert_eq!(callees[0].definition.as_ref().map(|d| d.line), Some(10));
+        assert!(callees[1].definition.is_none());
+
+        // Re-indexing a file drops the edges of its functions
+        conn.execute("DELETE FROM symbols WHERE file_id = ?1", params![pay]).unwrap();
+        assert_eq!(find_callers(&conn, "charge", 10, &SearchScope::none()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_large_classes() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Checkout.kt", 1, 1).unwrap();
+        let other = upsert_file(&conn, "src/Other.kt", 1, 1).unwrap();
+        let declare = |file: i64, name: &str, kind: SymbolKind, container: Option<&str>| {
+            let id = insert_symbol(&conn, file, name, kind, 1, None).unwrap();
+            conn.execute("UPDATE symbols SET container = ?1 WHERE id = ?2", params![container, id]).unwrap();
+        };
+        declare(file_id, "Checkout", SymbolKind::Class, None);
+        for name in ["pay", "refund", "total"] {
+            declare(file_id, name, SymbolKind::Function, Some("Checkout"));
+        }
+        declare(file_id, "items", SymbolKind::Property, Some("Checkout"));
+        // Nested type: its members belong to Checkout.Builder, not Checkout
+        declare(file_id, "Builder", SymbolKind::Class, Some("Checkout"));
+        declare(file_id, "build", SymbolKind::Function, Some("Checkout.Builder"));
+        declare(file_id, "add", SymbolKind::Function, Some("Checkout.Builder"));
+        // Same-named container in another file
+        declare(other, "pay", SymbolKind::Function, Some("Checkout"));
+        conn.execute("INSERT INTO refs (file_id, name, line) VALUES (?1, 'Checkout', 3)", params![other]).unwrap();
+
+        let classes = large_classes(&conn, 2, 10, &SearchScope::none()).unwrap();
+        let found: Vec<(&str, i64, i64, i64, i64)> =
+            classes.iter().map(|c| (c.class.name.as_str(), c.members, c.functions, c.properties, c.refs)).collect();
+        assert_eq!(found, vec![("Checkout", 5, 3, 1, 1), ("Builder", 2, 2, 0, 0)]);
+        assert_eq!(large_classes(&conn, 3, 10, &SearchScope::none()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_untested_symbols() {
+        let conn = create_test_db();
+        let declare = |file_id: i64, name: &str, kind: SymbolKind, visibility: &str| {
+            let id = insert_symbol(&conn, file_id, name, kind, 1, None).unwrap();
+            conn.execute("UPDATE symbols SET visibility = ?1 WHERE id = ?2", params![visibility, id]).unwrap();
+        };
+        let cart = upsert_file(&conn, "src/pay/Cart.kt", 1, 1).unwrap();
+        declare(cart, "Cart", SymbolKind::Class, "public");
+        declare(cart, "total", SymbolKind::Function, "public");
+        declare(cart, "discount", SymbolKind::Function, "public");
+        declare(cart, "round", SymbolKind::Function, "private");
+        declare(cart, "items", SymbolKind::Property, "public");
+        let test = upsert_file(&conn, "src/test/CartTest.kt", 1, 1).unwrap();
+        declare(test, "CartTest", SymbolKind::Class, "public");
+        let other = upsert_file(&conn, "src/pay/Checkout.kt", 1, 1).unwrap();
+        for (file_id, name) in [(test, "Cart"), (test, "total"), (other, "discount")] {
+            conn.execute("INSERT INTO refs (file_id, name, line) VALUES (?1, ?2, 3)", params![file_id, name]).unwrap();
+        }
+
+        let coverage = untested_symbols(&conn, &SearchScope::none()).unwrap();
+        assert_eq!(coverage.checked, 3);
+        let untested: Vec<&str> = coverage.untested.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(untested, vec!["discount"]);
+
+        let kinds = [SymbolKind::Property];
+        let scope = SearchScope { kinds: &kinds, ..SearchScope::none() };
+        assert_eq!(untested_symbols(&conn, &scope).unwrap().untested.len(), 1);
+    }
+
+    #[test]
+    fn test_di_graph() {
+        let conn = create_test_db();
+        let module = upsert_file(&conn, "src/di/AppModule.kt", 1, 1).unwrap();
+        let screen = upsert_file(&conn, "src/ui/Screen.kt", 1, 1).unwrap();
+        let bind = |file_id: i64, type_name: &str, role: &str, symbol: &str, line: i64| {
+            conn.execute(
+                "INSERT INTO di_bindings (file_id, type, role, mechanism, symbol, line) VALUES (?1, ?2, ?3, 'test', ?4, ?5)",
+                params![file_id, type_name, role, symbol, line],
+            )
+            .unwrap();
+        };
+        bind(module, "PaymentApi", "provides", "provideApi", 3);
+        bind(module, "HttpClient", "injects", "PaymentApi", 3);
+        bind(screen, "PaymentApi", "injects", "Screen", 7);
+        bind(screen, "Logger", "injects", "Screen", 8);
+
+        let graph = di_graph(&conn, "PaymentApi").unwrap();
+        assert_eq!(graph.providers.iter().map(|b| b.symbol.as_deref()).collect::<Vec<_>>(), vec![Some("provideApi")]);
+        assert_eq!(graph.injections.iter().map(|b| b.path.as_str()).collect::<Vec<_>>(), vec!["src/ui/Screen.kt"]);
+        assert_eq!(graph.dependencies.iter().map(|b| b.type_name.as_str()).collect::<Vec<_>>(), vec!["HttpClient"]);
+        assert!(di_graph(&conn, "Logger").unwrap().providers.is_empty());
+    }
+
+    #[test]
+    fn test_uncalled_overrides() {
+        let conn = create_test_db();
+        let declare = |path: &str, owner: &str, parents: &[&str], methods: &[(&str, &str)]| {
+            let file_id = upsert_file(&conn, path, 1, 1).unwrap();
+            let type_id = insert_symbol(&conn, file_id, owner, SymbolKind::Class, 1, None).unwrap();
+            for parent in parents {
+                insert_inheritance(&conn, type_id, parent, "extends").unwrap();
+            }
+            let mut ids = Vec::new();
+            for (line, (name, signature)) in methods.iter().enumerate() {
+                let id = insert_symbol(&conn, file_id, name, SymbolKind::Function, line + 2, Some(signature)).unwrap();
+                conn.execute("UPDATE symbols SET container = ?1 WHERE id = ?2", params![owner, id]).unwrap();
+                ids.push(id);
+            }
+            (file_id, ids)
+        };
+        declare("src/Shape.kt", "Shape", &[], &[("area", "open fun area(): Double"), ("draw", "abstract fun draw()")]);
+        let (_, circle) = declare("src/Circle.kt", "Circle", &["Shape"], &[("area", "override fun area(): Double"), ("draw", "override fun draw()")]);
+        // Override of an override, through a generic supertype
+        declare("src/Ring.kt", "Ring", &["com.app.Circle<Int>"], &[("area", "override fun area(): Double")]);
+        // Overrides a library method: not reported
+        declare("src/Screen.kt", "Screen", &["Activity"], &[("onCreate", "override fun onCreate()")]);
+        let (canvas, _) = declare("src/Canvas.kt", "Canvas", &[], &[("render", "fun render()")]);
+
+        // draw is called from outside the family; area only through super calls
+        conn.execute("INSERT INTO refs (file_id, name, line, context) VALUES (?1, 'draw', 3, 'shape.draw()')", params![canvas]).unwrap();
+        conn.execute("INSERT INTO refs (file_id, name, line, context) VALUES (?1, 'area', 5, 'super.area() * 2')", params![canvas]).unwrap();
+        conn.execute("INSERT INTO calls (caller_id, callee, line) VALUES (?1, 'area', 2)", params![circle[0]]).unwrap();
+
+        let uncalled = uncalled_overrides(&conn, 10, &SearchScope::none()).unwrap();
+        let found: Vec<(&str, &str, Vec<&str>)> = uncalled
+            .iter()
+            .map(|u| (u.base.name.as_str(), u.base.path.as_str(), u.overrides.iter().map(|o| o.path.as_str()).collect()))
+            .collect();
+        assert_eq!(found, vec![("area", "src/Shape.kt", vec!["src/Circle.kt", "src/Ring.kt"])]);
+
+        conn.execute("INSERT INTO calls (caller_id, callee, line) VALUES (?1, 'area', 9)", params![circle[1]]).unwrap();
+        assert!(uncalled_overrides(&conn, 10, &SearchScope::none()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_orphan_tests() {
+        let conn = create_test_db();
+        let declare = |path: &str, decls: &[(&str, Option<&str>)]| -> i64 {
+            let file_id = upsert_file(&conn, path, 1, 1).unwrap();
+            for (name, qualified) in decls {
+                let id = insert_symbol(&conn, file_id, name, SymbolKind::Class, 1, None).unwrap();
+                conn.execute("UPDATE symbols SET qualified_name = ?1 WHERE id = ?2", params![qualified, id]).unwrap();
+            }
+            file_id
+        };
+        let import = |file_id: i64, path: &str, line: i64| {
+            let name = path.rsplit('.').next().unwrap();
+            conn.execute("INSERT INTO imports (file_id, name, path, line) VALUES (?1, ?2, ?3, ?4)", params![file_id, name, path, line])
+                .unwrap();
+        };
+        declare("src/pay/Payment.kt", &[("Payment", Some("com.app.pay.Payment"))]);
+        declare("src/pay/util.py", &[]);
+
+        // Subject and imports present
+        let live = declare("src/test/PaymentTest.kt", &[("PaymentTest", Some("com.app.pay.PaymentTest"))]);
+        import(live, "com.app.pay.Payment", 3);
+        import(live, "org.junit.Test", 4);
+        import(live, "com.app.pay.R", 5);
+        // Subject removed, import of a removed class from a project package
+        let dead = declare("src/test/RefundTest.kt", &[("RefundTest", None)]);
+        import(dead, "com.app.pay.Refund", 3);
+        import(dead, "com.app.pay.Payment", 4);
+        // Named after a production file rather than a symbol
+        declare("src/test/test_util.py", &[]);
+
+        let orphans = orphan_tests(&conn, &SearchScope::none()).unwrap();
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].path, "src/test/RefundTest.kt");
+        assert_eq!(orphans[0].missing_subject.as_deref(), Some("Refund"));
+        let missing: Vec<(&str, i64)> = orphans[0].missing_imports.iter().map(|i| (i.path.as_str(), i.line)).collect();
+        assert_eq!(missing, vec![("com.app.pay.Refund", 3)]);
+    }
+
+    #[test]
+    fn test_resource_usages() {
+        let conn = create_test_db();
+        conn.execute_batch(
+            r#"
+            INSERT INTO xml_usages (file_path, line, class_name, usage_type) VALUES ('res/layout/main.xml', 4, 'com.app.ProfileView', 'view');
+            INSERT INTO xml_usages (file_path, line, class_name, usage_type) VALUES ('res/layout/main.xml', 9, 'com.app.MyProfileView', 'view');
+            INSERT INTO storyboard_usages (file_path, line, class_name, usage_type) VALUES ('Main.storyboard', 12, 'ProfileView', 'view');
+            "#,
+        )
+        .unwrap();
+        let usages = resource_usages(&conn, "ProfileView").unwrap();
+        let found: Vec<(&str, &str, i64)> = usages.iter().map(|u| (u.source, u.path.as_str(), u.line)).collect();
+        assert_eq!(found, vec![("xml", "res/layout/main.xml", 4), ("storyboard", "Main.storyboard", 12)]);
+    }
+
+    #[test]
+    fn test_public_api() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Pay.kt", 1, 1).unwrap();
+        let test_id = upsert_file(&conn, "src/PayTest.kt", 1, 1).unwrap();
+        for (file, name, kind, signature, visibility, container, qualified) in [
+            (file_id, "Pay", SymbolKind::Class, "class Pay", "public", None, Some("com.pay.Pay")),
+            (file_id, "charge", SymbolKind::Function, "fun charge(amount: Long)", "public", Some("Pay"), None),
+            (file_id, "audit", SymbolKind::Function, "private fun audit()", "private", Some("Pay"), None),
+            (file_id, "kotlin.math", SymbolKind::Import, "import kotlin.math", "public", None, None),
+            (test_id, "PayTest", SymbolKind::Class, "class PayTest", "public", None, None),
+        ] {
+            let id = insert_symbol(&conn, file, name, kind, 1, Some(signature)).unwrap();
+            conn.execute(
+                "UPDATE symbols SET visibility = ?1, container = ?2, qualified_name = ?3 WHERE id = ?4",
+                params![visibility, container, qualified, id],
+            )
+            .unwrap();
+        }
+
+        let scope = SearchScope { tests: Some(false), ..SearchScope::none() };
+        let api = public_api(&conn, &scope).unwrap();
+        assert_eq!(
+            api.iter().map(|e| (e.name.as_str(), e.kind.as_str(), e.signature.as_str())).collect::<Vec<_>>(),
+            vec![("Pay.charge", "function", "fun charge(amount: Long)"), ("com.pay.Pay", "class", "class Pay")]
+        );
+        assert_eq!(public_api(&conn, &SearchScope::none()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_duplicate_definitions() {
+        let conn = create_test_db();
+        let files: Vec<i64> = ["src/a/Util.kt", "src/b/Util.kt", "src/c/Util.kt", "src/test/UtilTest.kt"]
+            .iter()
+            .map(|path| upsert_file(&conn, path, 1, 1).unwrap())
+            .collect();
+        let declare = |file: usize, name: &str, kind: SymbolKind, signature: &str| {
+            insert_symbol(&conn, files[file], name, kind, 1, Some(signature)).unwrap();
+        };
+        for file in 0..3 {
+            declare(file, "formatDate", SymbolKind::Function, "fun formatDate(date: Date): String");
+        }
+        declare(3, "formatDate", SymbolKind::Function, "fun formatDate(date: Date): String");
+        declare(0, "Money", SymbolKind::Class, "data class Money(val amount: Long)");
+        declare(1, "Money", SymbolKind::Class, "data class Money(val amount: Long)");
+        // Different signature, repeated within one file, overrides: not duplicates
+        declare(2, "Money", SymbolKind::Class, "class Money(val amount: BigDecimal)");
+        declare(0, "parse", SymbolKind::Function, "fun parse(s: String)");
+        declare(0, "parse", SymbolKind::Function, "fun parse(s: String)");
+        declare(0, "onCreate", SymbolKind::Function, "override fun onCreate()");
+        declare(1, "onCreate", SymbolKind::Function, "override fun onCreate()");
+
+        let duplicates = duplicate_definitions(&conn, 10, &SearchScope::none()).unwrap();
+        let found: Vec<(&str, usize)> = duplicates.iter().map(|d| (d.name.as_str(), d.declarations.len())).collect();
+        assert_eq!(found, vec![("formatDate", 4), ("Money", 2)]);
+        assert_eq!(duplicates[1].signature, "data class Money(val amount: Long)");
+
+        let no_tests = SearchScope { tests: Some(false), ..SearchScope::none() };
+        assert_eq!(duplicate_definitions(&conn, 10, &no_tests).unwrap()[0].declarations.len(), 3);
+        assert_eq!(duplicate_definitions(&conn, 1, &no_tests).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_file_stats() {
+        let conn = create_test_db();
+        let pay = upsert_file(&conn, "src/Pay.kt", 1, 2048).unwrap();
+        let test = upsert_file(&conn, "src/PayTest.kt", 1, 512).unwrap();
+        insert_symbol(&conn, pay, "Pay", SymbolKind::Class, 1, None).unwrap();
+        insert_symbol(&conn, pay, "charge", SymbolKind::Function, 2, None).unwrap();
+        insert_symbol(&conn, pay, "refund", SymbolKind::Function, 8, None).unwrap();
+        for name in ["Pay", "charge"] {
+            conn.execute("INSERT INTO refs (file_id, name, line) VALUES (?1, ?2, 3)", params![test, name]).unwrap();
+        }
+
+        let stats = file_stats(&conn).unwrap();
+        assert_eq!(stats.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(), vec!["src/Pay.kt", "src/PayTest.kt"]);
+        assert_eq!((stats[0].size, stats[0].refs, stats[0].is_test), (2048, 0, false));
+        assert_eq!(stats[0].symbols, BTreeMap::from([("class".to_string(), 1), ("function".to_string(), 2)]));
+        assert_eq!((stats[1].refs, stats[1].is_test, stats[1].symbols.len()), (2, true, 0));
+    }
+
+    #[test]
+    fn test_complex_functions() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Checkout.kt", 1, 1).unwrap();
+        let test_id = upsert_file(&conn, "src/CheckoutTest.kt", 1, 1).unwrap();
+        assert!(!has_complexity(&conn).unwrap());
+        for (file, name, line, end, complexity) in [
+            (file_id, "total", 10, 40, 7),
+            (file_id, "apply", 50, 52, 9),
+            (file_id, "render", 60, 140, 3),
+            (test_id, "testTotal", 5, 30, 12),
+        ] {
+            let id = insert_symbol(&conn, file, name, SymbolKind::Function, line, None).unwrap();
+            conn.execute("UPDATE symbols SET end_line = ?1, complexity = ?2 WHERE id = ?3", params![end, complexity, id]).unwrap();
+        }
+        insert_symbol(&conn, file_id, "Checkout", SymbolKind::Class, 1, None).unwrap();
+        assert!(has_complexity(&conn).unwrap());
+
+        let names = |by_lines: bool, scope: &SearchScope| -> Vec<(String, i64, i64)> {
+            complex_functions(&conn, 3, by_lines, scope)
+                .unwrap()
+                .into_iter()
+                .map(|f| (f.function.name, f.complexity, f.lines))
+                .collect()
+        };
+        let no_tests = SearchScope { tests: Some(false), ..SearchScope::none() };
+        assert_eq!(
+            names(false, &no_tests),
+            vec![("apply".to_string(), 9, 3), ("total".to_string(), 7, 31), ("render".to_string(), 3, 81)]
+        );
+        assert_eq!(names(true, &SearchScope::none())[..2], [("render".to_string(), 3, 81), ("total".to_string(), 7, 31)]);
     }
 
     #[test]
-    fn test_simple_hash_deterministic() {
-        let h1 = simple_hash("/Users/test/project");
-        let h2 = simple_hash("/Users/test/project");
-        assert_eq!(h1, h2);
+    fn test_reachable_symbols() {
+        let conn = create_test_db();
+        let declare = |path: &str, decls: &[(&str, SymbolKind, usize, usize, Option<&str>)]| -> Vec<i64> {
+            let file_id = upsert_file(&conn, path, 1, 1).unwrap();
+            decls
+                .iter()
+                .map(|(name, kind, line, end, signature)| {
+                    let id = insert_symbol(&conn, file_id, name, *kind, *line, *signature).unwrap();
+                    conn.execute("UPDATE symbols SET end_line = ?1 WHERE id = ?2", params![*end as i64, id]).unwrap();
+                    id
+                })
+                .collect()
+        };
+        let add_ref = |id: i64, name: &str, line: i64| {
+            conn.execute(
+                "INSERT INTO refs (file_id, name, line) SELECT file_id, ?2, ?3 FROM symbols WHERE id = ?1",
+                params![id, name, line],
+            )
+            .unwrap();
+        };
+        let main = declare("app/Main.kt", &[("main", SymbolKind::Function, 1, 5, None)])[0];
+        let service = declare(
+            "app/Service.kt",
+            &[
+                ("Service", SymbolKind::Class, 1, 20, None),
+                ("run", SymbolKind::Function, 3, 6, None),
+                ("helper", SymbolKind::Function, 8, 10, None),
+                ("onEvent", SymbolKind::Function, 12, 14, Some("override fun onEvent()")),
+                ("unusedMethod", SymbolKind::Function, 16, 18, None),
+            ],
+        );
+        let orphan = declare("app/Orphan.kt", &[("Orphan", SymbolKind::Class, 1, 5, None), ("orphanHelper", SymbolKind::Function, 2, 4, None)]);
+        let module = declare("di/AppModule.kt", &[("AppModule", SymbolKind::Class, 1, 10, None), ("provideService", SymbolKind::Function, 3, 5, None)]);
+        declare("app/MainActivity.kt", &[("MainActivity", SymbolKind::Class, 1, 30, None)]);
+        let test = declare("test/ServiceTest.kt", &[("ServiceTest", SymbolKind::Class, 1, 9, None)]);
+        conn.execute("UPDATE files SET is_test = 1 WHERE path LIKE 'test/%'", []).unwrap();
+        add_ref(main, "Service", 2);
+        add_ref(orphan[1], "Service", 3);
+        add_ref(test[0], "Orphan", 4);
+        for (caller, callee) in [(main, "run"), (service[1], "helper")] {
+            conn.execute("INSERT INTO calls (caller_id, callee, line) VALUES (?1, ?2, 1)", params![caller, callee]).unwrap();
+        }
+        conn.execute("INSERT INTO symbol_annotations (symbol_id, name) VALUES (?1, 'Provides')", params![module[1]]).unwrap();
+
+        let names = |tests: bool| -> Vec<String> {
+            let entry = EntryPoints {
+                annotations: &["provides".to_string()],
+                functions: &["main".to_string()],
+                classes: &["MainActivity".to_string()],
+                tests,
+            };
+            let reached = reachable_symbols(&conn, &entry).unwrap();
+            let mut stmt = conn.prepare("SELECT id, name FROM symbols ORDER BY name").unwrap();
+            let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))).unwrap();
+            rows.map(|r| r.unwrap()).filter(|(id, _)| reached.contains(id)).map(|(_, name)| name).collect()
+        };
+        // The override is reached through its class, the provider's class through its member
+        assert_eq!(
+            names(false),
+            vec!["AppModule", "MainActivity", "Service", "helper", "main", "onEvent", "provideService", "run"]
+        );
+        // A test using Orphan keeps it, but not its uncalled member
+        let with_tests = names(true);
+        assert!(with_tests.contains(&"Orphan".to_string()) && with_tests.contains(&"ServiceTest".to_string()));
+        assert!(!with_tests.contains(&"orphanHelper".to_string()) && !with_tests.contains(&"unusedMethod".to_string()));
     }
 
     #[test]
-    fn test_simple_hash_different() {
-        let h1 = simple_hash("/Users/test/project1");
-        let h2 = simple_hash("/Users/test/project2");
-        assert_ne!(h1, h2);
+    fn test_near_duplicate_names() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Pay.kt", 1, 1).unwrap();
+        for (line, (name, kind)) in [
+            ("PaymentManager", SymbolKind::Class),
+            ("PaymentsManager", SymbolKind::Class),
+            ("PaymentMangr", SymbolKind::Class),
+            ("paymentManager", SymbolKind::Property),
+            ("PaymentService", SymbolKind::Class),
+            ("PaymentsManager", SymbolKind::Import),
+        ].into_iter().enumerate() {
+            insert_symbol(&conn, file_id, name, kind, line + 1, None).unwrap();
+        }
+        sync_name_trigrams(&conn).unwrap();
+
+        let found = near_duplicate_names(&conn, "PaymentManager", 0.2, 10, &SearchScope::none()).unwrap();
+        let names: Vec<&str> = found.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["paymentManager", "PaymentsManager", "PaymentMangr"]);
+        // The import of PaymentsManager is not a declaration
+        assert_eq!(found[1].declarations.len(), 1);
+
+        let classes = [SymbolKind::Class];
+        let scope = SearchScope { kinds: &classes, ..SearchScope::none() };
+        let found = near_duplicate_names(&conn, "PaymentManager", 0.1, 10, &scope).unwrap();
+        assert_eq!(found.iter().map(|d| d.name.as_str()).collect::<Vec<_>>(), vec!["PaymentsManager"]);
     }
 
     #[test]
-    fn test_init_db() {
+    fn test_complete_names() {
         let conn = create_test_db();
-        // Check tables exist
-        let count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='files'",
-            [], |row| row.get(0)
-        ).unwrap();
-        assert_eq!(count, 1);
+        let file_id = upsert_file(&conn, "src/Pay.kt", 1, 1).unwrap();
+        for (line, (name, kind)) in [
+            ("paymentHelper", SymbolKind::Function),
+            ("PaymentService", SymbolKind::Class),
+            ("Payment", SymbolKind::Import),
+            ("Pay", SymbolKind::Class),
+            ("Refund", SymbolKind::Class),
+        ].into_iter().enumerate() {
+            insert_symbol(&conn, file_id, name, kind, line + 1, None).unwrap();
+        }
+        let names = |prefix: &str, limit: usize| -> Vec<String> {
+            complete_names(&conn, prefix, limit).unwrap().into_iter().map(|c| c.name).collect()
+        };
+        assert_eq!(names("pay", 50), vec!["Pay", "paymentHelper", "PaymentService"]);
+        assert_eq!(names("PAYMENTS", 50), vec!["PaymentService"]);
+        assert_eq!(names("pay", 1), vec!["Pay"]);
+        assert!(names("x", 50).is_empty());
+
+        let plan: Vec<String> = conn
+            .prepare(&format!("EXPLAIN QUERY PLAN {}", COMPLETE_SQL))
+            .unwrap()
+            .query_map(params!["a", "b", 50], |row| row.get(3))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        // The index drives the scan and the order: no temp B-tree for ORDER BY
+        assert!(plan.iter().any(|step| step.contains("idx_names_text_nocase")), "{:?}", plan);
+        assert!(!plan.iter().any(|step| step.contains("TEMP B-TREE")), "{:?}", plan);
     }
 
     #[test]
-    fn test_escape_fts5_query_simple() {
-        assert_eq!(escape_fts5_query("MyClass"), "\"MyClass\"");
+    fn test_find_symbols_by_name() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/model.kt", 1000, 100).unwrap();
+        insert_symbol(&conn, file_id, "User", SymbolKind::Class, 5, Some("data class User")).unwrap();
+        insert_symbol(&conn, file_id, "UserRepository", SymbolKind::Interface, 20, Some("interface UserRepository")).unwrap();
+
+        let results = find_symbols_by_name(&conn, "User", None, 10).unwrap();
+        assert!(results.len() >= 1);
+        assert!(results.iter().any(|r| r.name == "User"));
     }
 
     #[test]
-    fn test_escape_fts5_query_prefix() {
-        assert_eq!(escape_fts5_query("Slow*"), "\"Slow\"*");
-        assert_eq!(escape_fts5_query("SlowUpstream*"), "\"SlowUpstream\"*");
+    fn test_symbol_container() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/model.kt", 1000, 100).unwrap();
+        let id = insert_symbol(&conn, file_id, "save", SymbolKind::Function, 7, Some("fun save()")).unwrap();
+        conn.execute("UPDATE symbols SET container = 'UserRepository' WHERE id = ?1", params![id]).unwrap();
+
+        let results = find_symbols_by_name(&conn, "save", None, 10).unwrap();
+        assert_eq!(results[0].container.as_deref(), Some("UserRepository"));
+        assert_eq!(results[0].display_name(), "UserRepository.save");
     }
 
     #[test]
-    fn test_escape_fts5_query_empty() {
-        assert_eq!(escape_fts5_query(""), "");
-        assert_eq!(escape_fts5_query("   "), "");
+    fn test_search_qualified() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Invoice.kt", 1000, 100).unwrap();
+        for (name, qualified) in [
+            ("Invoice", "com.example.billing.Invoice"),
+            ("total", "com.example.billing.Invoice.total"),
+            ("Invoice", "com.example.legacy.Invoice"),
+        ] {
+            let id = insert_symbol(&conn, file_id, name, SymbolKind::Class, 1, None).unwrap();
+            conn.execute("UPDATE symbols SET qualified_name = ?1 WHERE id = ?2", params![qualified, id]).unwrap();
+        }
+
+        let results = search_qualified(&conn, "billing.Invoice", 10, &SearchScope::none()).unwrap();
+        let names: Vec<_> = results.iter().filter_map(|r| r.qualified_name.as_deref()).collect();
+        assert_eq!(names, vec!["com.example.billing.Invoice", "com.example.billing.Invoice.total"]);
+
+        let results = search_qualified(&conn, "com::example::legacy::Invoice", 10, &SearchScope::none()).unwrap();
+        assert_eq!(results.len(), 1);
     }
 
     #[test]
-    fn test_escape_fts5_query_with_quotes() {
-        assert_eq!(escape_fts5_query("say \"hello\""), "\"say \"\"hello\"\"\"");
+    fn test_qualified_queries_and_ambiguity() {
+        assert!(is_qualified_query("com.example.billing.Invoice"));
+        assert!(is_qualified_query("net::http::Client"));
+        assert!(!is_qualified_query("Invoice"));
+        assert!(!is_qualified_query("v1.2"));
+        assert!(!is_qualified_query("billing. Invoice"));
+        assert!(!is_qualified_query(".Invoice"));
+
+        let result = |name: &str, kind: &str, qualified: &str| SearchResult {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            line: 1,
+            signature: None,
+            path: "src/Invoice.kt".to_string(),
+            container: None,
+            qualified_name: Some(qualified.to_string()),
+            visibility: None,
+            partial_parts: vec![],
+            params: None,
+            arity: None,
+        };
+        let results = [
+            result("Invoice", "class", "com.example.billing.Invoice"),
+            result("Invoice", "class", "com.example.legacy.Invoice"),
+            result("Invoice", "class", "org.other.billing.Invoice"),
+            result("Invoice", "import", "com.example.ui.Invoice"),
+            result("total", "function", "com.example.billing.Invoice.total"),
+        ];
+        assert_eq!(
+            ambiguous_declarations("Invoice", &results),
+            vec![
+                ("example.billing.Invoice".to_string(), "com.example.billing.Invoice".to_string()),
+                ("legacy.Invoice".to_string(), "com.example.legacy.Invoice".to_string()),
+                ("other.billing.Invoice".to_string(), "org.other.billing.Invoice".to_string()),
+            ]
+        );
+        assert_eq!(ambiguous_declarations("billing.Invoice", &results).len(), 2);
+        assert!(ambiguous_declarations("legacy.Invoice", &results).is_empty());
+        assert!(ambiguous_declarations("total", &results).is_empty());
+
+        let rust = [result("Client", "class", "net::http::Client"), result("Client", "class", "db::Client")];
+        let suffixes: Vec<String> = ambiguous_declarations("Client", &rust).into_iter().map(|(s, _)| s).collect();
+        assert_eq!(suffixes, vec!["db::Client", "http::Client"]);
     }
 
     #[test]
-    fn test_upsert_and_search() {
+    fn test_search_docs() {
         let conn = create_test_db();
-        let file_id = upsert_file(&conn, "src/main.kt", 1000, 100).unwrap();
-        assert!(file_id > 0);
-
-        insert_symbol(&conn, file_id, "MyService", SymbolKind::Class, 10, Some("class MyService")).unwrap();
-        insert_symbol(&conn, file_id, "processData", SymbolKind::Function, 20, Some("fun processData()")).unwrap();
+        let file_id = upsert_file(&conn, "src/net.kt", 1000, 100).unwrap();
+        for (name, doc) in [
+            ("retry", "Retry the request with exponential backoff."),
+            ("sleep", "Wait before the next attempt."),
+        ] {
+            let id = insert_symbol(&conn, file_id, name, SymbolKind::Function, 1, None).unwrap();
+            conn.execute("UPDATE symbols SET doc = ?1 WHERE id = ?2", params![doc, id]).unwrap();
+        }
 
-        let results = search_symbols(&conn, "MyService", 10).unwrap();
+        let results = search_docs(&conn, "retry with exponential backoff", Window::first(10), &SearchScope::none()).unwrap().0;
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].name, "MyService");
-        assert_eq!(results[0].kind, "class");
-        assert_eq!(results[0].path, "src/main.kt");
+        assert_eq!(results[0].0.name, "retry");
+        assert!(search_docs(&conn, "backoff attempt", Window::first(10), &SearchScope::none()).unwrap().0.is_empty());
     }
 
     #[test]
-    fn test_search_empty_query() {
+    fn test_search_annotation() {
         let conn = create_test_db();
-        let results = search_symbols(&conn, "", 10).unwrap();
-        assert!(results.is_empty());
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+        let file_id = upsert_file(&conn, "src/model.rs", 1000, 100).unwrap();
+        for (name, annotations) in [
+            ("User", vec![("derive", Some("Debug, Serialize"))]),
+            ("db", vec![("pytest.fixture", None)]),
+            ("Table", vec![("Entity", None), ("Table", Some("name = \"users\""))]),
+        ] {
+            let id = insert_symbol(&conn, file_id, name, SymbolKind::Class, 1, None).unwrap();
+            for (annotation, args) in annotations {
+                conn.execute(
+                    "INSERT INTO symbol_annotations (symbol_id, name, args) VALUES (?1, ?2, ?3)",
+                    params![id, annotation, args],
+                ).unwrap();
+            }
+        }
+
+        let search = |q: &str| -> Vec<String> {
+            search_annotation(&conn, q, Window::first(10), &SearchScope::none())
+                .unwrap()
+                .0
+                .into_iter()
+                .map(|(r, _)| r.name)
+                .collect()
+        };
+        assert_eq!(search("Serialize"), vec!["User"]);
+        assert_eq!(search("#[derive(Debug)]"), vec!["User"]);
+        assert_eq!(search("@fixture"), vec!["db"]);
+        assert_eq!(search("Table(users)"), vec!["Table"]);
+        assert!(search("Table(orders)").is_empty());
+
+        // Deleting the symbol removes its annotations
+        conn.execute("DELETE FROM symbols WHERE name = 'User'", []).unwrap();
+        let left: i64 = conn.query_row("SELECT COUNT(*) FROM symbol_annotations", [], |r| r.get(0)).unwrap();
+        assert_eq!(left, 3);
     }
 
     #[test]
-    fn test_find_files() {
+    fn test_search_annotation_equivalents_and_filters() {
         let conn = create_test_db();
-        upsert_file(&conn, "src/main.kt", 1000, 100).unwrap();
-        upsert_file(&conn, "src/utils/Helper.kt", 2000, 200).unwrap();
-
-        let files = find_files(&conn, "Helper", 10).unwrap();
-        assert_eq!(files.len(), 1);
-        assert!(files[0].contains("Helper"));
+        let file_id = upsert_file(&conn, "src/Old.kt", 1000, 100).unwrap();
+        for (name, kind, annotation, args) in [
+            ("Old", SymbolKind::Class, "Deprecated", Some("\"use New\"")),
+            ("legacy", SymbolKind::Function, "Deprecated", Some("\"gone\"")),
+            ("old_rust", SymbolKind::Function, "deprecated", Some("note = \"x\"")),
+            ("oldSwift", SymbolKind::Function, "available", Some("*, deprecated, message: \"y\"")),
+            ("newSwift", SymbolKind::Function, "available", Some("iOS 15, *")),
+            ("OldCs", SymbolKind::Function, "Obsolete", None),
+        ] {
+            let id = insert_symbol(&conn, file_id, name, kind, 1, None).unwrap();
+            conn.execute(
+                "INSERT INTO symbol_annotations (symbol_id, name, args) VALUES (?1, ?2, ?3)",
+                params![id, annotation, args],
+            ).unwrap();
+        }
+        let search = |q: &str, scope: &SearchScope| -> Vec<String> {
+            let mut names: Vec<String> = search_annotation(&conn, q, Window::first(10), scope).unwrap().0.into_iter().map(|(r, _)| r.name).collect();
+            names.sort();
+            names
+        };
+        let functions = [SymbolKind::Function];
+        let scope = SearchScope { kinds: &functions, ..SearchScope::none() };
+        assert_eq!(search("Deprecated", &scope), vec!["OldCs", "legacy", "oldSwift", "old_rust"]);
+        assert_eq!(search("Deprecated", &SearchScope::none()).len(), 5);
+        assert_eq!(search("Deprecated(gone)", &scope), vec!["legacy"]);
+
+        let cased = SearchScope { kinds: &functions, case_sensitive: true, ..SearchScope::none() };
+        assert_eq!(search("Deprecated", &cased), vec!["legacy"]);
+        assert_eq!(search("deprecated", &cased), vec!["old_rust"]);
     }
 
     #[test]
-    fn test_find_symbols_by_name() {
+    fn test_schema_docs_cover_schema() {
         let conn = create_test_db();
-        let file_id = upsert_file(&conn, "src/model.kt", 1000, 100).unwrap();
-        insert_symbol(&conn, file_id, "User", SymbolKind::Class, 5, Some("data class User")).unwrap();
-        insert_symbol(&conn, file_id, "UserRepository", SymbolKind::Interface, 20, Some("interface UserRepository")).unwrap();
+        let tables: Vec<String> = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' AND name NOT LIKE '%_fts%'").unwrap()
+            .query_map([], |row| row.get(0)).unwrap()
+            .collect::<Result<_, _>>().unwrap();
+        assert_eq!(tables.len(), SCHEMA_DOCS.len());
+        for table in &tables {
+            let doc = SCHEMA_DOCS.iter().find(|d| d.name == table).unwrap_or_else(|| panic!("{} is undocumented", table));
+            let columns: Vec<String> = conn
+                .prepare(&format!("PRAGMA table_info({})", table)).unwrap()
+                .query_map([], |row| row.get(1)).unwrap()
+                .collect::<Result<_, _>>().unwrap();
+            let documented: Vec<&str> = doc.columns.iter().map(|(c, _)| *c).collect();
+            assert_eq!(columns, documented, "columns of {}", table);
+            for example in doc.examples {
+                conn.prepare(example).unwrap_or_else(|e| panic!("{}: {}", example, e));
+            }
+        }
+    }
 
-        let results = find_symbols_by_name(&conn, "User", None, 10).unwrap();
-        assert!(results.len() >= 1);
-        assert!(results.iter().any(|r| r.name == "User"));
+    #[test]
+    fn test_upgrade_symbols_fts() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_db(&conn).unwrap();
+        conn.execute_batch(
+            "DROP TRIGGER symbols_ai; DROP TRIGGER symbols_ad; DROP TRIGGER symbols_au; DROP TABLE symbols_fts;
+             CREATE VIRTUAL TABLE symbols_fts USING fts5(name, signature, content=symbols, content_rowid=id);",
+        ).unwrap();
+        let file_id = upsert_file(&conn, "a.kt", 1, 1).unwrap();
+        conn.execute(
+            "INSERT INTO symbols (file_id, name, kind, line, qualified_name) VALUES (?1, 'Foo', 'class', 1, 'pkg.Foo')",
+            params![file_id],
+        ).unwrap();
+
+        upgrade_symbols_fts(&conn).unwrap();
+        let results = search_qualified(&conn, "pkg.Foo", 10, &SearchScope::none()).unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_add_column_to_old_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE symbols (id INTEGER PRIMARY KEY, name TEXT NOT NULL)").unwrap();
+        migrations::add_column_if_missing(&conn, "symbols", "container", "TEXT").unwrap();
+        migrations::add_column_if_missing(&conn, "symbols", "container", "TEXT").unwrap();
+        let columns: Vec<String> = conn
+            .prepare("PRAGMA table_info(symbols)").unwrap()
+            .query_map([], |row| row.get(1)).unwrap()
+            .collect::<Result<_, _>>().unwrap();
+        assert_eq!(columns, vec!["id", "name", "container"]);
     }
 
     #[test]
@@ -1324,6 +6185,200 @@ mod tests {
         assert_eq!(stats.symbol_count, 2);
     }
 
+    #[test]
+    fn test_find_best_definition() {
+        let conn = create_test_db();
+        let add_file = |path: &str, symbols: &[(&str, &str, usize)], imports: &[(&str, &str)]| {
+            let file_id = upsert_file(&conn, path, 1000, 100).unwrap();
+            for (name, qualified, line) in symbols {
+                let id = insert_symbol(&conn, file_id, name, SymbolKind::Class, *line, None).unwrap();
+                conn.execute("UPDATE symbols SET qualified_name = ?1 WHERE id = ?2", params![qualified, id]).unwrap();
+            }
+            for (name, import_path) in imports {
+                conn.execute(
+                    "INSERT INTO imports (file_id, name, path, line) VALUES (?1, ?2, ?3, 1)",
+                    params![file_id, name, import_path],
+                ).unwrap();
+            }
+        };
+        add_file("a/User.kt", &[("User", "com.a.User", 1)], &[]);
+        add_file("b/User.kt", &[("User", "com.b.User", 1)], &[]);
+        add_file("b/Screen.kt", &[], &[("User", "com.a.User")]);
+        add_file("c/Alias.kt", &[], &[("U", "com.b.User")]);
+        add_file("c/Star.kt", &[], &[("*", "com.b")]);
+        add_file("c/Local.kt", &[("User", "com.c.Local.User", 3), ("User", "com.c.Local.Inner.User", 20)], &[]);
+
+        let best = |name: &str, from: Option<(&str, Option<usize>)>| {
+            find_best_definition(&conn, name, from).unwrap().map(|d| format!("{}:{}", d.symbol.path, d.symbol.line))
+        };
+        // The import wins over the definition next door
+        assert_eq!(best("User", Some(("b/Screen.kt", None))).as_deref(), Some("a/User.kt:1"));
+        assert_eq!(best("U", Some(("c/Alias.kt", Some(5)))).as_deref(), Some("b/User.kt:1"));
+        assert_eq!(best("User", Some(("c/Star.kt", None))).as_deref(), Some("b/User.kt:1"));
+        assert_eq!(best("User", Some(("c/Local.kt", Some(25)))).as_deref(), Some("c/Local.kt:20"));
+        assert_eq!(best("User", Some(("c/Local.kt", Some(10)))).as_deref(), Some("c/Local.kt:3"));
+        // Same directory, then path order
+        assert_eq!(best("User", Some(("b/Other.kt", None))).as_deref(), Some("b/User.kt:1"));
+        assert_eq!(best("User", None).as_deref(), Some("a/User.kt:1"));
+        assert_eq!(best("Missing", None), None);
+    }
+
+    #[test]
+    fn test_semantic_search() {
+        let conn = create_test_db();
+        let embedder = crate::embed::HashEmbedder;
+        let api = upsert_file(&conn, "net/Api.kt", 1000, 100).unwrap();
+        let ui = upsert_file(&conn, "ui/Avatar.kt", 1000, 100).unwrap();
+        let retry = insert_symbol(&conn, api, "retryWithBackoff", SymbolKind::Function, 3, Some("fun retryWithBackoff(call: Call)")).unwrap();
+        conn.execute("UPDATE symbols SET doc = 'Retries HTTP requests that failed' WHERE id = ?1", params![retry]).unwrap();
+        insert_symbol(&conn, ui, "renderAvatar", SymbolKind::Function, 7, Some("fun renderAvatar(user: User)")).unwrap();
+
+        assert!(!set_embedding_model(&conn, "builtin").unwrap());
+        let sources = symbols_without_embedding(&conn).unwrap();
+        assert_eq!(sources.len(), 2);
+        let embeddings: Vec<(i64, Vec<f32>)> = sources
+            .iter()
+            .map(|s| (s.symbol_id, embedder.embed_text(&crate::embed::symbol_text(&s.name, &s.kind, s.signature.as_deref(), s.doc.as_deref()))))
+            .collect();
+        store_embeddings(&conn, &embeddings).unwrap();
+        assert!(symbols_without_embedding(&conn).unwrap().is_empty());
+        assert_eq!(embedding_counts(&conn).unwrap(), (2, 2));
+
+        let query = embedder.embed_text("retry failed network calls");
+        let names = |scope: &SearchScope| -> Vec<String> {
+            search_symbols_semantic(&conn, &query, 10, scope).unwrap().into_iter().map(|(s, _)| s.name).collect()
+        };
+        assert_eq!(names(&SearchScope::none()), vec!["retryWithBackoff", "renderAvatar"]);
+        let ui_only = ["ui/**".to_string()];
+        assert_eq!(names(&SearchScope { paths: &ui_only, ..SearchScope::none() }), vec!["renderAvatar"]);
+        // Vectors of another dimension are not compared
+        assert!(search_symbols_semantic(&conn, &[1.0, 0.0], 10, &SearchScope::none()).unwrap().is_empty());
+
+        // Another model invalidates every stored vector
+        assert!(set_embedding_model(&conn, "command:embed.py").unwrap());
+        assert_eq!(embedding_model(&conn).unwrap().as_deref(), Some("command:embed.py"));
+        assert_eq!(embedding_counts(&conn).unwrap(), (0, 2));
+    }
+
+    #[test]
+    fn test_aggregated_refs() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Screen.kt", 1, 1).unwrap();
+        conn.execute(
+            "INSERT INTO refs (file_id, name, line, context, count, sample_lines) VALUES (?1, 'User', 4, 'User()', 12, '4,9,30')",
+            params![file_id],
+        )
+        .unwrap();
+        conn.execute("INSERT INTO refs (file_id, name, line, context) VALUES (?1, 'User', 50, 'x: User')", params![file_id]).unwrap();
+
+        let refs = find_references(&conn, "User", 10).unwrap();
+        assert_eq!(refs.iter().map(|r| (r.line, r.count)).collect::<Vec<_>>(), vec![(4, 12), (50, 1)]);
+        assert_eq!(refs[0].sample_lines, vec![4, 9, 30]);
+        assert_eq!(refs[0].aggregate_note().as_deref(), Some("(12 usages: lines 4, 9, 30, ...)"));
+        assert_eq!(refs[1].aggregate_note(), None);
+        assert_eq!(count_refs(&conn).unwrap(), 13);
+        assert_eq!(file_stats(&conn).unwrap()[0].refs, 13);
+    }
+
+    #[test]
+    fn test_find_references_qualified() {
+        let conn = create_test_db();
+        let add_file = |path: &str, imports: &[(&str, &str)], used: &str| {
+            let file_id = upsert_file(&conn, path, 1000, 100).unwrap();
+            for (name, import_path) in imports {
+                conn.execute(
+                    "INSERT INTO imports (file_id, name, path, line) VALUES (?1, ?2, ?3, 1)",
+                    params![file_id, name, import_path],
+                ).unwrap();
+            }
+            conn.execute("INSERT INTO refs (file_id, name, line, context) VALUES (?1, ?2, 3, 'x')", params![file_id, used]).unwrap();
+            file_id
+        };
+        add_file("explicit.kt", &[("User", "com.a.User")], "User");
+        add_file("other.kt", &[("User", "com.b.User")], "User");
+        add_file("wildcard.kt", &[("*", "com.a")], "User");
+        add_file("alias.kt", &[("U", "com.a.User")], "U");
+        let same_package = add_file("repo.kt", &[], "User");
+        let repo = insert_symbol(&conn, same_package, "Repo", SymbolKind::Class, 1, None).unwrap();
+        conn.execute("UPDATE symbols SET qualified_name = 'com.a.Repo' WHERE id = ?1", params![repo]).unwrap();
+
+        let refs = find_references_qualified(&conn, "com.a.User", 10, &SearchScope::none()).unwrap();
+        let paths: Vec<&str> = refs.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(paths, vec!["alias.kt", "explicit.kt", "repo.kt", "wildcard.kt"]);
+
+        assert_eq!(split_qualified("crate::db::open"), Some(("crate::db", "::", "open")));
+        assert_eq!(split_qualified("User"), None);
+    }
+
+    #[test]
+    fn test_find_references_by_kind() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "Admin.kt", 1000, 100).unwrap();
+        for (line, kind) in [(2, "extension"), (4, "type"), (4, "instantiation")] {
+            conn.execute(
+                "INSERT INTO refs (file_id, name, line, context, ref_kind) VALUES (?1, 'User', ?2, 'x', ?3)",
+                params![file_id, line, kind],
+            ).unwrap();
+        }
+
+        assert_eq!(find_references(&conn, "User", 10).unwrap().len(), 3);
+        let created = find_references_of_kind(&conn, "User", Some(RefKind::Instantiation), None, 10, &SearchScope::none()).unwrap();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].ref_kind.as_deref(), Some("instantiation"));
+        assert_eq!(RefKind::from_name("extension"), Some(RefKind::Extension));
+        assert_eq!(RefKind::from_name("usage"), None);
+    }
+
+    #[test]
+    fn test_overloads_by_arity() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "Processor.kt", 1000, 100).unwrap();
+        for (line, signature, arity) in [(2, "fun process(item: Item)", 1), (3, "fun process(item: Item, retries: Int)", 2)] {
+            let id = insert_symbol(&conn, file_id, "process", SymbolKind::Function, line, Some(signature)).unwrap();
+            conn.execute("UPDATE symbols SET arity = ?1 WHERE id = ?2", params![arity, id]).unwrap();
+        }
+        for (line, arity) in [(7, Some(1)), (8, Some(2)), (9, None)] {
+            conn.execute(
+                "INSERT INTO refs (file_id, name, line, context, ref_kind, arity) VALUES (?1, 'process', ?2, 'x', 'call', ?3)",
+                params![file_id, line, arity],
+            ).unwrap();
+        }
+
+        let (definitions, _, usages) = find_cross_references(&conn, "process", None, Some(2), 10, &SearchScope::none()).unwrap();
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(definitions[0].params.as_deref(), Some("item: Item, retries: Int"));
+        assert_eq!(definitions[0].display_name_with_params(), "process(item: Item, retries: Int)");
+        assert_eq!(usages.iter().map(|r| r.line).collect::<Vec<_>>(), vec![8]);
+
+        let (definitions, _, usages) = find_cross_references(&conn, "process", None, None, 10, &SearchScope::none()).unwrap();
+        assert_eq!((definitions.len(), usages.len()), (2, 3));
+    }
+
+    #[test]
+    fn test_prune_string_refs() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "Di.kt", 1000, 100).unwrap();
+        insert_symbol(&conn, file_id, "UserService", SymbolKind::Class, 1, None).unwrap();
+        for (name, kind) in [("UserService", "string"), ("PaymentApi", "string"), ("PaymentApi", "type")] {
+            conn.execute(
+                "INSERT INTO refs (file_id, name, line, context, ref_kind) VALUES (?1, ?2, 3, 'x', ?3)",
+                params![file_id, name, kind],
+            ).unwrap();
+        }
+
+        assert_eq!(prune_string_refs(&conn).unwrap(), 1);
+        let kinds: Vec<(String, Option<String>)> = find_references(&conn, "PaymentApi", 10)
+            .unwrap()
+            .into_iter()
+            .chain(find_references(&conn, "UserService", 10).unwrap())
+            .map(|r| (r.name, r.ref_kind))
+            .collect();
+        assert_eq!(kinds, vec![
+            ("PaymentApi".to_string(), Some("type".to_string())),
+            ("UserService".to_string(), Some("string".to_string())),
+        ]);
+    }
+
     #[test]
     fn test_insert_and_find_inheritance() {
         let conn = create_test_db();
@@ -1340,10 +6395,455 @@ mod tests {
         assert_eq!(impls[0].name, "Child");
     }
 
+    #[test]
+    fn test_find_implementations_permits() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Shape.java", 1000, 100).unwrap();
+        let shape = insert_symbol(&conn, file_id, "Shape", SymbolKind::Interface, 1, None).unwrap();
+        let circle = insert_symbol(&conn, file_id, "Circle", SymbolKind::Class, 5, None).unwrap();
+        insert_symbol(&conn, file_id, "Square", SymbolKind::Class, 9, None).unwrap();
+        insert_inheritance(&conn, shape, "Circle", "permits").unwrap();
+        insert_inheritance(&conn, shape, "Square", "permits").unwrap();
+        insert_inheritance(&conn, circle, "Shape", "implements").unwrap();
+
+        // Circle comes first as a direct implementor and is listed once
+        let names: Vec<String> = find_implementations(&conn, "Shape", 10).unwrap().into_iter().map(|r| r.name).collect();
+        assert_eq!(names, vec!["Circle", "Square"]);
+        // A permits row is not an inheritance edge from Shape to Circle
+        assert!(find_implementations(&conn, "Circle", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_implementations_transitive() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Payable.kt", 1000, 100).unwrap();
+        insert_symbol(&conn, file_id, "Payable", SymbolKind::Interface, 1, None).unwrap();
+        let base = insert_symbol(&conn, file_id, "PaymentBase", SymbolKind::Class, 3, None).unwrap();
+        let card = insert_symbol(&conn, file_id, "CardCharge", SymbolKind::Class, 8, None).unwrap();
+        let virt = insert_symbol(&conn, file_id, "VirtualCard", SymbolKind::Class, 12, None).unwrap();
+        let cash = insert_symbol(&conn, file_id, "Cash", SymbolKind::Object, 16, None).unwrap();
+        insert_inheritance(&conn, base, "Payable", "implements").unwrap();
+        insert_inheritance(&conn, card, "PaymentBase<Card>", "extends").unwrap();
+        insert_inheritance(&conn, virt, "CardCharge", "extends").unwrap();
+        // Listed once although it reaches Payable both directly and through PaymentBase
+        insert_inheritance(&conn, cash, "Payable", "implements").unwrap();
+        insert_inheritance(&conn, cash, "PaymentBase", "extends").unwrap();
+
+        let found: Vec<(String, Option<String>)> = find_implementations_transitive(&conn, "Payable", 10)
+            .unwrap()
+            .into_iter()
+            .map(|i| (i.symbol.name, i.via))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Cash".to_string(), None),
+                ("PaymentBase".to_string(), None),
+                ("CardCharge".to_string(), Some("PaymentBase".to_string())),
+                ("VirtualCard".to_string(), Some("CardCharge".to_string())),
+            ]
+        );
+        assert_eq!(find_implementations_transitive(&conn, "Payable", 2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_file_dependencies() {
+        let conn = create_test_db();
+        let user = upsert_file(&conn, "model/User.kt", 1000, 100).unwrap();
+        let repo = upsert_file(&conn, "data/UserRepository.kt", 1000, 100).unwrap();
+        let screen = upsert_file(&conn, "ui/UserScreen.kt", 1000, 100).unwrap();
+        let other = upsert_file(&conn, "legacy/User.kt", 1000, 100).unwrap();
+        let id = insert_symbol(&conn, user, "User", SymbolKind::Class, 1, None).unwrap();
+        conn.execute("UPDATE symbols SET qualified_name = 'app.model.User' WHERE id = ?1", params![id]).unwrap();
+        insert_symbol(&conn, user, "Role", SymbolKind::Enum, 5, None).unwrap();
+        insert_symbol(&conn, other, "User", SymbolKind::Class, 1, None).unwrap();
+        insert_symbol(&conn, repo, "UserRepository", SymbolKind::Class, 3, None).unwrap();
+        conn.execute(
+            "INSERT INTO imports (file_id, name, path, line) VALUES (?1, 'User', 'app.model.User', 1)",
+            params![repo],
+        )
+        .unwrap();
+        for (file_id, name) in [(repo, "User"), (repo, "Role"), (screen, "UserRepository"), (screen, "User"), (user, "Role")] {
+            conn.execute("INSERT INTO refs (file_id, name, line) VALUES (?1, ?2, 1)", params![file_id, name]).unwrap();
+        }
+
+        // User is declared twice: only the import resolves it; uses inside model/User.kt are not edges
+        let deps = file_dependencies(&conn).unwrap();
+        let edges: Vec<(&str, &str, usize)> = deps.iter().map(|d| (d.from.as_str(), d.to.as_str(), d.symbols)).collect();
+        assert_eq!(
+            edges,
+            vec![("data/UserRepository.kt", "model/User.kt", 2), ("ui/UserScreen.kt", "data/UserRepository.kt", 1)]
+        );
+    }
+
+    #[test]
+    fn test_merge_partial_types() {
+        let conn = create_test_db();
+        let a = upsert_file(&conn, "Web/UsersController.cs", 1000, 100).unwrap();
+        let b = upsert_file(&conn, "Web/UsersController.Actions.cs", 1000, 100).unwrap();
+        for (file_id, line) in [(a, 5), (b, 3)] {
+            let id = insert_symbol(&conn, file_id, "UsersController", SymbolKind::Class, line, None).unwrap();
+            conn.execute(
+                "UPDATE symbols SET qualified_name = 'App.Web.UsersController', partial = 1 WHERE id = ?1",
+                params![id],
+            )
+            .unwrap();
+        }
+        insert_symbol(&conn, a, "UsersService", SymbolKind::Class, 20, None).unwrap();
+
+        let results = find_symbols_by_name(&conn, "Users", Some("class"), 10).unwrap();
+        assert_eq!(results.len(), 3);
+        let mut merged = merge_partial_types(&conn, results).unwrap();
+        merged.sort_by(|a, b| a.name.cmp(&b.name));
+        let summary: Vec<(&str, &str, &[String])> =
+            merged.iter().map(|r| (r.name.as_str(), r.path.as_str(), r.partial_parts.as_slice())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("UsersController", "Web/UsersController.cs", &["Web/UsersController.Actions.cs:3".to_string()][..]),
+                ("UsersService", "Web/UsersController.cs", &[][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_go_implementations() {
+        let conn = create_test_db();
+        let iface_file = upsert_file(&conn, "pkg/io/io.go", 1000, 100).unwrap();
+        let impl_file = upsert_file(&conn, "pkg/io/file.go", 1000, 100).unwrap();
+        let other_file = upsert_file(&conn, "cmd/tool/main.go", 1000, 100).unwrap();
+        let member = |file_id: i64, name: &str, container: &str| {
+            let id = insert_symbol(&conn, file_id, name, SymbolKind::Function, 1, None).unwrap();
+            conn.execute("UPDATE symbols SET container = ?1 WHERE id = ?2", params![container, id]).unwrap();
+        };
+
+        insert_symbol(&conn, iface_file, "Reader", SymbolKind::Interface, 1, None).unwrap();
+        member(iface_file, "Read", "Reader");
+        let rc = insert_symbol(&conn, iface_file, "ReadCloser", SymbolKind::Interface, 5, None).unwrap();
+        insert_inheritance(&conn, rc, "Reader", "extends").unwrap();
+        member(iface_file, "Close", "ReadCloser");
+
+        insert_symbol(&conn, impl_file, "File", SymbolKind::Class, 1, None).unwrap();
+        member(impl_file, "Read", "File");
+        member(impl_file, "Close", "File");
+        insert_symbol(&conn, impl_file, "Buffer", SymbolKind::Class, 10, None).unwrap();
+        member(impl_file, "Read", "Buffer");
+        // Same type name in another package, methods declared elsewhere
+        insert_symbol(&conn, other_file, "File", SymbolKind::Class, 1, None).unwrap();
+
+        let names = |iface: &str| -> Vec<(String, String)> {
+            find_go_implementations(&conn, iface, 10).unwrap().into_iter().map(|r| (r.name, r.path)).collect()
+        };
+        assert_eq!(
+            names("Reader"),
+            vec![("Buffer".to_string(), "pkg/io/file.go".to_string()), ("File".to_string(), "pkg/io/file.go".to_string())]
+        );
+        // Embedded Reader adds Read to the required set
+        assert_eq!(names("ReadCloser"), vec![("File".to_string(), "pkg/io/file.go".to_string())]);
+        assert!(names("Missing").is_empty());
+    }
+
     #[test]
     fn test_count_refs() {
         let conn = create_test_db();
         let count = count_refs(&conn).unwrap();
         assert_eq!(count, 0);
     }
+
+    #[test]
+    fn test_merge_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let src_path = dir.path().join("src.db");
+        {
+            let src = Connection::open(&src_path).unwrap();
+            init_db(&src).unwrap();
+            let file_id = upsert_file(&src, "lib/a.kt", 1, 1).unwrap();
+            insert_symbol(&src, file_id, "Child", SymbolKind::Class, 1, Some("class Child : Base()")).unwrap();
+            let child_id = src.last_insert_rowid();
+            insert_inheritance(&src, child_id, "Base", "extends").unwrap();
+            src.execute("INSERT INTO refs (file_id, name, line, context) VALUES (?1, 'Base', 1, 'x')", params![file_id]).unwrap();
+        }
+
+        let mut conn = create_test_db();
+        let file_id = upsert_file(&conn, "app/b.kt", 1, 1).unwrap();
+        insert_symbol(&conn, file_id, "Other", SymbolKind::Class, 1, Some("class Other")).unwrap();
+
+        let stats = merge_database(&mut conn, &src_path, "repo1/").unwrap();
+        assert_eq!(stats.files, 1);
+        assert_eq!(stats.symbols, 1);
+        assert_eq!(stats.refs, 1);
+
+        let impls = find_implementations(&conn, "Base", 10).unwrap();
+        assert_eq!(impls.len(), 1);
+        assert_eq!(impls[0].path, "repo1/lib/a.kt");
+        assert_eq!(search_symbols(&conn, "Child", 10).unwrap().len(), 1);
+
+        // Merging the same DB again must not duplicate anything
+        let again = merge_database(&mut conn, &src_path, "repo1/").unwrap();
+        assert_eq!(again.files, 0);
+        assert_eq!(again.skipped_files, 1);
+        assert_eq!(get_stats(&conn).unwrap().symbol_count, 2);
+    }
+
+    #[test]
+    fn test_merge_database_keeps_newer_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let src_path = dir.path().join("src.db");
+        {
+            let src = Connection::open(&src_path).unwrap();
+            init_db(&src).unwrap();
+            let a = upsert_file(&src, "lib/a.kt", 5, 1).unwrap();
+            insert_symbol(&src, a, "NewA", SymbolKind::Class, 1, None).unwrap();
+            src.execute("UPDATE files SET language = 'kotlin' WHERE id = ?1", params![a]).unwrap();
+            let b = upsert_file(&src, "lib/b.kt", 2, 1).unwrap();
+            insert_symbol(&src, b, "OldB", SymbolKind::Class, 1, None).unwrap();
+        }
+
+        let mut conn = create_test_db();
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+        let a = upsert_file(&conn, "lib/a.kt", 1, 1).unwrap();
+        insert_symbol(&conn, a, "OldA", SymbolKind::Class, 1, None).unwrap();
+        conn.execute("INSERT INTO refs (file_id, name, line) VALUES (?1, 'Gone', 1)", params![a]).unwrap();
+        let b = upsert_file(&conn, "lib/b.kt", 9, 1).unwrap();
+        insert_symbol(&conn, b, "NewB", SymbolKind::Class, 1, None).unwrap();
+
+        let stats = merge_database_with(&mut conn, &src_path, "", Duplicates::Newer).unwrap();
+        assert_eq!((stats.files, stats.replaced_files, stats.skipped_files), (1, 1, 1));
+        let names: Vec<String> = conn
+            .prepare("SELECT name FROM symbols ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(names, vec!["NewA", "NewB"]);
+        assert!(find_references(&conn, "Gone", 10).unwrap().is_empty());
+        let language: String =
+            conn.query_row("SELECT language FROM files WHERE path = 'lib/a.kt'", [], |row| row.get(0)).unwrap();
+        assert_eq!(language, "kotlin");
+    }
+
+    #[test]
+    fn test_search_content_lines() {
+        let conn = create_test_db();
+        assert!(!has_content_fts(&conn));
+        create_content_fts(&conn).unwrap();
+        assert!(has_content_fts(&conn));
+        let a = upsert_file(&conn, "app/Pay.kt", 1, 1).unwrap();
+        let b = upsert_file(&conn, "lib/Refund.kt", 1, 1).unwrap();
+        for (file_id, line, text) in [(a, 3, "val svc = PaymentService()"), (a, 9, "// id: 42"), (b, 1, "class RefundPaymentJob")] {
+            conn.execute("INSERT INTO content_fts (rowid, text) VALUES (?1, ?2)", params![content_rowid(file_id, line), text]).unwrap();
+        }
+
+        let hits = search_content(&conn, "paymentserv", 10, |_, _| true).unwrap();
+        assert_eq!(hits, vec![("app/Pay.kt".to_string(), 3, "val svc = PaymentService()".to_string())]);
+        assert_eq!(search_content(&conn, "Payment", 10, |_, _| true).unwrap().len(), 2);
+        assert_eq!(search_content(&conn, "Payment", 1, |path, _| path.starts_with("lib/")).unwrap()[0].0, "lib/Refund.kt");
+        assert_eq!(search_content(&conn, "42", 10, |_, _| true).unwrap()[0].1, 9);
+        assert!(search_content(&conn, "%", 10, |_, _| true).unwrap().is_empty());
+
+        delete_content_lines(&conn, "app/Pay.kt").unwrap();
+        assert_eq!(search_content(&conn, "Payment", 10, |_, _| true).unwrap().len(), 1);
+        conn.execute("DELETE FROM files WHERE path = 'lib/Refund.kt'", []).unwrap();
+        assert!(search_content(&conn, "Payment", 10, |_, _| true).unwrap().is_empty());
+        drop_content_fts(&conn).unwrap();
+        assert!(!has_content_fts(&conn));
+    }
+
+    #[test]
+    fn test_search_without_fts_index() {
+        let conn = create_test_db();
+        conn.execute_batch(
+            "DROP TRIGGER symbols_ai; DROP TRIGGER symbols_ad; DROP TRIGGER symbols_au; DROP TABLE symbols_fts;",
+        ).unwrap();
+        assert!(!has_fts_index(&conn));
+
+        let file_id = upsert_file(&conn, "src/pay.kt", 1, 1).unwrap();
+        insert_symbol(&conn, file_id, "PaymentService", SymbolKind::Class, 1, Some("class PaymentService")).unwrap();
+        insert_symbol(&conn, file_id, "refund", SymbolKind::Function, 5, Some("fun refund(p: Payment)")).unwrap();
+
+        let results = search_symbols(&conn, "Payment", 10).unwrap();
+        assert_eq!(results.len(), 2);
+        // A name starting with the query outranks a match in the signature only
+        assert_eq!(results[0].name, "PaymentService");
+        assert_eq!(results[1].name, "refund");
+
+        let results = search_symbols(&conn, "Pay*", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "PaymentService");
+
+        let scope = SearchScope { in_file: Some("other.kt"), module: None, dir_prefix: None, visibility: None, kinds: &[], languages: &[], paths: &[], exclude_paths: &[], changed_files: None, tests: None, case_sensitive: false };
+        assert!(search_symbols_scoped(&conn, "Payment", 10, &scope).unwrap().is_empty());
+        assert!(!get_stats(&conn).unwrap().fts_enabled);
+    }
+
+    #[test]
+    fn test_visibility_scope() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Repo.kt", 1, 1).unwrap();
+        let public_id = insert_symbol(&conn, file_id, "RepoApi", SymbolKind::Class, 1, Some("class RepoApi")).unwrap();
+        let private_id = insert_symbol(&conn, file_id, "RepoCache", SymbolKind::Class, 9, Some("private class RepoCache")).unwrap();
+        conn.execute("UPDATE symbols SET visibility = 'public' WHERE id = ?1", params![public_id]).unwrap();
+        conn.execute("UPDATE symbols SET visibility = 'private' WHERE id = ?1", params![private_id]).unwrap();
+
+        let scope = SearchScope { visibility: Some("private"), ..SearchScope::none() };
+        let results = search_symbols_scoped(&conn, "Repo*", 10, &scope).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "RepoCache");
+        assert_eq!(results[0].visibility.as_deref(), Some("private"));
+
+        let scope = SearchScope { in_file: Some("Repo.kt"), visibility: Some("public"), ..SearchScope::none() };
+        assert_eq!(find_class_like_scoped(&conn, "RepoApi", 10, &scope).unwrap().len(), 1);
+        assert!(find_class_like_scoped(&conn, "RepoCache", 10, &scope).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_kind_scope() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Cache.kt", 1, 1).unwrap();
+        insert_symbol(&conn, file_id, "Cache", SymbolKind::Class, 1, Some("class Cache")).unwrap();
+        insert_symbol(&conn, file_id, "CacheKey", SymbolKind::Interface, 5, Some("interface CacheKey")).unwrap();
+        insert_symbol(&conn, file_id, "cacheGet", SymbolKind::Function, 9, Some("fun cacheGet(key: CacheKey)")).unwrap();
+
+        let kinds = [SymbolKind::Class];
+        let scope = SearchScope { kinds: &kinds, ..SearchScope::none() };
+        let names: Vec<String> = search_symbols_scoped(&conn, "Cache*", 10, &scope).unwrap().into_iter().map(|r| r.name).collect();
+        assert_eq!(names, vec!["Cache"]);
+
+        let kinds = [SymbolKind::Class, SymbolKind::Interface];
+        let scope = SearchScope { kinds: &kinds, ..SearchScope::none() };
+        assert_eq!(search_symbols_scoped(&conn, "Cache*", 10, &scope).unwrap().len(), 2);
+        assert_eq!(SymbolKind::ALL.iter().filter_map(|k| SymbolKind::from_name(k.as_str())).count(), SymbolKind::ALL.len());
+    }
+
+    #[test]
+    fn test_language_scope() {
+        let conn = create_test_db();
+        let kotlin = upsert_file(&conn, "app/Cache.kt", 1, 1).unwrap();
+        let swift = upsert_file(&conn, "ios/Cache.swift", 1, 1).unwrap();
+        insert_symbol(&conn, kotlin, "Cache", SymbolKind::Class, 1, None).unwrap();
+        insert_symbol(&conn, swift, "Cache", SymbolKind::Class, 1, None).unwrap();
+        for file_id in [kotlin, swift] {
+            conn.execute("INSERT INTO refs (file_id, name, line, context) VALUES (?1, 'Cache', 9, 'x')", params![file_id]).unwrap();
+        }
+        let language: Option<String> = conn.query_row("SELECT language FROM files WHERE id = ?1", params![swift], |r| r.get(0)).unwrap();
+        assert_eq!(language.as_deref(), Some("swift"));
+
+        let languages = vec!["kotlin".to_string()];
+        let scope = SearchScope { languages: &languages, ..SearchScope::none() };
+        let results = search_symbols_scoped(&conn, "Cache*", 10, &scope).unwrap();
+        assert_eq!(results.iter().map(|r| r.path.as_str()).collect::<Vec<_>>(), vec!["app/Cache.kt"]);
+        let (definitions, _, usages) = find_cross_references(&conn, "Cache", None, None, 10, &scope).unwrap();
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(usages.iter().map(|r| r.path.as_str()).collect::<Vec<_>>(), vec!["app/Cache.kt"]);
+        let (definitions, _, usages) = find_cross_references(&conn, "Cache", None, None, 10, &SearchScope::none()).unwrap();
+        assert_eq!((definitions.len(), usages.len()), (2, 2));
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("ui/**", "ui/screens/Home.kt"));
+        assert!(glob_matches("**/*Dao.kt", "UserDao.kt"));
+        assert!(glob_matches("**/*Dao.kt", "data/db/UserDao.kt"));
+        assert!(!glob_matches("ui/**", "app/ui/Home.kt"));
+        assert!(!glob_matches("**/*Dao.kt", "data/db/UserDaoImpl.kt"));
+        let globs = PathGlobs::new(&["ui/**".to_string(), "**/*Dao.kt".to_string()]);
+        assert!(globs.matches("ui/screens/Home.kt") && globs.matches("data/db/UserDao.kt"));
+        assert!(!globs.matches("app/ui/Home.kt"));
+        assert!(PathGlobs::new(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_path_glob_scope() {
+        let conn = create_test_db();
+        for path in ["src/payments/Pay.kt", "src/payments/test/PayTest.kt", "test/Pay.kt", "src/orders/Pay.kt"] {
+            let file_id = upsert_file(&conn, path, 1, 1).unwrap();
+            insert_symbol(&conn, file_id, "Pay", SymbolKind::Class, 1, None).unwrap();
+        }
+        let paths_for = |paths: &[String], exclude_paths: &[String]| {
+            let scope = SearchScope { paths, exclude_paths, ..SearchScope::none() };
+            let mut found: Vec<String> = search_symbols_scoped(&conn, "Pay*", 10, &scope).unwrap().into_iter().map(|r| r.path).collect();
+            found.sort();
+            found
+        };
+        let payments = vec!["src/payments/**".to_string()];
+        let tests = vec!["**/test/**".to_string()];
+        assert_eq!(paths_for(&payments, &[]), vec!["src/payments/Pay.kt", "src/payments/test/PayTest.kt"]);
+        assert_eq!(paths_for(&payments, &tests), vec!["src/payments/Pay.kt"]);
+        assert_eq!(paths_for(&[], &tests), vec!["src/orders/Pay.kt", "src/payments/Pay.kt"]);
+
+        // Results found outside SQL are filtered the same way
+        let scope = SearchScope { paths: &payments, exclude_paths: &tests, ..SearchScope::none() };
+        assert!(scope.allows_path("src/payments/Pay.kt"));
+        assert!(!scope.allows_path("src/payments/test/PayTest.kt"));
+        assert!(!scope.allows_path("src/orders/Pay.kt"));
+        assert_eq!(glob_alternatives("./**/[ab]?.kt"), vec!["*/[ab]?.kt", "[ab]?.kt"]);
+        assert!(wildcard_match("*/[ab]?.kt", "x/y/[ab]c.kt"));
+    }
+
+    #[test]
+    fn test_test_files_scope() {
+        let conn = create_test_db();
+        for path in ["src/Pay.kt", "src/test/PayFake.kt", "src/PayTest.kt"] {
+            let file_id = upsert_file(&conn, path, 1, 1).unwrap();
+            insert_symbol(&conn, file_id, "Pay", SymbolKind::Class, 1, None).unwrap();
+        }
+        let is_test: i64 = conn.query_row("SELECT is_test FROM files WHERE path = 'src/PayTest.kt'", [], |r| r.get(0)).unwrap();
+        assert_eq!(is_test, 1);
+
+        let paths_for = |tests: Option<bool>| {
+            let scope = SearchScope { tests, ..SearchScope::none() };
+            let mut found: Vec<String> = search_symbols_scoped(&conn, "Pay*", 10, &scope).unwrap().into_iter().map(|r| r.path).collect();
+            found.sort();
+            found
+        };
+        assert_eq!(paths_for(Some(false)), vec!["src/Pay.kt"]);
+        assert_eq!(paths_for(Some(true)), vec!["src/PayTest.kt", "src/test/PayFake.kt"]);
+        assert_eq!(paths_for(None).len(), 3);
+        assert!(!SearchScope { tests: Some(false), ..SearchScope::none() }.allows_path("src/PayTest.kt"));
+    }
+
+    #[test]
+    fn test_changed_files_scope() {
+        let conn = create_test_db();
+        for path in ["src/A.kt", "src/B.kt"] {
+            let file_id = upsert_file(&conn, path, 1, 1).unwrap();
+            insert_symbol(&conn, file_id, "Pay", SymbolKind::Class, 1, None).unwrap();
+            conn.execute("INSERT INTO refs (file_id, name, line, context) VALUES (?1, 'Pay', 5, 'x')", params![file_id]).unwrap();
+        }
+        let changed = vec!["src/B.kt".to_string()];
+        let scope = SearchScope { changed_files: Some(&changed), ..SearchScope::none() };
+        let results = search_symbols_scoped(&conn, "Pay*", 10, &scope).unwrap();
+        assert_eq!(results.iter().map(|r| r.path.as_str()).collect::<Vec<_>>(), vec!["src/B.kt"]);
+        let (definitions, _, usages) = find_cross_references(&conn, "Pay", None, None, 10, &scope).unwrap();
+        assert_eq!((definitions.len(), usages.len()), (1, 1));
+        assert!(scope.allows_path("src/B.kt"));
+        assert!(!scope.allows_path("src/A.kt"));
+
+        // Nothing changed: nothing matches
+        let scope = SearchScope { changed_files: Some(&[]), ..SearchScope::none() };
+        assert!(search_symbols_scoped(&conn, "Pay*", 10, &scope).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_find_definitions() {
+        let conn = create_test_db();
+        let file_id = upsert_file(&conn, "src/Repo.kt", 1, 1).unwrap();
+        insert_symbol(&conn, file_id, "UserRepo", SymbolKind::Class, 1, Some("class UserRepo {")).unwrap();
+        let save_id = insert_symbol(&conn, file_id, "save", SymbolKind::Function, 2, Some("fun save() {")).unwrap();
+        insert_symbol(&conn, file_id, "save", SymbolKind::Function, 9, Some("fun save() {")).unwrap();
+        conn.execute("UPDATE symbols SET container = 'UserRepo' WHERE id = ?1", params![save_id]).unwrap();
+        conn.execute(
+            "UPDATE symbols SET end_line = 4, start_byte = 20, end_byte = 60 WHERE id = ?1",
+            params![save_id],
+        ).unwrap();
+
+        assert_eq!(find_definitions(&conn, "save", Window::first(10), &SearchScope::none()).unwrap().0.len(), 2);
+
+        let defs = find_definitions(&conn, "UserRepo.save", Window::first(10), &SearchScope::none()).unwrap().0;
+        assert_eq!(defs.len(), 1);
+        assert_eq!(defs[0].symbol.line, 2);
+        assert_eq!((defs[0].end_line, defs[0].start_byte, defs[0].end_byte), (Some(4), Some(20), Some(60)));
+        assert!(find_definitions(&conn, "Other.save", Window::first(10), &SearchScope::none()).unwrap().0.is_empty());
+    }
 }
@@ -0,0 +1,446 @@
+//! Schema migrations
+//!
+//! Ordered steps that bring an index written by an older release up to the current schema,
+//! applied by `open_db` and recorded in `schema_version`. Indexes from before the table existed
+//! start at version 0; every step checks what is already there, so running one on a partly
+//! upgraded index is safe. New indexes are stamped with the latest version by `init_db`.
+//!
+//! A schema change appends a step to `MIGRATIONS` (and updates `init_db`); applied steps are
+//! never edited or reordered.
+
+use anyhow::Result;
+use rusqlite::{params, Connection, Transaction, TransactionBehavior};
+use serde::Serialize;
+
+use crate::{fuzzy, parsers};
+
+/// Applied migrations, one row per step
+const SCHEMA_VERSION_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS schema_version (
+        version INTEGER PRIMARY KEY,
+        description TEXT NOT NULL,
+        applied_at INTEGER NOT NULL
+    );
+"#;
+
+/// A schema change
+#[derive(Debug, Serialize)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    #[serde(skip)]
+    apply: fn(&Connection) -> Result<()>,
+}
+
+/// Every migration, by version
+pub const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, description: "Add files.encoding, files.language and files.is_test", apply: file_columns },
+    Migration {
+        version: 2,
+        description: "Add symbol columns: container, qualified_name, doc, visibility, spans, partial, condition, arity, signature_hash, generated_by, humps, complexity",
+        apply: symbol_columns,
+    },
+    Migration { version: 3, description: "Add refs.ref_kind and refs.arity", apply: ref_kind_columns },
+    Migration {
+        version: 4,
+        description: "Create symbol_annotations, imports, name_trigrams, symbol_embeddings and calls",
+        apply: auxiliary_tables,
+    },
+    Migration {
+        version: 5,
+        description: "Index symbol names ignoring case; index refs by name and file",
+        apply: name_indexes,
+    },
+    Migration { version: 6, description: "Create di_bindings", apply: di_bindings_table },
+    Migration { version: 7, description: "Add refs.count and refs.sample_lines for aggregated refs", apply: aggregated_ref_columns },
+    Migration {
+        version: 8,
+        description: "Store reference names once in names; refs becomes a view over refs_data",
+        apply: intern_ref_names,
+    },
+    Migration { version: 9, description: "Add files.skipped", apply: skipped_files_column },
+    Migration {
+        version: 10,
+        description: "Store symbol names in names too; symbols becomes a view over symbols_data",
+        apply: intern_symbol_names,
+    },
+];
+
+/// Version of the current schema
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// Highest applied version; 0 for an index without `schema_version`
+pub fn current_version(conn: &Connection) -> Result<u32> {
+    let has_table = conn
+        .query_row("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'", [], |_| Ok(()))
+        .is_ok();
+    if !has_table {
+        return Ok(0);
+    }
+    Ok(conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))?)
+}
+
+/// Migrations not applied yet, in order
+pub fn pending(conn: &Connection) -> Result<Vec<&'static Migration>> {
+    let current = current_version(conn)?;
+    Ok(MIGRATIONS.iter().filter(|m| m.version > current).collect())
+}
+
+/// Apply pending migrations in order, each in its own transaction; returns the applied ones
+pub fn migrate(conn: &Connection) -> Result<Vec<&'static Migration>> {
+    if current_version(conn)? >= latest_version() {
+        return Ok(vec![]);
+    }
+    conn.execute_batch(SCHEMA_VERSION_SCHEMA)?;
+    // Steps that rebuild a table drop the old one, which with foreign keys on would cascade to
+    // the rows pointing at it; the setting cannot change inside a transaction
+    let foreign_keys: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+    conn.pragma_update(None, "foreign_keys", "OFF")?;
+    let applied = apply_pending(conn);
+    if foreign_keys {
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+    }
+    applied
+}
+
+fn apply_pending(conn: &Connection) -> Result<Vec<&'static Migration>> {
+    let mut applied = Vec::new();
+    for migration in pending(conn)? {
+        // Another process may have applied it since `pending`
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+        let done = tx
+            .query_row("SELECT 1 FROM schema_version WHERE version = ?1", params![migration.version], |_| Ok(()))
+            .is_ok();
+        if !done {
+            (migration.apply)(&tx)?;
+            record(&tx, migration)?;
+            applied.push(migration);
+        }
+        tx.commit()?;
+    }
+    Ok(applied)
+}
+
+/// Mark every migration applied; for indexes created with the current schema
+pub fn stamp_latest(conn: &Connection) -> Result<()> {
+    conn.execute_batch(SCHEMA_VERSION_SCHEMA)?;
+    for migration in MIGRATIONS {
+        record(conn, migration)?;
+    }
+    Ok(())
+}
+
+fn record(conn: &Connection, migration: &Migration) -> Result<()> {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64);
+    conn.execute(
+        "INSERT OR IGNORE INTO schema_version (version, description, applied_at) VALUES (?1, ?2, ?3)",
+        params![migration.version, migration.description, now],
+    )?;
+    Ok(())
+}
+
+/// Add a column to an existing table (no-op if the table or column is missing/present);
+/// returns whether the column was added
+pub(super) fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<bool> {
+    let columns: Vec<String> = conn
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get(1))?
+        .collect::<Result<_, _>>()?;
+    if !columns.is_empty() && !columns.iter().any(|c| c == column) {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl))?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+fn table_exists(conn: &Connection, table: &str) -> bool {
+    conn.query_row("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1", params![table], |_| Ok(()))
+        .is_ok()
+}
+
+fn file_columns(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "files", "encoding", "TEXT")?;
+    if add_column_if_missing(conn, "files", "language", "TEXT")? {
+        fill_languages(conn)?;
+    }
+    if add_column_if_missing(conn, "files", "is_test", "INTEGER NOT NULL DEFAULT 0")? {
+        fill_test_flags(conn)?;
+    }
+    Ok(())
+}
+
+fn symbol_columns(conn: &Connection) -> Result<()> {
+    for (column, decl) in [
+        ("container", "TEXT"),
+        ("qualified_name", "TEXT"),
+        ("doc", "TEXT"),
+        ("visibility", "TEXT"),
+        ("end_line", "INTEGER"),
+        ("start_byte", "INTEGER"),
+        ("end_byte", "INTEGER"),
+        ("partial", "INTEGER"),
+        ("condition", "TEXT"),
+        ("arity", "INTEGER"),
+        ("signature_hash", "TEXT"),
+        ("generated_by", "TEXT"),
+    ] {
+        add_column_if_missing(conn, "symbols", column, decl)?;
+    }
+    if add_column_if_missing(conn, "symbols", "humps", "TEXT")? {
+        fill_humps(conn)?;
+    }
+    add_column_if_missing(conn, "symbols", "complexity", "INTEGER")?;
+    Ok(())
+}
+
+fn ref_kind_columns(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "refs", "ref_kind", "TEXT")?;
+    add_column_if_missing(conn, "refs", "arity", "INTEGER")?;
+    Ok(())
+}
+
+fn auxiliary_tables(conn: &Connection) -> Result<()> {
+    conn.execute_batch(super::SYMBOL_ANNOTATIONS_SCHEMA)?;
+    conn.execute_batch(super::IMPORTS_SCHEMA)?;
+    conn.execute_batch(super::NAME_TRIGRAMS_SCHEMA)?;
+    conn.execute_batch(super::EMBEDDINGS_SCHEMA)?;
+    conn.execute_batch(super::CALLS_SCHEMA)?;
+    Ok(())
+}
+
+fn name_indexes(conn: &Connection) -> Result<()> {
+    if table_exists(conn, "symbols") {
+        conn.execute_batch(super::NAME_NOCASE_INDEX)?;
+    }
+    if table_exists(conn, "refs") {
+        conn.execute_batch(super::REFS_NAME_INDEX)?;
+    }
+    Ok(())
+}
+
+fn di_bindings_table(conn: &Connection) -> Result<()> {
+    conn.execute_batch(super::DI_BINDINGS_SCHEMA)?;
+    Ok(())
+}
+
+fn aggregated_ref_columns(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "refs", "count", "INTEGER")?;
+    add_column_if_missing(conn, "refs", "sample_lines", "TEXT")?;
+    Ok(())
+}
+
+fn intern_ref_names(conn: &Connection) -> Result<()> {
+    let refs_is_table = conn
+        .query_row("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'refs'", [], |_| Ok(()))
+        .is_ok();
+    if !refs_is_table {
+        return Ok(());
+    }
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS names (
+             id INTEGER PRIMARY KEY,
+             text TEXT NOT NULL UNIQUE
+         );
+         CREATE TABLE IF NOT EXISTS refs_data (
+             id INTEGER PRIMARY KEY,
+             file_id INTEGER NOT NULL,
+             name_id INTEGER NOT NULL,
+             line INTEGER NOT NULL,
+             context TEXT,
+             ref_kind TEXT,
+             arity INTEGER,
+             count INTEGER,
+             sample_lines TEXT,
+             FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+         );
+         INSERT OR IGNORE INTO names (text) SELECT DISTINCT name FROM refs;
+         INSERT INTO refs_data (id, file_id, name_id, line, context, ref_kind, arity, count, sample_lines)
+             SELECT r.id, r.file_id, n.id, r.line, r.context, r.ref_kind, r.arity, r.count, r.sample_lines
+             FROM refs r JOIN names n ON n.text = r.name;
+         DROP TABLE refs;",
+    )?;
+    conn.execute_batch(super::REFS_VIEW)?;
+    Ok(())
+}
+
+fn skipped_files_column(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "files", "skipped", "TEXT")?;
+    Ok(())
+}
+
+/// Renaming the table points the foreign keys of annotations, calls, embeddings and inheritance
+/// at `symbols_data`; the rows, with their ids, then move to a new table without the name column
+/// that takes over the name (`DROP COLUMN` needs SQLite 3.35). The FTS index keeps reading
+/// `symbols`
+fn intern_symbol_names(conn: &Connection) -> Result<()> {
+    if !table_exists(conn, "symbols") {
+        return Ok(());
+    }
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS symbols_ai; DROP TRIGGER IF EXISTS symbols_ad; DROP TRIGGER IF EXISTS symbols_au;
+         DROP INDEX IF EXISTS idx_symbols_name;
+         DROP INDEX IF EXISTS idx_symbols_name_nocase;
+         CREATE TABLE IF NOT EXISTS names (
+             id INTEGER PRIMARY KEY,
+             text TEXT NOT NULL UNIQUE
+         );
+         INSERT OR IGNORE INTO names (text) SELECT DISTINCT name FROM symbols;
+         ALTER TABLE symbols RENAME TO symbols_data;
+         CREATE TABLE symbols_interned (
+             id INTEGER PRIMARY KEY,
+             file_id INTEGER NOT NULL,
+             name_id INTEGER NOT NULL,
+             kind TEXT NOT NULL,
+             line INTEGER NOT NULL,
+             parent_id INTEGER,
+             signature TEXT,
+             container TEXT,
+             qualified_name TEXT,
+             doc TEXT,
+             visibility TEXT,
+             end_line INTEGER,
+             start_byte INTEGER,
+             end_byte INTEGER,
+             partial INTEGER,
+             condition TEXT,
+             arity INTEGER,
+             signature_hash TEXT,
+             generated_by TEXT,
+             humps TEXT,
+             complexity INTEGER,
+             FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE
+         );
+         INSERT INTO symbols_interned (id, file_id, name_id, kind, line, parent_id, signature, container, qualified_name,
+                                   doc, visibility, end_line, start_byte, end_byte, partial, condition, arity,
+                                   signature_hash, generated_by, humps, complexity)
+             SELECT s.id, s.file_id, n.id, s.kind, s.line, s.parent_id, s.signature, s.container, s.qualified_name,
+                    s.doc, s.visibility, s.end_line, s.start_byte, s.end_byte, s.partial, s.condition, s.arity,
+                    s.signature_hash, s.generated_by, s.humps, s.complexity
+             FROM symbols_data s JOIN names n ON n.text = s.name;
+         DROP TABLE symbols_data;
+         ALTER TABLE symbols_interned RENAME TO symbols_data;
+         CREATE INDEX IF NOT EXISTS idx_symbols_kind ON symbols_data(kind);
+         CREATE INDEX IF NOT EXISTS idx_symbols_file ON symbols_data(file_id);",
+    )?;
+    conn.execute_batch(super::SYMBOLS_VIEW)?;
+    if table_exists(conn, "symbols_fts") && super::fts5_available(conn) {
+        super::create_symbols_fts(conn)?;
+    }
+    Ok(())
+}
+
+/// Compute `files.language` for files indexed before the column existed
+fn fill_languages(conn: &Connection) -> Result<()> {
+    let rows: Vec<(i64, String)> = conn
+        .prepare("SELECT id, path FROM files")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    let mut stmt = conn.prepare("UPDATE files SET language = ?1 WHERE id = ?2")?;
+    for (id, path) in rows {
+        stmt.execute(params![parsers::language_of_path(&path), id])?;
+    }
+    Ok(())
+}
+
+/// Compute `files.is_test` for files indexed before the column existed
+fn fill_test_flags(conn: &Connection) -> Result<()> {
+    let paths: Vec<String> = conn
+        .prepare("SELECT path FROM files")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    let mut stmt = conn.prepare("UPDATE files SET is_test = 1 WHERE path = ?1")?;
+    for path in paths.iter().filter(|path| parsers::is_test_path(path)) {
+        stmt.execute(params![path])?;
+    }
+    Ok(())
+}
+
+/// Compute `symbols.humps` for rows indexed before the column existed
+fn fill_humps(conn: &Connection) -> Result<()> {
+    let rows: Vec<(i64, String)> = conn
+        .prepare("SELECT id, name FROM symbols WHERE humps IS NULL")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    let mut stmt = conn.prepare("UPDATE symbols SET humps = ?1 WHERE id = ?2")?;
+    for (id, name) in rows {
+        stmt.execute(params![fuzzy::hump_skeleton(&name), id])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        conn.prepare(&format!("PRAGMA table_info({})", table))
+            .unwrap()
+            .query_map([], |row| row.get(1))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_versions_are_ordered() {
+        for (idx, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, idx + 1);
+        }
+    }
+
+    #[test]
+    fn test_migrate_legacy_index() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+        conn.execute_batch(
+            "CREATE TABLE files (id INTEGER PRIMARY KEY, path TEXT NOT NULL UNIQUE, mtime INTEGER NOT NULL, size INTEGER NOT NULL);
+             CREATE TABLE symbols (id INTEGER PRIMARY KEY, file_id INTEGER NOT NULL, name TEXT NOT NULL, kind TEXT NOT NULL, line INTEGER NOT NULL, parent_id INTEGER, signature TEXT, container TEXT, FOREIGN KEY (file_id) REFERENCES files(id) ON DELETE CASCADE);
+             CREATE TABLE inheritance (id INTEGER PRIMARY KEY, child_id INTEGER NOT NULL, parent_name TEXT NOT NULL, kind TEXT NOT NULL, FOREIGN KEY (child_id) REFERENCES symbols(id) ON DELETE CASCADE);
+             CREATE TABLE refs (id INTEGER PRIMARY KEY, file_id INTEGER NOT NULL, name TEXT NOT NULL, line INTEGER NOT NULL, context TEXT);
+             INSERT INTO files (path, mtime, size) VALUES ('src/test/CartTest.kt', 1, 1);
+             INSERT INTO symbols (file_id, name, kind, line) VALUES (1, 'CartTest', 'class', 1);
+             INSERT INTO inheritance (child_id, parent_name, kind) VALUES (1, 'TestCase', 'extends');
+             INSERT INTO refs (file_id, name, line, context) VALUES (1, 'Cart', 3, 'val cart = Cart()');",
+        )
+        .unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 0);
+        assert_eq!(pending(&conn).unwrap().len(), MIGRATIONS.len());
+
+        assert_eq!(migrate(&conn).unwrap().len(), MIGRATIONS.len());
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert!(pending(&conn).unwrap().is_empty());
+        assert!(migrate(&conn).unwrap().is_empty());
+        assert!(columns(&conn, "refs").ends_with(&["ref_kind".to_string(), "arity".to_string(), "count".to_string(), "sample_lines".to_string()]));
+        assert!(!table_exists(&conn, "refs"));
+        assert!(columns(&conn, "refs_data").contains(&"name_id".to_string()));
+        let (name, context): (String, String) = conn.query_row("SELECT name, context FROM refs", [], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+        assert_eq!((name.as_str(), context.as_str()), ("Cart", "val cart = Cart()"));
+        assert!(columns(&conn, "symbols").contains(&"humps".to_string()));
+        assert!(!table_exists(&conn, "symbols"));
+        assert!(!columns(&conn, "symbols_data").contains(&"name".to_string()));
+        let inheritance: String = conn.query_row("SELECT sql FROM sqlite_master WHERE name = 'inheritance'", [], |row| row.get(0)).unwrap();
+        assert!(inheritance.contains("REFERENCES \"symbols_data\"(id)"), "{}", inheritance);
+        let parent: String = conn
+            .query_row("SELECT i.parent_name FROM symbols s JOIN inheritance i ON i.child_id = s.id WHERE s.name = 'CartTest'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(parent, "TestCase");
+        assert!(conn.query_row("PRAGMA foreign_keys", [], |row| row.get::<_, bool>(0)).unwrap());
+        assert!(conn.prepare("PRAGMA foreign_key_check").unwrap().query([]).unwrap().next().unwrap().is_none());
+        assert_eq!(conn.query_row("SELECT COUNT(*) FROM names", [], |row| row.get::<_, i64>(0)).unwrap(), 2);
+        assert!(table_exists(&conn, "di_bindings"));
+        let (is_test, humps): (i64, String) = conn
+            .query_row("SELECT f.is_test, s.humps FROM files f JOIN symbols s ON s.file_id = f.id", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!((is_test, humps.as_str()), (1, fuzzy::hump_skeleton("CartTest").as_str()));
+    }
+
+    #[test]
+    fn test_new_index_is_current() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert!(migrate(&conn).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,342 @@
+//! Refs shards
+//!
+//! With `[index] refs_shards = ["app", "services"]`, `rebuild` keeps the references of files under
+//! each listed top-level directory in a database of its own next to the index
+//! (`index.refs-app.db`) and the rest in `index.refs.db`, so writes and lookups in a monorepo work
+//! on smaller B-trees. Every connection attaches the shards and shadows `refs` with a TEMP view
+//! over them, so queries fan out unchanged; INSTEAD OF triggers route inserts by the file's
+//! top-level directory, and a trigger on `files` drops the refs of deleted files, which the
+//! foreign key cascade of `main.refs_data` no longer covers. Shards store names as ids into
+//! `main.names`, like `refs_data`.
+//!
+//! Table names inside triggers cannot be qualified, so each shard's table has its own name.
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+
+/// Directory shards per index; with the catch-all shard and one more for `merge_database`, stays
+/// within SQLite's limit of 10 attached databases
+pub const MAX_REFS_SHARDS: usize = 8;
+
+/// Metadata key holding the JSON list of shard directories
+const SHARDS_KEY: &str = "refs_shards";
+
+/// A shard: its schema alias, table and the directory it holds (`None` for the rest)
+struct Shard {
+    alias: String,
+    table: String,
+    dir: Option<String>,
+}
+
+fn shards(dirs: &[String], prefix: &str) -> Vec<Shard> {
+    dirs.iter()
+        .enumerate()
+        .map(|(i, dir)| Shard { alias: format!("{}_{}", prefix, i), table: format!("refs_shard_{}", i), dir: Some(dir.clone()) })
+        .chain(std::iter::once(Shard { alias: format!("{}_rest", prefix), table: "refs_shard_rest".to_string(), dir: None }))
+        .collect()
+}
+
+/// File of the shard holding `dir` (`None`: everything else) of the index at `db_path`
+pub fn shard_path(db_path: &Path, dir: Option<&str>) -> PathBuf {
+    let stem = db_path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    match dir {
+        Some(dir) => db_path.with_file_name(format!("{}.refs-{}.db", stem, dir)),
+        None => db_path.with_file_name(format!("{}.refs.db", stem)),
+    }
+}
+
+/// Shard files of the index at `db_path`, with their WAL/SHM files
+pub fn shard_files(db_path: &Path) -> Vec<PathBuf> {
+    let Some(stem) = db_path.file_stem().map(|s| s.to_string_lossy().into_owned()) else {
+        return vec![];
+    };
+    let dir = match db_path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let (named, rest) = (format!("{}.refs-", stem), format!("{}.refs.db", stem));
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            let name = p.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+            name.starts_with(&named) || name.starts_with(&rest)
+        })
+        .collect()
+}
+
+/// Shard directories of the index attached as `schema`; empty for an unsharded index
+fn directories(conn: &Connection, schema: &str) -> Result<Vec<String>> {
+    let value: Option<String> = conn
+        .query_row(&format!("SELECT value FROM {}.metadata WHERE key = ?1", schema), params![SHARDS_KEY], |row| row.get(0))
+        .ok();
+    Ok(match value {
+        Some(json) => serde_json::from_str(&json)?,
+        None => vec![],
+    })
+}
+
+/// Shard the refs of a new index at `db_path` by the top-level directories `dirs`
+pub fn create(conn: &Connection, db_path: &Path, dirs: &[String]) -> Result<()> {
+    if dirs.len() > MAX_REFS_SHARDS {
+        anyhow::bail!("refs_shards lists {} directories; at most {} are supported", dirs.len(), MAX_REFS_SHARDS);
+    }
+    for dir in dirs {
+        if dir.is_empty() || dir == "." || dir == ".." || dir.contains(['/', '\\']) {
+            anyhow::bail!("refs_shards entry '{}' is not a top-level directory name", dir);
+        }
+    }
+    conn.execute(
+        "INSERT OR REPLACE INTO metadata (key, value) VALUES (?1, ?2)",
+        params![SHARDS_KEY, serde_json::to_string(dirs)?],
+    )?;
+    attach(conn, db_path)
+}
+
+/// Attach the shards of a sharded index and route `refs` through them; no-op for other indexes
+pub fn attach(conn: &Connection, db_path: &Path) -> Result<()> {
+    let dirs = directories(conn, "main")?;
+    if dirs.is_empty() {
+        return Ok(());
+    }
+    let shards = shards(&dirs, "refs_shard");
+
+    // (name, type, not null, primary key) of main.refs_data, which migrations keep current
+    let columns: Vec<(String, String, bool, bool)> = conn
+        .prepare("PRAGMA main.table_info(refs_data)")?
+        .query_map([], |row| Ok((row.get(1)?, row.get(2)?, row.get(3)?, row.get::<_, i64>(5)? > 0)))?
+        .collect::<Result<_, _>>()?;
+    let names: Vec<&str> = columns.iter().map(|(name, ..)| name.as_str()).collect();
+    let column_list = names.join(", ");
+    let new_values = names
+        .iter()
+        .map(|&c| match c {
+            "name_id" => "(SELECT id FROM main.names WHERE text = NEW.name)".to_string(),
+            _ => format!("NEW.{}", c),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let view_columns = view_columns(conn, "main")?;
+
+    for shard in &shards {
+        let path = shard_path(db_path, shard.dir.as_deref());
+        conn.execute("ATTACH DATABASE ?1 AS ?2", params![path.to_string_lossy().as_ref(), shard.alias])?;
+        let _: String = conn.query_row(&format!("PRAGMA {}.journal_mode = WAL", shard.alias), [], |row| row.get(0))?;
+
+        let existing: Vec<String> = conn
+            .prepare(&format!("PRAGMA {}.table_info({})", shard.alias, shard.table))?
+            .query_map([], |row| row.get(1))?
+            .collect::<Result<_, _>>()?;
+        if existing.is_empty() {
+            let defs: Vec<String> = columns
+                .iter()
+                .map(|(name, ty, not_null, pk)| match (pk, not_null) {
+                    (true, _) => format!("{} INTEGER PRIMARY KEY", name),
+                    (false, true) => format!("{} {} NOT NULL", name, ty),
+                    (false, false) => format!("{} {}", name, ty),
+                })
+                .collect();
+            conn.execute_batch(&format!(
+                "CREATE TABLE {alias}.{table} ({defs});
+                 CREATE INDEX {alias}.idx_{table}_name_file ON {table}(name_id, file_id);
+                 CREATE INDEX {alias}.idx_{table}_file ON {table}(file_id);",
+                alias = shard.alias,
+                table = shard.table,
+                defs = defs.join(", "),
+            ))?;
+        } else if !existing.iter().any(|c| c == "name_id") {
+            anyhow::bail!("{} was written before reference names were interned; run 'ast-index rebuild'", path.display());
+        } else {
+            // Columns a migration added to main.refs_data after the shard was created
+            for (name, ty, ..) in columns.iter().filter(|(name, ..)| !existing.contains(name)) {
+                conn.execute_batch(&format!("ALTER TABLE {}.{} ADD COLUMN {} {}", shard.alias, shard.table, name, ty))?;
+            }
+        }
+    }
+
+    let in_dir = |dir: &str| format!("f.path >= '{0}/' AND f.path < '{0}0'", dir.replace('\'', "''"));
+    let view = shards
+        .iter()
+        .map(|s| format!("SELECT {} FROM {}.{} r JOIN main.names n ON n.id = r.name_id", view_columns, s.alias, s.table))
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+    let inserts: String = std::iter::once("INSERT OR IGNORE INTO names (text) VALUES (NEW.name);\n".to_string())
+        .chain(shards
+        .iter()
+        .map(|s| match &s.dir {
+            Some(dir) => format!(
+                "INSERT INTO {} ({}) SELECT {} FROM main.files f WHERE f.id = NEW.file_id AND {};\n",
+                s.table, column_list, new_values, in_dir(dir)
+            ),
+            None => format!(
+                "INSERT INTO {} ({}) SELECT {} WHERE NOT EXISTS (SELECT 1 FROM main.files f WHERE f.id = NEW.file_id AND ({}));\n",
+                s.table,
+                column_list,
+                new_values,
+                dirs.iter().map(|d| in_dir(d)).collect::<Vec<_>>().join(" OR ")
+            ),
+        }))
+        .collect();
+    let deletes = |condition: &str| -> String {
+        shards.iter().map(|s| format!("DELETE FROM {} WHERE {};\n", s.table, condition)).collect()
+    };
+    conn.execute_batch(&format!(
+        "DROP VIEW IF EXISTS temp.refs;
+         CREATE TEMP VIEW refs AS {view};
+         CREATE TEMP TRIGGER refs_shard_insert INSTEAD OF INSERT ON refs BEGIN
+         {inserts}END;
+         CREATE TEMP TRIGGER refs_shard_delete INSTEAD OF DELETE ON refs BEGIN
+         {delete_rows}END;
+         CREATE TEMP TRIGGER refs_shard_files_delete AFTER DELETE ON main.files BEGIN
+         {delete_files}END;",
+        delete_rows = deletes("id = OLD.id AND file_id = OLD.file_id"),
+        delete_files = deletes("file_id = OLD.id"),
+    ))?;
+    // `INSERT OR REPLACE INTO files` fires the delete trigger only with recursive triggers on
+    conn.pragma_update(None, "recursive_triggers", "ON")?;
+    Ok(())
+}
+
+/// Select list of the `refs` view of the index attached as `schema`, over a storage table `r`
+/// joined with its names `n`
+fn view_columns(conn: &Connection, schema: &str) -> Result<String> {
+    let columns: Vec<String> = conn
+        .prepare(&format!("PRAGMA {}.table_info(refs)", schema))?
+        .query_map([], |row| row.get(1))?
+        .collect::<Result<_, _>>()?;
+    Ok(columns
+        .iter()
+        .map(|c| if c == "name" { "n.text AS name".to_string() } else { format!("r.{}", c) })
+        .collect::<Vec<_>>()
+        .join(", "))
+}
+
+/// Attach for reading the shards of the index attached as `schema` from `db_path`; returns the SQL
+/// source of its refs rows (a table or a subquery) and the aliases to detach afterwards
+pub fn attach_source(conn: &Connection, schema: &str, db_path: &Path) -> Result<(String, Vec<String>)> {
+    let dirs = directories(conn, schema)?;
+    if dirs.is_empty() {
+        return Ok((format!("{}.refs", schema), vec![]));
+    }
+    let shards = shards(&dirs, &format!("{}_refs", schema));
+    for shard in &shards {
+        let path = shard_path(db_path, shard.dir.as_deref());
+        conn.execute("ATTACH DATABASE ?1 AS ?2", params![path.to_string_lossy().as_ref(), shard.alias])?;
+    }
+    let view_columns = view_columns(conn, schema)?;
+    let union = shards
+        .iter()
+        .map(|s| format!("SELECT {} FROM {}.{} r JOIN {}.names n ON n.id = r.name_id", view_columns, s.alias, s.table, schema))
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+    Ok((format!("({})", union), shards.into_iter().map(|s| s.alias).collect()))
+}
+
+/// References per shard: (directory, count), the catch-all shard last as `None`
+pub fn counts(conn: &Connection) -> Result<Vec<(Option<String>, i64)>> {
+    let dirs = directories(conn, "main")?;
+    if dirs.is_empty() {
+        return Ok(vec![]);
+    }
+    shards(&dirs, "refs_shard")
+        .into_iter()
+        .map(|s| {
+            let count = conn.query_row(
+                &format!("SELECT COALESCE(SUM(COALESCE(count, 1)), 0) FROM {}.{}", s.alias, s.table),
+                [],
+                |row| row.get(0),
+            )?;
+            Ok((s.dir, count))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sharded_db(dir: &Path) -> (Connection, PathBuf) {
+        let db_path = dir.join("index.db");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+        crate::db::init_db(&conn).unwrap();
+        create(&conn, &db_path, &["app".to_string(), "lib".to_string()]).unwrap();
+        (conn, db_path)
+    }
+
+    fn shard_rows(conn: &Connection, table: &str) -> Vec<String> {
+        let sql = if table == "refs" {
+            "SELECT name FROM refs ORDER BY name".to_string()
+        } else {
+            format!("SELECT n.text FROM {} r JOIN names n ON n.id = r.name_id ORDER BY n.text", table)
+        };
+        conn.prepare(&sql)
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_refs_routed_by_top_level_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let (conn, db_path) = sharded_db(dir.path());
+        for (id, path) in [(1, "app/Main.kt"), (2, "lib/Util.kt"), (3, "build.gradle.kts"), (4, "application/App.kt")] {
+            conn.execute("INSERT INTO files (id, path, mtime, size) VALUES (?1, ?2, 0, 0)", params![id, path]).unwrap();
+        }
+        conn.execute(
+            "INSERT INTO refs (file_id, name, line) VALUES (1, 'A', 1), (2, 'B', 1), (3, 'C', 1), (4, 'D', 1), (1, 'E', 2)",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(shard_rows(&conn, "refs_shard_0"), vec!["A", "E"]);
+        assert_eq!(shard_rows(&conn, "refs_shard_1"), vec!["B"]);
+        assert_eq!(shard_rows(&conn, "refs_shard_rest"), vec!["C", "D"]);
+        assert_eq!(shard_rows(&conn, "main.refs_data"), Vec::<String>::new());
+        assert_eq!(shard_rows(&conn, "refs"), vec!["A", "B", "C", "D", "E"]);
+        assert_eq!(counts(&conn).unwrap(), vec![(Some("app".to_string()), 2), (Some("lib".to_string()), 1), (None, 2)]);
+
+        // Deleting through the view, deleting a file and replacing a file reach the shards
+        conn.execute("DELETE FROM refs WHERE name = 'E'", []).unwrap();
+        conn.execute("DELETE FROM files WHERE id = 2", []).unwrap();
+        conn.execute("INSERT OR REPLACE INTO files (path, mtime, size) VALUES ('build.gradle.kts', 1, 1)", []).unwrap();
+        assert_eq!(shard_rows(&conn, "refs"), vec!["A", "D"]);
+
+        // A new connection sees the same refs
+        drop(conn);
+        let conn = Connection::open(&db_path).unwrap();
+        attach(&conn, &db_path).unwrap();
+        assert_eq!(shard_rows(&conn, "refs"), vec!["A", "D"]);
+        assert_eq!(shard_files(&db_path).iter().filter(|p| p.extension().is_some_and(|e| e == "db")).count(), 3);
+    }
+
+    #[test]
+    fn test_merge_sharded_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let (src, src_path) = sharded_db(dir.path());
+        src.execute("INSERT INTO files (id, path, mtime, size) VALUES (1, 'app/Main.kt', 0, 0), (2, 'Root.kt', 0, 0)", []).unwrap();
+        src.execute("INSERT INTO refs (file_id, name, line) VALUES (1, 'A', 1), (2, 'B', 1)", []).unwrap();
+        drop(src);
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        let stats = crate::db::merge_database(&mut conn, &src_path, "repo/").unwrap();
+        assert_eq!(stats.refs, 2);
+        assert_eq!(shard_rows(&conn, "refs"), vec!["A", "B"]);
+    }
+
+    #[test]
+    fn test_create_rejects_nested_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("index.db");
+        let conn = Connection::open(&db_path).unwrap();
+        crate::db::init_db(&conn).unwrap();
+        assert!(create(&conn, &db_path, &["services/payments".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+//! Embeddings for `search --semantic`
+//!
+//! `ast-index embed` turns each symbol's name, signature and doc comment into a vector stored in
+//! `symbol_embeddings`; a semantic search embeds the query the same way and ranks symbols by
+//! cosine similarity.
+//!
+//! The built-in embedder needs no model: identifiers are split into words (`retryFailedCall` ->
+//! retry failed call), words are reduced to a rough stem and a few synonym groups share one
+//! feature (`fetch`, `load`, `retrieve`), and words plus their character trigrams are hashed into
+//! a fixed-size vector. A local model (ONNX, candle, ...) plugs in through `[embed] command` in
+//! `.ast-index.toml`: the command reads a JSON array of texts on stdin and writes a JSON array of
+//! vectors, one per text, on stdout.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+
+use crate::config::EmbedConfig;
+
+/// Dimensions of the built-in embedder's vectors
+pub const DIMENSIONS: usize = 256;
+
+/// Turns texts into vectors
+pub trait Embedder: Send + Sync {
+    /// Identifies the model; stored with the vectors so ones from different models are never compared
+    fn id(&self) -> String;
+    /// One vector per text
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Dependency-free embedder hashing words and character trigrams
+pub struct HashEmbedder;
+
+/// Local model run as an external command, see the module docs
+pub struct CommandEmbedder {
+    command: Vec<String>,
+    batch_size: usize,
+}
+
+static EMBEDDER: OnceLock<Box<dyn Embedder>> = OnceLock::new();
+
+/// Install the embedder configured in `.ast-index.toml` for this process. Later calls are ignored.
+pub fn register(config: Option<&EmbedConfig>) -> Result<()> {
+    let embedder: Box<dyn Embedder> = match config {
+        Some(config) => {
+            if config.command.is_empty() {
+                anyhow::bail!("[embed] command must name a program");
+            }
+            Box::new(CommandEmbedder { command: config.command.clone(), batch_size: config.batch_size.max(1) })
+        }
+        None => Box::new(HashEmbedder),
+    };
+    let _ = EMBEDDER.set(embedder);
+    Ok(())
+}
+
+/// The registered embedder; the built-in one unless `[embed]` configures a model
+pub fn embedder() -> &'static dyn Embedder {
+    EMBEDDER.get_or_init(|| Box::new(HashEmbedder)).as_ref()
+}
+
+/// Text embedded for a symbol: its name split into words, kind, signature and doc comment
+pub fn symbol_text(name: &str, kind: &str, signature: Option<&str>, doc: Option<&str>) -> String {
+    let mut text = format!("{} {}", words(name).join(" "), kind);
+    for part in [signature, doc].into_iter().flatten() {
+        text.push_str(". ");
+        text.push_str(part);
+    }
+    text
+}
+
+/// Cosine similarity; 0 for vectors of different length or zero vectors
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 { 0.0 } else { dot / norms }
+}
+
+/// Vector as stored in `symbol_embeddings.vector`: little-endian f32s
+pub fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+/// Inverse of `to_blob`
+pub fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect()
+}
+
+/// Lowercase words of `text`, splitting identifiers at humps, `_` and digits
+/// (`parseHTTPRequest2` -> parse, http, request, 2)
+pub fn words(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+        let boundary = prev.is_some_and(|p| {
+            (c.is_uppercase() && p.is_lowercase())
+                || (c.is_uppercase() && p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+                || (c.is_numeric() != p.is_numeric())
+        });
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Words too common to say anything about a symbol
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "if", "in", "into", "is", "it",
+    "its", "of", "on", "or", "that", "the", "this", "to", "was", "when", "which", "with",
+];
+
+/// Words treated as one feature; the first word names the group
+const SYNONYMS: &[&[&str]] = &[
+    &["get", "fetch", "load", "retrieve", "read", "obtain"],
+    &["create", "make", "build", "construct", "new", "init", "initialize"],
+    &["delete", "remove", "erase", "drop", "destroy", "purge"],
+    &["update", "modify", "change", "edit", "patch"],
+    &["save", "store", "persist", "write", "put"],
+    &["find", "search", "lookup", "query", "locate"],
+    &["send", "post", "emit", "publish", "dispatch"],
+    &["network", "http", "https", "remote", "api", "rest", "web", "net"],
+    &["call", "request", "invoke", "invocation"],
+    &["retry", "retries", "reattempt", "backoff"],
+    &["fail", "failure", "error", "err", "exception", "fault"],
+    &["config", "configuration", "settings", "setting", "options", "preferences", "prefs"],
+    &["user", "account", "customer", "member"],
+    &["auth", "authenticate", "authentication", "login", "signin", "credential", "credentials"],
+    &["cache", "memo", "memoize"],
+    &["parse", "decode", "deserialize", "unmarshal"],
+    &["serialize", "encode", "marshal"],
+    &["check", "validate", "verify", "ensure"],
+    &["start", "begin", "launch", "open"],
+    &["stop", "end", "finish", "close", "shutdown", "terminate"],
+];
+
+/// Rough stem: plural and tense endings removed (`retries` -> retry, `failed` -> fail)
+fn stem(word: &str) -> String {
+    let n = word.chars().count();
+    if n <= 3 || !word.is_ascii() {
+        return word.to_string();
+    }
+    if let Some(base) = word.strip_suffix("ies") {
+        return format!("{}y", base);
+    }
+    for suffix in ["ing", "ed", "es", "s"] {
+        if let Some(base) = word.strip_suffix(suffix) {
+            if base.len() >= 3 && !base.ends_with('s') {
+                return base.to_string();
+            }
+        }
+    }
+    word.to_string()
+}
+
+/// Feature word for `word`: its synonym group, else its stem
+fn concept(word: &str) -> String {
+    let stemmed = stem(word);
+    SYNONYMS
+        .iter()
+        .find(|group| group.contains(&word) || group.contains(&stemmed.as_str()))
+        .map(|group| group[0].to_string())
+        .unwrap_or(stemmed)
+}
+
+/// FNV-1a
+fn hash(feature: &str) -> u64 {
+    feature.bytes().fold(0xcbf2_9ce4_8422_2325, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+impl HashEmbedder {
+    /// L2-normalized vector of `text`'s word and trigram features
+    pub fn embed_text(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0f32; DIMENSIONS];
+        let mut add = |feature: &str, weight: f32| {
+            let h = hash(feature);
+            let sign = if h >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[(h % DIMENSIONS as u64) as usize] += sign * weight;
+        };
+        for word in words(text) {
+            if STOP_WORDS.contains(&word.as_str()) {
+                continue;
+            }
+            let concept = concept(&word);
+            add(&format!("w:{}", concept), 1.0);
+            // Trigrams of the stem relate words sharing a root (`payment`, `payments`, `repay`)
+            let padded: Vec<char> = format!("<{}>", stem(&word)).chars().collect();
+            for trigram in padded.windows(3) {
+                add(&format!("t:{}", trigram.iter().collect::<String>()), 0.25);
+            }
+        }
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        vector
+    }
+}
+
+impl Embedder for HashEmbedder {
+    fn id(&self) -> String {
+        format!("builtin-hash-{}", DIMENSIONS)
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| self.embed_text(text)).collect())
+    }
+}
+
+impl CommandEmbedder {
+    fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let program = &self.command[0];
+        let mut child = Command::new(program)
+            .args(&self.command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| format!("Failed to run embedding command '{}'", program))?;
+        let input = serde_json::to_vec(texts)?;
+        let mut stdin = child.stdin.take().context("embedding command has no stdin")?;
+        // Written from another thread so a command that streams output early cannot deadlock
+        let writer = std::thread::spawn(move || stdin.write_all(&input));
+        let output = child.wait_with_output()?;
+        writer.join().map_err(|_| anyhow::anyhow!("writing to embedding command panicked"))?.ok();
+        if !output.status.success() {
+            anyhow::bail!("Embedding command '{}' failed ({})", program, output.status);
+        }
+        let vectors: Vec<Vec<f32>> = serde_json::from_slice(&output.stdout)
+            .with_context(|| format!("Embedding command '{}' must print a JSON array of vectors", program))?;
+        if vectors.len() != texts.len() {
+            anyhow::bail!(
+                "Embedding command '{}' returned {} vectors for {} texts",
+                program, vectors.len(), texts.len()
+            );
+        }
+        Ok(vectors)
+    }
+}
+
+impl Embedder for CommandEmbedder {
+    fn id(&self) -> String {
+        format!("command:{}", self.command.join(" "))
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            vectors.extend(self.embed_batch(batch)?);
+        }
+        Ok(vectors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        assert_eq!(words("parseHTTPRequest2"), vec!["parse", "http", "request", "2"]);
+        assert_eq!(words("retry_failed_call"), vec!["retry", "failed", "call"]);
+        assert_eq!(words("fun load(id: Int)"), vec!["fun", "load", "id", "int"]);
+    }
+
+    #[test]
+    fn test_concepts() {
+        assert_eq!(stem("retries"), "retry");
+        assert_eq!(stem("failed"), "fail");
+        assert_eq!(stem("calls"), "call");
+        assert_eq!(stem("class"), "class");
+        assert_eq!(concept("fetching"), "get");
+        assert_eq!(concept("requests"), "call");
+    }
+
+    #[test]
+    fn test_blob_round_trip() {
+        let vector = vec![0.5, -1.25, 3.0];
+        assert_eq!(from_blob(&to_blob(&vector)), vector);
+    }
+
+    #[test]
+    fn test_hash_embedder_ranks_related_text_higher() {
+        let embedder = HashEmbedder;
+        let query = embedder.embed_text("retry failed network calls");
+        let retry = embedder.embed_text(&symbol_text(
+            "retryWithBackoff", "function", Some("suspend fun retryWithBackoff(block: () -> Response)"),
+            Some("Retries HTTP requests that failed"),
+        ));
+        let render = embedder.embed_text(&symbol_text("renderAvatar", "function", Some("fun renderAvatar(user: User)"), None));
+        assert!(cosine(&query, &retry) > cosine(&query, &render) + 0.2);
+        assert!((cosine(&query, &query) - 1.0).abs() < 1e-5);
+        assert_eq!(query.len(), DIMENSIONS);
+    }
+
+    #[test]
+    fn test_command_embedder() {
+        let embedder = CommandEmbedder {
+            command: vec!["sh".into(), "-c".into(), "cat >/dev/null; echo '[[1,0],[0,1]]'".into()],
+            batch_size: 2,
+        };
+        let vectors = embedder.embed(&["a".to_string(), "b".to_string()]).unwrap();
+        assert_eq!(vectors, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+        // One vector per text, or an error
+        assert!(embedder.embed(&["a".to_string()]).is_err());
+        assert!(embedder.id().starts_with("command:sh"));
+    }
+}
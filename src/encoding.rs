@@ -0,0 +1,150 @@
+//! Source file encodings
+//!
+//! Files are read as bytes and transcoded to UTF-8 before parsing. The encoding comes from
+//! the BOM if there is one; otherwise zero-byte patterns reveal UTF-16 without BOM, valid
+//! UTF-8 is taken as is, and the remaining single-byte files are scored as Windows-1251,
+//! KOI8-R or Windows-1252 by where their high bytes fall.
+
+use std::io;
+use std::path::Path;
+
+use encoding_rs::{Encoding, KOI8_R, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1251, WINDOWS_1252};
+
+/// Read a file as text, transcoding it to UTF-8; returns the text and the detected encoding name
+pub fn read_file(path: &Path) -> io::Result<(String, &'static str)> {
+    let bytes = std::fs::read(path)?;
+    Ok(decode(&bytes))
+}
+
+/// Decode bytes of unknown encoding; returns the text and the lowercase encoding name
+/// (`utf-8`, `utf-16le`, `windows-1251`, ...)
+pub fn decode(bytes: &[u8]) -> (String, &'static str) {
+    let encoding = detect(bytes);
+    // decode() strips a BOM matching the encoding
+    let (text, _, _) = encoding.decode(bytes);
+    (text.into_owned(), name(encoding))
+}
+
+/// Binary content: a zero byte in the first 8000 bytes, as git decides, unless the bytes are
+/// UTF-16, where zero bytes are part of the text
+pub fn is_binary(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(8000)];
+    sample.contains(&0) && Encoding::for_bom(bytes).is_none() && detect_utf16(bytes).is_none()
+}
+
+/// Best guess at the encoding of `bytes`
+pub fn detect(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    // Zero bytes are valid UTF-8, so UTF-16 has to be ruled out first
+    if let Some(encoding) = detect_utf16(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+    detect_single_byte(bytes)
+}
+
+fn name(encoding: &'static Encoding) -> &'static str {
+    match encoding {
+        e if e == UTF_8 => "utf-8",
+        e if e == UTF_16LE => "utf-16le",
+        e if e == UTF_16BE => "utf-16be",
+        e if e == WINDOWS_1251 => "windows-1251",
+        e if e == WINDOWS_1252 => "windows-1252",
+        e if e == KOI8_R => "koi8-r",
+        _ => "unknown",
+    }
+}
+
+/// UTF-16 without BOM: mostly-ASCII source has a zero in every other byte
+fn detect_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(4096) & !1];
+    let pairs = sample.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+    let zeros_at = |offset: usize| sample.iter().skip(offset).step_by(2).filter(|&&b| b == 0).count();
+    let (even, odd) = (zeros_at(0), zeros_at(1));
+    if odd * 10 >= pairs * 3 && even * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even * 10 >= pairs * 3 && odd * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Single-byte code page from the distribution of bytes >= 0x80.
+/// Cyrillic text is made of runs of high bytes; Latin-1 text has isolated accented letters.
+/// Lowercase Cyrillic is 0xE0-0xFF in Windows-1251 but 0xC0-0xDF in KOI8-R.
+fn detect_single_byte(bytes: &[u8]) -> &'static Encoding {
+    let high = |b: u8| b >= 0x80;
+    let (mut total, mut in_runs, mut upper_half, mut lower_half) = (0usize, 0usize, 0usize, 0usize);
+    for (i, &b) in bytes.iter().enumerate() {
+        if !high(b) {
+            continue;
+        }
+        total += 1;
+        let prev = i.checked_sub(1).map(|p| bytes[p]);
+        let next = bytes.get(i + 1).copied();
+        if prev.is_some_and(high) || next.is_some_and(high) {
+            in_runs += 1;
+        }
+        match b {
+            0xE0..=0xFF => upper_half += 1,
+            0xC0..=0xDF => lower_half += 1,
+            _ => {}
+        }
+    }
+    if total == 0 || in_runs * 2 < total {
+        return WINDOWS_1252;
+    }
+    if lower_half > upper_half {
+        KOI8_R
+    } else {
+        WINDOWS_1251
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf8_and_boms() {
+        assert_eq!(decode("class Привет {}".as_bytes()), ("class Привет {}".to_string(), "utf-8"));
+        let with_bom = [&[0xEF, 0xBB, 0xBF][..], b"int x;"].concat();
+        assert_eq!(decode(&with_bom), ("int x;".to_string(), "utf-8"));
+        let utf16: Vec<u8> = [0xFF, 0xFE].into_iter().chain("void f();".encode_utf16().flat_map(|u| u.to_le_bytes())).collect();
+        assert_eq!(decode(&utf16), ("void f();".to_string(), "utf-16le"));
+    }
+
+    #[test]
+    fn test_utf16_without_bom() {
+        let be: Vec<u8> = "class Foo {}".encode_utf16().flat_map(|u| u.to_be_bytes()).collect();
+        assert_eq!(decode(&be), ("class Foo {}".to_string(), "utf-16be"));
+    }
+
+    #[test]
+    fn test_binary_detection() {
+        assert!(is_binary(b"\x7fELF\x02\x01\x01\x00\x00\x00"));
+        assert!(!is_binary(b"fun main() {}\n"));
+        let utf16: Vec<u8> = "class Foo {}".encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+        assert!(!is_binary(&utf16));
+    }
+
+    #[test]
+    fn test_single_byte_code_pages() {
+        let source = "// Модуль оплаты\nvoid pay();\n";
+        let (cp1251, _, _) = WINDOWS_1251.encode(source);
+        assert_eq!(decode(&cp1251), (source.to_string(), "windows-1251"));
+        let (koi8, _, _) = KOI8_R.encode(source);
+        assert_eq!(decode(&koi8), (source.to_string(), "koi8-r"));
+        let latin = "# Café résumé\nsub run {}\n";
+        let (cp1252, _, _) = WINDOWS_1252.encode(latin);
+        assert_eq!(decode(&cp1252), (latin.to_string(), "windows-1252"));
+    }
+}
@@ -0,0 +1,214 @@
+//! Approximate name matching
+//!
+//! Typos: symbol names are split into lowercase character trigrams stored in `name_trigrams`;
+//! names sharing trigrams with the query are candidates, ranked by how many edits turn the
+//! query into some part of the name. `PaymntRepo` is one edit away from a part of
+//! `PaymentRepository`, so partial recollection with typos still finds it.
+//!
+//! Near duplicates: `similar` compares whole names by edit distance relative to their length,
+//! so `PaymentsManager` and `PaymentMangr` show up next to `PaymentManager`.
+//!
+//! Abbreviations: `symbols.humps` holds the first letters of a name's humps (`PRI` for
+//! `PaymentRepositoryImpl`), so `PRImpl` is looked up by its own skeleton and then checked
+//! hump by hump.
+
+use std::collections::BTreeSet;
+
+/// Distinct lowercase trigrams of `name`; empty for names shorter than three characters
+pub fn trigrams(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().flat_map(char::to_lowercase).collect();
+    let set: BTreeSet<String> = chars.windows(3).map(|w| w.iter().collect()).collect();
+    set.into_iter().collect()
+}
+
+/// Fewest edits (insert, delete, substitute, swap adjacent) turning `query` into a substring of
+/// `name`, case-insensitive; the position where the best match starts is returned too
+pub fn substring_distance(query: &str, name: &str) -> (usize, usize) {
+    let q: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let n: Vec<char> = name.chars().flat_map(char::to_lowercase).collect();
+    // rows[i][j]: edits for q[..i] against a substring of n ending at j; start[i][j] where it began
+    let mut rows = vec![vec![0usize; n.len() + 1]; q.len() + 1];
+    let mut start = vec![vec![0usize; n.len() + 1]; q.len() + 1];
+    for (j, s) in start[0].iter_mut().enumerate() {
+        *s = j;
+    }
+    for i in 1..=q.len() {
+        rows[i][0] = i;
+        for j in 1..=n.len() {
+            let cost = usize::from(q[i - 1] != n[j - 1]);
+            let mut best = (rows[i - 1][j - 1] + cost, start[i - 1][j - 1]);
+            best = best.min((rows[i - 1][j] + 1, start[i - 1][j]));
+            best = best.min((rows[i][j - 1] + 1, start[i][j - 1]));
+            if i > 1 && j > 1 && q[i - 1] == n[j - 2] && q[i - 2] == n[j - 1] {
+                best = best.min((rows[i - 2][j - 2] + 1, start[i - 2][j - 2]));
+            }
+            (rows[i][j], start[i][j]) = best;
+        }
+    }
+    (0..=n.len()).map(|j| (rows[q.len()][j], start[q.len()][j])).min().unwrap_or((q.len(), 0))
+}
+
+/// Fewest edits (insert, delete, substitute, swap adjacent) turning `a` into `b`, case-insensitive
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().flat_map(char::to_lowercase).collect();
+    let b: Vec<char> = b.chars().flat_map(char::to_lowercase).collect();
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j - 1] + cost).min(rows[i - 1][j] + 1).min(rows[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// `edit_distance` relative to the longer name: 0 for names differing only in case, 1 for names
+/// with nothing in common
+pub fn normalized_distance(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 0.0;
+    }
+    edit_distance(a, b) as f64 / longest as f64
+}
+
+/// Most edits a match may need: one per four characters of the query, at least one
+pub fn max_distance(query: &str) -> usize {
+    (query.chars().count() / 4).max(1)
+}
+
+/// Whether `chars[i]` starts a hump: the first character, an uppercase letter, the first
+/// character after `_` or another separator, or the first digit of a number
+fn hump_starts(chars: &[char]) -> Vec<bool> {
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            let Some(&prev) = i.checked_sub(1).and_then(|p| chars.get(p)) else { return true };
+            c.is_uppercase()
+                || (c.is_alphanumeric() && !prev.is_alphanumeric())
+                || (c.is_numeric() && !prev.is_numeric())
+        })
+        .collect()
+}
+
+/// Uppercased hump initials: `PRI` for `PaymentRepositoryImpl`, `HTTPS` for `HTTPServer`,
+/// `PHR` for `parse_http_request`
+pub fn hump_skeleton(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    chars
+        .iter()
+        .zip(hump_starts(&chars))
+        .filter(|(c, start)| *start && c.is_alphanumeric())
+        .flat_map(|(c, _)| c.to_uppercase())
+        .collect()
+}
+
+/// Whether a query reads as a CamelHump abbreviation (`PRImpl`, `NPE`, `pHR`)
+pub fn is_abbreviation(query: &str) -> bool {
+    query.chars().all(|c| c.is_alphanumeric() || c == '_') && hump_skeleton(query).chars().count() >= 2
+}
+
+/// IntelliJ-style CamelHump match: the query's characters appear in order in the name, starting
+/// at a hump, and whenever the query leaves a hump it resumes at the start of a later one
+/// (`PRImpl` and `PaReIm` match `PaymentRepositoryImpl`, `HTTPS` matches `HTTPServer`)
+pub fn camel_match(query: &str, name: &str) -> bool {
+    let q: Vec<char> = query.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let starts = hump_starts(&n);
+    let eq = |a: char, b: char| a.to_lowercase().eq(b.to_lowercase());
+    // failed[qi][ni]: q[qi..] cannot match with n[ni] next in the current hump
+    let mut failed = vec![vec![false; n.len() + 1]; q.len() + 1];
+
+    fn rest(
+        q: &[char], n: &[char], starts: &[bool], eq: &dyn Fn(char, char) -> bool,
+        failed: &mut [Vec<bool>], qi: usize, ni: usize,
+    ) -> bool {
+        if qi == q.len() {
+            return true;
+        }
+        if failed[qi][ni] {
+            return false;
+        }
+        let continues = ni < n.len() && !starts[ni] && eq(q[qi], n[ni]);
+        let found = (continues && rest(q, n, starts, eq, failed, qi + 1, ni + 1))
+            || (ni..n.len())
+                .filter(|&j| starts[j] && eq(q[qi], n[j]))
+                .any(|j| rest(q, n, starts, eq, failed, qi + 1, j + 1));
+        if !found {
+            failed[qi][ni] = true;
+        }
+        found
+    }
+
+    !q.is_empty() && rest(&q, &n, &starts, &eq, &mut failed, 0, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigrams() {
+        assert_eq!(trigrams("Repo"), vec!["epo", "rep"]);
+        assert_eq!(trigrams("ab"), Vec::<String>::new());
+        assert_eq!(trigrams("Платёж").len(), 4);
+    }
+
+    #[test]
+    fn test_substring_distance() {
+        assert_eq!(substring_distance("PaymntRepo", "PaymentRepository"), (1, 0));
+        assert_eq!(substring_distance("repo", "PaymentRepository"), (0, 7));
+        assert_eq!(substring_distance("Paymnet", "Payment"), (1, 0));
+        assert_eq!(substring_distance("UserServce", "LoadUserService").0, 1);
+        assert!(substring_distance("Invoice", "PaymentRepository").0 > max_distance("Invoice"));
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("PaymentManager", "PaymentsManager"), 1);
+        assert_eq!(edit_distance("PaymentManager", "PaymentMangr"), 2);
+        assert_eq!(edit_distance("PaymentManager", "paymentmanager"), 0);
+        assert_eq!(edit_distance("Paymnet", "Payment"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert!((normalized_distance("PaymentManager", "PaymentsManager") - 1.0 / 15.0).abs() < 1e-9);
+        assert_eq!(normalized_distance("abc", "xyz"), 1.0);
+        assert_eq!(normalized_distance("", ""), 0.0);
+    }
+
+    #[test]
+    fn test_hump_skeleton() {
+        assert_eq!(hump_skeleton("PaymentRepositoryImpl"), "PRI");
+        assert_eq!(hump_skeleton("HTTPServer"), "HTTPS");
+        assert_eq!(hump_skeleton("parse_http_request"), "PHR");
+        assert_eq!(hump_skeleton("Base64Encoder"), "B6E");
+        assert_eq!(hump_skeleton("ПлатёжныйСервис"), "ПС");
+        assert!(is_abbreviation("PRImpl"));
+        assert!(!is_abbreviation("payment"));
+        assert!(!is_abbreviation("P R"));
+    }
+
+    #[test]
+    fn test_camel_match() {
+        for query in ["PRImpl", "PRI", "PaReIm", "RImpl", "PaymentRepo", "pRI", "PImpl"] {
+            assert!(camel_match(query, "PaymentRepositoryImpl"), "{}", query);
+        }
+        assert!(camel_match("HTTPS", "HTTPServer"));
+        assert!(camel_match("pHR", "parse_http_request"));
+        assert!(camel_match("NPE", "NullPointerException"));
+        assert!(!camel_match("PRImpl", "PaymentImplRepository"));
+        assert!(!camel_match("PaymRImpl", "PaymentRepository"));
+        // Inside a hump only continuations count, not any later letter
+        assert!(!camel_match("Pmt", "PaymentRepositoryImpl"));
+    }
+}
@@ -0,0 +1,106 @@
+//! Git plumbing shared by symbol history and revision indexing
+//!
+//! Everything goes through the `git` binary: trees are listed with `git ls-tree` and blob
+//! contents read through one long-lived `git cat-file --batch` process.
+
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use anyhow::{Context, Result};
+
+/// Output of `git <args>` run in `root`
+pub fn git(root: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git").args(args).current_dir(root).output().context("Failed to run git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git {} failed: {}", args[0], stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// A commit `rev` names
+pub struct Commit {
+    pub sha: String,
+    /// Committer time, seconds since the epoch
+    pub time: i64,
+}
+
+/// Resolve `rev` (branch, tag, `origin/main`, sha) to a commit
+pub fn resolve_commit(root: &Path, rev: &str) -> Result<Commit> {
+    let out = git(root, &["log", "-1", "--format=%H %ct", &format!("{}^{{commit}}", rev), "--"])
+        .with_context(|| format!("Unknown git revision '{}'", rev))?;
+    let (sha, time) = out.trim().split_once(' ').with_context(|| format!("Unknown git revision '{}'", rev))?;
+    Ok(Commit { sha: sha.to_string(), time: time.parse()? })
+}
+
+/// A file of a tree
+pub struct TreeEntry {
+    /// Path relative to the directory the tree was listed from
+    pub path: String,
+    pub object: String,
+    pub size: u64,
+}
+
+/// Regular files of `rev` under `root`; symlinks and submodules are left out
+pub fn ls_tree(root: &Path, rev: &str) -> Result<Vec<TreeEntry>> {
+    let out = git(root, &["ls-tree", "-r", "-l", "-z", rev])?;
+    Ok(out
+        .split('\0')
+        .filter_map(|record| {
+            // `<mode> <type> <object> <size>\t<path>`
+            let (meta, path) = record.split_once('\t')?;
+            let fields: Vec<&str> = meta.split_whitespace().collect();
+            let [mode, "blob", object, size] = fields[..] else { return None };
+            if mode == "120000" {
+                return None;
+            }
+            Some(TreeEntry { path: path.to_string(), object: object.to_string(), size: size.parse().ok()? })
+        })
+        .collect())
+}
+
+/// Blob contents through one `git cat-file --batch` process
+pub struct BlobReader {
+    child: Child,
+    input: BufWriter<ChildStdin>,
+    output: BufReader<ChildStdout>,
+}
+
+impl BlobReader {
+    pub fn spawn(root: &Path) -> Result<BlobReader> {
+        let mut child = Command::new("git")
+            .args(["cat-file", "--batch"])
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .context("Failed to run git cat-file")?;
+        let input = BufWriter::new(child.stdin.take().context("git cat-file has no stdin")?);
+        let output = BufReader::new(child.stdout.take().context("git cat-file has no stdout")?);
+        Ok(BlobReader { child, input, output })
+    }
+
+    /// Bytes of the blob `object`; None when missing or not a file
+    pub fn read(&mut self, object: &str) -> Result<Option<Vec<u8>>> {
+        writeln!(self.input, "{}", object)?;
+        self.input.flush()?;
+        let mut header = String::new();
+        self.output.read_line(&mut header)?;
+        // `<oid> <type> <size>`, or `<object> missing`
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        let [_, kind, size] = fields[..] else { return Ok(None) };
+        let size: usize = size.parse().with_context(|| format!("Unexpected git cat-file header: {}", header.trim()))?;
+        let mut content = vec![0; size + 1];
+        self.output.read_exact(&mut content)?;
+        content.pop();
+        Ok((kind == "blob").then_some(content))
+    }
+}
+
+impl Drop for BlobReader {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
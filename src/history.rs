@@ -0,0 +1,480 @@
+//! Symbol history across git commits
+//!
+//! Optional and built on demand by `history --update`: the first-parent history of HEAD is
+//! walked oldest first and, for each commit, the supported files it touched are parsed before
+//! and after (the blobs `git show <commit>:<path>` prints, read through one
+//! `git cat-file --batch`). Declarations that appeared, disappeared, changed signature or moved
+//! to another file are recorded with the commit. Updates continue from the last indexed commit;
+//! when HEAD no longer descends from it (rebase, reset) the history starts over. It lives in its
+//! own database next to the index, so `rebuild` keeps it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use crate::db::{self, SymbolKind};
+use crate::git::{git, BlobReader};
+use crate::parsers;
+
+/// Blobs larger than this are skipped; they are usually generated
+const MAX_BLOB_SIZE: usize = 2 * 1024 * 1024;
+
+const HISTORY_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS commits (
+        id INTEGER PRIMARY KEY,
+        sha TEXT NOT NULL UNIQUE,
+        time INTEGER NOT NULL,
+        date TEXT NOT NULL,
+        author TEXT NOT NULL,
+        subject TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS symbol_changes (
+        id INTEGER PRIMARY KEY,
+        commit_id INTEGER NOT NULL,
+        change TEXT NOT NULL,
+        name TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        path TEXT NOT NULL,
+        previous_path TEXT,
+        signature TEXT,
+        line INTEGER,
+        FOREIGN KEY (commit_id) REFERENCES commits(id) ON DELETE CASCADE
+    );
+    CREATE INDEX IF NOT EXISTS idx_symbol_changes_name ON symbol_changes(name);
+    CREATE TABLE IF NOT EXISTS metadata (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+"#;
+
+/// What happened to a declaration in a commit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Removed,
+    /// Same name and kind in the same file, different signature
+    Changed,
+    /// Removed from one file and added to another in the same commit
+    Moved,
+}
+
+impl Change {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Change::Added => "added",
+            Change::Removed => "removed",
+            Change::Changed => "changed",
+            Change::Moved => "moved",
+        }
+    }
+}
+
+/// A declaration in one version of a file
+#[derive(Debug, Clone, PartialEq)]
+struct Declaration {
+    name: String,
+    kind: &'static str,
+    signature: String,
+    line: usize,
+}
+
+/// A change found by comparing two versions of a file
+#[derive(Debug, Clone, PartialEq)]
+struct SymbolChange {
+    change: Change,
+    name: String,
+    kind: &'static str,
+    path: String,
+    previous_path: Option<String>,
+    signature: String,
+    /// Line in the new version; in the old one for removals
+    line: usize,
+}
+
+/// A recorded change with its commit, for `history`
+#[derive(Debug, Serialize)]
+pub struct HistoryEvent {
+    pub sha: String,
+    pub date: String,
+    pub author: String,
+    pub subject: String,
+    pub change: String,
+    pub name: String,
+    pub kind: String,
+    pub path: String,
+    pub previous_path: Option<String>,
+    pub signature: Option<String>,
+    pub line: Option<i64>,
+}
+
+/// Indexed commit range: count, oldest and newest commit
+#[derive(Debug, Serialize)]
+pub struct HistoryRange {
+    pub commits: i64,
+    pub first: Option<String>,
+    pub last: Option<String>,
+}
+
+/// The history database next to the project's index (`index.db` → `index.history.db`)
+pub fn history_db_path(root: &Path) -> Result<PathBuf> {
+    Ok(db::get_db_path(root)?.with_extension("history.db"))
+}
+
+pub fn history_exists(root: &Path) -> bool {
+    history_db_path(root).map(|p| p.exists()).unwrap_or(false)
+}
+
+pub fn open_history_db(root: &Path) -> Result<Connection> {
+    let path = history_db_path(root)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let conn = Connection::open(&path)?;
+    conn.pragma_update(None, "foreign_keys", "ON")?;
+    let _: String = conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    conn.execute_batch(HISTORY_SCHEMA)?;
+    Ok(conn)
+}
+
+/// Index the commits of HEAD not indexed yet, at most the newest `max_commits` on a first
+/// run; `progress` gets (done, total) after each commit. Returns the number of commits indexed.
+pub fn update_history(
+    conn: &mut Connection,
+    root: &Path,
+    max_commits: Option<usize>,
+    progress: &dyn Fn(usize, usize),
+) -> Result<usize> {
+    let head = git(root, &["rev-parse", "HEAD"]).context("Symbol history needs a git repository with commits")?;
+    let head = head.trim().to_string();
+    let last: Option<String> = conn
+        .query_row("SELECT value FROM metadata WHERE key = 'head'", [], |row| row.get(0))
+        .optional()?;
+    if last.as_deref() == Some(head.as_str()) {
+        return Ok(0);
+    }
+    let range = match &last {
+        Some(last) if git(root, &["merge-base", "--is-ancestor", last, &head]).is_ok() => format!("{}..{}", last, head),
+        _ => {
+            conn.execute_batch("DELETE FROM symbol_changes; DELETE FROM commits; DELETE FROM metadata;")?;
+            head.clone()
+        }
+    };
+
+    let limit = max_commits.filter(|_| last.is_none()).map(|n| format!("--max-count={}", n));
+    let mut args = vec!["log", "--first-parent", "--reverse", "--format=%H%x1f%P%x1f%at%x1f%as%x1f%an%x1f%s"];
+    if let Some(limit) = &limit {
+        args.push(limit);
+    }
+    args.push(&range);
+    let log = git(root, &args)?;
+    let commits: Vec<&str> = log.lines().filter(|l| !l.is_empty()).collect();
+
+    let mut files = FileVersions { blobs: BlobReader::spawn(root)?, latest: HashMap::new() };
+    for (done, line) in commits.iter().enumerate() {
+        let fields: Vec<&str> = line.splitn(6, '\x1f').collect();
+        let [sha, parents, time, date, author, subject] = fields[..] else {
+            anyhow::bail!("Unexpected git log line: {}", line);
+        };
+        let parent = parents.split_whitespace().next();
+        let changes = commit_changes(root, &mut files, sha, parent)?;
+
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO commits (sha, time, date, author, subject) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![sha, time.parse::<i64>().unwrap_or(0), date, author, subject],
+        )?;
+        let commit_id = tx.last_insert_rowid();
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO symbol_changes (commit_id, change, name, kind, path, previous_path, signature, line)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for c in &changes {
+                stmt.execute(params![
+                    commit_id, c.change.as_str(), c.name, c.kind, c.path, c.previous_path, c.signature, c.line as i64
+                ])?;
+            }
+        }
+        tx.execute("INSERT OR REPLACE INTO metadata (key, value) VALUES ('head', ?1)", params![sha])?;
+        tx.commit()?;
+        progress(done + 1, commits.len());
+    }
+    Ok(commits.len())
+}
+
+/// Recorded changes of declarations named `name` (optionally of one kind), oldest first
+pub fn symbol_history(conn: &Connection, name: &str, kind: Option<&str>) -> Result<Vec<HistoryEvent>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.sha, c.date, c.author, c.subject, s.change, s.name, s.kind, s.path, s.previous_path, s.signature, s.line
+         FROM symbol_changes s
+         JOIN commits c ON s.commit_id = c.id
+         WHERE s.name = ?1 AND (?2 IS NULL OR s.kind = ?2)
+         ORDER BY c.id, s.path, s.line",
+    )?;
+    let events = stmt
+        .query_map(params![name, kind], |row| {
+            Ok(HistoryEvent {
+                sha: row.get(0)?,
+                date: row.get(1)?,
+                author: row.get(2)?,
+                subject: row.get(3)?,
+                change: row.get(4)?,
+                name: row.get(5)?,
+                kind: row.get(6)?,
+                path: row.get(7)?,
+                previous_path: row.get(8)?,
+                signature: row.get(9)?,
+                line: row.get(10)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(events)
+}
+
+pub fn history_range(conn: &Connection) -> Result<HistoryRange> {
+    Ok(conn.query_row(
+        "SELECT COUNT(*),
+                (SELECT sha FROM commits ORDER BY id LIMIT 1),
+                (SELECT sha FROM commits ORDER BY id DESC LIMIT 1)
+         FROM commits",
+        [],
+        |row| Ok(HistoryRange { commits: row.get(0)?, first: row.get(1)?, last: row.get(2)? }),
+    )?)
+}
+
+/// Declaration changes of one commit against its first parent (everything added for a root commit)
+fn commit_changes(root: &Path, files: &mut FileVersions, sha: &str, parent: Option<&str>) -> Result<Vec<SymbolChange>> {
+    // Raw output: `:<old mode> <new mode> <old blob> <new blob> <status>` NUL `<path>` NUL
+    let diff = match parent {
+        Some(parent) => git(root, &["diff-tree", "-r", "-z", "--no-commit-id", "--relative", parent, sha])?,
+        None => git(root, &["diff-tree", "-r", "-z", "--no-commit-id", "--relative", "--root", sha])?,
+    };
+    let mut changes = Vec::new();
+    let mut fields = diff.split('\0');
+    while let (Some(raw), Some(path)) = (fields.next(), fields.next()) {
+        let ext = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("");
+        let blobs: Vec<&str> = raw.split_whitespace().collect();
+        let [_, _, old_blob, new_blob, _] = blobs[..] else { continue };
+        if !parsers::is_supported_extension(ext) {
+            continue;
+        }
+        let old = files.take(path, old_blob, ext)?;
+        let new = files.take(path, new_blob, ext)?;
+        changes.extend(diff_declarations(path, &old, &new));
+        files.keep(path, new_blob, new);
+    }
+    Ok(detect_moves(changes))
+}
+
+/// Declarations of file versions by blob id. The newest version of each path is kept: the old
+/// side of a commit is usually the new side of the previous one, so most files parse once.
+struct FileVersions {
+    blobs: BlobReader,
+    latest: HashMap<String, (String, Vec<Declaration>)>,
+}
+
+impl FileVersions {
+    /// Declarations of `blob` at `path`; none for the all-zero id of a missing side
+    fn take(&mut self, path: &str, blob: &str, ext: &str) -> Result<Vec<Declaration>> {
+        if blob.bytes().all(|b| b == b'0') {
+            return Ok(vec![]);
+        }
+        if let Some((kept, _)) = self.latest.get(path) {
+            if kept == blob {
+                return Ok(self.latest.remove(path).map(|(_, d)| d).unwrap_or_default());
+            }
+        }
+        Ok(self
+            .blobs
+            .read(blob)?
+            .filter(|content| content.len() <= MAX_BLOB_SIZE)
+            .map(|content| declarations(&crate::encoding::decode(&content).0, ext))
+            .unwrap_or_default())
+    }
+
+    fn keep(&mut self, path: &str, blob: &str, declarations: Vec<Declaration>) {
+        self.latest.insert(path.to_string(), (blob.to_string(), declarations));
+    }
+}
+
+/// Declarations of one file version; imports and packages are not declarations
+fn declarations(content: &str, ext: &str) -> Vec<Declaration> {
+    let Some(Ok((symbols, _))) = parsers::parse_file_by_extension(content, ext) else { return vec![] };
+    symbols
+        .into_iter()
+        .filter(|s| !matches!(s.kind, SymbolKind::Import | SymbolKind::Package))
+        .map(|s| Declaration { name: s.name, kind: s.kind.as_str(), signature: s.signature, line: s.line })
+        .collect()
+}
+
+/// Changes between two versions of the file at `path`. Declarations are keyed by name and kind;
+/// overloads share a key and are told apart by signature, so a key that loses one signature and
+/// gains another counts as changed
+fn diff_declarations(path: &str, old: &[Declaration], new: &[Declaration]) -> Vec<SymbolChange> {
+    let by_key = |declarations: &[Declaration]| {
+        let mut keys: BTreeMap<(String, &'static str), BTreeMap<String, usize>> = BTreeMap::new();
+        for (i, d) in declarations.iter().enumerate() {
+            keys.entry((d.name.clone(), d.kind)).or_default().entry(d.signature.clone()).or_insert(i);
+        }
+        keys
+    };
+    let (old_keys, new_keys) = (by_key(old), by_key(new));
+    let none = BTreeMap::new();
+    let change = |change: Change, d: &Declaration| SymbolChange {
+        change,
+        name: d.name.clone(),
+        kind: d.kind,
+        path: path.to_string(),
+        previous_path: None,
+        signature: d.signature.clone(),
+        line: d.line,
+    };
+
+    let mut changes = Vec::new();
+    let mut keys: Vec<&(String, &'static str)> = old_keys.keys().chain(new_keys.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        let before = old_keys.get(key).unwrap_or(&none);
+        let after = new_keys.get(key).unwrap_or(&none);
+        let gone: Vec<&Declaration> = before.iter().filter(|(sig, _)| !after.contains_key(*sig)).map(|(_, i)| &old[*i]).collect();
+        let came: Vec<&Declaration> = after.iter().filter(|(sig, _)| !before.contains_key(*sig)).map(|(_, i)| &new[*i]).collect();
+        let paired = gone.len().min(came.len());
+        changes.extend(came[..paired].iter().map(|d| change(Change::Changed, d)));
+        changes.extend(gone[paired..].iter().map(|d| change(Change::Removed, d)));
+        changes.extend(came[paired..].iter().map(|d| change(Change::Added, d)));
+    }
+    changes
+}
+
+/// Pair removals and additions of the same name and kind in different files into moves
+fn detect_moves(changes: Vec<SymbolChange>) -> Vec<SymbolChange> {
+    let mut removed: HashMap<(String, &'static str), Vec<usize>> = HashMap::new();
+    for (i, c) in changes.iter().enumerate() {
+        if c.change == Change::Removed {
+            removed.entry((c.name.clone(), c.kind)).or_default().push(i);
+        }
+    }
+    let mut moved_from: HashMap<usize, String> = HashMap::new();
+    let mut consumed = vec![false; changes.len()];
+    for (i, c) in changes.iter().enumerate() {
+        if c.change != Change::Added {
+            continue;
+        }
+        let Some(candidates) = removed.get_mut(&(c.name.clone(), c.kind)) else { continue };
+        if let Some(pos) = candidates.iter().position(|&r| changes[r].path != c.path) {
+            let r = candidates.remove(pos);
+            consumed[r] = true;
+            moved_from.insert(i, changes[r].path.clone());
+        }
+    }
+    changes
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| !consumed[*i])
+        .map(|(i, mut c)| {
+            if let Some(from) = moved_from.remove(&i) {
+                c.change = Change::Moved;
+                c.previous_path = Some(from);
+            }
+            c
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn declaration(name: &str, kind: &'static str, signature: &str, line: usize) -> Declaration {
+        Declaration { name: name.to_string(), kind, signature: signature.to_string(), line }
+    }
+
+    fn summary(changes: &[SymbolChange]) -> Vec<(&str, &str, &str, Option<&str>)> {
+        changes
+            .iter()
+            .map(|c| (c.change.as_str(), c.name.as_str(), c.path.as_str(), c.previous_path.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_declarations() {
+        let old = vec![
+            declaration("Cart", "class", "class Cart", 1),
+            declaration("total", "function", "fun total(): Int", 2),
+            declaration("clear", "function", "fun clear()", 3),
+            declaration("add", "function", "fun add(item: Item)", 4),
+        ];
+        let new = vec![
+            declaration("Cart", "class", "class Cart", 1),
+            declaration("total", "function", "fun total(currency: Currency): Money", 2),
+            declaration("add", "function", "fun add(item: Item)", 3),
+            declaration("add", "function", "fun add(item: Item, count: Int)", 4),
+        ];
+        let changes = diff_declarations("Cart.kt", &old, &new);
+        assert_eq!(
+            summary(&changes),
+            vec![
+                ("added", "add", "Cart.kt", None),
+                ("removed", "clear", "Cart.kt", None),
+                ("changed", "total", "Cart.kt", None),
+            ]
+        );
+        assert_eq!(changes[2].signature, "fun total(currency: Currency): Money");
+    }
+
+    #[test]
+    fn test_moves_pair_across_files() {
+        let mut changes = diff_declarations("old/Util.kt", &[declaration("format", "function", "fun format()", 5)], &[]);
+        changes.extend(diff_declarations("new/Format.kt", &[], &[declaration("format", "function", "fun format()", 1)]));
+        changes.extend(diff_declarations("new/Format.kt", &[], &[declaration("parse", "function", "fun parse()", 9)]));
+        assert_eq!(
+            summary(&detect_moves(changes)),
+            vec![("moved", "format", "new/Format.kt", Some("old/Util.kt")), ("added", "parse", "new/Format.kt", None)]
+        );
+    }
+
+    #[test]
+    fn test_update_history_from_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let run = |args: &[&str]| {
+            let status = Command::new("git").args(args).current_dir(root).output().unwrap().status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        let commit = |message: &str| {
+            run(&["add", "-A"]);
+            run(&["-c", "user.name=Dev", "-c", "user.email=dev@example.com", "commit", "-qm", message]);
+        };
+        run(&["init", "-q"]);
+        std::fs::write(root.join("Cart.py"), "def total(items):\n    return 0\n").unwrap();
+        commit("Add cart");
+        std::fs::write(root.join("Cart.py"), "def total(items, tax):\n    return 0\n\ndef clear():\n    pass\n").unwrap();
+        commit("Add tax");
+        std::fs::write(root.join("Cart.py"), "def total(items, tax):\n    return 0\n").unwrap();
+        commit("Drop clear");
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(HISTORY_SCHEMA).unwrap();
+        assert_eq!(update_history(&mut conn, root, None, &|_, _| {}).unwrap(), 3);
+        assert_eq!(update_history(&mut conn, root, None, &|_, _| {}).unwrap(), 0);
+
+        let changes = |name: &str| -> Vec<(String, String)> {
+            symbol_history(&conn, name, None).unwrap().into_iter().map(|e| (e.change, e.subject)).collect()
+        };
+        assert_eq!(
+            changes("total"),
+            vec![("added".to_string(), "Add cart".to_string()), ("changed".to_string(), "Add tax".to_string())]
+        );
+        assert_eq!(
+            changes("clear"),
+            vec![("added".to_string(), "Add tax".to_string()), ("removed".to_string(), "Drop clear".to_string())]
+        );
+        assert_eq!(history_range(&conn).unwrap().commits, 3);
+    }
+}
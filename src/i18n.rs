@@ -0,0 +1,171 @@
+//! Localized CLI messages
+//!
+//! Messages are Fluent files in `locales/<lang>/ast-index.ftl`, compiled into the
+//! binary. The locale comes from `locale` in `.ast-index.toml`, otherwise from
+//! `LC_ALL` / `LC_MESSAGES` / `LANG`. Unknown locales use English, and a message
+//! missing from a translation falls back to the English text.
+//!
+//! ```text
+//! println!("{}", t!("index-not-found"));
+//! println!("{}", t!("indexed-files", count = 42));
+//! ```
+
+use std::sync::{LazyLock, OnceLock};
+
+use anyhow::Result;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+pub use fluent_bundle::FluentValue;
+use unic_langid::LanguageIdentifier;
+
+/// Supported UI languages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Ru,
+}
+
+impl Locale {
+    pub const ALL: &'static [Locale] = &[Locale::En, Locale::Ru];
+
+    /// Parse a locale tag: `ru`, `ru-RU`, `ru_RU.UTF-8`, `en_US`, ...
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let lang = tag.split(['_', '-', '.', '@']).next()?.to_ascii_lowercase();
+        match lang.as_str() {
+            "en" => Some(Locale::En),
+            "ru" => Some(Locale::Ru),
+            _ => None,
+        }
+    }
+
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Ru => "ru",
+        }
+    }
+
+    fn source(&self) -> &'static str {
+        match self {
+            Locale::En => include_str!("../locales/en/ast-index.ftl"),
+            Locale::Ru => include_str!("../locales/ru/ast-index.ftl"),
+        }
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+static BUNDLES: LazyLock<Vec<(Locale, FluentBundle<FluentResource>)>> = LazyLock::new(|| {
+    Locale::ALL.iter().map(|&locale| (locale, build_bundle(locale))).collect()
+});
+
+fn build_bundle(locale: Locale) -> FluentBundle<FluentResource> {
+    let langid: LanguageIdentifier = locale.tag().parse().expect("valid language tag");
+    let resource = FluentResource::try_new(locale.source().to_string())
+        .unwrap_or_else(|(_, errors)| panic!("invalid {} messages: {:?}", locale.tag(), errors));
+    let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+    // Unicode isolation marks around arguments show up as garbage in terminals
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .unwrap_or_else(|errors| panic!("duplicate {} messages: {:?}", locale.tag(), errors));
+    bundle
+}
+
+/// Choose the UI language for this process. `configured` (from `.ast-index.toml`)
+/// wins over the environment. Later calls are ignored.
+pub fn set_locale(configured: Option<&str>) -> Result<()> {
+    let locale = match configured {
+        Some(tag) => Locale::from_tag(tag)
+            .ok_or_else(|| anyhow::anyhow!("Unknown locale '{}' (supported: en, ru)", tag))?,
+        None => locale_from_env(),
+    };
+    let _ = LOCALE.set(locale);
+    Ok(())
+}
+
+/// Current UI language (environment-derived if `set_locale` was never called)
+pub fn locale() -> Locale {
+    *LOCALE.get_or_init(locale_from_env)
+}
+
+fn locale_from_env() -> Locale {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| Locale::from_tag(&value))
+        .unwrap_or(Locale::En)
+}
+
+/// Format message `id` in the current locale. Prefer the `t!` macro.
+pub fn tr(id: &str, args: &[(&str, FluentValue)]) -> String {
+    tr_in(locale(), id, args)
+}
+
+/// Format message `id` in `locale`, falling back to English, then to the id itself
+pub fn tr_in(locale: Locale, id: &str, args: &[(&str, FluentValue)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    [locale, Locale::En]
+        .iter()
+        .find_map(|l| {
+            let (_, bundle) = BUNDLES.iter().find(|(bl, _)| bl == l)?;
+            let pattern = bundle.get_message(id)?.value()?;
+            let mut errors = vec![];
+            Some(bundle.format_pattern(pattern, Some(&fluent_args), &mut errors).into_owned())
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Localized message: `t!("index-not-found")`, `t!("indexed-files", count = n)`
+#[macro_export]
+macro_rules! t {
+    ($id:literal) => {
+        $crate::i18n::tr($id, &[])
+    };
+    ($id:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::tr($id, &[$((stringify!($name), $crate::i18n::FluentValue::from($value))),+])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_from_tag() {
+        assert_eq!(Locale::from_tag("ru_RU.UTF-8"), Some(Locale::Ru));
+        assert_eq!(Locale::from_tag("en-US"), Some(Locale::En));
+        assert_eq!(Locale::from_tag("RU"), Some(Locale::Ru));
+        assert_eq!(Locale::from_tag("C"), None);
+        assert_eq!(Locale::from_tag("de_DE"), None);
+    }
+
+    #[test]
+    fn test_translations_cover_english() {
+        let ids: Vec<&str> = Locale::En
+            .source()
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter_map(|line| line.split_once('=').map(|(id, _)| id.trim()))
+            .collect();
+        assert!(ids.contains(&"index-not-found"));
+        for (locale, bundle) in BUNDLES.iter() {
+            for id in &ids {
+                assert!(bundle.has_message(id), "{} is missing '{}'", locale.tag(), id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_with_plurals_and_fallback() {
+        assert_eq!(tr_in(Locale::En, "indexed-files", &[("count", 1.into())]), "Indexed 1 file");
+        assert_eq!(tr_in(Locale::Ru, "indexed-files", &[("count", 1.into())]), "Проиндексирован 1 файл");
+        assert_eq!(tr_in(Locale::Ru, "indexed-files", &[("count", 3.into())]), "Проиндексировано 3 файла");
+        assert_eq!(tr_in(Locale::Ru, "indexed-files", &[("count", 25.into())]), "Проиндексировано 25 файлов");
+        assert_eq!(tr_in(Locale::Ru, "no-such-message", &[]), "no-such-message");
+    }
+}
@@ -1,14 +1,17 @@
 use anyhow::Result;
 use rayon::prelude::*;
 use regex::Regex;
-use std::sync::LazyLock;
-use rusqlite::Connection;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, OnceLock};
+use rusqlite::{Connection, OptionalExtension};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 use std::time::SystemTime;
 
-use crate::parsers::{self, ParsedRef, ParsedSymbol};
+use crate::config::{IndexConfig, RefsLayout};
+use crate::db::SkipReason;
+use crate::parsers::{self, signature, ParsedRef, ParsedSymbol};
+use crate::progress::ParseProgress;
 
 /// Sorted module lookup for efficient longest-prefix matching.
 /// Entries sorted by path length descending so the longest (most specific) match is found first.
@@ -233,6 +236,92 @@ struct ParsedFile {
     size: i64,
     symbols: Vec<ParsedSymbol>,
     refs: Vec<ParsedRef>,
+    imports: Vec<parsers::imports::ParsedImport>,
+    /// Calls made by the file's functions (`caller` indexes `symbols`)
+    calls: Vec<parsers::calls::ParsedCall>,
+    /// Types provided to or injected from a DI container
+    di: Vec<parsers::di::ParsedBinding>,
+    /// Complexity of each symbol, aligned with `symbols`; empty when nothing was parsed
+    complexity: Vec<Option<u32>>,
+    /// Detected source encoding; None if the file was not read
+    encoding: Option<&'static str>,
+    /// Why the file was not parsed
+    skipped: Option<crate::db::SkipReason>,
+    /// Non-blank (line, text) pairs for `[index] content_fts`; empty when it is off
+    lines: Vec<(usize, String)>,
+}
+
+impl ParsedFile {
+    /// A file recorded without its contents
+    fn empty(rel_path: String, mtime: i64, size: i64, encoding: Option<&'static str>, skipped: Option<crate::db::SkipReason>) -> Self {
+        ParsedFile {
+            rel_path,
+            mtime,
+            size,
+            symbols: vec![],
+            refs: vec![],
+            imports: vec![],
+            calls: vec![],
+            di: vec![],
+            complexity: vec![],
+            encoding,
+            skipped,
+            lines: vec![],
+        }
+    }
+}
+
+/// Files at least this large are checked for minified content
+const MINIFIED_MIN_SIZE: usize = 16 * 1024;
+/// Mean line length above which a file counts as minified
+const MINIFIED_LINE_LENGTH: usize = 1000;
+/// Longest line the streaming reader buffers; a file with a longer one counts as minified
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+/// Source bytes from `input`, read one line at a time through a fixed-size buffer. Reading
+/// stops at the first line longer than `MAX_LINE_LENGTH`, so a bundle is never loaded whole;
+/// the flag tells whether the whole input was read
+fn read_lines_bounded(input: impl std::io::Read, size_hint: usize) -> Result<(Vec<u8>, bool)> {
+    use std::io::{BufRead, BufReader, Read};
+    let mut reader = BufReader::with_capacity(64 * 1024, input);
+    let mut bytes = Vec::with_capacity(size_hint);
+    loop {
+        let line = (&mut reader).take(MAX_LINE_LENGTH as u64 + 1).read_until(b'\n', &mut bytes)?;
+        if line == 0 {
+            return Ok((bytes, true));
+        }
+        if line > MAX_LINE_LENGTH && bytes.last() != Some(&b'\n') {
+            return Ok((bytes, false));
+        }
+    }
+}
+
+/// Source bytes being parsed across threads, against `[index] parse_memory_limit`
+struct Watermark {
+    in_flight: AtomicU64,
+}
+
+static PARSE_WATERMARK: Watermark = Watermark { in_flight: AtomicU64::new(0) };
+
+/// Bytes counted in a `Watermark` until dropped
+struct WatermarkGuard<'a> {
+    watermark: &'a Watermark,
+    bytes: u64,
+}
+
+impl Watermark {
+    /// Count `bytes` as being parsed; the flag tells whether that puts the total over `limit`,
+    /// in which case the file is parsed for symbols only rather than waiting for room
+    fn hold(&self, bytes: u64, limit: u64) -> (WatermarkGuard<'_>, bool) {
+        let total = self.in_flight.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        (WatermarkGuard { watermark: self, bytes }, total > limit)
+    }
+}
+
+impl Drop for WatermarkGuard<'_> {
+    fn drop(&mut self) {
+        self.watermark.in_flight.fetch_sub(self.bytes, Ordering::Relaxed);
+    }
 }
 
 /// Parse a single file without DB access (thread-safe)
@@ -249,36 +338,81 @@ fn parse_file(root: &Path, file_path: &Path) -> Result<ParsedFile> {
         .unwrap_or(file_path)
         .to_string_lossy()
         .to_string();
+    parse_source(rel_path, Some(file_path), mtime, size, || Ok(fs::File::open(file_path)?))
+}
 
-    // Skip files larger than 1 MB (likely generated/minified)
-    if size > 1_000_000 {
-        return Ok(ParsedFile {
-            rel_path,
-            mtime,
-            size,
-            symbols: vec![],
-            refs: vec![],
-        });
-    }
-
-    let content = fs::read_to_string(file_path)?;
-
-    // Detect file type by extension
-    let ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
-    let file_type = match parsers::FileType::from_extension(ext) {
-        Some(ft) => ft,
-        None => {
-            return Ok(ParsedFile {
-                rel_path,
-                mtime,
-                size,
-                symbols: vec![],
-                refs: vec![],
-            });
-        }
+/// Parse the file contents that `open` supplies unless the file is too large to parse.
+/// `disk_path` is where the file lives, if anywhere; Dart `part of` files look up their owner
+/// next to it
+fn parse_source<R: std::io::Read>(
+    rel_path: String,
+    disk_path: Option<&Path>,
+    mtime: i64,
+    size: i64,
+    open: impl FnOnce() -> Result<R>,
+) -> Result<ParsedFile> {
+    // Large, binary and minified files (bundles, generated code) are recorded but not parsed
+    let config = index_config();
+    if size as u64 > config.max_file_size {
+        return Ok(ParsedFile::empty(rel_path, mtime, size, None, Some(SkipReason::TooLarge)));
+    }
+    let (_held, over_watermark) = PARSE_WATERMARK.hold(size as u64, config.parse_memory_limit);
+    let (bytes, complete) = read_lines_bounded(open()?, size as usize)?;
+    if crate::encoding::is_binary(&bytes) {
+        return Ok(ParsedFile::empty(rel_path, mtime, size, None, Some(SkipReason::Binary)));
+    }
+    if !complete {
+        return Ok(ParsedFile::empty(rel_path, mtime, size, None, Some(SkipReason::Minified)));
+    }
+
+    // Legacy sources may be UTF-16 or a Windows code page: transcode to UTF-8 first
+    let (content, encoding) = crate::encoding::decode(&bytes);
+    drop(bytes);
+    if content.len() >= MINIFIED_MIN_SIZE && content.len() / content.lines().count().max(1) > MINIFIED_LINE_LENGTH {
+        return Ok(ParsedFile::empty(rel_path, mtime, size, Some(encoding), Some(SkipReason::Minified)));
+    }
+
+    let parse_start = crate::progress::timings_enabled().then(std::time::Instant::now);
+    // Detect parser by extension (built-in or custom from .ast-index.toml)
+    let ext = Path::new(&rel_path).extension().and_then(|e| e.to_str()).unwrap_or("");
+    // References of huge generated files, or of any file once parsing is over the memory
+    // watermark, would take far more memory than their symbols
+    let parsed = if size as u64 > config.refs_max_file_size || over_watermark {
+        parsers::symbols_only(|| parsers::parse_file_by_extension(&content, ext))
+    } else {
+        parsers::parse_file_by_extension(&content, ext)
+    };
+    let (mut symbols, refs) = match parsed {
+        Some(result) => result?,
+        None => return Ok(ParsedFile::empty(rel_path, mtime, size, Some(encoding), None)),
     };
 
-    let (symbols, refs) = parsers::parse_file_symbols(&content, file_type)?;
+    if parsers::FileType::from_extension(ext) == Some(parsers::FileType::Vue) {
+        parsers::typescript::assign_vue_component(&mut symbols, &content, &rel_path);
+    }
+    // Dart `part of 'owner.dart'` files belong to the owner's library
+    let namespace = match parsers::FileType::from_extension(ext) {
+        Some(parsers::FileType::Dart) => disk_path.and_then(|path| parsers::qualify::dart_part_library(&content, path)),
+        _ => None,
+    };
+    parsers::qualify::assign_qualified_names_in(&mut symbols, &content, ext, &rel_path, namespace);
+    parsers::docs::assign_docs(&mut symbols, &content, ext);
+    parsers::annotations::assign_annotations(&mut symbols, &content, ext);
+    parsers::visibility::assign_visibility(&mut symbols, &content, ext);
+    parsers::signature::normalize_signatures(&mut symbols, &content, ext);
+    parsers::span::assign_spans(&mut symbols, &content, ext);
+    parsers::conditions::assign_conditions(&mut symbols, &content, ext);
+    parsers::generated::add_generated_symbols(&mut symbols, &content, ext);
+    let imports = parsers::imports::extract_imports(&content, ext, &rel_path);
+    // Call edges are references too: `--no-refs` skips them, and `callers` answers with grep
+    let calls = if parsers::refs_enabled() { parsers::calls::extract_calls(&content, &symbols, ext) } else { vec![] };
+    let di = parsers::di::extract_bindings(&content, &symbols, ext);
+    let complexity = parsers::complexity::function_complexity(&content, &symbols, ext);
+    let lines = if config.content_fts { content_lines(&content) } else { vec![] };
+    if let Some(start) = parse_start {
+        let language = parsers::language_of(ext).unwrap_or("other");
+        crate::progress::record_parse(language, &rel_path, start.elapsed());
+    }
 
     Ok(ParsedFile {
         rel_path,
@@ -286,9 +420,30 @@ fn parse_file(root: &Path, file_path: &Path) -> Result<ParsedFile> {
         size,
         symbols,
         refs,
+        imports,
+        calls,
+        di,
+        complexity,
+        encoding: Some(encoding),
+        skipped: None,
+        lines,
     })
 }
 
+/// Lines longer than this many bytes are left out of `content_fts`
+const CONTENT_MAX_LINE_LENGTH: usize = 1000;
+
+/// Trimmed non-blank lines of `content` for `content_fts`, numbered from 1
+fn content_lines(content: &str) -> Vec<(usize, String)> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(line, text)| !text.is_empty() && text.len() <= CONTENT_MAX_LINE_LENGTH && crate::db::content_line_fits(*line))
+        .map(|(line, text)| (line, text.to_string()))
+        .collect()
+}
+
 /// Directories to always exclude from indexing (regardless of .gitignore)
 const EXCLUDED_DIRS: &[&str] = &[
     "node_modules",
@@ -408,6 +563,28 @@ pub fn is_excluded_dir(entry: &ignore::DirEntry) -> bool {
     }
 }
 
+/// `[index] include` and `exclude` globs, applied to source files by every way of indexing:
+/// rebuild, `rebuild --rev`, update and watch
+pub struct PathFilter {
+    include: crate::db::PathGlobs,
+    exclude: crate::db::PathGlobs,
+}
+
+impl PathFilter {
+    pub fn new(config: &IndexConfig) -> Self {
+        PathFilter {
+            include: crate::db::PathGlobs::new(&config.include),
+            exclude: crate::db::PathGlobs::new(&config.exclude),
+        }
+    }
+
+    /// Whether the source file at `rel_path` (relative to the project root) is indexed
+    pub fn allows(&self, rel_path: &str) -> bool {
+        let rel_path = rel_path.replace('\\', "/");
+        (self.include.is_empty() || self.include.matches(&rel_path)) && !self.exclude.matches(&rel_path)
+    }
+}
+
 /// Module-related file names to collect during directory walk
 fn is_module_file(name: &str) -> bool {
     name == "build.gradle" || name == "build.gradle.kts" || name == "Package.swift" || name.ends_with(".pm")
@@ -436,14 +613,14 @@ pub fn index_directory(conn: &mut Connection, root: &Path, progress: bool, no_ig
 /// When walk_dir is a subdirectory of root, only indexes that subdirectory.
 pub fn index_directory_scoped(conn: &mut Connection, root: &Path, walk_dir: &Path, progress: bool, no_ignore: bool) -> Result<WalkResult> {
     use ignore::WalkBuilder;
-    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::time::Instant;
 
-    let verbose = std::env::var("AST_INDEX_VERBOSE").is_ok();
+    // An index built without references stays without them
+    parsers::set_refs_enabled(crate::db::refs_indexed(conn));
+    // ...and one built with `--languages` stays within those languages
+    parsers::restrict_to_indexed_languages(&crate::db::indexed_languages(conn))?;
 
-    // Small chunks: parse CHUNK_SIZE files in parallel → write to DB → free memory → next chunk
-    // Peak memory: ~CHUNK_SIZE × (file content + ParsedFile), then freed each iteration
-    const CHUNK_SIZE: usize = 500;
+    let verbose = std::env::var("AST_INDEX_VERBOSE").is_ok();
 
     // Detect project type
     let project_type = detect_project_type(walk_dir);
@@ -487,6 +664,7 @@ pub fn index_directory_scoped(conn: &mut Connection, root: &Path, walk_dir: &Pat
     let walk_start = Instant::now();
     let walker = builder.build();
 
+    let filter = PathFilter::new(index_config());
     let mut files: Vec<PathBuf> = Vec::new();
     let mut module_files: Vec<PathBuf> = Vec::new();
     let mut storyboard_files: Vec<PathBuf> = Vec::new();
@@ -509,7 +687,9 @@ pub fn index_directory_scoped(conn: &mut Connection, root: &Path, walk_dir: &Pat
         }
         if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
             // Collect parseable source files
-            if parsers::is_supported_extension(ext) {
+            if parsers::is_supported_extension(ext)
+                && filter.allows(&path.strip_prefix(root).unwrap_or(path).to_string_lossy())
+            {
                 files.push(path.to_path_buf());
             }
             // Collect storyboard/xib files (iOS)
@@ -543,30 +723,15 @@ pub fn index_directory_scoped(conn: &mut Connection, root: &Path, walk_dir: &Pat
     }
 
     let mut total_count = 0;
-    let parsed_global = Arc::new(AtomicUsize::new(0));
+    // Verbose chunk logging and the bar would interleave
+    let bar = ParseProgress::new(total_files, "files", 2000, progress && !verbose);
 
-    // Thread count: --threads flag > AST_INDEX_THREADS env > CPU cores (max 8 for local, higher for network FS)
-    let num_threads = std::env::var("AST_INDEX_THREADS")
-        .ok()
-        .and_then(|s| s.parse::<usize>().ok())
-        .filter(|&n| n > 0)
-        .unwrap_or_else(|| {
-            std::thread::available_parallelism()
-                .map(|n| n.get().min(8))
-                .unwrap_or(4)
-        });
-    if verbose { eprintln!("[verbose] using {} threads for parsing", num_threads); }
-    let pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(num_threads)
-        .build()
-        .map_err(|e| anyhow::anyhow!("Failed to build thread pool: {}", e))?;
+    let pool = parse_thread_pool(verbose)?;
 
     let root_buf = root.to_path_buf();
     let total_chunks = (files.len() + CHUNK_SIZE - 1) / CHUNK_SIZE;
     for (chunk_idx, chunk) in files.chunks(CHUNK_SIZE).enumerate() {
         let root_clone = root_buf.clone();
-        let counter = parsed_global.clone();
-        let total = total_files;
 
         if verbose { eprintln!("[verbose] chunk {}/{}: parsing {} files...", chunk_idx + 1, total_chunks, chunk.len()); }
         let chunk_start = Instant::now();
@@ -577,10 +742,7 @@ pub fn index_directory_scoped(conn: &mut Connection, root: &Path, walk_dir: &Pat
                 .par_iter()
                 .filter_map(|path| {
                     let result = parse_file(&root_clone, path).ok();
-                    let c = counter.fetch_add(1, Ordering::Relaxed) + 1;
-                    if progress && c % 2000 == 0 {
-                        eprintln!("Parsed {} / {} files...", c, total);
-                    }
+                    bar.tick(&path.strip_prefix(&root_clone).unwrap_or(path).to_string_lossy());
                     result
                 })
                 .collect()
@@ -594,11 +756,10 @@ pub fn index_directory_scoped(conn: &mut Connection, root: &Path, walk_dir: &Pat
 
         if verbose { eprintln!("[verbose] chunk {}/{}: written in {:?}", chunk_idx + 1, total_chunks, write_start.elapsed()); }
 
-        if progress {
-            eprintln!("Written {} / {} files to DB...", total_count, total_files);
-        }
+        bar.note(&format!("Written {} / {} files to DB...", total_count, total_files));
     }
 
+    bar.finish();
     if progress {
         eprintln!("Written {} / {} files to DB", total_count, total_files);
     }
@@ -613,54 +774,322 @@ pub fn index_directory_scoped(conn: &mut Connection, root: &Path, walk_dir: &Pat
     })
 }
 
-/// Write a batch of parsed files to DB in a single transaction
+/// Small chunks: parse CHUNK_SIZE files in parallel → write to DB → free memory → next chunk
+/// Peak memory: ~CHUNK_SIZE × (file content + ParsedFile), then freed each iteration
+const CHUNK_SIZE: usize = 500;
+
+/// Thread pool for parsing
+fn parse_thread_pool(verbose: bool) -> Result<rayon::ThreadPool> {
+    // Thread count: --threads flag > AST_INDEX_THREADS env > CPU cores (max 8 for local, higher for network FS)
+    let num_threads = std::env::var("AST_INDEX_THREADS")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get().min(8))
+                .unwrap_or(4)
+        });
+    if verbose { eprintln!("[verbose] using {} threads for parsing", num_threads); }
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to build thread pool: {}", e))
+}
+
+/// Index the supported files of git revision `rev` under `root` straight from the object store,
+/// without reading or changing the worktree. Every file gets the commit time as its mtime;
+/// hidden and `EXCLUDED_DIRS` paths and those `PathFilter` rejects are left out as in a walk.
+/// Returns the commit and file count
+pub fn index_git_revision(conn: &mut Connection, root: &Path, rev: &str, progress: bool) -> Result<(crate::git::Commit, usize)> {
+    parsers::set_refs_enabled(crate::db::refs_indexed(conn));
+    let verbose = std::env::var("AST_INDEX_VERBOSE").is_ok();
+
+    let commit = crate::git::resolve_commit(root, rev)?;
+    let filter = PathFilter::new(index_config());
+    let files: Vec<crate::git::TreeEntry> = crate::git::ls_tree(root, &commit.sha)?
+        .into_iter()
+        .filter(|entry| {
+            let path = Path::new(&entry.path);
+            let mut dirs = path.parent().into_iter().flat_map(|dir| dir.iter()).filter_map(|c| c.to_str());
+            let hidden_or_excluded = dirs.any(|dir| dir.starts_with('.') || EXCLUDED_DIRS.contains(&dir));
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+            !hidden_or_excluded && !name.starts_with('.') && parsers::is_supported_extension(ext) && filter.allows(&entry.path)
+        })
+        .collect();
+    if progress {
+        eprintln!("Found {} files to parse at {}...", files.len(), &commit.sha[..12.min(commit.sha.len())]);
+    }
+
+    let pool = parse_thread_pool(verbose)?;
+    let mut blobs = crate::git::BlobReader::spawn(root)?;
+    let max_file_size = index_config().max_file_size;
+    let mut total_count = 0;
+    let bar = ParseProgress::new(files.len(), "files", 2000, progress && !verbose);
+    for chunk in files.chunks(CHUNK_SIZE) {
+        // One cat-file process serves the blobs in order; parsing then runs in parallel
+        let contents = chunk
+            .iter()
+            .map(|entry| if entry.size > max_file_size { Ok(None) } else { blobs.read(&entry.object) })
+            .collect::<Result<Vec<_>>>()?;
+        let parsed_files: Vec<ParsedFile> = pool.install(|| {
+            chunk
+                .par_iter()
+                .zip(contents)
+                .filter_map(|(entry, content)| {
+                    let open = || content.map(std::io::Cursor::new).ok_or_else(|| anyhow::anyhow!("Blob {} not found", entry.object));
+                    let result = parse_source(entry.path.clone(), None, commit.time, entry.size as i64, open).ok();
+                    bar.tick(&entry.path);
+                    result
+                })
+                .collect()
+        });
+        write_batch_to_db(conn, parsed_files, &mut total_count)?;
+        bar.note(&format!("Written {} / {} files to DB...", total_count, files.len()));
+    }
+    bar.finish();
+    Ok((commit, total_count))
+}
+
+/// Columns of a `refs` row written by `write_batch_to_db`
+const REF_COLUMNS: usize = 8;
+/// Lines kept for an aggregated refs row
+const SAMPLE_LINES: usize = 10;
+
+static INDEX_CONFIG: OnceLock<IndexConfig> = OnceLock::new();
+
+/// How this process writes the index (`[index]` in `.ast-index.toml`); writes clamp the batch
+/// size to what the connection's SQLite can bind. Later calls are ignored.
+pub fn set_index_config(config: &IndexConfig) {
+    let insert_batch_size = config.insert_batch_size.max(1);
+    let _ = INDEX_CONFIG.set(IndexConfig { insert_batch_size, ..config.clone() });
+}
+
+/// Most refs rows one statement can insert on `conn`: bundled SQLite binds up to 32766
+/// parameters, a system library may allow as few as 999
+fn max_insert_batch_size(conn: &Connection) -> usize {
+    let variables = conn.limit(rusqlite::limits::Limit::SQLITE_LIMIT_VARIABLE_NUMBER);
+    (variables.max(0) as usize / REF_COLUMNS).max(1)
+}
+
+pub fn index_config() -> &'static IndexConfig {
+    INDEX_CONFIG.get_or_init(IndexConfig::default)
+}
+
+/// A file's refs as rows: one per occurrence, or with `RefsLayout::Aggregated` one per name, kind
+/// and arity, at its first line, with the number of occurrences and their first lines
+fn ref_rows(mut refs: Vec<ParsedRef>, layout: RefsLayout) -> Vec<(ParsedRef, Option<i64>, Option<String>)> {
+    use std::collections::hash_map::{Entry, HashMap};
+
+    if layout != RefsLayout::Aggregated {
+        return refs.into_iter().map(|r| (r, None, None)).collect();
+    }
+    refs.sort_by_key(|r| r.line);
+    // (first occurrence, its lines, occurrences) in order of first occurrence
+    let mut rows: Vec<(ParsedRef, Vec<usize>, i64)> = Vec::new();
+    let mut by_key: HashMap<(String, &'static str, Option<usize>), usize> = HashMap::new();
+    for r in refs {
+        match by_key.entry((r.name.clone(), r.kind.as_str(), r.arity)) {
+            Entry::Occupied(entry) => {
+                let (_, lines, count) = &mut rows[*entry.get()];
+                if lines.len() < SAMPLE_LINES {
+                    lines.push(r.line);
+                }
+                *count += 1;
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(rows.len());
+                let line = r.line;
+                rows.push((r, vec![line], 1));
+            }
+        }
+    }
+    rows.into_iter()
+        .map(|(r, lines, count)| {
+            let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+            (r, Some(count), Some(lines.join(",")))
+        })
+        .collect()
+}
+
+/// Insert buffered refs rows (`REF_COLUMNS` values each) with one multi-row statement, into
+/// `refs_data` with interned names or else through the `refs` view of a sharded index
+fn insert_refs(tx: &rusqlite::Transaction, values: &[rusqlite::types::Value], interned: bool) -> Result<()> {
+    let rows = values.len() / REF_COLUMNS;
+    if rows == 0 {
+        return Ok(());
+    }
+    let (table, name) = if interned { ("refs_data", "name_id") } else { ("refs", "name") };
+    let sql = format!(
+        "INSERT INTO {} (file_id, {}, line, context, ref_kind, arity, count, sample_lines) VALUES {}",
+        table,
+        name,
+        vec!["(?, ?, ?, ?, ?, ?, ?, ?)"; rows].join(", ")
+    );
+    tx.prepare_cached(&sql)?.execute(rusqlite::params_from_iter(values))?;
+    Ok(())
+}
+
+/// Id of `name` in `names`, remembered in `cache` for the rest of the batch
+fn name_id(tx: &rusqlite::Transaction, cache: &mut std::collections::HashMap<String, i64>, name: &str) -> Result<i64> {
+    if let Some(&id) = cache.get(name) {
+        return Ok(id);
+    }
+    let id = crate::db::intern_name(tx, name)?;
+    cache.insert(name.to_string(), id);
+    Ok(id)
+}
+
+/// Write a batch of parsed files to DB in a single transaction; refs are written
+/// `insert_batch_size` rows per statement, in the configured layout
 fn write_batch_to_db(conn: &mut Connection, batch: Vec<ParsedFile>, total_count: &mut usize) -> Result<()> {
     let tx = conn.transaction()?;
 
     {
         let mut file_stmt = tx.prepare_cached(
-            "INSERT OR REPLACE INTO files (path, mtime, size) VALUES (?1, ?2, ?3)"
+            "INSERT OR REPLACE INTO files (path, mtime, size, encoding, language, is_test, skipped) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
         )?;
-        let mut del_sym_stmt = tx.prepare_cached("DELETE FROM symbols WHERE file_id = ?1")?;
-        let mut del_ref_stmt = tx.prepare_cached("DELETE FROM refs WHERE file_id = ?1")?;
+        let mut del_sym_stmt = tx.prepare_cached("DELETE FROM symbols_data WHERE file_id = ?1")?;
+        // A sharded index routes refs through the triggers of its TEMP `refs` view; otherwise
+        // names are interned here, saving the view's trigger a lookup per row
+        let interned = tx
+            .query_row("SELECT 1 FROM sqlite_temp_master WHERE name = 'refs'", [], |_| Ok(()))
+            .optional()?
+            .is_none();
+        let mut del_ref_stmt = tx.prepare_cached(if interned {
+            "DELETE FROM refs_data WHERE file_id = ?1"
+        } else {
+            "DELETE FROM refs WHERE file_id = ?1"
+        })?;
+        let mut name_ids: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
+        let mut del_import_stmt = tx.prepare_cached("DELETE FROM imports WHERE file_id = ?1")?;
+        let mut del_di_stmt = tx.prepare_cached("DELETE FROM di_bindings WHERE file_id = ?1")?;
         let mut sym_stmt = tx.prepare_cached(
-            "INSERT INTO symbols (file_id, name, kind, line, signature) VALUES (?1, ?2, ?3, ?4, ?5)"
+            "INSERT INTO symbols_data (file_id, name_id, kind, line, signature, container, qualified_name, doc, visibility, end_line, start_byte, end_byte, partial, condition, arity, signature_hash, generated_by, humps, complexity) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)"
         )?;
         let mut inh_stmt = tx.prepare_cached(
             "INSERT INTO inheritance (child_id, parent_name, kind) VALUES (?1, ?2, ?3)"
         )?;
-        let mut ref_stmt = tx.prepare_cached(
-            "INSERT INTO refs (file_id, name, line, context) VALUES (?1, ?2, ?3, ?4)"
+        let config = index_config();
+        let batch_size = config.insert_batch_size.min(max_insert_batch_size(&tx));
+        let mut ref_values: Vec<rusqlite::types::Value> = Vec::with_capacity(batch_size * REF_COLUMNS);
+        let mut ann_stmt = tx.prepare_cached(
+            "INSERT INTO symbol_annotations (symbol_id, name, args) VALUES (?1, ?2, ?3)"
+        )?;
+        let mut import_stmt = tx.prepare_cached(
+            "INSERT INTO imports (file_id, name, path, line) VALUES (?1, ?2, ?3, ?4)"
+        )?;
+        let mut call_stmt = tx.prepare_cached(
+            "INSERT INTO calls (caller_id, callee, line) VALUES (?1, ?2, ?3)"
         )?;
+        let mut di_stmt = tx.prepare_cached(
+            "INSERT INTO di_bindings (file_id, type, role, mechanism, symbol, line) VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+        )?;
+        // Lines go to `content_fts` when `rebuild` created it
+        let mut line_stmt = if crate::db::has_content_fts(&tx) {
+            Some(tx.prepare_cached("INSERT INTO content_fts (rowid, text) VALUES (?1, ?2)")?)
+        } else {
+            None
+        };
 
         for pf in batch {
-            file_stmt.execute(rusqlite::params![pf.rel_path, pf.mtime, pf.size])?;
+            let language = parsers::language_of_path(&pf.rel_path);
+            let is_test = parsers::is_test_path(&pf.rel_path);
+            // `INSERT OR REPLACE` drops the old row without firing the delete triggers
+            if line_stmt.is_some() {
+                crate::db::delete_content_lines(&tx, &pf.rel_path)?;
+            }
+            file_stmt.execute(rusqlite::params![pf.rel_path, pf.mtime, pf.size, pf.encoding, language, is_test, pf.skipped.map(|r| r.as_str())])?;
             let file_id = tx.last_insert_rowid();
 
             del_sym_stmt.execute(rusqlite::params![file_id])?;
             del_ref_stmt.execute(rusqlite::params![file_id])?;
+            del_import_stmt.execute(rusqlite::params![file_id])?;
+            del_di_stmt.execute(rusqlite::params![file_id])?;
 
-            for sym in pf.symbols {
+            let file_type = Path::new(&pf.rel_path)
+                .extension()
+                .and_then(|e| e.to_str())
+                .and_then(parsers::FileType::from_extension);
+            let mut symbol_ids = Vec::with_capacity(pf.symbols.len());
+            for (idx, sym) in pf.symbols.into_iter().enumerate() {
+                let complexity = pf.complexity.get(idx).copied().flatten();
+                let partial = parsers::is_partial(&sym, file_type).then_some(1);
+                let params = (sym.kind == crate::db::SymbolKind::Function)
+                    .then(|| signature::parameters(&sym.signature, &sym.name))
+                    .flatten();
+                let arity = (sym.kind == crate::db::SymbolKind::Function)
+                    .then(|| signature::function_arity(&sym.name, params.as_deref()))
+                    .flatten();
+                let humps = crate::fuzzy::hump_skeleton(&sym.name);
                 sym_stmt.execute(rusqlite::params![
                     file_id,
-                    sym.name,
+                    name_id(&tx, &mut name_ids, &sym.name)?,
                     sym.kind.as_str(),
                     sym.line as i64,
-                    sym.signature
+                    sym.signature,
+                    sym.container,
+                    sym.qualified_name,
+                    sym.doc,
+                    sym.visibility,
+                    sym.end_line.map(|l| l as i64),
+                    sym.byte_range.map(|(start, _)| start as i64),
+                    sym.byte_range.map(|(_, end)| end as i64),
+                    partial,
+                    sym.condition,
+                    arity.map(|a| a as i64),
+                    params.as_deref().map(signature::signature_hash),
+                    sym.generated_by,
+                    humps,
+                    complexity
                 ])?;
                 let symbol_id = tx.last_insert_rowid();
+                symbol_ids.push(symbol_id);
 
                 for (parent_name, inherit_kind) in sym.parents {
                     inh_stmt.execute(rusqlite::params![symbol_id, parent_name, inherit_kind])?;
                 }
+                for (name, args) in sym.annotations {
+                    ann_stmt.execute(rusqlite::params![symbol_id, name, args])?;
+                }
             }
 
-            for r in pf.refs {
-                ref_stmt.execute(rusqlite::params![file_id, r.name, r.line as i64, r.context])?;
+            for (r, count, sample_lines) in ref_rows(pf.refs, config.refs) {
+                let name = if interned { name_id(&tx, &mut name_ids, &r.name)?.into() } else { r.name.into() };
+                ref_values.extend([
+                    file_id.into(),
+                    name,
+                    (r.line as i64).into(),
+                    r.context.into(),
+                    r.kind.as_str().to_string().into(),
+                    r.arity.map(|a| a as i64).into(),
+                    count.into(),
+                    sample_lines.into(),
+                ]);
+                if ref_values.len() == batch_size * REF_COLUMNS {
+                    insert_refs(&tx, &ref_values, interned)?;
+                    ref_values.clear();
+                }
+            }
+            for import in pf.imports {
+                import_stmt.execute(rusqlite::params![file_id, import.name, import.path, import.line as i64])?;
+            }
+            for call in pf.calls {
+                call_stmt.execute(rusqlite::params![symbol_ids[call.caller], call.callee, call.line as i64])?;
+            }
+            for binding in pf.di {
+                di_stmt.execute(rusqlite::params![file_id, binding.type_name, binding.role.as_str(), binding.mechanism, binding.symbol, binding.line as i64])?;
+            }
+            if let Some(line_stmt) = &mut line_stmt {
+                for (line, text) in &pf.lines {
+                    line_stmt.execute(rusqlite::params![crate::db::content_rowid(file_id, *line), text])?;
+                }
             }
 
             *total_count += 1;
         }
+        insert_refs(&tx, &ref_values, interned)?;
     }
 
     tx.commit()?;
@@ -671,7 +1100,11 @@ fn write_batch_to_db(conn: &mut Connection, batch: Vec<ParsedFile>, total_count:
 pub fn update_directory_incremental(conn: &mut Connection, root: &Path, progress: bool) -> Result<(usize, usize, usize)> {
     use ignore::WalkBuilder;
     use std::collections::HashMap;
-    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // An index built without references stays without them
+    parsers::set_refs_enabled(crate::db::refs_indexed(conn));
+    // ...and one built with `--languages` stays within those languages
+    parsers::restrict_to_indexed_languages(&crate::db::indexed_languages(conn))?;
 
     // 1. Load existing files from DB with their mtime
     let mut existing_files: HashMap<String, (i64, i64)> = HashMap::new(); // path -> (file_id, mtime)
@@ -708,6 +1141,8 @@ pub fn update_directory_incremental(conn: &mut Connection, root: &Path, progress
     }
     let walker = builder.build();
 
+    // Files the filter no longer allows count as deleted
+    let filter = PathFilter::new(index_config());
     let current_files: Vec<PathBuf> = walker
         .filter_map(|e| e.ok())
         .filter(|e| {
@@ -716,6 +1151,7 @@ pub fn update_directory_incremental(conn: &mut Connection, root: &Path, progress
                 .and_then(|ext| ext.to_str())
                 .map(parsers::is_supported_extension)
                 .unwrap_or(false)
+                && filter.allows(&e.path().strip_prefix(root).unwrap_or(e.path()).to_string_lossy())
         })
         .map(|e| e.path().to_path_buf())
         .collect();
@@ -750,10 +1186,13 @@ pub fn update_directory_incremental(conn: &mut Connection, root: &Path, progress
         current_paths.insert(rel_path);
     }
 
-    // 4. Find deleted files
+    // 4. Find deleted files; files of languages left out by `--languages` were not walked
     let deleted_paths: Vec<String> = existing_files
         .keys()
         .filter(|p| !current_paths.contains(*p))
+        .filter(|p| {
+            !Path::new(p.as_str()).extension().and_then(|ext| ext.to_str()).is_some_and(parsers::is_filtered_out_extension)
+        })
         .cloned()
         .collect();
 
@@ -779,22 +1218,18 @@ pub fn update_directory_incremental(conn: &mut Connection, root: &Path, progress
 
     // 6. Parse and update changed/new files
     let updated_count = if !files_to_parse.is_empty() {
-        let total_files = files_to_parse.len();
-        let parsed_count = Arc::new(AtomicUsize::new(0));
+        let bar = ParseProgress::new(files_to_parse.len(), "changed files", 500, progress);
         let root_clone = root.to_path_buf();
-        let parsed_count_clone = parsed_count.clone();
 
         let parsed_files: Vec<ParsedFile> = files_to_parse
             .par_iter()
             .filter_map(|path| {
                 let result = parse_file(&root_clone, path).ok();
-                let c = parsed_count_clone.fetch_add(1, Ordering::Relaxed) + 1;
-                if progress && c % 500 == 0 {
-                    eprintln!("Parsed {} / {} changed files...", c, total_files);
-                }
+                bar.tick(&path.strip_prefix(&root_clone).unwrap_or(path).to_string_lossy());
                 result
             })
             .collect();
+        bar.finish();
 
         let count = parsed_files.len();
         let mut dummy_total = 0;
@@ -803,10 +1238,164 @@ pub fn update_directory_incremental(conn: &mut Connection, root: &Path, progress
     } else {
         0
     };
+    if updated_count > 0 || !deleted_paths.is_empty() {
+        crate::db::prune_string_refs(conn)?;
+        crate::db::sync_name_trigrams(conn)?;
+    }
 
     Ok((updated_count, files_to_parse.len(), deleted_paths.len()))
 }
 
+/// Re-index exactly `paths` (e.g. from a file watcher) instead of walking the whole tree:
+/// files that are new or whose mtime or size changed are parsed and written, and paths that are
+/// gone are deleted from the index together with the indexed files under them (a removed
+/// directory). Files the walk would skip (hidden or excluded directories, ignore rules,
+/// unsupported extensions) are left out, and those `PathFilter` rejects are deleted from the
+/// index. Returns (updated, deleted).
+pub fn update_files(conn: &mut Connection, root: &Path, paths: &[PathBuf]) -> Result<(usize, usize)> {
+    // An index built without references stays without them
+    parsers::set_refs_enabled(crate::db::refs_indexed(conn));
+    // ...and one built with `--languages` stays within those languages
+    parsers::restrict_to_indexed_languages(&crate::db::indexed_languages(conn))?;
+
+    let canonical_root = root.canonicalize().ok();
+    let honor_ignores = has_git_repo(root) || find_arc_root(root).is_some();
+    let filter = PathFilter::new(index_config());
+
+    let mut files_to_parse: Vec<PathBuf> = Vec::new();
+    let mut removed_paths: Vec<String> = Vec::new();
+    for path in paths {
+        // Watchers may report paths with symlinks resolved
+        let Some(rel) = path
+            .strip_prefix(root)
+            .ok()
+            .or_else(|| canonical_root.as_deref().and_then(|r| path.strip_prefix(r).ok()))
+        else {
+            continue;
+        };
+        if rel.as_os_str().is_empty() {
+            continue;
+        }
+        let file_path = root.join(rel);
+        if !file_path.exists() {
+            removed_paths.push(rel.to_string_lossy().to_string());
+            continue;
+        }
+        let supported = file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(parsers::is_supported_extension)
+            .unwrap_or(false);
+        if !file_path.is_file() || !supported || is_walk_skipped(root, rel, honor_ignores) {
+            continue;
+        }
+        if !filter.allows(&rel.to_string_lossy()) {
+            // Drop a copy indexed before the file was excluded
+            removed_paths.push(rel.to_string_lossy().to_string());
+            continue;
+        }
+        // Reading a file is an event too: skip files indexed as they are
+        let metadata = fs::metadata(&file_path)?;
+        let mtime = metadata
+            .modified()?
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_secs() as i64;
+        let indexed: Option<(i64, i64)> = conn
+            .query_row(
+                "SELECT mtime, size FROM files WHERE path = ?1",
+                rusqlite::params![rel.to_string_lossy()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok();
+        if indexed != Some((mtime, metadata.len() as i64)) {
+            files_to_parse.push(file_path);
+        }
+    }
+    files_to_parse.sort();
+    files_to_parse.dedup();
+
+    let mut deleted_count = 0;
+    if !removed_paths.is_empty() {
+        let tx = conn.transaction()?;
+        {
+            // The path itself, or everything under it: `dir/` <= path < `dir0` (`0` follows `/`)
+            let mut del_file_stmt =
+                tx.prepare_cached("DELETE FROM files WHERE path = ?1 OR (path >= ?1 || '/' AND path < ?1 || '0')")?;
+            for path in &removed_paths {
+                deleted_count += del_file_stmt.execute(rusqlite::params![path])?;
+            }
+        }
+        tx.commit()?;
+    }
+
+    let parsed_files: Vec<ParsedFile> = files_to_parse
+        .par_iter()
+        .filter_map(|path| parse_file(root, path).ok())
+        .collect();
+    let updated_count = parsed_files.len();
+    if updated_count > 0 {
+        let mut dummy_total = 0;
+        write_batch_to_db(conn, parsed_files, &mut dummy_total)?;
+    }
+    if updated_count > 0 || deleted_count > 0 {
+        crate::db::prune_string_refs(conn)?;
+        crate::db::sync_name_trigrams(conn)?;
+    }
+
+    Ok((updated_count, deleted_count))
+}
+
+/// Whether the directory walk skips `rel` (relative to `root`): it is hidden, inside a hidden or
+/// excluded directory, or matched by a `.gitignore`/`.arcignore` in `root` or a directory above
+/// it when `honor_ignores`
+fn is_walk_skipped(root: &Path, rel: &Path, honor_ignores: bool) -> bool {
+    let names: Vec<&str> = rel.components().filter_map(|c| c.as_os_str().to_str()).collect();
+    let dirs = &names[..names.len().saturating_sub(1)];
+    if names.iter().any(|name| name.starts_with('.')) || dirs.iter().any(|name| EXCLUDED_DIRS.contains(name)) {
+        return true;
+    }
+    if !honor_ignores {
+        return false;
+    }
+    let path = root.join(rel);
+    let mut ignore_dirs = vec![root.to_path_buf()];
+    for name in dirs {
+        let dir = ignore_dirs[ignore_dirs.len() - 1].join(name);
+        ignore_dirs.push(dir);
+    }
+    for dir in &ignore_dirs {
+        for ignore_file in [".gitignore", ".arcignore"] {
+            let ignore_path = dir.join(ignore_file);
+            if !ignore_path.is_file() {
+                continue;
+            }
+            let (matcher, _) = ignore::gitignore::Gitignore::new(&ignore_path);
+            if matcher.matched_path_or_any_parents(&path, false).is_ignore() {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Whether a file watcher can ignore a change of `path` under `root`: a hidden path or one in an
+/// excluded directory, or an existing file that is not a source file `filter` allows. Removed
+/// paths are kept, since they may be directories of indexed files
+pub fn is_unwatched(root: &Path, path: &Path, filter: &PathFilter) -> bool {
+    let Ok(rel) = path.strip_prefix(root) else { return false };
+    if rel.as_os_str().is_empty() {
+        return false;
+    }
+    if is_walk_skipped(root, rel, false) {
+        return true;
+    }
+    if !path.exists() {
+        return false;
+    }
+    let supported = path.extension().and_then(|ext| ext.to_str()).is_some_and(parsers::is_supported_extension);
+    !supported || !filter.allows(&rel.to_string_lossy())
+}
+
 /// Index modules from build.gradle files (Android) and Package.swift (iOS)
 pub fn index_modules(conn: &Connection, root: &Path) -> Result<usize> {
     use ignore::WalkBuilder;
@@ -2035,8 +2624,282 @@ mod tests {
         let result = parse_file(dir.path(), &large_file).unwrap();
         assert!(result.symbols.is_empty(), "should skip large files");
         assert!(result.refs.is_empty());
+        assert_eq!(result.skipped, Some(SkipReason::TooLarge));
+    }
+
+    #[test]
+    fn test_parse_file_skips_binary_and_minified_files() {
+        let dir = TempDir::new().unwrap();
+        let binary = dir.path().join("blob.kt");
+        fs::write(&binary, b"class A\0\0\x01\x02 {}").unwrap();
+        assert_eq!(parse_file(dir.path(), &binary).unwrap().skipped, Some(SkipReason::Binary));
+
+        let bundle = dir.path().join("bundle.min.js");
+        let line = "var a=function(){return 1};".repeat(100);
+        fs::write(&bundle, [line.as_str(); 10].join("\n")).unwrap();
+        let result = parse_file(dir.path(), &bundle).unwrap();
+        assert_eq!(result.skipped, Some(SkipReason::Minified));
+        assert!(result.symbols.is_empty());
+
+        let source = dir.path().join("Main.kt");
+        fs::write(&source, "class Main {\n    fun run() {}\n}\n".repeat(2000)).unwrap();
+        assert_eq!(parse_file(dir.path(), &source).unwrap().skipped, None);
+    }
+
+    #[test]
+    fn test_read_lines_bounded_stops_at_long_line() {
+        let source = "sub field { return 1; }\n".repeat(20_000);
+        assert_eq!(read_lines_bounded(source.as_bytes(), 0).unwrap(), (source.clone().into_bytes(), true));
+        let longest = format!("{}\n", "a".repeat(MAX_LINE_LENGTH));
+        assert!(read_lines_bounded(longest.as_bytes(), 0).unwrap().1);
+
+        let bundle = format!("/* v1 */\n{}", "var a=function(){return 1};".repeat(30_000));
+        let (bytes, complete) = read_lines_bounded(bundle.as_bytes(), 0).unwrap();
+        assert!(!complete);
+        assert_eq!(bytes.len(), "/* v1 */\n".len() + MAX_LINE_LENGTH + 1);
+
+        let dir = TempDir::new().unwrap();
+        let vendor = dir.path().join("vendor.js");
+        fs::write(&vendor, &bundle).unwrap();
+        let result = parse_file(dir.path(), &vendor).unwrap();
+        assert_eq!((result.skipped, result.encoding), (Some(SkipReason::Minified), None));
+    }
+
+    #[test]
+    fn test_large_file_parsed_for_symbols_only_by_default() {
+        let dir = TempDir::new().unwrap();
+        let body = "class Main {\n    fun run() { helper() }\n}\n";
+        let small = dir.path().join("Small.kt");
+        fs::write(&small, body).unwrap();
+        assert!(!parse_file(dir.path(), &small).unwrap().refs.is_empty());
+
+        let large = dir.path().join("Large.kt");
+        fs::write(&large, body.repeat(8000)).unwrap();
+        let size = fs::metadata(&large).unwrap().len();
+        assert!(size > index_config().refs_max_file_size && size <= index_config().max_file_size);
+        let result = parse_file(dir.path(), &large).unwrap();
+        assert_eq!(result.skipped, None);
+        assert!(!result.symbols.is_empty());
+        assert!(result.refs.is_empty());
+    }
+
+    #[test]
+    fn test_watermark_degrades_past_limit() {
+        let watermark = Watermark { in_flight: AtomicU64::new(0) };
+        let (first, over) = watermark.hold(600, 1000);
+        assert!(!over);
+        assert!(watermark.hold(600, 1000).1);
+        drop(first);
+        let (_second, over) = watermark.hold(600, 1000);
+        assert!(!over);
+        assert!(watermark.hold(2000, 1000).1);
+        assert_eq!(watermark.in_flight.load(Ordering::Relaxed), 600);
     }
 
+    #[test]
+    fn test_index_git_revision_reads_objects_not_worktree() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        let run = |args: &[&str]| {
+            let status = std::process::Command::new("git").args(args).current_dir(root).output().unwrap().status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        run(&["init", "-q"]);
+        fs::create_dir_all(root.join("build")).unwrap();
+        fs::write(root.join("cart.py"), "def total(items):\n    return 0\n").unwrap();
+        fs::write(root.join("build").join("gen.py"), "def generated():\n    pass\n").unwrap();
+        run(&["add", "-A"]);
+        run(&["-c", "user.name=Dev", "-c", "user.email=dev@example.com", "commit", "-qm", "Add cart"]);
+        run(&["tag", "v1"]);
+        fs::write(root.join("cart.py"), "def subtotal(items):\n    return 0\n").unwrap();
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        let (commit, count) = index_git_revision(&mut conn, root, "v1", false).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(commit.sha.len(), 40);
+        let names: Vec<String> = conn
+            .prepare("SELECT name FROM symbols")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(names, vec!["total"]);
+        let mtime: i64 = conn.query_row("SELECT mtime FROM files WHERE path = 'cart.py'", [], |row| row.get(0)).unwrap();
+        assert_eq!(mtime, commit.time);
+        assert!(index_git_revision(&mut conn, root, "no-such-branch", false).is_err());
+    }
+
+    #[test]
+    fn test_write_batch_refs_in_multi_row_statements() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        let refs = |count: usize| -> Vec<ParsedRef> {
+            (0..count)
+                .map(|i| ParsedRef {
+                    name: format!("Name{}", i),
+                    line: i + 1,
+                    context: String::new(),
+                    kind: crate::db::RefKind::Call,
+                    arity: (i % 2 == 0).then_some(i),
+                })
+                .collect()
+        };
+        let file = |path: &str, count: usize| ParsedFile {
+            rel_path: path.to_string(),
+            mtime: 1,
+            size: 1,
+            symbols: vec![],
+            refs: refs(count),
+            imports: vec![],
+            calls: vec![],
+            di: vec![],
+            complexity: vec![],
+            encoding: None,
+            skipped: None,
+            lines: vec![],
+        };
+        // Two full statements across both files and a partial one
+        let count = index_config().insert_batch_size * 2 + 7;
+        let mut total = 0;
+        write_batch_to_db(&mut conn, vec![file("a.kt", count - 10), file("b.kt", 10)], &mut total).unwrap();
+        assert_eq!(total, 2);
+        let stored: i64 = conn.query_row("SELECT COUNT(*) FROM refs", [], |row| row.get(0)).unwrap();
+        assert_eq!(stored, count as i64);
+        let (line, arity): (i64, Option<i64>) = conn
+            .query_row("SELECT r.line, r.arity FROM refs r JOIN files f ON r.file_id = f.id WHERE f.path = 'b.kt' AND r.name = 'Name4'", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!((line, arity), (5, Some(4)));
+    }
+
+    #[test]
+    fn test_write_batch_fits_the_variable_limit() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        // The lowest limit a system SQLite may have: 124 rows per statement
+        conn.set_limit(rusqlite::limits::Limit::SQLITE_LIMIT_VARIABLE_NUMBER, 999);
+        assert_eq!(max_insert_batch_size(&conn), 999 / REF_COLUMNS);
+
+        let count = index_config().insert_batch_size * 2 + 7;
+        let mut file = ParsedFile::empty("a.kt".to_string(), 1, 1, None, None);
+        file.refs = (0..count)
+            .map(|i| ParsedRef { name: format!("Name{}", i), line: i + 1, context: String::new(), kind: crate::db::RefKind::Call, arity: None })
+            .collect();
+        let mut total = 0;
+        write_batch_to_db(&mut conn, vec![file], &mut total).unwrap();
+        let stored: i64 = conn.query_row("SELECT COUNT(*) FROM refs", [], |row| row.get(0)).unwrap();
+        assert_eq!(stored, count as i64);
+    }
+
+    #[test]
+    fn test_write_batch_replaces_content_lines() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        crate::db::create_content_fts(&conn).unwrap();
+        let file = |content: &str| {
+            let mut file = ParsedFile::empty("a.kt".to_string(), 1, 1, None, None);
+            file.lines = content_lines(content);
+            file
+        };
+        let mut total = 0;
+        write_batch_to_db(&mut conn, vec![file("val old = OldPayment()\n\n   \n")], &mut total).unwrap();
+        write_batch_to_db(&mut conn, vec![file("\n  val fresh = NewPayment()  \n")], &mut total).unwrap();
+        let hits = crate::db::search_content(&conn, "Payment", 10, |_, _| true).unwrap();
+        assert_eq!(hits, vec![("a.kt".to_string(), 2, "val fresh = NewPayment()".to_string())]);
+    }
+
+    #[test]
+    fn test_ref_rows_aggregated() {
+        let r = |name: &str, line: usize, arity: Option<usize>| ParsedRef {
+            name: name.to_string(),
+            line,
+            context: format!("line {}", line),
+            kind: crate::db::RefKind::Call,
+            arity,
+        };
+        let refs = vec![r("load", 9, Some(1)), r("load", 4, Some(1)), r("save", 5, None), r("load", 30, Some(1)), r("load", 7, Some(2))];
+        assert_eq!(ref_rows(refs.clone(), RefsLayout::Occurrences).len(), 5);
+
+        // (name, first line, context, count, lines)
+        type Row = (String, usize, String, Option<i64>, Option<String>);
+        let rows: Vec<Row> = ref_rows(refs, RefsLayout::Aggregated)
+            .into_iter()
+            .map(|(r, count, lines)| (r.name, r.line, r.context, count, lines))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("load".to_string(), 4, "line 4".to_string(), Some(3), Some("4,9,30".to_string())),
+                ("save".to_string(), 5, "line 5".to_string(), Some(1), Some("5".to_string())),
+                ("load".to_string(), 7, "line 7".to_string(), Some(1), Some("7".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_filter_and_watch_rules() {
+        let config = IndexConfig {
+            include: vec!["src/**".to_string()],
+            exclude: vec!["**/generated/**".to_string(), "**/*.pb.go".to_string()],
+            ..IndexConfig::default()
+        };
+        let filter = PathFilter::new(&config);
+        assert!(filter.allows("src/app/Main.kt"));
+        assert!(!filter.allows("tools/Gen.kt"));
+        assert!(!filter.allows("src/generated/Api.kt"));
+        assert!(!filter.allows("src/api/user.pb.go"));
+        assert!(PathFilter::new(&IndexConfig::default()).allows("tools/Gen.kt"));
+
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/generated")).unwrap();
+        fs::write(root.join("src/Main.kt"), "class Main\n").unwrap();
+        fs::write(root.join("src/generated/Api.kt"), "class Api\n").unwrap();
+        fs::write(root.join("src/notes.txt"), "notes\n").unwrap();
+        assert!(!is_unwatched(root, &root.join("src/Main.kt"), &filter));
+        assert!(is_unwatched(root, &root.join("src/generated/Api.kt"), &filter));
+        assert!(is_unwatched(root, &root.join("src/notes.txt"), &filter));
+        assert!(is_unwatched(root, &root.join("node_modules/lib/index.js"), &filter));
+        assert!(is_unwatched(root, &root.join(".git/index"), &filter));
+        // A removed path may be a directory of indexed files
+        assert!(!is_unwatched(root, &root.join("src/old"), &filter));
+    }
+
+    #[cfg(feature = "lang-kotlin")]
+    #[test]
+    fn test_update_files() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join("src/old")).unwrap();
+        fs::create_dir_all(root.join("gen")).unwrap();
+        fs::write(root.join(".gitignore"), "gen/\n").unwrap();
+        fs::write(root.join("src/A.kt"), "class Alpha\n").unwrap();
+        fs::write(root.join("src/B.kt"), "class Beta\n").unwrap();
+        fs::write(root.join("src/old/C.kt"), "class Gamma\n").unwrap();
+        fs::write(root.join("src/older.kt"), "class Delta\n").unwrap();
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::init_db(&conn).unwrap();
+        index_directory(&mut conn, root, false, false).unwrap();
+
+        fs::write(root.join("src/A.kt"), "class Alpha2 {}\n").unwrap();
+        fs::remove_dir_all(root.join("src/old")).unwrap();
+        fs::write(root.join("gen/D.kt"), "class Generated\n").unwrap();
+        let paths = [root.join("src/A.kt"), root.join("src/B.kt"), root.join("src/old"), root.join("gen/D.kt")];
+        assert_eq!(update_files(&mut conn, root, &paths).unwrap(), (1, 1));
+        // Nothing changed since
+        assert_eq!(update_files(&mut conn, root, &paths[..2]).unwrap(), (0, 0));
+
+        let names: Vec<String> = conn
+            .prepare("SELECT name FROM symbols ORDER BY name").unwrap()
+            .query_map([], |row| row.get(0)).unwrap()
+            .collect::<Result<_, _>>().unwrap();
+        // `src/older.kt` survives removing `src/old`
+        assert_eq!(names, ["Alpha2", "Beta", "Delta"]);
+    }
+
+    #[cfg(feature = "lang-kotlin")]
     #[test]
     fn test_parse_file_kotlin() {
         let dir = TempDir::new().unwrap();
@@ -2048,6 +2911,7 @@ mod tests {
         assert!(result.symbols.iter().any(|s| s.name == "doSomething"));
     }
 
+    #[cfg(feature = "lang-swift")]
     #[test]
     fn test_parse_file_swift() {
         let dir = TempDir::new().unwrap();
@@ -2059,6 +2923,7 @@ mod tests {
         assert!(result.symbols.iter().any(|s| s.name == "setup"));
     }
 
+    #[cfg(feature = "lang-python")]
     #[test]
     fn test_parse_file_python() {
         let dir = TempDir::new().unwrap();
@@ -0,0 +1,296 @@
+//! JNI bindings between Kotlin/Java native methods and their C/C++ implementations
+//!
+//! A native method (`external fun` in Kotlin, `native` in Java) is bound when a C/C++ file
+//! defines the function the JVM looks up for it, `Java_<class>_<method>` with the class's binary
+//! name mangled as the JNI specification says (`_1` for `_`, `_00024` for the `$` of nested
+//! classes, an optional `__<arguments>` suffix for overloads), or registers the method by name
+//! in a `JNINativeMethod` table passed to `RegisterNatives`. Tables are attributed to the class
+//! paths (`"com/example/Codec"`) named in the same file. Kotlin top-level functions belong to the
+//! file class (`CodecKt`, or `@file:JvmName`), companion members to the companion or, with
+//! `@JvmStatic`, to the outer class.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+use rusqlite::Connection;
+use serde::Serialize;
+
+/// `external` or `native` among the modifiers of a declaration
+static NATIVE_MODIFIER_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[^(]*\b(?:external|native)\b").unwrap());
+static JVM_NAME_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"@file:JvmName\(\s*"([^"]+)"\s*\)"#).unwrap());
+static JNI_FUNCTION_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(Java_[A-Za-z0-9_]+)\s*\(").unwrap());
+/// `{"name", "(signature)", fn}` entries of a `JNINativeMethod` table
+static REGISTERED_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\{\s*"([A-Za-z_$][\w$]*)"\s*,\s*"(\([^"]*\)[^"]*)"\s*,"#).unwrap());
+/// Class paths in string literals (`"com/example/Codec"`, `"com/example/Codec$Inner"`)
+static CLASS_PATH_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""((?:[a-z_][\w]*/)+[A-Z][\w$]*)""#).unwrap());
+
+/// A Kotlin or Java native method
+#[derive(Debug, Clone, Serialize)]
+pub struct NativeMethod {
+    pub name: String,
+    /// Binary names of the classes the JVM may look the method up in (`com.example.Codec$Companion`)
+    pub classes: Vec<String>,
+    pub path: String,
+    pub line: i64,
+}
+
+/// A C/C++ `Java_...` function definition
+#[derive(Debug, Clone, Serialize)]
+pub struct JniFunction {
+    pub symbol: String,
+    /// Binary name of the class it implements a method of
+    pub class: String,
+    pub method: String,
+    pub path: String,
+    pub line: usize,
+}
+
+/// A method registered through a `JNINativeMethod` table
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredNative {
+    pub method: String,
+    pub signature: String,
+    /// Binary names of the classes named in the same file; any class when empty
+    pub classes: Vec<String>,
+    pub path: String,
+    pub line: usize,
+}
+
+/// Matched and unmatched bindings
+#[derive(Debug, Default, Serialize)]
+pub struct JniReport {
+    /// Native methods with an implementation
+    pub bound: usize,
+    /// Native methods nothing implements or registers
+    pub unimplemented: Vec<NativeMethod>,
+    /// `Java_` functions for methods no class declares native
+    pub undeclared_functions: Vec<JniFunction>,
+    /// Registered methods no named class declares native
+    pub undeclared_registrations: Vec<RegisteredNative>,
+}
+
+/// `Java_<class>_<method>` for a binary class name and method name
+pub fn mangle(class: &str, method: &str) -> String {
+    let escape = |s: &str| -> String {
+        s.chars()
+            .map(|c| match c {
+                '.' | '/' => "_".to_string(),
+                '_' => "_1".to_string(),
+                ';' => "_2".to_string(),
+                '[' => "_3".to_string(),
+                c if c.is_ascii_alphanumeric() => c.to_string(),
+                c => format!("_0{:04x}", c as u32),
+            })
+            .collect()
+    };
+    format!("Java_{}_{}", escape(class), escape(method))
+}
+
+/// Binary class name and method of a `Java_` function name, overload suffix dropped
+pub fn demangle(symbol: &str) -> Option<(String, String)> {
+    let mangled = symbol.strip_prefix("Java_")?;
+    let mut name = String::new();
+    let mut chars = mangled.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '_' {
+            name.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('1') => name.push('_'),
+            Some('2') => name.push(';'),
+            Some('3') => name.push('['),
+            Some('0') => {
+                chars.next();
+                let code: String = chars.by_ref().take(4).collect();
+                name.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+                continue;
+            }
+            // `__` starts the argument signature of an overloaded method
+            Some('_') => break,
+            _ => {
+                name.push('.');
+                continue;
+            }
+        }
+        chars.next();
+    }
+    let (class, method) = name.rsplit_once('.')?;
+    Some((class.to_string(), method.to_string()))
+}
+
+/// Native methods declared in indexed Kotlin and Java files
+pub fn native_methods(conn: &Connection, root: &Path) -> Result<Vec<NativeMethod>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT s.name, s.container, s.qualified_name, s.signature, f.path, s.line,
+               (SELECT p.name FROM symbols p WHERE p.file_id = f.id AND p.kind = 'package' LIMIT 1)
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE s.kind = 'function' AND f.language IN ('kotlin', 'java')
+          AND (s.signature LIKE '%external%' OR s.signature LIKE '%native%')
+        ORDER BY f.path, s.line
+        "#,
+    )?;
+    type Row = (String, Option<String>, Option<String>, Option<String>, String, i64, Option<String>);
+    let rows: Vec<Row> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut methods = Vec::new();
+    for (name, container, qualified_name, signature, path, line, file_package) in rows {
+        let signature = signature.unwrap_or_default();
+        if !NATIVE_MODIFIER_RE.is_match(&signature) {
+            continue;
+        }
+        // The package is what the qualified name adds in front of the container and name
+        let local = match &container {
+            Some(container) => format!(".{}.{}", container, name),
+            None => format!(".{}", name),
+        };
+        let package = qualified_name.as_deref().and_then(|q| q.strip_suffix(&local)).map(str::to_string).or(file_package);
+        let qualify = |class: &str| match &package {
+            Some(package) => format!("{}.{}", package, class),
+            None => class.to_string(),
+        };
+        let classes = match &container {
+            Some(container) => {
+                let mut classes = vec![qualify(&container.replace('.', "$"))];
+                // `@JvmStatic` companion members are static methods of the outer class
+                if let Some(outer) = container.strip_suffix(".Companion") {
+                    classes.push(qualify(&outer.replace('.', "$")));
+                }
+                classes
+            }
+            None => vec![qualify(&file_class(root, &path))],
+        };
+        methods.push(NativeMethod { name, classes, path, line });
+    }
+    Ok(methods)
+}
+
+/// Class of a Kotlin file's top-level functions: `@file:JvmName`, else the capitalized file
+/// name with `Kt`
+fn file_class(root: &Path, path: &str) -> String {
+    let content = std::fs::read_to_string(root.join(path)).unwrap_or_default();
+    if let Some(name) = JVM_NAME_RE.captures(&content).and_then(|c| c.get(1)) {
+        return name.as_str().to_string();
+    }
+    let stem = Path::new(path).file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    let mut chars = stem.chars();
+    let capitalized: String = chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default();
+    format!("{}Kt", capitalized)
+}
+
+/// `Java_` function definitions and `JNINativeMethod` entries of a C/C++ file
+pub fn scan_native_source(path: &str, content: &str) -> (Vec<JniFunction>, Vec<RegisteredNative>) {
+    let line_of = |offset: usize| content[..offset].matches('\n').count() + 1;
+    let mut functions = Vec::new();
+    for m in JNI_FUNCTION_RE.captures_iter(content).filter_map(|c| c.get(1)) {
+        // Definitions only: the parameter list is followed by a body, not by `;`
+        let after = &content[m.end()..];
+        let Some(close) = after.find(')') else { continue };
+        if !after[close + 1..].trim_start().starts_with('{') {
+            continue;
+        }
+        if let Some((class, method)) = demangle(m.as_str()) {
+            functions.push(JniFunction { symbol: m.as_str().to_string(), class, method, path: path.to_string(), line: line_of(m.start()) });
+        }
+    }
+
+    let mut classes: Vec<String> = CLASS_PATH_RE.captures_iter(content).map(|c| c[1].replace('/', ".")).collect();
+    classes.sort();
+    classes.dedup();
+    let registered = REGISTERED_RE
+        .captures_iter(content)
+        .map(|c| RegisteredNative {
+            method: c[1].to_string(),
+            signature: c[2].to_string(),
+            classes: classes.clone(),
+            path: path.to_string(),
+            line: line_of(c.get(0).map_or(0, |m| m.start())),
+        })
+        .collect();
+    (functions, registered)
+}
+
+/// Match native methods with `Java_` functions and registered methods
+pub fn check(methods: Vec<NativeMethod>, functions: Vec<JniFunction>, registered: Vec<RegisteredNative>) -> JniReport {
+    let declared: HashSet<(&str, &str)> =
+        methods.iter().flat_map(|m| m.classes.iter().map(|class| (class.as_str(), m.name.as_str()))).collect();
+    let implemented: HashSet<(&str, &str)> = functions.iter().map(|f| (f.class.as_str(), f.method.as_str())).collect();
+    let is_registered = |class: &str, method: &str| {
+        registered.iter().any(|r| r.method == method && (r.classes.is_empty() || r.classes.iter().any(|c| c == class)))
+    };
+
+    let mut report = JniReport::default();
+    for method in &methods {
+        if method.classes.iter().any(|class| implemented.contains(&(class.as_str(), method.name.as_str())) || is_registered(class, &method.name)) {
+            report.bound += 1;
+        } else {
+            report.unimplemented.push(method.clone());
+        }
+    }
+    report.undeclared_functions = functions.iter().filter(|f| !declared.contains(&(f.class.as_str(), f.method.as_str()))).cloned().collect();
+    report.undeclared_registrations = registered
+        .iter()
+        .filter(|r| {
+            !methods.iter().any(|m| m.name == r.method && (r.classes.is_empty() || m.classes.iter().any(|class| r.classes.contains(class))))
+        })
+        .cloned()
+        .collect();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mangle_and_demangle() {
+        assert_eq!(mangle("com.example.Codec", "encode"), "Java_com_example_Codec_encode");
+        assert_eq!(mangle("com.example.Codec$Companion", "init_all"), "Java_com_example_Codec_00024Companion_init_1all");
+        assert_eq!(demangle("Java_com_example_Codec_00024Companion_init_1all"), Some(("com.example.Codec$Companion".to_string(), "init_all".to_string())));
+        assert_eq!(demangle("Java_com_example_Codec_encode__ILjava_lang_String_2"), Some(("com.example.Codec".to_string(), "encode".to_string())));
+        assert_eq!(demangle("JNI_OnLoad"), None);
+    }
+
+    #[test]
+    fn test_scan_and_check() {
+        let source = r#"
+extern "C" JNIEXPORT jint JNICALL
+Java_com_ex_Codec_encode(JNIEnv* env, jobject thiz, jint x) {
+    return x;
+}
+JNIEXPORT void JNICALL Java_com_ex_Codec_decode(JNIEnv* env, jobject thiz);
+JNIEXPORT void JNICALL Java_com_ex_Codec_reset(JNIEnv* env, jobject thiz) {}
+
+static JNINativeMethod methods[] = {
+    {"play", "(Ljava/lang/String;)V", (void*) play_impl},
+    {"stop", "()V", (void*) stop_impl},
+};
+jint JNI_OnLoad(JavaVM* vm, void*) {
+    jclass cls = env->FindClass("com/ex/Audio");
+    env->RegisterNatives(cls, methods, 2);
+}
+"#;
+        let (functions, registered) = scan_native_source("jni/codec.cpp", source);
+        let symbols: Vec<&str> = functions.iter().map(|f| f.symbol.as_str()).collect();
+        assert_eq!(symbols, ["Java_com_ex_Codec_encode", "Java_com_ex_Codec_reset"]);
+        assert_eq!(functions[0].line, 3);
+        assert_eq!(registered.len(), 2);
+        assert_eq!(registered[0].classes, ["com.ex.Audio"]);
+
+        let method = |name: &str, class: &str| NativeMethod { name: name.to_string(), classes: vec![class.to_string()], path: "src/X.kt".to_string(), line: 1 };
+        let methods = vec![method("encode", "com.ex.Codec"), method("decode", "com.ex.Codec"), method("play", "com.ex.Audio")];
+        let report = check(methods, functions, registered);
+        assert_eq!(report.bound, 2);
+        assert_eq!(report.unimplemented.iter().map(|m| m.name.as_str()).collect::<Vec<_>>(), ["decode"]);
+        assert_eq!(report.undeclared_functions.iter().map(|f| f.method.as_str()).collect::<Vec<_>>(), ["reset"]);
+        assert_eq!(report.undeclared_registrations.iter().map(|r| r.method.as_str()).collect::<Vec<_>>(), ["stop"]);
+    }
+}
@@ -1,4 +1,16 @@
+pub mod codeowners;
+pub mod config;
 pub mod db;
+pub mod embed;
+pub mod encoding;
+pub mod fuzzy;
+pub mod git;
+pub mod history;
+pub mod i18n;
 pub mod indexer;
+pub mod jni;
 pub mod parsers;
+pub mod profiles;
+pub mod progress;
+pub mod proto;
 pub mod commands;
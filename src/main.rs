@@ -1,8 +1,9 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use colored::Colorize;
 use std::path::PathBuf;
 
-use ast_index::{db, commands};
+use ast_index::{config, db, commands, embed, i18n, indexer, parsers, profiles, progress};
 
 #[derive(Parser)]
 #[command(name = "ast-index")]
@@ -15,12 +16,18 @@ use ast_index::{db, commands};
 {usage-heading} {usage}
 
 Index Management:
-  rebuild                Rebuild index (full reindex)
-  update                 Update index (incremental)
-  stats                  Show index statistics
+  rebuild                Rebuild index (full reindex; --no-refs for definitions only, --rev REV from git, --timings per language, --languages to restrict)
+  update                 Update index (incremental, within the rebuild's --languages)
+  stats                  Show index statistics (--by module|language|directory|owner)
+  embed                  Compute embeddings for search --semantic
+  migrate                Apply schema migrations to an older index (--dry-run to preview)
+  optimize               Compact the index (FTS optimize, PRAGMA optimize, VACUUM)
   clear                  Clear index database
-  version                Show version
+  version                Show version (--verbose: SQLite build, bundled or system, fixed at build time)
   watch                  Watch for file changes and auto-update
+  fleet                  Index a list of repositories in parallel
+  merge                  Merge index DBs (e.g. CI shards) into one, deduplicating files
+  profiles               List named indexes for --index NAME (profile-add, profile-remove)
 
 Search & Navigation:
   search                 Universal search (files + symbols)
@@ -28,24 +35,46 @@ Search & Navigation:
   symbol                 Find symbols (classes, interfaces, functions)
   class                  Find class or interface
   hierarchy              Show class hierarchy
-  implementations        Find implementations (subclasses/implementors)
+  implementations        Find implementations, including subclasses of implementors
   refs                   Cross-references: definitions, imports, usages
+  show                   Print the full definition of a symbol
+  def                    Go to the best definition (path:line:col)
+  callers                Functions calling a function (call graph, --depth N)
+  callees                Functions a function calls (call graph, --depth N)
+  complete               Complete a name prefix (name, kind, path) for editors
   usages                 Find usages of a symbol
+  rename-preview         Locations and conflicts for renaming a symbol (dry run)
+  owners                 CODEOWNERS of a path, or of a symbol and its references
   outline                Show symbols in a file
   imports                Show imports in a file
+  explain                How the index recorded a file (parsed, skipped and why, missing)
   changed                Show changed symbols (git/arc diff)
+  history                When a symbol appeared, changed or disappeared (git, --update)
+  proto-usages           Where code generated from a proto message/service is used, per platform
+  di-graph               What provides a type to Dagger/Hilt/Koin/Spring DI and where it is injected
 
 Module Commands:
   module                 Find modules
   deps                   Show module dependencies
   dependents             Show reverse dependencies
+  graph                  Dependency graph as DOT or Mermaid (--scope module|dir|file)
+  check-arch             Check layering rules from .ast-index.toml (CI gate)
   unused-deps            Find unused dependencies in a module
   api                    Show public API of a module
+  api-surface            Snapshot the public API as JSON (--out api.json)
+  api-diff               Compare an API snapshot with the index (CI semver gate)
   unused-symbols         Find potentially unused symbols
+  similar                Find confusingly similar symbol names
+  duplicates             Find definitions copied across files
+  orphan-tests           Find tests of code that no longer exists
+  complexity             Find the most complex functions
+  god-classes            Find classes with the most members (--threshold 40)
+  uncalled-overrides     Find overridden methods nothing calls (dead hierarchies)
+  untested               Public symbols no test file references (coverage proxy)
+  lint-names             Check naming conventions ([[naming]] rules); fails on violations
 
 Code Patterns (grep-based):
   todo                   Find TODO/FIXME/HACK comments
-  callers                Find callers of a function
   call-tree              Show call hierarchy tree
   annotations            Find classes with annotation
   deprecated             Find @Deprecated items
@@ -62,6 +91,7 @@ Code Patterns (grep-based):
 Android:
   xml-usages             Find class usages in XML layouts
   resource-usages        Find resource usages
+  jni                    Match native methods with C/C++ JNI functions (CI gate)
 
 iOS (Swift/ObjC):
   storyboard-usages      Find class usages in storyboards/xibs
@@ -91,8 +121,10 @@ Project Configuration:
 Programmatic Access:
   agrep                  Structural code search via ast-grep
   query                  Execute raw SQL against the index DB
+  serve                  JSON HTTP API for search/refs/def/stats (--port 7345)
   db-path                Print path to the SQLite index database
   schema                 Show database schema (tables and columns)
+  export-dataset         Export symbols, docs and references as JSONL
 
 Options:
 {options}{after-help}\
@@ -101,9 +133,36 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    /// Output format: text or json
-    #[arg(long, global = true, default_value = "text")]
-    format: String,
+    /// Output format: text (default) or json (schema also accepts sql and markdown, graph dot and
+    /// mermaid, export-dataset jsonl and json)
+    #[arg(long, global = true)]
+    format: Option<String>,
+
+    /// Skip this many results of a query command and report the total
+    #[arg(long, global = true)]
+    offset: Option<usize>,
+
+    /// Continue a query command from the cursor printed with the previous page
+    #[arg(long, global = true, conflicts_with = "offset")]
+    cursor: Option<String>,
+
+    /// Index database file (overrides AST_INDEX_DB_PATH); `:memory:` indexes the project in memory
+    /// before the command runs, without touching disk
+    #[arg(long, global = true)]
+    db: Option<String>,
+
+    /// Same as --db :memory:
+    #[arg(long, global = true, conflicts_with = "db")]
+    ephemeral: bool,
+
+    /// Write the in-memory index to this file when the command finishes, e.g. for a CI cache
+    #[arg(long, global = true, value_name = "PATH")]
+    save_db: Option<PathBuf>,
+
+    /// Use the named index profile (see `profiles`): its project root and index file, from any
+    /// directory
+    #[arg(long, global = true, value_name = "NAME")]
+    index: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -117,11 +176,37 @@ enum Commands {
         #[arg(short, long, default_value = "50")]
         limit: usize,
     },
-    /// Find callers of a function
+    /// Functions calling a function, from the call graph (grep without an index)
     Callers {
         /// Function name
         function_name: String,
-        /// Max results
+        /// Also list the callers' callers, up to N levels
+        #[arg(long, default_value = "1")]
+        depth: usize,
+        /// Skip test files (test/spec directories, *Test.kt, *_test.go, test_*.py, *.spec.ts, ...)
+        #[arg(long, conflicts_with = "only_tests")]
+        no_tests: bool,
+        /// Only test files
+        #[arg(long)]
+        only_tests: bool,
+        /// Max callers per function
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+    /// Functions a function calls, from the call graph
+    Callees {
+        /// Function name
+        function_name: String,
+        /// Also list what the callees call, down to N levels
+        #[arg(long, default_value = "1")]
+        depth: usize,
+        /// Skip test files (test/spec directories, *Test.kt, *_test.go, test_*.py, *.spec.ts, ...)
+        #[arg(long, conflicts_with = "only_tests")]
+        no_tests: bool,
+        /// Only test files
+        #[arg(long)]
+        only_tests: bool,
+        /// Max callees per function
         #[arg(short, long, default_value = "50")]
         limit: usize,
     },
@@ -245,19 +330,53 @@ enum Commands {
         /// Number of parallel threads (default: CPU cores, max 8; increase for network filesystems)
         #[arg(long, short = 'j')]
         threads: Option<usize>,
+        /// Index definitions only, without references; `usages` falls back to grep
+        #[arg(long)]
+        no_refs: bool,
+        /// Index the source files of a git revision (branch, tag, sha) from git objects,
+        /// without a checkout
+        #[arg(long)]
+        rev: Option<String>,
+        /// Print the time spent per language parser, with the slowest file of each
+        #[arg(long)]
+        timings: bool,
+        /// Only index these languages (comma-separated, e.g. kotlin,java); update and watch keep to them
+        #[arg(long, value_delimiter = ',')]
+        languages: Vec<String>,
     },
     /// Update index (incremental)
-    Update,
+    Update {
+        /// Only update files in these languages, out of those the index was built with
+        #[arg(long, value_delimiter = ',')]
+        languages: Vec<String>,
+    },
+    /// Compute embeddings of symbols not embedded yet, for `search --semantic`
+    Embed,
     /// Restore index from a .db file
     Restore {
         /// Path to the .db file to restore
         path: String,
     },
     /// Show index statistics
-    Stats,
+    Stats {
+        /// Per-area report: module, language, directory or owner (CODEOWNERS)
+        #[arg(long)]
+        by: Option<String>,
+        /// Directory levels kept with --by directory (and for files outside modules)
+        #[arg(long, default_value = "2")]
+        depth: usize,
+    },
+    /// Apply pending schema migrations to an index written by an older release
+    Migrate {
+        /// List pending migrations without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Compact the index: merge full-text segments, refresh planner statistics and VACUUM
+    Optimize,
     /// Universal search (files + symbols)
     Search {
-        /// Search query
+        /// Search query; AND, OR, NOT and "quoted phrases" make it a boolean query over symbols
         query: String,
         /// Max results
         #[arg(short, long, default_value = "20")]
@@ -268,9 +387,68 @@ enum Commands {
         /// Filter by module path
         #[arg(long)]
         module: Option<String>,
-        /// Fuzzy search (exact → prefix → contains)
-        #[arg(long)]
+        /// Fuzzy search (exact → prefix → contains → within a few typos, e.g. PaymntRepo)
+        #[arg(long, group = "name_match")]
         fuzzy: bool,
+        /// Only symbols whose whole name is QUERY (ignoring case unless --case-sensitive)
+        #[arg(long, group = "name_match")]
+        exact: bool,
+        /// Only symbols whose name starts with QUERY
+        #[arg(long, group = "name_match")]
+        prefix: bool,
+        /// Only symbols whose name contains QUERY
+        #[arg(long, group = "name_match")]
+        contains: bool,
+        /// Match fully qualified names (e.g. com.example.billing.Invoice); implied for `a::b` queries
+        #[arg(long)]
+        qualified: bool,
+        /// Search doc comments instead of names (all words must match)
+        #[arg(long)]
+        in_docs: bool,
+        /// List symbols carrying this annotation (e.g. Composable, pytest.fixture, derive(Serialize));
+        /// Deprecated also finds #[deprecated], [Obsolete] and @available(*, deprecated)
+        #[arg(long)]
+        annotation: bool,
+        /// Only symbols with this visibility: public, protected, internal, private, package, crate
+        #[arg(long)]
+        visibility: Option<String>,
+        /// Only symbols of these kinds: class, interface, object, enum, function, property, typealias,
+        /// package, constant, import, annotation (repeatable or comma-separated)
+        #[arg(long = "kind", value_delimiter = ',')]
+        kinds: Vec<String>,
+        /// Only files in these languages (comma-separated, e.g. kotlin,swift)
+        #[arg(long, value_delimiter = ',')]
+        lang: Vec<String>,
+        /// Only files matching this path glob, e.g. 'src/payments/**' (repeatable)
+        #[arg(long)]
+        path: Vec<String>,
+        /// Skip files matching this path glob, e.g. '**/test/**' (repeatable)
+        #[arg(long)]
+        exclude_path: Vec<String>,
+        /// Only files changed since this git revision: committed, staged, modified or untracked
+        #[arg(long, value_name = "REV")]
+        changed_since: Option<String>,
+        /// Skip test files (test/spec directories, *Test.kt, *_test.go, test_*.py, *.spec.ts, ...)
+        #[arg(long, conflicts_with = "only_tests")]
+        no_tests: bool,
+        /// Only test files
+        #[arg(long)]
+        only_tests: bool,
+        /// Pass the query to SQLite FTS5 unchanged, e.g. '{doc} : retry NEAR(backoff)'
+        #[arg(long, group = "name_match")]
+        raw_fts: bool,
+        /// Rank symbols by meaning, e.g. "retry failed network calls" (needs `ast-index embed`)
+        #[arg(long, group = "name_match")]
+        semantic: bool,
+        /// Match letter case in names, doc words, file paths and file contents
+        #[arg(long, group = "case", conflicts_with_all = ["fuzzy", "semantic"])]
+        case_sensitive: bool,
+        /// Ignore letter case (the default unless `case_sensitive = true` in .ast-index.toml)
+        #[arg(long, group = "case")]
+        ignore_case: bool,
+        /// Print N lines of source around each symbol and content match, the match highlighted
+        #[arg(short = 'C', long, value_name = "N")]
+        context: Option<usize>,
     },
     /// Find files by name
     File {
@@ -299,9 +477,12 @@ enum Commands {
         /// Filter by module path
         #[arg(long)]
         module: Option<String>,
-        /// Fuzzy search (exact → prefix → contains)
+        /// Fuzzy search (exact → prefix → contains → within a few typos, e.g. PaymntRepo)
         #[arg(long)]
         fuzzy: bool,
+        /// Only symbols with this visibility: public, protected, internal, private, package, crate
+        #[arg(long)]
+        visibility: Option<String>,
     },
     /// Find class or interface
     Class {
@@ -316,14 +497,20 @@ enum Commands {
         /// Filter by module path
         #[arg(long)]
         module: Option<String>,
-        /// Fuzzy search (exact → prefix → contains)
+        /// Fuzzy search (exact → prefix → contains → within a few typos, e.g. PaymntRepo)
         #[arg(long)]
         fuzzy: bool,
+        /// Only symbols with this visibility: public, protected, internal, private, package, crate
+        #[arg(long)]
+        visibility: Option<String>,
     },
-    /// Find implementations (subclasses/implementors)
+    /// Find implementations (subclasses/implementors), transitively
     Implementations {
         /// Parent class/interface name
         parent: String,
+        /// Only types naming the parent themselves, not subclasses of implementors
+        #[arg(long)]
+        direct: bool,
         /// Max results
         #[arg(short, long, default_value = "20")]
         limit: usize,
@@ -333,6 +520,9 @@ enum Commands {
         /// Filter by module path
         #[arg(long)]
         module: Option<String>,
+        /// Only symbols with this visibility: public, protected, internal, private, package, crate
+        #[arg(long)]
+        visibility: Option<String>,
     },
     /// Show class hierarchy
     Hierarchy {
@@ -357,6 +547,20 @@ enum Commands {
         /// Module name
         module: String,
     },
+    /// Dependency graph from imports and references (--format dot|mermaid|json)
+    Graph {
+        /// Nodes: module, dir or file
+        #[arg(long, default_value = "module")]
+        scope: String,
+        /// Directory levels kept when files are collapsed by directory
+        #[arg(long, default_value = "2")]
+        depth: usize,
+        /// Leave out edges using fewer symbols
+        #[arg(long, default_value = "1")]
+        min_weight: usize,
+    },
+    /// Check the [[architecture]] layering rules in .ast-index.toml; fails on violations
+    CheckArch,
     /// Find unused dependencies in a module
     UnusedDeps {
         /// Module name (e.g., features.payments.impl)
@@ -400,14 +604,71 @@ enum Commands {
         #[arg(long)]
         unused: bool,
     },
+    /// Match Kotlin/Java native methods with C/C++ JNI functions and RegisterNatives tables;
+    /// fails on unmatched bindings
+    Jni,
     /// Show cross-references: definitions, imports, usages
     Refs {
         /// Symbol name
         symbol: String,
+        /// Only usages of this kind: call, instantiation, type, extension, string, other
+        #[arg(long)]
+        kind: Option<String>,
+        /// Only overloads taking this many parameters and calls passing this many arguments
+        #[arg(long)]
+        arity: Option<usize>,
+        /// Only files in these languages (comma-separated, e.g. kotlin,swift)
+        #[arg(long, value_delimiter = ',')]
+        lang: Vec<String>,
+        /// Only files changed since this git revision: committed, staged, modified or untracked
+        #[arg(long, value_name = "REV")]
+        changed_since: Option<String>,
+        /// Skip test files (test/spec directories, *Test.kt, *_test.go, test_*.py, *.spec.ts, ...)
+        #[arg(long, conflicts_with = "only_tests")]
+        no_tests: bool,
+        /// Only test files
+        #[arg(long)]
+        only_tests: bool,
+        /// List usages grouped by file or kind, with counts over all usages (limit then counts groups)
+        #[arg(long)]
+        group_by: Option<String>,
+        /// Print N lines of source around each definition, import and usage, the name highlighted
+        #[arg(short = 'C', long, value_name = "N")]
+        context: Option<usize>,
         /// Max results per section
         #[arg(short, long, default_value = "20")]
         limit: usize,
     },
+    /// Names starting with a prefix (ignoring case), as name, kind and path:line for editor completion
+    Complete {
+        /// Name prefix
+        prefix: String,
+        /// Max results
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+    /// Print the best definition of a name as path:line:col, resolved through the imports of --from
+    Def {
+        /// Symbol name, optionally prefixed with its container
+        name: String,
+        /// Where the name is used, as file or file:line (relative to the project root or absolute)
+        #[arg(long)]
+        from: Option<String>,
+    },
+    /// Print the full definition of a symbol (e.g. save or UserRepo.save)
+    Show {
+        /// Symbol name, optionally prefixed with its container
+        name: String,
+        /// Max definitions to print
+        #[arg(short, long, default_value = "5")]
+        limit: usize,
+        /// Filter by file path
+        #[arg(long)]
+        in_file: Option<String>,
+        /// Filter by module path
+        #[arg(long)]
+        module: Option<String>,
+    },
     /// Find usages of a symbol
     Usages {
         /// Symbol name
@@ -432,6 +693,11 @@ enum Commands {
         /// File path
         file: String,
     },
+    /// Show how the index recorded a file, or why it is missing or was not parsed
+    Explain {
+        /// File path
+        file: String,
+    },
     /// Show public API of a module
     Api {
         /// Module path (e.g., features/payments/api)
@@ -440,12 +706,75 @@ enum Commands {
         #[arg(short, long, default_value = "100")]
         limit: usize,
     },
+    /// Dry run of a rename: every location to edit and existing declarations of the new name
+    RenamePreview {
+        /// Current name
+        old: String,
+        /// New name
+        new: String,
+    },
+    /// CODEOWNERS owners of a path, or of a symbol's declarations and the files referencing it
+    Owners {
+        /// Symbol name, or a file or directory path
+        target: String,
+    },
+    /// Where the Kotlin/Java/Swift/Go code generated from a proto message, enum or service is used
+    ProtoUsages {
+        /// Message, enum or service name (e.g. PaymentRequest)
+        name: String,
+    },
+    /// What provides a type to the Dagger/Hilt/Koin/Spring container, what it needs and where
+    /// it is injected
+    DiGraph {
+        /// Type name (e.g. PaymentRepository)
+        type_name: String,
+    },
+    /// Write the public API (symbols and signatures) as a JSON snapshot
+    ApiSurface {
+        /// Write to file instead of stdout
+        #[arg(short, long)]
+        out: Option<String>,
+        /// Filter by module path
+        #[arg(long)]
+        module: Option<String>,
+        /// Only files in these languages (comma-separated, e.g. kotlin,swift)
+        #[arg(long, value_delimiter = ',')]
+        lang: Vec<String>,
+    },
+    /// Compare an API snapshot with the current index; fails on removed or changed symbols
+    ApiDiff {
+        /// Snapshot written by api-surface
+        old: String,
+        /// Compare with this snapshot instead of the current index
+        #[arg(long)]
+        new: Option<String>,
+        /// Filter by module path
+        #[arg(long)]
+        module: Option<String>,
+        /// Only files in these languages (comma-separated, e.g. kotlin,swift)
+        #[arg(long, value_delimiter = ',')]
+        lang: Vec<String>,
+    },
     /// Show changed symbols (git/arc diff)
     Changed {
         /// Base branch (auto-detected: trunk for arc, origin/main for git)
         #[arg(long)]
         base: Option<String>,
     },
+    /// Commits in which declarations of a symbol appeared, changed signature, moved or disappeared
+    History {
+        /// Symbol name
+        symbol: Option<String>,
+        /// Index the git history (first time) or bring it up to date with HEAD
+        #[arg(long)]
+        update: bool,
+        /// Index only the newest N commits when building the history
+        #[arg(long)]
+        max_commits: Option<usize>,
+        /// Only declarations of this kind (class, function, ...)
+        #[arg(long)]
+        kind: Option<String>,
+    },
     // === iOS Commands ===
     /// Find class usages in storyboards/xibs (iOS)
     StoryboardUsages {
@@ -569,6 +898,185 @@ enum Commands {
         /// Max results
         #[arg(short, long, default_value = "50")]
         limit: usize,
+        /// Only symbols with this visibility: public, protected, internal, private, package, crate
+        #[arg(long)]
+        visibility: Option<String>,
+        /// Active conditional-compilation names (comma-separated, e.g. DEBUG,test,feature="serde");
+        /// symbols under #if/#ifdef/#[cfg] branches inactive for this set are skipped
+        #[arg(long, value_delimiter = ',')]
+        active_cfg: Vec<String>,
+        /// Only files in these languages (comma-separated, e.g. kotlin,swift)
+        #[arg(long, value_delimiter = ',')]
+        lang: Vec<String>,
+        /// Only files changed since this git revision: committed, staged, modified or untracked
+        #[arg(long, value_name = "REV")]
+        changed_since: Option<String>,
+        /// Skip test files (test/spec directories, *Test.kt, *_test.go, test_*.py, *.spec.ts, ...)
+        #[arg(long, conflicts_with = "only_tests")]
+        no_tests: bool,
+        /// Only test files
+        #[arg(long)]
+        only_tests: bool,
+        /// Report symbols no entry point reaches over references and calls ([entry_points] in .ast-index.toml)
+        #[arg(long)]
+        reachability: bool,
+        /// Baseline of accepted findings: written if missing, else only findings outside it are
+        /// reported, and any fails the run
+        #[arg(long, value_name = "FILE")]
+        baseline: Option<String>,
+        /// Rewrite the baseline with the current findings
+        #[arg(long, requires = "baseline")]
+        update_baseline: bool,
+    },
+    /// Names within an edit distance of a name (PaymentsManager, PaymentMangr for PaymentManager)
+    Similar {
+        /// Symbol name
+        name: String,
+        /// Largest edit distance relative to the longer name (0 = same but for case, 1 = anything)
+        #[arg(long, default_value = "0.2")]
+        threshold: f64,
+        /// Only symbols of these kinds (comma-separated, e.g. class,interface)
+        #[arg(long = "kind", value_delimiter = ',')]
+        kinds: Vec<String>,
+        /// Only files in these languages (comma-separated, e.g. kotlin,swift)
+        #[arg(long, value_delimiter = ',')]
+        lang: Vec<String>,
+        /// Skip test files (test/spec directories, *Test.kt, *_test.go, test_*.py, *.spec.ts, ...)
+        #[arg(long, conflicts_with = "only_tests")]
+        no_tests: bool,
+        /// Only test files
+        #[arg(long)]
+        only_tests: bool,
+        /// Max names
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+    /// Definitions repeated with the same name, kind and signature in several files
+    Duplicates {
+        /// Only symbols of these kinds (comma-separated, e.g. class,function)
+        #[arg(long = "kind", value_delimiter = ',')]
+        kinds: Vec<String>,
+        /// Only files in these languages (comma-separated, e.g. kotlin,swift)
+        #[arg(long, value_delimiter = ',')]
+        lang: Vec<String>,
+        /// Filter by module path
+        #[arg(long)]
+        module: Option<String>,
+        /// Skip test files (test/spec directories, *Test.kt, *_test.go, test_*.py, *.spec.ts, ...)
+        #[arg(long, conflicts_with = "only_tests")]
+        no_tests: bool,
+        /// Only test files
+        #[arg(long)]
+        only_tests: bool,
+        /// Max duplicated definitions
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+    /// Classes with the most members and incoming references, as refactoring candidates
+    GodClasses {
+        /// Least number of direct members
+        #[arg(long, default_value = "40")]
+        threshold: usize,
+        /// Only files in these languages (comma-separated, e.g. kotlin,swift)
+        #[arg(long, value_delimiter = ',')]
+        lang: Vec<String>,
+        /// Filter by module path
+        #[arg(long)]
+        module: Option<String>,
+        /// Skip test files (test/spec directories, *Test.kt, *_test.go, test_*.py, *.spec.ts, ...)
+        #[arg(long, conflicts_with = "only_tests")]
+        no_tests: bool,
+        /// Only test files
+        #[arg(long)]
+        only_tests: bool,
+        /// Max classes
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+    /// Methods overridden in subtypes but never called, on the base type or any override
+    UncalledOverrides {
+        /// Only files in these languages (comma-separated, e.g. kotlin,swift)
+        #[arg(long, value_delimiter = ',')]
+        lang: Vec<String>,
+        /// Filter by module path
+        #[arg(long)]
+        module: Option<String>,
+        /// Skip test files (test/spec directories, *Test.kt, *_test.go, test_*.py, *.spec.ts, ...)
+        #[arg(long, conflicts_with = "only_tests")]
+        no_tests: bool,
+        /// Only test files
+        #[arg(long)]
+        only_tests: bool,
+        /// Max methods
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+    /// Public symbols with no references from test files
+    Untested {
+        /// Only symbols of these kinds (comma-separated, e.g. class,function; default types and functions)
+        #[arg(long = "kind", value_delimiter = ',')]
+        kinds: Vec<String>,
+        /// Only files under this directory (e.g. src/payments)
+        #[arg(long, value_name = "DIR")]
+        path: Option<String>,
+        /// Filter by module path
+        #[arg(long)]
+        module: Option<String>,
+        /// Only files in these languages (comma-separated, e.g. kotlin,swift)
+        #[arg(long, value_delimiter = ',')]
+        lang: Vec<String>,
+        /// Max results
+        #[arg(short, long, default_value = "100")]
+        limit: usize,
+    },
+    /// Check declarations against the [[naming]] rules in .ast-index.toml (built-in ones when
+    /// none); fails on violations
+    LintNames {
+        /// Filter by module path
+        #[arg(long)]
+        module: Option<String>,
+        /// Only files under this directory (e.g. src/payments)
+        #[arg(long, value_name = "DIR")]
+        path: Option<String>,
+        /// Skip test files (test/spec directories, *Test.kt, *_test.go, test_*.py, *.spec.ts, ...)
+        #[arg(long, conflicts_with = "only_tests")]
+        no_tests: bool,
+        /// Only test files
+        #[arg(long)]
+        only_tests: bool,
+        /// Violations shown per rule
+        #[arg(short, long, default_value = "100")]
+        limit: usize,
+    },
+    /// Test files whose subject or imported project symbols no longer exist
+    OrphanTests {
+        /// Only files in these languages (comma-separated, e.g. kotlin,swift)
+        #[arg(long, value_delimiter = ',')]
+        lang: Vec<String>,
+        /// Filter by module path
+        #[arg(long)]
+        module: Option<String>,
+    },
+    /// Functions with the highest cyclomatic complexity, to target reviews at hotspots
+    Complexity {
+        /// Number of functions
+        #[arg(long, default_value = "50")]
+        top: usize,
+        /// Rank by complexity or lines
+        #[arg(long, default_value = "complexity")]
+        sort: String,
+        /// Only files in these languages (comma-separated, e.g. kotlin,swift)
+        #[arg(long, value_delimiter = ',')]
+        lang: Vec<String>,
+        /// Filter by module path
+        #[arg(long)]
+        module: Option<String>,
+        /// Skip test files (test/spec directories, *Test.kt, *_test.go, test_*.py, *.spec.ts, ...)
+        #[arg(long, conflicts_with = "only_tests")]
+        no_tests: bool,
+        /// Only test files
+        #[arg(long)]
+        only_tests: bool,
     },
     /// Add additional source root to project
     AddRoot {
@@ -586,11 +1094,64 @@ enum Commands {
     /// List configured source roots
     ListRoots,
     /// Watch for file changes and auto-update index
-    Watch,
+    Watch {
+        /// Only update files in these languages, out of those the index was built with
+        #[arg(long, value_delimiter = ',')]
+        languages: Vec<String>,
+    },
+    /// Clone/update and index a list of repositories (per-repo DBs + merged DB)
+    Fleet {
+        /// File with one git URL or local path per line (optional name after a space)
+        #[arg(long)]
+        repos: String,
+        /// Target directory for checkouts, per-repo indexes and merged.db
+        #[arg(long, default_value = "ast-index-fleet")]
+        target: String,
+        /// Number of repositories indexed in parallel
+        #[arg(short, long, default_value = "4")]
+        jobs: usize,
+        /// Skip building merged.db
+        #[arg(long)]
+        no_merge: bool,
+        /// Skip module dependencies indexing
+        #[arg(long)]
+        no_deps: bool,
+    },
+    /// Merge index DBs (e.g. from parallel CI shards) into one; a file in several inputs is kept
+    /// once, from the input with its newest mtime
+    Merge {
+        /// Index DBs to merge
+        #[arg(required = true)]
+        inputs: Vec<String>,
+        /// Path of the merged index (replaced if it exists)
+        #[arg(long)]
+        out: String,
+    },
+    /// List named index profiles for --index
+    Profiles,
+    /// Add or replace a named index profile: the project root (default: current project) and,
+    /// with --db, its index file
+    ProfileAdd {
+        /// Profile name (letters, digits, '-' and '_')
+        name: String,
+        /// Project root of the profile
+        #[arg(long)]
+        root: Option<PathBuf>,
+    },
+    /// Remove a named index profile (the index itself is kept)
+    ProfileRemove {
+        /// Profile name
+        name: String,
+    },
     /// Clear index database for current project
     Clear,
     /// Show version
-    Version,
+    Version {
+        /// Also show SQLite version, compiled features and enabled parsers. Bundled or system
+        /// SQLite is chosen at build time (`bundled-sqlite` feature), there is no runtime switch
+        #[arg(short, long)]
+        verbose: bool,
+    },
     /// Install Claude Code plugin to ~/.claude/plugins/
     InstallClaudePlugin,
     // === Programmatic Access ===
@@ -613,23 +1174,103 @@ enum Commands {
         #[arg(short, long, default_value = "100")]
         limit: usize,
     },
+    /// Serve search, refs, def and stats as a JSON HTTP API from a warm index
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value = "7345")]
+        port: u16,
+        /// Address to bind; the default accepts local connections only
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
     /// Print path to the SQLite index database
     DbPath,
-    /// Show database schema (tables and columns)
+    /// Show database schema with column descriptions (--format json|sql|markdown)
     Schema,
+    /// Export symbols with signature, doc, body span and references as JSONL
+    ExportDataset {
+        /// Write to file instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Only export files under these licenses (SPDX ids, e.g. MIT,Apache-2.0)
+        #[arg(long, value_delimiter = ',')]
+        license: Vec<String>,
+        /// Skip files under these licenses (SPDX ids)
+        #[arg(long, value_delimiter = ',')]
+        exclude_license: Vec<String>,
+        /// Also export files without a detectable license
+        #[arg(long)]
+        include_unlicensed: bool,
+        /// Max references per symbol
+        #[arg(long, default_value = "20")]
+        max_refs: usize,
+    },
+}
+
+/// Apply the settings of `.ast-index.toml` that parsing, search and messages read globally
+fn register_project_config(config: &config::Config) -> Result<()> {
+    i18n::set_locale(config.locale.as_deref())?;
+    parsers::custom::register(&config.parsers)?;
+    parsers::generated::register(&config.generated)?;
+    parsers::register_ref_noise(&config.references)?;
+    embed::register(config.embed.as_ref())?;
+    db::register_search(&config.search);
+    Ok(())
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    let root = find_project_root()?;
-    let format = cli.format.as_str();
+    let profile = match cli.index.as_deref() {
+        Some(name) => Some(profiles::Registry::load()?.get(name)?.clone()),
+        None => None,
+    };
+    let profile_db = profile.as_ref().and_then(|p| p.db.as_ref()).map(|db| db.to_string_lossy().into_owned());
+    if let Some(db_path) = cli.db.as_deref().or(cli.ephemeral.then_some(db::MEMORY_DB_PATH)).or(profile_db.as_deref()) {
+        std::env::set_var("AST_INDEX_DB_PATH", db_path);
+    }
+    if cli.save_db.is_some() && !db::in_memory() {
+        anyhow::bail!("--save-db writes an in-memory index: add --ephemeral or --db {}", db::MEMORY_DB_PATH);
+    }
+    let root = match &profile {
+        Some(profile) => profile.root.clone(),
+        None => find_project_root()?,
+    };
+    let format = cli.format.as_deref().unwrap_or("text");
 
     // Migrate project DB from old kotlin-index to ast-index
     db::migrate_legacy_project(&root);
 
+    // Project config: compile user-defined parsers before any indexing. A broken config only
+    // stops the commands that index or act on one of its sections; the rest warn and go on
+    let needs_config = db::in_memory()
+        || matches!(
+            cli.command,
+            Commands::Rebuild { .. } | Commands::Update { .. } | Commands::Watch { .. } | Commands::Embed | Commands::CheckArch | Commands::LintNames { .. }
+        );
+    let project_config = match config::Config::load(&root).and_then(|config| register_project_config(&config).map(|_| config)) {
+        Ok(config) => config,
+        Err(e) if !needs_config => {
+            let config = config::Config::default();
+            register_project_config(&config)?;
+            eprintln!("{}", ast_index::t!("config-ignored", error = format!("{:#}", e)).yellow());
+            config
+        }
+        Err(e) => return Err(e),
+    };
+    if let Commands::Rebuild { languages, .. } | Commands::Update { languages } | Commands::Watch { languages } = &cli.command {
+        parsers::set_enabled_languages(languages)?;
+    }
+    parsers::set_rails_mode(project_config.rails.unwrap_or_else(|| parsers::is_rails_project(&root)));
+    let mut index_config = project_config.index.clone();
+    if matches!(cli.command, Commands::Rebuild { no_refs: true, .. }) {
+        index_config.refs = config::RefsLayout::None;
+    }
+    indexer::set_index_config(&index_config);
+
     // Compute directory scope: if cwd is inside project root, limit search to cwd subtree
+    // (a profile covers its whole project)
     let cwd = std::env::current_dir().unwrap_or_default();
-    let dir_prefix = if cwd != root {
+    let dir_prefix = if cwd != root && profile.is_none() {
         cwd.strip_prefix(&root)
             .ok()
             .map(|rel| {
@@ -641,11 +1282,26 @@ fn main() -> Result<()> {
         None
     };
     let dir_prefix_ref = dir_prefix.as_deref();
+    let page = |limit: usize| commands::Page::new(limit, cli.offset, cli.cursor.as_deref());
+
+    // `rebuild` builds the in-memory index itself, with its own options
+    if db::in_memory() && !matches!(cli.command, Commands::Rebuild { .. }) {
+        commands::management::build_memory_index(&root)?;
+    }
 
-    match cli.command {
+    let result = match cli.command {
         // Grep commands
         Commands::Todo { pattern, limit } => commands::grep::cmd_todo(&root, &pattern, limit),
-        Commands::Callers { function_name, limit } => commands::grep::cmd_callers(&root, &function_name, limit),
+        Commands::Callers { function_name, depth, no_tests, only_tests, limit } => {
+            let scope = db::SearchScope { tests: test_filter(no_tests, only_tests), ..db::SearchScope::none() };
+            let query = commands::calls::CallGraphQuery { name: &function_name, depth, limit };
+            commands::calls::cmd_callers(&root, &query, &scope, format)
+        }
+        Commands::Callees { function_name, depth, no_tests, only_tests, limit } => {
+            let scope = db::SearchScope { tests: test_filter(no_tests, only_tests), ..db::SearchScope::none() };
+            let query = commands::calls::CallGraphQuery { name: &function_name, depth, limit };
+            commands::calls::cmd_callees(&root, &query, &scope, format)
+        }
         Commands::CallTree { function_name, depth, limit } => commands::grep::cmd_call_tree(&root, &function_name, depth, limit),
         Commands::Provides { type_name, limit } => commands::grep::cmd_provides(&root, &type_name, limit),
         Commands::Suspend { query, limit } => commands::grep::cmd_suspend(&root, query.as_deref(), limit),
@@ -659,42 +1315,96 @@ fn main() -> Result<()> {
         Commands::Flows { query, limit } => commands::grep::cmd_flows(&root, query.as_deref(), limit),
         Commands::Previews { query, limit } => commands::grep::cmd_previews(&root, query.as_deref(), limit),
         // Management commands
-        Commands::Rebuild { r#type, no_deps, no_ignore, sub_projects, verbose, threads } => {
+        Commands::Rebuild { r#type, no_deps, no_ignore, sub_projects, verbose, threads, rev, timings, .. } => {
             if let Some(t) = threads {
                 std::env::set_var("AST_INDEX_THREADS", t.to_string());
             }
-            commands::management::cmd_rebuild(&root, &r#type, !no_deps, no_ignore, sub_projects, verbose)
+            if timings {
+                progress::enable_timings();
+            }
+            let result = commands::management::cmd_rebuild(&root, &r#type, !no_deps, no_ignore, sub_projects, verbose, rev.as_deref());
+            if timings && result.is_ok() {
+                progress::print_timings();
+            }
+            result
         }
-        Commands::Update => commands::management::cmd_update(&root),
+        Commands::Update { .. } => commands::management::cmd_update(&root),
+        Commands::Embed => commands::management::cmd_embed(&root),
         Commands::Restore { path } => commands::management::cmd_restore(&root, &path),
-        Commands::Stats => commands::management::cmd_stats(&root, format),
+        Commands::Stats { by, depth } => commands::management::cmd_stats(&root, by.as_deref(), depth, format),
+        Commands::Migrate { dry_run } => commands::management::cmd_migrate(&root, dry_run, format),
+        Commands::Optimize => commands::management::cmd_optimize(&root, format),
         // Index commands
-        Commands::Search { query, limit, in_file, module, fuzzy } => {
-            let scope = db::SearchScope { in_file: in_file.as_deref(), module: module.as_deref(), dir_prefix: dir_prefix_ref };
-            commands::index::cmd_search(&root, &query, limit, format, &scope, fuzzy)
+        Commands::Search { query, limit, in_file, module, fuzzy, exact, prefix, contains, qualified, in_docs, annotation, visibility, kinds, lang, path, exclude_path, raw_fts, semantic, case_sensitive, ignore_case, context, changed_since, no_tests, only_tests } => {
+            let kinds = parse_kinds(&kinds)?;
+            let changed_files = changed_since.as_deref().map(|rev| commands::files::files_changed_since(&root, rev)).transpose()?;
+            let languages = parsers::resolve_languages(&lang)?;
+            let case_sensitive = case_sensitive || (!ignore_case && project_config.case_sensitive.unwrap_or(false));
+            let scope = db::SearchScope { in_file: in_file.as_deref(), module: module.as_deref(), dir_prefix: dir_prefix_ref, visibility: visibility.as_deref(), kinds: &kinds, languages: &languages, paths: &path, exclude_paths: &exclude_path, changed_files: changed_files.as_deref(), tests: test_filter(no_tests, only_tests), case_sensitive };
+            if semantic {
+                commands::index::cmd_search_semantic(&root, &query, page(limit)?, format, &scope)
+            } else if annotation {
+                commands::index::cmd_search_annotation(&root, &query, page(limit)?, format, &scope)
+            } else if in_docs {
+                commands::index::cmd_search_docs(&root, &query, page(limit)?, format, &scope)
+            } else if qualified || query.contains("::") {
+                commands::index::cmd_search_qualified(&root, &query, page(limit)?, format, &scope)
+            } else {
+                let mode = if fuzzy {
+                    commands::index::SearchMode::Fuzzy
+                } else if raw_fts {
+                    commands::index::SearchMode::RawFts
+                } else if exact {
+                    commands::index::SearchMode::Name(db::NameMatch::Exact)
+                } else if prefix {
+                    commands::index::SearchMode::Name(db::NameMatch::Prefix)
+                } else if contains {
+                    commands::index::SearchMode::Name(db::NameMatch::Contains)
+                } else {
+                    commands::index::SearchMode::Default
+                };
+                commands::index::cmd_search(&root, &query, page(limit)?, format, &scope, mode, context)
+            }
+        }
+        Commands::Symbol { name, r#type, limit, in_file, module, fuzzy, visibility } => {
+            let scope = db::SearchScope { in_file: in_file.as_deref(), module: module.as_deref(), dir_prefix: dir_prefix_ref, visibility: visibility.as_deref(), kinds: &[], languages: &[], paths: &[], exclude_paths: &[], changed_files: None, tests: None, case_sensitive: false };
+            commands::index::cmd_symbol(&root, &name, r#type.as_deref(), page(limit)?, format, &scope, fuzzy)
         }
-        Commands::Symbol { name, r#type, limit, in_file, module, fuzzy } => {
-            let scope = db::SearchScope { in_file: in_file.as_deref(), module: module.as_deref(), dir_prefix: dir_prefix_ref };
-            commands::index::cmd_symbol(&root, &name, r#type.as_deref(), limit, format, &scope, fuzzy)
+        Commands::Class { name, limit, in_file, module, fuzzy, visibility } => {
+            let scope = db::SearchScope { in_file: in_file.as_deref(), module: module.as_deref(), dir_prefix: dir_prefix_ref, visibility: visibility.as_deref(), kinds: &[], languages: &[], paths: &[], exclude_paths: &[], changed_files: None, tests: None, case_sensitive: false };
+            commands::index::cmd_class(&root, &name, page(limit)?, format, &scope, fuzzy)
         }
-        Commands::Class { name, limit, in_file, module, fuzzy } => {
-            let scope = db::SearchScope { in_file: in_file.as_deref(), module: module.as_deref(), dir_prefix: dir_prefix_ref };
-            commands::index::cmd_class(&root, &name, limit, format, &scope, fuzzy)
+        Commands::Implementations { parent, direct, limit, in_file, module, visibility } => {
+            let scope = db::SearchScope { in_file: in_file.as_deref(), module: module.as_deref(), dir_prefix: dir_prefix_ref, visibility: visibility.as_deref(), kinds: &[], languages: &[], paths: &[], exclude_paths: &[], changed_files: None, tests: None, case_sensitive: false };
+            commands::index::cmd_implementations(&root, &parent, direct, page(limit)?, format, &scope)
         }
-        Commands::Implementations { parent, limit, in_file, module } => {
-            let scope = db::SearchScope { in_file: in_file.as_deref(), module: module.as_deref(), dir_prefix: dir_prefix_ref };
-            commands::index::cmd_implementations(&root, &parent, limit, format, &scope)
+        Commands::Refs { symbol, kind, arity, lang, changed_since, no_tests, only_tests, group_by, context, limit } => {
+            let languages = parsers::resolve_languages(&lang)?;
+            let changed_files = changed_since.as_deref().map(|rev| commands::files::files_changed_since(&root, rev)).transpose()?;
+            let scope = db::SearchScope { languages: &languages, changed_files: changed_files.as_deref(), tests: test_filter(no_tests, only_tests), ..db::SearchScope::none() };
+            let query = commands::index::RefsQuery { symbol: &symbol, kind: kind.as_deref(), arity, group_by: group_by.as_deref() };
+            commands::index::cmd_refs(&root, &query, page(limit)?, format, &scope, context)
+        }
+        Commands::Complete { prefix, limit } => commands::index::cmd_complete(&root, &prefix, limit, format),
+        Commands::Def { name, from } => commands::index::cmd_def(&root, &name, from.as_deref(), format),
+        Commands::Show { name, limit, in_file, module } => {
+            let scope = db::SearchScope { in_file: in_file.as_deref(), module: module.as_deref(), dir_prefix: dir_prefix_ref, visibility: None, kinds: &[], languages: &[], paths: &[], exclude_paths: &[], changed_files: None, tests: None, case_sensitive: false };
+            commands::index::cmd_show(&root, &name, page(limit)?, format, &scope)
         }
-        Commands::Refs { symbol, limit } => commands::index::cmd_refs(&root, &symbol, limit, format),
         Commands::Hierarchy { name } => commands::index::cmd_hierarchy(&root, &name),
         Commands::Usages { symbol, limit, in_file, module } => {
-            let scope = db::SearchScope { in_file: in_file.as_deref(), module: module.as_deref(), dir_prefix: dir_prefix_ref };
-            commands::index::cmd_usages(&root, &symbol, limit, format, &scope)
+            let scope = db::SearchScope { in_file: in_file.as_deref(), module: module.as_deref(), dir_prefix: dir_prefix_ref, visibility: None, kinds: &[], languages: &[], paths: &[], exclude_paths: &[], changed_files: None, tests: None, case_sensitive: false };
+            commands::index::cmd_usages(&root, &symbol, page(limit)?, format, &scope)
         }
         // Module commands
         Commands::Module { pattern, limit } => commands::modules::cmd_module(&root, &pattern, limit),
         Commands::Deps { module } => commands::modules::cmd_deps(&root, &module),
         Commands::Dependents { module } => commands::modules::cmd_dependents(&root, &module),
+        Commands::Graph { scope, depth, min_weight } => {
+            let options = commands::graph::GraphOptions { scope: &scope, depth, min_weight };
+            commands::graph::cmd_graph(&root, &options, format)
+        }
+        Commands::CheckArch => commands::graph::cmd_check_arch(&root, &project_config.architecture, format),
         Commands::UnusedDeps { module, verbose, no_transitive, no_xml, no_resources, strict } => {
             let check_transitive = !no_transitive && !strict;
             let check_xml = !no_xml && !strict;
@@ -702,10 +1412,28 @@ fn main() -> Result<()> {
             commands::modules::cmd_unused_deps(&root, &module, verbose, check_transitive, check_xml, check_resources)
         }
         // File commands
-        Commands::File { pattern, exact, limit } => commands::files::cmd_file(&root, &pattern, exact, limit),
+        Commands::File { pattern, exact, limit } => commands::files::cmd_file(&root, &pattern, exact, page(limit)?),
         Commands::Outline { file } => commands::files::cmd_outline(&root, &file),
         Commands::Imports { file } => commands::files::cmd_imports(&root, &file),
+        Commands::Explain { file } => commands::files::cmd_explain(&root, &file, format),
         Commands::Api { module_path, limit } => commands::files::cmd_api(&root, &module_path, limit),
+        Commands::RenamePreview { old, new } => commands::rename::cmd_rename_preview(&root, &old, &new, format),
+        Commands::Owners { target } => commands::owners::cmd_owners(&root, &target, format),
+        Commands::ProtoUsages { name } => commands::proto::cmd_proto_usages(&root, &name, format),
+        Commands::DiGraph { type_name } => commands::di::cmd_di_graph(&root, &type_name, format),
+        Commands::ApiSurface { out, module, lang } => {
+            let languages = parsers::resolve_languages(&lang)?;
+            let scope = db::SearchScope { module: module.as_deref(), languages: &languages, tests: Some(false), ..db::SearchScope::none() };
+            commands::api::cmd_api_surface(&root, &scope, out.as_deref())
+        }
+        Commands::ApiDiff { old, new, module, lang } => {
+            let languages = parsers::resolve_languages(&lang)?;
+            let scope = db::SearchScope { module: module.as_deref(), languages: &languages, tests: Some(false), ..db::SearchScope::none() };
+            commands::api::cmd_api_diff(&root, &old, new.as_deref(), &scope, format)
+        }
+        Commands::History { symbol, update, max_commits, kind } => {
+            commands::history::cmd_history(&root, symbol.as_deref(), update, max_commits, kind.as_deref(), format)
+        }
         Commands::Changed { base } => {
             let vcs = commands::files::detect_vcs(&root);
             let default_base = if vcs == "arc" {
@@ -721,6 +1449,7 @@ fn main() -> Result<()> {
         Commands::ResourceUsages { resource, module, r#type, unused } => {
             commands::android::cmd_resource_usages(&root, &resource, module.as_deref(), r#type.as_deref(), unused)
         }
+        Commands::Jni => commands::android::cmd_jni(&root, format),
         // iOS commands
         Commands::StoryboardUsages { class_name, module } => commands::ios::cmd_storyboard_usages(&root, &class_name, module.as_deref()),
         Commands::AssetUsages { asset, module, r#type, unused } => commands::ios::cmd_asset_usages(&root, &asset, module.as_deref(), r#type.as_deref(), unused),
@@ -737,25 +1466,100 @@ fn main() -> Result<()> {
         // Project insights
         Commands::Map { module, per_dir, limit } => commands::project_info::cmd_map(&root, module.as_deref(), per_dir, limit, format),
         Commands::Conventions => commands::project_info::cmd_conventions(&root, format),
-        Commands::UnusedSymbols { module, export_only, limit } => {
-            commands::analysis::cmd_unused_symbols(&root, module.as_deref(), export_only, limit, format)
+        Commands::UnusedSymbols { module, export_only, limit, visibility, active_cfg, lang, changed_since, no_tests, only_tests, reachability, baseline, update_baseline } => {
+            let languages = parsers::resolve_languages(&lang)?;
+            let changed_files = changed_since.as_deref().map(|rev| commands::files::files_changed_since(&root, rev)).transpose()?;
+            let scope = db::SearchScope { module: module.as_deref(), visibility: visibility.as_deref(), languages: &languages, changed_files: changed_files.as_deref(), tests: test_filter(no_tests, only_tests), ..db::SearchScope::none() };
+            let options = commands::analysis::UnusedOptions {
+                export_only,
+                active_cfg: &active_cfg,
+                reachability: reachability.then_some(&project_config.entry_points),
+                baseline: baseline.as_deref(),
+                update_baseline,
+            };
+            commands::analysis::cmd_unused_symbols(&root, &scope, &options, page(limit)?, format)
+        }
+        Commands::Similar { name, threshold, kinds, lang, no_tests, only_tests, limit } => {
+            let kinds = parse_kinds(&kinds)?;
+            let languages = parsers::resolve_languages(&lang)?;
+            let scope = db::SearchScope { kinds: &kinds, languages: &languages, tests: test_filter(no_tests, only_tests), ..db::SearchScope::none() };
+            commands::analysis::cmd_similar(&root, &name, threshold, &scope, limit, format)
+        }
+        Commands::Duplicates { kinds, lang, module, no_tests, only_tests, limit } => {
+            let kinds = parse_kinds(&kinds)?;
+            let languages = parsers::resolve_languages(&lang)?;
+            let scope = db::SearchScope { module: module.as_deref(), kinds: &kinds, languages: &languages, tests: test_filter(no_tests, only_tests), ..db::SearchScope::none() };
+            commands::analysis::cmd_duplicates(&root, &scope, limit, format)
+        }
+        Commands::GodClasses { threshold, lang, module, no_tests, only_tests, limit } => {
+            let languages = parsers::resolve_languages(&lang)?;
+            let scope = db::SearchScope { module: module.as_deref(), languages: &languages, tests: test_filter(no_tests, only_tests), ..db::SearchScope::none() };
+            commands::analysis::cmd_god_classes(&root, threshold, limit, &scope, format)
+        }
+        Commands::UncalledOverrides { lang, module, no_tests, only_tests, limit } => {
+            let languages = parsers::resolve_languages(&lang)?;
+            let scope = db::SearchScope { module: module.as_deref(), languages: &languages, tests: test_filter(no_tests, only_tests), ..db::SearchScope::none() };
+            commands::analysis::cmd_uncalled_overrides(&root, limit, &scope, format)
+        }
+        Commands::Untested { kinds, path, module, lang, limit } => {
+            let kinds = parse_kinds(&kinds)?;
+            let languages = parsers::resolve_languages(&lang)?;
+            let dir = path.map(|p| format!("{}/", p.trim_start_matches("./").trim_end_matches('/')));
+            let scope = db::SearchScope { module: module.as_deref(), dir_prefix: dir.as_deref(), kinds: &kinds, languages: &languages, ..db::SearchScope::none() };
+            commands::analysis::cmd_untested(&root, &scope, page(limit)?, format)
+        }
+        Commands::LintNames { module, path, no_tests, only_tests, limit } => {
+            let dir = path.map(|p| format!("{}/", p.trim_start_matches("./").trim_end_matches('/')));
+            let scope = db::SearchScope { module: module.as_deref(), dir_prefix: dir.as_deref(), tests: test_filter(no_tests, only_tests), ..db::SearchScope::none() };
+            commands::analysis::cmd_lint_names(&root, &project_config.naming_rules(), &scope, limit, format)
+        }
+        Commands::OrphanTests { lang, module } => {
+            let languages = parsers::resolve_languages(&lang)?;
+            let scope = db::SearchScope { module: module.as_deref(), languages: &languages, ..db::SearchScope::none() };
+            commands::analysis::cmd_orphan_tests(&root, &scope, format)
+        }
+        Commands::Complexity { top, sort, lang, module, no_tests, only_tests } => {
+            let languages = parsers::resolve_languages(&lang)?;
+            let scope = db::SearchScope { module: module.as_deref(), languages: &languages, tests: test_filter(no_tests, only_tests), ..db::SearchScope::none() };
+            commands::analysis::cmd_complexity(&root, top, &sort, &scope, format)
         }
         Commands::AddRoot { path, force } => commands::management::cmd_add_root(&root, &path, force),
         Commands::RemoveRoot { path } => commands::management::cmd_remove_root(&root, &path),
         Commands::ListRoots => commands::management::cmd_list_roots(&root),
-        Commands::Watch => commands::watch::cmd_watch(&root),
-        Commands::Clear => commands::management::cmd_clear(&root),
-        Commands::Version => {
-            println!("ast-index v{}", env!("CARGO_PKG_VERSION"));
-            Ok(())
+        Commands::Watch { .. } => commands::watch::cmd_watch(&root),
+        Commands::Fleet { repos, target, jobs, no_merge, no_deps } => {
+            commands::fleet::cmd_fleet(&repos, &target, jobs, no_merge, no_deps)
+        }
+        Commands::Merge { inputs, out } => commands::fleet::cmd_merge(&inputs, &out),
+        Commands::Profiles => commands::management::cmd_profiles(format),
+        Commands::ProfileAdd { name, root: profile_root } => {
+            commands::management::cmd_profile_add(&name, profile_root.as_deref().unwrap_or(&root), cli.db.as_deref())
         }
+        Commands::ProfileRemove { name } => commands::management::cmd_profile_remove(&name),
+        Commands::Clear => commands::management::cmd_clear(&root),
+        Commands::Version { verbose } => commands::management::cmd_version(verbose, format),
         Commands::InstallClaudePlugin => cmd_install_claude_plugin(),
         // Programmatic access
         Commands::Agrep { pattern, lang, json } => commands::grep::cmd_ast_grep(&root, &pattern, lang.as_deref(), json),
         Commands::Query { sql, limit } => commands::management::cmd_query(&root, &sql, limit),
+        Commands::Serve { port, host } => commands::serve::cmd_serve(&root, &host, port),
         Commands::DbPath => commands::management::cmd_db_path(&root),
-        Commands::Schema => commands::management::cmd_schema(&root),
+        Commands::Schema => commands::management::cmd_schema(&root, format),
+        Commands::ExportDataset { output, license, exclude_license, include_unlicensed, max_refs } => {
+            let filter = commands::export::LicenseFilter {
+                allow: &license,
+                deny: &exclude_license,
+                include_unknown: include_unlicensed || license.is_empty(),
+            };
+            commands::export::cmd_export_dataset(&root, cli.format.as_deref(), output.as_deref(), &filter, max_refs)
+        }
+    };
+
+    if let Some(dest) = &cli.save_db {
+        db::save_memory_db(dest)?;
+        eprintln!("Saved the in-memory index to {}", dest.display());
     }
+    result
 }
 
 fn cmd_install_claude_plugin() -> Result<()> {
@@ -801,6 +1605,30 @@ fn cmd_install_claude_plugin() -> Result<()> {
     Ok(())
 }
 
+/// `--kind` values as symbol kinds, listing the known ones on a typo
+fn parse_kinds(names: &[String]) -> Result<Vec<db::SymbolKind>> {
+    names
+        .iter()
+        .map(|name| {
+            db::SymbolKind::from_name(name).ok_or_else(|| {
+                let known: Vec<&str> = db::SymbolKind::ALL.iter().map(|k| k.as_str()).collect();
+                anyhow::anyhow!("Unknown symbol kind '{}' (known: {})", name, known.join(", "))
+            })
+        })
+        .collect()
+}
+
+/// `--no-tests` / `--only-tests` as `SearchScope::tests`
+fn test_filter(no_tests: bool, only_tests: bool) -> Option<bool> {
+    if no_tests {
+        Some(false)
+    } else if only_tests {
+        Some(true)
+    } else {
+        None
+    }
+}
+
 fn find_project_root() -> Result<PathBuf> {
     let cwd = std::env::current_dir()?;
     for ancestor in cwd.ancestors() {
@@ -0,0 +1,402 @@
+//! Annotations, decorators and attributes on declarations
+//!
+//! Collects `@Name(args)` (Kotlin/Java/Scala/Swift/Dart/TypeScript/Python),
+//! `#[name(args)]` (Rust) and `[Name(args)]` (C#) written on the lines above a
+//! symbol or in front of it on its own line. `#[derive(A, B)]` is kept as a
+//! single `derive` annotation with `A, B` as arguments.
+
+use crate::db::SymbolKind;
+use super::{FileType, ParsedSymbol};
+
+/// How a language spells annotations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    /// `@Name` / `@Name(args)`
+    At,
+    /// `#[name]` / `#[name(args)]`
+    RustAttr,
+    /// `[Name]` / `[Name(args), Other]`
+    CSharpAttr,
+}
+
+impl Syntax {
+    fn for_file_type(file_type: FileType) -> Option<Syntax> {
+        match file_type {
+            FileType::Kotlin | FileType::Java | FileType::Scala | FileType::Swift | FileType::Dart
+            | FileType::TypeScript | FileType::Vue | FileType::Svelte | FileType::Python => Some(Syntax::At),
+            FileType::Rust => Some(Syntax::RustAttr),
+            FileType::CSharp => Some(Syntax::CSharpAttr),
+            _ => None,
+        }
+    }
+
+    /// Whether a trimmed line starts an annotation
+    fn starts_line(self, line: &str) -> bool {
+        match self {
+            Syntax::At => line.starts_with('@') && !line.starts_with("@interface"),
+            Syntax::RustAttr => line.starts_with("#["),
+            Syntax::CSharpAttr => line.starts_with('['),
+        }
+    }
+}
+
+/// Lines searched below the reported symbol line for its name (annotations may start the node)
+const NAME_LOOKAHEAD: usize = 6;
+/// Max lines of a multi-line annotation above a declaration
+const MAX_ANNOTATION_LINES: usize = 20;
+
+/// Set `annotations` on every declaration that has them. `ext` selects the syntax.
+pub fn assign_annotations(symbols: &mut [ParsedSymbol], content: &str, ext: &str) {
+    let Some(syntax) = FileType::from_extension(ext).and_then(Syntax::for_file_type) else { return };
+    let lines: Vec<&str> = content.lines().collect();
+
+    for symbol in symbols.iter_mut() {
+        if matches!(symbol.kind, SymbolKind::Import | SymbolKind::Package | SymbolKind::Annotation) {
+            continue;
+        }
+        let Some(start) = symbol.line.checked_sub(1).filter(|&i| i < lines.len()) else { continue };
+        let prefix = declaration_prefix(&lines, symbol.line, &symbol.name);
+        let (inline, reset) = scan(&prefix, syntax);
+        let mut annotations = if reset { Vec::new() } else { annotations_above(&lines, start, syntax) };
+        annotations.extend(inline);
+        annotations.dedup();
+        symbol.annotations = annotations;
+    }
+}
+
+/// Source text in front of a symbol's name, starting at its reported line (1-based).
+/// The name is searched a few lines down since a node may start at its first annotation;
+/// `impl Display for User` / `ns::Foo` are found by their last identifier.
+pub(super) fn declaration_prefix(lines: &[&str], line: usize, name: &str) -> String {
+    let Some(start) = line.checked_sub(1).filter(|&i| i < lines.len()) else { return String::new() };
+    let ident = name
+        .rsplit(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .find(|s| !s.is_empty())
+        .unwrap_or(name);
+    (start..lines.len().min(start + NAME_LOOKAHEAD))
+        .find_map(|i| find_word(lines[i], ident).map(|col| (i, col)))
+        .map(|(i, col)| {
+            let mut text: String = lines[start..i].iter().map(|l| format!("{}\n", l)).collect();
+            text.push_str(&lines[i][..col]);
+            text
+        })
+        .unwrap_or_default()
+}
+
+/// Byte offset of `word` in `text` as a whole identifier
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word).map(|(i, _)| i).find(|&i| {
+        !text[..i].chars().next_back().is_some_and(is_ident)
+            && !text[i + word.len()..].chars().next().is_some_and(is_ident)
+    })
+}
+
+/// Annotations on the lines directly above line index `start`
+fn annotations_above(lines: &[&str], start: usize, syntax: Syntax) -> Vec<(String, Option<String>)> {
+    let mut blocks: Vec<String> = Vec::new();
+    let mut idx = start;
+    while idx > 0 {
+        let prev = lines[idx - 1].trim();
+        if syntax.starts_line(prev) {
+            blocks.push(prev.to_string());
+            idx -= 1;
+            continue;
+        }
+        // Line comments interleaved with attributes (`#[derive]` above `/// docs`)
+        if prev.starts_with("//") {
+            idx -= 1;
+            continue;
+        }
+        // Tail of a multi-line annotation such as `@Component({ ... })`
+        if prev.ends_with(')') || prev.ends_with(']') {
+            let first = (idx.saturating_sub(MAX_ANNOTATION_LINES)..idx - 1).rev().find(|&k| {
+                syntax.starts_line(lines[k].trim()) && is_balanced(&lines[k..idx].join("\n"))
+            });
+            if let Some(first) = first {
+                blocks.push(lines[first..idx].join("\n"));
+                idx = first;
+                continue;
+            }
+        }
+        break;
+    }
+    blocks.reverse();
+    blocks.iter().flat_map(|b| scan(b, syntax).0).collect()
+}
+
+/// Whether parentheses and brackets in `text` are balanced
+fn is_balanced(text: &str) -> bool {
+    let mut depth = 0i32;
+    for c in text.chars() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            return false;
+        }
+    }
+    depth == 0
+}
+
+/// Collect annotations from declaration text. The flag is set when a top-level `(` or `,`
+/// was seen, i.e. the text continues a parameter list and earlier annotations belong to
+/// another declaration (`class Foo(@Inject val bar: Bar)`).
+fn scan(text: &str, syntax: Syntax) -> (Vec<(String, Option<String>)>, bool) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut found = Vec::new();
+    let mut reset = false;
+    let mut angle = 0i32;
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '"' => {
+                i = skip_string(&chars, i);
+                continue;
+            }
+            // Rust lifetimes are not char literals
+            '\'' if syntax != Syntax::RustAttr => {
+                i = skip_string(&chars, i);
+                continue;
+            }
+            '@' if syntax == Syntax::At => {
+                let name_end = (i + 1..chars.len())
+                    .find(|&j| !(chars[j].is_alphanumeric() || matches!(chars[j], '_' | '.' | ':')))
+                    .unwrap_or(chars.len());
+                let raw: String = chars[i + 1..name_end].iter().collect();
+                // Kotlin use-site targets: `@field:Json` -> `Json`
+                let name = raw.rsplit(':').next().unwrap_or_default().to_string();
+                i = name_end;
+                let args = if chars.get(i) == Some(&'(') {
+                    let close = matching(&chars, i);
+                    let inner: String = chars[i + 1..close.min(chars.len())].iter().collect();
+                    i = close + 1;
+                    Some(inner)
+                } else {
+                    None
+                };
+                if !name.is_empty() && name != "interface" {
+                    found.push((name, normalize_args(args)));
+                }
+                continue;
+            }
+            '#' if syntax == Syntax::RustAttr && chars.get(i + 1) == Some(&'[') => {
+                let close = matching(&chars, i + 1);
+                let inner: String = chars[i + 2..close.min(chars.len())].iter().collect();
+                found.extend(split_attributes(&inner, false));
+                i = close + 1;
+                continue;
+            }
+            '[' if syntax == Syntax::CSharpAttr => {
+                let close = matching(&chars, i);
+                let inner: String = chars[i + 1..close.min(chars.len())].iter().collect();
+                found.extend(split_attributes(&inner, true));
+                i = close + 1;
+                continue;
+            }
+            '<' => angle += 1,
+            '>' if angle > 0 => angle -= 1,
+            '(' | ',' if angle == 0 => {
+                found.clear();
+                reset = true;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    (found, reset)
+}
+
+/// Index of the bracket closing the one at `open` (or `chars.len()` if unclosed)
+fn matching(chars: &[char], open: usize) -> usize {
+    let mut depth = 0i32;
+    let mut i = open;
+    while i < chars.len() {
+        match chars[i] {
+            '"' | '\'' => {
+                i = skip_string(chars, i);
+                continue;
+            }
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+/// Index just past the string literal starting at `start`
+fn skip_string(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            c if c == quote => return i + 1,
+            // Rust lifetimes and char literals never span lines
+            '\n' => return i,
+            _ => {}
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Split the inside of `#[...]` or `[...]` into (name, args) pairs.
+/// C# allows several comma-separated attributes and an `assembly:`/`return:` target.
+fn split_attributes(inner: &str, csharp: bool) -> Vec<(String, Option<String>)> {
+    let inner = inner.trim();
+    let inner = match inner.split_once(':') {
+        Some((target, rest)) if csharp && !rest.starts_with(':') && target.chars().all(char::is_alphanumeric) => rest,
+        _ => inner,
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    if csharp {
+        let mut depth = 0i32;
+        let mut last = 0;
+        for (i, c) in inner.char_indices() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                ',' if depth == 0 => {
+                    parts.push(&inner[last..i]);
+                    last = i + 1;
+                }
+                _ => {}
+            }
+        }
+        parts.push(&inner[last..]);
+    } else {
+        parts.push(inner);
+    }
+
+    parts
+        .into_iter()
+        .filter_map(|part| {
+            let part = part.trim();
+            let name_end = part
+                .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | ':')))
+                .unwrap_or(part.len());
+            let name = &part[..name_end];
+            // `[ObsoleteAttribute]` is the same attribute as `[Obsolete]`
+            let name = match name.strip_suffix("Attribute") {
+                Some(short) if csharp && !short.is_empty() && !short.ends_with('.') => short,
+                _ => name,
+            };
+            if name.is_empty() {
+                return None;
+            }
+            let rest = part[name_end..].trim();
+            let args = rest.strip_prefix('(').and_then(|r| r.strip_suffix(')')).map(str::to_string);
+            let args = if name == "derive" {
+                args.map(|a| a.split(',').map(str::trim).filter(|t| !t.is_empty()).collect::<Vec<_>>().join(", "))
+            } else {
+                args
+            };
+            Some((name.to_string(), normalize_args(args)))
+        })
+        .collect()
+}
+
+/// Collapse whitespace in annotation arguments; empty arguments become None
+fn normalize_args(args: Option<String>) -> Option<String> {
+    let args = args?.split_whitespace().collect::<Vec<_>>().join(" ");
+    if args.is_empty() { None } else { Some(args) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sym(name: &str, kind: SymbolKind, line: usize) -> ParsedSymbol {
+        ParsedSymbol {
+            name: name.to_string(),
+            kind,
+            line,
+            signature: String::new(),
+            parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
+        }
+    }
+
+    fn names(symbol: &ParsedSymbol) -> Vec<&str> {
+        symbol.annotations.iter().map(|(n, _)| n.as_str()).collect()
+    }
+
+    #[test]
+    fn test_kotlin_annotations() {
+        let content = "@Entity\n@Table(name = \"users\")\nclass User(@Id val id: Long, val name: String)\n\n@Composable fun Screen() {}\n";
+        let mut symbols = vec![
+            sym("User", SymbolKind::Class, 3),
+            sym("id", SymbolKind::Property, 3),
+            sym("name", SymbolKind::Property, 3),
+            sym("Screen", SymbolKind::Function, 5),
+        ];
+        assign_annotations(&mut symbols, content, "kt");
+        assert_eq!(names(&symbols[0]), vec!["Entity", "Table"]);
+        assert_eq!(symbols[0].annotations[1].1.as_deref(), Some("name = \"users\""));
+        assert_eq!(names(&symbols[1]), vec!["Id"]);
+        assert!(symbols[2].annotations.is_empty());
+        assert_eq!(names(&symbols[3]), vec!["Composable"]);
+    }
+
+    #[test]
+    fn test_multiline_decorator() {
+        let content = "@Component({\n  selector: 'app-root',\n})\nexport class AppComponent {}\n";
+        let mut symbols = vec![sym("AppComponent", SymbolKind::Class, 4)];
+        assign_annotations(&mut symbols, content, "ts");
+        assert_eq!(names(&symbols[0]), vec!["Component"]);
+    }
+
+    #[test]
+    fn test_annotation_on_symbol_line() {
+        // Tree-sitter nodes may start at the first annotation
+        let content = "@GetMapping(\"/users\")\npublic List<User> list() {}\n";
+        let mut symbols = vec![sym("list", SymbolKind::Function, 1)];
+        assign_annotations(&mut symbols, content, "java");
+        assert_eq!(names(&symbols[0]), vec!["GetMapping"]);
+    }
+
+    #[test]
+    fn test_python_decorators() {
+        let content = "@pytest.fixture(scope=\"session\")\ndef db():\n    pass\n";
+        let mut symbols = vec![sym("db", SymbolKind::Function, 2)];
+        assign_annotations(&mut symbols, content, "py");
+        assert_eq!(symbols[0].annotations, vec![("pytest.fixture".to_string(), Some("scope=\"session\"".to_string()))]);
+    }
+
+    #[test]
+    fn test_rust_and_csharp_attributes() {
+        let rust = "#[derive(Debug,\n    Serialize)]\n/// A user\n#[serde(rename_all = \"camelCase\")]\npub struct User {}\n";
+        let mut symbols = vec![sym("User", SymbolKind::Class, 5)];
+        assign_annotations(&mut symbols, rust, "rs");
+        assert_eq!(names(&symbols[0]), vec!["derive", "serde"]);
+        assert_eq!(symbols[0].annotations[0].1.as_deref(), Some("Debug, Serialize"));
+
+        let cs = "[HttpGet(\"{id}\"), Authorize]\npublic IActionResult Get(int id) {}\n";
+        let mut symbols = vec![sym("Get", SymbolKind::Function, 2)];
+        assign_annotations(&mut symbols, cs, "cs");
+        assert_eq!(names(&symbols[0]), vec!["HttpGet", "Authorize"]);
+
+        let cs = "[ApiController]\n[Route(\"api/items\",\n    Name = \"items\")]\n[ObsoleteAttribute, Produces(\"application/json\")]\npublic partial class ItemsController {}\n";
+        let mut symbols = vec![sym("ItemsController", SymbolKind::Class, 5)];
+        assign_annotations(&mut symbols, cs, "cs");
+        assert_eq!(names(&symbols[0]), vec!["ApiController", "Route", "Obsolete", "Produces"]);
+    }
+}
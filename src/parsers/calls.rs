@@ -0,0 +1,147 @@
+//! Call edges for the `calls` table
+//!
+//! A call is a name followed by `(` in code, with strings and comments blanked out the way
+//! `span` does for brace matching. It belongs to the innermost function whose span
+//! (`line..=end_line`) contains it, so calls inside lambdas count for the enclosing function;
+//! calls outside any function (field initializers, top-level scripts) are left out. Unlike refs,
+//! names declared in the same file are kept: most calls go to neighbouring functions.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::span::{code_lines, function_spans, innermost_function};
+use super::ParsedSymbol;
+
+/// A call from one of the file's functions
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedCall {
+    /// Index of the calling function in the file's symbols
+    pub caller: usize,
+    /// Called name as written (`process` for `service.process(`)
+    pub callee: String,
+    pub line: usize,
+}
+
+static CALL_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"([\p{L}_][\p{L}\p{N}_]*)\s*\(").unwrap());
+
+/// Control-flow and declaration words that precede `(` without being calls
+const NOT_CALLS: &[&str] = &[
+    "if", "else", "elif", "when", "while", "until", "unless", "for", "foreach", "do", "switch", "case",
+    "catch", "return", "throw", "throws", "try", "yield", "await", "guard", "match", "with", "assert",
+    "sizeof", "typeof", "alignof", "decltype", "defined", "fun", "func", "fn", "def", "sub", "function",
+    "lambda", "new", "delete", "and", "or", "not", "is", "in", "as", "this", "super", "self", "init",
+    "constructor", "synchronized", "using", "lock", "fixed", "elseif",
+];
+
+/// Calls made by the functions in `symbols`; symbols need their spans (`span::assign_spans`)
+pub fn extract_calls(content: &str, symbols: &[ParsedSymbol], ext: &str) -> Vec<ParsedCall> {
+    let functions = function_spans(symbols);
+    if functions.is_empty() {
+        return vec![];
+    }
+
+    let mut calls = Vec::new();
+    for (idx, code) in code_lines(content, ext).iter().enumerate() {
+        let line = idx + 1;
+        let Some(caller) = innermost_function(&functions, line) else { continue };
+        // A function's own name on its header line is the declaration, not a call
+        let mut declared: Vec<&str> = symbols.iter().filter(|s| s.line == line).map(|s| s.name.as_str()).collect();
+        for caps in CALL_RE.captures_iter(code) {
+            let Some(m) = caps.get(1) else { continue };
+            let name = m.as_str();
+            if code[..m.start()].ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                continue;
+            }
+            if let Some(pos) = declared.iter().position(|d| *d == name) {
+                declared.remove(pos);
+                continue;
+            }
+            if NOT_CALLS.contains(&name) || name.chars().all(|c| c == '_') {
+                continue;
+            }
+            calls.push(ParsedCall { caller, callee: name.to_string(), line });
+        }
+    }
+    calls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::SymbolKind;
+
+    fn function(name: &str, line: usize, end_line: usize) -> ParsedSymbol {
+        ParsedSymbol {
+            name: name.to_string(),
+            kind: SymbolKind::Function,
+            line,
+            signature: format!("fun {}()", name),
+            parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: Some(end_line),
+            byte_range: None,
+            condition: None,
+            generated_by: None,
+        }
+    }
+
+    fn edges(content: &str, symbols: &[ParsedSymbol], ext: &str) -> Vec<(String, String, usize)> {
+        extract_calls(content, symbols, ext)
+            .into_iter()
+            .map(|c| (symbols[c.caller].name.clone(), c.callee, c.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_calls_by_enclosing_function() {
+        let content = "\
+fun process(order: Order) {
+    validate(order)
+    if (order.total > 0) {
+        charge(order) // retry(order)
+    }
+    items.forEach { save(it) }
+    log(\"process(done)\")
+}
+
+fun validate(order: Order) = check(order)
+
+val config = load()
+";
+        let symbols = vec![function("process", 1, 8), function("validate", 10, 10)];
+        assert_eq!(
+            edges(content, &symbols, "kt"),
+            vec![
+                ("process".to_string(), "validate".to_string(), 2),
+                ("process".to_string(), "charge".to_string(), 4),
+                ("process".to_string(), "save".to_string(), 6),
+                ("process".to_string(), "log".to_string(), 7),
+                ("validate".to_string(), "check".to_string(), 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_and_recursive_calls() {
+        let content = "\
+def outer(n):
+    def inner(x):
+        return helper(x)  # helper(y) in a comment
+    return outer(n - 1) + inner(n)
+";
+        let symbols = vec![function("outer", 1, 4), function("inner", 2, 3)];
+        assert_eq!(
+            edges(content, &symbols, "py"),
+            vec![
+                ("inner".to_string(), "helper".to_string(), 3),
+                ("outer".to_string(), "outer".to_string(), 4),
+                ("outer".to_string(), "inner".to_string(), 4),
+            ]
+        );
+    }
+}
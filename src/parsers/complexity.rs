@@ -0,0 +1,120 @@
+//! Function complexity
+//!
+//! Approximate cyclomatic complexity: 1 plus the branch keywords (`if`, loops, `case`/`when`
+//! arms, `catch`/`except`/`rescue`, `guard`) and short-circuit operators in the function's
+//! span, with strings and comments blanked out. Branches in a nested function count for the
+//! nested function only; lambdas count for the function they are written in.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::span::{code_lines, function_spans, innermost_function};
+use super::{FileType, ParsedSymbol};
+
+static BRANCH_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:if|elif|elsif|for|foreach|while|until|unless|case|when|catch|except|rescue|guard)\b|&&|\|\|")
+        .unwrap()
+});
+
+/// `and`/`or` are boolean operators only where they are keywords (Kotlin's `or` is bitwise)
+static WORD_OPERATOR_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(?:and|or)\b").unwrap());
+
+/// Complexity of each symbol, aligned with `symbols`: Some for functions with a span
+pub fn function_complexity(content: &str, symbols: &[ParsedSymbol], ext: &str) -> Vec<Option<u32>> {
+    let mut complexity = vec![None; symbols.len()];
+    let functions = function_spans(symbols);
+    if functions.is_empty() {
+        return complexity;
+    }
+    for &(i, _, _) in &functions {
+        complexity[i] = Some(1);
+    }
+
+    let word_operators = matches!(
+        FileType::from_extension(ext),
+        Some(FileType::Python | FileType::Ruby | FileType::Perl)
+    );
+    for (idx, code) in code_lines(content, ext).iter().enumerate() {
+        let Some(function) = innermost_function(&functions, idx + 1) else { continue };
+        let mut branches = BRANCH_RE.find_iter(code).count();
+        if word_operators {
+            branches += WORD_OPERATOR_RE.find_iter(code).count();
+        }
+        if let Some(value) = complexity[function].as_mut() {
+            *value += branches as u32;
+        }
+    }
+    complexity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::SymbolKind;
+
+    fn symbol(name: &str, kind: SymbolKind, line: usize, end_line: usize) -> ParsedSymbol {
+        ParsedSymbol {
+            name: name.to_string(),
+            kind,
+            line,
+            signature: name.to_string(),
+            parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: Some(end_line),
+            byte_range: None,
+            condition: None,
+            generated_by: None,
+        }
+    }
+
+    #[test]
+    fn test_branches_by_enclosing_function() {
+        let content = "\
+class Checkout {
+    fun total(order: Order): Int {
+        if (order.items.isEmpty() || order.cancelled) return 0
+        for (item in order.items) {
+            when (item.kind) {
+                Kind.GIFT -> continue
+                else -> sum += item.price // if the price is missing
+            }
+        }
+        log(\"while summing\")
+        return sum
+    }
+
+    fun empty() = 0
+}
+";
+        let symbols = vec![
+            symbol("Checkout", SymbolKind::Class, 1, 15),
+            symbol("total", SymbolKind::Function, 2, 12),
+            symbol("empty", SymbolKind::Function, 14, 14),
+        ];
+        assert_eq!(function_complexity(content, &symbols, "kt"), vec![None, Some(5), Some(1)]);
+    }
+
+    #[test]
+    fn test_word_operators_and_nested_functions() {
+        let content = "\
+def parse(text):
+    def clean(line):
+        return line.strip() if line else ''
+    try:
+        if text and not text.startswith('#'):
+            return [clean(l) for l in text.splitlines()]
+    except ValueError:
+        return []
+";
+        let symbols = vec![
+            symbol("parse", SymbolKind::Function, 1, 8),
+            symbol("clean", SymbolKind::Function, 2, 3),
+        ];
+        assert_eq!(function_complexity(content, &symbols, "py"), vec![Some(5), Some(2)]);
+    }
+}
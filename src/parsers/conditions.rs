@@ -0,0 +1,281 @@
+//! Conditional compilation
+//!
+//! Declarations inside `#if DEBUG` / `#ifdef` / `#else` blocks (C, C++, Objective-C, Swift, C#)
+//! and Rust items under `#[cfg(...)]` get a condition in Rust `cfg` syntax: `DEBUG`,
+//! `not(DEBUG)`, `all(os(iOS), not(TARGET_SIMULATOR))`, `feature = "serde"`.
+//! `is_active` evaluates a condition against the configuration given with `--active-cfg`.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::span;
+use super::{FileType, ParsedSymbol};
+
+/// `#[cfg(...)]`, possibly followed by the item on the same line
+static CFG_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*#\[cfg\((.*?)\)\]\s*(.*)$").unwrap());
+static INNER_CFG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*#!\[cfg\((.*)\)\]\s*$").unwrap());
+static DIRECTIVE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*#\s*(if|ifdef|ifndef|elif|elseif|else|endif)\b(.*)$").unwrap());
+
+/// Set `condition` on symbols declared under conditional compilation
+pub fn assign_conditions(symbols: &mut [ParsedSymbol], content: &str, ext: &str) {
+    let conditions = match FileType::from_extension(ext) {
+        Some(FileType::Rust) => rust_conditions(content),
+        Some(FileType::Cpp | FileType::ObjC | FileType::Swift | FileType::CSharp) => preprocessor_conditions(content),
+        _ => return,
+    };
+    for symbol in symbols.iter_mut() {
+        if let Some(Some(condition)) = symbol.line.checked_sub(1).and_then(|i| conditions.get(i)) {
+            symbol.condition = Some(condition.clone());
+        }
+    }
+}
+
+/// Condition of every line (index = line - 1) from `#if`/`#ifdef`/`#elif`/`#else`/`#endif`
+pub fn preprocessor_conditions(content: &str) -> Vec<Option<String>> {
+    let lines: Vec<&str> = content.lines().collect();
+    // Per open block: condition of the current branch (None for include guards) and the
+    // conditions of all branches so far, negated by the following `#elif`/`#else`
+    let mut stack: Vec<(Option<String>, Vec<String>)> = Vec::new();
+    let mut result = Vec::with_capacity(lines.len());
+
+    for (idx, line) in lines.iter().enumerate() {
+        if let Some(caps) = DIRECTIVE_RE.captures(line) {
+            let expr = strip_comment(&caps[2]);
+            let opened = match &caps[1] {
+                "if" => Some(normalize_expr(expr)),
+                "ifdef" => Some(expr.to_string()),
+                "ifndef" if is_include_guard(&lines, idx, expr) => None,
+                "ifndef" => Some(format!("not({})", expr)),
+                "endif" => {
+                    stack.pop();
+                    None
+                }
+                branch => {
+                    if let Some((current, branches)) = stack.last_mut().filter(|(_, b)| !b.is_empty()) {
+                        let mut parts: Vec<String> = branches.iter().map(|c| negate(c)).collect();
+                        if branch != "else" {
+                            let condition = normalize_expr(expr);
+                            branches.push(condition.clone());
+                            parts.push(condition);
+                        }
+                        *current = Some(join_all(parts));
+                    }
+                    None
+                }
+            };
+            if matches!(&caps[1], "if" | "ifdef" | "ifndef") {
+                stack.push((opened.clone(), opened.into_iter().collect()));
+            }
+            result.push(None);
+            continue;
+        }
+        result.push(combine(stack.iter().filter_map(|(c, _)| c.clone())));
+    }
+    result
+}
+
+/// `#ifndef FOO_H` directly followed by `#define FOO_H`
+fn is_include_guard(lines: &[&str], idx: usize, name: &str) -> bool {
+    let next = lines[idx + 1..].iter().map(|l| l.trim()).find(|l| !l.is_empty());
+    next.is_some_and(|l| {
+        let mut words = l.trim_start_matches('#').split_whitespace();
+        l.starts_with('#') && words.next() == Some("define") && words.next() == Some(name)
+    })
+}
+
+/// Condition of every line (index = line - 1) from `#[cfg(...)]` items and a file-level `#![cfg(...)]`
+fn rust_conditions(content: &str) -> Vec<Option<String>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut per_line: Vec<Vec<String>> = vec![Vec::new(); lines.len()];
+
+    for (idx, line) in lines.iter().enumerate() {
+        if let Some(caps) = INNER_CFG_RE.captures(line) {
+            let condition = normalize_cfg(&caps[1]);
+            per_line.iter_mut().for_each(|conds| conds.push(condition.clone()));
+            continue;
+        }
+        let Some(caps) = CFG_ATTR_RE.captures(line) else { continue };
+        // The item follows its attributes
+        let Some(item) = (idx..lines.len()).find(|&i| {
+            let t = if i == idx { caps[2].trim() } else { lines[i].trim() };
+            !t.is_empty() && !t.starts_with("#[") && !t.starts_with("//")
+        }) else {
+            continue;
+        };
+        let end = span::end_line(&lines, item + 1, Some(FileType::Rust));
+        let condition = normalize_cfg(&caps[1]);
+        for conds in per_line.iter_mut().take(end.min(lines.len())).skip(item) {
+            conds.push(condition.clone());
+        }
+    }
+    per_line.into_iter().map(combine).collect()
+}
+
+fn combine(conditions: impl IntoIterator<Item = String>) -> Option<String> {
+    let conditions: Vec<String> = conditions.into_iter().collect();
+    (!conditions.is_empty()).then(|| join_all(conditions))
+}
+
+fn join_all(mut parts: Vec<String>) -> String {
+    if parts.len() == 1 {
+        parts.pop().unwrap_or_default()
+    } else {
+        format!("all({})", parts.join(", "))
+    }
+}
+
+fn negate(condition: &str) -> String {
+    match condition.strip_prefix("not(").and_then(|c| c.strip_suffix(')')) {
+        Some(inner) if split_top(inner, ',').len() == 1 => inner.to_string(),
+        _ => format!("not({})", condition),
+    }
+}
+
+fn strip_comment(text: &str) -> &str {
+    let end = [text.find("//"), text.find("/*")].into_iter().flatten().min().unwrap_or(text.len());
+    text[..end].trim()
+}
+
+/// Rust cfg predicate with whitespace normalized: `feature="serde"` → `feature = "serde"`
+fn normalize_cfg(text: &str) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    collapsed.replace(" = ", "=").replace('=', " = ").replace("( ", "(").replace(" )", ")")
+}
+
+/// C-style `#if` expression in cfg syntax: `defined(A) && !B` → `all(A, not(B))`
+pub fn normalize_expr(expr: &str) -> String {
+    let expr = expr.trim();
+    let stripped = strip_parens(expr);
+    if stripped != expr {
+        return normalize_expr(stripped);
+    }
+    for (op, func) in [("||", "any"), ("&&", "all")] {
+        let parts = split_top_str(expr, op);
+        if parts.len() > 1 {
+            return format!("{}({})", func, parts.iter().map(|p| normalize_expr(p)).collect::<Vec<_>>().join(", "));
+        }
+    }
+    if let Some(rest) = expr.strip_prefix('!') {
+        return negate(&normalize_expr(rest));
+    }
+    if let Some(rest) = expr.strip_prefix("defined").filter(|r| r.starts_with(['(', ' '])) {
+        let name = strip_parens(rest.trim());
+        if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return name.to_string();
+        }
+    }
+    expr.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `(x)` → `x` when the parentheses enclose the whole expression
+fn strip_parens(expr: &str) -> &str {
+    let Some(inner) = expr.strip_prefix('(').and_then(|e| e.strip_suffix(')')) else { return expr };
+    let mut depth = 0i32;
+    for c in inner.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            return expr;
+        }
+    }
+    inner.trim()
+}
+
+/// Split on a separator outside parentheses
+fn split_top_str<'a>(text: &'a str, sep: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            _ if depth == 0 && text[i..].starts_with(sep) => {
+                parts.push(text[start..i].trim());
+                i += sep.len();
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(text[start..].trim());
+    parts
+}
+
+fn split_top(text: &str, sep: char) -> Vec<&str> {
+    split_top_str(text, sep.encode_utf8(&mut [0; 4]))
+}
+
+/// Whether `condition` (cfg syntax) holds when exactly the names in `active` are set.
+/// Names compare after whitespace normalization (`feature="x"` matches `feature = "x"`).
+pub fn is_active(condition: &str, active: &[String]) -> bool {
+    let condition = condition.trim();
+    for (func, all) in [("all(", true), ("any(", false)] {
+        if let Some(inner) = condition.strip_prefix(func).and_then(|c| c.strip_suffix(')')) {
+            let mut parts = split_top(inner, ',').into_iter().filter(|p| !p.is_empty());
+            return if all { parts.all(|p| is_active(p, active)) } else { parts.any(|p| is_active(p, active)) };
+        }
+    }
+    if let Some(inner) = condition.strip_prefix("not(").and_then(|c| c.strip_suffix(')')) {
+        return !is_active(inner, active);
+    }
+    match condition {
+        "1" | "true" => true,
+        "0" | "false" => false,
+        _ => {
+            let name = normalize_cfg(condition);
+            active.iter().any(|a| normalize_cfg(a) == name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{parse_file_symbols, FileType};
+
+    fn condition_of(symbols: &[ParsedSymbol], name: &str) -> Option<String> {
+        symbols.iter().find(|s| s.name == name).and_then(|s| s.condition.clone())
+    }
+
+    #[test]
+    fn test_preprocessor_blocks() {
+        let code = "#ifndef API_H\n#define API_H\nvoid always();\n#if DEBUG\nvoid dump();\n#elif defined(STAGING) && !NO_LOG\nvoid stage();\n#else\nvoid release();\n#endif\n#ifdef USE_GL\n#if 0\nvoid old();\n#endif\n#endif\n#endif\n";
+        let lines = preprocessor_conditions(code);
+        assert_eq!(lines[2], None, "include guards are not conditions");
+        assert_eq!(lines[4].as_deref(), Some("DEBUG"));
+        assert_eq!(lines[6].as_deref(), Some("all(not(DEBUG), all(STAGING, not(NO_LOG)))"));
+        assert_eq!(lines[8].as_deref(), Some("all(not(DEBUG), not(all(STAGING, not(NO_LOG))))"));
+        assert_eq!(lines[12].as_deref(), Some("all(USE_GL, 0)"));
+    }
+
+    #[test]
+    fn test_rust_cfg_items() {
+        let code = "pub fn run() {}\n\n#[cfg(feature=\"serde\")]\npub fn to_json() {}\n\n#[cfg(test)]\nmod tests {\n    fn helper() {}\n}\n";
+        let (mut symbols, _) = parse_file_symbols(code, FileType::Rust).unwrap();
+        assign_conditions(&mut symbols, code, "rs");
+        assert_eq!(condition_of(&symbols, "run"), None);
+        assert_eq!(condition_of(&symbols, "to_json").as_deref(), Some("feature = \"serde\""));
+        assert_eq!(condition_of(&symbols, "helper").as_deref(), Some("test"));
+    }
+
+    #[test]
+    fn test_is_active() {
+        let active = vec!["DEBUG".to_string(), "feature=\"serde\"".to_string()];
+        assert!(is_active("DEBUG", &active));
+        assert!(!is_active("not(DEBUG)", &active));
+        assert!(is_active("feature = \"serde\"", &active));
+        assert!(is_active("any(test, DEBUG)", &active));
+        assert!(!is_active("all(DEBUG, test)", &active));
+        assert!(is_active("all(not(test), 1)", &active));
+        assert!(!is_active("0", &active));
+    }
+}
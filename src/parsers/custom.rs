@@ -0,0 +1,197 @@
+//! User-defined regex parsers declared in `.ast-index.toml`
+//!
+//! Parsers are compiled once at startup via `register()` and looked up by
+//! file extension, the same way built-in parsers are selected by `FileType`.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::config::CustomParserConfig;
+use crate::db::SymbolKind;
+use super::ParsedSymbol;
+
+/// A compiled custom parser
+pub struct CustomParser {
+    pub name: String,
+    pub extensions: Vec<String>,
+    rules: Vec<CompiledRule>,
+}
+
+struct CompiledRule {
+    regex: Regex,
+    kind: SymbolKind,
+    kinds: HashMap<String, SymbolKind>,
+}
+
+static CUSTOM_PARSERS: OnceLock<Vec<CustomParser>> = OnceLock::new();
+
+/// Kind named in config, accepting a few common aliases (`struct`, `method`, `const`, ...)
+pub(super) fn kind_from_config(name: &str) -> Option<SymbolKind> {
+    match name.to_lowercase().as_str() {
+        "struct" | "trait" => Some(SymbolKind::Class),
+        "protocol" => Some(SymbolKind::Interface),
+        "method" | "func" | "fn" => Some(SymbolKind::Function),
+        "field" | "var" | "val" => Some(SymbolKind::Property),
+        "const" => Some(SymbolKind::Constant),
+        "module" | "namespace" => Some(SymbolKind::Package),
+        other => SymbolKind::from_name(other),
+    }
+}
+
+/// Parse a kind name from config, accepting a few common aliases
+fn parse_kind(name: &str, parser: &str) -> Result<SymbolKind> {
+    kind_from_config(name).ok_or_else(|| anyhow::anyhow!("parser '{}': unknown symbol kind '{}'", parser, name))
+}
+
+/// Compile parser definitions from config
+pub fn compile(configs: &[CustomParserConfig]) -> Result<Vec<CustomParser>> {
+    let mut parsers = Vec::with_capacity(configs.len());
+    for (i, cfg) in configs.iter().enumerate() {
+        let name = cfg.name.clone().unwrap_or_else(|| format!("parsers[{}]", i));
+        let mut rules = Vec::with_capacity(cfg.rules.len());
+        for rule in &cfg.rules {
+            let regex = Regex::new(&rule.regex)
+                .with_context(|| format!("parser '{}': invalid regex {:?}", name, rule.regex))?;
+            if !regex.capture_names().any(|n| n == Some("name")) {
+                anyhow::bail!("parser '{}': regex {:?} has no (?P<name>...) group", name, rule.regex);
+            }
+            let kinds = rule
+                .kinds
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), parse_kind(v, &name)?)))
+                .collect::<Result<HashMap<_, _>>>()?;
+            rules.push(CompiledRule { regex, kind: parse_kind(&rule.kind, &name)?, kinds });
+        }
+        let extensions = cfg
+            .extensions
+            .iter()
+            .map(|e| e.trim_start_matches('.').to_lowercase())
+            .collect();
+        parsers.push(CustomParser { name, extensions, rules });
+    }
+    Ok(parsers)
+}
+
+/// Compile and install custom parsers for this process. Later calls are ignored.
+pub fn register(configs: &[CustomParserConfig]) -> Result<()> {
+    let parsers = compile(configs)?;
+    let _ = CUSTOM_PARSERS.set(parsers);
+    Ok(())
+}
+
+/// All registered custom parsers
+pub fn registered() -> &'static [CustomParser] {
+    CUSTOM_PARSERS.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// Find a registered custom parser for a file extension
+pub fn find(ext: &str) -> Option<&'static CustomParser> {
+    registered()
+        .iter()
+        .find(|p| p.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+}
+
+impl CustomParser {
+    /// Extract symbols line by line using the configured rules
+    pub fn parse_symbols(&self, content: &str) -> Vec<ParsedSymbol> {
+        let mut symbols = Vec::new();
+        for (line_num, line) in content.lines().enumerate() {
+            for rule in &self.rules {
+                for caps in rule.regex.captures_iter(line) {
+                    let Some(name) = caps.name("name") else { continue };
+                    let kind = caps
+                        .name("kind")
+                        .and_then(|k| rule.kinds.get(k.as_str()))
+                        .copied()
+                        .unwrap_or(rule.kind);
+                    let signature = caps
+                        .name("signature")
+                        .map(|s| s.as_str())
+                        .unwrap_or(line)
+                        .trim()
+                        .to_string();
+                    symbols.push(ParsedSymbol {
+                        name: name.as_str().to_string(),
+                        kind,
+                        line: line_num + 1,
+                        signature,
+                        parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
+                    });
+                }
+            }
+        }
+        symbols
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn routes_parser() -> CustomParser {
+        let config = Config::parse(r#"
+[[parsers]]
+name = "routes"
+extensions = [".routes"]
+
+[[parsers.rules]]
+regex = '^(?P<kind>GET|POST)\s+(?P<name>/\S*)'
+kind = "function"
+kinds = { POST = "property" }
+
+[[parsers.rules]]
+regex = '^flag\s+(?P<name>\w+)'
+"#).unwrap();
+        compile(&config.parsers).unwrap().remove(0)
+    }
+
+    #[test]
+    fn test_custom_parser_symbols() {
+        let parser = routes_parser();
+        assert_eq!(parser.extensions, vec!["routes"]);
+        let symbols = parser.parse_symbols("GET /users\nPOST /users/new\n\nflag beta_search\n");
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols[0].name, "/users");
+        assert_eq!(symbols[0].kind, SymbolKind::Function);
+        assert_eq!(symbols[1].kind, SymbolKind::Property);
+        assert_eq!(symbols[2].name, "beta_search");
+        assert_eq!(symbols[2].kind, SymbolKind::Constant);
+        assert_eq!(symbols[2].line, 4);
+    }
+
+    #[test]
+    fn test_custom_parser_requires_name_group() {
+        let config = Config::parse(r#"
+[[parsers]]
+extensions = ["x"]
+[[parsers.rules]]
+regex = 'flag (\w+)'
+"#).unwrap();
+        assert!(compile(&config.parsers).is_err());
+    }
+
+    #[test]
+    fn test_custom_parser_unknown_kind() {
+        let config = Config::parse(r#"
+[[parsers]]
+extensions = ["x"]
+[[parsers.rules]]
+regex = 'flag (?P<name>\w+)'
+kind = "gizmo"
+"#).unwrap();
+        assert!(compile(&config.parsers).is_err());
+    }
+}
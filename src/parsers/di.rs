@@ -0,0 +1,447 @@
+//! Dependency injection bindings for the `di_bindings` table
+//!
+//! Kotlin and Java declarations that provide a type to a DI container or have one injected:
+//! - Dagger/Hilt: `@Provides`/`@Binds` functions provide their return type and inject their
+//!   parameters; `@Inject` constructors provide their class and inject their parameters;
+//!   `@Inject` fields and properties are injected
+//! - Spring: `@Bean` functions like `@Provides`; `@Component`, `@Service`, `@Repository`,
+//!   `@Controller`, `@RestController` and `@Configuration` classes provide themselves and inject
+//!   their constructor parameters; `@Autowired` fields are injected
+//! - Koin (files importing `org.koin`): `single`/`factory`/`scoped`/`viewModel`/`worker`
+//!   definitions provide their type argument or the class they construct, `singleOf(::T)` and
+//!   friends provide `T`, `bind<T>()` / `bind T::class` provide `T`; `get<T>()`, `inject<T>()`,
+//!   `viewModel<T>()` and `val x: T by inject()` inject `T`
+//!
+//! Types are simple names without generics or nullability; `Provider<T>`, `Lazy<T>`,
+//! `ObjectProvider<T>` and `Optional<T>` stand for `T`.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::db::SymbolKind;
+use super::signature::{parameters, split_top_level};
+use super::span::code_lines;
+use super::{FileType, ParsedSymbol};
+
+/// Whether a binding makes a type available or consumes it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Provides,
+    Injects,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Provides => "provides",
+            Role::Injects => "injects",
+        }
+    }
+}
+
+/// A type provided to or injected from a DI container
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedBinding {
+    pub role: Role,
+    pub type_name: String,
+    /// How, as written (`@Provides`, `@Inject constructor`, `koin single`, `@Autowired`, ...)
+    pub mechanism: &'static str,
+    /// The providing function, class or Koin module; for injections the dependent type
+    pub symbol: Option<String>,
+    pub line: usize,
+}
+
+const SPRING_STEREOTYPES: &[&str] = &["Component", "Service", "Repository", "Controller", "RestController", "Configuration"];
+/// Wrappers that inject the wrapped type lazily
+const WRAPPERS: &[&str] = &["Provider", "Lazy", "ObjectProvider", "Optional"];
+/// Values (usually configuration) rather than container-managed types
+const VALUE_TYPES: &[&str] = &[
+    "Any", "Boolean", "Byte", "Char", "Character", "Double", "Float", "Int", "Integer", "Long", "Object", "Short", "String", "Unit",
+];
+
+static KOIN_DEFINITION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(single|factory|scoped|viewModel|worker)\s*(?:<\s*([\w.]+)[^>{]*>)?\s*(?:\([^)]*\))?\s*\{\s*(?:[\w,\s]+->\s*)?([\p{Lu}][\w.]*)?")
+        .unwrap()
+});
+static KOIN_CONSTRUCTOR_DSL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(singleOf|factoryOf|scopedOf|viewModelOf|workerOf)\s*\(\s*::([\p{Lu}][\w.]*)").unwrap());
+static KOIN_BIND_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bbind\s*(?:<\s*([\p{Lu}][\w.]*)|\s+([\p{Lu}][\w.]*)::class)").unwrap());
+static KOIN_GET_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:get|inject|koinInject|koinViewModel|getViewModel|viewModel|activityViewModel|sharedViewModel)\s*<\s*([\p{Lu}][\w.]*)[^>]*>\s*\(")
+        .unwrap()
+});
+static KOIN_BY_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:val|var)\s+\w+\s*:\s*([\p{Lu}][\w.]*)(?:<[^>]*>)?\??\s*by\s+(?:inject|viewModel|activityViewModel|sharedViewModel)\s*\(")
+        .unwrap()
+});
+
+/// DI bindings declared in a Kotlin or Java file; symbols need their annotations, signatures
+/// and spans
+pub fn extract_bindings(content: &str, symbols: &[ParsedSymbol], ext: &str) -> Vec<ParsedBinding> {
+    let file_type = FileType::from_extension(ext);
+    if !matches!(file_type, Some(FileType::Kotlin) | Some(FileType::Java)) {
+        return vec![];
+    }
+    let mut bindings = Vec::new();
+    for symbol in symbols {
+        declaration_bindings(symbol, symbols, file_type == Some(FileType::Kotlin), &mut bindings);
+    }
+    if file_type == Some(FileType::Kotlin) && content.contains("org.koin") {
+        koin_bindings(content, symbols, ext, &mut bindings);
+    }
+    bindings
+}
+
+fn has_annotation(symbol: &ParsedSymbol, names: &[&str]) -> Option<&'static str> {
+    symbol.annotations.iter().find_map(|(name, _)| {
+        let simple = name.rsplit('.').next().unwrap_or(name);
+        names.iter().find(|n| **n == simple).map(|n| match *n {
+            "Provides" => "@Provides",
+            "Binds" => "@Binds",
+            "Bean" => "@Bean",
+            "Inject" => "@Inject",
+            "Autowired" => "@Autowired",
+            "Component" => "@Component",
+            "Service" => "@Service",
+            "Repository" => "@Repository",
+            "Controller" => "@Controller",
+            "RestController" => "@RestController",
+            _ => "@Configuration",
+        })
+    })
+}
+
+fn declaration_bindings(symbol: &ParsedSymbol, symbols: &[ParsedSymbol], kotlin: bool, out: &mut Vec<ParsedBinding>) {
+    let binding = |role, type_name: String, mechanism, owner: Option<&str>| ParsedBinding {
+        role,
+        type_name,
+        mechanism,
+        symbol: owner.map(str::to_string),
+        line: symbol.line,
+    };
+    let owner_class = symbol.container.as_deref().map(|c| c.rsplit('.').next().unwrap_or(c));
+    let inject_all = |params: Option<String>, mechanism, owner: &str, out: &mut Vec<ParsedBinding>| {
+        for type_name in params.iter().flat_map(|p| parameter_types(p, kotlin)) {
+            out.push(binding(Role::Injects, type_name, mechanism, Some(owner)));
+        }
+    };
+
+    match symbol.kind {
+        SymbolKind::Function => {
+            if let Some(mechanism) = has_annotation(symbol, &["Provides", "Binds", "Bean"]) {
+                let Some(provided) = return_type(&symbol.signature, &symbol.name, kotlin) else { return };
+                out.push(binding(Role::Provides, provided.clone(), mechanism, Some(&symbol.name)));
+                let param_mechanism = match mechanism {
+                    "@Binds" => "@Binds implementation",
+                    "@Bean" => "@Bean parameter",
+                    _ => "@Provides parameter",
+                };
+                inject_all(parameters(&symbol.signature, &symbol.name), param_mechanism, &provided, out);
+            } else if owner_class == Some(symbol.name.as_str()) && has_annotation(symbol, &["Inject", "Autowired"]).is_some() {
+                // Java constructor
+                out.push(binding(Role::Provides, symbol.name.clone(), "@Inject constructor", Some(&symbol.name)));
+                inject_all(parameters(&symbol.signature, &symbol.name), "constructor", &symbol.name, out);
+            }
+        }
+        SymbolKind::Class => {
+            let injected_constructor = kotlin && symbol.signature.contains("@Inject constructor");
+            let stereotype = has_annotation(symbol, SPRING_STEREOTYPES);
+            if injected_constructor {
+                out.push(binding(Role::Provides, symbol.name.clone(), "@Inject constructor", Some(&symbol.name)));
+            } else if let Some(mechanism) = stereotype {
+                out.push(binding(Role::Provides, symbol.name.clone(), mechanism, Some(&symbol.name)));
+            } else {
+                return;
+            }
+            if kotlin {
+                let name = if injected_constructor { "constructor" } else { symbol.name.as_str() };
+                inject_all(parameters(&symbol.signature, name), "constructor", &symbol.name, out);
+            } else {
+                // Spring autowires the constructors of a Java component; `@Inject` ones are handled above
+                for constructor in symbols.iter().filter(|s| {
+                    s.kind == SymbolKind::Function
+                        && s.name == symbol.name
+                        && s.container.as_deref().is_some_and(|c| c.rsplit('.').next() == Some(symbol.name.as_str()))
+                        && has_annotation(s, &["Inject", "Autowired"]).is_none()
+                }) {
+                    for type_name in parameters(&constructor.signature, &constructor.name).iter().flat_map(|p| parameter_types(p, false)) {
+                        out.push(ParsedBinding {
+                            role: Role::Injects,
+                            type_name,
+                            mechanism: "constructor",
+                            symbol: Some(symbol.name.clone()),
+                            line: constructor.line,
+                        });
+                    }
+                }
+            }
+        }
+        SymbolKind::Property => {
+            if let Some(mechanism) = has_annotation(symbol, &["Inject", "Autowired"]) {
+                let declared = if kotlin {
+                    symbol.signature.split_once(':').map(|(_, t)| t.split('=').next().unwrap_or(t).trim())
+                } else {
+                    symbol.signature.split('=').next().and_then(|d| d.trim().rsplit_once(char::is_whitespace)).map(|(t, _)| t)
+                };
+                if let Some(type_name) = declared.and_then(|t| simple_type(strip_modifiers(t))) {
+                    let mechanism = if mechanism == "@Inject" { "@Inject field" } else { "@Autowired field" };
+                    out.push(binding(Role::Injects, type_name, mechanism, owner_class));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn koin_bindings(content: &str, symbols: &[ParsedSymbol], ext: &str, out: &mut Vec<ParsedBinding>) {
+    // Innermost declaration around a line, and the innermost type for injections
+    let enclosing = |line: usize, types_only: bool| -> Option<String> {
+        symbols
+            .iter()
+            .filter(|s| s.line <= line && s.end_line.unwrap_or(s.line) >= line)
+            .filter(|s| !types_only || matches!(s.kind, SymbolKind::Class | SymbolKind::Object))
+            .max_by_key(|s| s.line)
+            .map(|s| s.name.clone())
+    };
+    for (idx, code) in code_lines(content, ext).iter().enumerate() {
+        let line = idx + 1;
+        let mut provide = |type_name: &str, mechanism| {
+            if let Some(type_name) = simple_type(type_name) {
+                out.push(ParsedBinding { role: Role::Provides, type_name, mechanism, symbol: enclosing(line, false), line });
+            }
+        };
+        for caps in KOIN_DEFINITION_RE.captures_iter(code) {
+            let mechanism = match &caps[1] {
+                "single" => "koin single",
+                "factory" => "koin factory",
+                "scoped" => "koin scoped",
+                "viewModel" => "koin viewModel",
+                _ => "koin worker",
+            };
+            if let Some(provided) = caps.get(2).or(caps.get(3)) {
+                provide(provided.as_str(), mechanism);
+            }
+        }
+        for caps in KOIN_CONSTRUCTOR_DSL_RE.captures_iter(code) {
+            let mechanism = match &caps[1] {
+                "singleOf" => "koin singleOf",
+                "factoryOf" => "koin factoryOf",
+                "scopedOf" => "koin scopedOf",
+                "viewModelOf" => "koin viewModelOf",
+                _ => "koin workerOf",
+            };
+            provide(&caps[2], mechanism);
+        }
+        for caps in KOIN_BIND_RE.captures_iter(code) {
+            if let Some(bound) = caps.get(1).or(caps.get(2)) {
+                provide(bound.as_str(), "koin bind");
+            }
+        }
+        for caps in KOIN_GET_RE.captures_iter(code).chain(KOIN_BY_RE.captures_iter(code)) {
+            if let Some(type_name) = simple_type(&caps[1]) {
+                out.push(ParsedBinding { role: Role::Injects, type_name, mechanism: "koin get", symbol: enclosing(line, true), line });
+            }
+        }
+    }
+}
+
+/// Types of a parameter list: `repo: Repo, api: Provider<Api>` (Kotlin) or `Repo repo, final Api api` (Java)
+fn parameter_types(params: &str, kotlin: bool) -> Vec<String> {
+    split_top_level(params)
+        .into_iter()
+        .filter_map(|param| {
+            let param = param.split('=').next().unwrap_or(param).trim();
+            let declared = if kotlin {
+                param.split_once(':').map(|(_, t)| t)?
+            } else {
+                param.rsplit_once(char::is_whitespace).map(|(t, _)| t)?
+            };
+            simple_type(strip_modifiers(declared))
+        })
+        .collect()
+}
+
+/// Declared type without annotations and modifiers in front (`@Named("a") final Api` → `Api`)
+fn strip_modifiers(text: &str) -> &str {
+    let mut rest = text.trim();
+    loop {
+        if let Some(after) = rest.strip_prefix('@') {
+            let end = after.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.')).unwrap_or(after.len());
+            let after = after[end..].trim_start();
+            rest = match after.strip_prefix('(') {
+                Some(args) => args.find(')').map_or("", |close| &args[close + 1..]).trim_start(),
+                None => after,
+            };
+            continue;
+        }
+        match rest.split_once(char::is_whitespace) {
+            Some(("final" | "public" | "private" | "protected" | "internal" | "static" | "abstract" | "lateinit" | "var" | "val" | "override" | "open", after)) => {
+                rest = after.trim_start();
+            }
+            _ => return rest,
+        }
+    }
+}
+
+/// Return type of the function `name` declared by `signature`
+fn return_type(signature: &str, name: &str, kotlin: bool) -> Option<String> {
+    let open = signature.find(&format!("{}(", name)).or_else(|| signature.find(&format!("{}<", name)))?;
+    if kotlin {
+        let after_name = &signature[open + name.len()..];
+        let params_start = after_name.find('(')?;
+        let mut depth = 0usize;
+        for (i, c) in after_name[params_start..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let rest = after_name[params_start + i + 1..].trim_start().strip_prefix(':')?;
+                        let declared = rest.split(['=', '{']).next().unwrap_or(rest);
+                        return simple_type(declared.split(" where ").next().unwrap_or(declared));
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    } else {
+        simple_type(strip_modifiers(&signature[..open]))
+    }
+}
+
+/// Simple name of a declared type: `Provider<com.x.Api>?` → `Api`; None for primitives, boxed
+/// values and lowercase names
+fn simple_type(declared: &str) -> Option<String> {
+    let mut text = declared.trim().trim_end_matches('?').trim_end_matches("...").trim_end_matches("[]").trim();
+    if let Some((outer, inner)) = text.split_once('<') {
+        let outer = outer.rsplit('.').next().unwrap_or(outer).trim();
+        if WRAPPERS.contains(&outer) {
+            text = inner.rsplit_once('>').map_or(inner, |(inside, _)| inside);
+            return simple_type(text);
+        }
+        text = outer;
+    }
+    let simple = text.rsplit('.').next().unwrap_or(text).trim();
+    let is_type = simple.chars().next().is_some_and(char::is_uppercase) && !VALUE_TYPES.contains(&simple);
+    is_type.then(|| simple.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings(content: &str, ext: &str) -> Vec<(Role, String, &'static str, Option<String>)> {
+        let file_type = FileType::from_extension(ext).unwrap();
+        let (mut symbols, _) = crate::parsers::parse_file_symbols(content, file_type).unwrap();
+        crate::parsers::annotations::assign_annotations(&mut symbols, content, ext);
+        crate::parsers::signature::normalize_signatures(&mut symbols, content, ext);
+        crate::parsers::span::assign_spans(&mut symbols, content, ext);
+        extract_bindings(content, &symbols, ext).into_iter().map(|b| (b.role, b.type_name, b.mechanism, b.symbol)).collect()
+    }
+
+    fn has(found: &[(Role, String, &'static str, Option<String>)], role: Role, type_name: &str, mechanism: &str, symbol: &str) -> bool {
+        found.iter().any(|(r, t, m, s)| *r == role && t == type_name && *m == mechanism && s.as_deref() == Some(symbol))
+    }
+
+    #[test]
+    fn test_dagger_and_spring_kotlin() {
+        let code = r#"package app
+
+class Checkout @Inject constructor(
+    private val repo: PaymentRepo,
+    private val api: Provider<PaymentApi>,
+) {
+    @Inject lateinit var logger: Logger
+}
+
+@Module
+object AppModule {
+    @Provides
+    fun provideApi(client: HttpClient): PaymentApi = PaymentApiImpl(client)
+
+    @Binds
+    abstract fun bindRepo(impl: PaymentRepoImpl): PaymentRepo
+}
+
+@Service
+class Billing(private val repo: PaymentRepo, val limit: Int = 3)
+"#;
+        let found = bindings(code, "kt");
+        assert!(has(&found, Role::Provides, "Checkout", "@Inject constructor", "Checkout"), "got {:?}", found);
+        assert!(has(&found, Role::Injects, "PaymentRepo", "constructor", "Checkout"));
+        assert!(has(&found, Role::Injects, "PaymentApi", "constructor", "Checkout"));
+        assert!(has(&found, Role::Injects, "Logger", "@Inject field", "Checkout"));
+        assert!(has(&found, Role::Provides, "PaymentApi", "@Provides", "provideApi"));
+        assert!(has(&found, Role::Injects, "HttpClient", "@Provides parameter", "PaymentApi"));
+        assert!(has(&found, Role::Provides, "PaymentRepo", "@Binds", "bindRepo"));
+        assert!(has(&found, Role::Provides, "Billing", "@Service", "Billing"));
+        assert!(has(&found, Role::Injects, "PaymentRepo", "constructor", "Billing"));
+        assert!(!found.iter().any(|(_, t, _, _)| t == "Int"));
+    }
+
+    #[test]
+    fn test_spring_java() {
+        let code = r#"package app;
+
+@Configuration
+public class Cfg {
+    @Autowired
+    private PaymentRepo repo;
+
+    public Cfg(final PaymentApi api) {}
+
+    @Bean
+    public PaymentApi paymentApi(@Qualifier("main") HttpClient client) {
+        return new PaymentApiImpl(client);
+    }
+}
+"#;
+        let found = bindings(code, "java");
+        assert!(has(&found, Role::Provides, "Cfg", "@Configuration", "Cfg"), "got {:?}", found);
+        assert!(has(&found, Role::Injects, "PaymentRepo", "@Autowired field", "Cfg"));
+        assert!(has(&found, Role::Injects, "PaymentApi", "constructor", "Cfg"));
+        assert!(has(&found, Role::Provides, "PaymentApi", "@Bean", "paymentApi"));
+        assert!(has(&found, Role::Injects, "HttpClient", "@Bean parameter", "PaymentApi"));
+    }
+
+    #[test]
+    fn test_koin() {
+        let code = r#"package app
+
+import org.koin.dsl.module
+
+val appModule = module {
+    single { PaymentRepoImpl(get()) } bind PaymentRepo::class
+    single<PaymentApi> { PaymentApiImpl(get()) }
+    factoryOf(::Checkout)
+    viewModel { CheckoutViewModel(get()) }
+}
+
+class Screen {
+    private val vm: CheckoutViewModel by viewModel()
+    private val repo = get<PaymentRepo>()
+}
+"#;
+        let found = bindings(code, "kt");
+        assert!(has(&found, Role::Provides, "PaymentRepoImpl", "koin single", "appModule"), "got {:?}", found);
+        assert!(has(&found, Role::Provides, "PaymentRepo", "koin bind", "appModule"));
+        assert!(has(&found, Role::Provides, "PaymentApi", "koin single", "appModule"));
+        assert!(has(&found, Role::Provides, "Checkout", "koin factoryOf", "appModule"));
+        assert!(has(&found, Role::Provides, "CheckoutViewModel", "koin viewModel", "appModule"));
+        assert!(has(&found, Role::Injects, "CheckoutViewModel", "koin get", "Screen"));
+        assert!(has(&found, Role::Injects, "PaymentRepo", "koin get", "Screen"));
+        // Without a Koin import the DSL words are left alone
+        assert!(bindings(&code.replace("org.koin", "org.other"), "kt").is_empty());
+    }
+
+    #[test]
+    fn test_simple_type() {
+        assert_eq!(simple_type("Lazy<com.x.Api>?").as_deref(), Some("Api"));
+        assert_eq!(simple_type("List<Api>").as_deref(), Some("List"));
+        assert_eq!(simple_type("int"), None);
+        assert_eq!(simple_type("String?"), None);
+    }
+}
@@ -0,0 +1,263 @@
+//! Documentation comments attached to symbols
+//!
+//! Picks the comment block directly above a declaration (KDoc/JavaDoc `/** */`,
+//! rustdoc/XML-doc `///`, Go/Proto `//`, Ruby/Perl `#`) or the docstring right
+//! below a Python `def`/`class`, and keeps its first paragraph.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::db::SymbolKind;
+use super::{FileType, ParsedSymbol};
+
+/// Max stored doc length (characters)
+const MAX_DOC_LEN: usize = 500;
+
+/// How a language writes doc comments
+struct DocStyle {
+    /// `/** ... */` blocks
+    block: bool,
+    /// Line comment prefix that forms docs (`///`, `//`, `#`)
+    line_prefix: Option<&'static str>,
+    /// Python docstrings below the declaration
+    docstring: bool,
+}
+
+fn doc_style(file_type: FileType) -> Option<DocStyle> {
+    let style = |block, line_prefix, docstring| Some(DocStyle { block, line_prefix, docstring });
+    match file_type {
+        FileType::Kotlin | FileType::Java | FileType::Scala | FileType::TypeScript | FileType::Vue | FileType::Svelte => {
+            style(true, None, false)
+        }
+        FileType::Rust | FileType::CSharp | FileType::Swift | FileType::Dart | FileType::Cpp | FileType::ObjC => {
+            style(true, Some("///"), false)
+        }
+        FileType::Go | FileType::Proto => style(true, Some("//"), false),
+        FileType::Ruby | FileType::Perl => style(false, Some("#"), false),
+        FileType::Python => style(false, None, true),
+        FileType::Wsdl => None,
+    }
+}
+
+static XML_SEE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<(?:see|seealso|paramref|typeparamref)\s+\w+="([^"]*)"\s*/>"#).unwrap());
+static XML_TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"</?[A-Za-z][^>]*>").unwrap());
+
+/// Set `doc` on symbols that have a doc comment. `ext` selects the comment syntax.
+pub fn assign_docs(symbols: &mut [ParsedSymbol], content: &str, ext: &str) {
+    let Some(style) = FileType::from_extension(ext).and_then(doc_style) else { return };
+    let lines: Vec<&str> = content.lines().collect();
+
+    // Lines declaring a type: constructor properties on the same line must not take its doc
+    let type_lines: Vec<usize> = symbols
+        .iter()
+        .filter(|s| matches!(s.kind, SymbolKind::Class | SymbolKind::Interface | SymbolKind::Object | SymbolKind::Enum))
+        .map(|s| s.line)
+        .collect();
+
+    for symbol in symbols.iter_mut() {
+        if matches!(symbol.kind, SymbolKind::Import | SymbolKind::Package) {
+            continue;
+        }
+        if symbol.kind == SymbolKind::Property && type_lines.contains(&symbol.line) {
+            continue;
+        }
+        let doc = if style.docstring {
+            docstring_below(&lines, symbol.line)
+        } else {
+            comment_above(&lines, symbol.line, &style)
+        };
+        symbol.doc = doc.and_then(|raw| first_paragraph(&raw));
+    }
+}
+
+/// Raw text of the doc comment ending right above `line` (1-based), markers stripped
+fn comment_above(lines: &[&str], line: usize, style: &DocStyle) -> Option<String> {
+    let mut idx = line.checked_sub(1)?;
+    // `@Annotation`, `#[attr]` and `[Attribute]` lines may sit between the comment and the declaration
+    while idx > 0 {
+        let prev = lines.get(idx - 1)?.trim();
+        let is_attribute = prev.starts_with('@')
+            || prev.starts_with("#[")
+            || (prev.starts_with('[') && prev.ends_with(']'));
+        if !is_attribute {
+            break;
+        }
+        idx -= 1;
+    }
+    let end = idx.checked_sub(1)?;
+    let last = lines.get(end)?.trim();
+
+    if style.block && last.ends_with("*/") {
+        let mut start = end;
+        while !lines[start].contains("/*") {
+            start = start.checked_sub(1)?;
+        }
+        if !lines[start].trim_start().starts_with("/**") {
+            return None;
+        }
+        let text: Vec<&str> = lines[start..=end]
+            .iter()
+            .map(|l| {
+                let l = l.trim();
+                let l = l.strip_prefix("/**").unwrap_or(l);
+                let l = l.strip_suffix("*/").unwrap_or(l);
+                l.trim_start_matches('*').trim()
+            })
+            .collect();
+        return Some(text.join("\n"));
+    }
+
+    let prefix = style.line_prefix?;
+    let is_doc_line = |l: &str| {
+        let l = l.trim_start();
+        // `///` must not count as a plain `//` line comment and vice versa
+        l.starts_with(prefix) && !(prefix == "//" && l.starts_with("///")) && !l.starts_with("#!")
+    };
+    let mut start = end;
+    if !is_doc_line(lines[start]) {
+        return None;
+    }
+    while start > 0 && is_doc_line(lines[start - 1]) {
+        start -= 1;
+    }
+    let text: Vec<String> = lines[start..=end]
+        .iter()
+        .map(|l| {
+            let l = l.trim_start().trim_start_matches(prefix).trim();
+            XML_TAG_RE.replace_all(&XML_SEE_RE.replace_all(l, "$1"), "").trim().to_string()
+        })
+        .collect();
+    Some(text.join("\n"))
+}
+
+/// Raw text of a Python docstring following the declaration at `line` (1-based)
+fn docstring_below(lines: &[&str], line: usize) -> Option<String> {
+    // The signature may span several lines; the body starts after the line ending with ':'
+    let header_end = (line - 1..lines.len().min(line + 10))
+        .find(|&i| lines[i].trim_end().ends_with(':'))?;
+    let first = lines.get(header_end + 1..)?.iter().position(|l| !l.trim().is_empty())? + header_end + 1;
+    let text = lines[first].trim();
+    let text = text.trim_start_matches(['r', 'u', 'R', 'U']);
+    let quote = if text.starts_with("\"\"\"") {
+        "\"\"\""
+    } else if text.starts_with("'''") {
+        "'''"
+    } else {
+        return None;
+    };
+    let body = &text[3..];
+    if let Some(end) = body.find(quote) {
+        return Some(body[..end].to_string());
+    }
+    let mut collected = vec![body.to_string()];
+    for l in &lines[first + 1..] {
+        if let Some(end) = l.find(quote) {
+            collected.push(l[..end].trim().to_string());
+            break;
+        }
+        collected.push(l.trim().to_string());
+    }
+    Some(collected.join("\n"))
+}
+
+/// First paragraph of a doc text as a single line: stops at a blank line or a tag section
+fn first_paragraph(raw: &str) -> Option<String> {
+    let mut words: Vec<&str> = Vec::new();
+    for line in raw.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            if words.is_empty() {
+                continue;
+            }
+            break;
+        }
+        // @param, :param x:, Args:, rustdoc "# Examples" headings
+        if line.starts_with('@') || line.starts_with(':') || line.starts_with('#')
+            || matches!(line, "Args:" | "Arguments:" | "Returns:" | "Raises:" | "Parameters")
+        {
+            break;
+        }
+        words.extend(line.split_whitespace());
+    }
+    if words.is_empty() {
+        return None;
+    }
+    let doc = words.join(" ");
+    Some(match doc.char_indices().nth(MAX_DOC_LEN) {
+        Some((end, _)) => format!("{}...", &doc[..end]),
+        None => doc,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sym(name: &str, kind: SymbolKind, line: usize) -> ParsedSymbol {
+        ParsedSymbol {
+            name: name.to_string(),
+            kind,
+            line,
+            signature: String::new(),
+            parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
+        }
+    }
+
+    fn doc_of(content: &str, ext: &str, symbol: ParsedSymbol) -> Option<String> {
+        let mut symbols = vec![symbol];
+        assign_docs(&mut symbols, content, ext);
+        symbols.remove(0).doc
+    }
+
+    #[test]
+    fn test_kdoc_first_paragraph() {
+        let content = "/**\n * Retry with exponential backoff.\n * Gives up after five attempts.\n *\n * More details here.\n * @param attempts max attempts\n */\n@JvmStatic\nfun retry(attempts: Int) {}\n";
+        assert_eq!(
+            doc_of(content, "kt", sym("retry", SymbolKind::Function, 9)).as_deref(),
+            Some("Retry with exponential backoff. Gives up after five attempts.")
+        );
+    }
+
+    #[test]
+    fn test_plain_block_comment_is_not_doc() {
+        let content = "/* license header */\nclass Foo\n";
+        assert_eq!(doc_of(content, "java", sym("Foo", SymbolKind::Class, 2)), None);
+    }
+
+    #[test]
+    fn test_rustdoc_and_xml_doc() {
+        let rust = "/// Opens a connection.\n///\n/// # Errors\n#[inline]\npub fn open() {}\n";
+        assert_eq!(doc_of(rust, "rs", sym("open", SymbolKind::Function, 5)).as_deref(), Some("Opens a connection."));
+
+        let cs = "/// <summary>\n/// Sends the <see cref=\"Invoice\"/> by mail.\n/// </summary>\n[HttpPost]\npublic void Send() {}\n";
+        assert_eq!(doc_of(cs, "cs", sym("Send", SymbolKind::Function, 5)).as_deref(), Some("Sends the Invoice by mail."));
+    }
+
+    #[test]
+    fn test_go_line_comments() {
+        let content = "// Client talks to the billing API.\n// It is safe for concurrent use.\ntype Client struct {}\n\nfunc helper() {}\n";
+        assert_eq!(
+            doc_of(content, "go", sym("Client", SymbolKind::Class, 3)).as_deref(),
+            Some("Client talks to the billing API. It is safe for concurrent use.")
+        );
+        assert_eq!(doc_of(content, "go", sym("helper", SymbolKind::Function, 5)), None);
+    }
+
+    #[test]
+    fn test_python_docstring() {
+        let content = "def fetch(url,\n          retries=3):\n    \"\"\"Fetch a URL with retries.\n\n    Args:\n        url: target\n    \"\"\"\n    pass\n\ndef single():\n    '''One line.'''\n";
+        assert_eq!(doc_of(content, "py", sym("fetch", SymbolKind::Function, 1)).as_deref(), Some("Fetch a URL with retries."));
+        assert_eq!(doc_of(content, "py", sym("single", SymbolKind::Function, 10)).as_deref(), Some("One line."));
+    }
+}
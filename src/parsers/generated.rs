@@ -0,0 +1,347 @@
+//! Symbols generated by derives and macros
+//!
+//! `#[derive(Builder)] struct Config` defines `ConfigBuilder`, and `G_DEFINE_TYPE(GtkButton,
+//! gtk_button, ...)` defines `gtk_button_get_type`, but no declaration names them, so their
+//! usages had no definition. This pass adds such symbols with `generated_by` set, from a
+//! built-in list of common derives and macros extended by `[[generated]]` in `.ast-index.toml`.
+
+use std::sync::{LazyLock, OnceLock};
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::config::GeneratedConfig;
+use crate::db::SymbolKind;
+use super::custom::kind_from_config;
+use super::{strip_comments, FileType, ParsedSymbol};
+
+/// Built-in rules: (derive, macro, symbols)
+const BUILTIN: &[(Option<&str>, Option<&str>, &[&str])] = &[
+    (Some("Default"), None, &["function default"]),
+    (Some("Clone"), None, &["function clone"]),
+    (Some("Serialize"), None, &["function serialize"]),
+    (Some("Deserialize"), None, &["function deserialize"]),
+    (Some("Parser"), None, &["function parse"]),
+    (Some("new"), None, &["function new"]),
+    (Some("Builder"), None, &["class {type}Builder", "function {type}Builder.build"]),
+    (Some("TypedBuilder"), None, &["function builder"]),
+    (None, Some("G_DEFINE_TYPE"), &["function {2}_get_type", "function {2}_init", "function {2}_class_init"]),
+    (
+        None,
+        Some("G_DEFINE_TYPE_WITH_PRIVATE"),
+        &["function {2}_get_type", "function {2}_init", "function {2}_class_init", "function {2}_get_instance_private"],
+    ),
+    (None, Some("G_DECLARE_FINAL_TYPE"), &["class {1}", "function {2}_get_type", "function {3}_{4}", "function {3}_IS_{4}"]),
+    (None, Some("G_DECLARE_DERIVABLE_TYPE"), &["class {1}", "function {2}_get_type", "function {3}_{4}", "function {3}_IS_{4}"]),
+    (None, Some("Q_OBJECT"), &["function metaObject", "function qt_metacast", "function qt_metacall", "property staticMetaObject"]),
+    (None, Some("NS_DECL_ISUPPORTS"), &["function AddRef", "function Release", "function QueryInterface"]),
+];
+
+/// Macro invocation at the start of a line: `NAME(args)` or a bare `NAME;` / `NAME`
+static MACRO_CALL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*([A-Z][A-Z0-9_]*)\s*(?:\(([^)]*)\))?\s*;?\s*$").unwrap());
+/// `{type}` / `{1}` placeholders
+static PLACEHOLDER_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{(type|\d+)\}").unwrap());
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Trigger {
+    Derive(String),
+    Macro(String),
+}
+
+/// What one derive or macro generates
+#[derive(Debug, Clone)]
+pub struct Rule {
+    trigger: Trigger,
+    /// (kind, name template)
+    symbols: Vec<(SymbolKind, String)>,
+}
+
+static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+
+/// Parse a `"<kind> <name>"` entry
+fn parse_symbol(entry: &str, label: &str) -> Result<(SymbolKind, String)> {
+    let (kind, name) = entry
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| anyhow::anyhow!("generated {}: expected \"<kind> <name>\", got {:?}", label, entry))?;
+    let kind = kind_from_config(kind)
+        .ok_or_else(|| anyhow::anyhow!("generated {}: unknown symbol kind '{}'", label, kind))?;
+    Ok((kind, name.trim().to_string()))
+}
+
+/// Built-in rules with the configured ones replacing or extending them
+pub fn compile(configs: &[GeneratedConfig]) -> Result<Vec<Rule>> {
+    let mut rules: Vec<Rule> = BUILTIN
+        .iter()
+        .map(|(derive, macro_name, symbols)| {
+            let trigger = match derive {
+                Some(d) => Trigger::Derive(d.to_string()),
+                None => Trigger::Macro(macro_name.unwrap_or_default().to_string()),
+            };
+            let symbols = symbols.iter().map(|s| parse_symbol(s, "built-in")).collect::<Result<_>>()?;
+            Ok(Rule { trigger, symbols })
+        })
+        .collect::<Result<_>>()?;
+
+    for config in configs {
+        let trigger = match (&config.derive, &config.macro_name) {
+            (Some(derive), None) => Trigger::Derive(derive.clone()),
+            (None, Some(macro_name)) => Trigger::Macro(macro_name.clone()),
+            _ => anyhow::bail!("generated: each entry needs exactly one of `derive` or `macro`"),
+        };
+        let label = match &trigger {
+            Trigger::Derive(name) => format!("derive '{}'", name),
+            Trigger::Macro(name) => format!("macro '{}'", name),
+        };
+        let symbols = config.symbols.iter().map(|s| parse_symbol(s, &label)).collect::<Result<_>>()?;
+        rules.retain(|r| r.trigger != trigger);
+        rules.push(Rule { trigger, symbols });
+    }
+    Ok(rules)
+}
+
+/// Compile and install the rules from config (called once at startup)
+pub fn register(configs: &[GeneratedConfig]) -> Result<()> {
+    let rules = compile(configs)?;
+    let _ = RULES.set(rules);
+    Ok(())
+}
+
+fn rules() -> &'static [Rule] {
+    RULES.get_or_init(|| compile(&[]).unwrap_or_default())
+}
+
+/// Append the symbols generated by derives (Rust) and macro invocations (C, C++, Objective-C)
+pub fn add_generated_symbols(symbols: &mut Vec<ParsedSymbol>, content: &str, ext: &str) {
+    add_generated_with(symbols, content, ext, rules());
+}
+
+fn add_generated_with(symbols: &mut Vec<ParsedSymbol>, content: &str, ext: &str, rules: &[Rule]) {
+    let generated = match FileType::from_extension(ext) {
+        Some(FileType::Rust) => derived_symbols(symbols, rules),
+        Some(file_type @ (FileType::Cpp | FileType::ObjC)) => macro_symbols(symbols, content, file_type, rules),
+        _ => return,
+    };
+    for symbol in generated {
+        let exists = symbols.iter().any(|s| s.name == symbol.name && s.container == symbol.container);
+        if !exists {
+            symbols.push(symbol);
+        }
+    }
+}
+
+/// Where generated symbols go: containers for generated types and for members
+/// (`Owner.name` entries nest under the type container), plus what they inherit from their owner
+struct Placement<'a> {
+    types: Option<String>,
+    members: Option<String>,
+    owner: Option<&'a ParsedSymbol>,
+    separator: &'static str,
+}
+
+impl Placement<'_> {
+    fn symbol(&self, line: usize, kind: SymbolKind, name: String, origin: &str) -> ParsedSymbol {
+        let is_type = matches!(kind, SymbolKind::Class | SymbolKind::Interface | SymbolKind::Enum | SymbolKind::Object);
+        let (container, name) = match name.rsplit_once('.') {
+            Some((outer, member)) => (Some(join(self.types.as_deref(), outer)), member.to_string()),
+            None if is_type => (self.types.clone(), name),
+            None => (self.members.clone(), name),
+        };
+        let qualified_name = self.owner.and_then(|owner| {
+            let qualified = owner.qualified_name.as_deref()?;
+            let own = join(owner.container.as_deref(), &owner.name).replace('.', self.separator);
+            let namespace = qualified.strip_suffix(own.as_str())?.trim_end_matches(self.separator);
+            let path = join(container.as_deref(), &name).replace('.', self.separator);
+            Some(if namespace.is_empty() { path } else { format!("{}{}{}", namespace, self.separator, path) })
+        });
+        ParsedSymbol {
+            signature: format!("{} (generated by {})", name, origin),
+            name,
+            kind,
+            line,
+            parents: vec![],
+            container,
+            qualified_name,
+            doc: None,
+            annotations: vec![],
+            visibility: self.owner.and_then(|o| o.visibility.clone()),
+            end_line: Some(line),
+            byte_range: None,
+            condition: self.owner.and_then(|o| o.condition.clone()),
+            generated_by: Some(origin.to_string()),
+        }
+    }
+}
+
+/// `outer.name`, or `name` at the top level
+fn join(outer: Option<&str>, name: &str) -> String {
+    match outer {
+        Some(outer) => format!("{}.{}", outer, name),
+        None => name.to_string(),
+    }
+}
+
+/// Symbols from `#[derive(...)]` on structs and enums
+fn derived_symbols(symbols: &[ParsedSymbol], rules: &[Rule]) -> Vec<ParsedSymbol> {
+    let mut generated = Vec::new();
+    for owner in symbols.iter().filter(|s| matches!(s.kind, SymbolKind::Class | SymbolKind::Enum)) {
+        let derives = owner
+            .annotations
+            .iter()
+            .filter(|(name, _)| name == "derive")
+            .filter_map(|(_, args)| args.as_deref())
+            .flat_map(|args| args.split(','))
+            .map(|d| d.trim().rsplit("::").next().unwrap_or_default().to_string())
+            .filter(|d| !d.is_empty());
+        // Members belong to the deriving type, generated types sit next to it
+        let placement = Placement {
+            types: owner.container.clone(),
+            members: Some(join(owner.container.as_deref(), &owner.name)),
+            owner: Some(owner),
+            separator: "::",
+        };
+        for derive in derives {
+            let Some(rule) = rules.iter().find(|r| r.trigger == Trigger::Derive(derive.clone())) else { continue };
+            let origin = format!("derive({})", derive);
+            for (kind, template) in &rule.symbols {
+                if let Some(name) = expand(template, Some(&owner.name), &[]) {
+                    generated.push(placement.symbol(owner.line, *kind, name, &origin));
+                }
+            }
+        }
+    }
+    generated
+}
+
+/// Symbols from invocations of known macros, as members of the enclosing class if any
+fn macro_symbols(symbols: &[ParsedSymbol], content: &str, file_type: FileType, rules: &[Rule]) -> Vec<ParsedSymbol> {
+    let stripped = strip_comments(content, file_type);
+    let mut generated = Vec::new();
+    for (idx, line) in stripped.lines().enumerate() {
+        let Some(caps) = MACRO_CALL_RE.captures(line) else { continue };
+        let macro_name = &caps[1];
+        let Some(rule) = rules.iter().find(|r| r.trigger == Trigger::Macro(macro_name.to_string())) else { continue };
+        let args: Vec<&str> = caps.get(2).map(|a| a.as_str().split(',').map(str::trim).collect()).unwrap_or_default();
+        let line_num = idx + 1;
+        let enclosing = symbols
+            .iter()
+            .filter(|s| matches!(s.kind, SymbolKind::Class | SymbolKind::Interface | SymbolKind::Object))
+            .filter(|s| s.line <= line_num && s.end_line.is_some_and(|end| end >= line_num))
+            .max_by_key(|s| s.line);
+        let scope = enclosing.map(|s| join(s.container.as_deref(), &s.name));
+        let placement = Placement {
+            types: scope.clone(),
+            members: scope,
+            owner: enclosing,
+            separator: if file_type == FileType::Cpp { "::" } else { "." },
+        };
+        for (kind, template) in &rule.symbols {
+            if let Some(name) = expand(template, None, &args) {
+                let mut symbol = placement.symbol(line_num, *kind, name, macro_name);
+                symbol.signature = line.trim().to_string();
+                generated.push(symbol);
+            }
+        }
+    }
+    generated
+}
+
+/// Fill `{type}` and `{N}` placeholders; None if a macro argument is missing or empty
+fn expand(template: &str, type_name: Option<&str>, args: &[&str]) -> Option<String> {
+    let mut missing = false;
+    let expanded = PLACEHOLDER_RE.replace_all(template, |caps: &regex::Captures| {
+        let value = match &caps[1] {
+            "type" => type_name,
+            n => n.parse::<usize>().ok().and_then(|i| i.checked_sub(1)).and_then(|i| args.get(i).copied()),
+        };
+        match value.filter(|v| !v.is_empty()) {
+            Some(v) => v.to_string(),
+            None => {
+                missing = true;
+                String::new()
+            }
+        }
+    });
+    (!missing).then(|| expanded.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::{annotations, parse_file_symbols, qualify, span, visibility};
+
+    fn parse(code: &str, ext: &str, rules: &[Rule]) -> Vec<ParsedSymbol> {
+        let file_type = FileType::from_extension(ext).unwrap();
+        let (mut symbols, _) = parse_file_symbols(code, file_type).unwrap();
+        qualify::assign_qualified_names_in(&mut symbols, code, ext, "src/lib.rs", None);
+        annotations::assign_annotations(&mut symbols, code, ext);
+        visibility::assign_visibility(&mut symbols, code, ext);
+        span::assign_spans(&mut symbols, code, ext);
+        add_generated_with(&mut symbols, code, ext, rules);
+        symbols
+    }
+
+    fn generated(symbols: &[ParsedSymbol]) -> Vec<(String, Option<String>, String)> {
+        symbols
+            .iter()
+            .filter_map(|s| Some((s.name.clone(), s.container.clone(), s.generated_by.clone()?)))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_derives() {
+        let code = "#[derive(Debug, Default, derive_builder::Builder)]\npub struct Config {\n    port: u16,\n}\n\nimpl Config {\n    pub fn default() -> Self { todo!() }\n}\n";
+        let rules = compile(&[]).unwrap();
+        let symbols = parse(code, "rs", &rules);
+        let generated = generated(&symbols);
+        assert!(generated.contains(&("ConfigBuilder".into(), None, "derive(Builder)".into())), "{:?}", generated);
+        assert!(generated.contains(&("build".into(), Some("ConfigBuilder".into()), "derive(Builder)".into())));
+        // Declared by hand, so not generated again
+        assert!(!generated.iter().any(|(name, _, _)| name == "default"));
+        let builder = symbols.iter().find(|s| s.name == "ConfigBuilder").unwrap();
+        assert_eq!(builder.kind, SymbolKind::Class);
+        assert_eq!(builder.line, 2);
+        assert_eq!(builder.visibility.as_deref(), Some("public"));
+    }
+
+    #[test]
+    fn test_c_macros() {
+        let code = "G_DEFINE_TYPE (GtkButton, gtk_button, GTK_TYPE_WIDGET)\n\n// Q_OBJECT\nclass Player : public QObject {\n    Q_OBJECT\npublic:\n    void play();\n};\n";
+        let rules = compile(&[]).unwrap();
+        let symbols = parse(code, "cpp", &rules);
+        let generated = generated(&symbols);
+        assert!(generated.contains(&("gtk_button_get_type".into(), None, "G_DEFINE_TYPE".into())), "{:?}", generated);
+        assert!(generated.contains(&("gtk_button_class_init".into(), None, "G_DEFINE_TYPE".into())));
+        assert!(generated.contains(&("metaObject".into(), Some("Player".into()), "Q_OBJECT".into())));
+        // The commented-out invocation adds nothing
+        assert_eq!(generated.iter().filter(|(name, _, _)| name == "metaObject").count(), 1);
+        let get_type = symbols.iter().find(|s| s.name == "gtk_button_get_type").unwrap();
+        assert_eq!(get_type.signature, "G_DEFINE_TYPE (GtkButton, gtk_button, GTK_TYPE_WIDGET)");
+    }
+
+    #[test]
+    fn test_configured_rules() {
+        let configs = vec![
+            GeneratedConfig { derive: Some("Getters".into()), macro_name: None, symbols: vec!["function get_{type}".into()] },
+            GeneratedConfig { derive: None, macro_name: Some("Q_OBJECT".into()), symbols: vec!["function tr".into()] },
+        ];
+        let rules = compile(&configs).unwrap();
+        let symbols = parse("#[derive(Getters)]\nstruct Point {}\n", "rs", &rules);
+        assert_eq!(generated(&symbols), vec![("get_Point".into(), Some("Point".into()), "derive(Getters)".into())]);
+        let symbols = parse("class A {\n  Q_OBJECT\n};\n", "cpp", &rules);
+        assert_eq!(generated(&symbols), vec![("tr".into(), Some("A".into()), "Q_OBJECT".into())]);
+
+        let both = GeneratedConfig { derive: Some("A".into()), macro_name: Some("B".into()), symbols: vec![] };
+        assert!(compile(&[both]).is_err());
+        let bad_kind = GeneratedConfig { derive: Some("A".into()), macro_name: None, symbols: vec!["widget x".into()] };
+        assert!(compile(&[bad_kind]).is_err());
+    }
+
+    #[test]
+    fn test_missing_macro_argument() {
+        assert_eq!(expand("{2}_get_type", None, &["A", "a"]).as_deref(), Some("a_get_type"));
+        assert_eq!(expand("{2}_get_type", None, &["A"]), None);
+        assert_eq!(expand("{type}Builder", Some("Config"), &[]).as_deref(), Some("ConfigBuilder"));
+    }
+}
@@ -0,0 +1,310 @@
+//! Import statements
+//!
+//! Records which qualified names each file brings into scope, so a reference to a
+//! bare `User` can be resolved to the `User` the file actually imports:
+//! - Kotlin/Java/Scala: `import a.b.User`, `import a.b.User as U`, `import a.b.*` / `a.b._`, `import a.b.{A, B => C}`
+//! - Python: `from a.b import User`, `from .models import User as U`, `import a.b as ab`
+//! - Rust: `use crate::a::User;`, `use super::{b, c::D as E};`, `use a::*;`
+//! - C#: `using A.B;` (every type of the namespace), `using U = A.B.User;`
+//!
+//! Paths use the same separators as `qualified_name`, and relative Python and Rust
+//! paths are resolved against the module of the file.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::qualify::{python_module, rust_module};
+use super::{strip_comments, FileType};
+
+/// One name bound by an import statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedImport {
+    /// Name bound in the file (the alias if renamed), `*` for wildcard imports
+    pub name: String,
+    /// Imported qualified name, or the imported namespace for wildcard imports
+    pub path: String,
+    pub line: usize,
+}
+
+static JVM_IMPORT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^[ \t]*import\s+(?:static\s+)?([\w.`]+?)(?:\.\{([^}]*)\}|\.(\*|_))?(?:\s+as\s+(\w+))?[ \t]*;?[ \t]*$").unwrap()
+});
+static PY_FROM_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^[ \t]*from\s+(\.*[\w.]*)\s+import\s+(\([^)]*\)|[^\n]+)").unwrap()
+});
+static PY_IMPORT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^[ \t]*import\s+([\w.]+(?:\s+as\s+\w+)?(?:\s*,\s*[\w.]+(?:\s+as\s+\w+)?)*)").unwrap());
+static RUST_USE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^[ \t]*(?:pub(?:\([^)]*\))?\s+)?use\s+([^;]+);").unwrap());
+static CSHARP_USING_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^[ \t]*(?:global\s+)?using\s+(?:static\s+)?(?:(\w+)\s*=\s*)?([\w.]+)\s*;").unwrap()
+});
+
+/// Imports of a file. `ext` selects the language, `rel_path` resolves relative imports.
+pub fn extract_imports(content: &str, ext: &str, rel_path: &str) -> Vec<ParsedImport> {
+    let Some(file_type) = FileType::from_extension(ext) else { return Vec::new() };
+    let stripped = strip_comments(content, file_type);
+    let rel_path = rel_path.replace('\\', "/");
+    let mut imports = Vec::new();
+    match file_type {
+        FileType::Kotlin | FileType::Java | FileType::Scala => jvm_imports(&stripped, &mut imports),
+        FileType::Python => python_imports(&stripped, &rel_path, &mut imports),
+        FileType::Rust => rust_imports(&stripped, &rel_path, &mut imports),
+        FileType::CSharp => csharp_imports(&stripped, &mut imports),
+        _ => {}
+    }
+    imports
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+/// Last segment of a dotted path
+fn last_segment<'a>(path: &'a str, sep: &str) -> &'a str {
+    path.rsplit(sep).next().unwrap_or(path)
+}
+
+fn jvm_imports(content: &str, imports: &mut Vec<ParsedImport>) {
+    for caps in JVM_IMPORT_RE.captures_iter(content) {
+        let line = line_of(content, caps.get(0).map_or(0, |m| m.start()));
+        let path = caps[1].replace('`', "");
+        if let Some(group) = caps.get(2) {
+            // Scala selector group: `{A, B => C, _}`
+            for item in group.as_str().split(',').map(str::trim).filter(|i| !i.is_empty()) {
+                let (name, alias) = match item.split_once("=>") {
+                    Some((name, alias)) => (name.trim(), alias.trim()),
+                    None => (item, item),
+                };
+                if alias == "_" && name != "_" {
+                    continue; // `A => _` hides A
+                }
+                if name == "_" || name == "*" {
+                    imports.push(ParsedImport { name: "*".to_string(), path: path.clone(), line });
+                } else {
+                    imports.push(ParsedImport { name: alias.to_string(), path: format!("{}.{}", path, name), line });
+                }
+            }
+        } else if caps.get(3).is_some() {
+            imports.push(ParsedImport { name: "*".to_string(), path, line });
+        } else {
+            let name = caps.get(4).map_or_else(|| last_segment(&path, ".").to_string(), |m| m.as_str().to_string());
+            imports.push(ParsedImport { name, path, line });
+        }
+    }
+}
+
+/// Package a Python relative import (`..models`) starts from
+fn python_base(rel_path: &str, dots: usize) -> Option<String> {
+    let module = python_module(rel_path)?;
+    let mut parts: Vec<&str> = module.split('.').collect();
+    // A module's package is its parent, a package's `__init__` is the package itself
+    let levels = if rel_path.ends_with("__init__.py") { dots - 1 } else { dots };
+    if levels > parts.len() {
+        return None;
+    }
+    parts.truncate(parts.len() - levels);
+    Some(parts.join("."))
+}
+
+fn python_imports(content: &str, rel_path: &str, imports: &mut Vec<ParsedImport>) {
+    for caps in PY_FROM_RE.captures_iter(content) {
+        let line = line_of(content, caps.get(0).map_or(0, |m| m.start()));
+        let module = &caps[1];
+        let dots = module.len() - module.trim_start_matches('.').len();
+        let module = if dots > 0 {
+            let Some(base) = python_base(rel_path, dots) else { continue };
+            [base.as_str(), &module[dots..]].iter().filter(|p| !p.is_empty()).copied().collect::<Vec<_>>().join(".")
+        } else {
+            module.to_string()
+        };
+        let names = caps[2].trim_start_matches('(').trim_end_matches(')');
+        for item in names.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+            let mut words = item.split_whitespace();
+            let Some(name) = words.next() else { continue };
+            let alias = match (words.next(), words.next()) {
+                (Some("as"), Some(alias)) => alias,
+                _ => name,
+            };
+            if name == "*" {
+                imports.push(ParsedImport { name: "*".to_string(), path: module.clone(), line });
+            } else if !module.is_empty() {
+                imports.push(ParsedImport { name: alias.to_string(), path: format!("{}.{}", module, name), line });
+            }
+        }
+    }
+    for caps in PY_IMPORT_RE.captures_iter(content) {
+        let line = line_of(content, caps.get(0).map_or(0, |m| m.start()));
+        for item in caps[1].split(',').map(str::trim) {
+            let mut words = item.split_whitespace();
+            let Some(path) = words.next() else { continue };
+            // `import a.b` binds `a`; `import a.b as ab` binds `ab`
+            let name = match (words.next(), words.next()) {
+                (Some("as"), Some(alias)) => alias,
+                _ => path.split('.').next().unwrap_or(path),
+            };
+            let path = if name == path.split('.').next().unwrap_or(path) { name } else { path };
+            imports.push(ParsedImport { name: name.to_string(), path: path.to_string(), line });
+        }
+    }
+}
+
+/// Absolute form of the first segment of a `use` path (`self`, `super`, `crate` or an external crate)
+fn rust_absolute(path: &str, module: Option<&str>) -> String {
+    let mut segments: Vec<&str> = path.split("::").collect();
+    let Some(module) = module else { return path.to_string() };
+    let mut base: Vec<&str> = module.split("::").collect();
+    match segments.first() {
+        Some(&"self") => {
+            segments.remove(0);
+        }
+        Some(&"super") => {
+            while segments.first() == Some(&"super") {
+                segments.remove(0);
+                base.pop();
+            }
+        }
+        _ => return path.to_string(),
+    }
+    base.extend(segments);
+    base.join("::")
+}
+
+/// Flatten a use tree (`a::{b, c::{D, E as F}}`) into (bound name, path) pairs
+fn flatten_use_tree(prefix: &str, tree: &str, out: &mut Vec<(String, String)>) {
+    let tree = tree.trim();
+    if tree.is_empty() {
+        return;
+    }
+    let join = |rest: &str| if prefix.is_empty() { rest.to_string() } else { format!("{}::{}", prefix, rest) };
+    if let Some(open) = tree.find('{') {
+        let path = join(tree[..open].trim().trim_end_matches("::"));
+        let inner = tree[open + 1..].trim_end().strip_suffix('}').unwrap_or(&tree[open + 1..]);
+        let mut depth = 0;
+        let mut start = 0;
+        for (i, c) in inner.char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                ',' if depth == 0 => {
+                    flatten_use_tree(&path, &inner[start..i], out);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        flatten_use_tree(&path, &inner[start..], out);
+        return;
+    }
+    let (path, alias) = match tree.split_once(" as ") {
+        Some((path, alias)) => (path.trim(), Some(alias.trim())),
+        None => (tree, None),
+    };
+    if path == "self" {
+        // `use a::b::{self}` binds `b`
+        out.push((alias.unwrap_or(last_segment(prefix, "::")).to_string(), prefix.to_string()));
+    } else if path == "*" {
+        out.push(("*".to_string(), prefix.to_string()));
+    } else if let Some(namespace) = path.strip_suffix("::*") {
+        out.push(("*".to_string(), join(namespace)));
+    } else {
+        out.push((alias.unwrap_or(last_segment(path, "::")).to_string(), join(path)));
+    }
+}
+
+fn rust_imports(content: &str, rel_path: &str, imports: &mut Vec<ParsedImport>) {
+    let module = rust_module(rel_path);
+    for caps in RUST_USE_RE.captures_iter(content) {
+        let line = line_of(content, caps.get(0).map_or(0, |m| m.start()));
+        let tree: String = caps[1].split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut bound = Vec::new();
+        flatten_use_tree("", &tree.replace(":: ", "::").replace(" ::", "::"), &mut bound);
+        for (name, path) in bound {
+            if name == "_" || path.is_empty() {
+                continue;
+            }
+            imports.push(ParsedImport { name, path: rust_absolute(path.trim_start_matches("::"), module.as_deref()), line });
+        }
+    }
+}
+
+fn csharp_imports(content: &str, imports: &mut Vec<ParsedImport>) {
+    for caps in CSHARP_USING_RE.captures_iter(content) {
+        let line = line_of(content, caps.get(0).map_or(0, |m| m.start()));
+        let path = caps[2].to_string();
+        let name = caps.get(1).map_or("*", |m| m.as_str()).to_string();
+        imports.push(ParsedImport { name, path, line });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(imports: &[ParsedImport]) -> Vec<(&str, &str)> {
+        imports.iter().map(|i| (i.name.as_str(), i.path.as_str())).collect()
+    }
+
+    #[test]
+    fn test_jvm_imports() {
+        let content = "package app\n\nimport com.example.model.User\nimport com.example.db.*\nimport com.example.net.Client as HttpClient\n";
+        let imports = extract_imports(content, "kt", "app/Main.kt");
+        assert_eq!(pairs(&imports), vec![
+            ("User", "com.example.model.User"),
+            ("*", "com.example.db"),
+            ("HttpClient", "com.example.net.Client"),
+        ]);
+        assert_eq!(imports[1].line, 4);
+
+        let java = "import static org.junit.Assert.assertEquals;\nimport java.util.List;\n";
+        assert_eq!(pairs(&extract_imports(java, "java", "T.java")), vec![
+            ("assertEquals", "org.junit.Assert.assertEquals"),
+            ("List", "java.util.List"),
+        ]);
+
+        let scala = "import scala.collection.mutable._\nimport app.model.{User, Order => O}\n";
+        assert_eq!(pairs(&extract_imports(scala, "scala", "A.scala")), vec![
+            ("*", "scala.collection.mutable"),
+            ("User", "app.model.User"),
+            ("O", "app.model.Order"),
+        ]);
+    }
+
+    #[test]
+    fn test_python_imports() {
+        let content = "from app.models import User, Order as O\nfrom .db import (\n    Session,\n)\nfrom ..core import *\nimport os.path\nimport numpy as np\n";
+        let imports = extract_imports(content, "py", "src/app/api/views.py");
+        assert_eq!(pairs(&imports), vec![
+            ("User", "app.models.User"),
+            ("O", "app.models.Order"),
+            ("Session", "app.api.db.Session"),
+            ("*", "app.core"),
+            ("os", "os"),
+            ("np", "numpy"),
+        ]);
+    }
+
+    #[test]
+    fn test_rust_imports() {
+        let content = "use std::collections::HashMap;\nuse super::scope::{brace_body_end, indent_body_end as indent};\npub use crate::db::{self, SymbolKind};\nuse self::inner::*;\n";
+        let imports = extract_imports(content, "rs", "src/parsers/qualify.rs");
+        assert_eq!(pairs(&imports), vec![
+            ("HashMap", "std::collections::HashMap"),
+            ("brace_body_end", "crate::parsers::scope::brace_body_end"),
+            ("indent", "crate::parsers::scope::indent_body_end"),
+            ("db", "crate::db"),
+            ("SymbolKind", "crate::db::SymbolKind"),
+            ("*", "crate::parsers::qualify::inner"),
+        ]);
+    }
+
+    #[test]
+    fn test_csharp_usings() {
+        let content = "using System.Linq;\nusing Json = Newtonsoft.Json.JsonConvert;\nusing (var s = Open()) { }\n";
+        assert_eq!(pairs(&extract_imports(content, "cs", "A.cs")), vec![
+            ("*", "System.Linq"),
+            ("Json", "Newtonsoft.Json.JsonConvert"),
+        ]);
+    }
+}
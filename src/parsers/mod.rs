@@ -16,11 +16,28 @@
 //! - C# (.NET, Unity, ASP.NET)
 //! - Dart/Flutter
 
+pub mod annotations;
+pub mod calls;
+pub mod complexity;
+pub mod conditions;
+pub mod custom;
+pub mod di;
+pub mod docs;
+pub mod generated;
+pub mod imports;
 pub mod perl;
+pub mod qualify;
+pub mod ref_kind;
+pub mod references;
+pub mod scope;
+pub mod signature;
+pub mod span;
+pub mod string_refs;
 pub mod typescript;
+pub mod visibility;
 pub mod wsdl;
 
-use crate::db::SymbolKind;
+use crate::db::{RefKind, SymbolKind};
 
 /// A parsed symbol from source code
 #[derive(Debug, Clone)]
@@ -30,6 +47,26 @@ pub struct ParsedSymbol {
     pub line: usize,
     pub signature: String,
     pub parents: Vec<(String, String)>, // (parent_name, inherit_kind)
+    /// Enclosing type chain for members, e.g. "Outer.Inner"
+    pub container: Option<String>,
+    /// Namespace + container + name, e.g. "com.example.billing.Invoice.total"
+    pub qualified_name: Option<String>,
+    /// First paragraph of the doc comment (KDoc, JavaDoc, docstring, rustdoc, XML doc)
+    pub doc: Option<String>,
+    /// Annotations/decorators/attributes on the declaration as (name, arguments)
+    pub annotations: Vec<(String, Option<String>)>,
+    /// public, protected, internal, private, package (Java), crate (Rust) or fileprivate (Swift)
+    pub visibility: Option<String>,
+    /// Last line of the declaration including its body (1-based)
+    pub end_line: Option<usize>,
+    /// Byte offsets `[start, end)` of the declaration in the file
+    pub byte_range: Option<(usize, usize)>,
+    /// Conditional compilation guarding the declaration (`DEBUG`, `not(DEBUG)`, `feature = "serde"`),
+    /// set by `conditions::assign_conditions`
+    pub condition: Option<String>,
+    /// Derive or macro that generates the symbol (`derive(Builder)`, `G_DEFINE_TYPE`), set by
+    /// `generated::add_generated_symbols`; None for declared symbols
+    pub generated_by: Option<String>,
 }
 
 /// A reference/usage of a symbol
@@ -38,17 +75,18 @@ pub struct ParsedRef {
     pub name: String,
     pub line: usize,
     pub context: String,
+    pub kind: RefKind,
+    /// Number of arguments of a call or instantiation (`ref_kind::call_arity`)
+    pub arity: Option<usize>,
 }
 
-/// Max length for context strings stored in DB (characters)
-const MAX_CONTEXT_LEN: usize = 500;
-
-/// Truncate context to avoid storing huge minified lines
+/// Truncate context to avoid storing huge minified lines (`[references] max_context_length`)
 fn truncate_context(s: &str) -> String {
-    if s.len() <= MAX_CONTEXT_LEN {
+    let max_len = ref_noise().max_context_length;
+    if s.len() <= max_len {
         s.to_string()
     } else {
-        let mut end = MAX_CONTEXT_LEN;
+        let mut end = max_len;
         while end < s.len() && !s.is_char_boundary(end) {
             end += 1;
         }
@@ -56,10 +94,12 @@ fn truncate_context(s: &str) -> String {
     }
 }
 
-use std::collections::HashSet;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use anyhow::Result;
 use regex::Regex;
-use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, OnceLock, RwLock};
 
 /// Strip C-style comments (// and /* */) while preserving line numbers.
 /// Replaces comment content with spaces so line numbers remain correct.
@@ -340,7 +380,7 @@ pub use typescript::{parse_typescript_symbols, extract_vue_script, extract_svelt
 pub use wsdl::parse_wsdl_symbols;
 
 /// File type for parser dispatch
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum FileType {
     Kotlin,
     Java,
@@ -363,6 +403,58 @@ pub enum FileType {
 }
 
 impl FileType {
+    /// All built-in file types
+    pub const ALL: [FileType; 18] = [
+        FileType::Kotlin, FileType::Java, FileType::Swift, FileType::ObjC,
+        FileType::Perl, FileType::Proto, FileType::Wsdl, FileType::Cpp,
+        FileType::Python, FileType::Go, FileType::Rust, FileType::Ruby,
+        FileType::CSharp, FileType::Dart, FileType::TypeScript, FileType::Vue,
+        FileType::Svelte, FileType::Scala,
+    ];
+
+    /// Lowercase language name
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileType::Kotlin => "kotlin",
+            FileType::Java => "java",
+            FileType::Swift => "swift",
+            FileType::ObjC => "objc",
+            FileType::Perl => "perl",
+            FileType::Proto => "proto",
+            FileType::Wsdl => "wsdl",
+            FileType::Cpp => "cpp",
+            FileType::Python => "python",
+            FileType::Go => "go",
+            FileType::Rust => "rust",
+            FileType::Ruby => "ruby",
+            FileType::CSharp => "csharp",
+            FileType::Dart => "dart",
+            FileType::TypeScript => "typescript",
+            FileType::Vue => "vue",
+            FileType::Svelte => "svelte",
+            FileType::Scala => "scala",
+        }
+    }
+
+    /// Parse a language name as accepted by `--languages` (`kotlin`, `ts`, `c++`, ...)
+    pub fn from_name(name: &str) -> Option<FileType> {
+        let name = name.trim().to_lowercase();
+        let alias = match name.as_str() {
+            "kt" => "kotlin",
+            "js" | "javascript" | "ts" => "typescript",
+            "c" | "c++" => "cpp",
+            "c#" | "cs" => "csharp",
+            "objective-c" | "objectivec" => "objc",
+            "protobuf" => "proto",
+            "py" => "python",
+            "rs" => "rust",
+            "rb" => "ruby",
+            "xsd" => "wsdl",
+            other => other,
+        };
+        FileType::ALL.iter().copied().find(|ft| ft.name() == alias)
+    }
+
     /// Determine file type from extension, returns None for unsupported extensions
     pub fn from_extension(ext: &str) -> Option<FileType> {
         match ext {
@@ -390,9 +482,284 @@ impl FileType {
     }
 }
 
-/// Check if file extension is supported for indexing
+static ENABLED_LANGUAGES: RwLock<Option<Vec<FileType>>> = RwLock::new(None);
+
+fn parse_language_names(names: &[String]) -> Result<Vec<FileType>> {
+    names
+        .iter()
+        .map(|name| {
+            FileType::from_name(name).ok_or_else(|| {
+                let known: Vec<&str> = FileType::ALL.iter().map(|ft| ft.name()).collect();
+                anyhow::anyhow!("Unknown language '{}' (known: {})", name, known.join(", "))
+            })
+        })
+        .collect()
+}
+
+/// Restrict indexing to the given languages for this process (`--languages`).
+/// An empty list keeps all languages.
+pub fn set_enabled_languages(names: &[String]) -> Result<()> {
+    let languages = if names.is_empty() { None } else { Some(parse_language_names(names)?) };
+    *ENABLED_LANGUAGES.write().unwrap_or_else(|e| e.into_inner()) = languages;
+    Ok(())
+}
+
+/// Names of the languages indexing is restricted to; empty when all languages are enabled
+pub fn enabled_languages() -> Vec<&'static str> {
+    let enabled = ENABLED_LANGUAGES.read().unwrap_or_else(|e| e.into_inner());
+    enabled.iter().flatten().map(|ft| ft.name()).collect()
+}
+
+/// Keep an update within the languages its index was built with (`indexed`, empty for all):
+/// `update --languages` may narrow them but not add others, which only a rebuild indexes
+pub fn restrict_to_indexed_languages(indexed: &[String]) -> Result<()> {
+    if indexed.is_empty() {
+        return Ok(());
+    }
+    let indexed = parse_language_names(indexed)?;
+    let mut enabled = ENABLED_LANGUAGES.write().unwrap_or_else(|e| e.into_inner());
+    match enabled.as_ref() {
+        None => *enabled = Some(indexed),
+        Some(requested) => {
+            if let Some(missing) = requested.iter().find(|ft| !indexed.contains(ft)) {
+                let names: Vec<&str> = indexed.iter().map(|ft| ft.name()).collect();
+                anyhow::bail!(
+                    "The index was built with --languages {}; run `ast-index rebuild --languages` to add {}",
+                    names.join(","),
+                    missing.name()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Language stored in `files.language` for an extension: the built-in language name or the
+/// custom parser's name; None for files no parser handles
+pub fn language_of(ext: &str) -> Option<&'static str> {
+    FileType::from_extension(ext).map(|ft| ft.name()).or_else(|| custom::find(ext).map(|p| p.name.as_str()))
+}
+
+/// `language_of` by the path's extension
+pub fn language_of_path(path: &str) -> Option<&'static str> {
+    std::path::Path::new(path).extension().and_then(|e| e.to_str()).and_then(language_of)
+}
+
+/// Whether a path looks like a test file (stored in `files.is_test`): under a `test`, `tests`,
+/// `__tests__`, `spec`, `androidTest`, `testFixtures` or `*Tests` directory, or named like a test
+/// in its language (`PaymentTest.kt`, `payment_test.go`, `test_payment.py`, `payment.spec.ts`,
+/// `payment_spec.rb`, Perl `.t`)
+pub fn is_test_path(path: &str) -> bool {
+    const DIRS: &[&str] = &["test", "tests", "__tests__", "spec", "specs", "androidTest", "testFixtures"];
+    let mut segments: Vec<&str> = path.split(['/', '\\']).collect();
+    let file = segments.pop().unwrap_or_default();
+    if segments.iter().any(|dir| DIRS.contains(dir) || (dir.len() > 5 && dir.ends_with("Tests"))) {
+        return true;
+    }
+    let (stem, ext) = file.rsplit_once('.').unwrap_or((file, ""));
+    if ext == "t" || file == "conftest.py" {
+        return true;
+    }
+    let lower = stem.to_lowercase();
+    // `PaymentTest`, `PaymentTests`, `PaymentSpec`: a capitalized suffix after the subject
+    let camel_suffix = ["Test", "Tests", "Spec"].iter().any(|suffix| {
+        stem.strip_suffix(suffix).is_some_and(|subject| subject.chars().last().is_some_and(char::is_alphanumeric))
+    });
+    camel_suffix
+        || lower.starts_with("test_")
+        || ["_test", "_tests", "_spec", "_unittest", ".test", ".spec"].iter().any(|suffix| lower.ends_with(suffix))
+}
+
+/// Name of the code a test file tests, from the naming conventions `is_test_path` knows:
+/// `Payment` for `PaymentTest.kt`, `payment` for `payment_test.go`, `test_payment.py` and
+/// `payment.spec.ts`; None for files not named after a subject
+pub fn test_subject(path: &str) -> Option<&str> {
+    let file = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let (stem, rest) = file.split_once('.').unwrap_or((file, ""));
+    let non_empty = |s: &str| !s.is_empty();
+    if rest.starts_with("test.") || rest.starts_with("spec.") {
+        return Some(stem).filter(|s| non_empty(s));
+    }
+    for suffix in ["Tests", "Test", "Spec"] {
+        if let Some(subject) = stem.strip_suffix(suffix).filter(|s| s.chars().last().is_some_and(char::is_alphanumeric)) {
+            return Some(subject);
+        }
+    }
+    for suffix in ["_tests", "_test", "_spec", "_unittest"] {
+        if let Some(subject) = stem.strip_suffix(suffix).filter(|s| non_empty(s)) {
+            return Some(subject);
+        }
+    }
+    stem.strip_prefix("test_").filter(|s| non_empty(s))
+}
+
+/// Canonical names for a `--lang` filter: built-in languages (aliases like `kt` accepted) and
+/// custom parser names
+pub fn resolve_languages(names: &[String]) -> Result<Vec<String>> {
+    names
+        .iter()
+        .map(|name| {
+            if let Some(ft) = FileType::from_name(name) {
+                return Ok(ft.name().to_string());
+            }
+            if let Some(parser) = custom::registered().iter().find(|p| p.name == name.trim()) {
+                return Ok(parser.name.clone());
+            }
+            let known: Vec<&str> = FileType::ALL
+                .iter()
+                .map(|ft| ft.name())
+                .chain(custom::registered().iter().map(|p| p.name.as_str()))
+                .collect();
+            anyhow::bail!("Unknown language '{}' (known: {})", name, known.join(", "))
+        })
+        .collect()
+}
+
+/// Check if a language passes the `--languages` filter
+pub fn is_language_enabled(file_type: FileType) -> bool {
+    let enabled = ENABLED_LANGUAGES.read().unwrap_or_else(|e| e.into_inner());
+    enabled.as_ref().is_none_or(|enabled| enabled.contains(&file_type))
+}
+
+/// Whether files with this extension belong to a built-in language left out by `--languages` or
+/// by this build; an update keeps such files' index rows instead of treating them as deleted
+pub fn is_filtered_out_extension(ext: &str) -> bool {
+    FileType::from_extension(ext).is_some() && enabled_file_type(ext).is_none()
+}
+
+static RAILS_MODE: OnceLock<bool> = OnceLock::new();
+
+/// Enable or disable Rails DSL symbols (`has_many`, `scope`, callbacks) for this process.
+/// Later calls are ignored.
+pub fn set_rails_mode(enabled: bool) {
+    let _ = RAILS_MODE.set(enabled);
+}
+
+/// Whether Ruby files are parsed with Rails DSL symbols; on unless disabled by `set_rails_mode`
+pub fn rails_mode() -> bool {
+    RAILS_MODE.get().copied().unwrap_or(true)
+}
+
+static REFS_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable reference extraction; the indexer turns it off while writing an index
+/// built without references (`rebuild --no-refs`)
+pub fn set_refs_enabled(enabled: bool) {
+    REFS_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+thread_local! {
+    static SYMBOLS_ONLY: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f` with reference extraction off on this thread; the indexer parses files over
+/// `[index] refs_max_file_size` this way
+pub fn symbols_only<T>(f: impl FnOnce() -> T) -> T {
+    let previous = SYMBOLS_ONLY.replace(true);
+    let result = f();
+    SYMBOLS_ONLY.set(previous);
+    result
+}
+
+/// Whether parsing extracts references besides symbols
+pub fn refs_enabled() -> bool {
+    REFS_ENABLED.load(Ordering::Relaxed) && !SYMBOLS_ONLY.get()
+}
+
+/// Reference extraction knobs from `[references]` in `.ast-index.toml`
+#[derive(Debug, Clone)]
+pub struct RefNoise {
+    stoplist: HashSet<String>,
+    keep: HashSet<String>,
+    languages: HashMap<FileType, HashSet<String>>,
+    min_name_length: usize,
+    max_line_length: usize,
+    max_context_length: usize,
+}
+
+static REF_NOISE: OnceLock<RefNoise> = OnceLock::new();
+static DEFAULT_REF_NOISE: LazyLock<RefNoise> =
+    LazyLock::new(|| RefNoise::from_config(&crate::config::ReferencesConfig::default()).unwrap());
+
+impl RefNoise {
+    pub fn from_config(config: &crate::config::ReferencesConfig) -> Result<RefNoise> {
+        let languages = config
+            .languages
+            .iter()
+            .map(|(name, names)| {
+                let file_type = FileType::from_name(name).ok_or_else(|| {
+                    let known: Vec<&str> = FileType::ALL.iter().map(|ft| ft.name()).collect();
+                    anyhow::anyhow!("references.languages: unknown language '{}' (known: {})", name, known.join(", "))
+                })?;
+                Ok((file_type, names.iter().cloned().collect()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(RefNoise {
+            stoplist: config.stoplist.iter().cloned().collect(),
+            keep: config.keep.iter().cloned().collect(),
+            languages,
+            min_name_length: config.min_name_length,
+            max_line_length: config.max_line_length,
+            max_context_length: config.max_context_length,
+        })
+    }
+
+    /// Whether `name` is left out: a configured stopword, or a built-in one (`builtin`) not kept
+    fn is_stopped(&self, name: &str, builtin: bool) -> bool {
+        (builtin && !self.keep.contains(name)) || self.stoplist.contains(name)
+    }
+
+    /// Drop references to the stopwords configured for `file_type`
+    fn filter_language(&self, refs: &mut Vec<ParsedRef>, file_type: FileType) {
+        if let Some(stoplist) = self.languages.get(&file_type) {
+            refs.retain(|r| !stoplist.contains(&r.name));
+        }
+    }
+}
+
+/// Set the reference extraction knobs for this process. Later calls are ignored.
+pub fn register_ref_noise(config: &crate::config::ReferencesConfig) -> Result<()> {
+    let _ = REF_NOISE.set(RefNoise::from_config(config)?);
+    Ok(())
+}
+
+/// Knobs set by `register_ref_noise`, else the defaults
+fn ref_noise() -> &'static RefNoise {
+    REF_NOISE.get().unwrap_or(&DEFAULT_REF_NOISE)
+}
+
+/// Detect a Rails application: `config/application.rb` or a Gemfile depending on `rails`
+pub fn is_rails_project(root: &std::path::Path) -> bool {
+    static RAILS_GEM_RE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"(?m)^\s*gem\s+['"](rails|railties|activerecord)['"]"#).unwrap());
+    root.join("config/application.rb").is_file()
+        || std::fs::read_to_string(root.join("Gemfile")).is_ok_and(|gemfile| RAILS_GEM_RE.is_match(&gemfile))
+}
+
+/// Built-in file type for an extension, if it passes the `--languages` filter and this build
+/// has a parser for it (a partial build may leave its grammar out)
+fn enabled_file_type(ext: &str) -> Option<FileType> {
+    FileType::from_extension(ext).filter(|ft| is_language_enabled(*ft) && parser_backend(*ft).is_some())
+}
+
+/// Check if file extension is supported for indexing (built-in or custom parser)
 pub fn is_supported_extension(ext: &str) -> bool {
-    FileType::from_extension(ext).is_some()
+    enabled_file_type(ext).is_some() || (FileType::from_extension(ext).is_none() && custom::find(ext).is_some())
+}
+
+/// Parse a file by extension: built-in parsers first, then custom parsers from config.
+/// Returns None if no parser handles the extension.
+pub fn parse_file_by_extension(content: &str, ext: &str) -> Option<Result<(Vec<ParsedSymbol>, Vec<ParsedRef>)>> {
+    if FileType::from_extension(ext).is_some() {
+        return enabled_file_type(ext).map(|file_type| parse_file_symbols(content, file_type));
+    }
+    let parser = custom::find(ext)?;
+    let mut symbols = parser.parse_symbols(content);
+    scope::assign_containers(&mut symbols, content, scope::ScopeStyle::Braces);
+    if !refs_enabled() {
+        return Some(Ok((symbols, vec![])));
+    }
+    Some(extract_references(content, &symbols).map(|refs| (symbols, refs)))
 }
 
 /// Strip comments from content based on file type, preserving line numbers
@@ -426,42 +793,153 @@ fn strip_comments(content: &str, file_type: FileType) -> String {
     }
 }
 
+#[cfg(feature = "tree-sitter")]
 pub mod treesitter;
 
+/// Check if symbols for this file type come from the tree-sitter backend
+pub fn has_treesitter_parser(file_type: FileType) -> bool {
+    #[cfg(feature = "tree-sitter")]
+    {
+        treesitter::get_treesitter_parser(file_type).is_some()
+    }
+    #[cfg(not(feature = "tree-sitter"))]
+    {
+        let _ = file_type;
+        false
+    }
+}
+
 /// Parse symbols and references from file content using FileType enum.
 /// Tries tree-sitter first for supported languages, falls back to regex.
 pub fn parse_file_symbols(content: &str, file_type: FileType) -> Result<(Vec<ParsedSymbol>, Vec<ParsedRef>)> {
     // Try tree-sitter parser first
+    #[cfg(feature = "tree-sitter")]
     if let Some(ts_parser) = treesitter::get_treesitter_parser(file_type) {
-        let symbols = ts_parser.parse_symbols(content)?;
-        let refs = ts_parser.extract_refs(content, &symbols)?;
-        return Ok((symbols, refs));
+        match ts_parser.parse_symbols(content) {
+            Ok(mut symbols) => {
+                if ts_parser.sets_containers() {
+                    assign_owner_containers(&mut symbols, file_type);
+                } else {
+                    assign_containers(&mut symbols, &strip_comments(content, file_type), file_type);
+                }
+                let mut refs = if refs_enabled() { ts_parser.extract_refs(content, &symbols)? } else { vec![] };
+                ref_noise().filter_language(&mut refs, file_type);
+                return Ok((symbols, refs));
+            }
+            // Grammar failed on this file: use the regex parser if there is one
+            Err(e) if !has_regex_parser(file_type) => return Err(e),
+            Err(_) => {}
+        }
     }
 
-    // Fallback: regex-based parsing for unsupported languages
+    // Fallback: regex-based parsing for languages without a compiled-in grammar
     let stripped = strip_comments(content, file_type);
     let content = &stripped;
 
-    let symbols = match file_type {
+    let mut symbols = match file_type {
         FileType::Perl => parse_perl_symbols(content)?,
         FileType::Wsdl => parse_wsdl_symbols(content)?,
+        FileType::TypeScript => parse_typescript_symbols(content)?,
         FileType::Vue => {
             let script = extract_vue_script(content);
             let script_stripped = strip_c_comments(&script, false);
-            parse_typescript_symbols(&script_stripped)?
+            let mut symbols = parse_typescript_symbols(&script_stripped)?;
+            symbols.extend(typescript::parse_vue_macros(&script_stripped));
+            symbols
         }
         FileType::Svelte => {
             let script = extract_svelte_script(content);
             let script_stripped = strip_c_comments(&script, false);
             parse_typescript_symbols(&script_stripped)?
         }
-        // All other types are handled by tree-sitter above
+        // Everything else needs the tree-sitter backend
         _ => return Err(anyhow::anyhow!("No parser for {:?}", file_type)),
     };
-    let refs = extract_references(content, &symbols)?;
+    assign_containers(&mut symbols, content, file_type);
+    let mut refs = if refs_enabled() { extract_references(content, &symbols)? } else { vec![] };
+    ref_noise().filter_language(&mut refs, file_type);
     Ok((symbols, refs))
 }
 
+/// Whether a declaration is one part of a C# `partial` type or method
+pub fn is_partial(symbol: &ParsedSymbol, file_type: Option<FileType>) -> bool {
+    file_type == Some(FileType::CSharp)
+        && symbol
+            .signature
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .take_while(|word| *word != symbol.name)
+            .any(|word| word == "partial")
+}
+
+/// Fill symbol containers: Go methods belong to their receiver type,
+/// out-of-class C++ definitions (`Class::method`) to their class,
+/// everything else is resolved from the enclosing scope
+fn assign_containers(symbols: &mut [ParsedSymbol], content: &str, file_type: FileType) {
+    assign_owner_containers(symbols, file_type);
+    scope::assign_containers(symbols, content, scope::ScopeStyle::for_file_type(file_type));
+}
+
+/// Go receivers and C++ `Class::method` owners, which the declaration names outside any scope
+fn assign_owner_containers(symbols: &mut [ParsedSymbol], file_type: FileType) {
+    let owner_kind = match file_type {
+        FileType::Go => "receiver",
+        FileType::Cpp => "member",
+        _ => return,
+    };
+    for symbol in symbols.iter_mut() {
+        if let Some((owner, _)) = symbol.parents.iter().find(|(_, kind)| kind == owner_kind) {
+            symbol.container = Some(owner.trim_start_matches('*').to_string());
+        }
+    }
+}
+
+/// Name of the backend that parses this file type in the current build, if any
+pub fn parser_backend(file_type: FileType) -> Option<&'static str> {
+    if has_treesitter_parser(file_type) {
+        Some("tree-sitter")
+    } else if has_regex_parser(file_type) {
+        Some("regex")
+    } else {
+        None
+    }
+}
+
+/// Check if a regex parser exists for this file type
+fn has_regex_parser(file_type: FileType) -> bool {
+    matches!(
+        file_type,
+        FileType::Perl | FileType::Wsdl | FileType::TypeScript | FileType::Vue | FileType::Svelte
+    )
+}
+
+// Regex for identifiers that might be references:
+// - CamelCase identifiers (types, classes) like PaymentRepository, String, ПроверитьКонтракт,
+//   also joined by underscores like the Swift protobuf type Payments_V1_PaymentRequest (but not
+//   SCREAMING_SNAKE constants, which have no lowercase letter)
+// - Function calls like getCards(, process( (see `extract_references`)
+// Letters are matched by Unicode class so non-ASCII identifiers are found too
+static IDENTIFIER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(\p{Lu}[\p{L}\p{N}]*|\p{Lu}[\p{L}\p{N}_]*\p{Ll}[\p{L}\p{N}_]*)\b").unwrap()
+});
+
+// Keywords to skip (static to avoid re-creating on every call)
+static KEYWORDS: LazyLock<HashSet<&str>> = LazyLock::new(|| {
+    [
+        "if", "else", "when", "while", "for", "do", "try", "catch", "finally",
+        "return", "break", "continue", "throw", "is", "in", "as", "true", "false",
+        "null", "this", "super", "class", "interface", "object", "fun", "val", "var",
+        "import", "package", "private", "public", "protected", "internal", "override",
+        "abstract", "final", "open", "sealed", "data", "inner", "enum", "companion",
+        "lateinit", "const", "suspend", "inline", "crossinline", "noinline", "reified",
+        "annotation", "typealias", "get", "set", "init", "constructor", "by", "where",
+        // Common standard library that would create too much noise
+        "String", "Int", "Long", "Double", "Float", "Boolean", "Byte", "Short", "Char",
+        "Unit", "Any", "Nothing", "List", "Map", "Set", "Array", "Pair", "Triple",
+        "MutableList", "MutableMap", "MutableSet", "HashMap", "ArrayList", "HashSet",
+        "Exception", "Error", "Throwable", "Result", "Sequence",
+    ].into_iter().collect()
+});
+
 /// Extract references/usages from file content
 pub fn extract_references(content: &str, defined_symbols: &[ParsedSymbol]) -> Result<Vec<ParsedRef>> {
     let mut refs = Vec::new();
@@ -469,41 +947,20 @@ pub fn extract_references(content: &str, defined_symbols: &[ParsedSymbol]) -> Re
     // Build set of locally defined symbol names (to skip them)
     let defined_names: HashSet<&str> = defined_symbols.iter().map(|s| s.name.as_str()).collect();
 
-    // Regex for identifiers that might be references:
-    // - CamelCase identifiers (types, classes) like PaymentRepository, String
-    // - Function calls like getCards(, process(
-    static IDENTIFIER_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b([A-Z][a-zA-Z0-9]*)\b").unwrap());
-
     let identifier_re = &*IDENTIFIER_RE; // CamelCase types
-    static FUNC_CALL_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b([a-z][a-zA-Z0-9]*)\s*\(").unwrap());
+    static FUNC_CALL_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(\p{Ll}[\p{L}\p{N}]*)\s*\(").unwrap());
 
     let func_call_re = &*FUNC_CALL_RE; // function calls
-
-    // Keywords to skip (static to avoid re-creating on every call)
-    static KEYWORDS: LazyLock<HashSet<&str>> = LazyLock::new(|| {
-        [
-            "if", "else", "when", "while", "for", "do", "try", "catch", "finally",
-            "return", "break", "continue", "throw", "is", "in", "as", "true", "false",
-            "null", "this", "super", "class", "interface", "object", "fun", "val", "var",
-            "import", "package", "private", "public", "protected", "internal", "override",
-            "abstract", "final", "open", "sealed", "data", "inner", "enum", "companion",
-            "lateinit", "const", "suspend", "inline", "crossinline", "noinline", "reified",
-            "annotation", "typealias", "get", "set", "init", "constructor", "by", "where",
-            // Common standard library that would create too much noise
-            "String", "Int", "Long", "Double", "Float", "Boolean", "Byte", "Short", "Char",
-            "Unit", "Any", "Nothing", "List", "Map", "Set", "Array", "Pair", "Triple",
-            "MutableList", "MutableMap", "MutableSet", "HashMap", "ArrayList", "HashSet",
-            "Exception", "Error", "Throwable", "Result", "Sequence",
-        ].into_iter().collect()
-    });
     let keywords = &*KEYWORDS;
+    let noise = ref_noise();
 
-    for (line_num, line) in content.lines().enumerate() {
-        let line_num = line_num + 1;
+    let lines: Vec<&str> = content.lines().collect();
+    for (idx, line) in lines.iter().copied().enumerate() {
+        let line_num = idx + 1;
         let trimmed = line.trim();
 
         // Skip very long lines (minified code, generated files)
-        if trimmed.len() > 2000 {
+        if trimmed.len() > noise.max_line_length {
             continue;
         }
 
@@ -517,28 +974,55 @@ pub fn extract_references(content: &str, defined_symbols: &[ParsedSymbol]) -> Re
             continue;
         }
 
+        // Names in string literals (reflection, DI qualifiers)
+        let strings = string_refs::scan(line);
+        for s in &strings {
+            if !noise.is_stopped(&s.name, keywords.contains(s.name.as_str())) && !defined_names.contains(s.name.as_str()) {
+                refs.push(ParsedRef {
+                    name: s.name.clone(),
+                    line: line_num,
+                    context: truncate_context(trimmed),
+                    kind: RefKind::String,
+                    arity: None,
+                });
+            }
+        }
+
         // Extract CamelCase types (classes, interfaces, etc.)
         for caps in identifier_re.captures_iter(line) {
-            let name = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-            if !name.is_empty() && !keywords.contains(name) && !defined_names.contains(name) {
+            let Some(m) = caps.get(1) else { continue };
+            let name = m.as_str();
+            if strings.iter().any(|s| s.contains(m.start())) {
+                continue;
+            }
+            if !name.is_empty() && !noise.is_stopped(name, keywords.contains(name)) && !defined_names.contains(name) {
+                let kind = ref_kind::classify(line, m.start(), m.end());
+                let arity = matches!(kind, RefKind::Call | RefKind::Instantiation)
+                    .then(|| ref_kind::call_arity(&lines, idx, m.end()))
+                    .flatten();
                 refs.push(ParsedRef {
                     name: name.to_string(),
                     line: line_num,
                     context: truncate_context(trimmed),
+                    kind,
+                    arity,
                 });
             }
         }
 
         // Extract function calls
         for caps in func_call_re.captures_iter(line) {
-            let name = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-            if !name.is_empty() && !keywords.contains(name) && !defined_names.contains(name) {
-                // Only add if name length > 2 to avoid noise
-                if name.len() > 2 {
+            let Some(m) = caps.get(1) else { continue };
+            let name = m.as_str();
+            if !name.is_empty() && !noise.is_stopped(name, keywords.contains(name)) && !defined_names.contains(name) {
+                // Short names are mostly noise (`[references] min_name_length`)
+                if name.chars().count() >= noise.min_name_length {
                     refs.push(ParsedRef {
                         name: name.to_string(),
                         line: line_num,
                         context: truncate_context(trimmed),
+                        kind: RefKind::Call,
+                        arity: ref_kind::call_arity(&lines, idx, m.end()),
                     });
                 }
             }
@@ -552,6 +1036,74 @@ pub fn extract_references(content: &str, defined_symbols: &[ParsedSymbol]) -> Re
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_test_path() {
+        for path in [
+            "app/src/test/java/com/x/Foo.java",
+            "core/PaymentTest.kt",
+            "core/PaymentTests.swift",
+            "MyAppTests/LoginViewModel.swift",
+            "pkg/pay/pay_test.go",
+            "lib/test_pay.py",
+            "conftest.py",
+            "web/__tests__/Pay.tsx",
+            "web/pay.spec.ts",
+            "web/pay.test.js",
+            "spec/models/user_spec.rb",
+            "t/basic.t",
+            "src/net/socket_unittest.cc",
+        ] {
+            assert!(is_test_path(path), "{}", path);
+        }
+        for path in ["src/Latest.kt", "src/Contest.java", "src/testing/Util.kt", "src/Test.kt", "src/attestation.rs", "lib/inspect.py"] {
+            assert!(!is_test_path(path), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_test_subject() {
+        for (path, subject) in [
+            ("core/PaymentTest.kt", Some("Payment")),
+            ("core/PaymentTests.swift", Some("Payment")),
+            ("spec/PaymentSpec.groovy", Some("Payment")),
+            ("pkg/pay/pay_test.go", Some("pay")),
+            ("lib/test_pay.py", Some("pay")),
+            ("web/pay.spec.ts", Some("pay")),
+            ("web/pay.test.js", Some("pay")),
+            ("spec/models/user_spec.rb", Some("user")),
+            ("src/net/socket_unittest.cc", Some("socket")),
+            ("app/src/test/java/com/x/Fixtures.java", None),
+            ("conftest.py", None),
+            ("src/Test.kt", None),
+        ] {
+            assert_eq!(test_subject(path), subject, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_is_rails_project() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!is_rails_project(dir.path()));
+        std::fs::write(dir.path().join("Gemfile"), "source 'https://rubygems.org'\ngem 'sinatra'\n").unwrap();
+        assert!(!is_rails_project(dir.path()));
+        std::fs::write(dir.path().join("Gemfile"), "gem \"rails\", \"~> 7.1\"\n").unwrap();
+        assert!(is_rails_project(dir.path()));
+    }
+
+    #[test]
+    fn test_is_partial() {
+        let code = "public partial class Users : Base\n{\n    partial void OnCreated();\n}\npublic class PartialView {}\n";
+        let (symbols, _) = parse_file_symbols(code, FileType::CSharp).unwrap();
+        let partial: Vec<&str> = symbols
+            .iter()
+            .filter(|s| is_partial(s, Some(FileType::CSharp)))
+            .map(|s| s.name.as_str())
+            .collect();
+        assert!(partial.contains(&"Users"), "got {:?}", symbols);
+        assert!(!partial.contains(&"PartialView"));
+        assert!(!symbols.iter().any(|s| is_partial(s, Some(FileType::Kotlin))));
+    }
+
     #[test]
     fn test_is_supported_extension() {
         assert!(is_supported_extension("kt"));
@@ -572,6 +1124,15 @@ mod tests {
         assert!(is_supported_extension("svelte"));
     }
 
+    #[test]
+    fn test_extensions_need_a_parser_in_this_build() {
+        for ext in ["kt", "java", "swift", "m", "ts", "py", "go", "rs", "rb", "cs", "dart", "proto", "cpp", "scala", "pm", "vue"] {
+            let file_type = FileType::from_extension(ext).unwrap();
+            assert_eq!(is_supported_extension(ext), parser_backend(file_type).is_some(), "{}", ext);
+            assert_eq!(is_filtered_out_extension(ext), parser_backend(file_type).is_none(), "{}", ext);
+        }
+    }
+
     #[test]
     fn test_unsupported_extensions() {
         assert!(!is_supported_extension("txt"));
@@ -608,6 +1169,49 @@ mod tests {
         assert!(!refs.iter().any(|r| r.name == "if"));
     }
 
+    #[test]
+    fn test_ref_noise_from_config() {
+        let mut config = crate::config::ReferencesConfig {
+            stoplist: vec!["Logger".to_string()],
+            keep: vec!["String".to_string()],
+            ..Default::default()
+        };
+        config.languages.insert("kotlin".to_string(), vec!["Timber".to_string()]);
+        let noise = RefNoise::from_config(&config).unwrap();
+        assert!(noise.is_stopped("Logger", false));
+        assert!(!noise.is_stopped("String", true));
+        assert!(noise.is_stopped("Unit", true));
+        assert!(!noise.is_stopped("Repository", false));
+
+        let reference = |name: &str| ParsedRef {
+            name: name.to_string(),
+            line: 1,
+            context: String::new(),
+            kind: RefKind::Call,
+            arity: None,
+        };
+        let mut refs = vec![reference("Timber"), reference("Repository")];
+        noise.filter_language(&mut refs, FileType::Swift);
+        assert_eq!(refs.len(), 2);
+        noise.filter_language(&mut refs, FileType::Kotlin);
+        assert_eq!(refs.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), ["Repository"]);
+
+        config.languages.insert("klingon".to_string(), vec![]);
+        let err = RefNoise::from_config(&config).unwrap_err().to_string();
+        assert!(err.contains("unknown language 'klingon'") && err.contains("kotlin"), "{}", err);
+    }
+
+    #[test]
+    fn test_symbols_only_skips_refs_on_this_thread() {
+        let content = "class Cart {\n  fun total() = PriceCalculator().sum()\n}\n";
+        let (symbols, refs) = symbols_only(|| parse_file_by_extension(content, "kt").unwrap().unwrap());
+        assert!(symbols.iter().any(|s| s.name == "Cart"));
+        assert!(refs.is_empty());
+        assert!(refs_enabled());
+        let (_, refs) = parse_file_by_extension(content, "kt").unwrap().unwrap();
+        assert!(refs.iter().any(|r| r.name == "PriceCalculator"));
+    }
+
     #[test]
     fn test_extract_references_finds_types() {
         let content = "val repo: PaymentRepository = PaymentRepositoryImpl()\n";
@@ -617,6 +1221,23 @@ mod tests {
         assert!(refs.iter().any(|r| r.name == "PaymentRepositoryImpl"));
     }
 
+    #[test]
+    fn test_extract_references_underscored_types() {
+        let content = "let request = Payments_V1_PaymentRequest()\nlet retries = MAX_RETRIES + Config.DEFAULT_DELAY\n";
+        let refs = extract_references(content, &[]).unwrap();
+        assert!(refs.iter().any(|r| r.name == "Payments_V1_PaymentRequest"));
+        assert!(!refs.iter().any(|r| r.name.contains("MAX") || r.name.contains("DEFAULT")), "got {:?}", refs);
+    }
+
+    #[test]
+    fn test_extract_references_unicode_identifiers() {
+        let content = "val договор: ДоговорПоставки = загрузитьДоговор(id)\nпроверитьКонтракт(договор)\n";
+        let refs = extract_references(content, &[]).unwrap();
+        assert!(refs.iter().any(|r| r.name == "ДоговорПоставки" && r.kind == RefKind::Type));
+        assert!(refs.iter().any(|r| r.name == "загрузитьДоговор" && r.kind == RefKind::Call));
+        assert!(refs.iter().any(|r| r.name == "проверитьКонтракт" && r.line == 2));
+    }
+
     #[test]
     fn test_extract_references_skips_defined_symbols() {
         let content = "class MyClass {\n    val other: OtherClass\n}\n";
@@ -627,6 +1248,15 @@ mod tests {
                 line: 1,
                 signature: "class MyClass".to_string(),
                 parents: vec![],
+                container: None,
+                qualified_name: None,
+                doc: None,
+                annotations: vec![],
+                visibility: None,
+                end_line: None,
+                byte_range: None,
+                condition: None,
+                generated_by: None,
             },
         ];
         let refs = extract_references(content, &symbols).unwrap();
@@ -717,6 +1347,7 @@ mod tests {
         assert_eq!(lines[3], "line4");
     }
 
+    #[cfg(feature = "lang-kotlin")]
     #[test]
     fn test_kotlin_comment_not_indexed() {
         let code = "class RealClass {}\n// class FakeClass {}\n/* class AnotherFake {} */\n";
@@ -726,6 +1357,7 @@ mod tests {
         assert!(!symbols.iter().any(|s| s.name == "AnotherFake"), "block-commented class should not be indexed");
     }
 
+    #[cfg(feature = "lang-python")]
     #[test]
     fn test_python_comment_not_indexed() {
         let code = "class RealClass:\n    pass\n# class FakeClass:\n#     pass\n";
@@ -734,6 +1366,7 @@ mod tests {
         assert!(!symbols.iter().any(|s| s.name == "FakeClass"));
     }
 
+    #[cfg(feature = "lang-go")]
     #[test]
     fn test_go_comment_not_indexed() {
         let code = "type RealStruct struct {}\n// type FakeStruct struct {}\n";
@@ -742,6 +1375,7 @@ mod tests {
         assert!(!symbols.iter().any(|s| s.name == "FakeStruct"));
     }
 
+    #[cfg(feature = "lang-rust")]
     #[test]
     fn test_rust_comment_not_indexed() {
         let code = "struct RealStruct {}\n// struct FakeStruct {}\n/* struct AnotherFake {} */\n";
@@ -751,6 +1385,7 @@ mod tests {
         assert!(!symbols.iter().any(|s| s.name == "AnotherFake"));
     }
 
+    #[cfg(feature = "lang-swift")]
     #[test]
     fn test_swift_comment_not_indexed() {
         let code = "class RealClass {}\n// class FakeClass {}\n/* class AnotherFake {} */\n";
@@ -759,6 +1394,7 @@ mod tests {
         assert!(!symbols.iter().any(|s| s.name == "FakeClass"));
     }
 
+    #[cfg(feature = "lang-ruby")]
     #[test]
     fn test_ruby_comment_not_indexed() {
         let code = "class RealClass\nend\n# class FakeClass\n# end\n";
@@ -767,6 +1403,21 @@ mod tests {
         assert!(!symbols.iter().any(|s| s.name == "FakeClass"));
     }
 
+    #[test]
+    fn test_typescript_parses_with_or_without_treesitter() {
+        let code = "export class PaymentService {\n  process(): void {}\n}\n";
+        let (symbols, _) = parse_file_symbols(code, FileType::TypeScript).unwrap();
+        let class = symbols.iter().find(|s| s.name == "PaymentService").unwrap();
+        assert_eq!(class.line, 1);
+    }
+
+    #[cfg(not(feature = "lang-kotlin"))]
+    #[test]
+    fn test_no_parser_without_grammar() {
+        assert!(!has_treesitter_parser(FileType::Kotlin));
+        assert!(parse_file_symbols("class Foo", FileType::Kotlin).is_err());
+    }
+
     #[test]
     fn test_file_type_from_extension() {
         assert_eq!(FileType::from_extension("kt"), Some(FileType::Kotlin));
@@ -790,4 +1441,36 @@ mod tests {
         assert_eq!(FileType::from_extension("txt"), None);
         assert_eq!(FileType::from_extension(""), None);
     }
+
+    #[test]
+    fn test_parser_backends() {
+        let names: HashSet<&str> = FileType::ALL.iter().map(|ft| ft.name()).collect();
+        assert_eq!(names.len(), FileType::ALL.len());
+        assert_eq!(parser_backend(FileType::Perl), Some("regex"));
+        assert!(parser_backend(FileType::TypeScript).is_some());
+    }
+
+    #[test]
+    fn test_file_type_from_name() {
+        assert_eq!(FileType::from_name("kotlin"), Some(FileType::Kotlin));
+        assert_eq!(FileType::from_name(" Java "), Some(FileType::Java));
+        assert_eq!(FileType::from_name("js"), Some(FileType::TypeScript));
+        assert_eq!(FileType::from_name("c++"), Some(FileType::Cpp));
+        assert_eq!(FileType::from_name("c#"), Some(FileType::CSharp));
+        assert_eq!(FileType::from_name("cobol"), None);
+        for ft in FileType::ALL {
+            assert_eq!(FileType::from_name(ft.name()), Some(ft));
+        }
+    }
+
+    #[test]
+    fn test_languages() {
+        assert_eq!(language_of_path("app/src/Main.kt"), Some("kotlin"));
+        assert_eq!(language_of_path("web/App.tsx"), Some("typescript"));
+        assert_eq!(language_of_path("README.md"), None);
+        let names = vec!["kt".to_string(), "Swift".to_string()];
+        assert_eq!(resolve_languages(&names).unwrap(), vec!["kotlin", "swift"]);
+        let err = resolve_languages(&["cobol".to_string()]).unwrap_err().to_string();
+        assert!(err.contains("Unknown language 'cobol'") && err.contains("kotlin"), "{}", err);
+    }
 }
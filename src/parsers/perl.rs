@@ -67,6 +67,15 @@ pub fn parse_perl_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                     line: line_num,
                     signature: line.trim().to_string(),
                     parents,
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 current_package = Some((name, symbols.len() as i64 - 1));
             }
@@ -83,6 +92,15 @@ pub fn parse_perl_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                     line: line_num,
                     signature: line.trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
             continue;
@@ -98,6 +116,15 @@ pub fn parse_perl_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                     line: line_num,
                     signature: line.trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
             continue;
@@ -114,6 +141,15 @@ pub fn parse_perl_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                     line: line_num,
                     signature: line.trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
         }
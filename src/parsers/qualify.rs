@@ -0,0 +1,324 @@
+//! Fully qualified symbol names
+//!
+//! Combines the namespace of a symbol with its container chain and name:
+//! - Kotlin/Java/Scala: `package` statement, Dart: `library` directive (`part of` files use their owner's)
+//! - Go: package directory, Python/Rust: module path derived from the file path
+//! - C#/C++/TypeScript/Rust/Ruby: enclosing `namespace`/`mod`/`module` blocks
+//! - Proto: `package` statement
+
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::db::SymbolKind;
+use super::scope::{brace_body_end, indent_body_end};
+use super::{strip_comments, FileType, ParsedSymbol};
+
+static JVM_PACKAGE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*package\s+([\w.]+)").unwrap());
+static DART_LIBRARY_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*library\s+([\w.]+)\s*;").unwrap());
+static DART_PART_OF_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?m)^\s*part\s+of\s+(?:'([^']+)'|"([^"]+)"|([\w.]+))\s*;"#).unwrap());
+
+/// Separator between namespace segments and members for a language
+fn separator(file_type: Option<FileType>) -> &'static str {
+    match file_type {
+        Some(FileType::Rust | FileType::Cpp | FileType::Ruby | FileType::Perl) => "::",
+        _ => ".",
+    }
+}
+
+/// Python module path from a relative file path (`src/app/models.py` -> `app.models`)
+pub(super) fn python_module(rel_path: &str) -> Option<String> {
+    let path = rel_path.strip_suffix(".py")?;
+    let mut parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+    if parts.first() == Some(&"src") {
+        parts.remove(0);
+    }
+    if parts.last() == Some(&"__init__") {
+        parts.pop();
+    }
+    if parts.is_empty() { None } else { Some(parts.join(".")) }
+}
+
+/// Rust module path from a relative file path (`crates/x/src/net/http.rs` -> `crate::net::http`)
+pub(super) fn rust_module(rel_path: &str) -> Option<String> {
+    let path = rel_path.strip_suffix(".rs")?;
+    let parts: Vec<&str> = path.split('/').collect();
+    let mut module: Vec<&str> = match parts.iter().rposition(|p| *p == "src") {
+        // Each `src/bin/<name>.rs` or `src/bin/<name>/main.rs` is the root of its own binary crate
+        Some(pos) if parts.get(pos + 1) == Some(&"bin") && parts.len() > pos + 2 => {
+            std::iter::once("crate").chain(parts[pos + 3..].iter().copied()).collect()
+        }
+        Some(pos) => std::iter::once("crate").chain(parts[pos + 1..].iter().copied()).collect(),
+        None => parts,
+    };
+    if matches!(module.last(), Some(&("lib" | "main" | "mod"))) {
+        module.pop();
+    }
+    if module.is_empty() { None } else { Some(module.join("::")) }
+}
+
+/// Namespace of the whole file, if the language declares it once per file
+fn file_namespace(symbols: &[ParsedSymbol], content: &str, file_type: FileType, rel_path: &str) -> Option<String> {
+    let package_symbol = || symbols.iter().find(|s| s.kind == SymbolKind::Package).map(|s| s.name.clone());
+    match file_type {
+        FileType::Kotlin | FileType::Java | FileType::Scala => {
+            JVM_PACKAGE_RE.captures(content).map(|c| c[1].to_string())
+        }
+        FileType::Dart => DART_LIBRARY_RE.captures(content)
+            .or_else(|| DART_PART_OF_RE.captures(content).filter(|c| c.get(3).is_some()))
+            .map(|c| c[c.len() - 1].to_string()),
+        FileType::Proto => package_symbol(),
+        FileType::Go => match rel_path.rsplit_once('/') {
+            Some((dir, _)) => Some(dir.to_string()),
+            None => package_symbol(),
+        },
+        FileType::Python => python_module(rel_path),
+        FileType::Rust => rust_module(rel_path),
+        _ => None,
+    }
+}
+
+/// Languages whose `Package` symbols are nested namespace blocks
+fn has_block_namespaces(file_type: FileType) -> bool {
+    matches!(
+        file_type,
+        FileType::CSharp | FileType::Cpp | FileType::TypeScript | FileType::Rust | FileType::Ruby
+    )
+}
+
+/// Enclosing namespace blocks as (symbol index, first line, last line), outermost first
+fn namespace_scopes(symbols: &[ParsedSymbol], content: &str, file_type: FileType) -> Vec<(usize, usize, usize)> {
+    let stripped = strip_comments(content, file_type);
+    let lines: Vec<&str> = stripped.lines().collect();
+    let mut decl_lines: Vec<usize> = symbols.iter().map(|s| s.line).collect();
+    decl_lines.sort_unstable();
+    decl_lines.dedup();
+
+    let mut scopes: Vec<(usize, usize, usize)> = Vec::new();
+    for (i, symbol) in symbols.iter().enumerate() {
+        if symbol.kind != SymbolKind::Package {
+            continue;
+        }
+        // `namespace a::b::c` also emits a, b and c on the same line: keep the full name only
+        if symbols.iter().any(|s| {
+            s.kind == SymbolKind::Package && s.line == symbol.line && s.name.len() > symbol.name.len()
+        }) {
+            continue;
+        }
+        let end = if file_type == FileType::Ruby {
+            indent_body_end(&lines, symbol.line)
+        } else {
+            brace_body_end(&lines, symbol.line, &decl_lines)
+        };
+        match end {
+            Some(end) => scopes.push((i, symbol.line, end)),
+            // C# file-scoped namespace: `namespace App.Models;`
+            None if file_type == FileType::CSharp => scopes.push((i, symbol.line, lines.len().max(symbol.line))),
+            None => {}
+        }
+    }
+    scopes.sort_by_key(|&(_, start, end)| (start, usize::MAX - end));
+    scopes
+}
+
+/// Library of a Dart `part of 'owner.dart';` file, read from the owning file's `library` directive.
+/// `file_path` is the path of the part file on disk.
+pub fn dart_part_library(content: &str, file_path: &Path) -> Option<String> {
+    let caps = DART_PART_OF_RE.captures(content)?;
+    let uri = caps.get(1).or_else(|| caps.get(2))?.as_str();
+    let owner = fs::read_to_string(file_path.parent()?.join(uri)).ok()?;
+    DART_LIBRARY_RE.captures(&owner).map(|c| c[1].to_string())
+}
+
+/// Set `qualified_name` on every symbol except imports.
+/// `ext` selects the language rules, `rel_path` is the file path relative to the project root.
+pub fn assign_qualified_names(symbols: &mut [ParsedSymbol], content: &str, ext: &str, rel_path: &str) {
+    assign_qualified_names_in(symbols, content, ext, rel_path, None);
+}
+
+/// Like [`assign_qualified_names`], with `namespace` overriding the namespace declared by the file
+pub fn assign_qualified_names_in(
+    symbols: &mut [ParsedSymbol],
+    content: &str,
+    ext: &str,
+    rel_path: &str,
+    namespace: Option<String>,
+) {
+    let file_type = FileType::from_extension(ext);
+    let sep = separator(file_type);
+    let rel_path = rel_path.replace('\\', "/");
+
+    let file_ns = namespace.or_else(|| file_type.and_then(|ft| file_namespace(symbols, content, ft, &rel_path)));
+    let scopes = match file_type {
+        Some(ft) if has_block_namespaces(ft) => namespace_scopes(symbols, content, ft),
+        _ => Vec::new(),
+    };
+
+    let qualified: Vec<Option<String>> = symbols
+        .iter()
+        .enumerate()
+        .map(|(i, symbol)| {
+            if symbol.kind == SymbolKind::Import {
+                return None;
+            }
+            let mut parts: Vec<String> = Vec::new();
+            if let Some(ns) = &file_ns {
+                if !(symbol.kind == SymbolKind::Package && *ns == symbol.name) {
+                    parts.push(ns.clone());
+                }
+            }
+            for &(owner, start, end) in &scopes {
+                if owner != i && symbol.line >= start && symbol.line <= end {
+                    parts.push(symbols[owner].name.clone());
+                }
+            }
+            if let Some(container) = &symbol.container {
+                // Names that already spell out their container (`Outer.Inner` in Proto) are not prefixed twice
+                let spelled_out = symbol.name.strip_prefix(container.as_str()).is_some_and(|rest| rest.starts_with('.'));
+                if !spelled_out {
+                    parts.push(if sep == "." { container.clone() } else { container.replace('.', sep) });
+                }
+            }
+            parts.push(symbol.name.clone());
+            Some(parts.join(sep))
+        })
+        .collect();
+
+    for (symbol, q) in symbols.iter_mut().zip(qualified) {
+        symbol.qualified_name = q;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sym(name: &str, kind: SymbolKind, line: usize, container: Option<&str>) -> ParsedSymbol {
+        ParsedSymbol {
+            name: name.to_string(),
+            kind,
+            line,
+            signature: String::new(),
+            parents: vec![],
+            container: container.map(str::to_string),
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
+        }
+    }
+
+    fn qualified(symbols: &[ParsedSymbol]) -> Vec<Option<&str>> {
+        symbols.iter().map(|s| s.qualified_name.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_kotlin_package() {
+        let content = "package com.example.billing\n\nclass Invoice {\n    fun total() = 0\n}\n";
+        let mut symbols = vec![
+            sym("Invoice", SymbolKind::Class, 3, None),
+            sym("total", SymbolKind::Function, 4, Some("Invoice")),
+        ];
+        assign_qualified_names(&mut symbols, content, "kt", "src/main/kotlin/Invoice.kt");
+        assert_eq!(
+            qualified(&symbols),
+            vec![Some("com.example.billing.Invoice"), Some("com.example.billing.Invoice.total")]
+        );
+    }
+
+    #[test]
+    fn test_go_package_dir() {
+        let mut symbols = vec![
+            sym("billing", SymbolKind::Package, 1, None),
+            sym("Pay", SymbolKind::Function, 3, Some("Invoice")),
+        ];
+        assign_qualified_names(&mut symbols, "package billing\n\nfunc (i *Invoice) Pay() {}\n", "go", "internal/billing/invoice.go");
+        assert_eq!(symbols[1].qualified_name.as_deref(), Some("internal/billing.Invoice.Pay"));
+    }
+
+    #[test]
+    fn test_rust_module_path() {
+        let content = "mod tests {\n    fn helper() {}\n}\nstruct Client;\n";
+        let mut symbols = vec![
+            sym("tests", SymbolKind::Package, 1, None),
+            sym("helper", SymbolKind::Function, 2, None),
+            sym("Client", SymbolKind::Class, 4, None),
+        ];
+        assign_qualified_names(&mut symbols, content, "rs", "src/net/mod.rs");
+        assert_eq!(
+            qualified(&symbols),
+            vec![Some("crate::net::tests"), Some("crate::net::tests::helper"), Some("crate::net::Client")]
+        );
+
+        assert_eq!(rust_module("crates/core/src/db/schema.rs").as_deref(), Some("crate::db::schema"));
+        // Binaries are crate roots of their own
+        assert_eq!(rust_module("src/bin/migrate.rs").as_deref(), Some("crate"));
+        assert_eq!(rust_module("src/bin/migrate/main.rs").as_deref(), Some("crate"));
+        assert_eq!(rust_module("src/bin/migrate/args.rs").as_deref(), Some("crate::args"));
+    }
+
+    #[test]
+    fn test_csharp_namespaces() {
+        let block = "namespace App.Models\n{\n    public class User {}\n}\n";
+        let mut symbols = vec![
+            sym("App.Models", SymbolKind::Package, 1, None),
+            sym("User", SymbolKind::Class, 3, None),
+        ];
+        assign_qualified_names(&mut symbols, block, "cs", "Models/User.cs");
+        assert_eq!(symbols[1].qualified_name.as_deref(), Some("App.Models.User"));
+
+        let file_scoped = "namespace App.Services;\n\npublic class Mailer {}\n";
+        let mut symbols = vec![
+            sym("App.Services", SymbolKind::Package, 1, None),
+            sym("Mailer", SymbolKind::Class, 3, None),
+        ];
+        assign_qualified_names(&mut symbols, file_scoped, "cs", "Mailer.cs");
+        assert_eq!(symbols[1].qualified_name.as_deref(), Some("App.Services.Mailer"));
+    }
+
+    #[test]
+    fn test_python_module_path() {
+        let mut symbols = vec![sym("save", SymbolKind::Function, 2, Some("Repo"))];
+        assign_qualified_names(&mut symbols, "class Repo:\n    def save(self): pass\n", "py", "src/app/models/__init__.py");
+        assert_eq!(symbols[0].qualified_name.as_deref(), Some("app.models.Repo.save"));
+    }
+
+    #[test]
+    fn test_dart_part_files() {
+        let mut symbols = vec![sym("Cart", SymbolKind::Class, 3, None)];
+        assign_qualified_names(&mut symbols, "part of shop.models;\n\nclass Cart {}\n", "dart", "lib/cart.dart");
+        assert_eq!(symbols[0].qualified_name.as_deref(), Some("shop.models.Cart"));
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("shop.dart"), "library shop;\n\npart 'src/cart.dart';\n").unwrap();
+        let part = "part of '../shop.dart';\n\nclass Cart {}\n";
+        let library = dart_part_library(part, &dir.path().join("src/cart.dart"));
+        assert_eq!(library.as_deref(), Some("shop"));
+
+        let mut symbols = vec![sym("Cart", SymbolKind::Class, 3, None)];
+        assign_qualified_names_in(&mut symbols, part, "dart", "src/cart.dart", library);
+        assert_eq!(symbols[0].qualified_name.as_deref(), Some("shop.Cart"));
+    }
+
+    #[test]
+    fn test_proto_nested_names() {
+        let content = "package shop.v1;\n\nmessage Order {\n  message Item {}\n}\n";
+        let mut symbols = vec![
+            sym("shop.v1", SymbolKind::Package, 1, None),
+            sym("Order", SymbolKind::Class, 3, None),
+            sym("Order.Item", SymbolKind::Class, 4, Some("Order")),
+        ];
+        assign_qualified_names(&mut symbols, content, "proto", "shop.proto");
+        assert_eq!(qualified(&symbols), vec![Some("shop.v1"), Some("shop.v1.Order"), Some("shop.v1.Order.Item")]);
+    }
+}
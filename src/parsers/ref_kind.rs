@@ -0,0 +1,183 @@
+//! Reference kinds
+//!
+//! `extract_references` finds names by regex; this module looks at the tokens around a
+//! name to tell a call from an instantiation, a type annotation or a supertype, so
+//! `refs User --kind instantiation` can skip `val user: User`, and counts the arguments
+//! of calls for `refs process --arity 2`.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::db::RefKind;
+
+use super::signature;
+
+/// Lines an argument list may span
+const MAX_CALL_LINES: usize = 20;
+
+/// Type declaration lines: their depth-0 names after `:`/`extends`/... are supertypes
+static TYPE_DECL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\s*(?:@\w+(?:\([^)]*\))?\s+)*(?:(?:public|private|protected|internal|open|abstract|sealed|final|static|data|export|default|partial|readonly|unsafe|pub(?:\([\w:]+\))?)\s+)*(class|interface|object|struct|enum|trait|protocol|record|extension|impl|mixin)\b",
+    )
+    .unwrap()
+});
+/// `new Foo(` / `new com.example.Foo(`
+static NEW_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\bnew\s+(?:[\w$]+\.)*$").unwrap());
+/// Extension receiver: `fun Foo.bar()` / `fun <T> Foo<T>.bar()`
+static RECEIVER_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\bfun(?:\s*<[^>]*>)?\s*$").unwrap());
+/// Markers that put the following name in a type position
+static TYPE_MARKER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:[^:]:|->|@|\b(?:as\??|is|instanceof|extends|implements|mut|dyn|impl|const)|[&*])\s*$").unwrap()
+});
+
+/// Kind of the reference to the CamelCase name at `start..end` of `line`
+pub fn classify(line: &str, start: usize, end: usize) -> RefKind {
+    let before = &line[..start];
+    let after = &line[end..];
+    let rest = after.trim_start();
+    let (top, parens, angles) = top_level(before);
+
+    // Names in the body of a one-line declaration (`struct A { x: B }`) are not supertypes
+    if let Some(caps) = TYPE_DECL_RE.captures(before).filter(|_| !top.contains('{')) {
+        let keyword = &caps[1];
+        if parens == 0 && angles == 0 {
+            let supertypes = [":", " extends ", " implements ", " with ", " for "];
+            if matches!(keyword, "extension" | "impl") || supertypes.iter().any(|m| top.contains(m)) {
+                return RefKind::Extension;
+            }
+        }
+        // Python: class Repo(Base):
+        if keyword == "class" && parens == 1 && angles == 0 && line.trim_end().ends_with(':') {
+            return RefKind::Extension;
+        }
+    }
+    if RECEIVER_RE.is_match(before) && (rest.starts_with('.') || rest.starts_with('<')) {
+        return RefKind::Extension;
+    }
+    if NEW_RE.is_match(before) || rest.starts_with("::new(") || is_ruby_new(rest) {
+        return RefKind::Instantiation;
+    }
+    if rest.starts_with('(') {
+        return match before.trim_end().chars().last() {
+            Some('.') => RefKind::Call,
+            Some('@') => RefKind::Type,
+            _ => RefKind::Instantiation,
+        };
+    }
+    if angles > 0 || TYPE_MARKER_RE.is_match(before) || rest.starts_with(['<', '?', '>', '[']) {
+        return RefKind::Type;
+    }
+    // Java/C# declarations: `User user = ...`
+    if after.starts_with(char::is_whitespace) && rest.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        let word: String = rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        if !matches!(word.as_str(), "and" | "or" | "in" | "is" | "as" | "if" | "else" | "instanceof") {
+            return RefKind::Type;
+        }
+    }
+    RefKind::Other
+}
+
+/// Number of arguments of the call or instantiation whose name ends at byte `end` of
+/// `lines[idx]`, following the argument list onto later lines; None if no `(` follows.
+/// `Repo::new(...)` and Ruby `User.new(...)` count as calls of `Repo` / `User`.
+pub fn call_arity(lines: &[&str], idx: usize, end: usize) -> Option<usize> {
+    let rest = lines.get(idx)?.get(end..)?.trim_start();
+    let rest = rest.strip_prefix("::new").or_else(|| rest.strip_prefix(".new")).unwrap_or(rest);
+    let first = signature::skip_generics(rest).strip_prefix('(')?;
+    let mut text = first.to_string();
+    for line in lines.iter().skip(idx + 1).take(MAX_CALL_LINES) {
+        if signature::balanced(&text).is_some() {
+            break;
+        }
+        text.push('\n');
+        text.push_str(line);
+    }
+    signature::balanced(&text).map(|args| signature::split_top_level(args).len())
+}
+
+/// Ruby constructor call: `User.new` with or without parentheses
+fn is_ruby_new(rest: &str) -> bool {
+    rest.strip_prefix(".new").is_some_and(|tail| !tail.starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+}
+
+/// Text of `before` outside brackets, and how many `(` and `<` are still open at its end
+fn top_level(before: &str) -> (String, usize, usize) {
+    let mut top = String::with_capacity(before.len());
+    let (mut parens, mut angles) = (0usize, 0usize);
+    let mut prev = ' ';
+    for c in before.chars() {
+        match c {
+            '(' => parens += 1,
+            ')' => parens = parens.saturating_sub(1),
+            '<' => angles += 1,
+            '>' if !matches!(prev, '-' | '=') => angles = angles.saturating_sub(1),
+            _ if parens == 0 && angles == 0 => top.push(c),
+            _ => {}
+        }
+        prev = c;
+    }
+    (top, parens, angles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind_of(line: &str, name: &str) -> RefKind {
+        let start = line.find(name).unwrap();
+        classify(line, start, start + name.len())
+    }
+
+    #[test]
+    fn test_instantiations_and_calls() {
+        assert_eq!(kind_of("val user = User(name)", "User"), RefKind::Instantiation);
+        let line = "User user = new User(name);";
+        assert_eq!(classify(line, 16, 20), RefKind::Instantiation);
+        assert_eq!(kind_of("let repo = Repo::new(pool);", "Repo"), RefKind::Instantiation);
+        assert_eq!(kind_of("@user = User.new", "User"), RefKind::Instantiation);
+        assert_eq!(kind_of("err := client.Process(ctx)", "Process"), RefKind::Call);
+    }
+
+    #[test]
+    fn test_type_positions() {
+        assert_eq!(kind_of("val user: User = load()", "User"), RefKind::Type);
+        assert_eq!(kind_of("fun all(): List<User>", "User"), RefKind::Type);
+        assert_eq!(kind_of("fn get(pool: &Pool) -> Option<User> {", "Pool"), RefKind::Type);
+        assert_eq!(kind_of("const u = data as User;", "User"), RefKind::Type);
+        assert_eq!(kind_of("if (x is User) {", "User"), RefKind::Type);
+        assert_eq!(kind_of("User user = load();", "User"), RefKind::Type);
+        assert_eq!(kind_of("@Inject(Names.DB)", "Inject"), RefKind::Type);
+    }
+
+    #[test]
+    fn test_extensions() {
+        assert_eq!(kind_of("class Admin(val id: Id) : User(id), Auditable {", "User"), RefKind::Extension);
+        assert_eq!(kind_of("class Admin(val id: Id) : User(id), Auditable {", "Auditable"), RefKind::Extension);
+        assert_eq!(kind_of("class Admin(val id: Id) : User(id) {", "Id"), RefKind::Type);
+        assert_eq!(kind_of("public class Admin extends User implements Auditable {", "Auditable"), RefKind::Extension);
+        assert_eq!(kind_of("class Admin(User):", "User"), RefKind::Extension);
+        assert_eq!(kind_of("impl Display for User {", "Display"), RefKind::Extension);
+        assert_eq!(kind_of("extension User: Codable {", "Codable"), RefKind::Extension);
+        assert_eq!(kind_of("fun User.fullName(): String =", "User"), RefKind::Extension);
+        assert_eq!(kind_of("pub struct Admin { user: User }", "User"), RefKind::Type);
+    }
+
+    #[test]
+    fn test_other() {
+        assert_eq!(kind_of("val u = User.create()", "User"), RefKind::Other);
+        assert_eq!(kind_of("paint(Color.RED)", "Color"), RefKind::Other);
+    }
+
+    #[test]
+    fn test_call_arity() {
+        let lines = ["val r = process(a, mapOf<String, Int>(), listOf(1, 2))", "save(", "    item,", "    retries = 3,", ")", "Repo::new(pool)", "reset()", "val f = process"];
+        let arity = |idx: usize, name: &str| call_arity(&lines, idx, lines[idx].find(name).unwrap() + name.len());
+        assert_eq!(arity(0, "process"), Some(3));
+        assert_eq!(arity(1, "save"), Some(2));
+        assert_eq!(arity(5, "Repo"), Some(1));
+        assert_eq!(arity(6, "reset"), Some(0));
+        assert_eq!(arity(7, "process"), None);
+    }
+}
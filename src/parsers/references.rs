@@ -0,0 +1,249 @@
+//! References for snake_case languages
+//!
+//! `extract_references` only knows CamelCase names and camelCase calls, so most
+//! function references in Python, Ruby, Rust and Go are missed: `get_user(` is not
+//! a match, Ruby calls need no parentheses, Rust calls go through paths and macros.
+//! These extractors keep the CamelCase pass and add each language's call patterns.
+
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::db::RefKind;
+use super::{
+    ref_kind, ref_noise, string_refs, strip_comments, truncate_context, FileType, ParsedRef, ParsedSymbol, IDENTIFIER_RE,
+    KEYWORDS,
+};
+
+/// Languages with their own reference rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefLanguage {
+    Python,
+    Ruby,
+    Rust,
+    Go,
+}
+
+/// `name(` with snake_case or camelCase names (`\w` and `\p{Ll}` are Unicode-aware)
+static CALL_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b([\p{Ll}_]\w*)\s*\(").unwrap());
+/// Python decorators: `@login_required`, `@app.route(` (last segment)
+static PY_DECORATOR_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*@(?:[\w.]*\.)?([\p{Ll}_]\w*)").unwrap());
+/// Ruby method names may end in `!` or `?`
+static RB_CALL_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b([\p{Ll}_]\w*[!?]?)\s*\(").unwrap());
+/// Ruby calls on a receiver need no parentheses: `user.save!`, `list&.empty?`
+static RB_METHOD_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\.([\p{Ll}_]\w*[!?]?)").unwrap());
+/// Ruby method names passed as symbols: `before_action :authenticate_user!`
+static RB_SYMBOL_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?:^|[\s,(\[]):([\p{Ll}_]\w*[!?]?)").unwrap());
+/// Ruby commands without parentheses at the start of a statement: `validate_email user`
+static RB_COMMAND_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^\s*([\p{Ll}_]\w*[!?]?)\s+([\w:"'@\[]+)"#).unwrap());
+/// Rust macro invocations: `format_args!(`, `sql_query![`
+static RS_MACRO_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b([\p{Ll}_]\w*)!\s*[(\[{]").unwrap());
+/// Rust functions used through a path without a call: `.map(Self::parse_line)`
+static RS_PATH_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"::([\p{Ll}_]\w*)\b").unwrap());
+
+static PYTHON_KEYWORDS: &[&str] = &[
+    "def", "class", "return", "elif", "not", "and", "or", "lambda", "yield", "assert", "del", "with", "pass",
+    "raise", "except", "global", "nonlocal", "async", "await", "from", "print", "len", "str", "int", "dict",
+    "list", "set", "tuple", "range", "isinstance", "self", "cls", "type", "bool", "float", "open", "getattr",
+    "setattr", "hasattr", "enumerate", "zip", "map", "filter", "sorted", "min", "max", "sum", "any", "all",
+];
+static RUBY_KEYWORDS: &[&str] = &[
+    "def", "end", "unless", "elsif", "until", "case", "then", "return", "puts", "print", "require",
+    "require_relative", "include", "extend", "prepend", "raise", "rescue", "ensure", "yield", "and", "or", "not",
+    "self", "lambda", "proc", "loop", "new", "attr_accessor", "attr_reader", "attr_writer", "module", "alias",
+    "defined?", "begin", "nil", "redo", "retry", "undef",
+];
+static RUST_KEYWORDS: &[&str] = &[
+    "fn", "let", "mut", "match", "loop", "impl", "pub", "use", "mod", "ref", "move", "self", "crate", "unsafe",
+    "async", "await", "dyn", "struct", "trait", "type", "static", "extern", "println", "eprintln", "print",
+    "eprint", "format", "vec", "assert", "assert_eq", "assert_ne", "debug_assert", "panic", "write", "writeln",
+    "matches", "todo", "unimplemented", "unreachable", "Some", "None", "Ok", "Err", "Self", "Box", "Vec", "Option",
+];
+static GO_KEYWORDS: &[&str] = &[
+    "func", "range", "defer", "switch", "case", "select", "make", "len", "cap", "append", "new", "panic",
+    "recover", "print", "println", "delete", "copy", "close", "string", "int", "int64", "byte", "rune", "error",
+    "float64", "uint", "bool", "go", "chan", "map", "struct", "type", "var", "const",
+];
+
+impl RefLanguage {
+    pub fn file_type(self) -> FileType {
+        match self {
+            RefLanguage::Python => FileType::Python,
+            RefLanguage::Ruby => FileType::Ruby,
+            RefLanguage::Rust => FileType::Rust,
+            RefLanguage::Go => FileType::Go,
+        }
+    }
+
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            RefLanguage::Python => PYTHON_KEYWORDS,
+            RefLanguage::Ruby => RUBY_KEYWORDS,
+            RefLanguage::Rust => RUST_KEYWORDS,
+            RefLanguage::Go => GO_KEYWORDS,
+        }
+    }
+
+    /// Import and declaration lines whose names are not usages
+    fn skips_line(self, trimmed: &str) -> bool {
+        let prefixes: &[&str] = match self {
+            RefLanguage::Python => &["import ", "from "],
+            RefLanguage::Ruby => &["require ", "require_relative ", "require(", "require_relative("],
+            RefLanguage::Rust => &["use ", "pub use ", "pub(crate) use ", "mod ", "pub mod ", "extern crate "],
+            RefLanguage::Go => &["import ", "package "],
+        };
+        prefixes.iter().any(|p| trimmed.starts_with(p)) || (self == RefLanguage::Go && is_go_import_spec(trimmed))
+    }
+}
+
+/// A line of a Go `import (...)` block: `"fmt"` or `alias "github.com/x/y"`
+fn is_go_import_spec(trimmed: &str) -> bool {
+    let words: Vec<&str> = trimmed.split_whitespace().collect();
+    matches!(words.as_slice(), [path] | [_, path] if path.starts_with('"') && path.ends_with('"'))
+}
+
+/// Extract references with the rules of `language`: CamelCase types like
+/// `extract_references`, plus the language's call patterns for snake_case names
+pub fn extract_references_for(content: &str, defined_symbols: &[ParsedSymbol], language: RefLanguage) -> Result<Vec<ParsedRef>> {
+    let defined_names: HashSet<&str> = defined_symbols.iter().map(|s| s.name.as_str()).collect();
+    let language_keywords: HashSet<&str> = language.keywords().iter().copied().collect();
+    let stripped = strip_comments(content, language.file_type());
+    let original: Vec<&str> = content.lines().collect();
+    let noise = ref_noise();
+
+    let lines: Vec<&str> = stripped.lines().collect();
+
+    let mut refs = Vec::new();
+    for (idx, line) in lines.iter().copied().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.len() > noise.max_line_length || language.skips_line(trimmed) {
+            continue;
+        }
+        let line_num = idx + 1;
+        let context = truncate_context(original.get(idx).map_or(trimmed, |l| l.trim()));
+        // Byte offsets already recorded on this line, so overlapping patterns add a name once
+        let mut seen: HashSet<usize> = HashSet::new();
+        let mut push = |name: &str, start: usize, kind: RefKind| {
+            let is_keyword = noise.is_stopped(name, KEYWORDS.contains(name) || language_keywords.contains(name));
+            if name.chars().count() >= noise.min_name_length && !is_keyword && !defined_names.contains(name) && seen.insert(start) {
+                let arity = matches!(kind, RefKind::Call | RefKind::Instantiation)
+                    .then(|| ref_kind::call_arity(&lines, idx, start + name.len()))
+                    .flatten();
+                refs.push(ParsedRef { name: name.to_string(), line: line_num, context: context.clone(), kind, arity });
+            }
+        };
+
+        let strings = string_refs::scan(line);
+        for s in &strings {
+            push(&s.name, s.start, RefKind::String);
+        }
+        for m in IDENTIFIER_RE.captures_iter(line).filter_map(|c| c.get(1)) {
+            if !strings.iter().any(|s| s.contains(m.start())) {
+                push(m.as_str(), m.start(), ref_kind::classify(line, m.start(), m.end()));
+            }
+        }
+
+        let call_re = if language == RefLanguage::Ruby { &*RB_CALL_RE } else { &*CALL_RE };
+        for m in call_re.captures_iter(line).filter_map(|c| c.get(1)) {
+            push(m.as_str(), m.start(), RefKind::Call);
+        }
+
+        match language {
+            RefLanguage::Python => {
+                if let Some(m) = PY_DECORATOR_RE.captures(line).and_then(|c| c.get(1)) {
+                    push(m.as_str(), m.start(), RefKind::Type);
+                }
+            }
+            RefLanguage::Ruby => {
+                for m in RB_METHOD_RE.captures_iter(line).filter_map(|c| c.get(1)) {
+                    push(m.as_str(), m.start(), RefKind::Call);
+                }
+                for m in RB_SYMBOL_RE.captures_iter(line).filter_map(|c| c.get(1)) {
+                    push(m.as_str(), m.start(), RefKind::Other);
+                }
+                if let Some(caps) = RB_COMMAND_RE.captures(line) {
+                    let next_word: String = caps[2].chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+                    // `x if cond` / `x and y` are not commands; `x = 1` never matches
+                    if !matches!(next_word.as_str(), "if" | "unless" | "while" | "until" | "do" | "and" | "or" | "rescue" | "then") {
+                        let m = caps.get(1).unwrap();
+                        push(m.as_str(), m.start(), RefKind::Call);
+                    }
+                }
+            }
+            RefLanguage::Rust => {
+                for m in RS_MACRO_RE.captures_iter(line).filter_map(|c| c.get(1)) {
+                    push(m.as_str(), m.start(), RefKind::Call);
+                }
+                for m in RS_PATH_RE.captures_iter(line).filter_map(|c| c.get(1)) {
+                    push(m.as_str(), m.start(), RefKind::Other);
+                }
+            }
+            RefLanguage::Go => {}
+        }
+    }
+
+    Ok(refs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(content: &str, language: RefLanguage) -> Vec<(String, usize, RefKind)> {
+        extract_references_for(content, &[], language)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.name, r.line, r.kind))
+            .collect()
+    }
+
+    fn has(refs: &[(String, usize, RefKind)], name: &str, line: usize, kind: RefKind) -> bool {
+        refs.iter().any(|(n, l, k)| n == name && *l == line && *k == kind)
+    }
+
+    #[test]
+    fn test_python_references() {
+        let code = "from app.users import get_user\n# load_all() in a comment\n@login_required\ndef show(request):\n    user = get_user(request.user_id)\n    return render_page(user)\n";
+        let refs = names(code, RefLanguage::Python);
+        assert!(has(&refs, "get_user", 5, RefKind::Call), "got {:?}", refs);
+        assert!(has(&refs, "render_page", 6, RefKind::Call));
+        assert!(has(&refs, "login_required", 3, RefKind::Type));
+        assert!(!refs.iter().any(|(_, l, _)| *l == 1 || *l == 2), "imports and comments are skipped: {:?}", refs);
+    }
+
+    #[test]
+    fn test_ruby_references() {
+        let code = "class UsersController < ApplicationController\n  before_action :authenticate_user!\n  def create\n    @user = User.new(build_params(params))\n    @user.save!\n    notify_admins @user\n    count = 1\n  end\nend\n";
+        let refs = names(code, RefLanguage::Ruby);
+        assert!(has(&refs, "authenticate_user!", 2, RefKind::Other), "got {:?}", refs);
+        assert!(has(&refs, "build_params", 4, RefKind::Call));
+        assert!(has(&refs, "User", 4, RefKind::Instantiation));
+        assert!(has(&refs, "save!", 5, RefKind::Call));
+        assert!(has(&refs, "notify_admins", 6, RefKind::Call));
+        assert!(!refs.iter().any(|(n, _, _)| n == "count" || n == "end" || n == "new"));
+    }
+
+    #[test]
+    fn test_rust_references() {
+        let code = "use crate::db::open_db;\nfn run() {\n    let conn = db::open_db(&root)?;\n    let rows = lines.iter().map(Self::parse_line);\n    log_event!(\"done\");\n}\n";
+        let refs = names(code, RefLanguage::Rust);
+        assert!(has(&refs, "open_db", 3, RefKind::Call), "got {:?}", refs);
+        assert!(has(&refs, "parse_line", 4, RefKind::Other));
+        assert!(has(&refs, "log_event", 5, RefKind::Call));
+        assert!(!refs.iter().any(|(_, l, _)| *l == 1));
+        assert_eq!(refs.iter().filter(|(n, l, _)| n == "open_db" && *l == 3).count(), 1);
+    }
+
+    #[test]
+    fn test_go_references() {
+        let code = "import (\n\t\"fmt\"\n\tlog \"github.com/x/logger\"\n)\n\nfunc main() {\n\tcfg := load_config()\n\tserver.ListenAndServe(cfg)\n\tпроверитьКонтракт(cfg)\n}\n";
+        let refs = names(code, RefLanguage::Go);
+        assert!(has(&refs, "load_config", 7, RefKind::Call), "got {:?}", refs);
+        assert!(has(&refs, "ListenAndServe", 8, RefKind::Call));
+        assert!(has(&refs, "проверитьКонтракт", 9, RefKind::Call));
+        assert!(!refs.iter().any(|(_, l, _)| *l <= 4));
+    }
+}
@@ -0,0 +1,320 @@
+//! Nested symbol containment
+//!
+//! Fills `ParsedSymbol::container` after a parser has run, for parsers that
+//! do not track scopes in a syntax tree (`LanguageParser::sets_containers`):
+//! brace blocks for C-like languages, indentation for Python/Ruby and
+//! `package` statements for Perl. Symbols declared in a function body nest
+//! under the function (`Main.run`) instead of passing for members.
+
+use crate::db::SymbolKind;
+use super::{FileType, ParsedSymbol};
+
+/// How scopes are delimited in a language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeStyle {
+    /// `{ ... }` blocks
+    Braces,
+    /// Body is the following lines indented deeper than the declaration
+    Indent,
+    /// A `package` statement opens a scope that lasts until the next one
+    Package,
+}
+
+impl ScopeStyle {
+    pub fn for_file_type(file_type: FileType) -> ScopeStyle {
+        match file_type {
+            FileType::Python | FileType::Ruby => ScopeStyle::Indent,
+            FileType::Perl => ScopeStyle::Package,
+            _ => ScopeStyle::Braces,
+        }
+    }
+}
+
+/// Symbol kinds whose body can hold other symbols: members of types, locals of functions
+fn opens_scope(kind: SymbolKind) -> bool {
+    matches!(
+        kind,
+        SymbolKind::Class | SymbolKind::Interface | SymbolKind::Object | SymbolKind::Enum | SymbolKind::Function
+    )
+}
+
+/// Name members are qualified with (`impl Foo for Bar` -> `Bar`)
+pub(super) fn container_name(symbol: &ParsedSymbol) -> &str {
+    let name = symbol.name.as_str();
+    match name.strip_prefix("impl ") {
+        Some(rest) => {
+            let target = rest.rsplit(" for ").next().unwrap_or(rest);
+            target.split('<').next().unwrap_or(target).trim()
+        }
+        None => name,
+    }
+}
+
+/// Set `container` on every symbol declared inside another symbol's body.
+/// `content` should have comments stripped; symbols with a container already set are left alone.
+pub fn assign_containers(symbols: &mut [ParsedSymbol], content: &str, style: ScopeStyle) {
+    if symbols.len() < 2 {
+        return;
+    }
+    let lines: Vec<&str> = content.lines().collect();
+
+    // Body line range of every scope-opening symbol
+    let mut scopes: Vec<(usize, usize, usize)> = Vec::new(); // (symbol index, first line, last line)
+    match style {
+        ScopeStyle::Package => {
+            let mut packages: Vec<(usize, usize)> = symbols
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| s.kind == SymbolKind::Package)
+                .map(|(i, s)| (i, s.line))
+                .collect();
+            packages.sort_by_key(|&(_, line)| line);
+            for (n, &(i, line)) in packages.iter().enumerate() {
+                let end = packages.get(n + 1).map(|&(_, next)| next - 1).unwrap_or(lines.len());
+                scopes.push((i, line, end.max(line)));
+            }
+        }
+        ScopeStyle::Braces | ScopeStyle::Indent => {
+            let mut decl_lines: Vec<usize> = symbols.iter().map(|s| s.line).collect();
+            decl_lines.sort_unstable();
+            decl_lines.dedup();
+            for (i, symbol) in symbols.iter().enumerate() {
+                if !opens_scope(symbol.kind) {
+                    continue;
+                }
+                let end = if style == ScopeStyle::Braces {
+                    brace_body_end(&lines, symbol.line, &decl_lines)
+                } else {
+                    indent_body_end(&lines, symbol.line)
+                };
+                if let Some(end) = end {
+                    scopes.push((i, symbol.line, end));
+                }
+            }
+        }
+    }
+    if scopes.is_empty() {
+        return;
+    }
+
+    // Innermost enclosing scope of each symbol
+    let scope_kind = |i: usize| opens_scope(symbols[i].kind) || style == ScopeStyle::Package;
+    let enclosing: Vec<Option<usize>> = symbols
+        .iter()
+        .enumerate()
+        .map(|(i, symbol)| {
+            if matches!(symbol.kind, SymbolKind::Import | SymbolKind::Package) {
+                return None;
+            }
+            scopes
+                .iter()
+                .filter(|&&(owner, start, end)| {
+                    owner != i
+                        && symbol.line >= start
+                        && symbol.line <= end
+                        // A scope opened on the same line only holds non-scope members,
+                        // e.g. constructor properties in `class Foo(val x: Int) {`
+                        && (symbol.line > start || !scope_kind(i))
+                })
+                .min_by_key(|&&(_, start, end)| (end - start, usize::MAX - start))
+                .map(|&(owner, _, _)| owner)
+        })
+        .collect();
+
+    let chains: Vec<Option<String>> = (0..symbols.len())
+        .map(|i| {
+            let mut names: Vec<&str> = Vec::new();
+            let mut current = enclosing[i];
+            while let Some(owner) = current {
+                if names.len() > 32 {
+                    break;
+                }
+                names.push(container_name(&symbols[owner]));
+                current = enclosing[owner];
+            }
+            if names.is_empty() {
+                return None;
+            }
+            names.reverse();
+            Some(names.join("."))
+        })
+        .collect();
+
+    for (symbol, chain) in symbols.iter_mut().zip(chains) {
+        if symbol.container.is_none() {
+            symbol.container = chain;
+        }
+    }
+}
+
+/// Find the line of the `}` closing the body that starts at or after `line` (1-based).
+/// Gives up at a `;` outside parentheses or at the next declaration, whichever comes first.
+pub(super) fn brace_body_end(lines: &[&str], line: usize, decl_lines: &[usize]) -> Option<usize> {
+    let mut depth = 0i32;
+    let mut parens = 0i32;
+    let mut opened = false;
+    for (offset, text) in lines.get(line.checked_sub(1)?..)?.iter().enumerate() {
+        let line_no = line + offset;
+        if !opened && offset > 0 && parens == 0 && decl_lines.binary_search(&line_no).is_ok() {
+            return None;
+        }
+        let mut quote: Option<char> = None;
+        let mut escaped = false;
+        for c in text.chars() {
+            if let Some(q) = quote {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+                continue;
+            }
+            match c {
+                '"' | '`' => quote = Some(c),
+                '(' | '[' => parens += 1,
+                ')' | ']' => parens -= 1,
+                '{' => {
+                    opened = true;
+                    depth += 1;
+                }
+                '}' => {
+                    depth -= 1;
+                    if !opened || depth == 0 {
+                        return opened.then_some(line_no);
+                    }
+                }
+                ';' if !opened && parens <= 0 => return None,
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+/// Last line indented deeper than the declaration at `line` (1-based)
+pub(super) fn indent_body_end(lines: &[&str], line: usize) -> Option<usize> {
+    let decl = lines.get(line.checked_sub(1)?)?;
+    let indent = decl.len() - decl.trim_start().len();
+    let mut end = None;
+    for (offset, text) in lines[line..].iter().enumerate() {
+        if text.trim().is_empty() {
+            continue;
+        }
+        if text.len() - text.trim_start().len() <= indent {
+            break;
+        }
+        end = Some(line + offset + 1);
+    }
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sym(name: &str, kind: SymbolKind, line: usize) -> ParsedSymbol {
+        ParsedSymbol {
+            name: name.to_string(),
+            kind,
+            line,
+            signature: String::new(),
+            parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
+        }
+    }
+
+    fn containers(symbols: &[ParsedSymbol]) -> Vec<Option<&str>> {
+        symbols.iter().map(|s| s.container.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_brace_containers() {
+        let content = "class Outer(val id: Int) {\n    fun run() {\n        val s = \"}\"\n    }\n    class Inner {\n        fun go() {}\n    }\n}\nfun top() {}\n";
+        let mut symbols = vec![
+            sym("Outer", SymbolKind::Class, 1),
+            sym("id", SymbolKind::Property, 1),
+            sym("run", SymbolKind::Function, 2),
+            sym("Inner", SymbolKind::Class, 5),
+            sym("go", SymbolKind::Function, 6),
+            sym("top", SymbolKind::Function, 9),
+        ];
+        assign_containers(&mut symbols, content, ScopeStyle::Braces);
+        assert_eq!(
+            containers(&symbols),
+            vec![None, Some("Outer"), Some("Outer"), Some("Outer"), Some("Outer.Inner"), None]
+        );
+    }
+
+    #[test]
+    fn test_function_locals_nest_under_the_function() {
+        let content = "class Main {\n    fun run() {\n        val r = load()\n    }\n    val top = 2\n}\n";
+        let mut symbols = vec![
+            sym("Main", SymbolKind::Class, 1),
+            sym("run", SymbolKind::Function, 2),
+            sym("r", SymbolKind::Property, 3),
+            sym("top", SymbolKind::Property, 5),
+        ];
+        assign_containers(&mut symbols, content, ScopeStyle::Braces);
+        assert_eq!(containers(&symbols), vec![None, Some("Main"), Some("Main.run"), Some("Main")]);
+    }
+
+    #[test]
+    fn test_class_without_body() {
+        let content = "data class Empty(val a: Int)\nclass Next {\n    fun b() {}\n}\n";
+        let mut symbols = vec![
+            sym("Empty", SymbolKind::Class, 1),
+            sym("Next", SymbolKind::Class, 2),
+            sym("b", SymbolKind::Function, 3),
+        ];
+        assign_containers(&mut symbols, content, ScopeStyle::Braces);
+        assert_eq!(containers(&symbols), vec![None, None, Some("Next")]);
+    }
+
+    #[test]
+    fn test_rust_impl_container() {
+        let content = "impl Display for User {\n    fn fmt(&self) {}\n}\n";
+        let mut symbols = vec![
+            sym("impl Display for User", SymbolKind::Class, 1),
+            sym("fmt", SymbolKind::Function, 2),
+        ];
+        assign_containers(&mut symbols, content, ScopeStyle::Braces);
+        assert_eq!(symbols[1].container.as_deref(), Some("User"));
+    }
+
+    #[test]
+    fn test_indent_containers() {
+        let content = "class A:\n    def f(self):\n        pass\n\n    class B:\n        def g(self):\n            pass\n\ndef h():\n    pass\n";
+        let mut symbols = vec![
+            sym("A", SymbolKind::Class, 1),
+            sym("f", SymbolKind::Function, 2),
+            sym("B", SymbolKind::Class, 5),
+            sym("g", SymbolKind::Function, 6),
+            sym("h", SymbolKind::Function, 9),
+        ];
+        assign_containers(&mut symbols, content, ScopeStyle::Indent);
+        assert_eq!(containers(&symbols), vec![None, Some("A"), Some("A"), Some("A.B"), None]);
+    }
+
+    #[test]
+    fn test_perl_package_containers() {
+        let content = "package Foo;\nsub a {}\npackage Bar;\nsub b {}\n";
+        let mut symbols = vec![
+            sym("Foo", SymbolKind::Package, 1),
+            sym("a", SymbolKind::Function, 2),
+            sym("Bar", SymbolKind::Package, 3),
+            sym("b", SymbolKind::Function, 4),
+        ];
+        assign_containers(&mut symbols, content, ScopeStyle::Package);
+        assert_eq!(containers(&symbols), vec![None, Some("Foo"), None, Some("Bar")]);
+    }
+}
@@ -0,0 +1,420 @@
+//! Declaration signatures
+//!
+//! Parsers store the declaration line as signature. This pass turns it into the whole
+//! declaration header: lines are joined while brackets (including generic `<...>`
+//! parameter lists) are open or the header obviously continues, and the body from a
+//! top-level `{` or `;` on is dropped, so `fun <T : Entity> save(item: T): Result<T> {`
+//! is stored as `fun <T : Entity> save(item: T): Result<T>`.
+//! `parameters`, `arity` and `signature_hash` read the parameter list back out of a header
+//! to tell overloads of the same name apart.
+
+use crate::db::SymbolKind;
+use super::span::{header_continues, MAX_HEADER_LINES};
+use super::{FileType, ParsedSymbol};
+
+/// Replace line signatures with full declaration headers.
+/// Signatures a parser built itself (anything other than the trimmed declaration line) are kept.
+pub fn normalize_signatures(symbols: &mut [ParsedSymbol], content: &str, ext: &str) {
+    let file_type = FileType::from_extension(ext);
+    // Ruby blocks and Perl/WSDL declarations do not use brace bodies
+    if matches!(file_type, Some(FileType::Ruby | FileType::Perl | FileType::Wsdl)) {
+        return;
+    }
+    let syntax = Syntax {
+        generics: file_type != Some(FileType::Python),
+        lifetimes: file_type == Some(FileType::Rust),
+    };
+    let lines: Vec<&str> = content.lines().collect();
+
+    for symbol in symbols.iter_mut() {
+        if matches!(symbol.kind, SymbolKind::Import | SymbolKind::Package) {
+            continue;
+        }
+        let Some(line) = symbol.line.checked_sub(1).and_then(|i| lines.get(i)) else { continue };
+        if symbol.signature != line.trim() {
+            continue;
+        }
+        // Initializers of properties and constants can span many lines: keep their first line
+        let max_lines = if matches!(symbol.kind, SymbolKind::Property | SymbolKind::Constant) { 1 } else { MAX_HEADER_LINES };
+        let header = declaration_header(&lines, symbol.line, max_lines, syntax);
+        if !header.is_empty() {
+            symbol.signature = header;
+        }
+    }
+}
+
+/// Bracket and quote rules of a language
+#[derive(Debug, Clone, Copy)]
+pub struct Syntax {
+    /// `<` / `>` delimit generic parameter lists
+    pub generics: bool,
+    /// A lone `'` starts a Rust lifetime or label rather than a string
+    pub lifetimes: bool,
+}
+
+/// Header of the declaration starting at `line` (1-based), joined onto one line
+/// from at most `max_lines` lines
+pub fn declaration_header(lines: &[&str], line: usize, max_lines: usize, syntax: Syntax) -> String {
+    let mut header = String::new();
+    let mut open: Vec<char> = Vec::new();
+    let mut in_comment = false;
+
+    for idx in line - 1..lines.len().min(line - 1 + max_lines) {
+        let (piece, done) = scan_line(lines[idx], &mut open, &mut in_comment, syntax);
+        append_piece(&mut header, piece.trim());
+        if done {
+            break;
+        }
+        let next = lines.get(idx + 1).copied();
+        // An expression body (`fun f() =\n  expr`) is not part of the header
+        let continues = header_continues(&header, next) && !header.ends_with('=') && !header.ends_with("=>");
+        if open.is_empty() && !in_comment && !continues {
+            break;
+        }
+    }
+
+    let header = header.trim_end();
+    let header = header.strip_suffix(" =>").or_else(|| header.strip_suffix(" =")).unwrap_or(header);
+    header.trim_end().to_string()
+}
+
+/// Header part of one line, and whether the header ended on it (top-level `{` or `;`).
+/// `open` is the stack of unclosed brackets; a `<` that is never closed (a comparison)
+/// is dropped when its enclosing bracket closes. Strings are copied verbatim, comments are dropped.
+fn scan_line(text: &str, open: &mut Vec<char>, in_comment: &mut bool, syntax: Syntax) -> (String, bool) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1).copied();
+        if *in_comment {
+            if c == '*' && next == Some('/') {
+                *in_comment = false;
+                i += 1;
+            }
+            i += 1;
+            continue;
+        }
+        match c {
+            '/' if next == Some('/') => break,
+            '/' if next == Some('*') => {
+                *in_comment = true;
+                i += 2;
+                continue;
+            }
+            '"' if syntax.lifetimes && raw_string_hashes(&chars, i).is_some() => {
+                // Rust raw string: r#"..."# ends at a quote followed by as many hashes
+                let hashes = raw_string_hashes(&chars, i).unwrap_or(0);
+                let end = (i + 1..chars.len())
+                    .find(|&j| chars[j] == '"' && chars[j + 1..].iter().take_while(|&&h| h == '#').count() >= hashes)
+                    .map_or(chars.len(), |j| (j + 1 + hashes).min(chars.len()));
+                out.extend(&chars[i..end]);
+                i = end;
+                continue;
+            }
+            '"' | '`' | '\'' => {
+                let closing = chars[i + 1..].iter().position(|&q| q == c).map(|p| i + 1 + p);
+                match closing {
+                    Some(end) if c != '\'' || !syntax.lifetimes || end - i <= 3 => {
+                        out.extend(&chars[i..=end]);
+                        i = end + 1;
+                        continue;
+                    }
+                    _ => out.push(c),
+                }
+            }
+            '(' | '[' => {
+                open.push(c);
+                out.push(c);
+            }
+            ')' | ']' => {
+                let opening = if c == ')' { '(' } else { '[' };
+                if let Some(pos) = open.iter().rposition(|&o| o == opening) {
+                    open.truncate(pos);
+                }
+                out.push(c);
+            }
+            '<' if syntax.generics && next != Some('=') && next != Some('<') && prev != Some('<') => {
+                open.push(c);
+                out.push(c);
+            }
+            '>' if syntax.generics && !matches!(prev, Some('-' | '=')) && next != Some('=') => {
+                if open.last() == Some(&'<') {
+                    open.pop();
+                }
+                out.push(c);
+            }
+            '{' | ';' if open.iter().all(|&o| o == '<') => {
+                open.clear();
+                return (out, true);
+            }
+            _ => out.push(c),
+        }
+        i += 1;
+    }
+    (out, false)
+}
+
+/// Number of `#` of a Rust raw string opened by the quote at `quote`, if it is one
+fn raw_string_hashes(chars: &[char], quote: usize) -> Option<usize> {
+    let hashes = chars[..quote].iter().rev().take_while(|&&c| c == '#').count();
+    let r = quote.checked_sub(hashes + 1)?;
+    let starts_word = r == 0 || !(chars[r - 1].is_alphanumeric() || chars[r - 1] == '_');
+    (chars[r] == 'r' && starts_word).then_some(hashes)
+}
+
+/// Append a line of the header with whitespace collapsed and no padding inside brackets
+fn append_piece(header: &mut String, piece: &str) {
+    let piece = piece.split_whitespace().collect::<Vec<_>>().join(" ");
+    if piece.is_empty() {
+        return;
+    }
+    if header.is_empty() {
+        header.push_str(&piece);
+        return;
+    }
+    // A trailing comma before the closing bracket: `item: T,\n)`
+    if header.ends_with(',') && piece.starts_with([')', ']', '>']) {
+        header.pop();
+    }
+    if !header.ends_with(['(', '[', '<']) && !piece.starts_with([')', ']', '>']) {
+        header.push(' ');
+    }
+    header.push_str(&piece);
+}
+
+/// Parameter list of the function `name` declared by `signature`, whitespace collapsed:
+/// `item: T, retries: Int` for `fun <T> save(item: T, retries: Int): T`
+pub fn parameters(signature: &str, name: &str) -> Option<String> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut from = 0;
+    while let Some(pos) = signature[from..].find(name) {
+        let start = from + pos;
+        from = start + name.len();
+        if signature[..start].chars().next_back().is_some_and(is_ident) {
+            continue;
+        }
+        let rest = skip_generics(signature[from..].trim_start());
+        if let Some(inner) = rest.strip_prefix('(').and_then(balanced) {
+            return Some(inner.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+    }
+    None
+}
+
+/// Number of parameters in a parameter list, not counting receivers (`self`, `&mut self`, `cls`)
+/// or Python's `*` / `/` markers
+pub fn arity(parameters: &str) -> usize {
+    split_top_level(parameters)
+        .into_iter()
+        .filter(|p| {
+            let p = p.split(':').next().unwrap_or(p).trim();
+            !matches!(p, "self" | "&self" | "&mut self" | "mut self" | "cls" | "*" | "/" | "void")
+        })
+        .count()
+}
+
+/// Arity of a function from its parameter list, or one parameter per colon of an
+/// Objective-C selector (`configureWith:options:`)
+pub fn function_arity(name: &str, parameters: Option<&str>) -> Option<usize> {
+    parameters.map(arity).or_else(|| name.ends_with(':').then(|| name.matches(':').count()))
+}
+
+/// Stable hash of a parameter list (FNV-1a, hex), to tell overloads apart
+pub fn signature_hash(parameters: &str) -> String {
+    let hash = parameters.bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ u64::from(b)).wrapping_mul(0x100000001b3));
+    format!("{:016x}", hash)
+}
+
+/// Skip a leading generic parameter list: `<T : Entity>(item: T)` → `(item: T)`
+pub fn skip_generics(text: &str) -> &str {
+    if !text.starts_with('<') {
+        return text;
+    }
+    let mut depth = 0usize;
+    let mut prev = ' ';
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' => depth += 1,
+            // `->` in `<F: Fn() -> T>` closes nothing
+            '>' if prev != '-' => {
+                depth -= 1;
+                if depth == 0 {
+                    return text[i + 1..].trim_start();
+                }
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+    text
+}
+
+/// Text up to the `)` closing an already opened `(`, or None if it is not closed
+pub fn balanced(text: &str) -> Option<&str> {
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' => quote = Some('"'),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' if depth > 0 => depth -= 1,
+            ')' => return Some(text[..i].trim()),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Comma-separated items outside brackets, strings and generic `<...>` lists
+pub fn split_top_level(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut angles, mut start) = (0usize, 0usize, 0);
+    let mut quote: Option<char> = None;
+    let mut prev = ' ';
+    for (i, c) in text.char_indices() {
+        if let Some(q) = quote {
+            if c == q && prev != '\\' {
+                quote = None;
+            }
+            prev = if prev == '\\' { ' ' } else { c };
+            continue;
+        }
+        match c {
+            '"' => quote = Some('"'),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            // `Map<K, V>` opens a generic list, `a < b` does not
+            '<' if prev.is_alphanumeric() || prev == '_' => angles += 1,
+            '>' if angles > 0 && !matches!(prev, '-' | '=') => angles -= 1,
+            ',' if depth == 0 && angles == 0 => {
+                parts.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+    parts.push(text[start..].trim());
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(content: &str, generics: bool) -> String {
+        let lines: Vec<&str> = content.lines().collect();
+        declaration_header(&lines, 1, MAX_HEADER_LINES, Syntax { generics, lifetimes: false })
+    }
+
+    #[test]
+    fn test_single_line_generics() {
+        assert_eq!(
+            header("fun <T : Entity> save(item: T): Result<T> {\n    return Result.success(item)\n}", true),
+            "fun <T : Entity> save(item: T): Result<T>"
+        );
+        assert_eq!(
+            header("public <T extends Entity> Result<T> save(T item) { return null; }", true),
+            "public <T extends Entity> Result<T> save(T item)"
+        );
+    }
+
+    #[test]
+    fn test_multi_line_headers() {
+        assert_eq!(
+            header("pub fn save<T: Entity>(\n    item: T,\n) -> Result<T, Error> {\n    Ok(item)\n}", true),
+            "pub fn save<T: Entity>(item: T) -> Result<T, Error>"
+        );
+        assert_eq!(
+            header("class Repo<\n    K,\n    V : Entity\n> : Base<V>() {\n}", true),
+            "class Repo<K, V : Entity> : Base<V>()"
+        );
+        assert_eq!(
+            header("fun <K, V> group(\n    items: List<V>,\n    key: (V) -> K\n): Map<K, List<V>> = items.groupBy(key)", true),
+            "fun <K, V> group(items: List<V>, key: (V) -> K): Map<K, List<V>> = items.groupBy(key)"
+        );
+    }
+
+    #[test]
+    fn test_expression_body_and_comments() {
+        assert_eq!(header("fun total(): Int =\n    items.sum()", true), "fun total(): Int");
+        assert_eq!(header("void run(); // starts the loop", true), "void run()");
+        assert_eq!(header("def load(path: str = \"{x}\",\n         mode: int = 0) -> Dict[str, int]:", false),
+            "def load(path: str = \"{x}\", mode: int = 0) -> Dict[str, int]:");
+    }
+
+    #[test]
+    fn test_rust_lifetimes_and_raw_strings() {
+        let lines = ["pub fn first<'a>(items: &'a [Item<'a>]) -> Option<&'a Item<'a>> {"];
+        assert_eq!(
+            declaration_header(&lines, 1, 1, Syntax { generics: true, lifetimes: true }),
+            "pub fn first<'a>(items: &'a [Item<'a>]) -> Option<&'a Item<'a>>"
+        );
+        let lines = [r##"static RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"["'(]"#).unwrap());"##];
+        assert_eq!(
+            declaration_header(&lines, 1, 1, Syntax { generics: true, lifetimes: true }),
+            r##"static RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"["'(]"#).unwrap())"##
+        );
+    }
+
+    #[test]
+    fn test_normalize_keeps_parser_signatures() {
+        let content = "template <typename T>\nvoid push(T v) {}\n";
+        let mut symbols = vec![ParsedSymbol {
+            name: "push".to_string(),
+            kind: SymbolKind::Function,
+            line: 2,
+            signature: "template <typename T> void push(T v)".to_string(),
+            parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
+        }];
+        normalize_signatures(&mut symbols, content, "cpp");
+        assert_eq!(symbols[0].signature, "template <typename T> void push(T v)");
+    }
+
+    #[test]
+    fn test_parameters_and_arity() {
+        let params = |sig: &str, name: &str| parameters(sig, name);
+        assert_eq!(params("fun <T : Entity> save(item: T, retries: Int): T", "save").as_deref(), Some("item: T, retries: Int"));
+        assert_eq!(params("func (r *Repo) Save(ctx context.Context, u *User) error", "Save").as_deref(), Some("ctx context.Context, u *User"));
+        assert_eq!(params("pub fn map<F: Fn() -> T>(&self, f: F) -> Vec<T>", "map").as_deref(), Some("&self, f: F"));
+        assert_eq!(params("def process(self, *, key=None)", "process").as_deref(), Some("self, *, key=None"));
+        assert_eq!(params("void reprocess(int a)", "process"), None);
+
+        assert_eq!(arity("item: T, retries: Int"), 2);
+        assert_eq!(arity("&mut self, f: impl Fn(A, B) -> C, m: HashMap<K, V>"), 2);
+        assert_eq!(arity("self, *, key=None"), 1);
+        assert_eq!(arity(""), 0);
+        assert_eq!(arity("void"), 0);
+        assert_eq!(arity("callback: (Int, String) -> Unit,"), 1);
+    }
+
+    #[test]
+    fn test_signature_hash_is_stable() {
+        assert_eq!(signature_hash(""), "cbf29ce484222325");
+        assert_ne!(signature_hash("a: Int"), signature_hash("a: Long"));
+    }
+}
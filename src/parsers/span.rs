@@ -0,0 +1,379 @@
+//! Declaration spans
+//!
+//! Finds where each declaration ends so the whole definition (header and body) can be
+//! extracted later: brace matching for C-like languages, indentation for Python,
+//! `end` keywords for Ruby and `@end` for Objective-C containers. The byte range runs
+//! from the first non-blank character of the declaration line to the end of its last line.
+
+use crate::db::SymbolKind;
+use super::{FileType, ParsedSymbol};
+
+/// How far to look for the `{` of a declaration whose header spans several lines
+pub(super) const MAX_HEADER_LINES: usize = 30;
+
+/// Set `end_line` and `byte_range` on every symbol the parser did not already measure
+pub fn assign_spans(symbols: &mut [ParsedSymbol], content: &str, ext: &str) {
+    let file_type = FileType::from_extension(ext);
+    let lines: Vec<&str> = content.lines().collect();
+    let mut line_starts = Vec::with_capacity(lines.len());
+    let mut offset = 0;
+    for raw in content.split_inclusive('\n') {
+        line_starts.push(offset);
+        offset += raw.len();
+    }
+
+    for symbol in symbols.iter_mut() {
+        if symbol.end_line.is_some() {
+            continue;
+        }
+        let Some(first) = symbol.line.checked_sub(1).and_then(|i| lines.get(i)) else { continue };
+        let end_line = if matches!(symbol.kind, SymbolKind::Import | SymbolKind::Package) {
+            symbol.line
+        } else {
+            end_line(&lines, symbol.line, file_type)
+        };
+        let last = lines[end_line - 1];
+        let start = line_starts[symbol.line - 1] + (first.len() - first.trim_start().len());
+        let end = line_starts[end_line - 1] + last.trim_end().len();
+        symbol.end_line = Some(end_line);
+        symbol.byte_range = Some((start, end.max(start)));
+    }
+}
+
+/// Last line (1-based) of the declaration starting at `line`
+pub fn end_line(lines: &[&str], line: usize, file_type: Option<FileType>) -> usize {
+    let Some(first) = line.checked_sub(1).and_then(|i| lines.get(i)) else { return line };
+    match file_type {
+        Some(FileType::Python) => python_end(lines, line),
+        Some(FileType::Ruby) => ruby_end(lines, line),
+        Some(FileType::Wsdl) => line,
+        Some(FileType::ObjC) if is_objc_container(first) => objc_end(lines, line),
+        _ => brace_end(lines, line),
+    }
+}
+
+/// Code part of a line for brace counting: strings, char literals and comments blanked out.
+/// `in_comment` carries an open `/* ... */` across lines.
+pub(super) fn code_chars(text: &str, in_comment: &mut bool) -> Vec<char> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut code = Vec::with_capacity(chars.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if *in_comment {
+            if c == '*' && next == Some('/') {
+                *in_comment = false;
+                i += 1;
+            }
+        } else if c == '/' && next == Some('/') {
+            break;
+        } else if c == '/' && next == Some('*') {
+            *in_comment = true;
+            i += 1;
+        } else if c == '"' || c == '`' {
+            // Keep an empty literal so `val s = "{"` does not look like `val s =`
+            code.push(c);
+            code.push(c);
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                if chars[i] == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+        } else if c == '\'' && (chars.get(i + 2) == Some(&'\'') || (next == Some('\\') && chars.get(i + 3) == Some(&'\''))) {
+            // Char literal; a lone quote is a Rust lifetime or label and is kept
+            i += if next == Some('\\') { 3 } else { 2 };
+        } else {
+            code.push(c);
+        }
+        i += 1;
+    }
+    code
+}
+
+/// Code of each line for keyword scans: strings and comments blanked out, `#` comments cut for
+/// Python, Ruby and Perl, and lines starting with `#` (preprocessor, Rust attributes) emptied
+/// for other languages
+pub(super) fn code_lines(content: &str, ext: &str) -> Vec<String> {
+    let hash_comments = matches!(
+        FileType::from_extension(ext),
+        Some(FileType::Python | FileType::Ruby | FileType::Perl)
+    );
+    let mut in_comment = false;
+    content
+        .lines()
+        .map(|text| {
+            let mut code: String = code_chars(text, &mut in_comment).into_iter().collect();
+            if hash_comments {
+                if let Some(start) = code.find('#') {
+                    code.truncate(start);
+                }
+            } else if code.trim_start().starts_with('#') {
+                code.clear();
+            }
+            code
+        })
+        .collect()
+}
+
+/// Functions with a measured span as (index in symbols, first line, last line); generated
+/// symbols have no code of their own
+pub(super) fn function_spans(symbols: &[ParsedSymbol]) -> Vec<(usize, usize, usize)> {
+    symbols
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.kind == SymbolKind::Function && s.generated_by.is_none())
+        .filter_map(|(i, s)| s.end_line.map(|end| (i, s.line, end)))
+        .collect()
+}
+
+/// Innermost of `functions` around a 1-based line: the latest to start, then the shortest
+pub(super) fn innermost_function(functions: &[(usize, usize, usize)], line: usize) -> Option<usize> {
+    functions
+        .iter()
+        .filter(|(_, start, end)| *start <= line && line <= *end)
+        .max_by_key(|(_, start, end)| (*start, usize::MAX - end))
+        .map(|(i, _, _)| *i)
+}
+
+/// Does the header continue on the next line (`fun f(\n`, `class A :\n  B()`, `fn f()\nwhere`)?
+pub(super) fn header_continues(code: &str, next: Option<&str>) -> bool {
+    let code = code.trim_end();
+    const TRAILING: &[&str] = &[",", "=", "(", "[", "<", ":", "->", "=>", "&&", "||", "+", "."];
+    if TRAILING.iter().any(|t| code.ends_with(t)) || code == "where" || code.ends_with(" where") {
+        return true;
+    }
+    let Some(next) = next.map(str::trim_start) else { return false };
+    const LEADING: &[&str] = &["{", ":", ".", "?.", ")", "->", "=", "where", "extends", "implements", "throws", "with", "returns"];
+    LEADING.iter().any(|l| {
+        next.strip_prefix(l).is_some_and(|rest| {
+            // Keywords must be whole words
+            !l.chars().all(char::is_alphabetic) || !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        })
+    })
+}
+
+/// Matching `}` of the declaration body, or the end of a body-less declaration
+fn brace_end(lines: &[&str], line: usize) -> usize {
+    let mut in_comment = false;
+    let mut braces = 0i32;
+    let mut parens = 0i32;
+    let mut seen_brace = false;
+
+    for idx in line - 1..lines.len() {
+        let code: String = code_chars(lines[idx], &mut in_comment).into_iter().collect();
+        for c in code.chars() {
+            match c {
+                '{' => {
+                    braces += 1;
+                    seen_brace = true;
+                }
+                '}' => braces -= 1,
+                '(' | '[' => parens += 1,
+                ')' | ']' => parens -= 1,
+                ';' if !seen_brace && braces <= 0 && parens <= 0 => return idx + 1,
+                _ => {}
+            }
+        }
+        if seen_brace {
+            // `const X: &[T] = &[ T { .. }, .. ];` ends with the brackets, not the first `}`
+            if braces <= 0 && parens <= 0 {
+                return idx + 1;
+            }
+            continue;
+        }
+        if parens <= 0 && !in_comment && !header_continues(&code, lines.get(idx + 1).copied()) {
+            return idx + 1;
+        }
+        if idx + 1 - line >= MAX_HEADER_LINES {
+            break;
+        }
+    }
+    if seen_brace { lines.len() } else { line }
+}
+
+fn indent_of(text: &str) -> usize {
+    text.len() - text.trim_start().len()
+}
+
+/// End of a `def`/`class` block (by indentation) or of a multi-line assignment
+fn python_end(lines: &[&str], line: usize) -> usize {
+    let decl_indent = indent_of(lines[line - 1]);
+    // Header: until brackets close (multi-line signatures)
+    let mut depth = 0i32;
+    let mut header_end = line;
+    for (idx, text) in lines.iter().enumerate().skip(line - 1) {
+        let code = text.split('#').next().unwrap_or(text);
+        for c in code.chars() {
+            match c {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        header_end = idx + 1;
+        if depth <= 0 || idx + 1 - line >= MAX_HEADER_LINES {
+            break;
+        }
+    }
+    let header = lines[header_end - 1].split('#').next().unwrap_or("").trim_end();
+    if !header.ends_with(':') {
+        return header_end;
+    }
+
+    let mut end = header_end;
+    for (idx, text) in lines.iter().enumerate().skip(header_end) {
+        if text.trim().is_empty() {
+            continue;
+        }
+        if indent_of(text) <= decl_indent {
+            break;
+        }
+        end = idx + 1;
+    }
+    end
+}
+
+/// Matching `end` of a `def`/`class`/`module` at the same indentation
+fn ruby_end(lines: &[&str], line: usize) -> usize {
+    let first = lines[line - 1].trim();
+    let opens_block = ["def ", "class ", "module "].iter().any(|k| first.starts_with(k));
+    let one_liner = first.ends_with(" end") || first.ends_with(";end") || is_endless_def(first);
+    if !opens_block || one_liner {
+        return line;
+    }
+    let decl_indent = indent_of(lines[line - 1]);
+    for (idx, text) in lines.iter().enumerate().skip(line) {
+        let t = text.trim();
+        if indent_of(text) == decl_indent && (t == "end" || t.starts_with("end ") || t.starts_with("end.")) {
+            return idx + 1;
+        }
+        if !t.is_empty() && indent_of(text) < decl_indent {
+            break;
+        }
+    }
+    line
+}
+
+/// Ruby 3 endless method: `def admin? = false`, `def full(a, b = 1) = a + b`
+fn is_endless_def(text: &str) -> bool {
+    let Some(rest) = text.strip_prefix("def ") else { return false };
+    let name_end = rest.find(|c: char| c.is_whitespace() || c == '(').unwrap_or(rest.len());
+    let mut rest = &rest[name_end..];
+    if rest.starts_with('(') {
+        let mut depth = 0;
+        let Some(close) = rest.char_indices().find_map(|(i, c)| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            (depth == 0).then_some(i)
+        }) else { return false };
+        rest = &rest[close + 1..];
+    }
+    let rest = rest.trim_start();
+    rest.starts_with('=') && !rest.starts_with("==")
+}
+
+fn is_objc_container(text: &str) -> bool {
+    let t = text.trim_start();
+    t.starts_with("@interface") || t.starts_with("@implementation") || t.starts_with("@protocol")
+}
+
+/// `@end` closing an `@interface`/`@implementation`/`@protocol`
+fn objc_end(lines: &[&str], line: usize) -> usize {
+    let first = lines[line - 1].trim_start();
+    // Forward declaration: `@protocol Foo;`
+    if first.trim_end().ends_with(';') {
+        return line;
+    }
+    lines
+        .iter()
+        .enumerate()
+        .skip(line)
+        .find(|(_, text)| text.trim() == "@end")
+        .map_or(line, |(idx, _)| idx + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ends(content: &str, ext: &str, decl_lines: &[usize]) -> Vec<usize> {
+        let lines: Vec<&str> = content.lines().collect();
+        let file_type = FileType::from_extension(ext);
+        decl_lines.iter().map(|&l| end_line(&lines, l, file_type)).collect()
+    }
+
+    #[test]
+    fn test_brace_languages() {
+        let kt = r#"class Repo(
+    private val api: Api,
+) : Base() {
+    val label = "}{"
+    fun load(): List<Item> {
+        // }
+        return api.items()
+    }
+    fun size() =
+        api.count()
+}
+class Empty(val id: Int)
+class Next {
+}
+"#;
+        assert_eq!(ends(kt, "kt", &[1, 4, 5, 9, 12, 13]), vec![11, 4, 8, 10, 12, 14]);
+
+        let rs = "pub struct A;\nimpl A\nwhere\n    A: Sized,\n{\n    fn f<'a>(x: &'a str) -> char {\n        '}'\n    }\n}\n";
+        assert_eq!(ends(rs, "rs", &[1, 2, 6]), vec![1, 9, 8]);
+
+        let rs_const = "const KINDS: &[Kind] = &[\n    Kind { a: 1 },\n    Kind { a: 2 },\n];\nfn next() {}\n";
+        assert_eq!(ends(rs_const, "rs", &[1]), vec![4]);
+
+        let java = "interface Api {\n    void call();\n    /* { */\n    int size();\n}\n";
+        assert_eq!(ends(java, "java", &[1, 2, 4]), vec![5, 2, 4]);
+    }
+
+    #[test]
+    fn test_python_indentation() {
+        let py = "class A:\n    def f(self,\n          x):\n        return x\n\n    def g(self): pass\n\nVALUE = [\n    1,\n]\n";
+        assert_eq!(ends(py, "py", &[1, 2, 6, 8]), vec![6, 4, 6, 10]);
+    }
+
+    #[test]
+    fn test_ruby_and_objc() {
+        let rb = "class User\n  def name(fallback = nil)\n    @name\n  end\n\n  def admin? = false\nend\n";
+        assert_eq!(ends(rb, "rb", &[1, 2, 6]), vec![7, 4, 6]);
+
+        let m = "@interface Foo : NSObject\n- (void)bar;\n@end\n@implementation Foo\n- (void)bar {\n}\n@end\n";
+        assert_eq!(ends(m, "m", &[1, 2, 4, 5]), vec![3, 2, 7, 6]);
+    }
+
+    #[test]
+    fn test_byte_range() {
+        let content = "package a\n\nfunc Run() {\n\treturn\n}\n";
+        let mut symbols = vec![ParsedSymbol {
+            name: "Run".to_string(),
+            kind: SymbolKind::Function,
+            line: 3,
+            signature: String::new(),
+            parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
+        }];
+        assign_spans(&mut symbols, content, "go");
+        let (start, end) = symbols[0].byte_range.unwrap();
+        assert_eq!(symbols[0].end_line, Some(5));
+        assert_eq!(&content[start..end], "func Run() {\n\treturn\n}");
+    }
+}
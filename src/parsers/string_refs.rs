@@ -0,0 +1,111 @@
+//! References by name inside string literals
+//!
+//! Reflection and dependency injection name classes in strings: `Class.forName("com.x.Foo")`,
+//! `"Admin::User".constantize`, `@Qualifier("userService")`. A literal that is nothing but a
+//! (qualified) identifier is recorded as a `string` reference to its last segment; the indexer
+//! later drops those that name no indexed symbol (`db::prune_string_refs`).
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Whole-literal identifiers: `Foo`, `com.x.Foo`, `Admin::User`, `Outer$Inner`
+static QUALIFIED_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[\p{L}_][\w$]*(?:(?:\.|::|\$)[\p{L}_]\w*)*$").unwrap());
+
+/// A name referenced by a string literal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringRef {
+    pub name: String,
+    /// Byte range of the literal's content in the line
+    pub start: usize,
+    pub end: usize,
+}
+
+impl StringRef {
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+}
+
+/// Names referenced by the string literals of one line
+pub fn scan(line: &str) -> Vec<StringRef> {
+    literals(line)
+        .into_iter()
+        .filter_map(|(start, end)| {
+            let name = referenced_name(&line[start..end])?;
+            Some(StringRef { name, start, end })
+        })
+        .collect()
+}
+
+/// Type name a literal refers to: the last segment of a qualified name if it is CamelCase,
+/// or the class behind a camelCase bean name (`userService` → `UserService`)
+fn referenced_name(text: &str) -> Option<String> {
+    if !QUALIFIED_RE.is_match(text) {
+        return None;
+    }
+    let last = text.rsplit(['.', ':', '$']).next().unwrap_or(text);
+    let mut chars = last.chars();
+    let first = chars.next()?;
+    let name = if first.is_uppercase() {
+        last.to_string()
+    } else if last == text && first.is_lowercase() && last.chars().any(char::is_uppercase) {
+        first.to_uppercase().collect::<String>() + chars.as_str()
+    } else {
+        return None;
+    };
+    // All-caps literals are constants or codes ("GET", "UTF_8"), not type names
+    (name.chars().count() > 2 && name.chars().any(char::is_lowercase)).then_some(name)
+}
+
+/// Byte ranges of the contents of `"..."` and `'...'` literals closed on this line
+fn literals(line: &str) -> Vec<(usize, usize)> {
+    let bytes = line.as_bytes();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let quote = bytes[i];
+        if quote != b'"' && quote != b'\'' {
+            i += 1;
+            continue;
+        }
+        let start = i + 1;
+        let mut j = start;
+        while j < bytes.len() && bytes[j] != quote {
+            j += if bytes[j] == b'\\' { 2 } else { 1 };
+        }
+        if j >= bytes.len() {
+            break;
+        }
+        ranges.push((start, j));
+        i = j + 1;
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(line: &str) -> Vec<String> {
+        scan(line).into_iter().map(|r| r.name).collect()
+    }
+
+    #[test]
+    fn test_reflection_and_di_names() {
+        assert_eq!(names(r#"Class.forName("com.example.UserRepository")"#), vec!["UserRepository"]);
+        assert_eq!(names(r#"klass = "Admin::User".constantize"#), vec!["User"]);
+        assert_eq!(names(r#"@Qualifier("userService") UserService service"#), vec!["UserService"]);
+        assert_eq!(names(r#"single(named("PaymentApi")) { create() }"#), vec!["PaymentApi"]);
+        assert_eq!(names("has_many :posts, class_name: 'Article'"), vec!["Article"]);
+        assert_eq!(names(r#"Новый("Справочники.Контрагенты")"#), vec!["Контрагенты"]);
+    }
+
+    #[test]
+    fn test_ignores_other_strings() {
+        assert!(names(r#"log("User not found: $id")"#).is_empty());
+        assert!(names(r#"get("name"); method("GET"); path("src/main")"#).is_empty());
+        assert!(names(r#"val s = "say \"Hi\"""#).is_empty());
+    }
+}
@@ -3,7 +3,7 @@
 //! Parses C and C++ source files to extract:
 //! - Classes and structs (including template classes)
 //! - Functions (including template functions and JNI exports)
-//! - Method definitions (ClassName::MethodName)
+//! - Method definitions (ClassName::MethodName, Class<T>::MethodName)
 //! - Namespaces (including nested C++17 syntax)
 //! - Enums (including enum class)
 //! - Type aliases (typedef and using)
@@ -16,7 +16,7 @@ use std::sync::LazyLock;
 
 use crate::db::SymbolKind;
 use crate::parsers::ParsedSymbol;
-use super::{LanguageParser, parse_tree, node_text, node_line, line_text};
+use super::{LanguageParser, assign_tree_containers, parse_tree, node_text, node_line, node_extent, line_text};
 
 static CPP_LANGUAGE: LazyLock<Language> = LazyLock::new(|| tree_sitter_cpp::LANGUAGE.into());
 
@@ -25,6 +25,14 @@ static CPP_QUERY: LazyLock<Query> = LazyLock::new(|| {
         .expect("Failed to compile C++ tree-sitter query")
 });
 
+const SCOPE_KINDS: &[&str] = &[
+    "class_specifier",
+    "struct_specifier",
+    "union_specifier",
+    "enum_specifier",
+    "function_definition",
+];
+
 pub static CPP_PARSER: CppParser = CppParser;
 
 pub struct CppParser;
@@ -76,7 +84,8 @@ impl LanguageParser for CppParser {
         while let Some(m) = matches.next() {
             // --- Class with body (not forward declaration) ---
             if let Some(name_cap) = find_capture(m, idx_class_name) {
-                if find_capture(m, idx_class_node).is_some() {
+                // Templated declarations are handled by the template pattern below
+                if find_capture(m, idx_class_node).is_some() && enclosing_template(name_cap.node).is_none() {
                     let name = node_text(content, &name_cap.node);
                     let line = node_line(&name_cap.node);
                     let parents = extract_base_classes(content, &name_cap.node);
@@ -86,6 +95,15 @@ impl LanguageParser for CppParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents,
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -93,7 +111,8 @@ impl LanguageParser for CppParser {
 
             // --- Struct with body ---
             if let Some(name_cap) = find_capture(m, idx_struct_name) {
-                if find_capture(m, idx_struct_node).is_some() {
+                // Templated declarations are handled by the template pattern below
+                if find_capture(m, idx_struct_node).is_some() && enclosing_template(name_cap.node).is_none() {
                     let name = node_text(content, &name_cap.node);
                     let line = node_line(&name_cap.node);
                     let parents = extract_base_classes(content, &name_cap.node);
@@ -103,6 +122,15 @@ impl LanguageParser for CppParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents,
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -110,16 +138,28 @@ impl LanguageParser for CppParser {
 
             // --- Template class with body ---
             if let Some(name_cap) = find_capture(m, idx_template_class_name) {
-                if find_capture(m, idx_template_class_node).is_some() {
+                if let (Some(_), Some(template)) =
+                    (find_capture(m, idx_template_class_node), enclosing_template(name_cap.node))
+                {
                     let name = node_text(content, &name_cap.node);
                     let line = node_line(&name_cap.node);
                     let parents = extract_base_classes(content, &name_cap.node);
+                    let (end_line, byte_range) = node_extent(&template);
                     symbols.push(ParsedSymbol {
                         name: name.to_string(),
                         kind: SymbolKind::Class,
                         line,
-                        signature: line_text(content, line).trim().to_string(),
+                        signature: template_signature(content, &template),
                         parents,
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line,
+                        byte_range,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -127,16 +167,28 @@ impl LanguageParser for CppParser {
 
             // --- Template struct with body ---
             if let Some(name_cap) = find_capture(m, idx_template_struct_name) {
-                if find_capture(m, idx_template_struct_node).is_some() {
+                if let (Some(_), Some(template)) =
+                    (find_capture(m, idx_template_struct_node), enclosing_template(name_cap.node))
+                {
                     let name = node_text(content, &name_cap.node);
                     let line = node_line(&name_cap.node);
                     let parents = extract_base_classes(content, &name_cap.node);
+                    let (end_line, byte_range) = node_extent(&template);
                     symbols.push(ParsedSymbol {
                         name: name.to_string(),
                         kind: SymbolKind::Class,
                         line,
-                        signature: line_text(content, line).trim().to_string(),
+                        signature: template_signature(content, &template),
                         parents,
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line,
+                        byte_range,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -145,6 +197,9 @@ impl LanguageParser for CppParser {
             // --- Method definition: ClassName::MethodName ---
             if let Some(class_cap) = find_capture(m, idx_method_class) {
                 if let Some(name_cap) = find_capture(m, idx_method_name) {
+                    if enclosing_template(name_cap.node).is_some() {
+                        continue;
+                    }
                     let class_name = node_text(content, &class_cap.node);
                     let method_name = node_text(content, &name_cap.node);
                     let line = node_line(&name_cap.node);
@@ -160,6 +215,15 @@ impl LanguageParser for CppParser {
                                 line,
                                 signature: sig_line,
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: None,
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                             continue;
                         }
@@ -172,6 +236,15 @@ impl LanguageParser for CppParser {
                             line,
                             signature: sig_line,
                             parents: vec![(class_name.to_string(), "member".to_string())],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -184,13 +257,23 @@ impl LanguageParser for CppParser {
                     let class_name = node_text(content, &class_cap.node);
                     let method_name = node_text(content, &name_cap.node);
                     let line = node_line(&name_cap.node);
-                    if !is_reserved_word(method_name) {
+                    if let (false, Some(template)) = (is_reserved_word(method_name), enclosing_template(name_cap.node)) {
+                        let (end_line, byte_range) = node_extent(&template);
                         symbols.push(ParsedSymbol {
                             name: method_name.to_string(),
                             kind: SymbolKind::Function,
                             line,
-                            signature: line_text(content, line).trim().to_string(),
+                            signature: template_signature(content, &template),
                             parents: vec![(class_name.to_string(), "member".to_string())],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line,
+                            byte_range,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -209,6 +292,15 @@ impl LanguageParser for CppParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![(class_name.to_string(), "member".to_string())],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -218,13 +310,23 @@ impl LanguageParser for CppParser {
             if let Some(cap) = find_capture(m, idx_template_func_name) {
                 let name = node_text(content, &cap.node);
                 let line = node_line(&cap.node);
-                if !is_reserved_word(name) {
+                if let (false, Some(template)) = (is_reserved_word(name), enclosing_template(cap.node)) {
+                    let (end_line, byte_range) = node_extent(&template);
                     symbols.push(ParsedSymbol {
                         name: name.to_string(),
                         kind: SymbolKind::Function,
                         line,
-                        signature: line_text(content, line).trim().to_string(),
+                        signature: template_signature(content, &template),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line,
+                        byte_range,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -232,6 +334,9 @@ impl LanguageParser for CppParser {
 
             // --- Regular function ---
             if let Some(cap) = find_capture(m, idx_func_name) {
+                if enclosing_template(cap.node).is_some() {
+                    continue;
+                }
                 let name = node_text(content, &cap.node);
                 let line = node_line(&cap.node);
 
@@ -245,6 +350,15 @@ impl LanguageParser for CppParser {
                             line,
                             signature: sig_line,
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                         continue;
                     }
@@ -257,6 +371,15 @@ impl LanguageParser for CppParser {
                         line,
                         signature: sig_line,
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -279,6 +402,15 @@ impl LanguageParser for CppParser {
                                     line,
                                     signature: sig.clone(),
                                     parents: vec![],
+                                    container: None,
+                                    qualified_name: None,
+                                    doc: None,
+                                    annotations: vec![],
+                                    visibility: None,
+                                    end_line: None,
+                                    byte_range: None,
+                                    condition: None,
+                                    generated_by: None,
                                 });
                             }
                         }
@@ -288,6 +420,15 @@ impl LanguageParser for CppParser {
                             line,
                             signature: sig,
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     } else {
                         symbols.push(ParsedSymbol {
@@ -296,6 +437,15 @@ impl LanguageParser for CppParser {
                             line,
                             signature: sig,
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -312,6 +462,15 @@ impl LanguageParser for CppParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -326,6 +485,15 @@ impl LanguageParser for CppParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -343,6 +511,15 @@ impl LanguageParser for CppParser {
                             line,
                             signature: line_text(content, line).trim().to_string(),
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -359,6 +536,15 @@ impl LanguageParser for CppParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -373,6 +559,15 @@ impl LanguageParser for CppParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -394,13 +589,49 @@ impl LanguageParser for CppParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![(path.to_string(), "from".to_string())],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
         }
 
+        assign_tree_containers(&tree, content, &mut symbols, SCOPE_KINDS);
         Ok(symbols)
     }
+
+    fn sets_containers(&self) -> bool {
+        true
+    }
+}
+
+/// The `template<...>` declaration wrapping the class or function that owns `name_node`, if any
+fn enclosing_template(name_node: tree_sitter::Node) -> Option<tree_sitter::Node> {
+    let mut node = name_node.parent()?;
+    while !matches!(node.kind(), "function_definition" | "class_specifier" | "struct_specifier") {
+        node = node.parent()?;
+    }
+    node.parent().filter(|p| p.kind() == "template_declaration")
+}
+
+/// Header of a template declaration up to its body, joined onto one line
+/// so that parameter lists and return types spanning several lines stay in the signature
+fn template_signature(content: &str, template: &tree_sitter::Node) -> String {
+    let body_start = template
+        .named_child((template.named_child_count() as u32).saturating_sub(1))
+        .and_then(|decl| decl.child_by_field_name("body"))
+        .map_or(template.end_byte(), |body| body.start_byte());
+    content[template.start_byte()..body_start]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 /// Extract the name from a complex typedef declaration.
@@ -972,6 +1203,76 @@ struct Optional {
         );
     }
 
+    #[test]
+    fn test_multiline_template_declarations() {
+        let content = r#"
+template <typename T,
+          typename Alloc = std::allocator<T>>
+class Buffer : public Base<T> {
+    T* data_;
+};
+
+template <typename T>
+std::vector<T>
+make_list(int n,
+          const T& fill) {
+    return {};
+}
+
+template <typename T, typename A>
+void Buffer<T, A>::push(const T& value) {}
+"#;
+        let symbols = CPP_PARSER.parse_symbols(content).unwrap();
+        let buffers: Vec<_> = symbols.iter().filter(|s| s.name == "Buffer").collect();
+        assert_eq!(buffers.len(), 1, "template class must be emitted once, got: {:?}", symbols);
+        assert_eq!(buffers[0].line, 4);
+        assert_eq!(buffers[0].end_line, Some(6));
+        assert_eq!(
+            buffers[0].signature,
+            "template <typename T, typename Alloc = std::allocator<T>> class Buffer : public Base<T>"
+        );
+        assert!(buffers[0].parents.iter().any(|(p, _)| p == "Base"));
+
+        let lists: Vec<_> = symbols.iter().filter(|s| s.name == "make_list").collect();
+        assert_eq!(lists.len(), 1, "template function must be emitted once, got: {:?}", symbols);
+        assert_eq!(lists[0].line, 10);
+        assert_eq!(lists[0].end_line, Some(13));
+        assert_eq!(lists[0].signature, "template <typename T> std::vector<T> make_list(int n, const T& fill)");
+
+        let push = symbols.iter().find(|s| s.name == "push").expect("push not found");
+        assert_eq!(push.parents, vec![("Buffer".to_string(), "member".to_string())]);
+        assert_eq!(push.signature, "template <typename T, typename A> void Buffer<T, A>::push(const T& value)");
+    }
+
+    #[test]
+    fn test_namespace_qualified_names() {
+        let content = r#"
+namespace net {
+namespace http {
+class Client {
+    void send();
+};
+}
+
+namespace detail::impl {
+struct Node {};
+}
+
+void Server::start() {}
+}
+"#;
+        let (mut symbols, _) = crate::parsers::parse_file_symbols(content, crate::parsers::FileType::Cpp).unwrap();
+        crate::parsers::qualify::assign_qualified_names(&mut symbols, content, "cpp", "src/net.cpp");
+        let qualified = |name: &str| {
+            symbols.iter().find(|s| s.name == name).and_then(|s| s.qualified_name.clone())
+        };
+        assert_eq!(qualified("Client").as_deref(), Some("net::http::Client"));
+        assert_eq!(qualified("Node").as_deref(), Some("net::detail::impl::Node"));
+        let start = symbols.iter().find(|s| s.name == "start").expect("start not found");
+        assert_eq!(start.container.as_deref(), Some("Server"));
+        assert_eq!(start.qualified_name.as_deref(), Some("net::Server::start"));
+    }
+
     #[test]
     fn test_parse_enum_with_type() {
         let content = r#"
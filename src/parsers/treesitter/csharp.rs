@@ -6,7 +6,7 @@ use std::sync::LazyLock;
 
 use crate::db::SymbolKind;
 use crate::parsers::ParsedSymbol;
-use super::{LanguageParser, parse_tree, node_text, node_line, line_text};
+use super::{LanguageParser, assign_tree_containers, parse_tree, node_text, node_line, line_text};
 
 static CSHARP_LANGUAGE: LazyLock<Language> = LazyLock::new(|| tree_sitter_c_sharp::LANGUAGE.into());
 
@@ -15,6 +15,17 @@ static CSHARP_QUERY: LazyLock<Query> = LazyLock::new(|| {
         .expect("Failed to compile C# tree-sitter query")
 });
 
+const SCOPE_KINDS: &[&str] = &[
+    "class_declaration",
+    "struct_declaration",
+    "interface_declaration",
+    "record_declaration",
+    "enum_declaration",
+    "method_declaration",
+    "constructor_declaration",
+    "local_function_statement",
+];
+
 pub static CSHARP_PARSER: CSharpParser = CSharpParser;
 
 pub struct CSharpParser;
@@ -239,6 +250,15 @@ impl LanguageParser for CSharpParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -252,6 +272,15 @@ impl LanguageParser for CSharpParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -271,6 +300,15 @@ impl LanguageParser for CSharpParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents,
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -289,6 +327,15 @@ impl LanguageParser for CSharpParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents,
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -303,6 +350,15 @@ impl LanguageParser for CSharpParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -321,6 +377,15 @@ impl LanguageParser for CSharpParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents,
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -335,6 +400,15 @@ impl LanguageParser for CSharpParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -349,6 +423,15 @@ impl LanguageParser for CSharpParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -363,6 +446,15 @@ impl LanguageParser for CSharpParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -377,6 +469,15 @@ impl LanguageParser for CSharpParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -396,6 +497,15 @@ impl LanguageParser for CSharpParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -411,6 +521,15 @@ impl LanguageParser for CSharpParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -426,6 +545,15 @@ impl LanguageParser for CSharpParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -440,6 +568,15 @@ impl LanguageParser for CSharpParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -449,6 +586,7 @@ impl LanguageParser for CSharpParser {
                 let attr_name = node_text(content, &cap.node);
                 // Extract just the simple name (last component of qualified name)
                 let simple_name = attr_name.rsplit('.').next().unwrap_or(attr_name);
+                let simple_name = simple_name.strip_suffix("Attribute").filter(|n| !n.is_empty()).unwrap_or(simple_name);
                 let line = node_line(&cap.node);
                 if is_significant_attr(simple_name) {
                     symbols.push(ParsedSymbol {
@@ -457,14 +595,28 @@ impl LanguageParser for CSharpParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
             }
         }
 
+        assign_tree_containers(&tree, content, &mut symbols, SCOPE_KINDS);
         Ok(symbols)
     }
+
+    fn sets_containers(&self) -> bool {
+        true
+    }
 }
 
 /// Find a base_list child node within a declaration node
@@ -62,6 +62,11 @@ fn walk_node(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>) {
             extract_typedef(node, content, symbols);
             return;
         }
+        // class C = Base with M1, M2;
+        "mixin_application_class" => {
+            extract_mixin_application(node, content, symbols);
+            return;
+        }
         // Top-level functions: tree-sitter-dart 0.0.4 wraps them in lambda_expression
         "lambda_expression" => {
             if is_top_level(node) {
@@ -173,6 +178,15 @@ fn extract_import(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>) {
             line,
             signature: sig,
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 }
@@ -216,6 +230,15 @@ fn extract_class(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>) {
         line,
         signature: sig,
         parents,
+        container: None,
+        qualified_name: None,
+        doc: None,
+        annotations: vec![],
+        visibility: None,
+        end_line: None,
+        byte_range: None,
+        condition: None,
+        generated_by: None,
     });
 }
 
@@ -286,12 +309,25 @@ fn extract_mixin(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>) {
         let name = find_mixin_name(node, content);
         if name.is_empty() { return; }
 
+        let text = node_text(content, node);
+        let mut parents = Vec::new();
+        parse_parents_from_class_text(text.split('{').next().unwrap_or(text), &mut parents);
+
         symbols.push(ParsedSymbol {
             name,
             kind: SymbolKind::Class,
             line,
             signature: sig,
-            parents: vec![],
+            parents,
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
         return;
     }
@@ -348,6 +384,15 @@ fn extract_mixin(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>) {
         line,
         signature: sig,
         parents,
+        container: None,
+        qualified_name: None,
+        doc: None,
+        annotations: vec![],
+        visibility: None,
+        end_line: None,
+        byte_range: None,
+        condition: None,
+        generated_by: None,
     });
 }
 
@@ -382,7 +427,7 @@ fn extract_extension(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>
             find_first_type_identifier(&class_node, content)
                 .unwrap_or_default()
         };
-        let base = on_type.split('<').next().unwrap_or(&on_type).trim().to_string();
+        let base = type_base_name(&on_type);
         if !base.is_empty() {
             parents.push((base, "extends".to_string()));
         }
@@ -394,6 +439,15 @@ fn extract_extension(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>
         line,
         signature: sig,
         parents,
+        container: None,
+        qualified_name: None,
+        doc: None,
+        annotations: vec![],
+        visibility: None,
+        end_line: None,
+        byte_range: None,
+        condition: None,
+        generated_by: None,
     });
 }
 
@@ -420,6 +474,15 @@ fn extract_extension_type(node: &Node, content: &str, symbols: &mut Vec<ParsedSy
         line,
         signature: sig,
         parents,
+        container: None,
+        qualified_name: None,
+        doc: None,
+        annotations: vec![],
+        visibility: None,
+        end_line: None,
+        byte_range: None,
+        condition: None,
+        generated_by: None,
     });
 }
 
@@ -455,6 +518,15 @@ fn extract_enum(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>) {
         line,
         signature: sig,
         parents,
+        container: None,
+        qualified_name: None,
+        doc: None,
+        annotations: vec![],
+        visibility: None,
+        end_line: None,
+        byte_range: None,
+        condition: None,
+        generated_by: None,
     });
 }
 
@@ -486,11 +558,97 @@ fn extract_typedef(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>)
                 line,
                 signature: sig,
                 parents: vec![],
+                container: None,
+                qualified_name: None,
+                doc: None,
+                annotations: vec![],
+                visibility: None,
+                end_line: None,
+                byte_range: None,
+                condition: None,
+                generated_by: None,
             });
         }
     }
 }
 
+/// Extract a mixin application class: `class C = Base with M1, M2 implements I;`
+fn extract_mixin_application(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>) {
+    let Some((name, parents)) = parse_mixin_application(node_text(content, node)) else { return };
+    let line = node_line(node);
+
+    symbols.push(ParsedSymbol {
+        name,
+        kind: SymbolKind::Class,
+        line,
+        signature: line_text(content, line).trim().to_string(),
+        parents,
+        container: None,
+        qualified_name: None,
+        doc: None,
+        annotations: vec![],
+        visibility: None,
+        end_line: None,
+        byte_range: None,
+        condition: None,
+        generated_by: None,
+    });
+}
+
+/// Parse name and parents from mixin application text.
+/// The superclass is an `extends` parent, mixins are `with` parents.
+fn parse_mixin_application(text: &str) -> Option<(String, Vec<(String, String)>)> {
+    let (head, application) = text.split_once('=')?;
+    let words: Vec<&str> = head.split_whitespace().collect();
+    let class_idx = words.iter().position(|w| *w == "class")?;
+    let name = words.get(class_idx + 1)?.split('<').next()?.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let application = application.trim().trim_end_matches(';');
+    let (superclass, rest) = application.split_once(" with ")?;
+    let mut parents = vec![(type_base_name(superclass), "extends".to_string())];
+    let (mixins, interfaces) = match rest.split_once(" implements ") {
+        Some((mixins, interfaces)) => (mixins, Some(interfaces)),
+        None => (rest, None),
+    };
+    for (list, kind) in [(Some(mixins), "with"), (interfaces, "implements")] {
+        for t in list.map(split_type_list).unwrap_or_default() {
+            parents.push((type_base_name(t), kind.to_string()));
+        }
+    }
+    parents.retain(|(p, _)| !p.is_empty());
+
+    Some((name.to_string(), parents))
+}
+
+/// Split a comma-separated type list, ignoring commas inside type arguments
+fn split_type_list(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&list[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&list[start..]);
+    parts
+}
+
+/// Base name of a type reference: `core.List<T>?` -> `List`
+fn type_base_name(t: &str) -> String {
+    let t = t.trim().split('<').next().unwrap_or("").trim().trim_end_matches('?');
+    t.rsplit('.').next().unwrap_or(t).trim().to_string()
+}
+
 /// Extract a function from lambda_expression at top level.
 /// tree-sitter-dart 0.0.4 wraps "void main() {}" as lambda_expression > function_signature + function_body
 fn extract_lambda_function(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>) {
@@ -509,6 +667,15 @@ fn extract_lambda_function(node: &Node, content: &str, symbols: &mut Vec<ParsedS
                     line,
                     signature: sig,
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 return;
             }
@@ -526,6 +693,15 @@ fn extract_lambda_function(node: &Node, content: &str, symbols: &mut Vec<ParsedS
                         line,
                         signature: sig,
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                     return;
                 }
@@ -547,6 +723,15 @@ fn extract_function_signature(node: &Node, content: &str, symbols: &mut Vec<Pars
             line,
             signature: sig,
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 }
@@ -564,6 +749,15 @@ fn extract_getter(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>) {
             line,
             signature: sig,
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 }
@@ -581,6 +775,15 @@ fn extract_setter(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbol>) {
             line,
             signature: sig,
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 }
@@ -600,6 +803,15 @@ fn extract_local_var_as_property(node: &Node, content: &str, symbols: &mut Vec<P
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
         }
@@ -740,6 +952,15 @@ fn extract_constructor(node: &Node, content: &str, symbols: &mut Vec<ParsedSymbo
             line,
             signature: sig,
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 }
@@ -774,6 +995,15 @@ fn extract_factory_constructor(node: &Node, content: &str, symbols: &mut Vec<Par
             line,
             signature: sig,
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 }
@@ -792,6 +1022,15 @@ fn extract_const_constructor(node: &Node, content: &str, symbols: &mut Vec<Parse
             line,
             signature: sig,
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 }
@@ -809,6 +1048,15 @@ fn extract_top_level_vars(node: &Node, content: &str, symbols: &mut Vec<ParsedSy
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
         }
@@ -828,6 +1076,15 @@ fn extract_top_level_consts(node: &Node, content: &str, symbols: &mut Vec<Parsed
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
         }
@@ -842,6 +1099,29 @@ fn try_recover_from_error(node: &Node, content: &str, symbols: &mut Vec<ParsedSy
     let text = node_text(content, node).trim().to_string();
     let line = node_line(node);
 
+    // "class C = Base with M;" without a grammar node for it
+    if text.contains('=') && text.contains(" with ") {
+        if let Some((name, parents)) = parse_mixin_application(&text) {
+            symbols.push(ParsedSymbol {
+                name,
+                kind: SymbolKind::Class,
+                line,
+                signature: line_text(content, line).trim().to_string(),
+                parents,
+                container: None,
+                qualified_name: None,
+                doc: None,
+                annotations: vec![],
+                visibility: None,
+                end_line: None,
+                byte_range: None,
+                condition: None,
+                generated_by: None,
+            });
+            return;
+        }
+    }
+
     // Check for "sealed class X", "base class X", "final class X"
     if let Some(class_info) = try_parse_modified_class(&text) {
         let sig_line = line_text(content, line).trim().to_string();
@@ -856,6 +1136,15 @@ fn try_recover_from_error(node: &Node, content: &str, symbols: &mut Vec<ParsedSy
             line,
             signature: sig_line,
             parents,
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
 
         // Walk the next sibling (block node) for body declarations
@@ -876,6 +1165,15 @@ fn try_recover_from_error(node: &Node, content: &str, symbols: &mut Vec<ParsedSy
                 line,
                 signature: sig_line,
                 parents: ext_type_info.parents,
+                container: None,
+                qualified_name: None,
+                doc: None,
+                annotations: vec![],
+                visibility: None,
+                end_line: None,
+                byte_range: None,
+                condition: None,
+                generated_by: None,
             });
         }
     }
@@ -1495,4 +1793,50 @@ set appName(String value) {}
         assert!(symbols.iter().any(|s| s.name == "appName" && s.kind == SymbolKind::Property),
             "Should find top-level getter appName, got: {:?}", symbols);
     }
+
+    #[test]
+    fn test_parse_mixin_application() {
+        let (name, parents) = parse_mixin_application(
+            "class Button = Widget with Tappable, Focusable<Button> implements Semantics;").unwrap();
+        assert_eq!(name, "Button");
+        assert_eq!(parents, vec![
+            ("Widget".to_string(), "extends".to_string()),
+            ("Tappable".to_string(), "with".to_string()),
+            ("Focusable".to_string(), "with".to_string()),
+            ("Semantics".to_string(), "implements".to_string()),
+        ]);
+        assert!(parse_mixin_application("final x = a;").is_none());
+    }
+
+    #[test]
+    fn test_type_base_name() {
+        assert_eq!(type_base_name("String?"), "String");
+        assert_eq!(type_base_name(" core.List<Map<String, int>> "), "List");
+        assert_eq!(type_base_name("Iterable<T>"), "Iterable");
+    }
+
+    #[test]
+    fn test_parse_mixin_class_and_application_parents() {
+        let content = r#"mixin class Logger extends Base with Tagged {
+}
+
+class Button = Widget with Tappable;
+"#;
+        let symbols = DART_PARSER.parse_symbols(content).unwrap();
+        let logger = symbols.iter().find(|s| s.name == "Logger").unwrap();
+        assert!(logger.parents.iter().any(|(p, k)| p == "Tagged" && k == "with"),
+            "Expected with Tagged, got: {:?}", logger.parents);
+        let button = symbols.iter().find(|s| s.name == "Button" && s.kind == SymbolKind::Class).unwrap();
+        assert!(button.parents.iter().any(|(p, k)| p == "Widget" && k == "extends"));
+        assert!(button.parents.iter().any(|(p, k)| p == "Tappable" && k == "with"));
+    }
+
+    #[test]
+    fn test_parse_extension_nullable_target() {
+        let content = "extension NullableString on String? {\n}\n";
+        let symbols = DART_PARSER.parse_symbols(content).unwrap();
+        let ext = symbols.iter().find(|s| s.name == "NullableString").unwrap();
+        assert!(ext.parents.iter().any(|(p, k)| p == "String" && k == "extends"),
+            "Expected extends String, got: {:?}", ext.parents);
+    }
 }
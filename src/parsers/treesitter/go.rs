@@ -5,8 +5,9 @@ use tree_sitter::{Language, Query, QueryCursor, StreamingIterator};
 use std::sync::LazyLock;
 
 use crate::db::SymbolKind;
-use crate::parsers::ParsedSymbol;
-use super::{LanguageParser, parse_tree, node_text, node_line, line_text};
+use crate::parsers::references::{extract_references_for, RefLanguage};
+use crate::parsers::{ParsedRef, ParsedSymbol};
+use super::{LanguageParser, assign_tree_containers, parse_tree, node_text, node_line, line_text};
 
 static GO_LANGUAGE: LazyLock<Language> = LazyLock::new(|| tree_sitter_go::LANGUAGE.into());
 
@@ -15,6 +16,8 @@ static GO_QUERY: LazyLock<Query> = LazyLock::new(|| {
         .expect("Failed to compile Go tree-sitter query")
 });
 
+const SCOPE_KINDS: &[&str] = &["type_spec", "function_declaration", "method_declaration"];
+
 pub static GO_PARSER: GoParser = GoParser;
 
 pub struct GoParser;
@@ -60,6 +63,15 @@ impl LanguageParser for GoParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -87,6 +99,15 @@ impl LanguageParser for GoParser {
                         format!("import \"{}\"", path)
                     },
                     parents: vec![(path.to_string(), "from".to_string())],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -101,6 +122,15 @@ impl LanguageParser for GoParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -109,13 +139,47 @@ impl LanguageParser for GoParser {
             if let Some(cap) = find_capture(m, idx_interface_name) {
                 let name = node_text(content, &cap.node);
                 let line = node_line(&cap.node);
+                let body = cap.node.parent().and_then(|spec| spec.child_by_field_name("type"));
                 symbols.push(ParsedSymbol {
                     name: name.to_string(),
                     kind: SymbolKind::Interface,
                     line,
                     signature: line_text(content, line).trim().to_string(),
-                    parents: vec![],
+                    parents: body.map(|b| embedded_interfaces(content, &b)).unwrap_or_default(),
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
+                // Method set: each method_elem becomes a member function of the interface
+                if let Some(body) = body {
+                    let mut walker = body.walk();
+                    for elem in body.named_children(&mut walker).filter(|e| e.kind() == "method_elem") {
+                        let Some(method) = elem.child_by_field_name("name") else { continue };
+                        let method_line = node_line(&method);
+                        symbols.push(ParsedSymbol {
+                            name: node_text(content, &method).to_string(),
+                            kind: SymbolKind::Function,
+                            line: method_line,
+                            signature: line_text(content, method_line).trim().to_string(),
+                            parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
+                        });
+                    }
+                }
                 continue;
             }
 
@@ -132,6 +196,15 @@ impl LanguageParser for GoParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![(target.to_string(), "alias".to_string())],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -149,6 +222,15 @@ impl LanguageParser for GoParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![(receiver.to_string(), "receiver".to_string())],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -166,6 +248,15 @@ impl LanguageParser for GoParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![(receiver.to_string(), "receiver".to_string())],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -181,6 +272,15 @@ impl LanguageParser for GoParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -195,6 +295,15 @@ impl LanguageParser for GoParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -209,13 +318,51 @@ impl LanguageParser for GoParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
         }
 
+        assign_tree_containers(&tree, content, &mut symbols, SCOPE_KINDS);
         Ok(symbols)
     }
+
+    /// snake_case calls and Go call patterns on top of the CamelCase references
+    fn extract_refs(&self, content: &str, defined: &[ParsedSymbol]) -> Result<Vec<ParsedRef>> {
+        extract_references_for(content, defined, RefLanguage::Go)
+    }
+
+    fn sets_containers(&self) -> bool {
+        true
+    }
+}
+
+/// Interfaces embedded in an interface body (`type ReadWriter interface { Reader; ... }`).
+/// Type-set constraints such as `~int | ~string` are not embeddings.
+fn embedded_interfaces(content: &str, body: &tree_sitter::Node) -> Vec<(String, String)> {
+    let mut parents = Vec::new();
+    let mut walker = body.walk();
+    for elem in body.named_children(&mut walker).filter(|e| e.kind() == "type_elem") {
+        if elem.named_child_count() != 1 {
+            continue;
+        }
+        let Some(ty) = elem.named_child(0) else { continue };
+        if matches!(ty.kind(), "type_identifier" | "qualified_type") {
+            let name = node_text(content, &ty);
+            let name = name.rsplit('.').next().unwrap_or(name);
+            parents.push((name.to_string(), "extends".to_string()));
+        }
+    }
+    parents
 }
 
 /// Find a capture by index in a match
@@ -378,5 +525,22 @@ type ReadWriter interface {
         let symbols = GO_PARSER.parse_symbols(content).unwrap();
         assert!(symbols.iter().any(|s| s.name == "Reader" && s.kind == SymbolKind::Interface));
         assert!(symbols.iter().any(|s| s.name == "ReadWriter" && s.kind == SymbolKind::Interface));
+        let rw = symbols.iter().find(|s| s.name == "ReadWriter").unwrap();
+        assert_eq!(rw.parents, vec![("Reader".to_string(), "extends".to_string())]);
+        // Interface methods are indexed as members
+        assert!(symbols.iter().any(|s| s.name == "Read" && s.kind == SymbolKind::Function && s.line == 4));
+        assert!(symbols.iter().any(|s| s.name == "Write" && s.kind == SymbolKind::Function && s.line == 9));
+    }
+
+    #[test]
+    fn test_generic_receiver_and_constraints() {
+        let content = "package main\n\ntype Number interface {\n    ~int | ~float64\n}\n\ntype Box[T any] struct{ v T }\n\nfunc (b *Box[T]) Get() T { return b.v }\nfunc (b Box[T]) Len() int { return 1 }\n";
+        let symbols = GO_PARSER.parse_symbols(content).unwrap();
+        let number = symbols.iter().find(|s| s.name == "Number").unwrap();
+        assert!(number.parents.is_empty());
+        let get = symbols.iter().find(|s| s.name == "Get").unwrap();
+        assert_eq!(get.parents, vec![("Box".to_string(), "receiver".to_string())]);
+        let len = symbols.iter().find(|s| s.name == "Len").unwrap();
+        assert_eq!(len.parents, vec![("Box".to_string(), "receiver".to_string())]);
     }
 }
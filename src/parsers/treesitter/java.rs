@@ -6,7 +6,7 @@ use std::sync::LazyLock;
 
 use crate::db::SymbolKind;
 use crate::parsers::ParsedSymbol;
-use super::{LanguageParser, parse_tree, node_text, node_line, line_text};
+use super::{LanguageParser, assign_tree_containers, parse_tree, node_text, node_line, node_extent, line_text};
 
 static JAVA_LANGUAGE: LazyLock<Language> = LazyLock::new(|| tree_sitter_java::LANGUAGE.into());
 
@@ -15,6 +15,16 @@ static JAVA_QUERY: LazyLock<Query> = LazyLock::new(|| {
         .expect("Failed to compile Java tree-sitter query")
 });
 
+const SCOPE_KINDS: &[&str] = &[
+    "class_declaration",
+    "interface_declaration",
+    "enum_declaration",
+    "record_declaration",
+    "annotation_type_declaration",
+    "method_declaration",
+    "constructor_declaration",
+];
+
 pub static JAVA_PARSER: JavaParser = JavaParser;
 
 pub struct JavaParser;
@@ -50,6 +60,9 @@ impl LanguageParser for JavaParser {
         let idx_interface_node = idx("interface_node");
         let idx_enum_name = idx("enum_name");
         let idx_enum_node = idx("enum_node");
+        let idx_record_name = idx("record_name");
+        let idx_record_params = idx("record_params");
+        let idx_record_node = idx("record_node");
         let idx_method_name = idx("method_name");
         let idx_method_node = idx("method_node");
         let idx_constructor_name = idx("constructor_name");
@@ -78,6 +91,15 @@ impl LanguageParser for JavaParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents,
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: find_capture(m, idx_class_node).and_then(|n| interface_member_visibility(&n.node)),
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -97,6 +119,15 @@ impl LanguageParser for JavaParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents,
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: find_capture(m, idx_interface_node).and_then(|n| interface_member_visibility(&n.node)),
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -116,7 +147,70 @@ impl LanguageParser for JavaParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents,
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: find_capture(m, idx_enum_node).and_then(|n| interface_member_visibility(&n.node)),
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
+                    });
+                }
+                continue;
+            }
+
+            // === Records: the record is a class, its components are properties ===
+            if let Some(name_cap) = find_capture(m, idx_record_name) {
+                let name = node_text(content, &name_cap.node);
+                let line = node_line(&name_cap.node);
+                if emitted.insert((name.to_string(), line)) {
+                    let record_node = find_capture(m, idx_record_node).map(|n| n.node);
+                    symbols.push(ParsedSymbol {
+                        name: name.to_string(),
+                        kind: SymbolKind::Class,
+                        line,
+                        signature: line_text(content, line).trim().to_string(),
+                        parents: record_node.map(|n| extract_class_parents(content, &n)).unwrap_or_default(),
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: record_node.and_then(|n| interface_member_visibility(&n)),
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
+                    if let Some(params) = find_capture(m, idx_record_params) {
+                        let mut walker = params.node.walk();
+                        for param in params.node.named_children(&mut walker) {
+                            let Some(param_name) = param.child_by_field_name("name") else { continue };
+                            let component = node_text(content, &param_name);
+                            let param_line = node_line(&param_name);
+                            let (end_line, byte_range) = node_extent(&param);
+                            if emitted.insert((component.to_string(), param_line)) {
+                                symbols.push(ParsedSymbol {
+                                    name: component.to_string(),
+                                    kind: SymbolKind::Property,
+                                    line: param_line,
+                                    signature: line_text(content, param_line).trim().to_string(),
+                                    parents: vec![],
+                                    container: None,
+                                    qualified_name: None,
+                                    doc: None,
+                                    annotations: vec![],
+                                    // Components get a public accessor method
+                                    visibility: Some("public".to_string()),
+                                    end_line,
+                                    byte_range,
+                                    condition: None,
+                                    generated_by: None,
+                                });
+                            }
+                        }
+                    }
                 }
                 continue;
             }
@@ -134,6 +228,15 @@ impl LanguageParser for JavaParser {
                                 line,
                                 signature: line_text(content, line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: interface_member_visibility(&node_cap.node),
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
@@ -154,6 +257,15 @@ impl LanguageParser for JavaParser {
                                 line,
                                 signature: line_text(content, line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: interface_member_visibility(&node_cap.node),
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
@@ -174,6 +286,15 @@ impl LanguageParser for JavaParser {
                                 line,
                                 signature: line_text(content, line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: interface_member_visibility(&node_cap.node),
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
@@ -193,6 +314,15 @@ impl LanguageParser for JavaParser {
                             line,
                             signature: line_text(content, line).trim().to_string(),
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -211,6 +341,15 @@ impl LanguageParser for JavaParser {
                             line,
                             signature: line_text(content, line).trim().to_string(),
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -218,8 +357,13 @@ impl LanguageParser for JavaParser {
             }
         }
 
+        assign_tree_containers(&tree, content, &mut symbols, SCOPE_KINDS);
         Ok(symbols)
     }
+
+    fn sets_containers(&self) -> bool {
+        true
+    }
 }
 
 /// Check if a node is inside a class_body, interface_body, or enum_body
@@ -229,7 +373,25 @@ fn is_inside_type_body(node: &tree_sitter::Node) -> bool {
         .unwrap_or(false)
 }
 
-/// Extract parent types from a class_declaration (extends + implements)
+/// Members of an interface (including `default` and `static` methods and nested types)
+/// are implicitly public unless declared `private`
+fn interface_member_visibility(node: &tree_sitter::Node) -> Option<String> {
+    if node.parent()?.kind() != "interface_body" {
+        return None;
+    }
+    let mut cursor = node.walk();
+    let private = node
+        .children(&mut cursor)
+        .filter(|c| c.kind() == "modifiers")
+        .any(|modifiers| {
+            let mut mc = modifiers.walk();
+            let found = modifiers.children(&mut mc).any(|m| m.kind() == "private");
+            found
+        });
+    Some(if private { "private" } else { "public" }.to_string())
+}
+
+/// Extract parent types from a class_declaration or record_declaration (extends + implements + sealed permits)
 fn extract_class_parents(content: &str, class_node: &tree_sitter::Node) -> Vec<(String, String)> {
     let mut parents = Vec::new();
     let mut cursor = class_node.walk();
@@ -246,6 +408,10 @@ fn extract_class_parents(content: &str, class_node: &tree_sitter::Node) -> Vec<(
                 // super_interfaces -> "implements" type_list -> type_identifier+
                 extract_type_list(&child, content, "implements", &mut parents);
             }
+            "permits" => {
+                // sealed class: permitted subtypes are stored as reverse-inheritance hints
+                extract_type_list(&child, content, "permits", &mut parents);
+            }
             _ => {}
         }
     }
@@ -253,14 +419,16 @@ fn extract_class_parents(content: &str, class_node: &tree_sitter::Node) -> Vec<(
     parents
 }
 
-/// Extract parent types from an interface_declaration (extends)
+/// Extract parent types from an interface_declaration (extends + sealed permits)
 fn extract_interface_parents(content: &str, iface_node: &tree_sitter::Node) -> Vec<(String, String)> {
     let mut parents = Vec::new();
     let mut cursor = iface_node.walk();
 
     for child in iface_node.children(&mut cursor) {
-        if child.kind() == "extends_interfaces" {
-            extract_type_list(&child, content, "extends", &mut parents);
+        match child.kind() {
+            "extends_interfaces" => extract_type_list(&child, content, "extends", &mut parents),
+            "permits" => extract_type_list(&child, content, "permits", &mut parents),
+            _ => {}
         }
     }
 
@@ -510,4 +678,46 @@ public class Foo {
         assert!(cls.parents.iter().any(|(p, k)| p == "CrudRepository" && k == "extends"));
         assert!(cls.parents.iter().any(|(p, k)| p == "UserRepository" && k == "implements"));
     }
+
+    #[test]
+    fn test_parse_record() {
+        let content = "public record Point(int x, int y) implements Comparable<Point> {\n    public Point {\n        if (x < 0) throw new IllegalArgumentException();\n    }\n    public double norm() { return 0; }\n}\n";
+        let symbols = JAVA_PARSER.parse_symbols(content).unwrap();
+        let record = symbols.iter().find(|s| s.name == "Point" && s.kind == SymbolKind::Class).unwrap();
+        assert!(record.parents.iter().any(|(p, k)| p == "Comparable" && k == "implements"));
+        for component in ["x", "y"] {
+            let prop = symbols.iter().find(|s| s.name == component).unwrap();
+            assert_eq!(prop.kind, SymbolKind::Property);
+            assert_eq!(prop.visibility.as_deref(), Some("public"));
+        }
+        // Compact canonical constructor
+        assert!(symbols.iter().any(|s| s.name == "Point" && s.kind == SymbolKind::Function && s.line == 2));
+        assert!(symbols.iter().any(|s| s.name == "norm"));
+    }
+
+    #[test]
+    fn test_parse_sealed_permits() {
+        let content = "public sealed interface Shape permits Circle, Shapes.Square {}\npublic abstract sealed class Vehicle extends Base permits Car {}\n";
+        let symbols = JAVA_PARSER.parse_symbols(content).unwrap();
+        let shape = symbols.iter().find(|s| s.name == "Shape").unwrap();
+        assert_eq!(
+            shape.parents,
+            vec![("Circle".to_string(), "permits".to_string()), ("Square".to_string(), "permits".to_string())]
+        );
+        let vehicle = symbols.iter().find(|s| s.name == "Vehicle").unwrap();
+        assert!(vehicle.parents.iter().any(|(p, k)| p == "Base" && k == "extends"));
+        assert!(vehicle.parents.iter().any(|(p, k)| p == "Car" && k == "permits"));
+    }
+
+    #[test]
+    fn test_interface_members_are_public() {
+        let content = "interface Greeter {\n    String name();\n    default String greet() { return \"hi \" + name(); }\n    private String secret() { return \"\"; }\n}\nclass Impl {\n    String helper() { return \"\"; }\n}\n";
+        let symbols = JAVA_PARSER.parse_symbols(content).unwrap();
+        let visibility = |name: &str| symbols.iter().find(|s| s.name == name).unwrap().visibility.clone();
+        assert_eq!(visibility("name").as_deref(), Some("public"));
+        assert_eq!(visibility("greet").as_deref(), Some("public"));
+        assert_eq!(visibility("secret").as_deref(), Some("private"));
+        // Class members are left to the visibility pass
+        assert_eq!(visibility("helper"), None);
+    }
 }
@@ -6,7 +6,7 @@ use std::sync::LazyLock;
 
 use crate::db::SymbolKind;
 use crate::parsers::ParsedSymbol;
-use super::{LanguageParser, parse_tree, node_text, node_line, line_text};
+use super::{LanguageParser, assign_tree_containers, parse_tree, node_text, node_line, node_extent, line_text};
 
 static KT_LANGUAGE: LazyLock<Language> = LazyLock::new(|| tree_sitter_kotlin_ng::LANGUAGE.into());
 
@@ -15,6 +15,13 @@ static KT_QUERY: LazyLock<Query> = LazyLock::new(|| {
         .expect("Failed to compile Kotlin tree-sitter query")
 });
 
+const SCOPE_KINDS: &[&str] = &[
+    "class_declaration",
+    "object_declaration",
+    "companion_object",
+    "function_declaration",
+];
+
 pub static KOTLIN_PARSER: KotlinParser = KotlinParser;
 
 pub struct KotlinParser;
@@ -35,6 +42,9 @@ impl LanguageParser for KotlinParser {
         let idx_class_decl = idx("class_decl");
         let idx_object_name = idx("object_name");
         let idx_object_decl = idx("object_decl");
+        let idx_companion_decl = idx("companion_decl");
+        let idx_enum_entry_name = idx("enum_entry_name");
+        let idx_enum_entry_decl = idx("enum_entry_decl");
         let idx_func_name = idx("func_name");
         let idx_property_name = idx("property_name");
         let idx_typealias_name = idx("typealias_name");
@@ -80,6 +90,15 @@ impl LanguageParser for KotlinParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents,
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -102,6 +121,68 @@ impl LanguageParser for KotlinParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents,
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
+                });
+                continue;
+            }
+
+            // Companion object: members are scoped to Outer.Companion (or its explicit name)
+            if let Some(cap) = find_capture(m, idx_companion_decl) {
+                let node = &cap.node;
+                let name = node
+                    .child_by_field_name("name")
+                    .map(|n| node_text(content, &n))
+                    .unwrap_or("Companion");
+                let line = keyword_line(node, content, "companion").unwrap_or_else(|| node_line(node));
+                let (end_line, byte_range) = node_extent(node);
+                symbols.push(ParsedSymbol {
+                    name: name.to_string(),
+                    kind: SymbolKind::Object,
+                    line,
+                    signature: line_text(content, line).trim().to_string(),
+                    parents: parse_delegation_specifiers(node, content),
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line,
+                    byte_range,
+                    condition: None,
+                    generated_by: None,
+                });
+                continue;
+            }
+
+            // Enum entry
+            if let Some(cap) = find_capture(m, idx_enum_entry_name) {
+                let name = node_text(content, &cap.node);
+                let line = node_line(&cap.node);
+                let (end_line, byte_range) = find_capture(m, idx_enum_entry_decl)
+                    .map_or((None, None), |decl| node_extent(&decl.node));
+                symbols.push(ParsedSymbol {
+                    name: name.to_string(),
+                    kind: SymbolKind::Constant,
+                    line,
+                    signature: line_text(content, line).trim().to_string(),
+                    parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line,
+                    byte_range,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -116,6 +197,15 @@ impl LanguageParser for KotlinParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -130,6 +220,15 @@ impl LanguageParser for KotlinParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -144,13 +243,27 @@ impl LanguageParser for KotlinParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
         }
 
+        assign_tree_containers(&tree, content, &mut symbols, SCOPE_KINDS);
         Ok(symbols)
     }
+
+    fn sets_containers(&self) -> bool {
+        true
+    }
 }
 
 /// Check if a class_declaration node contains a specific keyword (e.g., "interface", "class")
@@ -166,6 +279,16 @@ fn has_keyword(node: &tree_sitter::Node, content: &str, keyword: &str) -> bool {
     false
 }
 
+/// Line of a keyword token (e.g. `companion`) among a node's direct children
+fn keyword_line(node: &tree_sitter::Node, content: &str, keyword: &str) -> Option<usize> {
+    let mut walker = node.walk();
+    let line = node
+        .children(&mut walker)
+        .find(|child| !child.is_named() && node_text(content, child) == keyword)
+        .map(|child| node_line(&child));
+    line
+}
+
 /// Check if a class_declaration has a specific class_modifier (e.g., "enum", "sealed", "data")
 fn has_class_modifier(node: &tree_sitter::Node, content: &str, modifier: &str) -> bool {
     let mut walker = node.walk();
@@ -427,4 +550,49 @@ mod tests {
         assert!(symbols.iter().any(|s| s.name == "real"));
         assert!(!symbols.iter().any(|s| s.name == "fake"));
     }
+
+    #[test]
+    fn test_parse_companion_object() {
+        let content = "class Account {\n    companion object {\n        fun create() = Account()\n    }\n}\nclass Service {\n    companion object Factory : Provider<Service> {\n        fun build() = Service()\n    }\n}\n";
+        let symbols = KOTLIN_PARSER.parse_symbols(content).unwrap();
+        let companion = symbols.iter().find(|s| s.name == "Companion").unwrap();
+        assert_eq!(companion.kind, SymbolKind::Object);
+        assert_eq!((companion.line, companion.end_line), (2, Some(4)));
+        let factory = symbols.iter().find(|s| s.name == "Factory").unwrap();
+        assert_eq!(factory.line, 7);
+        assert!(factory.parents.iter().any(|(p, _)| p == "Provider"));
+
+        let (symbols, _) = crate::parsers::parse_file_symbols(content, crate::parsers::FileType::Kotlin).unwrap();
+        let create = symbols.iter().find(|s| s.name == "create").unwrap();
+        assert_eq!(create.container.as_deref(), Some("Account.Companion"));
+        let build = symbols.iter().find(|s| s.name == "build").unwrap();
+        assert_eq!(build.container.as_deref(), Some("Service.Factory"));
+    }
+
+    #[test]
+    fn test_locals_nest_under_their_function() {
+        let content = "class Main {\n    val repo = Repo()\n    fun run() {\n        val r = repo.load()\n        class Local { fun go() {} }\n    }\n}\nfun top() {\n    val t = 1\n}\n";
+        let symbols = KOTLIN_PARSER.parse_symbols(content).unwrap();
+        let container = |name: &str| symbols.iter().find(|s| s.name == name).unwrap().container.as_deref();
+        assert_eq!(container("Main"), None);
+        assert_eq!(container("repo"), Some("Main"));
+        assert_eq!(container("run"), Some("Main"));
+        assert_eq!(container("r"), Some("Main.run"));
+        assert_eq!(container("Local"), Some("Main.run"));
+        assert_eq!(container("go"), Some("Main.run.Local"));
+        assert_eq!(container("t"), Some("top"));
+    }
+
+    #[test]
+    fn test_parse_enum_entries() {
+        let content = "enum class Status { ACTIVE, BLOCKED }\nenum class Level(val n: Int) {\n    LOW(1),\n    HIGH(2) {\n        override fun toString() = \"high\"\n    };\n}\n";
+        let (symbols, _) = crate::parsers::parse_file_symbols(content, crate::parsers::FileType::Kotlin).unwrap();
+        for (name, container) in [("ACTIVE", "Status"), ("BLOCKED", "Status"), ("LOW", "Level"), ("HIGH", "Level")] {
+            let entry = symbols.iter().find(|s| s.name == name).unwrap();
+            assert_eq!(entry.kind, SymbolKind::Constant);
+            assert_eq!(entry.container.as_deref(), Some(container));
+        }
+        let high = symbols.iter().find(|s| s.name == "HIGH").unwrap();
+        assert_eq!((high.line, high.end_line), (4, Some(6)));
+    }
 }
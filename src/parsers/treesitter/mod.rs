@@ -2,26 +2,42 @@
 //!
 //! Each language module implements `TreeSitterParser` which provides
 //! `parse_symbols()` to extract symbols from source code using tree-sitter queries.
+//! Grammars are compiled in per language via the `lang-*` cargo features.
 
+#[cfg(feature = "lang-cpp")]
 pub mod cpp;
+#[cfg(feature = "lang-csharp")]
 pub mod csharp;
+#[cfg(feature = "lang-dart")]
 pub mod dart;
+#[cfg(feature = "lang-go")]
 pub mod go;
+#[cfg(feature = "lang-java")]
 pub mod java;
+#[cfg(feature = "lang-kotlin")]
 pub mod kotlin;
+#[cfg(feature = "lang-objc")]
 pub mod objc;
+#[cfg(feature = "lang-proto")]
 pub mod proto;
+#[cfg(feature = "lang-python")]
 pub mod python;
+#[cfg(feature = "lang-ruby")]
 pub mod ruby;
+#[cfg(feature = "lang-rust")]
 pub mod rust_lang;
+#[cfg(feature = "lang-scala")]
 pub mod scala;
+#[cfg(feature = "lang-swift")]
 pub mod swift;
+#[cfg(feature = "lang-typescript")]
 pub mod typescript;
 
 use anyhow::Result;
 use tree_sitter::{Language, Parser, Tree};
 
 use super::{ParsedSymbol, ParsedRef, extract_references, FileType};
+use crate::db::SymbolKind;
 
 /// Trait for tree-sitter based language parsers
 pub trait LanguageParser: Send + Sync {
@@ -33,24 +49,46 @@ pub trait LanguageParser: Send + Sync {
     fn extract_refs(&self, content: &str, defined: &[ParsedSymbol]) -> Result<Vec<ParsedRef>> {
         extract_references(content, defined)
     }
+
+    /// Whether `parse_symbols` sets containers from the syntax tree (`assign_tree_containers`);
+    /// otherwise they are derived from braces or indentation afterwards (`scope::assign_containers`).
+    /// Such parsers list the node kinds of their type and function declarations, whose bodies
+    /// hold other symbols, in a `SCOPE_KINDS` constant
+    fn sets_containers(&self) -> bool {
+        false
+    }
 }
 
 /// Get a tree-sitter parser for the given file type, if available
 pub fn get_treesitter_parser(file_type: FileType) -> Option<&'static dyn LanguageParser> {
     match file_type {
+        #[cfg(feature = "lang-cpp")]
         FileType::Cpp => Some(&cpp::CPP_PARSER),
+        #[cfg(feature = "lang-csharp")]
         FileType::CSharp => Some(&csharp::CSHARP_PARSER),
+        #[cfg(feature = "lang-dart")]
         FileType::Dart => Some(&dart::DART_PARSER),
+        #[cfg(feature = "lang-go")]
         FileType::Go => Some(&go::GO_PARSER),
+        #[cfg(feature = "lang-java")]
         FileType::Java => Some(&java::JAVA_PARSER),
+        #[cfg(feature = "lang-kotlin")]
         FileType::Kotlin => Some(&kotlin::KOTLIN_PARSER),
+        #[cfg(feature = "lang-objc")]
         FileType::ObjC => Some(&objc::OBJC_PARSER),
+        #[cfg(feature = "lang-proto")]
         FileType::Proto => Some(&proto::PROTO_PARSER),
+        #[cfg(feature = "lang-python")]
         FileType::Python => Some(&python::PYTHON_PARSER),
+        #[cfg(feature = "lang-ruby")]
         FileType::Ruby => Some(&ruby::RUBY_PARSER),
+        #[cfg(feature = "lang-rust")]
         FileType::Rust => Some(&rust_lang::RUST_PARSER),
+        #[cfg(feature = "lang-scala")]
         FileType::Scala => Some(&scala::SCALA_PARSER),
+        #[cfg(feature = "lang-swift")]
         FileType::Swift => Some(&swift::SWIFT_PARSER),
+        #[cfg(feature = "lang-typescript")]
         FileType::TypeScript => Some(&typescript::TYPESCRIPT_PARSER),
         _ => None,
     }
@@ -81,7 +119,109 @@ fn node_line(node: &tree_sitter::Node) -> usize {
     node.start_position().row + 1
 }
 
+/// Helper to get the last line (1-based) and byte range of a declaration node
+#[cfg(any(feature = "lang-kotlin", feature = "lang-cpp", feature = "lang-proto", feature = "lang-java"))]
+fn node_extent(node: &tree_sitter::Node) -> (Option<usize>, Option<(usize, usize)>) {
+    (Some(node.end_position().row + 1), Some((node.start_byte(), node.end_byte())))
+}
+
 /// Helper to get the full line text for a node (for signature)
 fn line_text(content: &str, line: usize) -> &str {
     content.lines().nth(line - 1).unwrap_or("")
 }
+
+/// Set `container` from the declarations enclosing each symbol in the tree: `scope_kinds` are the
+/// node kinds of the grammar's type and function declarations. A symbol declared in a function
+/// body nests under the function (`Main.run`), so locals are never taken for members.
+/// Containers the parser already set are kept.
+fn assign_tree_containers(tree: &Tree, content: &str, symbols: &mut [ParsedSymbol], scope_kinds: &[&str]) {
+    let root = tree.root_node();
+    let line_starts: Vec<usize> =
+        std::iter::once(0).chain(content.match_indices('\n').map(|(i, _)| i + 1)).collect();
+
+    // Scope declarations around each symbol, innermost first, and the one the symbol itself declares
+    let mut enclosing: Vec<Vec<tree_sitter::Node>> = Vec::with_capacity(symbols.len());
+    let mut declared_by: std::collections::HashMap<usize, usize> = std::collections::HashMap::new();
+    for (i, symbol) in symbols.iter().enumerate() {
+        let offset = match symbol.kind {
+            SymbolKind::Import | SymbolKind::Package => None,
+            _ => name_offset(content, &line_starts, symbol),
+        };
+        let Some(offset) = offset else {
+            enclosing.push(vec![]);
+            continue;
+        };
+        let mut scopes = Vec::new();
+        let mut node = root.descendant_for_byte_range(offset, offset);
+        while let Some(current) = node {
+            if scope_kinds.contains(&current.kind()) {
+                scopes.push(current);
+            }
+            node = current.parent();
+        }
+        if scopes.first().is_some_and(|scope| declares(scope, offset)) {
+            declared_by.entry(scopes[0].id()).or_insert(i);
+            scopes.remove(0);
+        }
+        enclosing.push(scopes);
+    }
+
+    let chains: Vec<Option<String>> = enclosing
+        .iter()
+        .map(|scopes| {
+            let names: Vec<String> = scopes
+                .iter()
+                .rev()
+                .filter_map(|scope| match declared_by.get(&scope.id()) {
+                    Some(&owner) => Some(super::scope::container_name(&symbols[owner]).to_string()),
+                    None => scope_name(scope, content),
+                })
+                .collect();
+            (!names.is_empty()).then(|| names.join("."))
+        })
+        .collect();
+    for (symbol, chain) in symbols.iter_mut().zip(chains) {
+        if symbol.container.is_none() {
+            symbol.container = chain;
+        }
+    }
+}
+
+/// Byte offset of the symbol's name on its line; the line's first token when the name is not
+/// written there as a word (`impl Display for User`, generated accessors)
+fn name_offset(content: &str, line_starts: &[usize], symbol: &ParsedSymbol) -> Option<usize> {
+    let start = *line_starts.get(symbol.line.checked_sub(1)?)?;
+    let end = line_starts.get(symbol.line).map_or(content.len(), |next| next - 1);
+    let text = &content[start..end];
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let found = text.match_indices(symbol.name.as_str()).find(|&(at, name)| {
+        !name.is_empty()
+            && !text[..at].ends_with(is_word)
+            && !text[at + name.len()..].starts_with(is_word)
+    });
+    Some(start + found.map_or_else(|| text.len() - text.trim_start().len(), |(at, _)| at))
+}
+
+/// Whether `offset` (a symbol's name) is the name of this declaration rather than something in it:
+/// inside its `name` field, or before its body when it has no name field
+fn declares(scope: &tree_sitter::Node, offset: usize) -> bool {
+    if let Some(name) = scope.child_by_field_name("name") {
+        return name.byte_range().contains(&offset);
+    }
+    let mut cursor = scope.walk();
+    let body = scope
+        .child_by_field_name("body")
+        .or_else(|| scope.named_children(&mut cursor).find(|child| child.kind().ends_with("body")));
+    body.is_none_or(|body| offset < body.start_byte())
+}
+
+/// Name of a scope no symbol was parsed for: its `name` field, the `type` of a Rust impl,
+/// or the variable an anonymous function is assigned to
+fn scope_name(scope: &tree_sitter::Node, content: &str) -> Option<String> {
+    let name = scope
+        .child_by_field_name("name")
+        .or_else(|| (scope.kind() == "impl_item").then(|| scope.child_by_field_name("type")).flatten())
+        .or_else(|| scope.parent().and_then(|parent| parent.child_by_field_name("name")))?;
+    let text = node_text(content, &name);
+    Some(text.split('<').next().unwrap_or(text).trim().to_string())
+}
@@ -4,8 +4,8 @@ use anyhow::Result;
 use tree_sitter::{Language, Node, Query, QueryCursor, StreamingIterator};
 use std::sync::LazyLock;
 
-use crate::db::SymbolKind;
-use crate::parsers::ParsedSymbol;
+use crate::db::{RefKind, SymbolKind};
+use crate::parsers::{truncate_context, ParsedRef, ParsedSymbol, extract_references};
 use super::{LanguageParser, parse_tree, node_text, line_text};
 
 static OBJC_LANGUAGE: LazyLock<Language> = LazyLock::new(|| tree_sitter_objc::LANGUAGE.into());
@@ -78,6 +78,15 @@ impl LanguageParser for ObjcParser {
                             line,
                             signature: sig,
                             parents: vec![(class_name, "extends".to_string())],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     } else {
                         let mut parents = Vec::new();
@@ -98,6 +107,15 @@ impl LanguageParser for ObjcParser {
                             line,
                             signature: sig,
                             parents,
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -121,6 +139,15 @@ impl LanguageParser for ObjcParser {
                         line,
                         signature: sig,
                         parents,
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -141,6 +168,15 @@ impl LanguageParser for ObjcParser {
                             line,
                             signature: sig,
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -150,7 +186,7 @@ impl LanguageParser for ObjcParser {
             // Method declaration (in @interface/@protocol)
             if let Some(cap) = find_capture(m, idx_method_decl) {
                 let node = &cap.node;
-                if let Some(name) = extract_method_name(content, node) {
+                if let Some(name) = extract_method_selector(content, node) {
                     let line = node.start_position().row + 1;
                     let sig = line_text(content, line).trim().to_string();
                     symbols.push(ParsedSymbol {
@@ -159,6 +195,15 @@ impl LanguageParser for ObjcParser {
                         line,
                         signature: sig,
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -167,7 +212,7 @@ impl LanguageParser for ObjcParser {
             // Method definition (in @implementation)
             if let Some(cap) = find_capture(m, idx_method_def) {
                 let node = &cap.node;
-                if let Some(name) = extract_method_name(content, node) {
+                if let Some(name) = extract_method_selector(content, node) {
                     let line = node.start_position().row + 1;
                     let sig = line_text(content, line).trim().to_string();
                     symbols.push(ParsedSymbol {
@@ -176,6 +221,15 @@ impl LanguageParser for ObjcParser {
                         line,
                         signature: sig,
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -193,6 +247,15 @@ impl LanguageParser for ObjcParser {
                         line,
                         signature: sig,
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -211,6 +274,15 @@ impl LanguageParser for ObjcParser {
                             line,
                             signature: sig,
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -220,6 +292,129 @@ impl LanguageParser for ObjcParser {
 
         Ok(symbols)
     }
+
+    /// Generic references plus message sends (`[obj configureWith:u options:o]`) by full selector
+    fn extract_refs(&self, content: &str, defined: &[ParsedSymbol]) -> Result<Vec<ParsedRef>> {
+        let mut refs = extract_references(content, defined)?;
+        let tree = parse_tree(content, &OBJC_LANGUAGE)?;
+
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
+            if node.kind() != "message_expression" {
+                continue;
+            }
+            let Some(selector) = message_selector(node_text(content, &node)) else { continue };
+            if OBJC_NOISE_SELECTORS.contains(&selector.as_str()) {
+                continue;
+            }
+            let line = node.start_position().row + 1;
+            refs.push(ParsedRef {
+                arity: Some(selector.matches(':').count()),
+                name: selector,
+                line,
+                context: truncate_context(line_text(content, line).trim()),
+                kind: RefKind::Call,
+            });
+        }
+        Ok(refs)
+    }
+}
+
+/// Messages sent to nearly every object; indexing them only adds noise
+const OBJC_NOISE_SELECTORS: &[&str] = &["alloc", "init", "new", "copy", "retain", "release", "autorelease", "class"];
+
+/// Full selector of a method declaration/definition: `configureWith:options:` for
+/// `- (void)configureWith:(User *)user options:(Options *)opts`, `viewDidLoad` for unary methods
+fn extract_method_selector(content: &str, node: &Node) -> Option<String> {
+    let text = node_text(content, node);
+    let header = text.split(['{', ';']).next().unwrap_or(text);
+    keyword_selector(header).or_else(|| extract_method_name(content, node))
+}
+
+/// Selector of a message expression: `[obj configureWith:u options:o]` -> `configureWith:options:`,
+/// `[obj reload]` -> `reload`
+fn message_selector(text: &str) -> Option<String> {
+    let inner = text.trim().strip_prefix('[')?.strip_suffix(']')?;
+    if let Some(selector) = keyword_selector(inner) {
+        return Some(selector);
+    }
+    let flat = top_level_text(inner);
+    let words: Vec<&str> = flat
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+        .collect();
+    // A lone word is the selector only when the receiver is itself an expression: `[[A alloc] init]`
+    let nested_receiver = inner.trim_start().starts_with(['[', '(']);
+    match words.as_slice() {
+        [_, .., selector] => Some(selector.to_string()),
+        [selector] if nested_receiver => Some(selector.to_string()),
+        _ => None,
+    }
+}
+
+/// Join the `keyword:` parts found outside nested groups: `a:(int)x b:(int)y` -> `a:b:`.
+/// The `:` of a `?:` conditional is not a selector part.
+fn keyword_selector(text: &str) -> Option<String> {
+    let flat = top_level_text(text);
+    let mut selector = String::new();
+    let mut in_conditional = false;
+    for (i, c) in flat.char_indices() {
+        match c {
+            '?' => in_conditional = true,
+            ':' if in_conditional => in_conditional = false,
+            ':' => {
+                // The keyword touches its colon; `a :(int)b` is an anonymous part
+                let before = &flat[..i];
+                let keyword_len: usize = before
+                    .chars()
+                    .rev()
+                    .take_while(|ch| ch.is_alphanumeric() || *ch == '_')
+                    .map(char::len_utf8)
+                    .sum();
+                selector.push_str(&before[before.len() - keyword_len..]);
+                selector.push(':');
+            }
+            _ => {}
+        }
+    }
+    (!selector.is_empty()).then_some(selector)
+}
+
+/// Text outside nested `()`, `[]`, `{}` groups, with string and char literals blanked
+fn top_level_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut depth = 0usize;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' => {
+                let mut escaped = false;
+                for next in chars.by_ref() {
+                    if escaped {
+                        escaped = false;
+                    } else if next == '\\' {
+                        escaped = true;
+                    } else if next == c {
+                        break;
+                    }
+                }
+                out.push(' ');
+            }
+            '(' | '[' | '{' => {
+                depth += 1;
+                out.push(' ');
+            }
+            ')' | ']' | '}' => {
+                depth = depth.saturating_sub(1);
+                out.push(' ');
+            }
+            _ if depth == 0 => out.push(c),
+            _ => {}
+        }
+    }
+    out
 }
 
 /// Extract the class/implementation name (first identifier child after @interface/@implementation keyword)
@@ -306,7 +501,7 @@ fn extract_protocol_parents(content: &str, node: &Node, parents: &mut Vec<(Strin
     }
 }
 
-/// Extract method name from method_declaration or method_definition
+/// Extract method name from method_declaration or method_definition (fallback for unary selectors)
 /// For simple methods like `- (void)viewDidLoad`, the name is the first identifier child.
 /// For methods with parameters like `- (void)setName:(NSString *)name`, we extract
 /// the first selector part from keyword_declarator or method_selector.
@@ -728,4 +923,39 @@ typedef struct { int x; int y; } CGPoint;
         assert!(symbols.iter().any(|s| s.name == "defaultTitle" && s.kind == SymbolKind::Function),
             "expected defaultTitle, got: {:?}", symbols);
     }
+
+    #[test]
+    fn test_keyword_selector() {
+        assert_eq!(
+            keyword_selector("- (void)configureWith:(User *)user options:(Options *)opts").as_deref(),
+            Some("configureWith:options:")
+        );
+        assert_eq!(
+            keyword_selector("- (void)load:(void (^)(BOOL ok))done :(int)retries").as_deref(),
+            Some("load::")
+        );
+        assert_eq!(keyword_selector("- (void)viewDidLoad"), None);
+    }
+
+    #[test]
+    fn test_message_selector() {
+        assert_eq!(message_selector("[obj configureWith:user options:[self opts]]").as_deref(), Some("configureWith:options:"));
+        assert_eq!(message_selector("[view setHidden:flag ? YES : NO]").as_deref(), Some("setHidden:"));
+        assert_eq!(message_selector("[NSString stringWithFormat:@\"%d: %@\", n, s]").as_deref(), Some("stringWithFormat:"));
+        assert_eq!(message_selector("[self.tableView reloadData]").as_deref(), Some("reloadData"));
+        assert_eq!(message_selector("[[Cache alloc] init]").as_deref(), Some("init"));
+    }
+
+    #[test]
+    fn test_full_selector_symbols_and_refs() {
+        let content = "@implementation Screen\n- (void)configureWith:(User *)user options:(Options *)opts {\n}\n- (void)reload {\n    [self configureWith:self.user\n              options:nil];\n}\n@end\n";
+        let symbols = OBJC_PARSER.parse_symbols(content).unwrap();
+        assert!(symbols.iter().any(|s| s.name == "configureWith:options:" && s.kind == SymbolKind::Function),
+            "expected full selector, got: {:?}", symbols);
+        assert!(symbols.iter().any(|s| s.name == "reload"));
+
+        let refs = OBJC_PARSER.extract_refs(content, &symbols).unwrap();
+        assert!(refs.iter().any(|r| r.name == "configureWith:options:" && r.line == 5),
+            "expected message send ref, got: {:?}", refs);
+    }
 }
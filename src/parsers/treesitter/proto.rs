@@ -6,7 +6,7 @@ use std::sync::LazyLock;
 
 use crate::db::SymbolKind;
 use crate::parsers::ParsedSymbol;
-use super::{LanguageParser, parse_tree, node_text, node_line, line_text};
+use super::{LanguageParser, parse_tree, node_text, node_line, node_extent, line_text};
 
 static PROTO_LANGUAGE: LazyLock<Language> = LazyLock::new(|| tree_sitter_proto::LANGUAGE.into());
 
@@ -38,6 +38,7 @@ impl LanguageParser for ProtoParser {
         let idx_rpc_name = idx("rpc_name");
         let idx_rpc_request_type = idx("rpc_request_type");
         let idx_rpc_response_type = idx("rpc_response_type");
+        let idx_rpc_node = idx("rpc_node");
 
         let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
 
@@ -52,6 +53,15 @@ impl LanguageParser for ProtoParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -70,6 +80,15 @@ impl LanguageParser for ProtoParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -85,6 +104,15 @@ impl LanguageParser for ProtoParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -94,17 +122,22 @@ impl LanguageParser for ProtoParser {
                 let name = node_text(content, &name_cap.node);
                 let line = node_line(&name_cap.node);
 
-                let request_type = find_capture(m, idx_rpc_request_type)
-                    .map(|c| node_text(content, &c.node))
-                    .unwrap_or("");
-                let response_type = find_capture(m, idx_rpc_response_type)
-                    .map(|c| node_text(content, &c.node))
-                    .unwrap_or("");
-
-                let signature = format!(
-                    "rpc {}({}) returns ({})",
-                    name, request_type, response_type
-                );
+                let rpc_node = find_capture(m, idx_rpc_node).map(|c| c.node);
+                // The declaration text keeps `stream` markers and fully qualified types
+                let signature = match rpc_node {
+                    Some(node) => rpc_signature(node_text(content, &node)),
+                    None => {
+                        let request_type = find_capture(m, idx_rpc_request_type)
+                            .map(|c| node_text(content, &c.node))
+                            .unwrap_or("");
+                        let response_type = find_capture(m, idx_rpc_response_type)
+                            .map(|c| node_text(content, &c.node))
+                            .unwrap_or("");
+                        format!("rpc {}({}) returns ({})", name, request_type, response_type)
+                    }
+                };
+                let container = rpc_node.and_then(|node| enclosing_service(content, node));
+                let (end_line, byte_range) = rpc_node.map_or((None, None), |node| node_extent(&node));
 
                 symbols.push(ParsedSymbol {
                     name: name.to_string(),
@@ -112,6 +145,15 @@ impl LanguageParser for ProtoParser {
                     line,
                     signature,
                     parents: vec![],
+                    container,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line,
+                    byte_range,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -126,14 +168,16 @@ impl LanguageParser for ProtoParser {
 
 /// Recursively walk the tree to collect messages and enums with proper nesting paths.
 ///
-/// For nested messages/enums, builds dot-separated names like `Outer.Inner`
-/// and sets the parent relationship with `nested_in`.
+/// For nested messages/enums, builds dot-separated names like `Outer.Inner`,
+/// sets the parent relationship with `nested_in` and the enclosing message as container.
+/// Enum values are indexed as constants of their enum.
 fn collect_messages_and_enums(
     content: &str,
     node: &tree_sitter::Node,
     parent_path: &[String],
     symbols: &mut Vec<ParsedSymbol>,
 ) {
+    let container = (!parent_path.is_empty()).then(|| parent_path.join("."));
     let mut walk_cursor = node.walk();
     for child in node.children(&mut walk_cursor) {
         match child.kind() {
@@ -161,6 +205,15 @@ fn collect_messages_and_enums(
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents,
+                        container: container.clone(),
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
 
                     // Recurse into message_body for nested messages/enums
@@ -184,12 +237,22 @@ fn collect_messages_and_enums(
 
                     let line = node_line(&child);
                     symbols.push(ParsedSymbol {
-                        name: full_name,
+                        name: full_name.clone(),
                         kind: SymbolKind::Enum,
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: container.clone(),
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
+                    collect_enum_values(content, &child, &full_name, symbols);
                 }
             }
             _ => {}
@@ -197,6 +260,54 @@ fn collect_messages_and_enums(
     }
 }
 
+/// Index the values of an enum (`ACTIVE = 1;`) as constants contained in `enum_name`
+fn collect_enum_values(content: &str, enum_node: &tree_sitter::Node, enum_name: &str, symbols: &mut Vec<ParsedSymbol>) {
+    let mut cursor = enum_node.walk();
+    let Some(body) = enum_node.children(&mut cursor).find(|c| c.kind() == "enum_body") else { return };
+    let mut body_cursor = body.walk();
+    for field in body.children(&mut body_cursor).filter(|c| c.kind() == "enum_field") {
+        let mut field_cursor = field.walk();
+        let Some(ident) = field.children(&mut field_cursor).find(|c| c.kind() == "identifier") else { continue };
+        let line = node_line(&field);
+        let (end_line, byte_range) = node_extent(&field);
+        symbols.push(ParsedSymbol {
+            name: node_text(content, &ident).to_string(),
+            kind: SymbolKind::Constant,
+            line,
+            signature: line_text(content, line).trim().to_string(),
+            parents: vec![],
+            container: Some(enum_name.to_string()),
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line,
+            byte_range,
+            condition: None,
+            generated_by: None,
+        });
+    }
+}
+
+/// Name of the service an rpc is declared in
+fn enclosing_service(content: &str, node: tree_sitter::Node) -> Option<String> {
+    let mut current = node.parent();
+    while let Some(parent) = current {
+        if parent.kind() == "service" {
+            return extract_named_child_text(content, &parent, "service_name");
+        }
+        current = parent.parent();
+    }
+    None
+}
+
+/// One-line signature of an rpc declaration, without its options block:
+/// `rpc Watch(stream WatchRequest) returns (stream WatchEvent)`
+fn rpc_signature(text: &str) -> String {
+    let head = text.split(['{', ';']).next().unwrap_or(text);
+    head.split_whitespace().collect::<Vec<_>>().join(" ").replace("( ", "(").replace(" )", ")")
+}
+
 /// Extract text from a named child node type (e.g., "message_name" -> identifier text)
 fn extract_named_child_text(content: &str, node: &tree_sitter::Node, child_kind: &str) -> Option<String> {
     let mut cursor = node.walk();
@@ -694,4 +805,46 @@ message Outer {
         let msg = symbols.iter().find(|s| s.name == "Foo" && s.kind == SymbolKind::Class).unwrap();
         assert_eq!(msg.line, 4, "message should be on line 4");
     }
+
+    #[test]
+    fn test_rpc_signature() {
+        assert_eq!(
+            rpc_signature("rpc Watch(\n    stream WatchRequest\n  ) returns (stream WatchEvent) {\n    option deprecated = true;\n  }"),
+            "rpc Watch(stream WatchRequest) returns (stream WatchEvent)"
+        );
+        assert_eq!(
+            rpc_signature("rpc Delete(DeleteRequest) returns (google.protobuf.Empty);"),
+            "rpc Delete(DeleteRequest) returns (google.protobuf.Empty)"
+        );
+    }
+
+    #[test]
+    fn test_rpc_container_and_enum_values() {
+        let content = r#"
+syntax = "proto3";
+
+service EventService {
+    rpc StreamEvents(stream EventRequest) returns (stream EventResponse);
+}
+
+message Event {
+    message Payload {}
+    enum Level {
+        LOW = 0;
+        HIGH = 1;
+    }
+}
+"#;
+        let symbols = PROTO_PARSER.parse_symbols(content).unwrap();
+        let rpc = symbols.iter().find(|s| s.name == "StreamEvents").unwrap();
+        assert_eq!(rpc.container.as_deref(), Some("EventService"));
+        assert_eq!(rpc.signature, "rpc StreamEvents(stream EventRequest) returns (stream EventResponse)");
+
+        let payload = symbols.iter().find(|s| s.name == "Event.Payload").unwrap();
+        assert_eq!(payload.container.as_deref(), Some("Event"));
+
+        let high = symbols.iter().find(|s| s.name == "HIGH").unwrap();
+        assert_eq!(high.kind, SymbolKind::Constant);
+        assert_eq!(high.container.as_deref(), Some("Event.Level"));
+    }
 }
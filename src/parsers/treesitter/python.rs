@@ -5,8 +5,9 @@ use tree_sitter::{Language, Query, QueryCursor, StreamingIterator};
 use std::sync::LazyLock;
 
 use crate::db::SymbolKind;
-use crate::parsers::ParsedSymbol;
-use super::{LanguageParser, parse_tree, node_text, node_line, line_text};
+use crate::parsers::references::{extract_references_for, RefLanguage};
+use crate::parsers::{ParsedRef, ParsedSymbol};
+use super::{LanguageParser, assign_tree_containers, parse_tree, node_text, node_line, line_text};
 
 static PY_LANGUAGE: LazyLock<Language> = LazyLock::new(|| tree_sitter_python::LANGUAGE.into());
 
@@ -15,6 +16,8 @@ static PY_QUERY: LazyLock<Query> = LazyLock::new(|| {
         .expect("Failed to compile Python tree-sitter query")
 });
 
+const SCOPE_KINDS: &[&str] = &["class_definition", "function_definition"];
+
 pub static PYTHON_PARSER: PythonParser = PythonParser;
 
 pub struct PythonParser;
@@ -44,6 +47,9 @@ impl LanguageParser for PythonParser {
         let idx_decorated_func_name = idx("decorated_func_name");
         let idx_method_name = idx("method_name");
         let idx_decorated_method_name = idx("decorated_method_name");
+        let idx_class_field_name = idx("class_field_name");
+        let idx_class_field_type = idx("class_field_type");
+        let idx_type_alias_name = idx("type_alias_name");
         let idx_assignment_name = idx("assignment_name");
         let idx_assignment_value = idx("assignment_value");
 
@@ -63,6 +69,15 @@ impl LanguageParser for PythonParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -79,6 +94,15 @@ impl LanguageParser for PythonParser {
                     line,
                     signature: sig.clone(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
 
                 for cap in m.captures.iter().filter(|c| Some(c.index) == idx_import_from_name) {
@@ -90,6 +114,15 @@ impl LanguageParser for PythonParser {
                             line,
                             signature: sig.clone(),
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -108,6 +141,15 @@ impl LanguageParser for PythonParser {
                     line,
                     signature: sig.clone(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
 
                 if let Some(name_cap) = find_capture(m, idx_import_from_aliased_name) {
@@ -118,6 +160,15 @@ impl LanguageParser for PythonParser {
                         line,
                         signature: sig,
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -137,6 +188,15 @@ impl LanguageParser for PythonParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents,
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -146,7 +206,7 @@ impl LanguageParser for PythonParser {
             if let Some(cap) = find_capture(m, idx_decorator) {
                 let dec_text = node_text(content, &cap.node);
                 let line = node_line(&cap.node);
-                let name = dec_text.trim_start_matches('@');
+                let name = decorator_name(dec_text);
                 if is_significant_decorator(name) {
                     symbols.push(ParsedSymbol {
                         name: format!("@{}", name),
@@ -154,6 +214,15 @@ impl LanguageParser for PythonParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -163,8 +232,7 @@ impl LanguageParser for PythonParser {
             if let Some(cap) = find_capture(m, idx_func_decorator) {
                 let dec_text = node_text(content, &cap.node);
                 let line = node_line(&cap.node);
-                let name = dec_text.trim_start_matches('@');
-                let name = name.split('(').next().unwrap_or(name);
+                let name = decorator_name(dec_text);
                 if is_significant_decorator(name) {
                     symbols.push(ParsedSymbol {
                         name: format!("@{}", name),
@@ -172,6 +240,15 @@ impl LanguageParser for PythonParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -189,6 +266,15 @@ impl LanguageParser for PythonParser {
                             line,
                             signature: line_text(content, line).trim().to_string(),
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -207,6 +293,15 @@ impl LanguageParser for PythonParser {
                             line,
                             signature: line_text(content, line).trim().to_string(),
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -224,6 +319,15 @@ impl LanguageParser for PythonParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -240,26 +344,105 @@ impl LanguageParser for PythonParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
+                    });
+                }
+                continue;
+            }
+
+            // Dataclass field
+            if let Some(name_cap) = find_capture(m, idx_class_field_name) {
+                let is_class_var = find_capture(m, idx_class_field_type)
+                    .map(|c| node_text(content, &c.node))
+                    .is_some_and(|t| t.starts_with("ClassVar") || t.starts_with("typing.ClassVar"));
+                let class_node = name_cap.node.parent().and_then(|a| a.parent()).and_then(|e| e.parent()).and_then(|b| b.parent());
+                if !is_class_var && class_node.is_some_and(|c| is_dataclass(content, &c)) {
+                    let name = node_text(content, &name_cap.node);
+                    let line = node_line(&name_cap.node);
+                    symbols.push(ParsedSymbol {
+                        name: name.to_string(),
+                        kind: SymbolKind::Property,
+                        line,
+                        signature: line_text(content, line).trim().to_string(),
+                        parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
             }
 
+            // PEP 695 type alias
+            if let Some(cap) = find_capture(m, idx_type_alias_name) {
+                let text = node_text(content, &cap.node);
+                let name = text.split('[').next().unwrap_or(text).trim();
+                let line = node_line(&cap.node);
+                symbols.push(ParsedSymbol {
+                    name: name.to_string(),
+                    kind: SymbolKind::TypeAlias,
+                    line,
+                    signature: line_text(content, line).trim().to_string(),
+                    parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
+                });
+                continue;
+            }
+
             // Module-level assignments
             if let Some(name_cap) = find_capture(m, idx_assignment_name) {
                 let name = node_text(content, &name_cap.node);
                 let line = node_line(&name_cap.node);
                 let sig = line_text(content, line).trim().to_string();
 
+                // `UserId: TypeAlias = int`
+                let annotated_alias = name_cap
+                    .node
+                    .parent()
+                    .and_then(|a| a.child_by_field_name("type"))
+                    .is_some_and(|t| matches!(node_text(content, &t), "TypeAlias" | "typing.TypeAlias"));
+
                 if let Some(val_cap) = find_capture(m, idx_assignment_value) {
                     let val = node_text(content, &val_cap.node);
-                    if is_type_alias_value(val) && name.chars().next().map(|c| c.is_uppercase()).unwrap_or(false) {
+                    let conventional_alias = is_type_alias_value(val) && name.chars().next().map(|c| c.is_uppercase()).unwrap_or(false);
+                    if annotated_alias || conventional_alias {
                         symbols.push(ParsedSymbol {
                             name: name.to_string(),
                             kind: SymbolKind::TypeAlias,
                             line,
                             signature: sig,
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                         continue;
                     }
@@ -272,14 +455,33 @@ impl LanguageParser for PythonParser {
                         line,
                         signature: sig,
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
             }
         }
 
+        assign_tree_containers(&tree, content, &mut symbols, SCOPE_KINDS);
         Ok(symbols)
     }
+
+    /// snake_case calls and Python call patterns on top of the CamelCase references
+    fn extract_refs(&self, content: &str, defined: &[ParsedSymbol]) -> Result<Vec<ParsedRef>> {
+        extract_references_for(content, defined, RefLanguage::Python)
+    }
+
+    fn sets_containers(&self) -> bool {
+        true
+    }
 }
 
 fn parse_python_parents(content: &str, node: &tree_sitter::Node) -> Vec<(String, String)> {
@@ -317,6 +519,23 @@ fn is_type_alias_value(val: &str) -> bool {
         || val.starts_with("Type")
 }
 
+/// Decorator name without `@` and call arguments: `@app.route("/x")` -> `app.route`
+fn decorator_name(text: &str) -> &str {
+    let name = text.trim_start_matches('@');
+    name.split('(').next().unwrap_or(name).trim()
+}
+
+/// Whether a class_definition is decorated with `@dataclass` / `@dataclasses.dataclass(...)`
+fn is_dataclass(content: &str, class_node: &tree_sitter::Node) -> bool {
+    let Some(decorated) = class_node.parent().filter(|p| p.kind() == "decorated_definition") else { return false };
+    let mut cursor = decorated.walk();
+    let found = decorated
+        .children(&mut cursor)
+        .filter(|c| c.kind() == "decorator")
+        .any(|d| decorator_name(node_text(content, &d)).rsplit('.').next() == Some("dataclass"));
+    found
+}
+
 fn is_significant_decorator(name: &str) -> bool {
     name.contains("route")
         || name.contains("handler")
@@ -420,4 +639,35 @@ mod tests {
         assert!(symbols.iter().any(|s| s.name == "fetch_data" && s.kind == SymbolKind::Function));
         assert!(symbols.iter().any(|s| s.name == "process_event" && s.kind == SymbolKind::Function));
     }
+
+    #[test]
+    fn test_dataclass_fields() {
+        let content = "@dataclass(frozen=True)\nclass User:\n    id: int\n    name: str = \"\"\n    LIMIT: ClassVar[int] = 3\n\nclass Plain:\n    cache: dict = {}\n";
+        let symbols = PYTHON_PARSER.parse_symbols(content).unwrap();
+        assert!(symbols.iter().any(|s| s.name == "@dataclass"));
+        assert!(symbols.iter().any(|s| s.name == "id" && s.kind == SymbolKind::Property && s.line == 3));
+        assert!(symbols.iter().any(|s| s.name == "name" && s.kind == SymbolKind::Property));
+        assert!(!symbols.iter().any(|s| s.name == "LIMIT"));
+        assert!(!symbols.iter().any(|s| s.name == "cache"));
+    }
+
+    #[test]
+    fn test_annotated_and_pep695_type_aliases() {
+        let content = "UserId: TypeAlias = int\ntype Pair[T] = tuple[T, T]\ncount: int = 0\n";
+        let symbols = PYTHON_PARSER.parse_symbols(content).unwrap();
+        assert!(symbols.iter().any(|s| s.name == "UserId" && s.kind == SymbolKind::TypeAlias));
+        assert!(symbols.iter().any(|s| s.name == "Pair" && s.kind == SymbolKind::TypeAlias && s.line == 2));
+        assert!(!symbols.iter().any(|s| s.name == "count"));
+    }
+
+    #[test]
+    fn test_decorated_async_functions() {
+        let content = "@app.route(\n    \"/users\",\n)\nasync def list_users(request):\n    pass\n\nclass Service:\n    @staticmethod\n    async def fetch(url):\n        pass\n";
+        let symbols = PYTHON_PARSER.parse_symbols(content).unwrap();
+        assert!(symbols.iter().any(|s| s.name == "@app.route"));
+        let list_users = symbols.iter().find(|s| s.name == "list_users").unwrap();
+        assert!(list_users.signature.starts_with("async def"));
+        let fetch = symbols.iter().find(|s| s.name == "fetch").unwrap();
+        assert!(fetch.signature.starts_with("async def"));
+    }
 }
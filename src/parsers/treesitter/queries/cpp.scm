@@ -49,6 +49,15 @@
         scope: (namespace_identifier) @template_method_class
         name: (identifier) @template_method_name))))
 
+; Template method definition on a class template: Class<T>::MethodName
+(template_declaration
+  (function_definition
+    declarator: (function_declarator
+      declarator: (qualified_identifier
+        scope: (template_type
+          name: (type_identifier) @template_method_class)
+        name: (identifier) @template_method_name))))
+
 ; Destructor definition outside class: ClassName::~ClassName()
 (function_definition
   declarator: (function_declarator
@@ -62,6 +71,10 @@
 (namespace_definition
   name: (namespace_identifier) @namespace_name)
 
+; namespace a::b::c { ... } (C++17)
+(namespace_definition
+  name: (nested_namespace_specifier) @namespace_name)
+
 ; === Enums ===
 
 ; enum Name { ... } or enum class Name { ... }
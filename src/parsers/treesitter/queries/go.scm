@@ -50,6 +50,19 @@
       type: (type_identifier) @method_receiver_value))
   name: (field_identifier) @method_name_value)
 
+; Methods on generic types: func (b *Box[T]) Get()
+(method_declaration
+  receiver: (parameter_list
+    (parameter_declaration
+      type: (pointer_type (generic_type type: (type_identifier) @method_receiver))))
+  name: (field_identifier) @method_name)
+
+(method_declaration
+  receiver: (parameter_list
+    (parameter_declaration
+      type: (generic_type type: (type_identifier) @method_receiver_value)))
+  name: (field_identifier) @method_name_value)
+
 ; Constants (single and block)
 (const_declaration
   (const_spec
@@ -10,6 +10,11 @@
 (enum_declaration
   name: (identifier) @enum_name) @enum_node
 
+; Records: record Point(int x, int y)
+(record_declaration
+  name: (identifier) @record_name
+  parameters: (formal_parameters) @record_params) @record_node
+
 ; Methods
 (method_declaration
   name: (identifier) @method_name) @method_node
@@ -18,6 +23,10 @@
 (constructor_declaration
   name: (identifier) @constructor_name) @constructor_node
 
+; Compact canonical constructors of records: public Point { ... }
+(compact_constructor_declaration
+  name: (identifier) @constructor_name) @constructor_node
+
 ; Fields
 (field_declaration
   declarator: (variable_declarator
@@ -7,6 +7,13 @@
 (object_declaration
   name: (identifier) @object_name) @object_decl
 
+; Companion objects (the name is optional and defaults to Companion)
+(companion_object) @companion_decl
+
+; Enum entries: enum class Status { ACTIVE, BLOCKED }
+(enum_entry
+  (identifier) @enum_entry_name) @enum_entry_decl
+
 ; Function declarations (including suspend, extension, etc.)
 (function_declaration
   name: (identifier) @func_name)
@@ -18,4 +18,4 @@
   (rpc_name
     (identifier) @rpc_name)
   (message_or_enum_type) @rpc_request_type
-  (message_or_enum_type) @rpc_response_type)
+  (message_or_enum_type) @rpc_response_type) @rpc_node
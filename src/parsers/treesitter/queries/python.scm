@@ -52,6 +52,18 @@
       (function_definition
         name: (identifier) @decorated_method_name))))
 
+; Annotated class attributes (dataclass fields): name: str = ""
+(class_definition
+  body: (block
+    (expression_statement
+      (assignment
+        left: (identifier) @class_field_name
+        type: (type) @class_field_type))))
+
+; PEP 695 type aliases: type Pair[T] = tuple[T, T]
+(type_alias_statement
+  left: (type) @type_alias_name)
+
 ; Module-level assignments (constants and type aliases)
 (module
   (expression_statement
@@ -1,12 +1,16 @@
 //! Tree-sitter based Ruby parser
+//!
+//! Rails DSL declarations (`has_many`, `validates`, callbacks, `scope`) are indexed
+//! only in Rails mode, see `crate::parsers::rails_mode`.
 
 use anyhow::Result;
 use tree_sitter::{Language, Query, QueryCursor, StreamingIterator};
 use std::sync::LazyLock;
 
 use crate::db::SymbolKind;
-use crate::parsers::ParsedSymbol;
-use super::{LanguageParser, parse_tree, node_text, node_line, line_text};
+use crate::parsers::references::{extract_references_for, RefLanguage};
+use crate::parsers::{ParsedRef, ParsedSymbol};
+use super::{LanguageParser, assign_tree_containers, parse_tree, node_text, node_line, line_text};
 
 static RUBY_LANGUAGE: LazyLock<Language> = LazyLock::new(|| tree_sitter_ruby::LANGUAGE.into());
 
@@ -15,12 +19,30 @@ static RUBY_QUERY: LazyLock<Query> = LazyLock::new(|| {
         .expect("Failed to compile Ruby tree-sitter query")
 });
 
+const SCOPE_KINDS: &[&str] = &["class", "module", "method", "singleton_method"];
+
 pub static RUBY_PARSER: RubyParser = RubyParser;
 
 pub struct RubyParser;
 
 impl LanguageParser for RubyParser {
     fn parse_symbols(&self, content: &str) -> Result<Vec<ParsedSymbol>> {
+        self.parse_symbols_with(content, crate::parsers::rails_mode())
+    }
+
+    /// snake_case calls and Ruby call patterns on top of the CamelCase references
+    fn extract_refs(&self, content: &str, defined: &[ParsedSymbol]) -> Result<Vec<ParsedRef>> {
+        extract_references_for(content, defined, RefLanguage::Ruby)
+    }
+
+    fn sets_containers(&self) -> bool {
+        true
+    }
+}
+
+impl RubyParser {
+    /// Parse symbols; `rails` enables Rails DSL declarations
+    pub fn parse_symbols_with(&self, content: &str, rails: bool) -> Result<Vec<ParsedSymbol>> {
         let tree = parse_tree(content, &RUBY_LANGUAGE)?;
         let mut symbols = Vec::new();
         let query = &*RUBY_QUERY;
@@ -41,6 +63,8 @@ impl LanguageParser for RubyParser {
         let idx_call_method = idx("call_method");
         let idx_call_first_arg = idx("call_first_arg");
 
+        // (line of the enclosing class/module, mixed-in module) for include/extend/prepend
+        let mut mixins: Vec<(usize, String)> = Vec::new();
         let mut matches = cursor.matches(query, tree.root_node(), content.as_bytes());
 
         while let Some(m) = matches.next() {
@@ -57,6 +81,15 @@ impl LanguageParser for RubyParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents,
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -71,6 +104,15 @@ impl LanguageParser for RubyParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -87,6 +129,15 @@ impl LanguageParser for RubyParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -102,6 +153,15 @@ impl LanguageParser for RubyParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -117,6 +177,15 @@ impl LanguageParser for RubyParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -147,6 +216,15 @@ impl LanguageParser for RubyParser {
                                 line,
                                 signature: line_text(content, line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: None,
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
@@ -154,33 +232,66 @@ impl LanguageParser for RubyParser {
                     // include / extend / prepend
                     "include" | "extend" | "prepend" if !has_receiver => {
                         if let Some(arg) = first_arg {
+                            if let Some(owner) = call_node.and_then(enclosing_definition) {
+                                mixins.push((node_line(&owner), arg.to_string()));
+                            }
                             symbols.push(ParsedSymbol {
                                 name: format!("{} {}", method, arg),
                                 kind: SymbolKind::Import,
                                 line,
                                 signature: line_text(content, line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: None,
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
 
-                    // attr_reader / attr_writer / attr_accessor
+                    // attr_reader / attr_writer / attr_accessor: one property per name
                     "attr_reader" | "attr_writer" | "attr_accessor" if !has_receiver => {
-                        if let Some(arg) = first_arg {
-                            let sym_name = normalize_symbol(arg);
+                        let names: Vec<_> = call_node
+                            .and_then(|n| n.child_by_field_name("arguments"))
+                            .map(|args| {
+                                let mut walker = args.walk();
+                                args.named_children(&mut walker)
+                                    .filter(|a| matches!(a.kind(), "simple_symbol" | "string"))
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        for arg in names {
+                            let arg_text = node_text(content, &arg).trim_matches(|c| c == '\'' || c == '"');
+                            let arg_line = node_line(&arg);
                             symbols.push(ParsedSymbol {
-                                name: format!(":{}", sym_name),
+                                name: format!(":{}", normalize_symbol(arg_text)),
                                 kind: SymbolKind::Property,
-                                line,
-                                signature: line_text(content, line).trim().to_string(),
+                                line: arg_line,
+                                signature: line_text(content, arg_line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: None,
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
 
-                    // Rails associations: has_many, has_one, belongs_to, has_and_belongs_to_many
+                    // Rails associations: has_many, has_one, belongs_to, has_and_belongs_to_many,
+                    // Active Storage attachments
                     "has_many" | "has_one" | "belongs_to" | "has_and_belongs_to_many"
-                        if !has_receiver =>
+                    | "has_one_attached" | "has_many_attached"
+                        if rails && !has_receiver =>
                     {
                         if let Some(arg) = first_arg {
                             let sym_name = normalize_symbol(arg);
@@ -190,20 +301,38 @@ impl LanguageParser for RubyParser {
                                 line,
                                 signature: line_text(content, line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: None,
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
 
                     // Rails validates
-                    "validates" if !has_receiver => {
+                    "validates" | "validate" if rails && !has_receiver => {
                         if let Some(arg) = first_arg {
                             let sym_name = normalize_symbol(arg);
                             symbols.push(ParsedSymbol {
-                                name: format!("validates :{}", sym_name),
+                                name: format!("{} :{}", method, sym_name),
                                 kind: SymbolKind::Annotation,
                                 line,
                                 signature: line_text(content, line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: None,
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
@@ -212,9 +341,12 @@ impl LanguageParser for RubyParser {
                     "before_action" | "after_action" | "around_action"
                     | "before_create" | "after_create"
                     | "before_save" | "after_save"
+                    | "before_update" | "after_update"
                     | "before_destroy" | "after_destroy"
                     | "before_validation" | "after_validation"
-                        if !has_receiver =>
+                    | "after_commit" | "after_initialize"
+                    | "skip_before_action"
+                        if rails && !has_receiver =>
                     {
                         if let Some(arg) = first_arg {
                             let sym_name = normalize_symbol(arg);
@@ -224,12 +356,21 @@ impl LanguageParser for RubyParser {
                                 line,
                                 signature: line_text(content, line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: None,
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
 
                     // Rails scope
-                    "scope" if !has_receiver => {
+                    "scope" if rails && !has_receiver => {
                         if let Some(arg) = first_arg {
                             let sym_name = normalize_symbol(arg);
                             symbols.push(ParsedSymbol {
@@ -238,6 +379,15 @@ impl LanguageParser for RubyParser {
                                 line,
                                 signature: line_text(content, line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: None,
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
@@ -252,6 +402,15 @@ impl LanguageParser for RubyParser {
                                 line,
                                 signature: line_text(content, line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: None,
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
@@ -266,6 +425,15 @@ impl LanguageParser for RubyParser {
                                 line,
                                 signature: line_text(content, line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: None,
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
@@ -280,6 +448,15 @@ impl LanguageParser for RubyParser {
                                 line,
                                 signature: line_text(content, line).trim().to_string(),
                                 parents: vec![],
+                                container: None,
+                                qualified_name: None,
+                                doc: None,
+                                annotations: vec![],
+                                visibility: None,
+                                end_line: None,
+                                byte_range: None,
+                                condition: None,
+                                generated_by: None,
                             });
                         }
                     }
@@ -290,10 +467,32 @@ impl LanguageParser for RubyParser {
             }
         }
 
+        // Mixed-in modules are parents of the class/module that includes them
+        for (owner_line, module) in mixins {
+            if let Some(owner) = symbols.iter_mut().find(|s| {
+                s.line == owner_line && matches!(s.kind, SymbolKind::Class | SymbolKind::Package)
+            }) {
+                owner.parents.push((module, "includes".to_string()));
+            }
+        }
+
+        assign_tree_containers(&tree, content, &mut symbols, SCOPE_KINDS);
         Ok(symbols)
     }
 }
 
+/// Name node of the class or module whose body contains `node`
+fn enclosing_definition(node: tree_sitter::Node) -> Option<tree_sitter::Node> {
+    let mut current = node.parent();
+    while let Some(n) = current {
+        if matches!(n.kind(), "class" | "module") {
+            return n.child_by_field_name("name");
+        }
+        current = n.parent();
+    }
+    None
+}
+
 /// Check if a name is an ALL_CAPS constant
 fn is_constant_name(name: &str) -> bool {
     !name.is_empty()
@@ -434,6 +633,48 @@ mod tests {
         assert!(symbols.iter().any(|s| s.name == ":age" && s.kind == SymbolKind::Property));
     }
 
+    #[test]
+    fn test_attr_accessor_multiple_names() {
+        let content = "class User\n  attr_accessor :name, :email\n  attr_reader \"slug\"\nend\n";
+        let symbols = RUBY_PARSER.parse_symbols(content).unwrap();
+        let props: Vec<(&str, usize)> = symbols
+            .iter()
+            .filter(|s| s.kind == SymbolKind::Property)
+            .map(|s| (s.name.as_str(), s.line))
+            .collect();
+        assert_eq!(props, vec![(":name", 2), (":email", 2), (":slug", 3)]);
+    }
+
+    #[test]
+    fn test_mixins_are_parents() {
+        let content = "module Publishable\n  include Comparable\nend\n\nclass Post < ApplicationRecord\n  include Publishable\n  extend FriendlyId\nend\n";
+        let symbols = RUBY_PARSER.parse_symbols(content).unwrap();
+        let module = symbols.iter().find(|s| s.name == "Publishable" && s.kind == SymbolKind::Package).unwrap();
+        assert_eq!(module.parents, vec![("Comparable".to_string(), "includes".to_string())]);
+        let post = symbols.iter().find(|s| s.name == "Post").unwrap();
+        assert_eq!(
+            post.parents,
+            vec![
+                ("ApplicationRecord".to_string(), "extends".to_string()),
+                ("Publishable".to_string(), "includes".to_string()),
+                ("FriendlyId".to_string(), "includes".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rails_dsl_requires_rails_mode() {
+        let content = "class Post < ApplicationRecord\n  attr_reader :draft\n  has_many :comments\n  has_one_attached :cover\n  scope :active, -> { where(active: true) }\n  after_commit :reindex\nend\n";
+        let rails: Vec<String> = RUBY_PARSER.parse_symbols_with(content, true).unwrap().into_iter().map(|s| s.name).collect();
+        assert!(rails.contains(&"has_many :comments".to_string()));
+        assert!(rails.contains(&"has_one_attached :cover".to_string()));
+        assert!(rails.contains(&"scope :active".to_string()));
+        assert!(rails.contains(&"after_commit :reindex".to_string()));
+
+        let plain: Vec<String> = RUBY_PARSER.parse_symbols_with(content, false).unwrap().into_iter().map(|s| s.name).collect();
+        assert_eq!(plain, vec!["Post", ":draft"]);
+    }
+
     #[test]
     fn test_parse_constants() {
         let content = "class Config\n  LIMIT = 100\n  DEFAULT_ROLE = \"user\"\nend\n";
@@ -5,8 +5,9 @@ use tree_sitter::{Language, Query, QueryCursor, StreamingIterator};
 use std::sync::LazyLock;
 
 use crate::db::SymbolKind;
-use crate::parsers::ParsedSymbol;
-use super::{LanguageParser, parse_tree, node_text, node_line, line_text};
+use crate::parsers::references::{extract_references_for, RefLanguage};
+use crate::parsers::{ParsedRef, ParsedSymbol};
+use super::{LanguageParser, assign_tree_containers, parse_tree, node_text, node_line, line_text};
 
 static RUST_LANGUAGE: LazyLock<Language> = LazyLock::new(|| tree_sitter_rust::LANGUAGE.into());
 
@@ -15,6 +16,15 @@ static RUST_QUERY: LazyLock<Query> = LazyLock::new(|| {
         .expect("Failed to compile Rust tree-sitter query")
 });
 
+const SCOPE_KINDS: &[&str] = &[
+    "struct_item",
+    "enum_item",
+    "union_item",
+    "trait_item",
+    "impl_item",
+    "function_item",
+];
+
 pub static RUST_PARSER: RustParser = RustParser;
 
 pub struct RustParser;
@@ -80,6 +90,15 @@ impl LanguageParser for RustParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -94,6 +113,15 @@ impl LanguageParser for RustParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -108,6 +136,15 @@ impl LanguageParser for RustParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -127,7 +164,16 @@ impl LanguageParser for RustParser {
                         kind: SymbolKind::Class,
                         line: impl_line,
                         signature: line_text(content, impl_line).trim().to_string(),
-                        parents: vec![(trait_name.to_string(), "implements".to_string())],
+                        parents: vec![(trait_path_name(trait_name).to_string(), "implements".to_string())],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -146,6 +192,15 @@ impl LanguageParser for RustParser {
                     line: impl_line,
                     signature: line_text(content, impl_line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -160,6 +215,15 @@ impl LanguageParser for RustParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -174,6 +238,15 @@ impl LanguageParser for RustParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -188,6 +261,15 @@ impl LanguageParser for RustParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -202,6 +284,15 @@ impl LanguageParser for RustParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -217,6 +308,15 @@ impl LanguageParser for RustParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -233,6 +333,15 @@ impl LanguageParser for RustParser {
                         line,
                         signature: line_text(content, line).trim().to_string(),
                         parents: vec![],
+                        container: None,
+                        qualified_name: None,
+                        doc: None,
+                        annotations: vec![],
+                        visibility: None,
+                        end_line: None,
+                        byte_range: None,
+                        condition: None,
+                        generated_by: None,
                     });
                 }
                 continue;
@@ -248,6 +357,15 @@ impl LanguageParser for RustParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -262,6 +380,15 @@ impl LanguageParser for RustParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -276,6 +403,15 @@ impl LanguageParser for RustParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -301,6 +437,15 @@ impl LanguageParser for RustParser {
                                         line,
                                         signature: sig.clone(),
                                         parents: vec![],
+                                        container: None,
+                                        qualified_name: None,
+                                        doc: None,
+                                        annotations: vec![],
+                                        visibility: None,
+                                        end_line: None,
+                                        byte_range: None,
+                                        condition: None,
+                                        generated_by: None,
                                     });
                                 }
                             }
@@ -316,6 +461,15 @@ impl LanguageParser for RustParser {
                             line,
                             signature: sig,
                             parents: vec![],
+                            container: None,
+                            qualified_name: None,
+                            doc: None,
+                            annotations: vec![],
+                            visibility: None,
+                            end_line: None,
+                            byte_range: None,
+                            condition: None,
+                            generated_by: None,
                         });
                     }
                 }
@@ -323,8 +477,24 @@ impl LanguageParser for RustParser {
             }
         }
 
+        assign_tree_containers(&tree, content, &mut symbols, SCOPE_KINDS);
         Ok(symbols)
     }
+
+    /// snake_case calls and Rust call patterns on top of the CamelCase references
+    fn extract_refs(&self, content: &str, defined: &[ParsedSymbol]) -> Result<Vec<ParsedRef>> {
+        extract_references_for(content, defined, RefLanguage::Rust)
+    }
+
+    fn sets_containers(&self) -> bool {
+        true
+    }
+}
+
+/// Trait name as used for `implements` rows: `std::fmt::Display` -> `Display`, `From<T>` -> `From`
+fn trait_path_name(path: &str) -> &str {
+    let base = path.split('<').next().unwrap_or(path);
+    base.rsplit("::").next().unwrap_or(base).trim()
 }
 
 /// Find a capture by index in a match
@@ -368,6 +538,23 @@ mod tests {
         let symbols = RUST_PARSER.parse_symbols(content).unwrap();
         assert!(symbols.iter().any(|s| s.name == "impl Repository for SqlUserRepository"));
         assert!(symbols.iter().any(|s| s.name == "impl User"));
+
+        // Methods belong to the impl target
+        let (symbols, _) = crate::parsers::parse_file_symbols(content, crate::parsers::FileType::Rust).unwrap();
+        let find = symbols.iter().find(|s| s.name == "find").unwrap();
+        assert_eq!(find.container.as_deref(), Some("SqlUserRepository"));
+        let new = symbols.iter().find(|s| s.name == "new").unwrap();
+        assert_eq!(new.container.as_deref(), Some("User"));
+    }
+
+    #[test]
+    fn test_impl_trait_paths() {
+        let content = "impl std::fmt::Display for Config {\n}\nimpl<T: Clone> From<T> for Wrapper<T> {\n}\n";
+        let symbols = RUST_PARSER.parse_symbols(content).unwrap();
+        let display = symbols.iter().find(|s| s.name == "impl std::fmt::Display for Config").unwrap();
+        assert_eq!(display.parents, vec![("Display".to_string(), "implements".to_string())]);
+        let from = symbols.iter().find(|s| s.name.starts_with("impl From<T> for")).unwrap();
+        assert_eq!(from.parents, vec![("From".to_string(), "implements".to_string())]);
     }
 
     #[test]
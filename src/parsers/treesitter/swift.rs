@@ -6,7 +6,7 @@ use std::sync::LazyLock;
 
 use crate::db::SymbolKind;
 use crate::parsers::ParsedSymbol;
-use super::{LanguageParser, parse_tree, node_text, node_line, line_text};
+use super::{LanguageParser, assign_tree_containers, parse_tree, node_text, node_line, line_text};
 
 static SWIFT_LANGUAGE: LazyLock<Language> = LazyLock::new(|| tree_sitter_swift::LANGUAGE.into());
 
@@ -15,6 +15,13 @@ static SWIFT_QUERY: LazyLock<Query> = LazyLock::new(|| {
         .expect("Failed to compile Swift tree-sitter query")
 });
 
+const SCOPE_KINDS: &[&str] = &[
+    "class_declaration",
+    "protocol_declaration",
+    "function_declaration",
+    "init_declaration",
+];
+
 pub static SWIFT_PARSER: SwiftParser = SwiftParser;
 
 pub struct SwiftParser;
@@ -75,6 +82,15 @@ impl LanguageParser for SwiftParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents,
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -95,6 +111,15 @@ impl LanguageParser for SwiftParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents,
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -107,12 +132,31 @@ impl LanguageParser for SwiftParser {
                 let extended_name = format!("{}+Extension", base_name);
                 let line = node_line(&ext_cap.node);
 
+                // The extension points at the extended type; every declared conformance is `implements`
+                let mut parents = vec![(base_name.to_string(), "extends".to_string())];
+                if let Some(decl_node) = ext_cap.node.parent() {
+                    parents.extend(
+                        collect_parents_from_node(&decl_node, content)
+                            .into_iter()
+                            .map(|(name, _)| (name, "implements".to_string())),
+                    );
+                }
+
                 symbols.push(ParsedSymbol {
                     name: extended_name,
                     kind: SymbolKind::Object,
                     line,
                     signature: line_text(content, line).trim().to_string(),
-                    parents: vec![(base_name.to_string(), "extends".to_string())],
+                    parents,
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -133,6 +177,15 @@ impl LanguageParser for SwiftParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents,
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -147,6 +200,15 @@ impl LanguageParser for SwiftParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -160,6 +222,15 @@ impl LanguageParser for SwiftParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -174,6 +245,15 @@ impl LanguageParser for SwiftParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
@@ -188,13 +268,27 @@ impl LanguageParser for SwiftParser {
                     line,
                     signature: line_text(content, line).trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
                 continue;
             }
         }
 
+        assign_tree_containers(&tree, content, &mut symbols, SCOPE_KINDS);
         Ok(symbols)
     }
+
+    fn sets_containers(&self) -> bool {
+        true
+    }
 }
 
 /// Collect parent types by walking a declaration node's inheritance_specifier children.
@@ -305,6 +399,24 @@ mod tests {
         let ext = symbols.iter().find(|s| s.name == "String+Extension").unwrap();
         assert_eq!(ext.kind, SymbolKind::Object);
         assert!(ext.parents.iter().any(|(p, k)| p == "String" && k == "extends"));
+        assert!(ext.parents.iter().any(|(p, k)| p == "CustomProtocol" && k == "implements"));
+    }
+
+    #[test]
+    fn test_parse_extension_conformances() {
+        let content = "extension Array: Codable, Equatable where Element: Codable {\n}\nextension User {\n}\n";
+        let symbols = SWIFT_PARSER.parse_symbols(content).unwrap();
+        let array = symbols.iter().find(|s| s.name == "Array+Extension").unwrap();
+        assert_eq!(
+            array.parents,
+            vec![
+                ("Array".to_string(), "extends".to_string()),
+                ("Codable".to_string(), "implements".to_string()),
+                ("Equatable".to_string(), "implements".to_string()),
+            ]
+        );
+        let user = symbols.iter().find(|s| s.name == "User+Extension").unwrap();
+        assert_eq!(user.parents, vec![("User".to_string(), "extends".to_string())]);
     }
 
     #[test]
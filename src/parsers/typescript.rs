@@ -28,97 +28,97 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
 
     // Class definition: class ClassName extends/implements ...
     static CLASS_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*(?:export\s+)?(?:abstract\s+)?class\s+([A-Z][A-Za-z0-9_]*)\s*(?:<[^>]*>)?\s*(?:extends\s+([A-Z][A-Za-z0-9_.<>,\s]*))?(?:\s+implements\s+([A-Z][A-Za-z0-9_.<>,\s]*))?"
+        r"(?m)^[ \t]*(?:export\s+)?(?:abstract\s+)?class\s+(\p{Lu}\w*)\s*(?:<[^>]*>)?\s*(?:extends\s+(\p{Lu}[\w.<>,\s]*))?(?:\s+implements\s+(\p{Lu}[\w.<>,\s]*))?"
     ).unwrap());
     let class_re = &*CLASS_RE;
 
     // Interface definition: interface InterfaceName extends ...
     static INTERFACE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*(?:export\s+)?interface\s+([A-Z][A-Za-z0-9_]*)\s*(?:<[^>]*>)?\s*(?:extends\s+([A-Z][A-Za-z0-9_.<>,\s]*))?"
+        r"(?m)^[ \t]*(?:export\s+)?interface\s+(\p{Lu}\w*)\s*(?:<[^>]*>)?\s*(?:extends\s+(\p{Lu}[\w.<>,\s]*))?"
     ).unwrap());
     let interface_re = &*INTERFACE_RE;
 
     // Type alias: type TypeName = ...
     static TYPE_ALIAS_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*(?:export\s+)?type\s+([A-Z][A-Za-z0-9_]*)\s*(?:<[^>]*>)?\s*="
+        r"(?m)^[ \t]*(?:export\s+)?type\s+(\p{Lu}\w*)\s*(?:<[^>]*>)?\s*="
     ).unwrap());
     let type_alias_re = &*TYPE_ALIAS_RE;
 
     // Enum: enum EnumName { ... }
     static ENUM_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*(?:export\s+)?(?:const\s+)?enum\s+([A-Z][A-Za-z0-9_]*)"
+        r"(?m)^[ \t]*(?:export\s+)?(?:const\s+)?enum\s+(\p{Lu}\w*)"
     ).unwrap());
     let enum_re = &*ENUM_RE;
 
     // Regular function: function functionName(...) or export function
     static FUNC_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*(?:export\s+)?(?:async\s+)?function\s+([a-zA-Z_][a-zA-Z0-9_]*)\s*(?:<[^>]*>)?\s*\("
+        r"(?m)^[ \t]*(?:export\s+)?(?:async\s+)?function\s+([\p{L}_]\w*)\s*(?:<[^>]*>)?\s*\("
     ).unwrap());
     let func_re = &*FUNC_RE;
 
     // Arrow function as const: const functionName = (...) => or const functionName = async (...) =>
     static ARROW_FUNC_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*(?:export\s+)?(?:const|let)\s+([a-zA-Z_][a-zA-Z0-9_]*)\s*(?::\s*[^=]+)?\s*=\s*(?:async\s+)?\([^)]*\)\s*(?::\s*[^=]+)?\s*=>"
+        r"(?m)^[ \t]*(?:export\s+)?(?:const|let)\s+([\p{L}_]\w*)\s*(?::\s*[^=]+)?\s*=\s*(?:async\s+)?\([^)]*\)\s*(?::\s*[^=]+)?\s*=>"
     ).unwrap());
     let arrow_func_re = &*ARROW_FUNC_RE;
 
     // Arrow function without parens: const fn = x =>
     static ARROW_FUNC_SIMPLE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*(?:export\s+)?(?:const|let)\s+([a-zA-Z_][a-zA-Z0-9_]*)\s*=\s*(?:async\s+)?[a-zA-Z_][a-zA-Z0-9_]*\s*=>"
+        r"(?m)^[ \t]*(?:export\s+)?(?:const|let)\s+([\p{L}_]\w*)\s*=\s*(?:async\s+)?[\p{L}_]\w*\s*=>"
     ).unwrap());
     let arrow_func_simple_re = &*ARROW_FUNC_SIMPLE_RE;
 
     // React functional component as arrow function: const ComponentName = (props) => {
     static REACT_ARROW_COMPONENT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*(?:export\s+)?const\s+([A-Z][A-Za-z0-9_]*)\s*(?::\s*(?:React\.)?FC[^=]*)?\s*=\s*(?:\([^)]*\)|[a-zA-Z_][a-zA-Z0-9_]*)\s*(?::\s*[^=]+)?\s*=>"
+        r"(?m)^[ \t]*(?:export\s+)?const\s+(\p{Lu}\w*)\s*(?::\s*(?:React\.)?FC[^=]*)?\s*=\s*(?:\([^)]*\)|[\p{L}_]\w*)\s*(?::\s*[^=]+)?\s*=>"
     ).unwrap());
     let react_arrow_component_re = &*REACT_ARROW_COMPONENT_RE;
 
     // React functional component as function: function ComponentName(props) {
     static REACT_FUNC_COMPONENT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*(?:export\s+)?function\s+([A-Z][A-Za-z0-9_]*)\s*\("
+        r"(?m)^[ \t]*(?:export\s+)?function\s+(\p{Lu}\w*)\s*\("
     ).unwrap());
     let react_func_component_re = &*REACT_FUNC_COMPONENT_RE;
 
     // React hooks: const [state, setState] = useState(...) or custom hooks: function useXxx()
     static HOOK_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*(?:export\s+)?(?:const|function)\s+(use[A-Z][a-zA-Z0-9_]*)"
+        r"(?m)^[ \t]*(?:export\s+)?(?:const|function)\s+(use\p{Lu}\w*)"
     ).unwrap());
     let hook_re = &*HOOK_RE;
 
     // Decorator: @DecoratorName or @DecoratorName(...)
     static DECORATOR_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*@([A-Z][a-zA-Z0-9_]*)\s*(?:\([^)]*\))?"
+        r"(?m)^[ \t]*@(\p{Lu}\w*)\s*(?:\([^)]*\))?"
     ).unwrap());
     let decorator_re = &*DECORATOR_RE;
 
     // Import: import { X } from 'module' or import X from 'module'
     static IMPORT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r#"(?m)^[ \t]*import\s+(?:\{[^}]*\}|\*\s+as\s+[a-zA-Z_][a-zA-Z0-9_]*|[a-zA-Z_][a-zA-Z0-9_]*)\s+from\s+['"]([^'"]+)['"]"#
+        r#"(?m)^[ \t]*import\s+(?:\{[^}]*\}|\*\s+as\s+[\p{L}_]\w*|[\p{L}_]\w*)\s+from\s+['"]([^'"]+)['"]"#
     ).unwrap());
     let import_re = &*IMPORT_RE;
 
     // Module-level const (UPPER_CASE): const API_URL = ...
     static CONST_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^(?:export\s+)?const\s+([A-Z][A-Z0-9_]+)\s*(?::\s*[^=]+)?\s*="
+        r"(?m)^(?:export\s+)?const\s+(\p{Lu}[\p{Lu}\p{N}_]+)\s*(?::\s*[^=]+)?\s*="
     ).unwrap());
     let const_re = &*CONST_RE;
 
     // Namespace: namespace NamespaceName { ... }
     static NAMESPACE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*(?:export\s+)?(?:declare\s+)?namespace\s+([A-Z][A-Za-z0-9_]*)"
+        r"(?m)^[ \t]*(?:export\s+)?(?:declare\s+)?namespace\s+(\p{Lu}\w*)"
     ).unwrap());
     let namespace_re = &*NAMESPACE_RE;
 
-    // Vue defineComponent: export default defineComponent({ name: 'ComponentName' })
+    // Vue component name: defineComponent({ name: 'ComponentName' }) or defineOptions({ name: 'ComponentName' })
     static VUE_COMPONENT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r#"(?m)defineComponent\s*\(\s*\{[^}]*name\s*:\s*['"]([A-Z][A-Za-z0-9_]*)['"]"#
+        r#"(?m)(?:defineComponent|defineOptions)\s*\(\s*\{[^}]*name\s*:\s*['"](\p{Lu}\w*)['"]"#
     ).unwrap());
     let vue_component_re = &*VUE_COMPONENT_RE;
 
     // Svelte: export let propName (props)
     static SVELTE_PROP_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
-        r"(?m)^[ \t]*export\s+let\s+([a-zA-Z_][a-zA-Z0-9_]*)"
+        r"(?m)^[ \t]*export\s+let\s+([\p{L}_]\w*)"
     ).unwrap());
     let svelte_prop_re = &*SVELTE_PROP_RE;
 
@@ -156,6 +156,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line,
             signature: line_text.trim().to_string(),
             parents,
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -183,6 +192,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line,
             signature: line_text.trim().to_string(),
             parents,
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -199,6 +217,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line,
             signature: line_text.trim().to_string(),
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -215,6 +242,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line,
             signature: line_text.trim().to_string(),
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -241,6 +277,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line,
             signature: line_text.trim().to_string(),
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -270,6 +315,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                 line,
                 signature: line_text.trim().to_string(),
                 parents: vec![],
+                container: None,
+                qualified_name: None,
+                doc: None,
+                annotations: vec![],
+                visibility: None,
+                end_line: None,
+                byte_range: None,
+                condition: None,
+                generated_by: None,
             });
         }
     }
@@ -289,6 +343,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                     line,
                     signature: line_text.trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
         }
@@ -312,6 +375,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line,
             signature: line_text.trim().to_string(),
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -338,6 +410,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line,
             signature: line_text.trim().to_string(),
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -354,6 +435,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line,
             signature: line_text.trim().to_string(),
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -379,6 +469,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                 line,
                 signature: line_text.trim().to_string(),
                 parents: vec![],
+                container: None,
+                qualified_name: None,
+                doc: None,
+                annotations: vec![],
+                visibility: None,
+                end_line: None,
+                byte_range: None,
+                condition: None,
+                generated_by: None,
             });
         }
     }
@@ -401,6 +500,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line,
             signature: line_text.trim().to_string(),
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -424,6 +532,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                 line,
                 signature: line_text.trim().to_string(),
                 parents: vec![],
+                container: None,
+                qualified_name: None,
+                doc: None,
+                annotations: vec![],
+                visibility: None,
+                end_line: None,
+                byte_range: None,
+                condition: None,
+                generated_by: None,
             });
         }
     }
@@ -441,6 +558,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line,
             signature: line_text.trim().to_string(),
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -457,6 +583,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line,
             signature: line_text.trim().to_string(),
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -473,6 +608,15 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line,
             signature: line_text.trim().to_string(),
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -483,16 +627,221 @@ pub fn parse_typescript_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
     Ok(symbols)
 }
 
-/// Extract script content from Vue SFC
+/// Extract script content from Vue SFC.
+/// Markup outside `<script>` blocks is dropped but its line breaks are kept,
+/// so line numbers in the result match the `.vue` file.
 pub fn extract_vue_script(content: &str) -> String {
     static SCRIPT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<script[^>]*>(.*?)</script>").unwrap());
     let script_re = &*SCRIPT_RE;
 
-    script_re.captures_iter(content)
-        .filter_map(|cap| cap.get(1))
-        .map(|m| m.as_str())
-        .collect::<Vec<_>>()
-        .join("\n")
+    let mut script = String::new();
+    let mut last_end = 0;
+    for body in script_re.captures_iter(content).filter_map(|cap| cap.get(1)) {
+        match content[last_end..body.start()].matches('\n').count() {
+            0 if !script.is_empty() => script.push(' '),
+            n => script.push_str(&"\n".repeat(n)),
+        }
+        script.push_str(body.as_str());
+        last_end = body.end();
+    }
+    script
+}
+
+/// Parse the `<script setup>` compiler macros of a Vue SFC:
+/// keys of `defineProps` and `defineEmits` become properties.
+/// Handles runtime declarations (`defineProps({ ... })`, `defineEmits(['change'])`),
+/// type literals (`defineProps<{ ... }>()`) and interfaces named in the type argument.
+pub fn parse_vue_macros(script: &str) -> Vec<ParsedSymbol> {
+    static MACRO_RE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\b(?:defineProps|defineEmits)\s*(<\s*([A-Za-z_$][\w$]*)?|\(\s*)").unwrap());
+    static STRING_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"'([^'\n]+)'|"([^"\n]+)""#).unwrap());
+
+    let lines: Vec<&str> = script.lines().collect();
+    let mut keys: Vec<(usize, String)> = Vec::new();
+    for cap in MACRO_RE.captures_iter(script) {
+        let open = cap.get(0).unwrap().end();
+        let body = if let Some(type_name) = cap.get(2) {
+            // defineProps<Props>(): the keys live in `interface Props` / `type Props = { ... }`
+            let decl = Regex::new(&format!(
+                r"\b(?:interface\s+{0}\b[^{{]*|type\s+{0}\s*=\s*)\{{",
+                regex::escape(type_name.as_str())
+            ))
+            .ok()
+            .and_then(|re| re.find(script));
+            decl.and_then(|m| balanced_body(script, m.end() - 1))
+        } else {
+            balanced_body(script, open)
+        };
+        let Some((start, end)) = body else { continue };
+        if script.as_bytes()[start - 1] == b'[' {
+            // defineEmits(['change', 'update:modelValue'])
+            for s in STRING_RE.captures_iter(&script[start..end]) {
+                let m = s.get(1).or_else(|| s.get(2)).unwrap();
+                keys.push((start + m.start(), m.as_str().to_string()));
+            }
+        } else {
+            keys.extend(member_keys(script, start, end));
+        }
+    }
+
+    let mut symbols = Vec::new();
+    for (offset, name) in keys {
+        let line = find_line_number(script, offset);
+        if symbols.iter().any(|s: &ParsedSymbol| s.name == name && s.line == line) {
+            continue;
+        }
+        symbols.push(ParsedSymbol {
+            name,
+            kind: SymbolKind::Property,
+            line,
+            signature: lines.get(line - 1).unwrap_or(&"").trim().to_string(),
+            parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
+        });
+    }
+    symbols
+}
+
+/// Inner byte range of the `{...}` or `[...]` group opening at `open`, skipping string literals
+fn balanced_body(s: &str, open: usize) -> Option<(usize, usize)> {
+    let bytes = s.as_bytes();
+    if !matches!(bytes.get(open), Some(b'{' | b'[')) {
+        return None;
+    }
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'[' | b'(' => depth += 1,
+            b'}' | b']' | b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((open + 1, i));
+                }
+            }
+            q @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != q {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Keys of the object literal or type literal body `s[start..end]` as (byte offset, name).
+/// Emit call signatures `(e: 'change', id: number): void` yield the event name.
+fn member_keys(s: &str, start: usize, end: usize) -> Vec<(usize, String)> {
+    static KEY_RE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"^(?:readonly\s+)?(?:'([^']+)'|"([^"]+)"|([A-Za-z_$][\w$]*))\s*\??\s*[:(]"#).unwrap()
+    });
+    static EVENT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"^\(\s*\w+\s*:\s*(?:'([^']+)'|"([^"]+)")"#).unwrap());
+
+    let bytes = s.as_bytes();
+    let mut keys = Vec::new();
+    let mut member_start = start;
+    let mut depth = 0usize;
+    let mut i = start;
+    while i <= end {
+        let c = if i < end { bytes[i] } else { b',' };
+        match c {
+            b'{' | b'[' | b'(' => depth += 1,
+            b'}' | b']' | b')' => depth = depth.saturating_sub(1),
+            q @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < end && bytes[i] != q {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b',' | b';' | b'\n' if depth == 0 => {
+                let member = &s[member_start..i.min(end)];
+                let offset = member_start + (member.len() - member.trim_start().len());
+                let member = member.trim_start();
+                if let Some(cap) = KEY_RE.captures(member).or_else(|| EVENT_RE.captures(member)) {
+                    let m = cap.get(1).or_else(|| cap.get(2)).or_else(|| cap.get(3)).unwrap();
+                    keys.push((offset + m.start(), m.as_str().to_string()));
+                }
+                member_start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    keys
+}
+
+/// Name the component of a Vue SFC and attach the props/emits found by `parse_vue_macros` to it.
+/// The name comes from `defineComponent`/`defineOptions`, else from the file path:
+/// `user-card.vue` -> `UserCard`, `UserCard/index.vue` -> `UserCard`.
+pub fn assign_vue_component(symbols: &mut Vec<ParsedSymbol>, content: &str, rel_path: &str) {
+    let declared = symbols.iter().find(|s| {
+        s.kind == SymbolKind::Class
+            && (s.signature.contains("defineComponent") || s.signature.contains("defineOptions"))
+    });
+    let name = match declared {
+        Some(component) => component.name.clone(),
+        None => {
+            let Some(name) = component_name_from_path(rel_path) else { return };
+            let line = content
+                .lines()
+                .position(|l| {
+                    let l = l.trim_start();
+                    l.starts_with("<template") || l.starts_with("<script")
+                })
+                .map_or(1, |i| i + 1);
+            symbols.push(ParsedSymbol {
+                name: name.clone(),
+                kind: SymbolKind::Class,
+                line,
+                signature: content.lines().nth(line - 1).unwrap_or("").trim().to_string(),
+                parents: vec![],
+                container: None,
+                qualified_name: None,
+                doc: None,
+                annotations: vec![],
+                visibility: None,
+                end_line: Some(content.lines().count().max(line)),
+                byte_range: None,
+                condition: None,
+                generated_by: None,
+            });
+            name
+        }
+    };
+    for symbol in symbols.iter_mut() {
+        if symbol.kind == SymbolKind::Property && symbol.container.is_none() {
+            symbol.container = Some(name.clone());
+        }
+    }
+}
+
+/// PascalCase component name from a `.vue` file path
+fn component_name_from_path(rel_path: &str) -> Option<String> {
+    let path = std::path::Path::new(rel_path);
+    let mut stem = path.file_stem()?.to_str()?;
+    if stem == "index" {
+        stem = path.parent()?.file_name()?.to_str()?;
+    }
+    let name: String = stem
+        .split(['-', '_', '.'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect();
+    if name.is_empty() { None } else { Some(name) }
 }
 
 /// Extract script content from Svelte component
@@ -702,4 +1051,62 @@ div { color: red; }
         assert!(script.contains("import { ref } from 'vue'"));
         assert!(script.contains("const message = ref"));
     }
+
+    #[test]
+    fn test_extract_vue_script_keeps_line_numbers() {
+        let content = "<template>\n  <div/>\n</template>\n<script setup>\nconst a = 1\n</script>\n";
+        let script = extract_vue_script(content);
+        assert_eq!(script.lines().nth(4), Some("const a = 1"));
+    }
+
+    #[test]
+    fn test_vue_define_props_and_emits() {
+        let script = r#"
+const props = defineProps<{
+  id: number
+  title: string; done?: boolean
+  onHover: (e: MouseEvent) => void
+}>()
+const emit = defineEmits<{
+  (e: 'toggle', id: number): void
+}>()
+defineEmits(['select', 'update:modelValue'])
+"#;
+        let symbols = parse_vue_macros(script);
+        let names: Vec<(&str, usize)> = symbols.iter().map(|s| (s.name.as_str(), s.line)).collect();
+        assert_eq!(
+            names,
+            vec![("id", 3), ("title", 4), ("done", 4), ("onHover", 5), ("toggle", 8), ("select", 10), ("update:modelValue", 10)]
+        );
+        assert!(symbols.iter().all(|s| s.kind == SymbolKind::Property));
+
+        let runtime = "defineProps({\n  label: String,\n  size: { type: String, default: 'md' },\n})\n";
+        let names: Vec<String> = parse_vue_macros(runtime).into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["label", "size"]);
+
+        let interface = "interface Props {\n  user: User\n}\nconst p = withDefaults(defineProps<Props>(), {})\n";
+        let names: Vec<String> = parse_vue_macros(interface).into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["user"]);
+    }
+
+    #[test]
+    fn test_vue_component_name() {
+        let content = "<template><li/></template>\n<script setup>\ndefineProps(['title'])\n</script>\n";
+        let mut symbols = parse_vue_macros(&extract_vue_script(content));
+        assign_vue_component(&mut symbols, content, "src/components/todo-item.vue");
+        let component = symbols.iter().find(|s| s.kind == SymbolKind::Class).expect("component symbol");
+        assert_eq!(component.name, "TodoItem");
+        assert_eq!(component.end_line, Some(4));
+        let title = symbols.iter().find(|s| s.name == "title").unwrap();
+        assert_eq!(title.container.as_deref(), Some("TodoItem"));
+
+        assert_eq!(component_name_from_path("src/UserCard/index.vue").as_deref(), Some("UserCard"));
+
+        let script = "defineOptions({ name: 'FancyButton' })\n";
+        let mut symbols = parse_typescript_symbols(script).unwrap();
+        assign_vue_component(&mut symbols, script, "src/Named.vue");
+        let classes: Vec<&str> =
+            symbols.iter().filter(|s| s.kind == SymbolKind::Class).map(|s| s.name.as_str()).collect();
+        assert_eq!(classes, vec!["FancyButton"]);
+    }
 }
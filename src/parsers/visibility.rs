@@ -0,0 +1,249 @@
+//! Symbol visibility
+//!
+//! Derives `public`/`protected`/`internal`/`private` (plus Java `package`, Rust `crate`
+//! and Swift `fileprivate`) from modifiers in front of the declaration, language
+//! defaults, naming conventions (Go capitalization, `_name` in Python/Dart/Perl) and
+//! access sections (C++ `private:`, Ruby `private`).
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::db::SymbolKind;
+use super::annotations::declaration_prefix;
+use super::{FileType, ParsedSymbol};
+
+static RUST_PUB_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\bpub\b\s*(\()?").unwrap());
+
+/// Set `visibility` on every declaration of a language with known visibility rules,
+/// unless the parser already did.
+pub fn assign_visibility(symbols: &mut [ParsedSymbol], content: &str, ext: &str) {
+    let Some(file_type) = FileType::from_extension(ext) else { return };
+    let lines: Vec<&str> = content.lines().collect();
+
+    for symbol in symbols.iter_mut() {
+        // Parsers that know the rule from the syntax tree have set it already
+        if symbol.visibility.is_some() || matches!(symbol.kind, SymbolKind::Import | SymbolKind::Package | SymbolKind::Annotation) {
+            continue;
+        }
+        let prefix = declaration_prefix(&lines, symbol.line, &symbol.name);
+        symbol.visibility = visibility(file_type, symbol, &prefix, &lines).map(str::to_string);
+    }
+}
+
+/// Modifier words of the declaration itself: `class Foo(private val x` -> `private val`
+fn modifiers(prefix: &str) -> Vec<&str> {
+    let own = prefix.rsplit(['(', ',', '{', ';']).next().unwrap_or(prefix);
+    own.split(|c: char| !(c.is_alphanumeric() || c == '_')).filter(|w| !w.is_empty()).collect()
+}
+
+fn visibility(file_type: FileType, symbol: &ParsedSymbol, prefix: &str, lines: &[&str]) -> Option<&'static str> {
+    let words = modifiers(prefix);
+    let has = |w: &str| words.contains(&w);
+    let first_of = |options: &[&'static str]| options.iter().copied().find(|w| has(w));
+    let underscore = |name: &str| if name.starts_with('_') { "private" } else { "public" };
+
+    Some(match file_type {
+        FileType::Kotlin | FileType::Scala => first_of(&["private", "protected", "internal"]).unwrap_or("public"),
+        FileType::Java => first_of(&["public", "private", "protected"]).unwrap_or("package"),
+        FileType::Swift => match first_of(&["open", "public", "fileprivate", "private", "internal"]) {
+            Some("open") | Some("public") => "public",
+            Some(v) => v,
+            None => "internal",
+        },
+        FileType::CSharp => match first_of(&["private", "protected", "internal", "public"]) {
+            Some(v) => v,
+            // Members default to private, top-level types to internal
+            None if symbol.container.is_some() => "private",
+            None => "internal",
+        },
+        FileType::Rust => match RUST_PUB_RE.captures(prefix) {
+            Some(c) if c.get(1).is_some() => "crate",
+            Some(_) => "public",
+            None => "private",
+        },
+        FileType::Go => {
+            if symbol.name.chars().next().is_some_and(char::is_uppercase) { "public" } else { "private" }
+        }
+        FileType::Python => {
+            let dunder = symbol.name.starts_with("__") && symbol.name.ends_with("__");
+            if dunder { "public" } else { underscore(&symbol.name) }
+        }
+        FileType::Dart | FileType::Perl => underscore(&symbol.name),
+        FileType::TypeScript | FileType::Vue | FileType::Svelte => {
+            if symbol.name.starts_with('#') {
+                "private"
+            } else if let Some(v) = first_of(&["private", "protected"]) {
+                v
+            } else if has("export") || symbol.container.is_some() {
+                "public"
+            } else {
+                // Not exported: module-local
+                "private"
+            }
+        }
+        FileType::Cpp => {
+            if symbol.container.is_none() {
+                if has("static") { "private" } else { "public" }
+            } else {
+                cpp_access(lines, symbol.line)?
+            }
+        }
+        FileType::Ruby => first_of(&["private", "protected"]).unwrap_or_else(|| ruby_access(lines, symbol.line)),
+        FileType::Proto => "public",
+        FileType::ObjC | FileType::Wsdl => return None,
+    })
+}
+
+/// Nearest `public:`/`protected:`/`private:` label above a member, or the class/struct default
+fn cpp_access(lines: &[&str], line: usize) -> Option<&'static str> {
+    for text in lines.get(..line.checked_sub(1)?)?.iter().rev() {
+        let t = text.trim();
+        for label in ["public", "protected", "private"] {
+            if t.strip_prefix(label).is_some_and(|rest| rest.trim_start().starts_with(':') && !rest.trim_start().starts_with("::")) {
+                return Some(label);
+            }
+        }
+        if t.starts_with("class ") || t.contains(" class ") {
+            return Some("private");
+        }
+        if t.starts_with("struct ") || t.contains(" struct ") {
+            return Some("public");
+        }
+    }
+    None
+}
+
+/// Visibility set by a bare `private`/`protected`/`public` line earlier in the same class body
+fn ruby_access(lines: &[&str], line: usize) -> &'static str {
+    let Some(decl) = line.checked_sub(1).and_then(|i| lines.get(i)) else { return "public" };
+    let indent = decl.len() - decl.trim_start().len();
+    for text in lines[..line - 1].iter().rev() {
+        let t = text.trim();
+        if t.is_empty() {
+            continue;
+        }
+        let text_indent = text.len() - text.trim_start().len();
+        if text_indent < indent {
+            break;
+        }
+        if text_indent == indent {
+            match t {
+                "private" => return "private",
+                "protected" => return "protected",
+                "public" => return "public",
+                _ => {}
+            }
+        }
+    }
+    "public"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sym(name: &str, kind: SymbolKind, line: usize, container: Option<&str>) -> ParsedSymbol {
+        ParsedSymbol {
+            name: name.to_string(),
+            kind,
+            line,
+            signature: String::new(),
+            parents: vec![],
+            container: container.map(str::to_string),
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
+        }
+    }
+
+    fn visibilities(symbols: &[ParsedSymbol]) -> Vec<Option<&str>> {
+        symbols.iter().map(|s| s.visibility.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_kotlin_and_java_modifiers() {
+        let kt = "class Repo(private val api: Api) {\n    internal fun load() {}\n    fun save() {}\n}\n";
+        let mut symbols = vec![
+            sym("Repo", SymbolKind::Class, 1, None),
+            sym("api", SymbolKind::Property, 1, Some("Repo")),
+            sym("load", SymbolKind::Function, 2, Some("Repo")),
+            sym("save", SymbolKind::Function, 3, Some("Repo")),
+        ];
+        assign_visibility(&mut symbols, kt, "kt");
+        assert_eq!(visibilities(&symbols), vec![Some("public"), Some("private"), Some("internal"), Some("public")]);
+
+        let java = "public class A {\n    void helper() {}\n    @Override\n    protected void run() {}\n}\n";
+        let mut symbols = vec![
+            sym("A", SymbolKind::Class, 1, None),
+            sym("helper", SymbolKind::Function, 2, Some("A")),
+            sym("run", SymbolKind::Function, 3, Some("A")),
+        ];
+        assign_visibility(&mut symbols, java, "java");
+        assert_eq!(visibilities(&symbols), vec![Some("public"), Some("package"), Some("protected")]);
+    }
+
+    #[test]
+    fn test_rust_pub() {
+        let content = "pub struct A;\npub(crate) fn b() {}\nfn c() {}\n";
+        let mut symbols = vec![
+            sym("A", SymbolKind::Class, 1, None),
+            sym("b", SymbolKind::Function, 2, None),
+            sym("c", SymbolKind::Function, 3, None),
+        ];
+        assign_visibility(&mut symbols, content, "rs");
+        assert_eq!(visibilities(&symbols), vec![Some("public"), Some("crate"), Some("private")]);
+    }
+
+    #[test]
+    fn test_naming_conventions() {
+        let mut symbols = vec![sym("Serve", SymbolKind::Function, 1, None), sym("parse", SymbolKind::Function, 2, None)];
+        assign_visibility(&mut symbols, "func Serve() {}\nfunc parse() {}\n", "go");
+        assert_eq!(visibilities(&symbols), vec![Some("public"), Some("private")]);
+
+        let mut symbols = vec![
+            sym("_cache", SymbolKind::Function, 1, None),
+            sym("__init__", SymbolKind::Function, 2, None),
+        ];
+        assign_visibility(&mut symbols, "def _cache(): pass\ndef __init__(self): pass\n", "py");
+        assert_eq!(visibilities(&symbols), vec![Some("private"), Some("public")]);
+    }
+
+    #[test]
+    fn test_access_sections() {
+        let cpp = "class Socket {\n    void reset();\npublic:\n    void send();\n};\n";
+        let mut symbols = vec![
+            sym("reset", SymbolKind::Function, 2, Some("Socket")),
+            sym("send", SymbolKind::Function, 4, Some("Socket")),
+        ];
+        assign_visibility(&mut symbols, cpp, "cpp");
+        assert_eq!(visibilities(&symbols), vec![Some("private"), Some("public")]);
+
+        let rb = "class User\n  def name; end\n\n  private\n\n  def token; end\nend\n";
+        let mut symbols = vec![
+            sym("name", SymbolKind::Function, 2, Some("User")),
+            sym("token", SymbolKind::Function, 6, Some("User")),
+        ];
+        assign_visibility(&mut symbols, rb, "rb");
+        assert_eq!(visibilities(&symbols), vec![Some("public"), Some("private")]);
+    }
+
+    #[test]
+    fn test_typescript_exports() {
+        let content = "export class Api {\n  private token: string;\n  get() {}\n}\nfunction helper() {}\n";
+        let mut symbols = vec![
+            sym("Api", SymbolKind::Class, 1, None),
+            sym("token", SymbolKind::Property, 2, Some("Api")),
+            sym("get", SymbolKind::Function, 3, Some("Api")),
+            sym("helper", SymbolKind::Function, 5, None),
+        ];
+        assign_visibility(&mut symbols, content, "ts");
+        assert_eq!(visibilities(&symbols), vec![Some("public"), Some("private"), Some("public"), Some("private")]);
+    }
+}
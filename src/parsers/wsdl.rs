@@ -172,6 +172,15 @@ pub fn parse_wsdl_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                     line: line_num,
                     signature: line.trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
         }
@@ -191,6 +200,15 @@ pub fn parse_wsdl_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                     line: line_num,
                     signature: line.trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
         }
@@ -205,6 +223,15 @@ pub fn parse_wsdl_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                     line: line_num,
                     signature: line.trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
         }
@@ -219,6 +246,15 @@ pub fn parse_wsdl_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                     line: line_num,
                     signature: line.trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
         }
@@ -233,6 +269,15 @@ pub fn parse_wsdl_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                     line: line_num,
                     signature: line.trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
         }
@@ -247,6 +292,15 @@ pub fn parse_wsdl_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
                     line: line_num,
                     signature: line.trim().to_string(),
                     parents: vec![],
+                    container: None,
+                    qualified_name: None,
+                    doc: None,
+                    annotations: vec![],
+                    visibility: None,
+                    end_line: None,
+                    byte_range: None,
+                    condition: None,
+                    generated_by: None,
                 });
             }
         }
@@ -267,6 +321,15 @@ pub fn parse_wsdl_symbols(content: &str) -> Result<Vec<ParsedSymbol>> {
             line: 1,
             signature: format!("targetNamespace=\"{}\"", namespace),
             parents: vec![],
+            container: None,
+            qualified_name: None,
+            doc: None,
+            annotations: vec![],
+            visibility: None,
+            end_line: None,
+            byte_range: None,
+            condition: None,
+            generated_by: None,
         });
     }
 
@@ -0,0 +1,133 @@
+//! Named index profiles
+//!
+//! A registry in the user's config directory (`~/.config/ast-index/profiles.toml`, or the file
+//! `AST_INDEX_PROFILES` names) maps profile names to a project root and optionally an index file,
+//! so `ast-index --index work search Foo` queries that project from any directory:
+//!
+//! ```toml
+//! [profiles.work]
+//! root = "/home/me/src/app"
+//!
+//! [profiles.oss]
+//! root = "/home/me/oss/kotlinx"
+//! db = "/data/indexes/kotlinx.db"
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// A named index: the project it covers and, if not the default cache location, its index file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub root: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db: Option<PathBuf>,
+}
+
+/// Profiles by name
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Registry {
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Registry {
+    /// Location of the registry file
+    pub fn path() -> Result<PathBuf> {
+        if let Ok(path) = std::env::var("AST_INDEX_PROFILES") {
+            return Ok(PathBuf::from(path));
+        }
+        Ok(dirs::config_dir()
+            .context("Could not find config directory")?
+            .join("ast-index")
+            .join("profiles.toml"))
+    }
+
+    /// The user's registry; empty if there is no file yet
+    pub fn load() -> Result<Registry> {
+        Registry::load_from(&Registry::path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Registry> {
+        if !path.exists() {
+            return Ok(Registry::default());
+        }
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Registry::path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, toml::to_string(self)?).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// The profile called `name`, listing the known ones if there is none
+    pub fn get(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).with_context(|| {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                format!("Unknown index profile '{}'; add one with 'ast-index profile-add {} --root <dir>'", name, name)
+            } else {
+                format!("Unknown index profile '{}' (known: {})", name, known.join(", "))
+            }
+        })
+    }
+
+    /// Add or replace a profile; names are letters, digits, `-` and `_`
+    pub fn add(&mut self, name: &str, profile: Profile) -> Result<()> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            anyhow::bail!("Invalid profile name '{}': use letters, digits, '-' and '_'", name);
+        }
+        self.profiles.insert(name.to_string(), profile);
+        Ok(())
+    }
+
+    /// Remove a profile; returns whether it existed
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.profiles.remove(name).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("profiles.toml");
+        let mut registry = Registry::load_from(&path).unwrap();
+        assert!(registry.profiles.is_empty());
+
+        registry.add("work", Profile { root: PathBuf::from("/src/app"), db: None }).unwrap();
+        registry
+            .add("oss", Profile { root: PathBuf::from("/oss/lib"), db: Some(PathBuf::from("/data/lib.db")) })
+            .unwrap();
+        assert!(registry.add("my work", Profile { root: PathBuf::from("/x"), db: None }).is_err());
+        registry.save_to(&path).unwrap();
+
+        let registry = Registry::load_from(&path).unwrap();
+        assert_eq!(registry.get("work").unwrap().root, PathBuf::from("/src/app"));
+        assert_eq!(registry.get("oss").unwrap().db, Some(PathBuf::from("/data/lib.db")));
+        let err = registry.get("home").unwrap_err().to_string();
+        assert!(err.contains("known: oss, work"), "{}", err);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut registry = Registry::default();
+        registry.add("work", Profile { root: PathBuf::from("/src/app"), db: None }).unwrap();
+        assert!(registry.remove("work"));
+        assert!(!registry.remove("work"));
+    }
+}
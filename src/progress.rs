@@ -0,0 +1,143 @@
+//! Progress and timing reports for indexing
+//!
+//! - `ParseProgress`: an indicatif bar of files parsed out of the total with an ETA and the file
+//!   just parsed on an interactive stderr, a plain line every so many files otherwise
+//! - Parser timings: time spent per language, collected once `rebuild --timings` turns them on
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Parse progress shared by the parsing threads
+pub struct ParseProgress {
+    bar: ProgressBar,
+    /// What is counted: "files", "changed files"
+    label: &'static str,
+    /// Files between plain lines when stderr is not a terminal
+    plain_every: usize,
+    /// Enabled, but stderr is not a terminal: plain lines instead of the bar
+    plain: bool,
+    done: AtomicUsize,
+}
+
+impl ParseProgress {
+    /// Progress over `total` files; prints nothing unless `enabled`
+    pub fn new(total: usize, label: &'static str, plain_every: usize, enabled: bool) -> Self {
+        let bar = if enabled { ProgressBar::new(total as u64) } else { ProgressBar::hidden() };
+        let template = format!("[{{bar:24}}] {{pos}}/{{len}} {} {{percent:>3}}% ETA {{eta}}  {{wide_msg:.dim}}", label);
+        if let Ok(style) = ProgressStyle::with_template(&template) {
+            bar.set_style(style.progress_chars("= "));
+        }
+        ParseProgress { plain: enabled && bar.is_hidden(), bar, label, plain_every: plain_every.max(1), done: AtomicUsize::new(0) }
+    }
+
+    /// Count `path` as parsed
+    pub fn tick(&self, path: &str) {
+        if self.plain {
+            let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+            if done.is_multiple_of(self.plain_every) {
+                eprintln!("Parsed {} / {} {}...", done, self.bar.length().unwrap_or_default(), self.label);
+            }
+            return;
+        }
+        self.bar.set_message(path.to_string());
+        self.bar.inc(1);
+    }
+
+    /// A status line between batches; the bar already shows it on a terminal
+    pub fn note(&self, message: &str) {
+        if self.plain {
+            eprintln!("{}", message);
+        }
+    }
+
+    /// Erase the bar before the summary prints
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+static TIMINGS_ENABLED: AtomicBool = AtomicBool::new(false);
+static TIMINGS: LazyLock<Mutex<HashMap<&'static str, LanguageTiming>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Parse time of one language, summed over the parsing threads
+#[derive(Debug, Clone, Default)]
+pub struct LanguageTiming {
+    pub files: usize,
+    pub total: Duration,
+    /// The file that took longest, and how long
+    pub slowest: Option<(String, Duration)>,
+}
+
+/// Record parse times per language from now on (`rebuild --timings`)
+pub fn enable_timings() {
+    TIMINGS_ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn timings_enabled() -> bool {
+    TIMINGS_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Add `elapsed`, spent parsing `path`, to `language`
+pub fn record_parse(language: &'static str, path: &str, elapsed: Duration) {
+    let mut timings = TIMINGS.lock().unwrap_or_else(|e| e.into_inner());
+    let timing = timings.entry(language).or_default();
+    timing.files += 1;
+    timing.total += elapsed;
+    if timing.slowest.as_ref().is_none_or(|(_, slowest)| elapsed > *slowest) {
+        timing.slowest = Some((path.to_string(), elapsed));
+    }
+}
+
+/// Recorded timings, the most time first
+pub fn parse_timings() -> Vec<(&'static str, LanguageTiming)> {
+    let timings = TIMINGS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut timings: Vec<_> = timings.iter().map(|(language, timing)| (*language, timing.clone())).collect();
+    timings.sort_by(|a, b| b.1.total.cmp(&a.1.total).then(a.0.cmp(b.0)));
+    timings
+}
+
+/// Print the recorded timings as a table
+pub fn print_timings() {
+    let timings = parse_timings();
+    if timings.is_empty() {
+        eprintln!("{}", "No files were parsed".dimmed());
+        return;
+    }
+    eprintln!("\n{}", "Parse time by language (summed over threads):".bold());
+    for (language, timing) in &timings {
+        let per_file = timing.total / timing.files.max(1) as u32;
+        let slowest = timing
+            .slowest
+            .as_ref()
+            .map(|(path, elapsed)| format!("slowest {} ({:.1?})", path, elapsed))
+            .unwrap_or_default();
+        eprintln!(
+            "  {:<12} {:>7} files {:>10.1?} {:>9.1?}/file  {}",
+            language,
+            timing.files,
+            timing.total,
+            per_file,
+            slowest.dimmed()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_parse_keeps_slowest_file() {
+        record_parse("test-lang", "a.tl", Duration::from_millis(5));
+        record_parse("test-lang", "b.tl", Duration::from_millis(40));
+        record_parse("test-lang", "c.tl", Duration::from_millis(10));
+        let (_, timing) = parse_timings().into_iter().find(|(language, _)| *language == "test-lang").unwrap();
+        assert_eq!((timing.files, timing.total), (3, Duration::from_millis(55)));
+        assert_eq!(timing.slowest, Some(("b.tl".to_string(), Duration::from_millis(40))));
+    }
+}
@@ -0,0 +1,367 @@
+//! Links proto declarations to the code protoc plugins generate from them
+//!
+//! Each plugin maps a message, enum or service to the type and function names it generates in
+//! its languages: `PaymentRequest` stays `PaymentRequest` for Java, Kotlin and Go, becomes
+//! `Payments_V1_PaymentRequest` for Swift, and a `PaymentService` gets `PaymentServiceGrpc`,
+//! `PaymentServiceCoroutineStub`, `Payments_V1_PaymentServiceAsyncClient` or
+//! `PaymentServiceClient` stubs. Usages are references to those names from files of the
+//! plugin's languages; JVM and Go files must also import the generated package (`java_package`
+//! or the proto package, `go_package`), so hand-written types sharing a message name are left out.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::db::RefResult;
+
+/// What a proto declaration declares
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProtoKind {
+    Message,
+    Enum,
+    Service,
+}
+
+impl ProtoKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProtoKind::Message => "message",
+            ProtoKind::Enum => "enum",
+            ProtoKind::Service => "service",
+        }
+    }
+}
+
+/// Names a protoc plugin generates, as templates: `{name}` is the declaration's own name,
+/// `{lower}` the same in lowerCamelCase, `{go}` the nesting path joined by `_` and `{swift}`
+/// the Swift name with its package prefix
+pub struct ProtoPlugin {
+    pub name: &'static str,
+    pub languages: &'static [&'static str],
+    messages: &'static [&'static str],
+    enums: &'static [&'static str],
+    services: &'static [&'static str],
+}
+
+const JVM: &[&str] = &["java", "kotlin"];
+
+/// Plugins whose output is looked up
+pub const PLUGINS: &[ProtoPlugin] = &[
+    ProtoPlugin { name: "protoc-java", languages: JVM, messages: &["{name}", "{name}OrBuilder"], enums: &["{name}"], services: &[] },
+    ProtoPlugin { name: "grpc-java", languages: JVM, messages: &[], enums: &[], services: &["{name}Grpc"] },
+    ProtoPlugin { name: "protoc-kotlin", languages: &["kotlin"], messages: &["{name}Kt", "{lower}"], enums: &[], services: &[] },
+    ProtoPlugin {
+        name: "grpc-kotlin",
+        languages: &["kotlin"],
+        messages: &[],
+        enums: &[],
+        services: &["{name}GrpcKt", "{name}CoroutineStub", "{name}CoroutineImplBase"],
+    },
+    ProtoPlugin { name: "wire", languages: JVM, messages: &["{name}"], enums: &["{name}"], services: &["{name}Client", "Grpc{name}Client"] },
+    ProtoPlugin { name: "swift-protobuf", languages: &["swift"], messages: &["{swift}"], enums: &["{swift}"], services: &[] },
+    ProtoPlugin {
+        name: "grpc-swift",
+        languages: &["swift"],
+        messages: &[],
+        enums: &[],
+        services: &[
+            "{swift}Client", "{swift}AsyncClient", "{swift}ClientProtocol", "{swift}AsyncClientProtocol",
+            "{swift}NIOClient", "{swift}Provider", "{swift}AsyncProvider",
+        ],
+    },
+    ProtoPlugin { name: "protoc-gen-go", languages: &["go"], messages: &["{go}"], enums: &["{go}"], services: &[] },
+    ProtoPlugin {
+        name: "grpc-go",
+        languages: &["go"],
+        messages: &[],
+        enums: &[],
+        services: &["{name}Client", "{name}Server", "New{name}Client", "Register{name}Server", "Unimplemented{name}Server"],
+    },
+];
+
+/// A message, enum or service declared in a proto file, with the file's package and options
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtoDeclaration {
+    /// Nesting path (`Outer.Inner`)
+    pub name: String,
+    pub kind: ProtoKind,
+    pub path: String,
+    pub line: i64,
+    pub package: Option<String>,
+    /// File options (`java_package`, `go_package`, `swift_prefix`, ...)
+    #[serde(skip)]
+    pub options: HashMap<String, String>,
+}
+
+/// A name generated for a declaration in one language
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GeneratedName {
+    pub name: String,
+    pub language: &'static str,
+    /// Plugins generating it
+    pub plugins: Vec<&'static str>,
+}
+
+impl ProtoDeclaration {
+    /// Names the plugins generate for this declaration, per language in plugin order
+    pub fn generated_names(&self) -> Vec<GeneratedName> {
+        let mut names: Vec<GeneratedName> = Vec::new();
+        for plugin in PLUGINS {
+            let templates = match self.kind {
+                ProtoKind::Message => plugin.messages,
+                ProtoKind::Enum => plugin.enums,
+                ProtoKind::Service => plugin.services,
+            };
+            for template in templates {
+                let name = self.expand(template);
+                for &language in plugin.languages {
+                    match names.iter_mut().find(|n| n.name == name && n.language == language) {
+                        Some(existing) => existing.plugins.push(plugin.name),
+                        None => names.push(GeneratedName { name: name.clone(), language, plugins: vec![plugin.name] }),
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    /// Package of the generated Java and Kotlin classes: `java_package`, else the proto package
+    pub fn java_package(&self) -> Option<&str> {
+        self.options.get("java_package").map(String::as_str).or(self.package.as_deref())
+    }
+
+    /// Import path of the generated Go package (`go_package` without its `;name` suffix)
+    pub fn go_import_path(&self) -> Option<&str> {
+        self.options.get("go_package").and_then(|value| value.split(';').next())
+    }
+
+    fn expand(&self, template: &str) -> String {
+        let simple = self.name.rsplit('.').next().unwrap_or(&self.name);
+        let mut lower = simple.to_string();
+        if let Some(first) = lower.get(..1) {
+            lower.replace_range(..1, &first.to_lowercase());
+        }
+        // Swift nests generated types like the proto does, so only top-level ones carry the prefix
+        let swift = if self.name.contains('.') { simple.to_string() } else { format!("{}{}", self.swift_prefix(), simple) };
+        template
+            .replace("{name}", simple)
+            .replace("{lower}", &lower)
+            .replace("{go}", &self.name.replace('.', "_"))
+            .replace("{swift}", &swift)
+    }
+
+    /// `swift_prefix`, else the package in UpperCamelCase per component, each followed by `_`
+    fn swift_prefix(&self) -> String {
+        if let Some(prefix) = self.options.get("swift_prefix") {
+            return prefix.clone();
+        }
+        let Some(package) = &self.package else {
+            return String::new();
+        };
+        package
+            .split('.')
+            .map(|component| {
+                let camel: String = component
+                    .split('_')
+                    .map(|word| {
+                        let mut chars = word.chars();
+                        chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+                    })
+                    .collect();
+                format!("{}_", camel)
+            })
+            .collect()
+    }
+}
+
+/// Usages of a declaration's generated names in one language
+#[derive(Debug, Serialize)]
+pub struct PlatformUsage {
+    pub language: &'static str,
+    pub names: Vec<GeneratedName>,
+    /// Declarations of the generated names, when generated sources are indexed
+    pub generated: Vec<RefResult>,
+    pub references: Vec<RefResult>,
+}
+
+/// Messages, enums and services declared in proto files as `name`, or nested under that
+/// last name (`Inner` finds `Outer.Inner`)
+pub fn find_declarations(conn: &Connection, name: &str) -> Result<Vec<ProtoDeclaration>> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT s.name, s.kind, f.path, s.line, s.file_id
+        FROM symbols s
+        JOIN files f ON s.file_id = f.id
+        WHERE f.language = 'proto' AND s.kind IN ('class', 'enum', 'interface')
+          AND (s.name = ?1 OR s.name LIKE '%.' || ?1)
+        ORDER BY f.path, s.line
+        "#,
+    )?;
+    let rows: Vec<(String, String, String, i64, i64)> = stmt
+        .query_map(params![name], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))?
+        .collect::<Result<_, _>>()?;
+
+    // Options are indexed as `name:value` properties and the package as a package symbol
+    let mut file_stmt = conn.prepare("SELECT kind, name FROM symbols WHERE file_id = ?1 AND kind IN ('package', 'property')")?;
+    let mut declarations = Vec::new();
+    for (name, kind, path, line, file_id) in rows {
+        let mut package = None;
+        let mut options = HashMap::new();
+        for entry in file_stmt.query_map(params![file_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+            let (kind, value) = entry?;
+            if kind == "package" {
+                package = Some(value);
+            } else if let Some((option, value)) = value.split_once(':') {
+                options.insert(option.to_string(), value.to_string());
+            }
+        }
+        let kind = match kind.as_str() {
+            "interface" => ProtoKind::Service,
+            "enum" => ProtoKind::Enum,
+            _ => ProtoKind::Message,
+        };
+        declarations.push(ProtoDeclaration { name, kind, path, line, package, options });
+    }
+    Ok(declarations)
+}
+
+/// Declarations of and references to the names generated for `declaration`, per language
+pub fn find_usages(conn: &Connection, declaration: &ProtoDeclaration) -> Result<Vec<PlatformUsage>> {
+    let generated = declaration.generated_names();
+    let mut languages: Vec<&'static str> = Vec::new();
+    for name in &generated {
+        if !languages.contains(&name.language) {
+            languages.push(name.language);
+        }
+    }
+    let mut usages = Vec::new();
+    for language in languages {
+        let names: Vec<GeneratedName> = generated.iter().filter(|n| n.language == language).cloned().collect();
+        // Files that can see the generated package, and files the plugins generate
+        let (visible, generated_file, package) = match language {
+            "java" | "kotlin" => match declaration.java_package() {
+                Some(package) => (
+                    " AND (EXISTS (SELECT 1 FROM imports i WHERE i.file_id = f.id AND (i.path = ?3 OR i.path LIKE ?3 || '.%'))
+                           OR EXISTS (SELECT 1 FROM symbols p WHERE p.file_id = f.id AND p.kind = 'package' AND p.name = ?3))",
+                    " AND EXISTS (SELECT 1 FROM symbols p WHERE p.file_id = f.id AND p.kind = 'package' AND p.name = ?3)",
+                    Some(package),
+                ),
+                None => ("", "", None),
+            },
+            "go" => match declaration.go_import_path() {
+                Some(path) => (
+                    " AND EXISTS (SELECT 1 FROM symbols i JOIN inheritance ih ON ih.child_id = i.id
+                                  WHERE i.file_id = f.id AND i.kind = 'import' AND ih.kind = 'from' AND ih.parent_name = ?3)",
+                    " AND f.path LIKE '%.pb.go'",
+                    Some(path),
+                ),
+                None => ("", " AND f.path LIKE '%.pb.go'", None),
+            },
+            _ => ("", " AND (f.path LIKE '%.pb.swift' OR f.path LIKE '%.grpc.swift')", None),
+        };
+        let mut refs_stmt = conn.prepare(&format!(
+            "SELECT r.name, r.line, r.context, f.path, r.ref_kind, r.count, r.sample_lines FROM refs r JOIN files f ON r.file_id = f.id
+             WHERE r.name = ?1 AND f.language = ?2{} ORDER BY f.path, r.line",
+            visible
+        ))?;
+        let mut symbols_stmt = conn.prepare(&format!(
+            "SELECT s.name, s.line, s.signature, f.path, NULL, NULL, NULL FROM symbols s JOIN files f ON s.file_id = f.id
+             WHERE s.name = ?1 AND f.language = ?2 AND s.kind NOT IN ('import', 'package'){} ORDER BY f.path, s.line",
+            generated_file
+        ))?;
+        let mut references = Vec::new();
+        let mut declared = Vec::new();
+        for name in &names {
+            let mut bound = vec![name.name.as_str(), language];
+            bound.extend(package);
+            for r in refs_stmt.query_map(rusqlite::params_from_iter(&bound), RefResult::from_row)? {
+                references.push(r?);
+            }
+            if !generated_file.contains("?3") {
+                bound.truncate(2);
+            }
+            for s in symbols_stmt.query_map(rusqlite::params_from_iter(&bound), RefResult::from_row)? {
+                declared.push(s?);
+            }
+        }
+        references.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+        usages.push(PlatformUsage { language, names, generated: declared, references });
+    }
+    Ok(usages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{self, SymbolKind};
+
+    fn declaration(name: &str, kind: ProtoKind, options: &[(&str, &str)]) -> ProtoDeclaration {
+        ProtoDeclaration {
+            name: name.to_string(),
+            kind,
+            path: "proto/payments.proto".to_string(),
+            line: 1,
+            package: Some("payments.v1".to_string()),
+            options: options.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    fn names(declaration: &ProtoDeclaration, language: &str) -> Vec<String> {
+        declaration.generated_names().into_iter().filter(|n| n.language == language).map(|n| n.name).collect()
+    }
+
+    #[test]
+    fn test_generated_names() {
+        let message = declaration("PaymentRequest", ProtoKind::Message, &[]);
+        assert_eq!(names(&message, "java"), ["PaymentRequest", "PaymentRequestOrBuilder"]);
+        assert_eq!(names(&message, "kotlin"), ["PaymentRequest", "PaymentRequestOrBuilder", "PaymentRequestKt", "paymentRequest"]);
+        assert_eq!(names(&message, "swift"), ["Payments_V1_PaymentRequest"]);
+        assert_eq!(names(&message, "go"), ["PaymentRequest"]);
+        let shared = message.generated_names().into_iter().find(|n| n.language == "java").unwrap();
+        assert_eq!(shared.plugins, ["protoc-java", "wire"]);
+
+        let nested = declaration("PaymentRequest.Item", ProtoKind::Message, &[("swift_prefix", "PB")]);
+        assert_eq!(names(&nested, "go"), ["PaymentRequest_Item"]);
+        assert_eq!(names(&nested, "swift"), ["Item"]);
+        assert_eq!(names(&declaration("Status", ProtoKind::Enum, &[("swift_prefix", "PB")]), "swift"), ["PBStatus"]);
+
+        let service = declaration("PaymentService", ProtoKind::Service, &[("go_package", "example.com/pay/v1;paypb")]);
+        assert!(names(&service, "kotlin").contains(&"PaymentServiceCoroutineStub".to_string()));
+        assert!(names(&service, "swift").contains(&"Payments_V1_PaymentServiceAsyncClient".to_string()));
+        assert!(names(&service, "go").contains(&"NewPaymentServiceClient".to_string()));
+        assert_eq!(service.go_import_path(), Some("example.com/pay/v1"));
+        assert_eq!(service.java_package(), Some("payments.v1"));
+    }
+
+    #[test]
+    fn test_usages_need_the_generated_package() {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        let proto = db::upsert_file(&conn, "proto/payments.proto", 1, 1).unwrap();
+        db::insert_symbol(&conn, proto, "payments.v1", SymbolKind::Package, 2, None).unwrap();
+        db::insert_symbol(&conn, proto, "java_package:com.example.payments", SymbolKind::Property, 3, None).unwrap();
+        db::insert_symbol(&conn, proto, "PaymentRequest", SymbolKind::Class, 5, None).unwrap();
+
+        let client = db::upsert_file(&conn, "app/src/Checkout.kt", 1, 1).unwrap();
+        conn.execute("INSERT INTO imports (file_id, name, path, line) VALUES (?1, 'PaymentRequest', 'com.example.payments.PaymentRequest', 2)", params![client]).unwrap();
+        let unrelated = db::upsert_file(&conn, "legacy/src/Old.kt", 1, 1).unwrap();
+        let ios = db::upsert_file(&conn, "ios/Checkout.swift", 1, 1).unwrap();
+        for (file_id, name) in [(client, "PaymentRequest"), (unrelated, "PaymentRequest"), (ios, "Payments_V1_PaymentRequest")] {
+            conn.execute("INSERT INTO refs (file_id, name, line) VALUES (?1, ?2, 7)", params![file_id, name]).unwrap();
+        }
+
+        let declarations = find_declarations(&conn, "PaymentRequest").unwrap();
+        assert_eq!(declarations.len(), 1);
+        assert_eq!(declarations[0].java_package(), Some("com.example.payments"));
+        let usages = find_usages(&conn, &declarations[0]).unwrap();
+        let paths = |language: &str| -> Vec<String> {
+            usages.iter().find(|u| u.language == language).unwrap().references.iter().map(|r| r.path.clone()).collect()
+        };
+        assert_eq!(paths("kotlin"), ["app/src/Checkout.kt"]);
+        assert_eq!(paths("swift"), ["ios/Checkout.swift"]);
+        assert!(paths("go").is_empty());
+    }
+}